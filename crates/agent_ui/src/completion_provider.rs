@@ -1958,12 +1958,8 @@ pub(crate) fn search_symbols(
     workspace: &Entity<Workspace>,
     cx: &mut App,
 ) -> Task<Vec<SymbolMatch>> {
-    let symbols_task = workspace.update(cx, |workspace, cx| {
-        workspace
-            .project()
-            .update(cx, |project, cx| project.symbols(&query, cx))
-    });
     let project = workspace.read(cx).project().clone();
+    let symbols_task = symbol_index::search_symbols(&project, &query, cx);
     cx.spawn(async move |cx| {
         let Some(symbols) = symbols_task.await.log_err() else {
             return Vec::new();
@@ -0,0 +1,129 @@
+use editor::{Editor, actions::SelectAll};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render, SharedString,
+    Window, div,
+};
+use language::Buffer;
+use project::bookmark_store::BookmarkStore;
+use theme::ActiveTheme as _;
+use ui::prelude::*;
+use workspace::{ModalView, Workspace};
+
+use crate::EditLabel;
+
+/// A modal for setting the label of the bookmark on the current line, creating the bookmark if
+/// the line has none. Confirming an empty label clears it.
+pub struct BookmarkLabelEditor {
+    label_editor: Entity<Editor>,
+    bookmark_store: Entity<BookmarkStore>,
+    buffer: Entity<Buffer>,
+    row: u32,
+    location: SharedString,
+}
+
+impl BookmarkLabelEditor {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        _: &EditLabel,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let Some((buffer, row)) =
+            editor.update(cx, |editor, cx| editor.bookmark_position_at_cursor(cx))
+        else {
+            return;
+        };
+        let bookmark_store = workspace.project().read(cx).bookmark_store();
+        workspace.toggle_modal(window, cx, |window, cx| {
+            Self::new(bookmark_store, buffer, row, window, cx)
+        });
+    }
+
+    fn new(
+        bookmark_store: Entity<BookmarkStore>,
+        buffer: Entity<Buffer>,
+        row: u32,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let snapshot = buffer.read(cx).snapshot();
+        let current_label = bookmark_store
+            .read(cx)
+            .bookmark_at_row(&buffer, row, &snapshot, cx)
+            .and_then(|bookmark| bookmark.label.clone());
+        let label_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Bookmark label", window, cx);
+            if let Some(label) = current_label {
+                editor.set_text(label.to_string(), window, cx);
+                editor.select_all(&SelectAll, window, cx);
+            }
+            editor
+        });
+        let file_name = buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string())
+            .unwrap_or_else(|| "untitled".to_string());
+
+        Self {
+            label_editor,
+            bookmark_store,
+            buffer,
+            row,
+            location: format!("Bookmark at {file_name}:{}", row + 1).into(),
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        let label = self.label_editor.read(cx).text(cx);
+        let buffer = self.buffer.clone();
+        let row = self.row;
+        self.bookmark_store.update(cx, |bookmark_store, cx| {
+            bookmark_store.set_label(buffer, row, Some(label.into()), cx);
+        });
+        cx.emit(DismissEvent);
+    }
+}
+
+impl ModalView for BookmarkLabelEditor {}
+
+impl EventEmitter<DismissEvent> for BookmarkLabelEditor {}
+
+impl Focusable for BookmarkLabelEditor {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.label_editor.focus_handle(cx)
+    }
+}
+
+impl Render for BookmarkLabelEditor {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(24.))
+            .elevation_2(cx)
+            .key_context("BookmarkLabelEditor")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.label_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .child(Label::new(self.location.clone()).color(Color::Muted)),
+            )
+    }
+}
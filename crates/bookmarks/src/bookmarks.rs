@@ -0,0 +1,421 @@
+mod bookmark_label;
+
+use std::sync::Arc;
+
+use editor::{Editor, SelectionEffects, scroll::Autoscroll};
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render, SharedString,
+    Task, WeakEntity, Window, actions,
+};
+use language::Buffer;
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use text::Point;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace};
+
+pub use bookmark_label::BookmarkLabelEditor;
+
+actions!(
+    bookmarks,
+    [
+        /// Opens a picker listing every bookmark in the project.
+        Toggle,
+        /// Sets or edits the label of the bookmark on the current line.
+        EditLabel,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(register).detach();
+}
+
+fn register(workspace: &mut Workspace, _window: Option<&mut Window>, _: &mut Context<Workspace>) {
+    workspace.register_action(BookmarksPicker::toggle);
+    workspace.register_action(BookmarkLabelEditor::toggle);
+}
+
+pub struct BookmarksPicker {
+    picker: Entity<Picker<BookmarksPickerDelegate>>,
+}
+
+impl BookmarksPicker {
+    fn toggle(
+        workspace: &mut Workspace,
+        _: &Toggle,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let entries = bookmark_entries(workspace.project(), cx);
+        let weak_workspace = workspace.weak_handle();
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            let delegate =
+                BookmarksPickerDelegate::new(cx.entity().downgrade(), weak_workspace, entries);
+            let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+            Self { picker }
+        });
+    }
+}
+
+impl ModalView for BookmarksPicker {}
+
+impl EventEmitter<DismissEvent> for BookmarksPicker {}
+
+impl Focusable for BookmarksPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for BookmarksPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("BookmarksPicker")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+struct BookmarkEntry {
+    buffer: Entity<Buffer>,
+    position: text::Anchor,
+    /// The bookmark's label, or the text of its line when it has none.
+    title: SharedString,
+    /// The path and line number of the bookmark.
+    location: SharedString,
+}
+
+fn bookmark_entries(project: &Entity<Project>, cx: &App) -> Vec<BookmarkEntry> {
+    let bookmark_store = project.read(cx).bookmark_store();
+    bookmark_store
+        .read(cx)
+        .all_bookmarks()
+        .map(|(abs_path, buffer, bookmark)| {
+            let buffer_ref = buffer.read(cx);
+            let snapshot = buffer_ref.snapshot();
+            let row = bookmark.position.summary::<Point>(&snapshot).row;
+            let title = bookmark.label.clone().unwrap_or_else(|| {
+                snapshot
+                    .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+                    .collect::<String>()
+                    .trim()
+                    .to_string()
+                    .into()
+            });
+            let path = buffer_ref
+                .file()
+                .map(|file| file.full_path(cx))
+                .unwrap_or_else(|| abs_path.to_path_buf());
+            BookmarkEntry {
+                buffer: buffer.clone(),
+                position: bookmark.position,
+                title,
+                location: format!("{}:{}", path.display(), row + 1).into(),
+            }
+        })
+        .collect()
+}
+
+pub struct BookmarksPickerDelegate {
+    bookmarks_picker: WeakEntity<BookmarksPicker>,
+    workspace: WeakEntity<Workspace>,
+    entries: Vec<BookmarkEntry>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl BookmarksPickerDelegate {
+    fn new(
+        bookmarks_picker: WeakEntity<BookmarksPicker>,
+        workspace: WeakEntity<Workspace>,
+        entries: Vec<BookmarkEntry>,
+    ) -> Self {
+        let candidates = entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| {
+                StringMatchCandidate::new(id, &format!("{} {}", entry.title, entry.location))
+            })
+            .collect();
+        Self {
+            bookmarks_picker,
+            workspace,
+            entries,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for BookmarksPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _: &mut App) -> Arc<str> {
+        "Search bookmarks...".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some(if self.entries.is_empty() {
+            "No bookmarks in this project".into()
+        } else {
+            "No matching bookmarks".into()
+        })
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.entries.get(mat.candidate_id))
+        else {
+            return;
+        };
+        let buffer = entry.buffer.clone();
+        let position = entry.position;
+
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let pane = workspace.active_pane().clone();
+                let editor = workspace.open_project_item::<Editor>(
+                    pane,
+                    buffer.clone(),
+                    true,
+                    true,
+                    false,
+                    true,
+                    window,
+                    cx,
+                );
+                editor.update(cx, |editor, cx| {
+                    let point = position.summary::<Point>(&buffer.read(cx).snapshot());
+                    editor.change_selections(
+                        SelectionEffects::scroll(Autoscroll::center()),
+                        window,
+                        cx,
+                        |s| s.select_ranges([point..point]),
+                    );
+                });
+            })
+            .log_err();
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.bookmarks_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let entry = self.entries.get(mat.candidate_id)?;
+        let location_start = entry.title.len() + 1;
+        let title_positions = mat
+            .positions
+            .iter()
+            .copied()
+            .filter(|position| *position < entry.title.len())
+            .collect();
+        let location_positions = mat
+            .positions
+            .iter()
+            .filter_map(|position| position.checked_sub(location_start))
+            .collect();
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(
+                    Icon::new(IconName::Bookmark)
+                        .size(IconSize::Small)
+                        .color(Color::Accent),
+                )
+                .child(
+                    h_flex()
+                        .gap_x_2()
+                        .child(HighlightedLabel::new(entry.title.clone(), title_positions))
+                        .child(
+                            HighlightedLabel::new(entry.location.clone(), location_positions)
+                                .color(Color::Muted)
+                                .size(LabelSize::Small),
+                        ),
+                ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use editor::actions::{MoveToEnd, ToggleBookmark};
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use std::path::Path;
+    use util::path;
+    use workspace::{AppState, MultiWorkspace};
+
+    #[gpui::test]
+    async fn test_labeling_and_picking_bookmarks(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            crate::init(cx);
+            editor::init(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({ "a.rs": "fn main() {\n    start();\n}\n\nfn start() {}\n" }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+        let (multi_workspace, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let workspace = multi_workspace.read_with(cx, |mw, _| mw.workspace().clone());
+        let editor = workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_abs_path(
+                    Path::new(path!("/dir/a.rs")).to_path_buf(),
+                    Default::default(),
+                    window,
+                    cx,
+                )
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+
+        cx.dispatch_action(ToggleBookmark);
+        cx.dispatch_action(MoveToEnd);
+        cx.dispatch_action(EditLabel);
+        let has_label_editor = |cx: &mut VisualTestContext| {
+            workspace.update(cx, |workspace, cx| {
+                workspace.active_modal::<BookmarkLabelEditor>(cx).is_some()
+            })
+        };
+        assert!(has_label_editor(cx));
+        cx.simulate_input("the end");
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+        assert!(!has_label_editor(cx));
+
+        let picker = open_picker(&workspace, cx);
+        picker.update(cx, |picker, _| {
+            let titles = picker
+                .delegate
+                .matches
+                .iter()
+                .map(|mat| picker.delegate.entries[mat.candidate_id].title.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(titles, vec!["fn main() {", "the end"]);
+            assert_eq!(
+                picker.delegate.entries[0].location.as_ref(),
+                format!("dir{}a.rs:1", std::path::MAIN_SEPARATOR)
+            );
+        });
+
+        cx.simulate_input("main");
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| assert_eq!(picker.delegate.matches.len(), 1));
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+        let cursor_row = editor.update(cx, |editor, cx| {
+            editor
+                .selections
+                .newest::<Point>(&editor.display_snapshot(cx))
+                .head()
+                .row
+        });
+        assert_eq!(cursor_row, 0);
+    }
+
+    fn open_picker(
+        workspace: &Entity<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> Entity<Picker<BookmarksPickerDelegate>> {
+        cx.dispatch_action(Toggle);
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<BookmarksPicker>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        })
+    }
+}
@@ -122,6 +122,19 @@ struct Args {
     /// When directories are provided, recurses into them and shows all changed files in a single multi-diff view.
     #[arg(long, action = clap::ArgAction::Append, num_args = 2, value_names = ["OLD_PATH", "NEW_PATH"])]
     diff: Vec<String>,
+    /// Run all code cells of a Jupyter notebook and save their outputs to it,
+    /// without opening a window. Exits with a non-zero status if a cell fails.
+    #[arg(long, value_name = "NOTEBOOK")]
+    run_notebook: Option<PathBuf>,
+    /// The Jupyter kernel to run the notebook in. Defaults to the notebook's kernel.
+    #[arg(long, value_name = "NAME", requires = "run_notebook")]
+    notebook_kernel: Option<String>,
+    /// How long each notebook cell may run, in seconds. Defaults to 600.
+    #[arg(long, value_name = "SECONDS", requires = "run_notebook")]
+    notebook_timeout: Option<u64>,
+    /// Also export the executed notebook to Markdown at this path.
+    #[arg(long, value_name = "PATH", requires = "run_notebook")]
+    notebook_markdown: Option<PathBuf>,
     /// Uninstall Zed from user system
     #[cfg(all(
         any(target_os = "linux", target_os = "macos"),
@@ -503,6 +516,25 @@ fn main() -> Result<()> {
         anyhow::bail!(msg.join("\n"));
     }
 
+    if let Some(notebook) = &args.run_notebook {
+        let mut command = std::process::Command::new(app.path());
+        command.arg("--run-notebook").arg(notebook);
+        if let Some(kernel) = &args.notebook_kernel {
+            command.arg("--notebook-kernel").arg(kernel);
+        }
+        if let Some(timeout) = args.notebook_timeout {
+            command.arg("--notebook-timeout").arg(timeout.to_string());
+        }
+        if let Some(markdown) = &args.notebook_markdown {
+            command.arg("--notebook-markdown").arg(markdown);
+        }
+        if let Some(dir) = &user_data_dir {
+            command.arg("--user-data-dir").arg(dir);
+        }
+        let status = command.status().context("Failed to run Zed")?;
+        std::process::exit(status.code().unwrap_or(1));
+    }
+
     #[cfg(all(
         any(target_os = "linux", target_os = "macos"),
         not(feature = "no-bundled-uninstall")
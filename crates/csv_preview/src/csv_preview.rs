@@ -20,6 +20,9 @@ mod settings;
 mod table_data_engine;
 mod types;
 
+pub use renderer::render_table_cell;
+pub use settings::{FontType, VerticalAlignment};
+
 actions!(csv, [OpenPreview, OpenPreviewToTheSide]);
 
 pub struct TabularDataPreviewFeatureFlag;
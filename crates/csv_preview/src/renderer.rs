@@ -3,3 +3,5 @@ mod render_table;
 mod row_identifiers;
 mod table_cell;
 mod table_header;
+
+pub use table_cell::render_table_cell;
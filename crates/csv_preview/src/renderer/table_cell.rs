@@ -38,15 +38,34 @@ fn create_table_cell(
     font_type: FontType,
     cx: &Context<'_, CsvPreviewView>,
 ) -> gpui::Stateful<Div> {
-    div()
-        .id(ElementId::NamedInteger(
+    render_table_cell(
+        ElementId::NamedInteger(
             format!(
                 "csv-display-cell-{}-{}",
                 *display_cell_id.row, *display_cell_id.col
             )
             .into(),
             0,
-        ))
+        ),
+        cell_content,
+        vertical_alignment,
+        font_type,
+        cx,
+    )
+}
+
+/// Renders a table cell styled like the cells of the CSV preview.
+///
+/// Exposed so that other tabular views can present data consistently.
+pub fn render_table_cell(
+    id: impl Into<ElementId>,
+    cell_content: SharedString,
+    vertical_alignment: VerticalAlignment,
+    font_type: FontType,
+    cx: &App,
+) -> gpui::Stateful<Div> {
+    div()
+        .id(id)
         .cursor_pointer()
         .flex()
         .h_full()
@@ -14,6 +14,7 @@ use gpui::{
     Subscription, Task, TextStyle, UniformList, UniformListScrollHandle, WeakEntity, actions,
     anchored, deferred, uniform_list,
 };
+use hex_editor::{HexBuffer, HexEditor, SaveBytes};
 use notifications::status_toast::{StatusToast, ToastIcon};
 use project::debugger::{MemoryCell, dap_command::DataBreakpointContext, session::Session};
 use settings::Settings;
@@ -26,7 +27,15 @@ use workspace::Workspace;
 
 use crate::{ToggleDataBreakpoint, session::running::stack_frame_list::StackFrameList};
 
-actions!(debugger, [GoToSelectedAddress]);
+actions!(
+    debugger,
+    [
+        /// Goes to the address stored in the selected memory.
+        GoToSelectedAddress,
+        /// Opens the selected memory in a hex editor.
+        OpenMemoryInHexEditor
+    ]
+);
 
 pub(crate) struct MemoryView {
     workspace: WeakEntity<Workspace>,
@@ -575,6 +584,53 @@ impl MemoryView {
         cx.notify();
     }
 
+    fn open_in_hex_editor(
+        &mut self,
+        _: &OpenMemoryInHexEditor,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(SelectedMemoryRange::DragComplete(drag)) = self.view_state().selection.clone()
+        else {
+            return;
+        };
+        let range = drag.memory_range();
+        let Some(memory): Option<Vec<u8>> = self.session.update(cx, |this, cx| {
+            this.read_memory(range.clone(), cx)
+                .map(|cell| cell.0)
+                .collect()
+        }) else {
+            return;
+        };
+        let start_address = *range.start();
+        let supports_write_memory = self
+            .session
+            .read(cx)
+            .capabilities()
+            .supports_write_memory_request
+            .unwrap_or_default();
+        let save = supports_write_memory.then(|| {
+            let session = self.session.downgrade();
+            Box::new(move |bytes: Arc<[u8]>, cx: &mut App| {
+                Task::ready(
+                    session
+                        .update(cx, |session, cx| {
+                            session.write_memory(start_address, &bytes, cx)
+                        })
+                        .map_err(|_| anyhow::anyhow!("the debug session has ended")),
+                )
+            }) as SaveBytes
+        });
+        let title = format!("Memory {start_address:#x}..{:#x}", range.end());
+        let buffer = cx.new(|_| HexBuffer::from_bytes(title, start_address, memory, save));
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let editor = cx.new(|cx| HexEditor::new(buffer, window, cx));
+                workspace.add_item_to_active_pane(Box::new(editor), None, true, window, cx);
+            })
+            .ok();
+    }
+
     /// Jump to memory pointed to by selected memory range.
     fn go_to_address(
         &mut self,
@@ -634,6 +690,12 @@ impl MemoryView {
                 GoToSelectedAddress.boxed_clone(),
             );
 
+            menu = menu.action_disabled_when(
+                *memory_unreadable,
+                "Open in Hex Editor",
+                OpenMemoryInHexEditor.boxed_clone(),
+            );
+
             if supports_data_breakpoints {
                 menu = menu.action_disabled_when(
                     *memory_unreadable,
@@ -863,6 +925,7 @@ impl Render for MemoryView {
             .id("Memory-view")
             .on_action(cx.listener(Self::cancel))
             .on_action(cx.listener(Self::go_to_address))
+            .on_action(cx.listener(Self::open_in_hex_editor))
             .p_1()
             .on_action(cx.listener(Self::confirm))
             .on_action(cx.listener(Self::toggle_data_breakpoint))
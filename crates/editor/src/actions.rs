@@ -307,6 +307,43 @@ pub enum UuidVersion {
     V7,
 }
 
+/// The image format of a code snapshot.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeSnapshotFormat {
+    #[default]
+    Png,
+    Svg,
+}
+
+/// Saves an image of the selected lines, rendered with the current theme's syntax highlighting.
+#[derive(PartialEq, Clone, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = editor)]
+#[serde(deny_unknown_fields)]
+pub struct ExportCodeSnapshot {
+    #[serde(default)]
+    pub format: CodeSnapshotFormat,
+    #[serde(default = "default_true")]
+    pub line_numbers: bool,
+    /// Whether to draw a window title bar above the code.
+    #[serde(default = "default_true")]
+    pub window_chrome: bool,
+}
+
+/// Copies an image of the selected lines, rendered with the current theme's syntax highlighting.
+#[derive(PartialEq, Clone, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = editor)]
+#[serde(deny_unknown_fields)]
+pub struct CopyCodeSnapshot {
+    #[serde(default)]
+    pub format: CodeSnapshotFormat,
+    #[serde(default = "default_true")]
+    pub line_numbers: bool,
+    /// Whether to draw a window title bar above the code.
+    #[serde(default = "default_true")]
+    pub window_chrome: bool,
+}
+
 /// Splits selection into individual lines.
 #[derive(PartialEq, Clone, Deserialize, Default, JsonSchema, Action)]
 #[action(namespace = editor)]
@@ -562,10 +599,14 @@ actions!(
         GoToImplementation,
         /// Goes to implementation in a split pane.
         GoToImplementationSplit,
+        /// Goes to the next bookmark, wrapping around to the first one.
+        GoToNextBookmark,
         /// Goes to the next change in the file.
         GoToNextChange,
         /// Goes to the parent module of the current file.
         GoToParentModule,
+        /// Goes to the previous bookmark, wrapping around to the last one.
+        GoToPreviousBookmark,
         /// Goes to the previous change in the file.
         GoToPreviousChange,
         /// Goes to the next symbol.
@@ -805,6 +846,8 @@ actions!(
         Tab,
         /// Removes a tab character or outdents.
         Backtab,
+        /// Toggles a bookmark at the current line.
+        ToggleBookmark,
         /// Toggles a breakpoint at the current line.
         ToggleBreakpoint,
         /// Toggles the case of selected text.
@@ -0,0 +1,303 @@
+use std::{ops::Range, path::Path, sync::Arc};
+
+use collections::{BTreeMap, HashMap};
+use gpui::{App, AppContext as _, Context, Entity, Window};
+use itertools::Itertools as _;
+use language::Buffer;
+use multi_buffer::{Anchor, ToPoint as _};
+use project::bookmark_store::{Bookmark, BookmarkStore, BookmarkStoreEvent, SerializedBookmark};
+use text::{Bias, Point};
+use ui::{ButtonSize, ButtonStyle, IconButton, IconName, IconSize, Tooltip, prelude::*};
+use workspace::Workspace;
+
+use crate::{
+    Direction, DisplayPoint, Editor, SelectionEffects,
+    actions::{GoToNextBookmark, GoToPreviousBookmark, ToggleBookmark},
+    display_map::{DisplayRow, ToDisplayPoint as _},
+    persistence::EditorDb,
+    scroll::Autoscroll,
+};
+
+/// Restores each workspace's bookmarks from the database and keeps the database up to date as
+/// bookmarks change.
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(
+        |workspace: &mut Workspace, _: Option<&mut Window>, cx: &mut Context<Workspace>| {
+            let bookmark_store = workspace.project().read(cx).bookmark_store();
+            restore_bookmarks(workspace, &bookmark_store, cx);
+            cx.subscribe(
+                &bookmark_store,
+                |workspace, bookmark_store, event: &BookmarkStoreEvent, cx| {
+                    let BookmarkStoreEvent::BookmarksUpdated(path) = event;
+                    let Some(workspace_id) = workspace.database_id() else {
+                        return;
+                    };
+                    let bookmarks = bookmark_store
+                        .read(cx)
+                        .serialized_bookmarks(path, cx)
+                        .into_iter()
+                        .map(|bookmark| {
+                            (bookmark.row, bookmark.label.map(|label| label.to_string()))
+                        })
+                        .collect::<Vec<_>>();
+                    let path = path.clone();
+                    let db = EditorDb::global(cx);
+                    cx.background_spawn(async move {
+                        if bookmarks.is_empty() {
+                            db.delete_file_bookmarks(workspace_id, path).await
+                        } else {
+                            db.save_file_bookmarks(workspace_id, path, bookmarks).await
+                        }
+                    })
+                    .detach_and_log_err(cx);
+                },
+            )
+            .detach();
+        },
+    )
+    .detach();
+}
+
+fn restore_bookmarks(
+    workspace: &Workspace,
+    bookmark_store: &Entity<BookmarkStore>,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    let db = EditorDb::global(cx);
+    let bookmark_store = bookmark_store.downgrade();
+    cx.spawn(async move |_, cx| {
+        let rows = cx
+            .background_spawn(async move { db.get_bookmarks(workspace_id) })
+            .await?;
+        if rows.is_empty() {
+            return anyhow::Ok(());
+        }
+        let mut bookmarks = BTreeMap::<Arc<Path>, Vec<SerializedBookmark>>::default();
+        for (path, row, label) in rows {
+            bookmarks
+                .entry(path.into())
+                .or_default()
+                .push(SerializedBookmark {
+                    row,
+                    label: label.map(Into::into),
+                });
+        }
+        bookmark_store
+            .update(cx, |bookmark_store, cx| {
+                bookmark_store.with_serialized_bookmarks(bookmarks, cx)
+            })?
+            .await
+    })
+    .detach_and_log_err(cx);
+}
+
+impl Editor {
+    pub fn toggle_bookmark(
+        &mut self,
+        _: &ToggleBookmark,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(bookmark_store) = self.bookmark_store.clone() else {
+            return;
+        };
+        let display_snapshot = self.display_snapshot(cx);
+        let multi_buffer = self.buffer.read(cx);
+        let mut positions: Vec<(Entity<Buffer>, u32)> = Vec::new();
+        for selection in self.selections.all::<Point>(&display_snapshot) {
+            let Some((buffer, point, _)) = multi_buffer.point_to_buffer_point(selection.head(), cx)
+            else {
+                continue;
+            };
+            if !positions.contains(&(buffer.clone(), point.row)) {
+                positions.push((buffer, point.row));
+            }
+        }
+
+        bookmark_store.update(cx, |bookmark_store, cx| {
+            for (buffer, row) in positions {
+                bookmark_store.toggle_bookmark(buffer, row, cx);
+            }
+        });
+    }
+
+    pub fn go_to_next_bookmark(
+        &mut self,
+        _: &GoToNextBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_bookmark(Direction::Next, window, cx);
+    }
+
+    pub fn go_to_previous_bookmark(
+        &mut self,
+        _: &GoToPreviousBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_bookmark(Direction::Prev, window, cx);
+    }
+
+    /// Returns the buffer and buffer row of the newest cursor, where a bookmark for it would be
+    /// placed.
+    pub fn bookmark_position_at_cursor(&self, cx: &mut App) -> Option<(Entity<Buffer>, u32)> {
+        let head = self
+            .selections
+            .newest::<Point>(&self.display_snapshot(cx))
+            .head();
+        let (buffer, point, _) = self.buffer.read(cx).point_to_buffer_point(head, cx)?;
+        Some((buffer, point.row))
+    }
+
+    fn go_to_bookmark(
+        &mut self,
+        direction: Direction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let rows = self
+            .bookmarks_in_range(Point::zero()..snapshot.max_point(), cx)
+            .into_iter()
+            .map(|(anchor, _)| anchor.to_point(&snapshot).row)
+            .sorted()
+            .dedup()
+            .collect::<Vec<_>>();
+        let head_row = self
+            .selections
+            .newest::<Point>(&self.display_snapshot(cx))
+            .head()
+            .row;
+        let row = match direction {
+            Direction::Next => rows.iter().find(|row| **row > head_row).or(rows.first()),
+            Direction::Prev => rows
+                .iter()
+                .rev()
+                .find(|row| **row < head_row)
+                .or(rows.last()),
+        };
+        let Some(&row) = row else {
+            return;
+        };
+
+        let destination = Point::new(row, 0);
+        self.unfold_ranges(&[destination..destination], false, false, cx);
+        self.change_selections(
+            SelectionEffects::scroll(Autoscroll::center()),
+            window,
+            cx,
+            |s| s.select_ranges([destination..destination]),
+        );
+    }
+
+    /// Returns the bookmarks within the given multi-buffer range, anchored in the multi-buffer.
+    fn bookmarks_in_range(&self, range: Range<Point>, cx: &App) -> Vec<(Anchor, Bookmark)> {
+        let Some(bookmark_store) = self.bookmark_store.as_ref() else {
+            return Vec::new();
+        };
+        let bookmark_store = bookmark_store.read(cx);
+        let multi_buffer = self.buffer.read(cx);
+        let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+
+        let mut bookmarks = Vec::new();
+        for (buffer_snapshot, range, excerpt_id) in
+            multi_buffer_snapshot.range_to_buffer_ranges(range.start..=range.end)
+        {
+            let Some(buffer) = multi_buffer.buffer(buffer_snapshot.remote_id()) else {
+                continue;
+            };
+            let range =
+                buffer_snapshot.anchor_before(range.start)..buffer_snapshot.anchor_after(range.end);
+            bookmarks.extend(
+                bookmark_store
+                    .bookmarks(&buffer, Some(range), buffer_snapshot, cx)
+                    .map(|bookmark| {
+                        (
+                            Anchor::in_buffer(excerpt_id, bookmark.position),
+                            bookmark.clone(),
+                        )
+                    }),
+            );
+        }
+        bookmarks
+    }
+
+    /// Returns the display rows of the bookmarks that will be rendered in the gutter.
+    pub(crate) fn active_bookmarks(
+        &self,
+        range: Range<DisplayRow>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> HashMap<DisplayRow, (Anchor, Bookmark)> {
+        if self.bookmark_store.is_none() {
+            return HashMap::default();
+        }
+        let snapshot = self.snapshot(window, cx);
+        let range = snapshot.display_point_to_point(DisplayPoint::new(range.start, 0), Bias::Left)
+            ..snapshot.display_point_to_point(DisplayPoint::new(range.end, 0), Bias::Right);
+
+        self.bookmarks_in_range(range, cx)
+            .into_iter()
+            .map(|(anchor, bookmark)| {
+                let display_row = anchor
+                    .to_point(snapshot.buffer_snapshot())
+                    .to_display_point(&snapshot)
+                    .row();
+                (display_row, (anchor, bookmark))
+            })
+            .collect()
+    }
+
+    pub(crate) fn render_bookmark(
+        &self,
+        position: Anchor,
+        row: DisplayRow,
+        bookmark: &Bookmark,
+        cx: &mut Context<Self>,
+    ) -> IconButton {
+        let focus_handle = self.focus_handle.clone();
+        let label = bookmark.label.clone();
+
+        IconButton::new(("bookmark_indicator", row.0 as usize), IconName::Bookmark)
+            .icon_size(IconSize::XSmall)
+            .size(ButtonSize::None)
+            .icon_color(Color::Accent)
+            .style(ButtonStyle::Transparent)
+            .on_click(cx.listener(move |editor, _, window, cx| {
+                window.focus(&editor.focus_handle(cx), cx);
+                editor.toggle_bookmark_at_anchor(position, cx);
+            }))
+            .tooltip(move |_window, cx| match &label {
+                Some(label) => Tooltip::with_meta_in(
+                    label.clone(),
+                    Some(&ToggleBookmark),
+                    "Click to remove bookmark",
+                    &focus_handle,
+                    cx,
+                ),
+                None => {
+                    Tooltip::for_action_in("Remove bookmark", &ToggleBookmark, &focus_handle, cx)
+                }
+            })
+    }
+
+    fn toggle_bookmark_at_anchor(&mut self, position: Anchor, cx: &mut Context<Self>) {
+        let Some(bookmark_store) = self.bookmark_store.clone() else {
+            return;
+        };
+        let Some(buffer) = self.buffer.read(cx).buffer_for_anchor(position, cx) else {
+            return;
+        };
+        let row = position
+            .text_anchor
+            .summary::<Point>(&buffer.read(cx).snapshot())
+            .row;
+        bookmark_store.update(cx, |bookmark_store, cx| {
+            bookmark_store.toggle_bookmark(buffer, row, cx);
+        });
+    }
+}
@@ -0,0 +1,481 @@
+use std::fmt::Write as _;
+
+use anyhow::{Context as _, Result};
+use fs::Fs;
+use gpui::{
+    ClipboardItem, FontStyle, FontWeight, Hsla, Image, ImageFormat, Rgba, SharedString, Task,
+    font_name_with_fallbacks_shared,
+};
+use language::Point;
+use multi_buffer::MultiBufferRow;
+use theme::ActiveTheme as _;
+use ui::{App, Context, Window};
+
+use crate::{CodeSnapshotFormat, CopyCodeSnapshot, Editor, ExportCodeSnapshot};
+
+/// The scale at which PNG snapshots are rasterized, so that they stay sharp on high-density displays.
+const PNG_SCALE_FACTOR: f32 = 2.;
+const PADDING: f32 = 16.;
+const WINDOW_CHROME_HEIGHT: f32 = 32.;
+const CORNER_RADIUS: f32 = 8.;
+
+#[derive(Debug, Clone, PartialEq)]
+struct CodeSnapshotSpan {
+    text: String,
+    color: Hsla,
+    font_weight: Option<FontWeight>,
+    italic: bool,
+}
+
+/// The highlighted lines of an editor, ready to be rendered into an image.
+#[derive(Debug, Clone, PartialEq)]
+struct CodeSnapshot {
+    title: SharedString,
+    first_line_number: u32,
+    lines: Vec<Vec<CodeSnapshotSpan>>,
+}
+
+struct CodeSnapshotStyle {
+    font_family: SharedString,
+    font_size: f32,
+    line_height: f32,
+    char_width: f32,
+    background: Hsla,
+    border: Hsla,
+    line_number_color: Hsla,
+    title_bar_background: Hsla,
+    title_color: Hsla,
+    line_numbers: bool,
+    window_chrome: bool,
+}
+
+impl Editor {
+    pub fn export_code_snapshot(
+        &mut self,
+        action: &ExportCodeSnapshot,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (snapshot, style) =
+            self.code_snapshot(action.line_numbers, action.window_chrome, window, cx);
+        let format = action.format;
+        let image = render_code_snapshot(&snapshot, &style, format, cx);
+
+        let extension = match format {
+            CodeSnapshotFormat::Png => "png",
+            CodeSnapshotFormat::Svg => "svg",
+        };
+        let suggested_name = format!("{}.{extension}", snapshot.title);
+        let directory = self
+            .target_file_abs_path(cx)
+            .and_then(|path| Some(path.parent()?.to_path_buf()))
+            .unwrap_or_default();
+        let save_dialog = cx.prompt_for_new_path(&directory, Some(&suggested_name));
+        let fs = self
+            .project
+            .as_ref()
+            .map(|project| project.read(cx).fs().clone())
+            .unwrap_or_else(|| <dyn Fs>::global(cx));
+
+        let task: Task<Result<()>> = cx.background_spawn(async move {
+            let image = image?;
+            let Ok(path) = save_dialog.await else {
+                return Ok(());
+            };
+            let Some(path) = path? else {
+                return Ok(());
+            };
+            fs.write(&path, &image)
+                .await
+                .with_context(|| format!("failed to write code snapshot to {path:?}"))
+        });
+        self.detach_and_notify_err(task, window, cx);
+    }
+
+    pub fn copy_code_snapshot(
+        &mut self,
+        action: &CopyCodeSnapshot,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (snapshot, style) =
+            self.code_snapshot(action.line_numbers, action.window_chrome, window, cx);
+        let format = action.format;
+        match render_code_snapshot(&snapshot, &style, format, cx) {
+            Ok(bytes) => {
+                let format = match format {
+                    CodeSnapshotFormat::Png => ImageFormat::Png,
+                    CodeSnapshotFormat::Svg => ImageFormat::Svg,
+                };
+                cx.write_to_clipboard(ClipboardItem::new_image(&Image::from_bytes(format, bytes)));
+            }
+            Err(error) => self.detach_and_notify_err(Task::ready(Err::<(), _>(error)), window, cx),
+        }
+    }
+
+    /// Collects the highlighted lines covered by the newest selection,
+    /// or the whole buffer if the selection is empty.
+    fn code_snapshot(
+        &mut self,
+        line_numbers: bool,
+        window_chrome: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> (CodeSnapshot, CodeSnapshotStyle) {
+        let display_snapshot = self.display_snapshot(cx);
+        let buffer = display_snapshot.buffer_snapshot();
+        let selection = self.selections.newest::<Point>(&display_snapshot);
+        let (start_row, end_row) = if selection.is_empty() {
+            (0, buffer.max_point().row)
+        } else if selection.end.row > selection.start.row && selection.end.column == 0 {
+            (selection.start.row, selection.end.row - 1)
+        } else {
+            (selection.start.row, selection.end.row)
+        };
+        let range =
+            Point::new(start_row, 0)..Point::new(end_row, buffer.line_len(MultiBufferRow(end_row)));
+
+        let first_line_number = buffer
+            .point_to_buffer_point(range.start)
+            .map_or(start_row, |(_, point, _)| point.row)
+            + 1;
+        let tab_size = buffer.language_settings_at(range.start, cx).tab_size.get() as usize;
+
+        let editor_style = self.style(cx).clone();
+        let mut lines = vec![Vec::new()];
+        for chunk in buffer.chunks(range, true) {
+            let highlight = chunk
+                .syntax_highlight_id
+                .and_then(|id| id.style(&editor_style.syntax))
+                .unwrap_or_default();
+            for (ix, text) in chunk.text.split('\n').enumerate() {
+                if ix > 0 {
+                    lines.push(Vec::new());
+                }
+                if text.is_empty() {
+                    continue;
+                }
+
+                let span = CodeSnapshotSpan {
+                    text: text.replace('\t', &" ".repeat(tab_size)),
+                    color: highlight.color.unwrap_or(editor_style.text.color),
+                    font_weight: highlight.font_weight,
+                    italic: highlight.font_style == Some(FontStyle::Italic),
+                };
+                let line = lines.last_mut().expect("lines is never empty");
+                if let Some(last) = line.last_mut()
+                    && last.color == span.color
+                    && last.font_weight == span.font_weight
+                    && last.italic == span.italic
+                {
+                    last.text.push_str(&span.text);
+                } else {
+                    line.push(span);
+                }
+            }
+        }
+
+        let text_style = &editor_style.text;
+        let font_size = text_style.font_size.to_pixels(window.rem_size());
+        let font_id = window.text_system().resolve_font(&text_style.font());
+        let char_width = window
+            .text_system()
+            .em_advance(font_id, font_size)
+            .unwrap_or(font_size * 0.6);
+        let colors = cx.theme().colors();
+        let style = CodeSnapshotStyle {
+            // The SVG renderer only knows the concrete names of the bundled fonts.
+            font_family: font_name_with_fallbacks_shared(
+                &text_style.font_family,
+                &SharedString::new_static("monospace"),
+            )
+            .clone(),
+            font_size: font_size.into(),
+            line_height: text_style.line_height_in_pixels(window.rem_size()).into(),
+            char_width: char_width.into(),
+            background: colors.editor_background,
+            border: colors.border,
+            line_number_color: colors.editor_line_number,
+            title_bar_background: colors.title_bar_background,
+            title_color: colors.text_muted,
+            line_numbers,
+            window_chrome,
+        };
+
+        let snapshot = CodeSnapshot {
+            title: self.title(cx).to_string().into(),
+            first_line_number,
+            lines,
+        };
+        (snapshot, style)
+    }
+}
+
+fn render_code_snapshot(
+    snapshot: &CodeSnapshot,
+    style: &CodeSnapshotStyle,
+    format: CodeSnapshotFormat,
+    cx: &App,
+) -> Result<Vec<u8>> {
+    let svg = snapshot.to_svg(style);
+    match format {
+        CodeSnapshotFormat::Svg => Ok(svg.into_bytes()),
+        CodeSnapshotFormat::Png => cx
+            .svg_renderer()
+            .render_png(svg.as_bytes(), PNG_SCALE_FACTOR)
+            .context("failed to render code snapshot"),
+    }
+}
+
+impl CodeSnapshot {
+    fn to_svg(&self, style: &CodeSnapshotStyle) -> String {
+        let last_line_number = self.first_line_number as usize + self.lines.len().saturating_sub(1);
+        let gutter_width = if style.line_numbers {
+            (last_line_number.to_string().len() + 2) as f32 * style.char_width
+        } else {
+            0.
+        };
+        let max_line_len = self
+            .lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|span| span.text.chars().count())
+                    .sum::<usize>()
+            })
+            .max()
+            .unwrap_or(0);
+        let header_height = if style.window_chrome {
+            WINDOW_CHROME_HEIGHT
+        } else {
+            0.
+        };
+        let width = PADDING * 2. + gutter_width + max_line_len as f32 * style.char_width;
+        let height = header_height + PADDING * 2. + self.lines.len() as f32 * style.line_height;
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        )
+        .ok();
+        writeln!(
+            svg,
+            r#"<clipPath id="window"><rect width="{width}" height="{height}" rx="{CORNER_RADIUS}"/></clipPath>"#
+        )
+        .ok();
+        writeln!(svg, r#"<g clip-path="url(#window)">"#).ok();
+        writeln!(
+            svg,
+            r#"<rect width="{width}" height="{height}" fill="{}"/>"#,
+            svg_color(style.background)
+        )
+        .ok();
+
+        if style.window_chrome {
+            writeln!(
+                svg,
+                r#"<rect width="{width}" height="{WINDOW_CHROME_HEIGHT}" fill="{}"/>"#,
+                svg_color(style.title_bar_background)
+            )
+            .ok();
+            for (ix, color) in ["#ff5f57", "#febc2e", "#28c840"].into_iter().enumerate() {
+                writeln!(
+                    svg,
+                    r#"<circle cx="{}" cy="{}" r="6" fill="{color}"/>"#,
+                    PADDING + ix as f32 * 20.,
+                    WINDOW_CHROME_HEIGHT / 2.
+                )
+                .ok();
+            }
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}" text-anchor="middle" dominant-baseline="central" font-family="{}" font-size="{}" fill="{}">{}</text>"#,
+                width / 2.,
+                WINDOW_CHROME_HEIGHT / 2.,
+                escape_xml(&style.font_family),
+                style.font_size * 0.9,
+                svg_color(style.title_color),
+                escape_xml(&self.title)
+            )
+            .ok();
+        }
+        writeln!(svg, "</g>").ok();
+        writeln!(
+            svg,
+            r#"<rect x="0.5" y="0.5" width="{}" height="{}" rx="{CORNER_RADIUS}" fill="none" stroke="{}"/>"#,
+            width - 1.,
+            height - 1.,
+            svg_color(style.border)
+        )
+        .ok();
+
+        writeln!(
+            svg,
+            r#"<g font-family="{}" font-size="{}" xml:space="preserve">"#,
+            escape_xml(&style.font_family),
+            style.font_size
+        )
+        .ok();
+        for (ix, line) in self.lines.iter().enumerate() {
+            let baseline = header_height
+                + PADDING
+                + ix as f32 * style.line_height
+                + (style.line_height + style.font_size * 0.7) / 2.;
+            if style.line_numbers {
+                writeln!(
+                    svg,
+                    r#"<text x="{}" y="{baseline}" text-anchor="end" fill="{}">{}</text>"#,
+                    PADDING + gutter_width - style.char_width * 2.,
+                    svg_color(style.line_number_color),
+                    self.first_line_number as usize + ix
+                )
+                .ok();
+            }
+            if line.is_empty() {
+                continue;
+            }
+
+            write!(
+                svg,
+                r#"<text x="{}" y="{baseline}">"#,
+                PADDING + gutter_width
+            )
+            .ok();
+            for span in line {
+                write!(svg, r#"<tspan fill="{}""#, svg_color(span.color)).ok();
+                if let Some(font_weight) = span.font_weight {
+                    write!(svg, r#" font-weight="{}""#, font_weight.0).ok();
+                }
+                if span.italic {
+                    write!(svg, r#" font-style="italic""#).ok();
+                }
+                write!(svg, ">{}</tspan>", escape_xml(&span.text)).ok();
+            }
+            writeln!(svg, "</text>").ok();
+        }
+        writeln!(svg, "</g>").ok();
+        writeln!(svg, "</svg>").ok();
+        svg
+    }
+}
+
+fn svg_color(color: Hsla) -> String {
+    let color = Rgba::from(color);
+    let channel = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}{:02x}",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        channel(color.a)
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, black, red};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use std::path::{Path, PathBuf};
+    use util::path;
+
+    #[gpui::test]
+    async fn test_export_code_snapshot(cx: &mut TestAppContext) {
+        crate::editor_tests::init_test(cx, |_| {});
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/dir"), json!({ "main.rs": "fn main() {}\n" }))
+            .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/dir/main.rs"), cx)
+            })
+            .await
+            .unwrap();
+        let (editor, cx) =
+            cx.add_window_view(|window, cx| Editor::for_buffer(buffer, Some(project), window, cx));
+
+        editor.update_in(cx, |editor, window, cx| {
+            editor.export_code_snapshot(
+                &ExportCodeSnapshot {
+                    format: CodeSnapshotFormat::Svg,
+                    line_numbers: true,
+                    window_chrome: true,
+                },
+                window,
+                cx,
+            )
+        });
+        cx.simulate_new_path_selection(|_| Some(PathBuf::from(path!("/dir/main.svg"))));
+        cx.run_until_parked();
+
+        let svg = fs.load(Path::new(path!("/dir/main.svg"))).await.unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("main.rs"));
+    }
+
+    #[test]
+    fn test_code_snapshot_svg() {
+        let snapshot = CodeSnapshot {
+            title: "main.rs".into(),
+            first_line_number: 9,
+            lines: vec![
+                vec![
+                    CodeSnapshotSpan {
+                        text: "fn".into(),
+                        color: red(),
+                        font_weight: Some(FontWeight::BOLD),
+                        italic: false,
+                    },
+                    CodeSnapshotSpan {
+                        text: " a<T>() {}".into(),
+                        color: black(),
+                        font_weight: None,
+                        italic: true,
+                    },
+                ],
+                Vec::new(),
+            ],
+        };
+        let style = CodeSnapshotStyle {
+            font_family: "Zed Mono".into(),
+            font_size: 10.,
+            line_height: 20.,
+            char_width: 6.,
+            background: black(),
+            border: black(),
+            line_number_color: black(),
+            title_bar_background: black(),
+            title_color: black(),
+            line_numbers: true,
+            window_chrome: false,
+        };
+
+        let svg = snapshot.to_svg(&style);
+        // Two digits of line numbers plus two columns of spacing, and 12 columns of code.
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="128" height="72""#)
+        );
+        assert!(svg.contains(r#"<tspan fill="#ff0000ff" font-weight="700">fn</tspan>"#));
+        assert!(svg.contains(r#"font-style="italic"> a&lt;T&gt;() {}</tspan>"#));
+        assert!(svg.contains(">9</text>"));
+        assert!(svg.contains(">10</text>"));
+        assert!(!svg.contains("main.rs"));
+    }
+}
@@ -13,9 +13,11 @@
 //! If you're looking to improve Vim mode, you should check out Vim crate that wraps Editor and overrides its behavior.
 pub mod actions;
 pub mod blink_manager;
+mod bookmarks;
 mod bracket_colorization;
 mod clangd_ext;
 pub mod code_context_menus;
+mod code_snapshot;
 pub mod display_map;
 mod document_colors;
 mod document_symbols;
@@ -50,6 +52,7 @@ mod edit_prediction_tests;
 #[cfg(test)]
 mod editor_tests;
 mod signature_help;
+mod snippets;
 #[cfg(any(test, feature = "test-support"))]
 pub mod test;
 
@@ -159,6 +162,7 @@ use project::{
     CompletionResponse, CompletionSource, DisableAiSettings, DocumentHighlight, InlayHint, InlayId,
     InvalidationStrategy, Location, LocationLink, LspAction, PrepareRenameResponse, Project,
     ProjectItem, ProjectPath, ProjectTransaction,
+    bookmark_store::BookmarkStore,
     debugger::{
         breakpoint_store::{
             Breakpoint, BreakpointEditAction, BreakpointSessionState, BreakpointState,
@@ -185,6 +189,7 @@ use settings::{
 };
 use smallvec::{SmallVec, smallvec};
 use snippet::Snippet;
+use snippets::{SnippetTransformedRange, SnippetVariables};
 use std::{
     any::{Any, TypeId},
     borrow::Cow,
@@ -339,6 +344,7 @@ pub fn init(cx: &mut App) {
     workspace::register_project_item::<Editor>(cx);
     workspace::FollowableViewRegistry::register::<Editor>(cx);
     workspace::register_serializable_item::<Editor>(cx);
+    bookmarks::init(cx);
 
     cx.observe_new(
         |workspace: &mut Workspace, _: Option<&mut Window>, _cx: &mut Context<Workspace>| {
@@ -1290,6 +1296,7 @@ pub struct Editor {
     expect_bounds_change: Option<Bounds<Pixels>>,
     runnables: RunnableData,
     breakpoint_store: Option<Entity<BreakpointStore>>,
+    bookmark_store: Option<Entity<BookmarkStore>>,
     gutter_breakpoint_indicator: (Option<PhantomBreakpointIndicator>, Option<Task<()>>),
     pub(crate) gutter_diff_review_indicator: (Option<PhantomDiffReviewIndicator>, Option<Task<()>>),
     pub(crate) diff_review_drag_state: Option<DiffReviewDragState>,
@@ -1694,6 +1701,7 @@ struct SnippetState {
     ranges: Vec<Vec<Range<Anchor>>>,
     active_index: usize,
     choices: Vec<Option<Vec<String>>>,
+    transformed_ranges: Vec<SnippetTransformedRange>,
 }
 
 #[doc(hidden)]
@@ -2204,7 +2212,7 @@ impl Editor {
                         }
                     }
 
-                    project::Event::EntryRenamed(transaction, project_path, abs_path) => {
+                    project::Event::EntryRenamed(transaction, project_path, abs_path, _) => {
                         let Some(workspace) = editor.workspace() else {
                             return;
                         };
@@ -2344,6 +2352,10 @@ impl Editor {
             (EditorMode::Full { .. }, Some(project)) => Some(project.read(cx).breakpoint_store()),
             _ => None,
         };
+        let bookmark_store = match (&mode, project.as_ref()) {
+            (EditorMode::Full { .. }, Some(project)) => Some(project.read(cx).bookmark_store()),
+            _ => None,
+        };
 
         let mut code_action_providers = Vec::new();
         let mut load_uncommitted_diff = None;
@@ -2516,6 +2528,7 @@ impl Editor {
             blame_subscription: None,
 
             breakpoint_store,
+            bookmark_store,
             gutter_breakpoint_indicator: (None, None),
             gutter_diff_review_indicator: (None, None),
             diff_review_drag_state: None,
@@ -2616,6 +2629,13 @@ impl Editor {
                     cx.notify();
                 }));
         }
+        if let Some(bookmarks) = editor.bookmark_store.as_ref() {
+            editor
+                ._subscriptions
+                .push(cx.observe(bookmarks, |_, _, cx| {
+                    cx.notify();
+                }));
+        }
         editor._subscriptions.extend(project_subscriptions);
 
         editor._subscriptions.push(cx.subscribe_in(
@@ -3335,7 +3355,6 @@ impl Editor {
         self.completion_provider = provider;
     }
 
-    #[cfg(any(test, feature = "test-support"))]
     pub fn completion_provider(&self) -> Option<Rc<dyn CompletionProvider>> {
         self.completion_provider.clone()
     }
@@ -6579,6 +6598,7 @@ impl Editor {
             intent,
             &buffer_handle,
             &completions_menu.initial_position.text_anchor,
+            &self.snippet_variables(cx),
             cx,
         );
 
@@ -10549,6 +10569,7 @@ impl Editor {
             is_end_tabstop: bool,
             ranges: Vec<Range<T>>,
             choices: Option<Vec<String>>,
+            transformed_ranges: Vec<SnippetTransformedRange>,
         }
 
         let tabstops = self.buffer.update(cx, |buffer, cx| {
@@ -10590,10 +10611,40 @@ impl Editor {
                         .collect::<Vec<_>>();
                     tabstop_ranges.sort_unstable_by(|a, b| a.start.cmp(&b.start, snapshot));
 
+                    // Transformed ranges follow the tabstop's first range in the same insertion.
+                    let source = tabstop.ranges.first().cloned().unwrap_or_default();
+                    let transformed_ranges = tabstop
+                        .transformed_ranges
+                        .iter()
+                        .flat_map(|transformed_range| {
+                            let source = source.clone();
+                            let mut delta = 0_isize;
+                            insertion_ranges.iter().map(move |insertion_range| {
+                                let insertion_start = insertion_range.start + delta;
+                                delta += snippet.text.len() as isize
+                                    - (insertion_range.end - insertion_range.start) as isize;
+
+                                let position = |snippet_offset: isize| {
+                                    (insertion_start + snippet_offset).min(snapshot.len())
+                                };
+                                let range = &transformed_range.range;
+                                SnippetTransformedRange {
+                                    source: snapshot.anchor_before(position(source.start))
+                                        ..snapshot.anchor_after(position(source.end)),
+                                    range: snapshot.anchor_after(position(range.start))
+                                        ..snapshot.anchor_before(position(range.end)),
+                                    transform: transformed_range.transform.clone(),
+                                    follows_source: range.start >= source.end,
+                                }
+                            })
+                        })
+                        .collect();
+
                     Tabstop {
                         is_end_tabstop,
                         ranges: tabstop_ranges,
                         choices: tabstop.choices.clone(),
+                        transformed_ranges,
                     }
                 })
                 .collect::<Vec<_>>()
@@ -10619,15 +10670,18 @@ impl Editor {
                     .map(|tabstop| tabstop.choices.clone())
                     .collect();
 
-                let ranges = tabstops
-                    .into_iter()
-                    .map(|tabstop| tabstop.ranges)
-                    .collect::<Vec<_>>();
+                let mut ranges = Vec::new();
+                let mut transformed_ranges = Vec::new();
+                for tabstop in tabstops {
+                    ranges.push(tabstop.ranges);
+                    transformed_ranges.extend(tabstop.transformed_ranges);
+                }
 
                 self.snippet_stack.push(SnippetState {
                     active_index: 0,
                     ranges,
                     choices,
+                    transformed_ranges,
                 });
             }
 
@@ -15862,9 +15916,11 @@ impl Editor {
             .map(|selection| selection.range())
             .collect_vec();
 
+        let variables = self.snippet_variables(cx);
+        let variables = |name: &str| variables.resolve(name, cx);
         let snippet = if let Some(snippet_body) = &action.snippet {
             if action.language.is_none() && action.name.is_none() {
-                Snippet::parse(snippet_body)?
+                Snippet::parse_with_variables(snippet_body, &variables)?
             } else {
                 bail!("`snippet` is mutually exclusive with `language` and `name`")
             }
@@ -15876,7 +15932,7 @@ impl Editor {
                 .into_iter()
                 .find(|snippet| snippet.name == *name)
                 .context("snippet not found")?;
-            Snippet::parse(&snippet.body)?
+            Snippet::parse_with_variables(&snippet.body, &variables)?
         } else {
             // todo(andrew): open modal to select snippet
             bail!("`name` or `snippet` is required")
@@ -24205,6 +24261,9 @@ impl Editor {
                 if *is_local && self.has_active_edit_prediction() {
                     self.update_visible_edit_prediction(window, cx);
                 }
+                if *is_local {
+                    self.refresh_snippet_transforms(window, cx);
+                }
 
                 // Clean up orphaned review comments after edits
                 self.cleanup_orphaned_review_comments(cx);
@@ -25886,6 +25945,7 @@ fn process_completion_for_edit(
     intent: CompletionIntent,
     buffer: &Entity<Buffer>,
     cursor_position: &text::Anchor,
+    snippet_variables: &SnippetVariables,
     cx: &mut Context<Editor>,
 ) -> CompletionEdit {
     let buffer = buffer.read(cx);
@@ -25911,7 +25971,8 @@ fn process_completion_for_edit(
         {
             snippet_source = label;
         }
-        match Snippet::parse(&snippet_source).log_err() {
+        let variables = |name: &str| snippet_variables.resolve(name, cx);
+        match Snippet::parse_with_variables(&snippet_source, &variables).log_err() {
             Some(parsed_snippet) => (Some(parsed_snippet.clone()), parsed_snippet.text),
             None => (None, completion.new_text.clone()),
         }
@@ -13047,6 +13047,89 @@ async fn test_snippets(cx: &mut TestAppContext) {
     "});
 }
 
+#[gpui::test]
+async fn test_snippet_variables(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    cx.set_state(indoc! {"
+        one
+        t«woˇ»
+    "});
+    cx.update_editor(|editor, window, cx| {
+        editor.insert_snippet_at_selections(
+            &InsertSnippet {
+                snippet: Some(
+                    "${TM_SELECTED_TEXT:x}:$TM_LINE_NUMBER:${TM_CURRENT_LINE/(\\w+)/${1:/upcase}/}"
+                        .to_string(),
+                ),
+                ..InsertSnippet::default()
+            },
+            window,
+            cx,
+        );
+    });
+    cx.assert_editor_state(indoc! {"
+        one
+        two:2:TWOˇ
+    "});
+}
+
+#[gpui::test]
+async fn test_snippet_transforms(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    // Ensure automatic grouping doesn't occur.
+    cx.update_buffer(|buffer, _| buffer.set_group_interval(Duration::ZERO));
+
+    cx.set_state("ˇ");
+    cx.update_editor(|editor, window, cx| {
+        let snippet = Snippet::parse("let ${1:name} = ${1/(.*)/${1:/upcase}/};$0").unwrap();
+        editor
+            .insert_snippet(
+                &[MultiBufferOffset(0)..MultiBufferOffset(0)],
+                snippet,
+                window,
+                cx,
+            )
+            .unwrap();
+    });
+    cx.assert_editor_state("let «nameˇ» = NAME;");
+
+    // Transformed text is updated as the tabstop is edited.
+    cx.update_editor(|editor, window, cx| editor.handle_input("foo", window, cx));
+    cx.assert_editor_state("let fooˇ = FOO;");
+    cx.update_editor(|editor, window, cx| editor.backspace(&Backspace, window, cx));
+    cx.assert_editor_state("let foˇ = FO;");
+
+    // Undoing an edit also undoes the transformed text.
+    cx.update_editor(|editor, window, cx| {
+        editor.undo(&Undo, window, cx);
+        assert_eq!(editor.text(cx), "let foo = FOO;");
+    });
+
+    // Text typed next to transformed text belongs to the tabstop.
+    cx.set_state("ˇ");
+    cx.update_editor(|editor, window, cx| {
+        let snippet = Snippet::parse("$1${1/(.*)/-$1/}").unwrap();
+        editor
+            .insert_snippet(
+                &[MultiBufferOffset(0)..MultiBufferOffset(0)],
+                snippet,
+                window,
+                cx,
+            )
+            .unwrap();
+    });
+    cx.assert_editor_state("ˇ-");
+    cx.update_editor(|editor, window, cx| editor.handle_input("a", window, cx));
+    cx.assert_editor_state("aˇ-a");
+    cx.update_editor(|editor, window, cx| editor.handle_input("b", window, cx));
+    cx.assert_editor_state("abˇ-ab");
+}
+
 #[gpui::test]
 async fn test_snippet_indentation(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -26369,6 +26452,102 @@ async fn test_breakpoint_toggling(cx: &mut TestAppContext) {
     assert_breakpoint(&breakpoints, &abs_path, vec![]);
 }
 
+#[gpui::test]
+async fn test_bookmark_toggling_and_navigation(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/a"),
+        json!({
+            "main.rs": "one\ntwo\nthree\nfour\nfive",
+        }),
+    )
+    .await;
+    let project = Project::test(fs, [path!("/a").as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/a/main.rs"), cx)
+        })
+        .await
+        .unwrap();
+    let (editor, cx) = cx.add_window_view(|window, cx| {
+        Editor::new(
+            EditorMode::full(),
+            MultiBuffer::build_from_buffer(buffer, cx),
+            Some(project.clone()),
+            window,
+            cx,
+        )
+    });
+    let abs_path: Arc<Path> = Path::new(path!("/a/main.rs")).into();
+    let cursor_row = |cx: &mut VisualTestContext| {
+        editor.update(cx, |editor, cx| {
+            editor
+                .selections
+                .newest::<Point>(&editor.display_snapshot(cx))
+                .head()
+                .row
+        })
+    };
+    let bookmarked_rows = |cx: &mut VisualTestContext| {
+        project.read_with(cx, |project, cx| {
+            project
+                .bookmark_store()
+                .read(cx)
+                .serialized_bookmarks(&abs_path, cx)
+                .into_iter()
+                .map(|bookmark| bookmark.row)
+                .collect::<Vec<_>>()
+        })
+    };
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+            s.select_ranges([
+                Point::new(1, 1)..Point::new(1, 1),
+                Point::new(3, 0)..Point::new(3, 0),
+            ])
+        });
+        editor.toggle_bookmark(&actions::ToggleBookmark, window, cx);
+    });
+    assert_eq!(bookmarked_rows(cx), vec![1, 3]);
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.move_to_beginning(&MoveToBeginning, window, cx);
+        editor.go_to_next_bookmark(&actions::GoToNextBookmark, window, cx);
+    });
+    assert_eq!(cursor_row(cx), 1);
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.go_to_next_bookmark(&actions::GoToNextBookmark, window, cx);
+    });
+    assert_eq!(cursor_row(cx), 3);
+
+    // Navigation wraps around in both directions
+    editor.update_in(cx, |editor, window, cx| {
+        editor.go_to_next_bookmark(&actions::GoToNextBookmark, window, cx);
+    });
+    assert_eq!(cursor_row(cx), 1);
+    editor.update_in(cx, |editor, window, cx| {
+        editor.go_to_previous_bookmark(&actions::GoToPreviousBookmark, window, cx);
+    });
+    assert_eq!(cursor_row(cx), 3);
+
+    // Bookmarks follow edits above them
+    editor.update_in(cx, |editor, window, cx| {
+        editor.move_to_beginning(&MoveToBeginning, window, cx);
+        editor.handle_input("zero\n", window, cx);
+    });
+    assert_eq!(bookmarked_rows(cx), vec![2, 4]);
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.go_to_previous_bookmark(&actions::GoToPreviousBookmark, window, cx);
+        editor.toggle_bookmark(&actions::ToggleBookmark, window, cx);
+    });
+    assert_eq!(bookmarked_rows(cx), vec![2]);
+}
+
 #[gpui::test]
 async fn test_log_breakpoint_editing(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
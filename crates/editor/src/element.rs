@@ -60,6 +60,7 @@ use multi_buffer::{
 
 use project::{
     DisableAiSettings, Entry, ProjectPath,
+    bookmark_store::Bookmark,
     debugger::breakpoint_store::{Breakpoint, BreakpointSessionState},
     project_settings::ProjectSettings,
 };
@@ -515,6 +516,8 @@ impl EditorElement {
         register_action(editor, window, Editor::copy_file_name);
         register_action(editor, window, Editor::copy_file_name_without_extension);
         register_action(editor, window, Editor::copy_highlight_json);
+        register_action(editor, window, Editor::export_code_snapshot);
+        register_action(editor, window, Editor::copy_code_snapshot);
         register_action(editor, window, Editor::copy_permalink_to_line);
         register_action(editor, window, Editor::open_permalink_to_line);
         register_action(editor, window, Editor::copy_file_location);
@@ -646,6 +649,9 @@ impl EditorElement {
         register_action(editor, window, Editor::insert_uuid_v7);
         register_action(editor, window, Editor::open_selections_in_multibuffer);
         register_action(editor, window, Editor::toggle_breakpoint);
+        register_action(editor, window, Editor::toggle_bookmark);
+        register_action(editor, window, Editor::go_to_next_bookmark);
+        register_action(editor, window, Editor::go_to_previous_bookmark);
         register_action(editor, window, Editor::edit_log_breakpoint);
         register_action(editor, window, Editor::enable_breakpoint);
         register_action(editor, window, Editor::disable_breakpoint);
@@ -3186,6 +3192,65 @@ impl EditorElement {
         })
     }
 
+    fn layout_bookmarks(
+        &self,
+        line_height: Pixels,
+        range: Range<DisplayRow>,
+        scroll_position: gpui::Point<ScrollOffset>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        snapshot: &EditorSnapshot,
+        bookmarks: HashMap<DisplayRow, (Anchor, Bookmark)>,
+        row_infos: &[RowInfo],
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<AnyElement> {
+        if self.split_side == Some(SplitSide::Left) {
+            return Vec::new();
+        }
+
+        self.editor.update(cx, |editor, cx| {
+            bookmarks
+                .into_iter()
+                .filter_map(|(display_row, (anchor, bookmark))| {
+                    if !range.contains(&display_row) {
+                        return None;
+                    }
+                    if row_infos
+                        .get((display_row - range.start).0 as usize)
+                        .is_some_and(|row_info| {
+                            row_info.expand_info.is_some()
+                                || row_info
+                                    .diff_status
+                                    .is_some_and(|status| status.is_deleted())
+                        })
+                    {
+                        return None;
+                    }
+
+                    let row =
+                        MultiBufferRow(DisplayPoint::new(display_row, 0).to_point(snapshot).row);
+                    if snapshot.is_line_folded(row) {
+                        return None;
+                    }
+
+                    let button = editor.render_bookmark(anchor, display_row, &bookmark, cx);
+                    let button = prepaint_gutter_button(
+                        button.into_any_element(),
+                        display_row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_position,
+                        gutter_hitbox,
+                        window,
+                        cx,
+                    );
+                    Some(button)
+                })
+                .collect_vec()
+        })
+    }
+
     fn should_render_diff_review_button(
         &self,
         range: Range<DisplayRow>,
@@ -3243,6 +3308,7 @@ impl EditorElement {
         gutter_hitbox: &Hitbox,
         snapshot: &EditorSnapshot,
         breakpoints: &mut HashMap<DisplayRow, (Anchor, Breakpoint, Option<BreakpointSessionState>)>,
+        bookmarks: &mut HashMap<DisplayRow, (Anchor, Bookmark)>,
         window: &mut Window,
         cx: &mut App,
     ) -> Vec<AnyElement> {
@@ -3322,6 +3388,7 @@ impl EditorElement {
                     }
 
                     let removed_breakpoint = breakpoints.remove(&display_row);
+                    bookmarks.remove(&display_row);
                     let button = editor.render_run_indicator(
                         &self.style,
                         Some(display_row) == active_task_indicator_row,
@@ -6412,6 +6479,10 @@ impl EditorElement {
                 breakpoint.paint(window, cx);
             }
 
+            for bookmark in layout.bookmarks.iter_mut() {
+                bookmark.paint(window, cx);
+            }
+
             for test_indicator in layout.test_indicators.iter_mut() {
                 test_indicator.paint(window, cx);
             }
@@ -10152,6 +10223,10 @@ impl Element for EditorElement {
                         }
                     });
 
+                    let mut bookmark_rows = self.editor.update(cx, |editor, cx| {
+                        editor.active_bookmarks(start_row..end_row, window, cx)
+                    });
+
                     let mut expand_toggles =
                         window.with_element_namespace("expand_toggles", |window| {
                             self.layout_expand_toggles(
@@ -10740,6 +10815,7 @@ impl Element for EditorElement {
                             &gutter_hitbox,
                             &snapshot,
                             &mut breakpoint_rows,
+                            &mut bookmark_rows,
                             window,
                             cx,
                         )
@@ -10750,6 +10826,21 @@ impl Element for EditorElement {
                     let show_breakpoints = snapshot
                         .show_breakpoints
                         .unwrap_or(gutter_settings.breakpoints);
+                    if show_breakpoints {
+                        bookmark_rows.retain(|row, _| !breakpoint_rows.contains_key(row));
+                    }
+                    let bookmarks = self.layout_bookmarks(
+                        line_height,
+                        start_row..end_row,
+                        scroll_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &snapshot,
+                        bookmark_rows,
+                        &row_infos,
+                        window,
+                        cx,
+                    );
                     let breakpoints = if show_breakpoints {
                         self.layout_breakpoints(
                             line_height,
@@ -11056,6 +11147,7 @@ impl Element for EditorElement {
                         mouse_context_menu,
                         test_indicators,
                         breakpoints,
+                        bookmarks,
                         diff_review_button,
                         crease_toggles,
                         crease_trailers,
@@ -11243,6 +11335,7 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     test_indicators: Vec<AnyElement>,
     breakpoints: Vec<AnyElement>,
+    bookmarks: Vec<AnyElement>,
     diff_review_button: Option<AnyElement>,
     crease_toggles: Vec<Option<AnyElement>>,
     expand_toggles: Vec<Option<(AnyElement, gpui::Point<Pixels>)>>,
@@ -1706,6 +1706,14 @@ impl SearchableItem for Editor {
     ) {
         let text = self.buffer.read(cx);
         let text = text.snapshot(cx);
+        if query.is_structural() {
+            if let Some(replacement) = structural_replacement(&text, identifier, query) {
+                self.transact(window, cx, |this, _, cx| {
+                    this.edit([(identifier.clone(), Arc::from(replacement))], cx);
+                });
+            }
+            return;
+        }
         let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
         let text: Cow<_> = if text.len() == 1 {
             text.first().cloned().unwrap().into()
@@ -1732,9 +1740,16 @@ impl SearchableItem for Editor {
         let text = text.snapshot(cx);
         let mut edits = vec![];
 
-        // A regex might have replacement variables so we cannot apply
-        // the same replacement to all matches
-        if query.is_regex() {
+        // Regex and structural queries might have replacement variables so we
+        // cannot apply the same replacement to all matches
+        if query.is_structural() {
+            edits = matches
+                .filter_map(|m| {
+                    structural_replacement(&text, m, query)
+                        .map(|replacement| (m.clone(), Arc::from(replacement)))
+                })
+                .collect();
+        } else if query.is_regex() {
             edits = matches
                 .filter_map(|m| {
                     let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
@@ -1900,6 +1915,18 @@ impl SearchableItem for Editor {
     }
 }
 
+fn structural_replacement(
+    snapshot: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+) -> Option<String> {
+    let buffer_ranges = snapshot.range_to_buffer_ranges(range.clone());
+    let [(buffer, range, _)] = buffer_ranges.as_slice() else {
+        return None;
+    };
+    query.structural_replacement_for(buffer, range.start.0..range.end.0)
+}
+
 pub fn active_match_index(
     direction: Direction,
     ranges: &[Range<Anchor>],
@@ -223,6 +223,19 @@ impl Domain for EditorDb {
                 PRIMARY KEY(workspace_id, path, start)
             );
         ),
+        // Line bookmarks, keyed by file path like `file_folds`.
+        sql! (
+            CREATE TABLE file_bookmarks (
+                workspace_id INTEGER NOT NULL,
+                path TEXT NOT NULL,
+                row INTEGER NOT NULL,
+                label TEXT,
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE,
+                PRIMARY KEY(workspace_id, path, row)
+            );
+        ),
     ];
 }
 
@@ -319,6 +332,17 @@ impl EditorDb {
         }
     }
 
+    query! {
+        pub fn get_bookmarks(
+            workspace_id: WorkspaceId
+        ) -> Result<Vec<(PathBuf, u32, Option<String>)>> {
+            SELECT path, row, label
+            FROM file_bookmarks
+            WHERE workspace_id = ?1
+            ORDER BY path, row
+        }
+    }
+
     pub async fn save_editor_selections(
         &self,
         editor_id: ItemId,
@@ -408,6 +432,42 @@ VALUES {placeholders};
         })
         .await
     }
+
+    pub async fn save_file_bookmarks(
+        &self,
+        workspace_id: WorkspaceId,
+        path: Arc<Path>,
+        bookmarks: Vec<(u32, Option<String>)>,
+    ) -> Result<()> {
+        log::debug!("Saving bookmarks for file {path:?} in workspace {workspace_id:?}");
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                DELETE FROM file_bookmarks WHERE workspace_id = ?1 AND path = ?2;
+            ))?((workspace_id, path.as_ref()))?;
+
+            for (row, label) in bookmarks {
+                conn.exec_bound(sql!(
+                    INSERT OR IGNORE INTO file_bookmarks (workspace_id, path, row, label)
+                    VALUES (?1, ?2, ?3, ?4);
+                ))?((workspace_id, path.as_ref(), row, label))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn delete_file_bookmarks(
+        &self,
+        workspace_id: WorkspaceId,
+        path: Arc<Path>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                DELETE FROM file_bookmarks WHERE workspace_id = ?1 AND path = ?2;
+            ))?((workspace_id, path.as_ref()))
+        })
+        .await
+    }
 }
 
 #[cfg(test)]
@@ -614,4 +674,53 @@ mod tests {
         assert_eq!(retrieved_a[0].0, 10); // file_a's fold
         assert_eq!(retrieved_b[0].0, 30); // file_b's fold
     }
+
+    #[gpui::test]
+    async fn test_save_and_get_file_bookmarks(cx: &mut gpui::TestAppContext) {
+        let db = cx.update(|cx| workspace::WorkspaceDb::global(cx));
+        let workspace_id = db.next_id().await.unwrap();
+        let editor_db = cx.update(|cx| EditorDb::global(cx));
+
+        let file_path_a: Arc<Path> = Arc::from(Path::new("/tmp/bookmarks_a.rs"));
+        let file_path_b: Arc<Path> = Arc::from(Path::new("/tmp/bookmarks_b.rs"));
+        editor_db
+            .save_file_bookmarks(
+                workspace_id,
+                file_path_a.clone(),
+                vec![(3, Some("entry point".to_string())), (1, None)],
+            )
+            .await
+            .unwrap();
+        editor_db
+            .save_file_bookmarks(workspace_id, file_path_b.clone(), vec![(7, None)])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            editor_db.get_bookmarks(workspace_id).unwrap(),
+            vec![
+                (file_path_a.to_path_buf(), 1, None),
+                (
+                    file_path_a.to_path_buf(),
+                    3,
+                    Some("entry point".to_string())
+                ),
+                (file_path_b.to_path_buf(), 7, None),
+            ]
+        );
+
+        // Saving replaces the bookmarks of that file only
+        editor_db
+            .save_file_bookmarks(workspace_id, file_path_a.clone(), vec![(5, None)])
+            .await
+            .unwrap();
+        editor_db
+            .delete_file_bookmarks(workspace_id, file_path_b.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            editor_db.get_bookmarks(workspace_id).unwrap(),
+            vec![(file_path_a.to_path_buf(), 5, None)]
+        );
+    }
 }
@@ -0,0 +1,367 @@
+use std::{ops::Range, path::Path};
+
+use collections::HashMap;
+use gpui::{App, Context, Window};
+use multi_buffer::{
+    Anchor, AnchorRangeExt as _, MultiBufferOffset, MultiBufferRow, MultiBufferSnapshot, ToOffset,
+};
+use rand::Rng as _;
+use snippet::Transform;
+use text::{Bias, Point};
+use time::{OffsetDateTime, UtcOffset};
+
+use crate::{Editor, SelectionEffects};
+
+/// The values of the variables that snippets can reference, like `$TM_FILENAME`, captured from
+/// the newest selection of an editor.
+pub(crate) struct SnippetVariables {
+    values: HashMap<&'static str, String>,
+}
+
+impl SnippetVariables {
+    /// Resolves a variable. Variables that are the same for every editor, like the clipboard or
+    /// the current date, are only computed when referenced.
+    pub(crate) fn resolve(&self, name: &str, cx: &App) -> Option<String> {
+        if let Some(value) = self.values.get(name) {
+            return Some(value.clone());
+        }
+
+        let now = OffsetDateTime::now_utc()
+            .to_offset(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC));
+        let value = match name {
+            "CLIPBOARD" => cx
+                .read_from_clipboard()
+                .and_then(|item| item.text())
+                .unwrap_or_default(),
+            "CURRENT_YEAR" => now.year().to_string(),
+            "CURRENT_YEAR_SHORT" => format!("{:02}", now.year() % 100),
+            "CURRENT_MONTH" => format!("{:02}", u8::from(now.month())),
+            "CURRENT_MONTH_NAME" => now.month().to_string(),
+            "CURRENT_MONTH_NAME_SHORT" => now.month().to_string()[..3].to_string(),
+            "CURRENT_DATE" => format!("{:02}", now.day()),
+            "CURRENT_DAY_NAME" => now.weekday().to_string(),
+            "CURRENT_DAY_NAME_SHORT" => now.weekday().to_string()[..3].to_string(),
+            "CURRENT_HOUR" => format!("{:02}", now.hour()),
+            "CURRENT_MINUTE" => format!("{:02}", now.minute()),
+            "CURRENT_SECOND" => format!("{:02}", now.second()),
+            "CURRENT_SECONDS_UNIX" => now.unix_timestamp().to_string(),
+            "CURRENT_TIMEZONE_OFFSET" => {
+                let (hours, minutes, _) = now.offset().as_hms();
+                let sign = if now.offset().is_negative() { '-' } else { '+' };
+                format!("{sign}{:02}:{:02}", hours.abs(), minutes.abs())
+            }
+            "RANDOM" => format!("{:06}", rand::rng().random_range(0..1_000_000)),
+            "RANDOM_HEX" => format!("{:06x}", rand::rng().random_range(0..0x1000000)),
+            "UUID" => uuid::Uuid::new_v4().to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// A range of a snippet whose text is its tabstop's text, transformed with a regex.
+#[derive(Debug)]
+pub(crate) struct SnippetTransformedRange {
+    /// The range of the tabstop whose text is transformed.
+    pub source: Range<Anchor>,
+    /// The range of the transformed text. Unlike tabstops, it doesn't grow when text is typed at
+    /// its edges, as that text belongs to the tabstop next to it.
+    pub range: Range<Anchor>,
+    pub transform: Transform,
+    /// Whether the range comes after its tabstop.
+    pub follows_source: bool,
+}
+
+impl SnippetTransformedRange {
+    fn resolve(&self, buffer: &MultiBufferSnapshot) -> Range<MultiBufferOffset> {
+        let start = self.range.start.to_offset(buffer);
+        let end = self.range.end.to_offset(buffer);
+        if start <= end {
+            start..end
+        } else if self.follows_source {
+            // Text was typed where the range was empty. It belongs to the tabstop, so the range
+            // is after it.
+            start..start
+        } else {
+            end..end
+        }
+    }
+}
+
+impl Editor {
+    pub(crate) fn snippet_variables(&self, cx: &App) -> SnippetVariables {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let selection = self.selections.newest_anchor();
+        let selection_range = selection.start.to_offset(&buffer)..selection.end.to_offset(&buffer);
+        let head = selection.head().to_offset(&buffer);
+        let point = buffer.offset_to_point(head);
+        let line_range = Point::new(point.row, 0)
+            ..Point::new(point.row, buffer.line_len(MultiBufferRow(point.row)));
+        let (word_range, _) = buffer.surrounding_word(head, None);
+
+        let mut values = HashMap::default();
+        values.insert(
+            "TM_SELECTED_TEXT",
+            buffer.text_for_range(selection_range).collect(),
+        );
+        values.insert(
+            "TM_CURRENT_LINE",
+            buffer.text_for_range(line_range).collect(),
+        );
+        values.insert(
+            "TM_CURRENT_WORD",
+            buffer.text_for_range(word_range).collect(),
+        );
+        values.insert("TM_LINE_INDEX", point.row.to_string());
+        values.insert("TM_LINE_NUMBER", (point.row + 1).to_string());
+        values.insert("CURSOR_INDEX", "0".to_string());
+        values.insert("CURSOR_NUMBER", "1".to_string());
+
+        if let Some(file) = buffer.file_at(head) {
+            let file_name = file.file_name(cx);
+            values.insert("TM_FILENAME", file_name.to_string());
+            if let Some(stem) = Path::new(file_name).file_stem() {
+                values.insert("TM_FILENAME_BASE", stem.to_string_lossy().into_owned());
+            }
+            let path = file
+                .as_local()
+                .map(|file| file.abs_path(cx))
+                .unwrap_or_else(|| file.full_path(cx));
+            if let Some(directory) = path.parent() {
+                values.insert("TM_DIRECTORY", directory.to_string_lossy().into_owned());
+            }
+            values.insert("TM_FILEPATH", path.to_string_lossy().into_owned());
+            values.insert(
+                "RELATIVE_FILEPATH",
+                file.path().display(file.path_style(cx)).into_owned(),
+            );
+            if let Some(file) = project::File::from_dyn(Some(file)) {
+                let worktree = file.worktree.read(cx);
+                values.insert("WORKSPACE_NAME", worktree.root_name_str().to_string());
+                values.insert(
+                    "WORKSPACE_FOLDER",
+                    worktree.abs_path().to_string_lossy().into_owned(),
+                );
+            }
+        }
+
+        if let Some(scope) = buffer.language_scope_at(head) {
+            if let Some(prefix) = scope.line_comment_prefixes().first() {
+                values.insert("LINE_COMMENT", prefix.trim_end().to_string());
+            }
+            if let Some(block_comment) = scope.block_comment() {
+                values.insert(
+                    "BLOCK_COMMENT_START",
+                    block_comment.start.trim_end().to_string(),
+                );
+                values.insert(
+                    "BLOCK_COMMENT_END",
+                    block_comment.end.trim_start().to_string(),
+                );
+            }
+        }
+
+        SnippetVariables { values }
+    }
+
+    /// Updates the transformed ranges of the active snippet after its tabstops are edited.
+    pub(crate) fn refresh_snippet_transforms(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(snippet) = self.snippet_stack.last() else {
+            return;
+        };
+        if snippet.transformed_ranges.is_empty() {
+            return;
+        }
+
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let mut edits = Vec::new();
+        for (ix, transformed_range) in snippet.transformed_ranges.iter().enumerate() {
+            let source = transformed_range.source.start.to_offset(&buffer)
+                ..transformed_range.source.end.to_offset(&buffer);
+            if source.start > source.end {
+                continue;
+            }
+            let range = transformed_range.resolve(&buffer);
+            let source_text = buffer.text_for_range(source).collect::<String>();
+            let new_text = transformed_range.transform.apply(&source_text);
+            if buffer.text_for_range(range.clone()).collect::<String>() != new_text {
+                edits.push((ix, range, new_text));
+            }
+        }
+        edits.sort_by_key(|(_, range, _)| range.start);
+        let mut last_end = MultiBufferOffset(0);
+        edits.retain(|(_, range, _)| {
+            let overlaps = range.start < last_end;
+            last_end = last_end.max(range.end);
+            !overlaps
+        });
+        if edits.is_empty() {
+            return;
+        }
+
+        // Edits at the edges of tabstops and selections would make them grow, so map them through
+        // the edits explicitly, keeping them next to the transformed text rather than around it.
+        let offset_edits = edits
+            .iter()
+            .map(|(_, range, new_text)| (range.start.0..range.end.0, new_text.len()))
+            .collect::<Vec<_>>();
+        let map = |offset: MultiBufferOffset, bias| {
+            MultiBufferOffset(map_offset(offset.0, &offset_edits, bias))
+        };
+        let map_range = |range: Range<MultiBufferOffset>| {
+            let start = map(range.start, Bias::Right);
+            start..map(range.end, Bias::Left).max(start)
+        };
+        let tabstop_ranges = snippet
+            .ranges
+            .iter()
+            .map(|ranges| {
+                ranges
+                    .iter()
+                    .map(|range| map_range(range.to_offset(&buffer)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let transformed_ranges = snippet
+            .transformed_ranges
+            .iter()
+            .enumerate()
+            .map(|(ix, transformed_range)| {
+                let source = map_range(transformed_range.source.to_offset(&buffer));
+                let range = match edits.iter().find(|(edit_ix, ..)| *edit_ix == ix) {
+                    Some((_, range, new_text)) => {
+                        let start = map(range.start, Bias::Left);
+                        start..start + new_text.len()
+                    }
+                    None => map_range(transformed_range.resolve(&buffer)),
+                };
+                (source, range)
+            })
+            .collect::<Vec<_>>();
+        let selections = self
+            .selections
+            .all::<MultiBufferOffset>(&self.display_snapshot(cx))
+            .into_iter()
+            .map(|mut selection| {
+                if selection.is_empty() {
+                    let head = map(selection.head(), Bias::Left);
+                    selection.collapse_to(head, selection.goal);
+                } else {
+                    let range = map_range(selection.range());
+                    selection.start = range.start;
+                    selection.end = range.end;
+                }
+                selection
+            })
+            .collect::<Vec<_>>();
+
+        // Make the transformed text part of the edit that caused it, so that they're undone
+        // together.
+        self.buffer.update(cx, |buffer, cx| {
+            let last_transaction = buffer.last_transaction_id(cx);
+            buffer.start_transaction(cx);
+            buffer.edit(
+                edits
+                    .iter()
+                    .map(|(_, range, new_text)| (range.clone(), new_text.as_str())),
+                None,
+                cx,
+            );
+            if let Some(transaction) = buffer.end_transaction(cx)
+                && let Some(last_transaction) = last_transaction
+            {
+                buffer.merge_transactions(transaction, last_transaction, cx);
+            }
+        });
+
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        if let Some(snippet) = self.snippet_stack.last_mut() {
+            snippet.ranges = tabstop_ranges
+                .into_iter()
+                .map(|ranges| {
+                    ranges
+                        .into_iter()
+                        .map(|range| {
+                            buffer.anchor_before(range.start)..buffer.anchor_after(range.end)
+                        })
+                        .collect()
+                })
+                .collect();
+            for (transformed_range, (source, range)) in snippet
+                .transformed_ranges
+                .iter_mut()
+                .zip(transformed_ranges)
+            {
+                transformed_range.source =
+                    buffer.anchor_before(source.start)..buffer.anchor_after(source.end);
+                transformed_range.range =
+                    buffer.anchor_after(range.start)..buffer.anchor_before(range.end);
+            }
+        }
+
+        let current_selections = self
+            .selections
+            .all::<MultiBufferOffset>(&self.display_snapshot(cx));
+        if current_selections
+            .iter()
+            .map(|selection| selection.range())
+            .ne(selections.iter().map(|selection| selection.range()))
+        {
+            self.change_selections(
+                SelectionEffects::no_scroll()
+                    .completions(false)
+                    .nav_history(false),
+                window,
+                cx,
+                |s| s.select(selections),
+            );
+        }
+    }
+}
+
+/// Maps an offset through sorted, non-overlapping edits, given as their old range and new length.
+///
+/// Offsets at the edges of an edit stay outside of its new text: a left bias keeps them before
+/// it, and a right bias after it. Offsets inside an edit move to the end of its new text when
+/// biased left, and to its start otherwise.
+fn map_offset(offset: usize, edits: &[(Range<usize>, usize)], bias: Bias) -> usize {
+    let mut delta = 0;
+    for (range, new_len) in edits {
+        let new_start = range.start.saturating_add_signed(delta);
+        if offset < range.start || (offset == range.start && bias == Bias::Left) {
+            break;
+        } else if offset >= range.end {
+            delta += *new_len as isize - range.len() as isize;
+        } else {
+            return match bias {
+                Bias::Left => new_start + new_len,
+                Bias::Right => new_start,
+            };
+        }
+    }
+    offset.saturating_add_signed(delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_offset() {
+        // Replaces 2..4 with 3 characters, and inserts 2 characters at 6.
+        let edits = [(2..4, 3), (6..6, 2)];
+        assert_eq!(map_offset(1, &edits, Bias::Left), 1);
+        assert_eq!(map_offset(2, &edits, Bias::Left), 2);
+        assert_eq!(map_offset(2, &edits, Bias::Right), 2);
+        assert_eq!(map_offset(3, &edits, Bias::Left), 5);
+        assert_eq!(map_offset(3, &edits, Bias::Right), 2);
+        assert_eq!(map_offset(4, &edits, Bias::Left), 5);
+        assert_eq!(map_offset(6, &edits, Bias::Left), 7);
+        assert_eq!(map_offset(6, &edits, Bias::Right), 9);
+        assert_eq!(map_offset(8, &edits, Bias::Right), 11);
+    }
+}
@@ -179,6 +179,45 @@ pub trait Extension: Send + Sync + 'static {
         locator_name: String,
         config: SpawnInTerminal,
     ) -> Result<DebugRequest>;
+
+    async fn format(
+        &self,
+        formatter_id: Arc<str>,
+        request: FormatRequest,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<FormatOutput>;
+
+    async fn lint(
+        &self,
+        linter_id: Arc<str>,
+        request: LintRequest,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<LintDiagnostic>>;
+
+    async fn llm_provider_models(&self, provider_id: Arc<str>) -> Result<Vec<LlmModelInfo>>;
+
+    async fn llm_provider_is_authenticated(&self, provider_id: Arc<str>) -> Result<bool>;
+
+    async fn llm_count_tokens(
+        &self,
+        provider_id: Arc<str>,
+        model_id: Arc<str>,
+        request: LlmCompletionRequest,
+    ) -> Result<u64>;
+
+    async fn llm_stream_completion_start(
+        &self,
+        provider_id: Arc<str>,
+        model_id: Arc<str>,
+        request: LlmCompletionRequest,
+    ) -> Result<String>;
+
+    async fn llm_stream_completion_next(
+        &self,
+        stream_id: String,
+    ) -> Result<Option<LlmCompletionEvent>>;
+
+    async fn llm_stream_completion_close(&self, stream_id: String) -> Result<()>;
 }
 
 pub fn parse_wasm_extension_version(extension_id: &str, wasm_bytes: &[u8]) -> Result<Version> {
@@ -33,6 +33,8 @@ pub struct ExtensionHostProxy {
     context_server_proxy: RwLock<Option<Arc<dyn ExtensionContextServerProxy>>>,
     debug_adapter_provider_proxy: RwLock<Option<Arc<dyn ExtensionDebugAdapterProviderProxy>>>,
     language_model_provider_proxy: RwLock<Option<Arc<dyn ExtensionLanguageModelProviderProxy>>>,
+    formatter_proxy: RwLock<Option<Arc<dyn ExtensionFormatterProxy>>>,
+    linter_proxy: RwLock<Option<Arc<dyn ExtensionLinterProxy>>>,
 }
 
 impl ExtensionHostProxy {
@@ -59,6 +61,8 @@ impl ExtensionHostProxy {
             context_server_proxy: RwLock::default(),
             debug_adapter_provider_proxy: RwLock::default(),
             language_model_provider_proxy: RwLock::default(),
+            formatter_proxy: RwLock::default(),
+            linter_proxy: RwLock::default(),
         }
     }
 
@@ -104,6 +108,14 @@ impl ExtensionHostProxy {
             .write()
             .replace(Arc::new(proxy));
     }
+
+    pub fn register_formatter_proxy(&self, proxy: impl ExtensionFormatterProxy) {
+        self.formatter_proxy.write().replace(Arc::new(proxy));
+    }
+
+    pub fn register_linter_proxy(&self, proxy: impl ExtensionLinterProxy) {
+        self.linter_proxy.write().replace(Arc::new(proxy));
+    }
 }
 
 pub trait ExtensionThemeProxy: Send + Sync + 'static {
@@ -496,3 +508,75 @@ impl ExtensionLanguageModelProviderProxy for ExtensionHostProxy {
         proxy.unregister_language_model_provider(provider_id, cx)
     }
 }
+
+pub trait ExtensionFormatterProxy: Send + Sync + 'static {
+    fn register_formatter(
+        &self,
+        extension: Arc<dyn Extension>,
+        formatter_id: Arc<str>,
+        languages: Vec<LanguageName>,
+        cx: &mut App,
+    );
+
+    fn unregister_formatter(&self, formatter_id: Arc<str>, cx: &mut App);
+}
+
+impl ExtensionFormatterProxy for ExtensionHostProxy {
+    fn register_formatter(
+        &self,
+        extension: Arc<dyn Extension>,
+        formatter_id: Arc<str>,
+        languages: Vec<LanguageName>,
+        cx: &mut App,
+    ) {
+        let Some(proxy) = self.formatter_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.register_formatter(extension, formatter_id, languages, cx)
+    }
+
+    fn unregister_formatter(&self, formatter_id: Arc<str>, cx: &mut App) {
+        let Some(proxy) = self.formatter_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.unregister_formatter(formatter_id, cx)
+    }
+}
+
+pub trait ExtensionLinterProxy: Send + Sync + 'static {
+    fn register_linter(
+        &self,
+        extension: Arc<dyn Extension>,
+        linter_id: Arc<str>,
+        languages: Vec<LanguageName>,
+        cx: &mut App,
+    );
+
+    fn unregister_linter(&self, linter_id: Arc<str>, cx: &mut App);
+}
+
+impl ExtensionLinterProxy for ExtensionHostProxy {
+    fn register_linter(
+        &self,
+        extension: Arc<dyn Extension>,
+        linter_id: Arc<str>,
+        languages: Vec<LanguageName>,
+        cx: &mut App,
+    ) {
+        let Some(proxy) = self.linter_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.register_linter(extension, linter_id, languages, cx)
+    }
+
+    fn unregister_linter(&self, linter_id: Arc<str>, cx: &mut App) {
+        let Some(proxy) = self.linter_proxy.read().clone() else {
+            return;
+        };
+
+        proxy.unregister_linter(linter_id, cx)
+    }
+}
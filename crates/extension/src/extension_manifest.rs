@@ -119,6 +119,10 @@ pub struct ExtensionManifest {
     pub debug_locators: BTreeMap<Arc<str>, DebugLocatorManifestEntry>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub language_model_providers: BTreeMap<Arc<str>, LanguageModelProviderManifestEntry>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub formatters: BTreeMap<Arc<str>, FormatterManifestEntry>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub linters: BTreeMap<Arc<str>, LinterManifestEntry>,
 }
 
 impl ExtensionManifest {
@@ -366,6 +370,20 @@ pub struct LanguageModelProviderManifestEntry {
     pub icon: Option<String>,
 }
 
+/// Manifest entry for a formatter.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct FormatterManifestEntry {
+    /// The list of languages this formatter can format.
+    pub languages: Vec<LanguageName>,
+}
+
+/// Manifest entry for a linter.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct LinterManifestEntry {
+    /// The list of languages this linter should run on.
+    pub languages: Vec<LanguageName>,
+}
+
 impl ExtensionManifest {
     pub async fn load(fs: Arc<dyn Fs>, extension_dir: &Path) -> Result<Self> {
         let extension_name = extension_dir
@@ -437,6 +455,8 @@ fn manifest_from_old_manifest(
         debug_adapters: Default::default(),
         debug_locators: Default::default(),
         language_model_providers: Default::default(),
+        formatters: Default::default(),
+        linters: Default::default(),
     }
 }
 
@@ -471,6 +491,8 @@ mod tests {
             debug_adapters: Default::default(),
             debug_locators: Default::default(),
             language_model_providers: BTreeMap::default(),
+            formatters: BTreeMap::default(),
+            linters: BTreeMap::default(),
         }
     }
 
@@ -1,5 +1,8 @@
 mod context_server;
 mod dap;
+mod formatter;
+mod linter;
+mod llm_provider;
 mod lsp;
 mod slash_command;
 
@@ -9,6 +12,9 @@ use util::redact::should_redact;
 
 pub use context_server::*;
 pub use dap::*;
+pub use formatter::*;
+pub use linter::*;
+pub use llm_provider::*;
 pub use lsp::*;
 pub use slash_command::*;
 
@@ -0,0 +1,27 @@
+use crate::Command;
+
+/// A request to format a buffer.
+#[derive(Debug, Clone)]
+pub struct FormatRequest {
+    /// The name of the buffer's language.
+    pub language_name: String,
+    /// The absolute path of the buffer, if it is backed by a file.
+    pub path: Option<String>,
+    /// The text of the buffer.
+    pub text: String,
+    /// The number of columns a tab stop spans.
+    pub tab_size: u32,
+    /// Whether to indent with tabs.
+    pub hard_tabs: bool,
+}
+
+/// The result of formatting a buffer.
+#[derive(Debug)]
+pub enum FormatOutput {
+    /// The buffer is already formatted.
+    Unchanged,
+    /// The formatted text of the buffer.
+    Text(String),
+    /// A command that reads the buffer on stdin and writes the formatted text to stdout.
+    Command(Command),
+}
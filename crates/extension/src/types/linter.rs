@@ -0,0 +1,43 @@
+/// A request to lint a buffer.
+#[derive(Debug, Clone)]
+pub struct LintRequest {
+    /// The name of the buffer's language.
+    pub language_name: String,
+    /// The absolute path of the buffer.
+    pub path: String,
+    /// The text of the buffer.
+    pub text: String,
+}
+
+/// A position in a buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LintPosition {
+    /// The zero-based line number.
+    pub line: u32,
+    /// The zero-based column, in UTF-16 code units.
+    pub column: u32,
+}
+
+/// The severity of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+/// A diagnostic reported by a linter.
+#[derive(Debug, Clone)]
+pub struct LintDiagnostic {
+    /// The start of the range the diagnostic applies to (inclusive).
+    pub start: LintPosition,
+    /// The end of the range the diagnostic applies to (exclusive).
+    pub end: LintPosition,
+    /// The severity of the diagnostic.
+    pub severity: LintSeverity,
+    /// The diagnostic message.
+    pub message: String,
+    /// The code of the rule that produced the diagnostic, if any.
+    pub code: Option<String>,
+}
@@ -0,0 +1,199 @@
+/// Information about a language model offered by a provider.
+#[derive(Debug, Clone)]
+pub struct LlmModelInfo {
+    /// The ID of the model, unique within its provider.
+    pub id: String,
+    /// The display name of the model.
+    pub name: String,
+    /// The maximum number of tokens in the context window.
+    pub max_token_count: u64,
+    /// The maximum number of tokens the model can produce in a single response.
+    pub max_output_tokens: Option<u64>,
+    /// The capabilities of the model.
+    pub capabilities: LlmModelCapabilities,
+    /// Whether this model should be used as the provider's default model.
+    pub is_default: bool,
+    /// Whether this model should be used as the provider's default fast model.
+    pub is_default_fast: bool,
+}
+
+/// The capabilities of a language model.
+#[derive(Debug, Clone, Copy)]
+pub struct LlmModelCapabilities {
+    /// Whether the model accepts images as input.
+    pub supports_images: bool,
+    /// Whether the model supports tool calls.
+    pub supports_tools: bool,
+    /// Whether the model supports the `auto` tool choice.
+    pub supports_tool_choice_auto: bool,
+    /// Whether the model supports the `any` tool choice.
+    pub supports_tool_choice_any: bool,
+    /// Whether the model supports the `none` tool choice.
+    pub supports_tool_choice_none: bool,
+    /// Whether the model supports extended thinking.
+    pub supports_thinking: bool,
+    /// The format the model expects tool input schemas in.
+    pub tool_input_format: LlmToolInputFormat,
+}
+
+/// The format of a tool's input schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmToolInputFormat {
+    /// A JSON schema.
+    JsonSchema,
+    /// A subset of an OpenAPI 3.0 schema object.
+    JsonSchemaSubset,
+}
+
+/// The role of a message in a conversation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmMessageRole {
+    User,
+    Assistant,
+    System,
+}
+
+/// A message in a completion request.
+#[derive(Debug, Clone)]
+pub struct LlmRequestMessage {
+    /// The role of the message author.
+    pub role: LlmMessageRole,
+    /// The contents of the message.
+    pub content: Vec<LlmMessageContent>,
+    /// Whether the message should be cached by the provider, if supported.
+    pub cache: bool,
+}
+
+/// A piece of content in a message.
+#[derive(Debug, Clone)]
+pub enum LlmMessageContent {
+    Text(String),
+    Image(LlmImage),
+    ToolUse(LlmToolUse),
+    ToolResult(LlmToolResult),
+    Thinking(LlmThinking),
+    RedactedThinking(String),
+}
+
+/// An image.
+#[derive(Debug, Clone)]
+pub struct LlmImage {
+    /// The base64-encoded PNG image.
+    pub source: String,
+    /// The width of the image in pixels, if known.
+    pub width: Option<u32>,
+    /// The height of the image in pixels, if known.
+    pub height: Option<u32>,
+}
+
+/// A tool call.
+#[derive(Debug, Clone)]
+pub struct LlmToolUse {
+    /// The ID of the tool call.
+    pub id: String,
+    /// The name of the tool.
+    pub name: String,
+    /// The tool input, as a JSON string.
+    pub input: String,
+    /// Whether the input has been fully streamed.
+    pub is_input_complete: bool,
+    /// The thought signature sent along with the tool call, if any.
+    pub thought_signature: Option<String>,
+}
+
+/// The result of a tool call.
+#[derive(Debug, Clone)]
+pub struct LlmToolResult {
+    /// The ID of the tool call this is a result for.
+    pub tool_use_id: String,
+    /// The name of the tool.
+    pub tool_name: String,
+    /// Whether the tool call failed.
+    pub is_error: bool,
+    /// The output of the tool.
+    pub content: LlmToolResultContent,
+}
+
+/// The output of a tool.
+#[derive(Debug, Clone)]
+pub enum LlmToolResultContent {
+    Text(String),
+    Image(LlmImage),
+}
+
+/// Thinking produced by a model.
+#[derive(Debug, Clone)]
+pub struct LlmThinking {
+    /// The thinking text.
+    pub text: String,
+    /// The signature of the thinking block, if any.
+    pub signature: Option<String>,
+}
+
+/// A tool the model may call.
+#[derive(Debug, Clone)]
+pub struct LlmToolDefinition {
+    /// The name of the tool.
+    pub name: String,
+    /// The description of the tool.
+    pub description: String,
+    /// The JSON schema of the tool input, as a JSON string.
+    pub input_schema: String,
+}
+
+/// How the model should choose tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmToolChoice {
+    Auto,
+    Any,
+    None,
+}
+
+/// A request for a completion.
+#[derive(Debug, Clone)]
+pub struct LlmCompletionRequest {
+    /// The messages in the conversation.
+    pub messages: Vec<LlmRequestMessage>,
+    /// The tools available to the model.
+    pub tools: Vec<LlmToolDefinition>,
+    /// How the model should choose tools.
+    pub tool_choice: Option<LlmToolChoice>,
+    /// Sequences that stop generation when produced.
+    pub stop_sequences: Vec<String>,
+    /// The sampling temperature.
+    pub temperature: Option<f32>,
+    /// Whether the model is allowed to think before responding.
+    pub thinking_allowed: bool,
+    /// The maximum number of tokens to produce.
+    pub max_tokens: Option<u64>,
+}
+
+/// An event in a completion stream.
+#[derive(Debug, Clone)]
+pub enum LlmCompletionEvent {
+    Started,
+    Text(String),
+    Thinking(LlmThinking),
+    RedactedThinking(String),
+    ToolUse(LlmToolUse),
+    Stop(LlmStopReason),
+    Usage(LlmTokenUsage),
+}
+
+/// The reason a completion stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmStopReason {
+    EndTurn,
+    MaxTokens,
+    ToolUse,
+    Refusal,
+}
+
+/// Token usage for a completion.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LlmTokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
@@ -25,10 +25,12 @@ pub use wit::{
         LaunchRequest, StartDebuggingRequestArguments, StartDebuggingRequestArgumentsRequest,
         TaskTemplate, TcpArguments, TcpArgumentsTemplate, resolve_tcp_template,
     },
+    zed::extension::formatter::{FormatOutput, FormatRequest},
     zed::extension::github::{
         GithubRelease, GithubReleaseAsset, GithubReleaseOptions, github_release_by_tag_name,
         latest_github_release,
     },
+    zed::extension::linter::{Diagnostic, DiagnosticSeverity, LintRequest, Position},
     zed::extension::nodejs::{
         node_binary_path, npm_install_package, npm_package_installed_version,
         npm_package_latest_version,
@@ -54,6 +56,16 @@ pub mod lsp {
     };
 }
 
+/// Constructs for implementing language model providers.
+pub mod llm {
+    pub use crate::wit::zed::extension::llm_provider::{
+        CompletionEvent, CompletionRequest, ImageData, MessageContent, MessageRole,
+        ModelCapabilities, ModelInfo, RequestMessage, StopReason, ThinkingContent, TokenUsage,
+        ToolChoice, ToolDefinition, ToolInputFormat, ToolResult, ToolResultContent, ToolUse,
+        delete_credential, get_credential, store_credential,
+    };
+}
+
 /// A result returned from a Zed extension.
 pub type Result<T, E = String> = core::result::Result<T, E>;
 
@@ -281,6 +293,95 @@ pub trait Extension: Send + Sync {
     ) -> Result<DebugRequest, String> {
         Err("`run_dap_locator` not implemented".to_string())
     }
+
+    /// Formats a buffer with the specified formatter.
+    ///
+    /// The formatter can either return the formatted text directly, or a command
+    /// that Zed will run to format the buffer.
+    fn format(
+        &mut self,
+        _formatter_id: &str,
+        _request: FormatRequest,
+        _worktree: &Worktree,
+    ) -> Result<FormatOutput> {
+        Err("`format` not implemented".to_string())
+    }
+
+    /// Returns the diagnostics the specified linter reports for a buffer.
+    fn lint(
+        &mut self,
+        _linter_id: &str,
+        _request: LintRequest,
+        _worktree: &Worktree,
+    ) -> Result<Vec<Diagnostic>> {
+        Err("`lint` not implemented".to_string())
+    }
+
+    /// Returns the models offered by the specified language model provider.
+    fn llm_provider_models(&mut self, _provider_id: &str) -> Result<Vec<llm::ModelInfo>> {
+        Ok(Vec::new())
+    }
+
+    /// Returns whether the specified language model provider has the credentials it needs to
+    /// make requests.
+    ///
+    /// Credentials are typically read with [`llm::get_credential`].
+    fn llm_provider_is_authenticated(&mut self, _provider_id: &str) -> bool {
+        false
+    }
+
+    /// Returns the number of tokens the given request would use with the specified model.
+    ///
+    /// The default implementation estimates four characters per token.
+    fn llm_count_tokens(
+        &mut self,
+        _provider_id: &str,
+        _model_id: &str,
+        request: &llm::CompletionRequest,
+    ) -> Result<u64> {
+        let characters = request
+            .messages
+            .iter()
+            .flat_map(|message| &message.content)
+            .map(|content| match content {
+                llm::MessageContent::Text(text) | llm::MessageContent::RedactedThinking(text) => {
+                    text.len()
+                }
+                llm::MessageContent::Thinking(thinking) => thinking.text.len(),
+                llm::MessageContent::ToolUse(tool_use) => tool_use.input.len(),
+                llm::MessageContent::ToolResult(tool_result) => match &tool_result.content {
+                    llm::ToolResultContent::Text(text) => text.len(),
+                    llm::ToolResultContent::Image(_) => 0,
+                },
+                llm::MessageContent::Image(_) => 0,
+            })
+            .sum::<usize>();
+        Ok(characters.div_ceil(4) as u64)
+    }
+
+    /// Starts streaming a completion from the specified model and returns an ID for the stream.
+    ///
+    /// Zed will call [`Extension::llm_stream_completion_next`] with the returned ID until the
+    /// stream ends, and then [`Extension::llm_stream_completion_close`].
+    fn llm_stream_completion_start(
+        &mut self,
+        _provider_id: &str,
+        _model_id: &str,
+        _request: &llm::CompletionRequest,
+    ) -> Result<String> {
+        Err("`llm_stream_completion_start` not implemented".to_string())
+    }
+
+    /// Returns the next event from the specified completion stream, or `None` if it has ended.
+    fn llm_stream_completion_next(
+        &mut self,
+        _stream_id: &str,
+    ) -> Result<Option<llm::CompletionEvent>> {
+        Err("`llm_stream_completion_next` not implemented".to_string())
+    }
+
+    /// Releases the resources held by the specified completion stream.
+    fn llm_stream_completion_close(&mut self, _stream_id: &str) {}
 }
 
 /// Registers the provided type as a Zed extension.
@@ -559,6 +660,56 @@ impl wit::Guest for Component {
     ) -> Result<DebugRequest, String> {
         extension().run_dap_locator(locator_name, build_task)
     }
+
+    fn format(
+        formatter_id: String,
+        request: FormatRequest,
+        worktree: &Worktree,
+    ) -> Result<FormatOutput, String> {
+        extension().format(&formatter_id, request, worktree)
+    }
+
+    fn lint(
+        linter_id: String,
+        request: LintRequest,
+        worktree: &Worktree,
+    ) -> Result<Vec<Diagnostic>, String> {
+        extension().lint(&linter_id, request, worktree)
+    }
+
+    fn llm_provider_models(provider_id: String) -> Result<Vec<llm::ModelInfo>, String> {
+        extension().llm_provider_models(&provider_id)
+    }
+
+    fn llm_provider_is_authenticated(provider_id: String) -> bool {
+        extension().llm_provider_is_authenticated(&provider_id)
+    }
+
+    fn llm_count_tokens(
+        provider_id: String,
+        model_id: String,
+        request: llm::CompletionRequest,
+    ) -> Result<u64, String> {
+        extension().llm_count_tokens(&provider_id, &model_id, &request)
+    }
+
+    fn llm_stream_completion_start(
+        provider_id: String,
+        model_id: String,
+        request: llm::CompletionRequest,
+    ) -> Result<String, String> {
+        extension().llm_stream_completion_start(&provider_id, &model_id, &request)
+    }
+
+    fn llm_stream_completion_next(
+        stream_id: String,
+    ) -> Result<Option<llm::CompletionEvent>, String> {
+        extension().llm_stream_completion_next(&stream_id)
+    }
+
+    fn llm_stream_completion_close(stream_id: String) {
+        extension().llm_stream_completion_close(&stream_id)
+    }
 }
 
 /// The ID of a language server.
@@ -1,3 +1,5 @@
+mod test_runner;
+
 use std::collections::HashMap;
 use std::env;
 use std::fs;
@@ -6,7 +8,7 @@ use std::sync::Arc;
 
 use ::fs::{CopyOptions, Fs, RealFs, copy_recursive};
 use anyhow::{Context as _, Result, anyhow, bail};
-use clap::Parser;
+use clap::{CommandFactory as _, Parser};
 use extension::extension_builder::{CompileExtensionOptions, ExtensionBuilder};
 use extension::{ExtensionManifest, ExtensionSnippets};
 use language::LanguageConfig;
@@ -19,8 +21,26 @@ use tokio::process::Command;
 use tree_sitter::{Language, Query, WasmStore};
 
 #[derive(Parser, Debug)]
-#[command(name = "zed-extension")]
+#[command(name = "zed-extension", args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// The extension is packaged when no subcommand is given, to keep
+    /// supporting the flags being passed on their own.
+    #[command(flatten)]
+    package: Option<PackageArgs>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Package the extension for publishing
+    Package(PackageArgs),
+    /// Compile the extension and check its grammars, queries, fixtures and WASM exports
+    Test(test_runner::TestArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct PackageArgs {
     /// The path to the extension directory
     #[arg(long)]
     source_dir: PathBuf,
@@ -32,27 +52,43 @@ struct Args {
     scratch_dir: PathBuf,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     env_logger::init();
 
     let args = Args::parse();
+    match args.command.or(args.package.map(Command::Package)) {
+        Some(Command::Package(args)) => tokio::runtime::Runtime::new()?.block_on(
+            package_extension(args.source_dir, args.output_dir, args.scratch_dir),
+        ),
+        Some(Command::Test(args)) => test_runner::run(args),
+        None => Args::command()
+            .error(
+                clap::error::ErrorKind::MissingSubcommand,
+                "pass a subcommand, or the flags to package the extension",
+            )
+            .exit(),
+    }
+}
+
+async fn package_extension(
+    source_dir: PathBuf,
+    output_dir: PathBuf,
+    scratch_dir: PathBuf,
+) -> Result<()> {
     let fs = Arc::new(RealFs::new(None, gpui_platform::background_executor()));
     let engine = wasmtime::Engine::default();
     let mut wasm_store = WasmStore::new(&engine)?;
 
-    let extension_path = args
-        .source_dir
+    let extension_path = source_dir
         .canonicalize()
         .context("failed to canonicalize source_dir")?;
-    let scratch_dir = args
-        .scratch_dir
+    let scratch_dir = scratch_dir
         .canonicalize()
         .context("failed to canonicalize scratch_dir")?;
-    let output_dir = if args.output_dir.is_relative() {
-        env::current_dir()?.join(&args.output_dir)
+    let output_dir = if output_dir.is_relative() {
+        env::current_dir()?.join(&output_dir)
     } else {
-        args.output_dir
+        output_dir
     };
 
     log::info!("loading extension manifest");
@@ -60,13 +96,7 @@ async fn main() -> Result<()> {
 
     log::info!("compiling extension");
 
-    let user_agent = format!(
-        "Zed Extension CLI/{} ({}; {})",
-        env!("CARGO_PKG_VERSION"),
-        std::env::consts::OS,
-        std::env::consts::ARCH
-    );
-    let http_client = Arc::new(ReqwestClient::user_agent(&user_agent)?);
+    let http_client = Arc::new(ReqwestClient::user_agent(&user_agent())?);
 
     let builder = ExtensionBuilder::new(http_client, scratch_dir);
     builder
@@ -127,6 +157,15 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+fn user_agent() -> String {
+    format!(
+        "Zed Extension CLI/{} ({}; {})",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
+
 async fn copy_extension_resources(
     manifest: &ExtensionManifest,
     extension_path: &Path,
@@ -318,26 +357,60 @@ fn test_grammars(
     Ok(grammars)
 }
 
-fn test_languages(
+/// A language provided by an extension, along with its compiled grammar.
+struct ExtensionLanguage {
+    dir: PathBuf,
+    config: LanguageConfig,
+    grammar: Option<Language>,
+}
+
+fn load_languages(
     manifest: &ExtensionManifest,
     extension_path: &Path,
     grammars: &HashMap<String, Language>,
-) -> Result<()> {
+) -> Result<Vec<ExtensionLanguage>> {
+    let mut languages = Vec::new();
     for relative_language_dir in &manifest.languages {
-        let language_dir = extension_path.join(relative_language_dir);
-        let config_path = language_dir.join(LanguageConfig::FILE_NAME);
-        let config = LanguageConfig::load(&config_path)?;
-        let grammar = if let Some(name) = &config.grammar {
-            Some(
+        let dir = extension_path.join(relative_language_dir);
+        let config = LanguageConfig::load(dir.join(LanguageConfig::FILE_NAME))?;
+        let grammar = match &config.grammar {
+            Some(name) => Some(
                 grammars
                     .get(name.as_ref())
-                    .with_context(|| format!("grammar not found: '{name}'"))?,
-            )
-        } else {
-            None
+                    .with_context(|| format!("grammar not found: '{name}'"))?
+                    .clone(),
+            ),
+            None => None,
         };
+        languages.push(ExtensionLanguage {
+            dir,
+            config,
+            grammar,
+        });
+    }
+    Ok(languages)
+}
+
+/// Compiles the query at the given path against the language's grammar.
+fn load_query(language: &ExtensionLanguage, query_path: &Path) -> Result<Query> {
+    let grammar = language.grammar.as_ref().with_context(|| {
+        format!(
+            "language {} provides query {} but no grammar",
+            language.config.name,
+            query_path.display()
+        )
+    })?;
+    let query_source = fs::read_to_string(query_path)?;
+    Query::new(grammar, &query_source).map_err(|error| anyhow!("{error}"))
+}
 
-        let query_entries = fs::read_dir(&language_dir)?;
+fn test_languages(
+    manifest: &ExtensionManifest,
+    extension_path: &Path,
+    grammars: &HashMap<String, Language>,
+) -> Result<()> {
+    for language in load_languages(manifest, extension_path, grammars)? {
+        let query_entries = fs::read_dir(&language.dir)?;
         for entry in query_entries {
             let entry = entry?;
             let file_path = entry.path();
@@ -370,22 +443,13 @@ fn test_languages(
                             })?;
                 }
                 _ if file_name.ends_with(".scm") => {
-                    let grammar = grammar.with_context(|| {
-                        format! {
-                            "language {} provides query {} but no grammar",
-                            config.name,
-                            file_path.display()
-                        }
-                    })?;
-
-                    let query_source = fs::read_to_string(&file_path)?;
-                    let _query = Query::new(grammar, &query_source)?;
+                    let _query = load_query(&language, &file_path)?;
                 }
                 _ => {}
             }
         }
 
-        log::info!("loaded language {}", config.name);
+        log::info!("loaded language {}", language.config.name);
     }
 
     Ok(())
@@ -0,0 +1,626 @@
+use std::cmp::Reverse;
+use std::fmt::Write as _;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+
+use ::fs::RealFs;
+use anyhow::{Context as _, Result, bail};
+use async_trait::async_trait;
+use extension::extension_builder::{CompileExtensionOptions, ExtensionBuilder};
+use extension::{Extension as _, ExtensionHostProxy, ExtensionManifest, WorktreeDelegate};
+use extension_host::wasm_host::WasmHost;
+use gpui::AsyncApp;
+use node_runtime::NodeRuntime;
+use reqwest_client::ReqwestClient;
+use streaming_iterator::StreamingIterator as _;
+use tree_sitter::{Language, Parser, Query, QueryCursor, Tree, WasmStore};
+use util::rel_path::RelPath;
+
+use crate::{ExtensionLanguage, load_languages, load_query, test_grammars, user_agent};
+
+/// The directory, relative to the extension root, containing the fixture files.
+///
+/// It is also used as the root of the worktree passed to the extension's WASM exports.
+const FIXTURES_DIR: &str = "tests";
+const SNAPSHOT_EXTENSION: &str = "snap";
+
+#[derive(clap::Args, Debug)]
+pub struct TestArgs {
+    /// The path to the extension directory
+    #[arg(long)]
+    source_dir: PathBuf,
+    /// The path to a directory where build dependencies are downloaded
+    #[arg(long)]
+    scratch_dir: PathBuf,
+    /// Overwrite the fixture snapshots instead of comparing against them
+    #[arg(long)]
+    update_snapshots: bool,
+}
+
+pub fn run(args: TestArgs) -> Result<()> {
+    let extension_path = args
+        .source_dir
+        .canonicalize()
+        .context("failed to canonicalize source_dir")?;
+    let scratch_dir = args
+        .scratch_dir
+        .canonicalize()
+        .context("failed to canonicalize scratch_dir")?;
+    let http_client = Arc::new(ReqwestClient::user_agent(&user_agent())?);
+
+    gpui_platform::headless()
+        .with_http_client(http_client.clone())
+        .run(move |cx| {
+            settings::init(cx);
+            release_channel::init(
+                semver::Version::parse(env!("CARGO_PKG_VERSION")).expect("invalid version"),
+                cx,
+            );
+            gpui_tokio::init(cx);
+
+            cx.spawn(async move |cx| {
+                let exit_code = match run_tests(
+                    extension_path,
+                    scratch_dir,
+                    args.update_snapshots,
+                    http_client,
+                    cx,
+                )
+                .await
+                {
+                    Ok(report) => {
+                        print!("{}", report.summary());
+                        report.exit_code()
+                    }
+                    Err(error) => {
+                        eprintln!("error: {error:?}");
+                        1
+                    }
+                };
+
+                cx.update(|cx| cx.quit());
+                process::exit(exit_code);
+            })
+            .detach();
+        });
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct TestReport {
+    passed: usize,
+    failures: Vec<(String, anyhow::Error)>,
+}
+
+impl TestReport {
+    fn record(&mut self, name: String, result: Result<()>) {
+        match result {
+            Ok(()) => {
+                println!("test {name} ... ok");
+                self.passed += 1;
+            }
+            Err(error) => {
+                println!("test {name} ... FAILED");
+                self.failures.push((name, error));
+            }
+        }
+    }
+
+    fn exit_code(&self) -> i32 {
+        if self.failures.is_empty() { 0 } else { 1 }
+    }
+
+    fn summary(&self) -> String {
+        let mut summary = String::new();
+        if !self.failures.is_empty() {
+            summary.push_str("\nfailures:\n");
+            for (name, error) in &self.failures {
+                writeln!(summary, "\n---- {name} ----\n{error:?}").ok();
+            }
+        }
+
+        writeln!(
+            summary,
+            "\ntest result: {}. {} passed; {} failed",
+            if self.failures.is_empty() {
+                "ok"
+            } else {
+                "FAILED"
+            },
+            self.passed,
+            self.failures.len()
+        )
+        .ok();
+        summary
+    }
+}
+
+async fn run_tests(
+    extension_path: PathBuf,
+    scratch_dir: PathBuf,
+    update_snapshots: bool,
+    http_client: Arc<ReqwestClient>,
+    cx: &mut AsyncApp,
+) -> Result<TestReport> {
+    let fs = Arc::new(RealFs::new(None, cx.background_executor().clone()));
+
+    log::info!("loading extension manifest");
+    let mut manifest = ExtensionManifest::load(fs.clone(), &extension_path).await?;
+
+    log::info!("compiling extension");
+    ExtensionBuilder::new(http_client.clone(), scratch_dir.clone())
+        .compile_extension(
+            &extension_path,
+            &mut manifest,
+            CompileExtensionOptions { release: false },
+            fs.clone(),
+        )
+        .await
+        .context("failed to compile extension")?;
+
+    let engine = tree_sitter::wasmtime::Engine::default();
+    let mut wasm_store = WasmStore::new(&engine)?;
+    let grammars = test_grammars(&manifest, &extension_path, &mut wasm_store)?;
+
+    let languages = load_languages(&manifest, &extension_path, &grammars)?;
+
+    let mut report = TestReport::default();
+    test_queries(&languages, &mut report)?;
+
+    let fixtures_dir = extension_path.join(FIXTURES_DIR);
+    if fixtures_dir.is_dir() {
+        let mut parser = Parser::new();
+        parser.set_wasm_store(WasmStore::new(&engine)?)?;
+        test_fixtures(
+            &languages,
+            &fixtures_dir,
+            &mut parser,
+            update_snapshots,
+            &mut report,
+        )?;
+    }
+
+    if manifest.lib.kind.is_some() {
+        let worktree_root = if fixtures_dir.is_dir() {
+            fixtures_dir
+        } else {
+            extension_path.clone()
+        };
+        let wasm_bytes = fs::read(extension_path.join("extension.wasm"))
+            .context("failed to read extension.wasm")?;
+        let work_dir = scratch_dir.join("work");
+        fs::create_dir_all(&work_dir)?;
+        let wasm_host = cx.update(|cx| {
+            WasmHost::new(
+                fs,
+                http_client,
+                NodeRuntime::unavailable(),
+                Arc::new(ExtensionHostProxy::new()),
+                work_dir,
+                cx,
+            )
+        });
+        test_wasm_exports(
+            &wasm_host,
+            Arc::new(manifest),
+            wasm_bytes,
+            worktree_root,
+            &mut report,
+            cx,
+        )
+        .await?;
+    }
+
+    Ok(report)
+}
+
+/// Checks that every query of every language compiles against the language's grammar.
+fn test_queries(languages: &[ExtensionLanguage], report: &mut TestReport) -> Result<()> {
+    for language in languages {
+        let mut query_paths = fs::read_dir(&language.dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        query_paths.retain(|path| path.extension().is_some_and(|extension| extension == "scm"));
+        query_paths.sort();
+
+        for query_path in query_paths {
+            let file_name = query_path
+                .file_name()
+                .context("invalid query path")?
+                .to_string_lossy();
+            let name = format!("{}::{file_name}", language.config.name);
+            let result = load_query(language, &query_path).map(|_| ());
+            report.record(name, result);
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses every fixture file with the grammar of the language that matches it,
+/// and compares the highlights and outline of the fixture against its snapshots.
+fn test_fixtures(
+    languages: &[ExtensionLanguage],
+    fixtures_dir: &Path,
+    parser: &mut Parser,
+    update_snapshots: bool,
+    report: &mut TestReport,
+) -> Result<()> {
+    for fixture_path in fixture_paths(fixtures_dir)? {
+        let file_name = fixture_path
+            .file_name()
+            .context("invalid fixture path")?
+            .to_string_lossy();
+        let Some(language) =
+            languages.iter().find(|language| {
+                language.config.matcher.path_suffixes.iter().any(|suffix| {
+                    file_name == *suffix || file_name.ends_with(&format!(".{suffix}"))
+                })
+            })
+        else {
+            log::warn!("no language found for fixture {}", fixture_path.display());
+            continue;
+        };
+        let Some(grammar) = &language.grammar else {
+            continue;
+        };
+
+        let fixture_name = fixture_path
+            .strip_prefix(fixtures_dir)?
+            .to_string_lossy()
+            .into_owned();
+        let source = fs::read_to_string(&fixture_path)?;
+        parser.set_language(grammar)?;
+        let tree = parser
+            .parse(&source, None)
+            .with_context(|| format!("failed to parse fixture {fixture_name}"))?;
+
+        for (query_name, snapshot) in [
+            (
+                "highlights",
+                highlights_snapshot as fn(&Query, &Tree, &str) -> String,
+            ),
+            ("outline", outline_snapshot),
+        ] {
+            let query_path = language.dir.join(query_name).with_extension("scm");
+            if !query_path.exists() {
+                continue;
+            }
+
+            let result = load_query(language, &query_path).and_then(|query| {
+                let mut snapshot_path = fixture_path.clone().into_os_string();
+                snapshot_path.push(format!(".{query_name}.{SNAPSHOT_EXTENSION}"));
+                check_snapshot(
+                    Path::new(&snapshot_path),
+                    &snapshot(&query, &tree, &source),
+                    update_snapshots,
+                )
+            });
+            report.record(format!("{fixture_name}::{query_name}"), result);
+        }
+    }
+
+    Ok(())
+}
+
+fn fixture_paths(fixtures_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut dirs = vec![fixtures_dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path
+                .extension()
+                .is_none_or(|extension| extension != SNAPSHOT_EXTENSION)
+            {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn check_snapshot(snapshot_path: &Path, actual: &str, update_snapshots: bool) -> Result<()> {
+    if update_snapshots {
+        fs::write(snapshot_path, actual)
+            .with_context(|| format!("failed to write snapshot {}", snapshot_path.display()))?;
+        return Ok(());
+    }
+
+    let Ok(expected) = fs::read_to_string(snapshot_path) else {
+        bail!(
+            "snapshot {} does not exist, run with --update-snapshots to create it",
+            snapshot_path.display()
+        );
+    };
+    if expected != actual {
+        bail!(
+            "snapshot {} does not match, run with --update-snapshots to update it\n\nexpected:\n{expected}\nactual:\n{actual}",
+            snapshot_path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Lists every capture of the highlights query, one per line.
+fn highlights_snapshot(query: &Query, tree: &Tree, source: &str) -> String {
+    let mut output = String::new();
+    let mut cursor = QueryCursor::new();
+    let mut captures = cursor.captures(query, tree.root_node(), source.as_bytes());
+    while let Some((query_match, capture_ix)) = captures.next() {
+        let capture = query_match.captures[*capture_ix];
+        let node = capture.node;
+        writeln!(
+            output,
+            "{}:{}-{}:{} @{} {:?}",
+            node.start_position().row,
+            node.start_position().column,
+            node.end_position().row,
+            node.end_position().column,
+            query.capture_names()[capture.index as usize],
+            &source[node.byte_range()],
+        )
+        .ok();
+    }
+    output
+}
+
+/// Lists the outline items, indented by their depth.
+fn outline_snapshot(query: &Query, tree: &Tree, source: &str) -> String {
+    let Some(item_capture_ix) = query.capture_index_for_name("item") else {
+        return String::new();
+    };
+    let text_capture_ixs = ["name", "context"]
+        .into_iter()
+        .filter_map(|name| query.capture_index_for_name(name))
+        .collect::<Vec<_>>();
+
+    let mut items = Vec::<(Range<usize>, String)>::new();
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), source.as_bytes());
+    while let Some(query_match) = matches.next() {
+        let Some(item) = query_match
+            .captures
+            .iter()
+            .find(|capture| capture.index == item_capture_ix)
+        else {
+            continue;
+        };
+
+        let mut text_nodes = query_match
+            .captures
+            .iter()
+            .filter(|capture| text_capture_ixs.contains(&capture.index))
+            .map(|capture| capture.node)
+            .collect::<Vec<_>>();
+        text_nodes.sort_by_key(|node| node.start_byte());
+        let text = text_nodes
+            .iter()
+            .map(|node| &source[node.byte_range()])
+            .collect::<Vec<_>>()
+            .join(" ");
+        items.push((item.node.byte_range(), text));
+    }
+    items.sort_by_key(|(range, _)| (range.start, Reverse(range.end)));
+
+    let mut output = String::new();
+    let mut ancestors = Vec::<Range<usize>>::new();
+    for (range, text) in items {
+        while ancestors
+            .last()
+            .is_some_and(|ancestor| ancestor.end < range.end || ancestor.start > range.start)
+        {
+            ancestors.pop();
+        }
+        writeln!(output, "{}{text}", "  ".repeat(ancestors.len())).ok();
+        ancestors.push(range);
+    }
+    output
+}
+
+/// Loads the extension's WASM and calls its language server exports against a fake worktree.
+async fn test_wasm_exports(
+    wasm_host: &Arc<WasmHost>,
+    manifest: Arc<ExtensionManifest>,
+    wasm_bytes: Vec<u8>,
+    worktree_root: PathBuf,
+    report: &mut TestReport,
+    cx: &mut AsyncApp,
+) -> Result<()> {
+    let extension = wasm_host
+        .load_extension(wasm_bytes, &manifest, cx)
+        .await
+        .context("failed to load extension.wasm")?;
+    let worktree: Arc<dyn WorktreeDelegate> = Arc::new(FakeWorktree {
+        root: worktree_root,
+    });
+
+    for (language_server_id, entry) in &manifest.language_servers {
+        for language_name in entry.languages() {
+            let result = extension
+                .language_server_command(
+                    language_server_id.clone(),
+                    language_name.clone(),
+                    worktree.clone(),
+                )
+                .await
+                .map(|command| {
+                    log::info!(
+                        "{language_server_id} command: {} {}",
+                        command.command.display(),
+                        command.args.join(" ")
+                    );
+                });
+            report.record(
+                format!("{language_server_id}::{language_name}::language-server-command"),
+                result,
+            );
+
+            let result = extension
+                .language_server_initialization_options(
+                    language_server_id.clone(),
+                    language_name.clone(),
+                    worktree.clone(),
+                )
+                .await
+                .and_then(|options| parse_optional_json(options.as_deref()));
+            report.record(
+                format!(
+                    "{language_server_id}::{language_name}::language-server-initialization-options"
+                ),
+                result,
+            );
+        }
+
+        let result = extension
+            .language_server_workspace_configuration(language_server_id.clone(), worktree.clone())
+            .await
+            .and_then(|configuration| parse_optional_json(configuration.as_deref()));
+        report.record(
+            format!("{language_server_id}::language-server-workspace-configuration"),
+            result,
+        );
+    }
+
+    Ok(())
+}
+
+fn parse_optional_json(json: Option<&str>) -> Result<()> {
+    if let Some(json) = json {
+        serde_json::from_str::<serde_json::Value>(json).context("invalid JSON")?;
+    }
+    Ok(())
+}
+
+/// A worktree backed by a directory on disk, with an empty environment.
+struct FakeWorktree {
+    root: PathBuf,
+}
+
+#[async_trait]
+impl WorktreeDelegate for FakeWorktree {
+    fn id(&self) -> u64 {
+        0
+    }
+
+    fn root_path(&self) -> String {
+        self.root.to_string_lossy().into_owned()
+    }
+
+    async fn read_text_file(&self, path: &RelPath) -> Result<String> {
+        fs::read_to_string(self.root.join(path.as_std_path()))
+            .with_context(|| format!("failed to read {}", path.as_unix_str()))
+    }
+
+    async fn which(&self, _binary_name: String) -> Option<String> {
+        None
+    }
+
+    async fn shell_env(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use util::test::TempTree;
+
+    use super::*;
+
+    #[test]
+    fn test_report_summary() {
+        let mut report = TestReport::default();
+        report.record("Rust::highlights.scm".into(), Ok(()));
+        assert_eq!(report.exit_code(), 0);
+        assert_eq!(report.summary(), "\ntest result: ok. 1 passed; 0 failed\n");
+
+        report.record(
+            "Rust::outline.scm".into(),
+            Err(anyhow::anyhow!("invalid node type")),
+        );
+        assert_eq!(report.exit_code(), 1);
+        let summary = report.summary();
+        assert!(summary.contains("---- Rust::outline.scm ----\ninvalid node type"));
+        assert!(summary.ends_with("test result: FAILED. 1 passed; 1 failed\n"));
+    }
+
+    #[test]
+    fn test_check_snapshot() {
+        let dir = TempTree::new(json!({ "main.rs.outline.snap": "fn main\n" }));
+        let snapshot_path = dir.path().join("main.rs.outline.snap");
+        let missing_path = dir.path().join("lib.rs.outline.snap");
+
+        check_snapshot(&snapshot_path, "fn main\n", false).unwrap();
+        let error = check_snapshot(&snapshot_path, "fn other\n", false).unwrap_err();
+        assert!(error.to_string().contains("does not match"));
+        let error = check_snapshot(&missing_path, "fn lib\n", false).unwrap_err();
+        assert!(error.to_string().contains("does not exist"));
+
+        check_snapshot(&missing_path, "fn lib\n", true).unwrap();
+        check_snapshot(&missing_path, "fn lib\n", false).unwrap();
+    }
+
+    fn parse_rust(source: &str) -> (Language, Tree) {
+        let language = Language::from(tree_sitter_rust::LANGUAGE);
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        (language, tree)
+    }
+
+    #[test]
+    fn test_highlights_snapshot() {
+        let source = "fn main() {}";
+        let (language, tree) = parse_rust(source);
+        let query = Query::new(&language, "\"fn\" @keyword\n(identifier) @variable").unwrap();
+        assert_eq!(
+            highlights_snapshot(&query, &tree, source),
+            "0:0-0:2 @keyword \"fn\"\n0:3-0:7 @variable \"main\"\n"
+        );
+    }
+
+    #[test]
+    fn test_outline_snapshot() {
+        let source = "fn main() {}\nstruct Point {\n    x: i32,\n    y: i32,\n}\n";
+        let (language, tree) = parse_rust(source);
+        let query = Query::new(
+            &language,
+            r#"
+            (function_item "fn" @context name: (identifier) @name) @item
+            (struct_item "struct" @context name: (type_identifier) @name) @item
+            (field_declaration name: (field_identifier) @name) @item
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            outline_snapshot(&query, &tree, source),
+            "fn main\nstruct Point\n  x\n  y\n"
+        );
+
+        // Queries without an `@item` capture have no outline.
+        let query = Query::new(&language, "(identifier) @name").unwrap();
+        assert_eq!(outline_snapshot(&query, &tree, source), "");
+    }
+
+    #[test]
+    fn test_fixture_paths_skip_snapshots() {
+        let dir = TempTree::new(json!({
+            "main.rs": "fn main() {}",
+            "main.rs.highlights.snap": "",
+            "nested": { "lib.rs": "" },
+        }));
+        assert_eq!(
+            fixture_paths(dir.path()).unwrap(),
+            [dir.path().join("main.rs"), dir.path().join("nested/lib.rs")]
+        );
+    }
+}
@@ -149,6 +149,8 @@ fn manifest() -> ExtensionManifest {
         debug_adapters: Default::default(),
         debug_locators: Default::default(),
         language_model_providers: BTreeMap::default(),
+        formatters: BTreeMap::default(),
+        linters: BTreeMap::default(),
     }
 }
 
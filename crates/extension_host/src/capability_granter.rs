@@ -114,6 +114,8 @@ mod tests {
             debug_adapters: Default::default(),
             debug_locators: Default::default(),
             language_model_providers: BTreeMap::default(),
+            formatters: BTreeMap::default(),
+            linters: BTreeMap::default(),
         }
     }
 
@@ -15,10 +15,10 @@ use collections::{BTreeMap, BTreeSet, HashSet, btree_map};
 pub use extension::ExtensionManifest;
 use extension::extension_builder::{CompileExtensionOptions, ExtensionBuilder};
 use extension::{
-    ExtensionContextServerProxy, ExtensionDebugAdapterProviderProxy, ExtensionEvents,
-    ExtensionGrammarProxy, ExtensionHostProxy, ExtensionLanguageProxy,
-    ExtensionLanguageServerProxy, ExtensionSlashCommandProxy, ExtensionSnippetProxy,
-    ExtensionThemeProxy,
+    Extension, ExtensionContextServerProxy, ExtensionDebugAdapterProviderProxy, ExtensionEvents,
+    ExtensionFormatterProxy, ExtensionGrammarProxy, ExtensionHostProxy,
+    ExtensionLanguageModelProviderProxy, ExtensionLanguageProxy, ExtensionLanguageServerProxy,
+    ExtensionLinterProxy, ExtensionSlashCommandProxy, ExtensionSnippetProxy, ExtensionThemeProxy,
 };
 use fs::{Fs, RemoveOptions};
 use futures::future::join_all;
@@ -40,6 +40,7 @@ use language::{
     LanguageConfig, LanguageMatcher, LanguageName, LanguageQueries, LoadedLanguage,
     QUERY_FILENAME_PREFIXES, Rope,
 };
+use language_model::LanguageModelRegistry;
 use node_runtime::NodeRuntime;
 use project::ContextProviderWithTasks;
 use release_channel::ReleaseChannel;
@@ -60,6 +61,7 @@ use url::Url;
 use util::{ResultExt, paths::RemotePathBuf};
 use wasm_host::{
     WasmExtension, WasmHost,
+    llm_provider::{self, ExtensionLanguageModelProvider},
     wit::{is_supported_wasm_api_version, wasm_api_version_range},
 };
 
@@ -1212,6 +1214,18 @@ impl ExtensionStore {
             for command_name in extension.manifest.slash_commands.keys() {
                 self.proxy.unregister_slash_command(command_name.clone());
             }
+            for formatter_id in extension.manifest.formatters.keys() {
+                self.proxy.unregister_formatter(formatter_id.clone(), cx);
+            }
+            for linter_id in extension.manifest.linters.keys() {
+                self.proxy.unregister_linter(linter_id.clone(), cx);
+            }
+            for provider_id in extension.manifest.language_model_providers.keys() {
+                self.proxy.unregister_language_model_provider(
+                    llm_provider::registry_provider_id(extension_id, provider_id),
+                    cx,
+                );
+            }
         }
 
         self.wasm_extensions
@@ -1414,6 +1428,28 @@ impl ExtensionStore {
                 }
             }
 
+            let mut llm_providers = Vec::new();
+            for (manifest, wasm_extension) in &wasm_extensions {
+                for (provider_id, provider) in &manifest.language_model_providers {
+                    match wasm_extension
+                        .llm_provider_models(provider_id.clone())
+                        .await
+                    {
+                        Ok(models) => llm_providers.push((
+                            wasm_extension.clone(),
+                            provider_id.clone(),
+                            provider.clone(),
+                            models,
+                        )),
+                        Err(error) => log::error!(
+                            "Failed to load models for language model provider {provider_id} \
+                            of extension {}: {error:#}",
+                            manifest.id
+                        ),
+                    }
+                }
+            }
+
             this.update(cx, |this, cx| {
                 this.reload_complete_senders.clear();
 
@@ -1471,6 +1507,54 @@ impl ExtensionStore {
                         this.proxy
                             .register_debug_locator(extension.clone(), debug_adapter.clone());
                     }
+
+                    for (formatter_id, formatter) in &manifest.formatters {
+                        this.proxy.register_formatter(
+                            extension.clone(),
+                            formatter_id.clone(),
+                            formatter.languages.clone(),
+                            cx,
+                        );
+                    }
+
+                    for (linter_id, linter) in &manifest.linters {
+                        this.proxy.register_linter(
+                            extension.clone(),
+                            linter_id.clone(),
+                            linter.languages.clone(),
+                            cx,
+                        );
+                    }
+                }
+
+                for (wasm_extension, provider_id, provider, models) in llm_providers {
+                    let extension_id = wasm_extension.manifest.id.clone();
+                    let icon_path = provider.icon.as_ref().map(|icon| {
+                        root_dir
+                            .join(extension_id.as_ref())
+                            .join(icon)
+                            .to_string_lossy()
+                            .into_owned()
+                            .into()
+                    });
+                    let extension: Arc<dyn Extension> = Arc::new(wasm_extension);
+                    this.proxy.register_language_model_provider(
+                        llm_provider::registry_provider_id(&extension_id, &provider_id),
+                        Box::new(move |cx| {
+                            let provider = Arc::new(ExtensionLanguageModelProvider::new(
+                                extension,
+                                provider_id,
+                                provider.name,
+                                icon_path,
+                                models,
+                                cx,
+                            ));
+                            LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
+                                registry.register_provider(provider, cx);
+                            });
+                        }),
+                        cx,
+                    );
                 }
 
                 this.wasm_extensions.extend(wasm_extensions);
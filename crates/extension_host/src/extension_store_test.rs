@@ -166,6 +166,8 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         debug_adapters: Default::default(),
                         debug_locators: Default::default(),
                         language_model_providers: BTreeMap::default(),
+                        formatters: BTreeMap::default(),
+                        linters: BTreeMap::default(),
                     }),
                     dev: false,
                 },
@@ -198,6 +200,8 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         debug_adapters: Default::default(),
                         debug_locators: Default::default(),
                         language_model_providers: BTreeMap::default(),
+                        formatters: BTreeMap::default(),
+                        linters: BTreeMap::default(),
                     }),
                     dev: false,
                 },
@@ -379,6 +383,8 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                 debug_adapters: Default::default(),
                 debug_locators: Default::default(),
                 language_model_providers: BTreeMap::default(),
+                formatters: BTreeMap::default(),
+                linters: BTreeMap::default(),
             }),
             dev: false,
         },
@@ -1,3 +1,4 @@
+pub mod llm_provider;
 pub mod wit;
 
 use crate::capability_granter::CapabilityGranter;
@@ -7,9 +8,10 @@ use async_trait::async_trait;
 use dap::{DebugRequest, StartDebuggingRequestArgumentsRequest};
 use extension::{
     CodeLabel, Command, Completion, ContextServerConfiguration, DebugAdapterBinary,
-    DebugTaskDefinition, ExtensionCapability, ExtensionHostProxy, KeyValueStoreDelegate,
-    ProjectDelegate, SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput, Symbol,
-    WorktreeDelegate,
+    DebugTaskDefinition, ExtensionCapability, ExtensionHostProxy, FormatOutput, FormatRequest,
+    KeyValueStoreDelegate, LintDiagnostic, LintRequest, LlmCompletionEvent, LlmCompletionRequest,
+    LlmModelInfo, ProjectDelegate, SlashCommand, SlashCommandArgumentCompletion,
+    SlashCommandOutput, Symbol, WorktreeDelegate,
 };
 use fs::Fs;
 use futures::future::LocalBoxFuture;
@@ -527,6 +529,133 @@ impl extension::Extension for WasmExtension {
         })
         .await?
     }
+
+    async fn format(
+        &self,
+        formatter_id: Arc<str>,
+        request: FormatRequest,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<FormatOutput> {
+        self.call(|extension, store| {
+            async move {
+                let resource = store.data_mut().table().push(worktree)?;
+                extension
+                    .call_format(store, &formatter_id, request, resource)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn lint(
+        &self,
+        linter_id: Arc<str>,
+        request: LintRequest,
+        worktree: Arc<dyn WorktreeDelegate>,
+    ) -> Result<Vec<LintDiagnostic>> {
+        self.call(|extension, store| {
+            async move {
+                let resource = store.data_mut().table().push(worktree)?;
+                extension
+                    .call_lint(store, &linter_id, request, resource)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn llm_provider_models(&self, provider_id: Arc<str>) -> Result<Vec<LlmModelInfo>> {
+        self.call(|extension, store| {
+            async move {
+                extension
+                    .call_llm_provider_models(store, &provider_id)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn llm_provider_is_authenticated(&self, provider_id: Arc<str>) -> Result<bool> {
+        self.call(|extension, store| {
+            async move {
+                extension
+                    .call_llm_provider_is_authenticated(store, &provider_id)
+                    .await
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn llm_count_tokens(
+        &self,
+        provider_id: Arc<str>,
+        model_id: Arc<str>,
+        request: LlmCompletionRequest,
+    ) -> Result<u64> {
+        self.call(|extension, store| {
+            async move {
+                extension
+                    .call_llm_count_tokens(store, &provider_id, &model_id, request)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn llm_stream_completion_start(
+        &self,
+        provider_id: Arc<str>,
+        model_id: Arc<str>,
+        request: LlmCompletionRequest,
+    ) -> Result<String> {
+        self.call(|extension, store| {
+            async move {
+                extension
+                    .call_llm_stream_completion_start(store, &provider_id, &model_id, request)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn llm_stream_completion_next(
+        &self,
+        stream_id: String,
+    ) -> Result<Option<LlmCompletionEvent>> {
+        self.call(|extension, store| {
+            async move {
+                extension
+                    .call_llm_stream_completion_next(store, &stream_id)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    async fn llm_stream_completion_close(&self, stream_id: String) -> Result<()> {
+        self.call(|extension, store| {
+            async move {
+                extension
+                    .call_llm_stream_completion_close(store, &stream_id)
+                    .await
+            }
+            .boxed()
+        })
+        .await?
+    }
 }
 
 pub struct WasmState {
@@ -937,6 +1066,15 @@ impl WasmState {
         self.host.work_dir.join(self.manifest.id.as_ref())
     }
 
+    /// Returns the URL under which credentials for the given language model provider are stored,
+    /// if the provider is declared in this extension's manifest.
+    fn llm_provider_credential_url(&self, provider_id: &str) -> Option<String> {
+        self.manifest
+            .language_model_providers
+            .contains_key(provider_id)
+            .then(|| llm_provider::credential_url(&self.manifest.id, provider_id))
+    }
+
     fn extension_error(&self, message: String) -> anyhow::Error {
         anyhow!(
             "from extension \"{}\" version {}: {}",
@@ -0,0 +1,1013 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use credentials_provider::CredentialsProvider;
+use extension::{
+    Extension, LlmCompletionEvent, LlmCompletionRequest, LlmImage, LlmMessageContent,
+    LlmMessageRole, LlmModelInfo, LlmRequestMessage, LlmStopReason, LlmThinking, LlmToolChoice,
+    LlmToolDefinition, LlmToolInputFormat, LlmToolResult, LlmToolResultContent, LlmToolUse,
+};
+use futures::{FutureExt as _, StreamExt as _, future::BoxFuture, stream::BoxStream};
+use gpui::{
+    AnyView, App, AsyncApp, BackgroundExecutor, Context, Entity, SharedString, Task, Window,
+};
+use language_model::{
+    AuthenticateError, ConfigurationViewTargetAgent, IconOrSvg, LanguageModel,
+    LanguageModelCompletionError, LanguageModelCompletionEvent, LanguageModelId, LanguageModelName,
+    LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
+    LanguageModelProviderState, LanguageModelRequest, LanguageModelToolChoice,
+    LanguageModelToolResultContent, LanguageModelToolSchemaFormat, LanguageModelToolUse,
+    MessageContent, RateLimiter, Role, StopReason, TokenUsage,
+};
+use ui::{ConfiguredApiCard, prelude::*};
+use ui_input::InputField;
+use util::ResultExt as _;
+
+/// Returns the URL under which the credential for the given extension language model provider
+/// is stored in the system keychain.
+pub fn credential_url(extension_id: &str, provider_id: &str) -> String {
+    format!("extension-llm-{extension_id}:{provider_id}")
+}
+
+/// Returns the ID of the given extension language model provider in the language model registry.
+pub fn registry_provider_id(extension_id: &str, provider_id: &str) -> Arc<str> {
+    format!("{extension_id}:{provider_id}").into()
+}
+
+/// A [`LanguageModelProvider`] implemented by a Wasm extension.
+pub struct ExtensionLanguageModelProvider {
+    extension: Arc<dyn Extension>,
+    /// The ID of the provider within its extension.
+    provider_id: Arc<str>,
+    id: LanguageModelProviderId,
+    name: LanguageModelProviderName,
+    icon_path: Option<SharedString>,
+    models: Vec<LlmModelInfo>,
+    state: Entity<State>,
+}
+
+pub struct State {
+    extension: Arc<dyn Extension>,
+    provider_id: Arc<str>,
+    credential_url: String,
+    is_authenticated: bool,
+}
+
+impl State {
+    fn authenticate(&mut self, cx: &mut Context<Self>) -> Task<Result<(), AuthenticateError>> {
+        if self.is_authenticated {
+            return Task::ready(Ok(()));
+        }
+
+        let extension = self.extension.clone();
+        let provider_id = self.provider_id.clone();
+        cx.spawn(async move |this, cx| {
+            let is_authenticated = extension.llm_provider_is_authenticated(provider_id).await?;
+            this.update(cx, |this, cx| {
+                this.is_authenticated = is_authenticated;
+                cx.notify();
+            })?;
+            if is_authenticated {
+                Ok(())
+            } else {
+                Err(AuthenticateError::CredentialsNotFound)
+            }
+        })
+    }
+
+    fn set_api_key(&mut self, api_key: Option<String>, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let credentials_provider = <dyn CredentialsProvider>::global(cx);
+        let credential_url = self.credential_url.clone();
+        let extension = self.extension.clone();
+        let provider_id = self.provider_id.clone();
+        cx.spawn(async move |this, cx| {
+            if let Some(api_key) = &api_key {
+                credentials_provider
+                    .write_credentials(&credential_url, "Bearer", api_key.as_bytes(), cx)
+                    .await?;
+            } else {
+                credentials_provider
+                    .delete_credentials(&credential_url, cx)
+                    .await?;
+            }
+
+            let is_authenticated = extension.llm_provider_is_authenticated(provider_id).await?;
+            this.update(cx, |this, cx| {
+                this.is_authenticated = is_authenticated;
+                cx.notify();
+            })
+        })
+    }
+}
+
+impl ExtensionLanguageModelProvider {
+    pub fn new(
+        extension: Arc<dyn Extension>,
+        provider_id: Arc<str>,
+        name: String,
+        icon_path: Option<SharedString>,
+        models: Vec<LlmModelInfo>,
+        cx: &mut App,
+    ) -> Self {
+        let extension_id = extension.manifest().id.clone();
+        let state = cx.new(|_| State {
+            extension: extension.clone(),
+            provider_id: provider_id.clone(),
+            credential_url: credential_url(&extension_id, &provider_id),
+            is_authenticated: false,
+        });
+
+        Self {
+            id: LanguageModelProviderId::from(registry_provider_id(&extension_id, &provider_id)),
+            name: LanguageModelProviderName::from(name),
+            extension,
+            provider_id,
+            icon_path,
+            models,
+            state,
+        }
+    }
+
+    fn create_language_model(&self, model: &LlmModelInfo) -> Arc<dyn LanguageModel> {
+        Arc::new(ExtensionLanguageModel {
+            extension: self.extension.clone(),
+            provider_id: self.provider_id.clone(),
+            registry_provider_id: self.id.clone(),
+            provider_name: self.name.clone(),
+            model: model.clone(),
+            request_limiter: RateLimiter::new(4),
+        })
+    }
+}
+
+impl LanguageModelProviderState for ExtensionLanguageModelProvider {
+    type ObservableEntity = State;
+
+    fn observable_entity(&self) -> Option<Entity<Self::ObservableEntity>> {
+        Some(self.state.clone())
+    }
+}
+
+impl LanguageModelProvider for ExtensionLanguageModelProvider {
+    fn id(&self) -> LanguageModelProviderId {
+        self.id.clone()
+    }
+
+    fn name(&self) -> LanguageModelProviderName {
+        self.name.clone()
+    }
+
+    fn icon(&self) -> IconOrSvg {
+        match &self.icon_path {
+            Some(icon_path) => IconOrSvg::Svg(icon_path.clone()),
+            None => IconOrSvg::default(),
+        }
+    }
+
+    fn default_model(&self, _cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        self.models
+            .iter()
+            .find(|model| model.is_default)
+            .or_else(|| self.models.first())
+            .map(|model| self.create_language_model(model))
+    }
+
+    fn default_fast_model(&self, _cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        self.models
+            .iter()
+            .find(|model| model.is_default_fast)
+            .map(|model| self.create_language_model(model))
+    }
+
+    fn provided_models(&self, _cx: &App) -> Vec<Arc<dyn LanguageModel>> {
+        self.models
+            .iter()
+            .map(|model| self.create_language_model(model))
+            .collect()
+    }
+
+    fn is_authenticated(&self, cx: &App) -> bool {
+        self.state.read(cx).is_authenticated
+    }
+
+    fn authenticate(&self, cx: &mut App) -> Task<Result<(), AuthenticateError>> {
+        self.state.update(cx, |state, cx| state.authenticate(cx))
+    }
+
+    fn configuration_view(
+        &self,
+        _target_agent: ConfigurationViewTargetAgent,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> AnyView {
+        let provider_name = self.name.0.clone();
+        cx.new(|cx| ConfigurationView::new(self.state.clone(), provider_name, window, cx))
+            .into()
+    }
+
+    fn reset_credentials(&self, cx: &mut App) -> Task<Result<()>> {
+        self.state
+            .update(cx, |state, cx| state.set_api_key(None, cx))
+    }
+}
+
+pub struct ExtensionLanguageModel {
+    extension: Arc<dyn Extension>,
+    provider_id: Arc<str>,
+    registry_provider_id: LanguageModelProviderId,
+    provider_name: LanguageModelProviderName,
+    model: LlmModelInfo,
+    request_limiter: RateLimiter,
+}
+
+impl LanguageModel for ExtensionLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        LanguageModelId::from(self.model.id.clone())
+    }
+
+    fn name(&self) -> LanguageModelName {
+        LanguageModelName::from(self.model.name.clone())
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        self.registry_provider_id.clone()
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        self.provider_name.clone()
+    }
+
+    fn telemetry_id(&self) -> String {
+        format!("{}/{}", self.registry_provider_id.0, self.model.id)
+    }
+
+    fn supports_thinking(&self) -> bool {
+        self.model.capabilities.supports_thinking
+    }
+
+    fn supports_images(&self) -> bool {
+        self.model.capabilities.supports_images
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.model.capabilities.supports_tools
+    }
+
+    fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
+        let capabilities = &self.model.capabilities;
+        match choice {
+            LanguageModelToolChoice::Auto => capabilities.supports_tool_choice_auto,
+            LanguageModelToolChoice::Any => capabilities.supports_tool_choice_any,
+            LanguageModelToolChoice::None => capabilities.supports_tool_choice_none,
+        }
+    }
+
+    fn tool_input_format(&self) -> LanguageModelToolSchemaFormat {
+        match self.model.capabilities.tool_input_format {
+            LlmToolInputFormat::JsonSchema => LanguageModelToolSchemaFormat::JsonSchema,
+            LlmToolInputFormat::JsonSchemaSubset => LanguageModelToolSchemaFormat::JsonSchemaSubset,
+        }
+    }
+
+    fn max_token_count(&self) -> u64 {
+        self.model.max_token_count
+    }
+
+    fn max_output_tokens(&self) -> Option<u64> {
+        self.model.max_output_tokens
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        _cx: &App,
+    ) -> BoxFuture<'static, Result<u64>> {
+        let extension = self.extension.clone();
+        let provider_id = self.provider_id.clone();
+        let model_id: Arc<str> = self.model.id.as_str().into();
+        let request = into_llm_request(request, self.model.max_output_tokens);
+        async move {
+            extension
+                .llm_count_tokens(provider_id, model_id, request)
+                .await
+        }
+        .boxed()
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let extension = self.extension.clone();
+        let provider_id = self.provider_id.clone();
+        let model_id: Arc<str> = self.model.id.as_str().into();
+        let request = into_llm_request(request, self.model.max_output_tokens);
+        let executor = cx.background_executor().clone();
+
+        let future = self.request_limiter.stream(async move {
+            let stream_id = extension
+                .llm_stream_completion_start(provider_id, model_id, request)
+                .await?;
+            Ok(completion_event_stream(extension, stream_id, executor))
+        });
+
+        async move { Ok(future.await?.boxed()) }.boxed()
+    }
+}
+
+/// Polls the extension for the events of the given completion stream, closing the stream once it
+/// has ended or failed, or once the returned stream is dropped.
+fn completion_event_stream(
+    extension: Arc<dyn Extension>,
+    stream_id: String,
+    executor: BackgroundExecutor,
+) -> BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>> {
+    let guard = CompletionStreamGuard {
+        extension,
+        stream_id: Some(stream_id),
+        executor,
+    };
+    futures::stream::unfold(guard, |mut guard| async move {
+        let stream_id = guard.stream_id.clone()?;
+        match guard
+            .extension
+            .llm_stream_completion_next(stream_id.clone())
+            .await
+        {
+            Ok(Some(event)) => Some((into_completion_event(event), guard)),
+            Ok(None) => {
+                guard.close().await;
+                None
+            }
+            Err(error) => {
+                guard.close().await;
+                Some((Err(LanguageModelCompletionError::Other(error)), guard))
+            }
+        }
+    })
+    .boxed()
+}
+
+/// Owns an extension-side completion stream and closes it when dropped before the stream ended,
+/// e.g. because the consumer cancelled the completion.
+struct CompletionStreamGuard {
+    extension: Arc<dyn Extension>,
+    stream_id: Option<String>,
+    executor: BackgroundExecutor,
+}
+
+impl CompletionStreamGuard {
+    async fn close(&mut self) {
+        if let Some(stream_id) = self.stream_id.take() {
+            self.extension
+                .llm_stream_completion_close(stream_id)
+                .await
+                .log_err();
+        }
+    }
+}
+
+impl Drop for CompletionStreamGuard {
+    fn drop(&mut self) {
+        if let Some(stream_id) = self.stream_id.take() {
+            let extension = self.extension.clone();
+            self.executor
+                .spawn(async move {
+                    extension
+                        .llm_stream_completion_close(stream_id)
+                        .await
+                        .log_err();
+                })
+                .detach();
+        }
+    }
+}
+
+fn into_llm_request(
+    request: LanguageModelRequest,
+    max_output_tokens: Option<u64>,
+) -> LlmCompletionRequest {
+    LlmCompletionRequest {
+        messages: request
+            .messages
+            .into_iter()
+            .map(|message| LlmRequestMessage {
+                role: match message.role {
+                    Role::User => LlmMessageRole::User,
+                    Role::Assistant => LlmMessageRole::Assistant,
+                    Role::System => LlmMessageRole::System,
+                },
+                content: message
+                    .content
+                    .into_iter()
+                    .map(into_llm_message_content)
+                    .collect(),
+                cache: message.cache,
+            })
+            .collect(),
+        tools: request
+            .tools
+            .into_iter()
+            .map(|tool| LlmToolDefinition {
+                name: tool.name,
+                description: tool.description,
+                input_schema: tool.input_schema.to_string(),
+            })
+            .collect(),
+        tool_choice: request.tool_choice.map(|choice| match choice {
+            LanguageModelToolChoice::Auto => LlmToolChoice::Auto,
+            LanguageModelToolChoice::Any => LlmToolChoice::Any,
+            LanguageModelToolChoice::None => LlmToolChoice::None,
+        }),
+        stop_sequences: request.stop,
+        temperature: request.temperature,
+        thinking_allowed: request.thinking_allowed,
+        max_tokens: max_output_tokens,
+    }
+}
+
+fn into_llm_message_content(content: MessageContent) -> LlmMessageContent {
+    match content {
+        MessageContent::Text(text) => LlmMessageContent::Text(text),
+        MessageContent::Thinking { text, signature } => {
+            LlmMessageContent::Thinking(LlmThinking { text, signature })
+        }
+        MessageContent::RedactedThinking(data) => LlmMessageContent::RedactedThinking(data),
+        MessageContent::Image(image) => LlmMessageContent::Image(into_llm_image(image)),
+        MessageContent::ToolUse(tool_use) => LlmMessageContent::ToolUse(LlmToolUse {
+            id: tool_use.id.to_string(),
+            name: tool_use.name.to_string(),
+            input: if tool_use.raw_input.is_empty() {
+                tool_use.input.to_string()
+            } else {
+                tool_use.raw_input
+            },
+            is_input_complete: tool_use.is_input_complete,
+            thought_signature: tool_use.thought_signature,
+        }),
+        MessageContent::ToolResult(tool_result) => LlmMessageContent::ToolResult(LlmToolResult {
+            tool_use_id: tool_result.tool_use_id.to_string(),
+            tool_name: tool_result.tool_name.to_string(),
+            is_error: tool_result.is_error,
+            content: match tool_result.content {
+                LanguageModelToolResultContent::Text(text) => {
+                    LlmToolResultContent::Text(text.to_string())
+                }
+                LanguageModelToolResultContent::Image(image) => {
+                    LlmToolResultContent::Image(into_llm_image(image))
+                }
+            },
+        }),
+    }
+}
+
+fn into_llm_image(image: language_model::LanguageModelImage) -> LlmImage {
+    LlmImage {
+        source: image.source.to_string(),
+        width: image.size.map(|size| size.width.0 as u32),
+        height: image.size.map(|size| size.height.0 as u32),
+    }
+}
+
+fn into_completion_event(
+    event: LlmCompletionEvent,
+) -> Result<LanguageModelCompletionEvent, LanguageModelCompletionError> {
+    Ok(match event {
+        LlmCompletionEvent::Started => LanguageModelCompletionEvent::Started,
+        LlmCompletionEvent::Text(text) => LanguageModelCompletionEvent::Text(text),
+        LlmCompletionEvent::Thinking(thinking) => LanguageModelCompletionEvent::Thinking {
+            text: thinking.text,
+            signature: thinking.signature,
+        },
+        LlmCompletionEvent::RedactedThinking(data) => {
+            LanguageModelCompletionEvent::RedactedThinking { data }
+        }
+        LlmCompletionEvent::ToolUse(tool_use) => {
+            match serde_json::from_str::<serde_json::Value>(&tool_use.input) {
+                Ok(input) => LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                    id: tool_use.id.into(),
+                    name: tool_use.name.into(),
+                    raw_input: tool_use.input,
+                    input,
+                    is_input_complete: tool_use.is_input_complete,
+                    thought_signature: tool_use.thought_signature,
+                }),
+                // Partial input is expected to be invalid JSON while it is still being streamed.
+                Err(_) if !tool_use.is_input_complete => {
+                    LanguageModelCompletionEvent::ToolUse(LanguageModelToolUse {
+                        id: tool_use.id.into(),
+                        name: tool_use.name.into(),
+                        raw_input: tool_use.input,
+                        input: serde_json::Value::Object(Default::default()),
+                        is_input_complete: false,
+                        thought_signature: tool_use.thought_signature,
+                    })
+                }
+                Err(error) => LanguageModelCompletionEvent::ToolUseJsonParseError {
+                    id: tool_use.id.into(),
+                    tool_name: tool_use.name.into(),
+                    raw_input: tool_use.input.into(),
+                    json_parse_error: error.to_string(),
+                },
+            }
+        }
+        LlmCompletionEvent::Stop(reason) => LanguageModelCompletionEvent::Stop(match reason {
+            LlmStopReason::EndTurn => StopReason::EndTurn,
+            LlmStopReason::MaxTokens => StopReason::MaxTokens,
+            LlmStopReason::ToolUse => StopReason::ToolUse,
+            LlmStopReason::Refusal => StopReason::Refusal,
+        }),
+        LlmCompletionEvent::Usage(usage) => LanguageModelCompletionEvent::UsageUpdate(TokenUsage {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_creation_input_tokens: usage.cache_creation_input_tokens,
+            cache_read_input_tokens: usage.cache_read_input_tokens,
+        }),
+    })
+}
+
+struct ConfigurationView {
+    api_key_editor: Entity<InputField>,
+    state: Entity<State>,
+    provider_name: SharedString,
+    load_credentials_task: Option<Task<()>>,
+}
+
+impl ConfigurationView {
+    fn new(
+        state: Entity<State>,
+        provider_name: SharedString,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let api_key_editor = cx.new(|cx| InputField::new(window, cx, "API key"));
+
+        cx.observe(&state, |_, _, cx| {
+            cx.notify();
+        })
+        .detach();
+
+        let load_credentials_task = Some(cx.spawn({
+            let state = state.clone();
+            async move |this, cx| {
+                let task = state.update(cx, |state, cx| state.authenticate(cx));
+                let _ = task.await;
+
+                this.update(cx, |this, cx| {
+                    this.load_credentials_task = None;
+                    cx.notify();
+                })
+                .log_err();
+            }
+        }));
+
+        Self {
+            api_key_editor,
+            state,
+            provider_name,
+            load_credentials_task,
+        }
+    }
+
+    fn save_api_key(&mut self, _: &menu::Confirm, _window: &mut Window, cx: &mut Context<Self>) {
+        let api_key = self.api_key_editor.read(cx).text(cx).trim().to_string();
+        if api_key.is_empty() {
+            return;
+        }
+
+        let state = self.state.clone();
+        cx.spawn(async move |_, cx| {
+            state
+                .update(cx, |state, cx| state.set_api_key(Some(api_key), cx))
+                .await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn reset_api_key(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.api_key_editor
+            .update(cx, |editor, cx| editor.set_text("", window, cx));
+
+        let state = self.state.clone();
+        cx.spawn(async move |_, cx| {
+            state
+                .update(cx, |state, cx| state.set_api_key(None, cx))
+                .await
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl Render for ConfigurationView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if self.load_credentials_task.is_some() {
+            div()
+                .child(Label::new("Loading credentials..."))
+                .into_any_element()
+        } else if !self.state.read(cx).is_authenticated {
+            v_flex()
+                .size_full()
+                .on_action(cx.listener(Self::save_api_key))
+                .child(Label::new(format!(
+                    "To use {} in Zed, paste your API key below and hit enter:",
+                    self.provider_name
+                )))
+                .child(self.api_key_editor.clone())
+                .into_any_element()
+        } else {
+            ConfiguredApiCard::new("API key configured")
+                .on_click(cx.listener(|this, _, window, cx| this.reset_api_key(window, cx)))
+                .into_any_element()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        path::{Path, PathBuf},
+    };
+
+    use anyhow::anyhow;
+    use async_trait::async_trait;
+    use collections::HashMap;
+    use dap::{DebugRequest, StartDebuggingRequestArgumentsRequest};
+    use extension::{
+        CodeLabel, Command, Completion, ContextServerConfiguration, DebugAdapterBinary,
+        DebugTaskDefinition, ExtensionManifest, FormatOutput, FormatRequest, KeyValueStoreDelegate,
+        LintDiagnostic, LintRequest, LlmModelCapabilities, ProjectDelegate, SlashCommand,
+        SlashCommandArgumentCompletion, SlashCommandOutput, Symbol, WorktreeDelegate,
+    };
+    use gpui::TestAppContext;
+    use language::LanguageName;
+    use lsp::LanguageServerName;
+    use parking_lot::Mutex;
+    use task::{DebugScenario, SpawnInTerminal, TaskTemplate, ZedDebugConfig};
+
+    use super::*;
+
+    /// An extension that only provides a language model, streaming a fixed list of events.
+    struct FakeLlmExtension {
+        manifest: Arc<ExtensionManifest>,
+        events: Vec<LlmCompletionEvent>,
+        started_streams: Mutex<usize>,
+        open_streams: Mutex<HashMap<String, VecDeque<LlmCompletionEvent>>>,
+        closed_streams: Mutex<Vec<String>>,
+    }
+
+    impl FakeLlmExtension {
+        fn new(events: Vec<LlmCompletionEvent>) -> Arc<Self> {
+            let manifest = serde_json::from_value(serde_json::json!({
+                "id": "fake-llm",
+                "name": "Fake LLM",
+                "version": "0.1.0",
+                "schema_version": 1,
+            }))
+            .unwrap();
+            Arc::new(Self {
+                manifest: Arc::new(manifest),
+                events,
+                started_streams: Mutex::default(),
+                open_streams: Mutex::default(),
+                closed_streams: Mutex::default(),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Extension for FakeLlmExtension {
+        fn manifest(&self) -> Arc<ExtensionManifest> {
+            self.manifest.clone()
+        }
+
+        fn work_dir(&self) -> Arc<Path> {
+            Path::new("/fake-llm").into()
+        }
+
+        async fn language_server_command(
+            &self,
+            _: LanguageServerName,
+            _: LanguageName,
+            _: Arc<dyn WorktreeDelegate>,
+        ) -> Result<Command> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn language_server_initialization_options(
+            &self,
+            _: LanguageServerName,
+            _: LanguageName,
+            _: Arc<dyn WorktreeDelegate>,
+        ) -> Result<Option<String>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn language_server_workspace_configuration(
+            &self,
+            _: LanguageServerName,
+            _: Arc<dyn WorktreeDelegate>,
+        ) -> Result<Option<String>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn language_server_initialization_options_schema(
+            &self,
+            _: LanguageServerName,
+            _: Arc<dyn WorktreeDelegate>,
+        ) -> Result<Option<String>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn language_server_workspace_configuration_schema(
+            &self,
+            _: LanguageServerName,
+            _: Arc<dyn WorktreeDelegate>,
+        ) -> Result<Option<String>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn language_server_additional_initialization_options(
+            &self,
+            _: LanguageServerName,
+            _: LanguageServerName,
+            _: Arc<dyn WorktreeDelegate>,
+        ) -> Result<Option<String>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn language_server_additional_workspace_configuration(
+            &self,
+            _: LanguageServerName,
+            _: LanguageServerName,
+            _: Arc<dyn WorktreeDelegate>,
+        ) -> Result<Option<String>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn labels_for_completions(
+            &self,
+            _: LanguageServerName,
+            _: Vec<Completion>,
+        ) -> Result<Vec<Option<CodeLabel>>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn labels_for_symbols(
+            &self,
+            _: LanguageServerName,
+            _: Vec<Symbol>,
+        ) -> Result<Vec<Option<CodeLabel>>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn complete_slash_command_argument(
+            &self,
+            _: SlashCommand,
+            _: Vec<String>,
+        ) -> Result<Vec<SlashCommandArgumentCompletion>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn run_slash_command(
+            &self,
+            _: SlashCommand,
+            _: Vec<String>,
+            _: Option<Arc<dyn WorktreeDelegate>>,
+        ) -> Result<SlashCommandOutput> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn context_server_command(
+            &self,
+            _: Arc<str>,
+            _: Arc<dyn ProjectDelegate>,
+        ) -> Result<Command> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn context_server_configuration(
+            &self,
+            _: Arc<str>,
+            _: Arc<dyn ProjectDelegate>,
+        ) -> Result<Option<ContextServerConfiguration>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn suggest_docs_packages(&self, _: Arc<str>) -> Result<Vec<String>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn index_docs(
+            &self,
+            _: Arc<str>,
+            _: Arc<str>,
+            _: Arc<dyn KeyValueStoreDelegate>,
+        ) -> Result<()> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn get_dap_binary(
+            &self,
+            _: Arc<str>,
+            _: DebugTaskDefinition,
+            _: Option<PathBuf>,
+            _: Arc<dyn WorktreeDelegate>,
+        ) -> Result<DebugAdapterBinary> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn dap_request_kind(
+            &self,
+            _: Arc<str>,
+            _: serde_json::Value,
+        ) -> Result<StartDebuggingRequestArgumentsRequest> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn dap_config_to_scenario(&self, _: ZedDebugConfig) -> Result<DebugScenario> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn dap_locator_create_scenario(
+            &self,
+            _: String,
+            _: TaskTemplate,
+            _: String,
+            _: String,
+        ) -> Result<Option<DebugScenario>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn run_dap_locator(&self, _: String, _: SpawnInTerminal) -> Result<DebugRequest> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn format(
+            &self,
+            _: Arc<str>,
+            _: FormatRequest,
+            _: Arc<dyn WorktreeDelegate>,
+        ) -> Result<FormatOutput> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn lint(
+            &self,
+            _: Arc<str>,
+            _: LintRequest,
+            _: Arc<dyn WorktreeDelegate>,
+        ) -> Result<Vec<LintDiagnostic>> {
+            Err(anyhow!("unsupported"))
+        }
+
+        async fn llm_provider_models(&self, _: Arc<str>) -> Result<Vec<LlmModelInfo>> {
+            Ok(Vec::new())
+        }
+
+        async fn llm_provider_is_authenticated(&self, _: Arc<str>) -> Result<bool> {
+            Ok(true)
+        }
+
+        async fn llm_count_tokens(
+            &self,
+            _: Arc<str>,
+            _: Arc<str>,
+            request: LlmCompletionRequest,
+        ) -> Result<u64> {
+            Ok(request.messages.len() as u64)
+        }
+
+        async fn llm_stream_completion_start(
+            &self,
+            _: Arc<str>,
+            _: Arc<str>,
+            _: LlmCompletionRequest,
+        ) -> Result<String> {
+            let stream_id = {
+                let mut started_streams = self.started_streams.lock();
+                *started_streams += 1;
+                format!("stream-{started_streams}")
+            };
+            self.open_streams
+                .lock()
+                .insert(stream_id.clone(), self.events.iter().cloned().collect());
+            Ok(stream_id)
+        }
+
+        async fn llm_stream_completion_next(
+            &self,
+            stream_id: String,
+        ) -> Result<Option<LlmCompletionEvent>> {
+            let mut open_streams = self.open_streams.lock();
+            let events = open_streams
+                .get_mut(&stream_id)
+                .ok_or_else(|| anyhow!("unknown stream {stream_id}"))?;
+            Ok(events.pop_front())
+        }
+
+        async fn llm_stream_completion_close(&self, stream_id: String) -> Result<()> {
+            self.open_streams
+                .lock()
+                .remove(&stream_id)
+                .ok_or_else(|| anyhow!("unknown stream {stream_id}"))?;
+            self.closed_streams.lock().push(stream_id);
+            Ok(())
+        }
+    }
+
+    fn fake_model(
+        extension: Arc<FakeLlmExtension>,
+        cx: &mut TestAppContext,
+    ) -> Arc<dyn LanguageModel> {
+        let provider = cx.update(|cx| {
+            ExtensionLanguageModelProvider::new(
+                extension,
+                "fake".into(),
+                "Fake".into(),
+                None,
+                vec![LlmModelInfo {
+                    id: "fake-model".into(),
+                    name: "Fake Model".into(),
+                    max_token_count: 1000,
+                    max_output_tokens: None,
+                    is_default: true,
+                    is_default_fast: false,
+                    capabilities: LlmModelCapabilities {
+                        supports_images: false,
+                        supports_tools: false,
+                        supports_tool_choice_auto: false,
+                        supports_tool_choice_any: false,
+                        supports_tool_choice_none: false,
+                        supports_thinking: false,
+                        tool_input_format: LlmToolInputFormat::JsonSchema,
+                    },
+                }],
+                cx,
+            )
+        });
+        cx.update(|cx| provider.default_model(cx)).unwrap()
+    }
+
+    #[gpui::test]
+    async fn test_completion_stream_closes_when_finished(cx: &mut TestAppContext) {
+        let extension = FakeLlmExtension::new(vec![
+            LlmCompletionEvent::Started,
+            LlmCompletionEvent::Text("Hello".into()),
+            LlmCompletionEvent::Text(", world".into()),
+            LlmCompletionEvent::Stop(LlmStopReason::EndTurn),
+        ]);
+        let model = fake_model(extension.clone(), cx);
+
+        let stream = model
+            .stream_completion(LanguageModelRequest::default(), &cx.to_async())
+            .await
+            .unwrap();
+        let events = stream.map(|event| event.unwrap()).collect::<Vec<_>>().await;
+
+        let text = events
+            .iter()
+            .filter_map(|event| match event {
+                LanguageModelCompletionEvent::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<String>();
+        assert_eq!(text, "Hello, world");
+        assert!(matches!(
+            events.last(),
+            Some(LanguageModelCompletionEvent::Stop(StopReason::EndTurn))
+        ));
+        cx.run_until_parked();
+        assert_eq!(*extension.closed_streams.lock(), ["stream-1"]);
+        assert!(extension.open_streams.lock().is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_completion_stream_closes_when_dropped(cx: &mut TestAppContext) {
+        let extension = FakeLlmExtension::new(vec![
+            LlmCompletionEvent::Text("Hello".into()),
+            LlmCompletionEvent::Text(", world".into()),
+        ]);
+        let model = fake_model(extension.clone(), cx);
+
+        let mut stream = model
+            .stream_completion(LanguageModelRequest::default(), &cx.to_async())
+            .await
+            .unwrap();
+        let first = stream.next().await.unwrap().unwrap();
+        assert!(matches!(first, LanguageModelCompletionEvent::Text(text) if text == "Hello"));
+        assert!(extension.closed_streams.lock().is_empty());
+
+        drop(stream);
+        cx.run_until_parked();
+        assert_eq!(*extension.closed_streams.lock(), ["stream-1"]);
+        assert!(extension.open_streams.lock().is_empty());
+    }
+}
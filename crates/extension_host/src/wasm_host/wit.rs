@@ -9,7 +9,10 @@ mod since_v0_5_0;
 mod since_v0_6_0;
 mod since_v0_8_0;
 use dap::DebugRequest;
-use extension::{DebugTaskDefinition, KeyValueStoreDelegate, WorktreeDelegate};
+use extension::{
+    DebugTaskDefinition, FormatOutput, FormatRequest, KeyValueStoreDelegate, LintDiagnostic,
+    LintRequest, LlmCompletionEvent, LlmCompletionRequest, LlmModelInfo, WorktreeDelegate,
+};
 use gpui::BackgroundExecutor;
 use language::LanguageName;
 use lsp::LanguageServerName;
@@ -1266,6 +1269,203 @@ impl Extension {
             }
         }
     }
+
+    pub async fn call_format(
+        &self,
+        store: &mut Store<WasmState>,
+        formatter_id: &str,
+        request: FormatRequest,
+        resource: Resource<Arc<dyn WorktreeDelegate>>,
+    ) -> Result<Result<FormatOutput, String>> {
+        match self {
+            Extension::V0_8_0(ext) => Ok(ext
+                .call_format(store, formatter_id, &request.into(), resource)
+                .await?
+                .map(Into::into)),
+            Extension::V0_6_0(_)
+            | Extension::V0_5_0(_)
+            | Extension::V0_4_0(_)
+            | Extension::V0_3_0(_)
+            | Extension::V0_2_0(_)
+            | Extension::V0_1_0(_)
+            | Extension::V0_0_6(_)
+            | Extension::V0_0_4(_)
+            | Extension::V0_0_1(_) => {
+                anyhow::bail!("`format` not available prior to v0.8.0");
+            }
+        }
+    }
+
+    pub async fn call_lint(
+        &self,
+        store: &mut Store<WasmState>,
+        linter_id: &str,
+        request: LintRequest,
+        resource: Resource<Arc<dyn WorktreeDelegate>>,
+    ) -> Result<Result<Vec<LintDiagnostic>, String>> {
+        match self {
+            Extension::V0_8_0(ext) => Ok(ext
+                .call_lint(store, linter_id, &request.into(), resource)
+                .await?
+                .map(|diagnostics| diagnostics.into_iter().map(Into::into).collect())),
+            Extension::V0_6_0(_)
+            | Extension::V0_5_0(_)
+            | Extension::V0_4_0(_)
+            | Extension::V0_3_0(_)
+            | Extension::V0_2_0(_)
+            | Extension::V0_1_0(_)
+            | Extension::V0_0_6(_)
+            | Extension::V0_0_4(_)
+            | Extension::V0_0_1(_) => {
+                anyhow::bail!("`lint` not available prior to v0.8.0");
+            }
+        }
+    }
+
+    pub async fn call_llm_provider_models(
+        &self,
+        store: &mut Store<WasmState>,
+        provider_id: &str,
+    ) -> Result<Result<Vec<LlmModelInfo>, String>> {
+        match self {
+            Extension::V0_8_0(ext) => Ok(ext
+                .call_llm_provider_models(store, provider_id)
+                .await?
+                .map(|models| models.into_iter().map(Into::into).collect())),
+            Extension::V0_6_0(_)
+            | Extension::V0_5_0(_)
+            | Extension::V0_4_0(_)
+            | Extension::V0_3_0(_)
+            | Extension::V0_2_0(_)
+            | Extension::V0_1_0(_)
+            | Extension::V0_0_6(_)
+            | Extension::V0_0_4(_)
+            | Extension::V0_0_1(_) => {
+                anyhow::bail!("`llm_provider_models` not available prior to v0.8.0");
+            }
+        }
+    }
+
+    pub async fn call_llm_provider_is_authenticated(
+        &self,
+        store: &mut Store<WasmState>,
+        provider_id: &str,
+    ) -> Result<bool> {
+        match self {
+            Extension::V0_8_0(ext) => {
+                ext.call_llm_provider_is_authenticated(store, provider_id)
+                    .await
+            }
+            Extension::V0_6_0(_)
+            | Extension::V0_5_0(_)
+            | Extension::V0_4_0(_)
+            | Extension::V0_3_0(_)
+            | Extension::V0_2_0(_)
+            | Extension::V0_1_0(_)
+            | Extension::V0_0_6(_)
+            | Extension::V0_0_4(_)
+            | Extension::V0_0_1(_) => {
+                anyhow::bail!("`llm_provider_is_authenticated` not available prior to v0.8.0");
+            }
+        }
+    }
+
+    pub async fn call_llm_count_tokens(
+        &self,
+        store: &mut Store<WasmState>,
+        provider_id: &str,
+        model_id: &str,
+        request: LlmCompletionRequest,
+    ) -> Result<Result<u64, String>> {
+        match self {
+            Extension::V0_8_0(ext) => {
+                ext.call_llm_count_tokens(store, provider_id, model_id, &request.into())
+                    .await
+            }
+            Extension::V0_6_0(_)
+            | Extension::V0_5_0(_)
+            | Extension::V0_4_0(_)
+            | Extension::V0_3_0(_)
+            | Extension::V0_2_0(_)
+            | Extension::V0_1_0(_)
+            | Extension::V0_0_6(_)
+            | Extension::V0_0_4(_)
+            | Extension::V0_0_1(_) => {
+                anyhow::bail!("`llm_count_tokens` not available prior to v0.8.0");
+            }
+        }
+    }
+
+    pub async fn call_llm_stream_completion_start(
+        &self,
+        store: &mut Store<WasmState>,
+        provider_id: &str,
+        model_id: &str,
+        request: LlmCompletionRequest,
+    ) -> Result<Result<String, String>> {
+        match self {
+            Extension::V0_8_0(ext) => {
+                ext.call_llm_stream_completion_start(store, provider_id, model_id, &request.into())
+                    .await
+            }
+            Extension::V0_6_0(_)
+            | Extension::V0_5_0(_)
+            | Extension::V0_4_0(_)
+            | Extension::V0_3_0(_)
+            | Extension::V0_2_0(_)
+            | Extension::V0_1_0(_)
+            | Extension::V0_0_6(_)
+            | Extension::V0_0_4(_)
+            | Extension::V0_0_1(_) => {
+                anyhow::bail!("`llm_stream_completion_start` not available prior to v0.8.0");
+            }
+        }
+    }
+
+    pub async fn call_llm_stream_completion_next(
+        &self,
+        store: &mut Store<WasmState>,
+        stream_id: &str,
+    ) -> Result<Result<Option<LlmCompletionEvent>, String>> {
+        match self {
+            Extension::V0_8_0(ext) => Ok(ext
+                .call_llm_stream_completion_next(store, stream_id)
+                .await?
+                .map(|event| event.map(Into::into))),
+            Extension::V0_6_0(_)
+            | Extension::V0_5_0(_)
+            | Extension::V0_4_0(_)
+            | Extension::V0_3_0(_)
+            | Extension::V0_2_0(_)
+            | Extension::V0_1_0(_)
+            | Extension::V0_0_6(_)
+            | Extension::V0_0_4(_)
+            | Extension::V0_0_1(_) => {
+                anyhow::bail!("`llm_stream_completion_next` not available prior to v0.8.0");
+            }
+        }
+    }
+
+    pub async fn call_llm_stream_completion_close(
+        &self,
+        store: &mut Store<WasmState>,
+        stream_id: &str,
+    ) -> Result<()> {
+        match self {
+            Extension::V0_8_0(ext) => ext.call_llm_stream_completion_close(store, stream_id).await,
+            Extension::V0_6_0(_)
+            | Extension::V0_5_0(_)
+            | Extension::V0_4_0(_)
+            | Extension::V0_3_0(_)
+            | Extension::V0_2_0(_)
+            | Extension::V0_1_0(_)
+            | Extension::V0_0_6(_)
+            | Extension::V0_0_4(_)
+            | Extension::V0_0_1(_) => {
+                anyhow::bail!("`llm_stream_completion_close` not available prior to v0.8.0");
+            }
+        }
+    }
 }
 
 trait ToWasmtimeResult<T> {
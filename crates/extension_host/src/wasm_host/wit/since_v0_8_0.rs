@@ -13,6 +13,7 @@ use anyhow::{Context as _, Result, bail};
 use async_compression::futures::bufread::GzipDecoder;
 use async_tar::Archive;
 use async_trait::async_trait;
+use credentials_provider::CredentialsProvider;
 use extension::{
     ExtensionLanguageServerProxy, KeyValueStoreDelegate, ProjectDelegate, WorktreeDelegate,
 };
@@ -32,7 +33,8 @@ use std::{
 use task::{SpawnInTerminal, ZedDebugConfig};
 use url::Url;
 use util::{
-    archive::extract_zip, fs::make_file_executable, maybe, paths::PathStyle, rel_path::RelPath,
+    ResultExt as _, archive::extract_zip, fs::make_file_executable, maybe, paths::PathStyle,
+    rel_path::RelPath,
 };
 use wasmtime::component::{Linker, Resource};
 
@@ -514,6 +516,250 @@ impl TryFrom<ContextServerConfiguration> for extension::ContextServerConfigurati
     }
 }
 
+impl From<extension::FormatRequest> for formatter::FormatRequest {
+    fn from(value: extension::FormatRequest) -> Self {
+        Self {
+            language_name: value.language_name,
+            path: value.path,
+            text: value.text,
+            tab_size: value.tab_size,
+            hard_tabs: value.hard_tabs,
+        }
+    }
+}
+
+impl From<formatter::FormatOutput> for extension::FormatOutput {
+    fn from(value: formatter::FormatOutput) -> Self {
+        match value {
+            formatter::FormatOutput::Unchanged => Self::Unchanged,
+            formatter::FormatOutput::Text(text) => Self::Text(text),
+            formatter::FormatOutput::Command(command) => Self::Command(command.into()),
+        }
+    }
+}
+
+impl From<extension::LintRequest> for linter::LintRequest {
+    fn from(value: extension::LintRequest) -> Self {
+        Self {
+            language_name: value.language_name,
+            path: value.path,
+            text: value.text,
+        }
+    }
+}
+
+impl From<linter::Position> for extension::LintPosition {
+    fn from(value: linter::Position) -> Self {
+        Self {
+            line: value.line,
+            column: value.column,
+        }
+    }
+}
+
+impl From<linter::DiagnosticSeverity> for extension::LintSeverity {
+    fn from(value: linter::DiagnosticSeverity) -> Self {
+        match value {
+            linter::DiagnosticSeverity::Error => Self::Error,
+            linter::DiagnosticSeverity::Warning => Self::Warning,
+            linter::DiagnosticSeverity::Information => Self::Information,
+            linter::DiagnosticSeverity::Hint => Self::Hint,
+        }
+    }
+}
+
+impl From<linter::Diagnostic> for extension::LintDiagnostic {
+    fn from(value: linter::Diagnostic) -> Self {
+        Self {
+            start: value.start.into(),
+            end: value.end.into(),
+            severity: value.severity.into(),
+            message: value.message,
+            code: value.code,
+        }
+    }
+}
+
+impl From<llm_provider::ModelInfo> for extension::LlmModelInfo {
+    fn from(value: llm_provider::ModelInfo) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            max_token_count: value.max_token_count,
+            max_output_tokens: value.max_output_tokens,
+            capabilities: value.capabilities.into(),
+            is_default: value.is_default,
+            is_default_fast: value.is_default_fast,
+        }
+    }
+}
+
+impl From<llm_provider::ModelCapabilities> for extension::LlmModelCapabilities {
+    fn from(value: llm_provider::ModelCapabilities) -> Self {
+        Self {
+            supports_images: value.supports_images,
+            supports_tools: value.supports_tools,
+            supports_tool_choice_auto: value.supports_tool_choice_auto,
+            supports_tool_choice_any: value.supports_tool_choice_any,
+            supports_tool_choice_none: value.supports_tool_choice_none,
+            supports_thinking: value.supports_thinking,
+            tool_input_format: match value.tool_input_format {
+                llm_provider::ToolInputFormat::JsonSchema => {
+                    extension::LlmToolInputFormat::JsonSchema
+                }
+                llm_provider::ToolInputFormat::JsonSchemaSubset => {
+                    extension::LlmToolInputFormat::JsonSchemaSubset
+                }
+            },
+        }
+    }
+}
+
+impl From<extension::LlmCompletionRequest> for llm_provider::CompletionRequest {
+    fn from(value: extension::LlmCompletionRequest) -> Self {
+        Self {
+            messages: value.messages.into_iter().map(Into::into).collect(),
+            tools: value.tools.into_iter().map(Into::into).collect(),
+            tool_choice: value.tool_choice.map(|choice| match choice {
+                extension::LlmToolChoice::Auto => llm_provider::ToolChoice::Auto,
+                extension::LlmToolChoice::Any => llm_provider::ToolChoice::Any,
+                extension::LlmToolChoice::None => llm_provider::ToolChoice::None,
+            }),
+            stop_sequences: value.stop_sequences,
+            temperature: value.temperature,
+            thinking_allowed: value.thinking_allowed,
+            max_tokens: value.max_tokens,
+        }
+    }
+}
+
+impl From<extension::LlmRequestMessage> for llm_provider::RequestMessage {
+    fn from(value: extension::LlmRequestMessage) -> Self {
+        Self {
+            role: match value.role {
+                extension::LlmMessageRole::User => llm_provider::MessageRole::User,
+                extension::LlmMessageRole::Assistant => llm_provider::MessageRole::Assistant,
+                extension::LlmMessageRole::System => llm_provider::MessageRole::System,
+            },
+            content: value.content.into_iter().map(Into::into).collect(),
+            cache: value.cache,
+        }
+    }
+}
+
+impl From<extension::LlmMessageContent> for llm_provider::MessageContent {
+    fn from(value: extension::LlmMessageContent) -> Self {
+        match value {
+            extension::LlmMessageContent::Text(text) => Self::Text(text),
+            extension::LlmMessageContent::Image(image) => Self::Image(image.into()),
+            extension::LlmMessageContent::ToolUse(tool_use) => Self::ToolUse(tool_use.into()),
+            extension::LlmMessageContent::ToolResult(tool_result) => {
+                Self::ToolResult(llm_provider::ToolResult {
+                    tool_use_id: tool_result.tool_use_id,
+                    tool_name: tool_result.tool_name,
+                    is_error: tool_result.is_error,
+                    content: match tool_result.content {
+                        extension::LlmToolResultContent::Text(text) => {
+                            llm_provider::ToolResultContent::Text(text)
+                        }
+                        extension::LlmToolResultContent::Image(image) => {
+                            llm_provider::ToolResultContent::Image(image.into())
+                        }
+                    },
+                })
+            }
+            extension::LlmMessageContent::Thinking(thinking) => Self::Thinking(thinking.into()),
+            extension::LlmMessageContent::RedactedThinking(data) => Self::RedactedThinking(data),
+        }
+    }
+}
+
+impl From<extension::LlmImage> for llm_provider::ImageData {
+    fn from(value: extension::LlmImage) -> Self {
+        Self {
+            source: value.source,
+            width: value.width,
+            height: value.height,
+        }
+    }
+}
+
+impl From<extension::LlmToolUse> for llm_provider::ToolUse {
+    fn from(value: extension::LlmToolUse) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            input: value.input,
+            is_input_complete: value.is_input_complete,
+            thought_signature: value.thought_signature,
+        }
+    }
+}
+
+impl From<llm_provider::ToolUse> for extension::LlmToolUse {
+    fn from(value: llm_provider::ToolUse) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            input: value.input,
+            is_input_complete: value.is_input_complete,
+            thought_signature: value.thought_signature,
+        }
+    }
+}
+
+impl From<extension::LlmThinking> for llm_provider::ThinkingContent {
+    fn from(value: extension::LlmThinking) -> Self {
+        Self {
+            text: value.text,
+            signature: value.signature,
+        }
+    }
+}
+
+impl From<llm_provider::ThinkingContent> for extension::LlmThinking {
+    fn from(value: llm_provider::ThinkingContent) -> Self {
+        Self {
+            text: value.text,
+            signature: value.signature,
+        }
+    }
+}
+
+impl From<extension::LlmToolDefinition> for llm_provider::ToolDefinition {
+    fn from(value: extension::LlmToolDefinition) -> Self {
+        Self {
+            name: value.name,
+            description: value.description,
+            input_schema: value.input_schema,
+        }
+    }
+}
+
+impl From<llm_provider::CompletionEvent> for extension::LlmCompletionEvent {
+    fn from(value: llm_provider::CompletionEvent) -> Self {
+        match value {
+            llm_provider::CompletionEvent::Started => Self::Started,
+            llm_provider::CompletionEvent::Text(text) => Self::Text(text),
+            llm_provider::CompletionEvent::Thinking(thinking) => Self::Thinking(thinking.into()),
+            llm_provider::CompletionEvent::RedactedThinking(data) => Self::RedactedThinking(data),
+            llm_provider::CompletionEvent::ToolUse(tool_use) => Self::ToolUse(tool_use.into()),
+            llm_provider::CompletionEvent::Stop(reason) => Self::Stop(match reason {
+                llm_provider::StopReason::EndTurn => extension::LlmStopReason::EndTurn,
+                llm_provider::StopReason::MaxTokens => extension::LlmStopReason::MaxTokens,
+                llm_provider::StopReason::ToolUse => extension::LlmStopReason::ToolUse,
+                llm_provider::StopReason::Refusal => extension::LlmStopReason::Refusal,
+            }),
+            llm_provider::CompletionEvent::Usage(usage) => Self::Usage(extension::LlmTokenUsage {
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+                cache_creation_input_tokens: usage.cache_creation_input_tokens,
+                cache_read_input_tokens: usage.cache_read_input_tokens,
+            }),
+        }
+    }
+}
+
 impl HostKeyValueStore for WasmState {
     async fn insert(
         &mut self,
@@ -772,6 +1018,10 @@ impl nodejs::Host for WasmState {
 #[async_trait]
 impl lsp::Host for WasmState {}
 
+impl formatter::Host for WasmState {}
+
+impl linter::Host for WasmState {}
+
 impl From<::http_client::github::GithubRelease> for github::GithubRelease {
     fn from(value: ::http_client::github::GithubRelease) -> Self {
         Self {
@@ -887,6 +1137,68 @@ impl slash_command::Host for WasmState {}
 #[async_trait]
 impl context_server::Host for WasmState {}
 
+impl llm_provider::Host for WasmState {
+    async fn get_credential(&mut self, provider_id: String) -> wasmtime::Result<Option<String>> {
+        let Some(url) = self.llm_provider_credential_url(&provider_id) else {
+            return Ok(None);
+        };
+        let credential = self
+            .on_main_thread(move |cx| {
+                async move {
+                    let credentials_provider =
+                        cx.update(|cx| <dyn CredentialsProvider>::global(cx));
+                    let (_, password) = credentials_provider
+                        .read_credentials(&url, cx)
+                        .await
+                        .log_err()??;
+                    String::from_utf8(password).ok()
+                }
+                .boxed_local()
+            })
+            .await;
+        Ok(credential)
+    }
+
+    async fn store_credential(
+        &mut self,
+        provider_id: String,
+        value: String,
+    ) -> wasmtime::Result<Result<(), String>> {
+        let url = self.llm_provider_credential_url(&provider_id);
+        self.on_main_thread(move |cx| {
+            async move {
+                let url =
+                    url.context("language model provider not declared in extension manifest")?;
+                let credentials_provider = cx.update(|cx| <dyn CredentialsProvider>::global(cx));
+                credentials_provider
+                    .write_credentials(&url, "Bearer", value.as_bytes(), cx)
+                    .await
+            }
+            .boxed_local()
+        })
+        .await
+        .to_wasmtime_result()
+    }
+
+    async fn delete_credential(
+        &mut self,
+        provider_id: String,
+    ) -> wasmtime::Result<Result<(), String>> {
+        let url = self.llm_provider_credential_url(&provider_id);
+        self.on_main_thread(move |cx| {
+            async move {
+                let url =
+                    url.context("language model provider not declared in extension manifest")?;
+                let credentials_provider = cx.update(|cx| <dyn CredentialsProvider>::global(cx));
+                credentials_provider.delete_credentials(&url, cx).await
+            }
+            .boxed_local()
+        })
+        .await
+        .to_wasmtime_result()
+    }
+}
+
 impl dap::Host for WasmState {
     async fn resolve_tcp_template(
         &mut self,
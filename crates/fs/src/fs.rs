@@ -1,4 +1,5 @@
 pub mod fs_watcher;
+mod windowed_file;
 
 use parking_lot::Mutex;
 use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
@@ -49,6 +50,8 @@ use std::{
 use tempfile::TempDir;
 use text::LineEnding;
 
+pub use windowed_file::{ReadAt, WindowedFile};
+
 #[cfg(feature = "test-support")]
 mod fake_git_repo;
 #[cfg(feature = "test-support")]
@@ -57,7 +60,7 @@ use collections::{BTreeMap, btree_map};
 use fake_git_repo::FakeGitRepositoryState;
 #[cfg(feature = "test-support")]
 use git::{
-    repository::{InitialGraphCommitData, RepoPath, repo_path},
+    repository::{InitialGraphCommitData, MergeStages, RepoPath, repo_path},
     status::{FileStatus, StatusCode, TrackedStatus, UnmergedStatus},
 };
 #[cfg(feature = "test-support")]
@@ -127,6 +130,12 @@ pub trait Fs: Send + Sync {
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
     async fn write(&self, path: &Path, content: &[u8]) -> Result<()>;
+    /// Opens a file to be read in windows rather than loaded at once. Only its
+    /// metadata is read here; the reads happen when the windows are requested.
+    fn open_windowed(&self, path: &Path) -> Result<WindowedFile>;
+    /// Overwrites the given ranges of a file in place, without changing its
+    /// length or rewriting the bytes in between.
+    async fn overwrite_ranges(&self, path: &Path, ranges: Vec<(usize, Vec<u8>)>) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
     async fn is_file(&self, path: &Path) -> bool;
     async fn is_dir(&self, path: &Path) -> bool;
@@ -921,6 +930,17 @@ impl Fs for RealFs {
             .await
     }
 
+    fn open_windowed(&self, path: &Path) -> Result<WindowedFile> {
+        WindowedFile::open(path)
+    }
+
+    async fn overwrite_ranges(&self, path: &Path, ranges: Vec<(usize, Vec<u8>)>) -> Result<()> {
+        let path = path.to_owned();
+        self.executor
+            .spawn(async move { windowed_file::overwrite_file_ranges(&path, &ranges) })
+            .await
+    }
+
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf> {
         let path = path.to_owned();
         self.executor
@@ -1976,6 +1996,18 @@ impl FakeFs {
         .unwrap();
     }
 
+    pub fn set_merge_stages_for_repo(&self, dot_git: &Path, merge_stages: &[(&str, MergeStages)]) {
+        self.with_git_state(dot_git, true, |state| {
+            state.merge_stages.clear();
+            state.merge_stages.extend(
+                merge_stages
+                    .iter()
+                    .map(|(path, stages)| (repo_path(path), stages.clone())),
+            );
+        })
+        .unwrap();
+    }
+
     pub fn set_index_for_repo(&self, dot_git: &Path, index_state: &[(&str, String)]) {
         self.with_git_state(dot_git, true, |state| {
             state.index_contents.clear();
@@ -2355,6 +2387,29 @@ impl FileHandle for FakeHandle {
     }
 }
 
+/// A file opened in a [`FakeFs`], which reads the current contents of its
+/// path like an open file would.
+#[cfg(feature = "test-support")]
+struct FakeWindowedFile {
+    state: Arc<Mutex<FakeFsState>>,
+    path: PathBuf,
+}
+
+#[cfg(feature = "test-support")]
+impl ReadAt for FakeWindowedFile {
+    fn read_at(&self, bytes: &mut [u8], offset: usize) -> io::Result<usize> {
+        let mut state = self.state.lock();
+        let content = state
+            .entry(&self.path)
+            .and_then(|entry| entry.file_content(&self.path))
+            .map_err(io::Error::other)?;
+        let available = content.get(offset..).unwrap_or_default();
+        let len = bytes.len().min(available.len());
+        bytes[..len].copy_from_slice(&available[..len]);
+        Ok(len)
+    }
+}
+
 #[cfg(feature = "test-support")]
 #[async_trait::async_trait]
 impl Fs for FakeFs {
@@ -2689,6 +2744,44 @@ impl Fs for FakeFs {
         Ok(())
     }
 
+    fn open_windowed(&self, path: &Path) -> Result<WindowedFile> {
+        let path = normalize_path(path);
+        let mut state = self.state.lock();
+        let len = state.entry(&path)?.file_content(&path)?.len();
+        let file = FakeWindowedFile {
+            state: self.state.clone(),
+            path,
+        };
+        Ok(WindowedFile::new(Arc::new(file), len))
+    }
+
+    async fn overwrite_ranges(&self, path: &Path, ranges: Vec<(usize, Vec<u8>)>) -> Result<()> {
+        self.simulate_random_delay().await;
+        let path = normalize_path(path);
+        let mut state = self.state.lock();
+        let new_mtime = state.get_and_increment_mtime();
+        let FakeFsEntry::File {
+            mtime,
+            len,
+            content,
+            ..
+        } = state.entry(&path)?
+        else {
+            anyhow::bail!("not a file: {path:?}");
+        };
+        for (offset, bytes) in ranges {
+            let end = offset + bytes.len();
+            if content.len() < end {
+                content.resize(end, 0);
+            }
+            content[offset..end].copy_from_slice(&bytes);
+        }
+        *len = content.len() as u64;
+        *mtime = new_mtime;
+        state.emit_event([(path, Some(PathEventKind::Changed))]);
+        Ok(())
+    }
+
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf> {
         let path = normalize_path(path);
         self.simulate_random_delay().await;
@@ -0,0 +1,117 @@
+use std::{fs::File, io, ops::Range, path::Path, sync::Arc};
+
+use anyhow::{Context as _, Result};
+
+/// A file that supports reads at arbitrary offsets.
+pub trait ReadAt: Send + Sync {
+    /// Reads into `bytes` starting at `offset`, returning the number of bytes
+    /// read, which is zero at the end of the file.
+    fn read_at(&self, bytes: &mut [u8], offset: usize) -> io::Result<usize>;
+}
+
+/// A file read in windows with positioned reads, so that files of any size can
+/// be shown without loading them into memory. Unlike a memory map, reading
+/// never faults when another process truncates the file.
+#[derive(Clone)]
+pub struct WindowedFile {
+    file: Arc<dyn ReadAt>,
+    len: usize,
+}
+
+impl WindowedFile {
+    pub fn new(file: Arc<dyn ReadAt>, len: usize) -> Self {
+        Self { file, len }
+    }
+
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+        let len = file
+            .metadata()
+            .with_context(|| format!("reading metadata of {}", path.display()))?
+            .len();
+        Ok(Self::new(Arc::new(file), len as usize))
+    }
+
+    /// The length of the file when it was opened.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Reads the given range, which is cut short if the file was truncated
+    /// since it was opened.
+    pub fn read(&self, range: Range<usize>) -> Vec<u8> {
+        let end = range.end.min(self.len);
+        let mut bytes = vec![0; end.saturating_sub(range.start)];
+        let mut read_len = 0;
+        while read_len < bytes.len() {
+            match self
+                .file
+                .read_at(&mut bytes[read_len..], range.start + read_len)
+            {
+                Ok(0) => break,
+                Ok(len) => read_len += len,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => {
+                    log::error!("failed to read file: {error}");
+                    break;
+                }
+            }
+        }
+        bytes.truncate(read_len);
+        bytes
+    }
+}
+
+impl ReadAt for File {
+    #[cfg(unix)]
+    fn read_at(&self, bytes: &mut [u8], offset: usize) -> io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(self, bytes, offset as u64)
+    }
+
+    #[cfg(windows)]
+    fn read_at(&self, bytes: &mut [u8], offset: usize) -> io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(self, bytes, offset as u64)
+    }
+}
+
+/// Overwrites the given ranges of a file in place, without changing its length
+/// or rewriting the bytes in between.
+pub(crate) fn overwrite_file_ranges(path: &Path, ranges: &[(usize, Vec<u8>)]) -> Result<()> {
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .with_context(|| format!("opening {}", path.display()))?;
+    for (offset, bytes) in ranges {
+        let mut offset = *offset;
+        let mut bytes = bytes.as_slice();
+        while !bytes.is_empty() {
+            match write_at(&file, bytes, offset) {
+                Ok(0) => anyhow::bail!("failed to write to {}", path.display()),
+                Ok(len) => {
+                    bytes = &bytes[len..];
+                    offset += len;
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => {
+                    return Err(error).with_context(|| format!("writing {}", path.display()));
+                }
+            }
+        }
+    }
+    file.sync_all()
+        .with_context(|| format!("syncing {}", path.display()))
+}
+
+#[cfg(unix)]
+fn write_at(file: &File, bytes: &[u8], offset: usize) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::write_at(file, bytes, offset as u64)
+}
+
+#[cfg(windows)]
+fn write_at(file: &File, bytes: &[u8], offset: usize) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_write(file, bytes, offset as u64)
+}
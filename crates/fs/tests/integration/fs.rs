@@ -712,3 +712,45 @@ async fn test_realfs_watch_stress_reports_missed_paths(
         missed_paths.len()
     );
 }
+
+#[gpui::test]
+async fn test_realfs_windowed_file(executor: BackgroundExecutor) {
+    let fs = RealFs::new(None, executor);
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("app.log");
+    std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+    let file = fs.open_windowed(&path).unwrap();
+    assert_eq!(file.read(4..8), b"two\n");
+
+    // Reading past the end of a truncated file returns fewer bytes.
+    std::fs::write(&path, "one\n").unwrap();
+    assert_eq!(file.len(), 14);
+    assert_eq!(file.read(0..14), b"one\n");
+    assert!(file.read(8..14).is_empty());
+
+    std::fs::write(&path, [0u8; 8]).unwrap();
+    fs.overwrite_ranges(&path, vec![(1, vec![1, 2]), (6, vec![3])])
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), [0, 1, 2, 0, 0, 0, 3, 0]);
+}
+
+#[gpui::test]
+async fn test_fake_fs_windowed_file(executor: BackgroundExecutor) {
+    let fs = FakeFs::new(executor);
+    let path = Path::new(path!("/root/app.log"));
+    fs.insert_file(path, b"one\ntwo\nthree\n".to_vec()).await;
+    let file = fs.open_windowed(path).unwrap();
+    assert_eq!(file.read(4..8), b"two\n");
+
+    fs.insert_file(path, b"one\n".to_vec()).await;
+    assert_eq!(file.len(), 14);
+    assert_eq!(file.read(0..14), b"one\n");
+    assert!(file.read(8..14).is_empty());
+
+    fs.insert_file(path, vec![0; 8]).await;
+    fs.overwrite_ranges(path, vec![(1, vec![1, 2]), (6, vec![3])])
+        .await
+        .unwrap();
+    assert_eq!(fs.read_file_sync(path).unwrap(), [0, 1, 2, 0, 0, 0, 3, 0]);
+}
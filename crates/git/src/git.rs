@@ -49,6 +49,8 @@ actions!(
         Blame,
         /// Shows the git history for the current file.
         FileHistory,
+        /// Shows the uncommitted changes to the current Jupyter notebook cell by cell.
+        DiffNotebook,
         /// Stages the current file.
         StageFile,
         /// Unstages the current file.
@@ -359,6 +359,17 @@ pub struct CommitSummary {
     pub has_parent: bool,
 }
 
+/// The versions of a conflicted file that git records in the index during a merge.
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct MergeStages {
+    /// Stage 1, the merge base. Missing when both sides added the file.
+    pub base: Option<String>,
+    /// Stage 2, our side. Missing when we deleted the file.
+    pub ours: Option<String>,
+    /// Stage 3, their side. Missing when they deleted the file.
+    pub theirs: Option<String>,
+}
+
 #[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
 pub struct CommitDetails {
     pub sha: SharedString,
@@ -635,6 +646,10 @@ pub trait GitRepository: Send + Sync {
     ///
     /// Also returns `None` for symlinks.
     fn load_committed_text(&self, path: RepoPath) -> BoxFuture<'_, Option<String>>;
+
+    /// Returns the base, ours and theirs versions of a conflicted file from stages 1, 2 and 3 of
+    /// the index, or None if the index has no conflict stages for the given path.
+    fn load_merge_stages(&self, path: RepoPath) -> BoxFuture<'_, Result<Option<MergeStages>>>;
     fn load_blob_content(&self, oid: Oid) -> BoxFuture<'_, Result<String>>;
 
     fn set_index_text(
@@ -1327,6 +1342,43 @@ impl GitRepository for RealGitRepository {
             .boxed()
     }
 
+    fn load_merge_stages(&self, path: RepoPath) -> BoxFuture<'_, Result<Option<MergeStages>>> {
+        let repo = self.repository.clone();
+        self.executor
+            .spawn(async move {
+                // git2 unwraps internally on empty paths or `.`
+                if path.is_empty() {
+                    bail!("empty path has no merge stages");
+                }
+                let repo = repo.lock();
+                let mut index = repo.index()?;
+                index.read(false)?;
+                let [base, ours, theirs] = [1, 2, 3].map(|stage| {
+                    index
+                        .get_path(path.as_std_path(), stage)
+                        .map(|entry| entry.id)
+                });
+                if base.is_none() && ours.is_none() && theirs.is_none() {
+                    return Ok(None);
+                }
+                let load = |oid: Option<git2::Oid>| -> Result<Option<String>> {
+                    let Some(oid) = oid else {
+                        return Ok(None);
+                    };
+                    let content = repo.find_blob(oid)?.content().to_owned();
+                    Ok(Some(
+                        String::from_utf8(content).context("conflicted file is not UTF-8")?,
+                    ))
+                };
+                Ok(Some(MergeStages {
+                    base: load(base)?,
+                    ours: load(ours)?,
+                    theirs: load(theirs)?,
+                }))
+            })
+            .boxed()
+    }
+
     fn load_blob_content(&self, oid: Oid) -> BoxFuture<'_, Result<String>> {
         let repo = self.repository.clone();
         self.executor
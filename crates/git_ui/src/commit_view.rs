@@ -21,6 +21,7 @@ use language::{
 };
 use multi_buffer::PathKey;
 use project::{Project, WorktreeId, git_store::Repository};
+use settings::Settings as _;
 use std::{
     any::{Any, TypeId},
     collections::HashSet,
@@ -42,6 +43,8 @@ use workspace::{
 
 use crate::commit_tooltip::CommitAvatar;
 use crate::git_panel::GitPanel;
+use crate::git_panel_settings::GitPanelSettings;
+use crate::notebook_diff::{NotebookPair, is_notebook};
 
 actions!(git, [ApplyCurrentStash, PopCurrentStash, DropCurrentStash,]);
 
@@ -271,6 +274,23 @@ impl CommitView {
                     raw_new_text
                 };
                 let old_text = if is_binary { None } else { raw_old_text };
+                let notebook = if !is_binary && is_notebook(file.path.as_std_path()) {
+                    NotebookPair::parse(
+                        old_text.as_deref(),
+                        (!is_deleted).then_some(new_text.as_str()),
+                    )
+                } else {
+                    None
+                };
+                let (old_text, new_text, notebook_language) = if let Some(notebook) = notebook {
+                    let language = notebook.load_language(&language_registry).await;
+                    let outputs =
+                        cx.update(|cx| GitPanelSettings::get_global(cx).notebook_diff_outputs);
+                    let rendered = notebook.render(language.as_ref(), outputs);
+                    (rendered.old, rendered.new, language)
+                } else {
+                    (old_text, new_text, None)
+                };
                 let worktree_id = repository_clone
                     .update(cx, |repository, cx| {
                         repository
@@ -296,6 +316,11 @@ impl CommitView {
                 }) as Arc<dyn language::File>;
 
                 let buffer = build_buffer(new_text, file, &language_registry, cx).await?;
+                if let Some(language) = notebook_language {
+                    buffer.update(cx, |buffer, cx| {
+                        buffer.set_language_async(Some(language), cx)
+                    });
+                }
                 let buffer_id = cx.update(|cx| buffer.read(cx).remote_id());
 
                 let status_code = if is_created {
@@ -1134,3 +1159,104 @@ fn stash_matches_index(sha: &str, stash_index: usize, repo: &Repository) -> bool
         .map(|entry| entry.oid.to_string() == sha)
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::repository::{CommitFile, repo_path};
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+    use workspace::MultiWorkspace;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            editor::init(cx);
+            crate::init(cx);
+        });
+    }
+
+    fn notebook(source: &str) -> String {
+        json!({
+            "nbformat": 4,
+            "nbformat_minor": 5,
+            "metadata": {},
+            "cells": [
+                {
+                    "id": "assign",
+                    "cell_type": "code",
+                    "execution_count": 1,
+                    "metadata": {},
+                    "source": [source],
+                    "outputs": [],
+                },
+            ],
+        })
+        .to_string()
+    }
+
+    #[gpui::test]
+    async fn test_stash_renders_notebooks(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "analysis.ipynb": notebook("x = 1"),
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        cx.run_until_parked();
+        let repository = project
+            .read_with(cx, |project, cx| project.active_repository(cx))
+            .unwrap();
+
+        let commit = CommitDetails {
+            sha: "5e1f0a7c".into(),
+            message: "On main: try another value".into(),
+            ..Default::default()
+        };
+        let commit_diff = CommitDiff {
+            files: vec![CommitFile {
+                path: repo_path("analysis.ipynb"),
+                old_text: Some(notebook("x = 1")),
+                new_text: Some(notebook("x = 2")),
+                is_binary: false,
+            }],
+        };
+
+        let (_multi_workspace, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let commit_view = cx.new_window_entity(|window, cx| {
+            CommitView::new(
+                commit,
+                commit_diff,
+                repository,
+                project.clone(),
+                Some(0),
+                window,
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        commit_view.read_with(cx, |commit_view, cx| {
+            assert_eq!(commit_view.stash, Some(0));
+            let text = commit_view.editor.read(cx).text(cx);
+            assert!(
+                text.contains("# %% id=\"assign\"\nx = 2\n"),
+                "notebook should be rendered cell by cell: {text:?}"
+            );
+            assert!(!text.contains("\"cell_type\""), "{text:?}");
+            assert_eq!(commit_view.calculate_changed_lines(cx), (1, 1));
+        });
+    }
+}
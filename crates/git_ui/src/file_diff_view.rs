@@ -1,15 +1,16 @@
 //! FileDiffView provides a UI for displaying differences between two buffers.
 
 use anyhow::Result;
-use buffer_diff::BufferDiff;
+use buffer_diff::{BufferDiff, BufferDiffEvent};
 use editor::{Editor, EditorEvent, MultiBuffer};
 use futures::{FutureExt, select_biased};
 use gpui::{
-    AnyElement, App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, FocusHandle,
-    Focusable, Font, IntoElement, Render, Task, WeakEntity, Window,
+    AnyElement, App, AppContext as _, AsyncApp, AsyncWindowContext, Context, Entity, EventEmitter,
+    FocusHandle, Focusable, Font, IntoElement, Render, Task, WeakEntity, Window,
 };
-use language::{Buffer, HighlightedText, LanguageRegistry};
-use project::Project;
+use language::{Buffer, HighlightedText, Language, LanguageRegistry};
+use project::{Project, ProjectPath};
+use settings::SettingsStore;
 use std::{
     any::{Any, TypeId},
     path::PathBuf,
@@ -25,14 +26,34 @@ use workspace::{
     searchable::SearchableItemHandle,
 };
 
+use crate::notebook_diff::{RenderedNotebook, is_notebook};
+
 pub struct FileDiffView {
     editor: Entity<Editor>,
-    old_buffer: Entity<Buffer>,
+    base: DiffBase,
     new_buffer: Entity<Buffer>,
+    notebook: Option<RenderedNotebook>,
     buffer_changes_tx: watch::Sender<()>,
     _recalculate_diff_task: Task<Result<()>>,
 }
 
+/// What the new buffer is compared against.
+enum DiffBase {
+    /// Another file's buffer.
+    Buffer(Entity<Buffer>),
+    /// The committed text of the new buffer's file, taken from its uncommitted diff.
+    Head(Entity<BufferDiff>),
+}
+
+impl DiffBase {
+    fn text(&self, cx: &App) -> Option<String> {
+        match self {
+            DiffBase::Buffer(buffer) => Some(buffer.read(cx).text()),
+            DiffBase::Head(diff) => diff.read(cx).base_text_string(cx),
+        }
+    }
+}
+
 const RECALCULATE_DIFF_DEBOUNCE: Duration = Duration::from_millis(250);
 
 impl FileDiffView {
@@ -52,42 +73,101 @@ impl FileDiffView {
             let new_buffer = project
                 .update(cx, |project, cx| project.open_local_buffer(&new_path, cx))
                 .await?;
-            let languages = project.update(cx, |project, _| project.languages().clone());
-
-            let buffer_diff = build_buffer_diff(&old_buffer, &new_buffer, languages, cx).await?;
-
-            workspace.update_in(cx, |workspace, window, cx| {
-                let diff_view = cx.new(|cx| {
-                    FileDiffView::new(
-                        old_buffer,
-                        new_buffer,
-                        buffer_diff,
-                        project.clone(),
-                        window,
-                        cx,
-                    )
-                });
 
-                let pane = workspace.active_pane();
-                pane.update(cx, |pane, cx| {
-                    pane.add_item(Box::new(diff_view.clone()), true, true, None, window, cx);
-                });
+            Self::add_to_workspace(
+                DiffBase::Buffer(old_buffer),
+                new_buffer,
+                project,
+                workspace,
+                cx,
+            )
+            .await
+        })
+    }
 
-                diff_view
-            })
+    /// Opens a diff of the uncommitted changes to the file at `path`, rendering
+    /// Jupyter notebooks cell by cell.
+    pub fn open_uncommitted(
+        path: ProjectPath,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        window.spawn(cx, async move |cx| {
+            let project = workspace.update(cx, |workspace, _| workspace.project().clone())?;
+            let new_buffer = project
+                .update(cx, |project, cx| project.open_buffer(path, cx))
+                .await?;
+            let uncommitted_diff = project
+                .update(cx, |project, cx| {
+                    project.open_uncommitted_diff(new_buffer.clone(), cx)
+                })
+                .await?;
+
+            Self::add_to_workspace(
+                DiffBase::Head(uncommitted_diff),
+                new_buffer,
+                project,
+                workspace,
+                cx,
+            )
+            .await
         })
     }
 
-    pub fn new(
-        old_buffer: Entity<Buffer>,
+    async fn add_to_workspace(
+        base: DiffBase,
         new_buffer: Entity<Buffer>,
+        project: Entity<Project>,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<Entity<Self>> {
+        let languages = project.update(cx, |project, _| project.languages().clone());
+        let notebook = render_notebook(&base, &new_buffer, &languages, cx).await;
+        let (old_text, displayed_buffer) = match &notebook {
+            Some(notebook) => (notebook.old_text.clone(), notebook.new_buffer.clone()),
+            None => (cx.update(|_, cx| base.text(cx))?, new_buffer.clone()),
+        };
+
+        let buffer_diff = build_buffer_diff(old_text, &displayed_buffer, languages, cx).await?;
+
+        workspace.update_in(cx, |workspace, window, cx| {
+            let diff_view = cx.new(|cx| {
+                FileDiffView::new(
+                    base,
+                    new_buffer,
+                    notebook,
+                    buffer_diff,
+                    project.clone(),
+                    window,
+                    cx,
+                )
+            });
+
+            let pane = workspace.active_pane();
+            pane.update(cx, |pane, cx| {
+                pane.add_item(Box::new(diff_view.clone()), true, true, None, window, cx);
+            });
+
+            diff_view
+        })
+    }
+
+    fn new(
+        base: DiffBase,
+        new_buffer: Entity<Buffer>,
+        notebook: Option<RenderedNotebook>,
         diff: Entity<BufferDiff>,
         project: Entity<Project>,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
+        let displayed_buffer = notebook
+            .as_ref()
+            .map_or(&new_buffer, |notebook| &notebook.new_buffer)
+            .clone();
         let multibuffer = cx.new(|cx| {
-            let mut multibuffer = MultiBuffer::singleton(new_buffer.clone(), cx);
+            let mut multibuffer = MultiBuffer::singleton(displayed_buffer, cx);
             multibuffer.add_diff(diff.clone(), cx);
             multibuffer
         });
@@ -106,7 +186,19 @@ impl FileDiffView {
 
         let (buffer_changes_tx, mut buffer_changes_rx) = watch::channel(());
 
-        for buffer in [&old_buffer, &new_buffer] {
+        let old_buffer = match &base {
+            DiffBase::Buffer(old_buffer) => Some(old_buffer),
+            DiffBase::Head(uncommitted_diff) => {
+                cx.subscribe(uncommitted_diff, |this, _, event, _| {
+                    if let BufferDiffEvent::DiffChanged(_) = event {
+                        this.buffer_changes_tx.send(()).ok();
+                    }
+                })
+                .detach();
+                None
+            }
+        };
+        for buffer in old_buffer.into_iter().chain([&new_buffer]) {
             cx.subscribe(buffer, move |this, _, event, _| match event {
                 language::BufferEvent::Edited { .. }
                 | language::BufferEvent::LanguageChanged(_)
@@ -117,12 +209,20 @@ impl FileDiffView {
             })
             .detach();
         }
+        if notebook.is_some() {
+            // Re-render when the notebook outputs setting changes.
+            cx.observe_global::<SettingsStore>(|this, _| {
+                this.buffer_changes_tx.send(()).ok();
+            })
+            .detach();
+        }
 
         Self {
             editor,
             buffer_changes_tx,
-            old_buffer,
+            base,
             new_buffer,
+            notebook,
             _recalculate_diff_task: cx.spawn(async move |this, cx| {
                 while buffer_changes_rx.recv().await.is_ok() {
                     loop {
@@ -138,16 +238,12 @@ impl FileDiffView {
                     }
 
                     log::trace!("start recalculating");
-                    let (old_snapshot, new_snapshot) = this.update(cx, |this, cx| {
-                        (
-                            this.old_buffer.read(cx).snapshot(),
-                            this.new_buffer.read(cx).snapshot(),
-                        )
-                    })?;
+                    let (old_text, old_language, new_snapshot) =
+                        this.update(cx, |this, cx| this.texts_to_diff(cx))?;
                     diff.update(cx, |diff, cx| {
                         diff.set_base_text(
-                            Some(old_snapshot.text().as_str().into()),
-                            old_snapshot.language().cloned(),
+                            old_text.map(|text| text.as_str().into()),
+                            old_language,
                             new_snapshot.text.clone(),
                             cx,
                         )
@@ -160,16 +256,67 @@ impl FileDiffView {
             }),
         }
     }
+
+    fn texts_to_diff(
+        &mut self,
+        cx: &mut Context<Self>,
+    ) -> (
+        Option<String>,
+        Option<Arc<Language>>,
+        language::BufferSnapshot,
+    ) {
+        let old_text = self.base.text(cx);
+        if let Some(notebook) = &mut self.notebook {
+            let new_text = self.new_buffer.read(cx).text();
+            notebook.update(old_text, &new_text, cx);
+            let snapshot = notebook.new_buffer.read(cx).snapshot();
+            return (
+                notebook.old_text.clone(),
+                snapshot.language().cloned(),
+                snapshot,
+            );
+        }
+
+        let new_snapshot = self.new_buffer.read(cx).snapshot();
+        let old_language = match &self.base {
+            DiffBase::Buffer(old_buffer) => old_buffer.read(cx).language().cloned(),
+            DiffBase::Head(_) => new_snapshot.language().cloned(),
+        };
+        (old_text, old_language, new_snapshot)
+    }
+}
+
+/// Renders both sides cell by cell if they're Jupyter notebooks that parse.
+async fn render_notebook(
+    base: &DiffBase,
+    new_buffer: &Entity<Buffer>,
+    language_registry: &Arc<LanguageRegistry>,
+    cx: &mut AsyncApp,
+) -> Option<RenderedNotebook> {
+    let (old_text, new_text) = cx.update(|cx| {
+        let is_notebook_buffer = |buffer: &Entity<Buffer>| {
+            buffer
+                .read(cx)
+                .file()
+                .is_some_and(|file| is_notebook(file.path().as_std_path()))
+        };
+        let compares_notebooks = is_notebook_buffer(new_buffer)
+            && match base {
+                DiffBase::Buffer(old_buffer) => is_notebook_buffer(old_buffer),
+                DiffBase::Head(_) => true,
+            };
+        compares_notebooks.then(|| (base.text(cx), new_buffer.read(cx).text()))
+    })?;
+    RenderedNotebook::new(old_text, new_text, language_registry, cx).await
 }
 
 #[ztracing::instrument(skip_all)]
-async fn build_buffer_diff(
-    old_buffer: &Entity<Buffer>,
+pub(crate) async fn build_buffer_diff(
+    old_text: Option<String>,
     new_buffer: &Entity<Buffer>,
     language_registry: Arc<LanguageRegistry>,
     cx: &mut AsyncApp,
 ) -> Result<Entity<BufferDiff>> {
-    let old_buffer_snapshot = old_buffer.read_with(cx, |buffer, _| buffer.snapshot());
     let new_buffer_snapshot = new_buffer.read_with(cx, |buffer, _| buffer.snapshot());
 
     let diff = cx.new(|cx| BufferDiff::new(&new_buffer_snapshot.text, cx));
@@ -178,7 +325,7 @@ async fn build_buffer_diff(
         .update(cx, |diff, cx| {
             diff.update_diff(
                 new_buffer_snapshot.text.clone(),
-                Some(old_buffer_snapshot.text().into()),
+                old_text.map(|text| text.into()),
                 Some(true),
                 new_buffer_snapshot.language().cloned(),
                 cx,
@@ -239,7 +386,10 @@ impl Item for FileDiffView {
                 })
                 .unwrap_or_else(|| "untitled".into())
         };
-        let old_filename = title_text(&self.old_buffer);
+        let old_filename = match &self.base {
+            DiffBase::Buffer(old_buffer) => title_text(old_buffer),
+            DiffBase::Head(_) => "HEAD".into(),
+        };
         let new_filename = title_text(&self.new_buffer);
 
         format!("{old_filename} ↔ {new_filename}").into()
@@ -253,7 +403,10 @@ impl Item for FileDiffView {
                 .map(|file| file.full_path(cx).compact().to_string_lossy().into_owned())
                 .unwrap_or_else(|| "untitled".into())
         };
-        let old_path = path(&self.old_buffer);
+        let old_path = match &self.base {
+            DiffBase::Buffer(old_buffer) => path(old_buffer),
+            DiffBase::Head(_) => "HEAD".into(),
+        };
         let new_path = path(&self.new_buffer);
 
         Some(format!("{old_path} ↔ {new_path}").into())
@@ -585,4 +738,93 @@ mod tests {
             assert!(!buffer.is_dirty(), "Buffer should not be dirty after save");
         });
     }
+
+    #[gpui::test]
+    async fn test_notebook_diff_view(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings
+                        .git_panel
+                        .get_or_insert_default()
+                        .notebook_diff_outputs = Some(settings::NotebookDiffOutputs::Hidden);
+                })
+            });
+        });
+
+        let notebook = |first_source: &str, output: &str| {
+            serde_json::json!({
+                "nbformat": 4,
+                "nbformat_minor": 5,
+                "metadata": {},
+                "cells": [
+                    {
+                        "id": "assign",
+                        "cell_type": "code",
+                        "execution_count": 1,
+                        "metadata": {},
+                        "source": [first_source],
+                        "outputs": [],
+                    },
+                    {
+                        "id": "print",
+                        "cell_type": "code",
+                        "execution_count": 2,
+                        "metadata": {},
+                        "source": ["print(x)"],
+                        "outputs": [
+                            {"output_type": "stream", "name": "stdout", "text": [output]}
+                        ],
+                    },
+                ],
+            })
+            .to_string()
+        };
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/test"),
+            serde_json::json!({
+                "old.ipynb": notebook("x = 1", "1\n"),
+                "new.ipynb": notebook("x = 2", "2\n"),
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), [path!("/test").as_ref()], cx).await;
+
+        let (multi_workspace, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let workspace = multi_workspace.read_with(cx, |mw, _| mw.workspace().clone());
+
+        let diff_view = workspace
+            .update_in(cx, |workspace, window, cx| {
+                FileDiffView::open(
+                    path!("/test/old.ipynb").into(),
+                    path!("/test/new.ipynb").into(),
+                    workspace.weak_handle(),
+                    window,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+
+        // Only the changed source line differs, not the outputs or the JSON around it.
+        assert_state_with_diff(
+            &diff_view.read_with(cx, |diff_view, _| diff_view.editor.clone()),
+            cx,
+            &unindent(
+                r#"
+                  ˇ# %% id="assign"
+                - x = 1
+                + x = 2
+
+                  # %% id="print"
+                  print(x)
+                "#,
+            ),
+        );
+    }
 }
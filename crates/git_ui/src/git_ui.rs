@@ -25,7 +25,7 @@ use ui::prelude::*;
 use workspace::{ModalView, Workspace, notifications::DetachAndPromptErr};
 use zed_actions;
 
-use crate::{git_panel::GitPanel, text_diff_view::TextDiffView};
+use crate::{file_diff_view::FileDiffView, git_panel::GitPanel, text_diff_view::TextDiffView};
 
 mod askpass_modal;
 pub mod branch_picker;
@@ -38,7 +38,9 @@ pub mod file_history_view;
 pub mod git_panel;
 mod git_panel_settings;
 pub mod git_picker;
+pub mod merge_editor;
 pub mod multi_diff_view;
+mod notebook_diff;
 pub mod picker_prompt;
 pub mod project_diff;
 pub(crate) mod remote_output;
@@ -63,6 +65,7 @@ pub fn init(cx: &mut App) {
         repository_selector::register(workspace);
         git_picker::register(workspace);
         conflict_view::register_conflict_notification(workspace, cx);
+        merge_editor::register(workspace);
 
         let project = workspace.project().read(cx);
         if project.is_read_only(cx) {
@@ -213,6 +216,22 @@ pub fn init(cx: &mut App) {
                 };
             },
         );
+        workspace.register_action(|workspace, _: &git::DiffNotebook, window, cx| {
+            let Some(active_item) = workspace.active_item(cx) else {
+                return;
+            };
+            let project_path = match active_item.downcast::<ProjectDiff>() {
+                Some(project_diff) => project_diff.read(cx).active_path(cx),
+                None => active_item.project_path(cx),
+            };
+            let Some(project_path) = project_path
+                .filter(|project_path| notebook_diff::is_notebook(project_path.path.as_std_path()))
+            else {
+                return;
+            };
+            FileDiffView::open_uncommitted(project_path, workspace.weak_handle(), window, cx)
+                .detach_and_log_err(cx);
+        });
         workspace.register_action(|workspace, _: &git::FileHistory, window, cx| {
             let Some(active_item) = workspace.active_item(cx) else {
                 return;
@@ -0,0 +1,1030 @@
+//! MergeEditor resolves the merge conflicts in a file using a three-way view.
+//!
+//! The base, ours and theirs versions of the file are loaded from the conflict stages of the
+//! repository's index, falling back to reconstructing them from the file's conflict markers
+//! when the index has no stages for it. They are shown side by side, with ours and theirs diffed against the base. Below them, the file
+//! itself is shown in an editable result pane, where conflicts can be resolved by hand or by
+//! accepting one or both sides.
+
+use std::{
+    any::{Any, TypeId},
+    ops::Range,
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use buffer_diff::BufferDiff;
+use editor::{Editor, EditorEvent, MultiBuffer, SelectionEffects, scroll::Autoscroll};
+use git::repository::MergeStages;
+use gpui::{
+    AnyElement, App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, FocusHandle,
+    Focusable, Font, IntoElement, Render, Task, WeakEntity, Window, actions,
+};
+use language::{
+    Anchor, Buffer, BufferEvent, BufferSnapshot, Capability, HighlightedText, Language,
+    LanguageRegistry, OffsetRangeExt as _, ToOffset as _, ToPoint as _,
+};
+use project::{ConflictRegion, ConflictSet, Project};
+use ui::{Divider, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{
+    Item, ItemHandle as _, ItemNavHistory, ToolbarItemLocation, Workspace,
+    item::{ItemEvent, SaveOptions, TabContentParams},
+    notifications::DetachAndPromptErr,
+    searchable::SearchableItemHandle,
+};
+
+actions!(
+    git,
+    [
+        /// Opens the merge editor for the conflicted file in the active editor.
+        OpenMergeEditor,
+    ]
+);
+
+actions!(
+    merge_editor,
+    [
+        /// Resolves the current conflict by keeping our side.
+        AcceptOurs,
+        /// Resolves the current conflict by keeping their side.
+        AcceptTheirs,
+        /// Resolves the current conflict by keeping both sides, ours first.
+        AcceptBoth,
+        /// Moves to the next unresolved conflict.
+        NextConflict,
+        /// Moves to the previous unresolved conflict.
+        PreviousConflict,
+        /// Saves the file and stages it, marking its conflicts as resolved.
+        MarkResolved,
+    ]
+);
+
+pub struct MergeEditor {
+    project: Entity<Project>,
+    result_buffer: Entity<Buffer>,
+    result_editor: Entity<Editor>,
+    base_editor: Entity<Editor>,
+    ours_editor: Entity<Editor>,
+    theirs_editor: Entity<Editor>,
+    ours_branch_name: SharedString,
+    theirs_branch_name: SharedString,
+    /// The conflicts that remain in the result, as of its last parse.
+    conflicts: Arc<[ConflictRegion]>,
+    staged: bool,
+    _reparse_conflicts: Task<()>,
+}
+
+/// The versions of a conflicted file on either side of a merge.
+struct MergeSides {
+    base: String,
+    ours: String,
+    theirs: String,
+    conflicts: Arc<[ConflictRegion]>,
+}
+
+impl MergeSides {
+    /// Takes the sides from the index's conflict stages. A side that is missing from the index,
+    /// because the file was added or deleted on one side of the merge, is shown as empty.
+    fn from_stages(stages: MergeStages, snapshot: &BufferSnapshot) -> Self {
+        Self {
+            base: stages.base.unwrap_or_default(),
+            ours: stages.ours.unwrap_or_default(),
+            theirs: stages.theirs.unwrap_or_default(),
+            conflicts: ConflictSet::parse(snapshot).conflicts,
+        }
+    }
+
+    /// Replaces each conflict in `snapshot` with its base, ours and theirs sections in turn. This
+    /// is used when the index has no conflict stages for the file.
+    ///
+    /// The base section is only present in conflicts written with git's `diff3` or `zdiff3`
+    /// conflict styles. Otherwise, the base version of a conflict is empty, and everything on
+    /// either side of it appears as an addition.
+    fn new(snapshot: &BufferSnapshot) -> Self {
+        let conflicts = ConflictSet::parse(snapshot).conflicts;
+        let text = |range: Range<usize>| snapshot.text_for_range(range).collect::<String>();
+        let mut sides = Self {
+            base: String::new(),
+            ours: String::new(),
+            theirs: String::new(),
+            conflicts: conflicts.clone(),
+        };
+        let mut offset = 0;
+        for conflict in conflicts.iter() {
+            let range = conflict.range.to_offset(snapshot);
+            let unchanged = text(offset..range.start);
+            sides.base.push_str(&unchanged);
+            sides.ours.push_str(&unchanged);
+            sides.theirs.push_str(&unchanged);
+            if let Some(base) = &conflict.base {
+                sides.base.push_str(&text(base.to_offset(snapshot)));
+            }
+            sides
+                .ours
+                .push_str(&text(conflict.ours.to_offset(snapshot)));
+            sides
+                .theirs
+                .push_str(&text(conflict.theirs.to_offset(snapshot)));
+            offset = range.end;
+        }
+        let unchanged = text(offset..snapshot.len());
+        sides.base.push_str(&unchanged);
+        sides.ours.push_str(&unchanged);
+        sides.theirs.push_str(&unchanged);
+        sides
+    }
+}
+
+pub(crate) fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, _: &OpenMergeEditor, window, cx| {
+        let Some(buffer) = workspace
+            .active_item_as::<Editor>(cx)
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+        else {
+            return;
+        };
+        MergeEditor::open(buffer, workspace.weak_handle(), window, cx).detach_and_prompt_err(
+            "Failed to open merge editor",
+            window,
+            cx,
+            |_, _, _| None,
+        );
+    });
+}
+
+impl MergeEditor {
+    pub fn open(
+        buffer: Entity<Buffer>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        window.spawn(cx, async move |cx| {
+            let project = workspace.update(cx, |workspace, _| workspace.project().clone())?;
+            let languages = project.read_with(cx, |project, _| project.languages().clone());
+            let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+            let language = snapshot.language().cloned();
+            let stages = project.update(cx, |project, cx| {
+                let (repository, repo_path) = project
+                    .git_store()
+                    .read(cx)
+                    .repository_and_path_for_buffer_id(snapshot.remote_id(), cx)?;
+                Some(repository.update(cx, |repository, _| repository.load_merge_stages(repo_path)))
+            });
+            let stages = match stages {
+                Some(stages) => stages
+                    .await
+                    .context("loading merge stages")
+                    .and_then(|stages| stages)
+                    .log_err()
+                    .flatten(),
+                None => None,
+            };
+            let sides = cx
+                .background_spawn(async move {
+                    match stages {
+                        Some(stages) => MergeSides::from_stages(stages, &snapshot),
+                        None => MergeSides::new(&snapshot),
+                    }
+                })
+                .await;
+            anyhow::ensure!(
+                !sides.conflicts.is_empty(),
+                "The file has no merge conflicts"
+            );
+
+            let [base_buffer, ours_buffer, theirs_buffer] =
+                [&sides.base, &sides.ours, &sides.theirs].map(|text| {
+                    cx.new(|cx| {
+                        let mut buffer = Buffer::local(text.as_str(), cx);
+                        buffer.set_language_registry(languages.clone());
+                        buffer.set_language(language.clone(), cx);
+                        buffer.set_capability(Capability::ReadOnly, cx);
+                        buffer
+                    })
+                });
+            let ours_diff = build_side_diff(&ours_buffer, &sides.base, &languages, cx).await;
+            let theirs_diff = build_side_diff(&theirs_buffer, &sides.base, &languages, cx).await;
+
+            workspace.update_in(cx, |workspace, window, cx| {
+                let merge_editor = cx.new(|cx| {
+                    let base_editor = side_editor(base_buffer, None, window, cx);
+                    let ours_editor = side_editor(ours_buffer, Some(ours_diff), window, cx);
+                    let theirs_editor = side_editor(theirs_buffer, Some(theirs_diff), window, cx);
+                    MergeEditor::new(
+                        buffer,
+                        sides.conflicts,
+                        base_editor,
+                        ours_editor,
+                        theirs_editor,
+                        project,
+                        window,
+                        cx,
+                    )
+                });
+                workspace.active_pane().update(cx, |pane, cx| {
+                    pane.add_item(Box::new(merge_editor.clone()), true, true, None, window, cx);
+                });
+                merge_editor
+            })
+        })
+    }
+
+    fn new(
+        result_buffer: Entity<Buffer>,
+        conflicts: Arc<[ConflictRegion]>,
+        base_editor: Entity<Editor>,
+        ours_editor: Entity<Editor>,
+        theirs_editor: Entity<Editor>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let result_editor = cx
+            .new(|cx| Editor::for_buffer(result_buffer.clone(), Some(project.clone()), window, cx));
+        cx.subscribe(&result_editor, |_, _, event: &EditorEvent, cx| {
+            cx.emit(event.clone())
+        })
+        .detach();
+        cx.subscribe(&result_buffer, |this, _, event, cx| {
+            if let BufferEvent::Edited { .. } | BufferEvent::Reloaded = event {
+                this.reparse_conflicts(cx);
+            }
+        })
+        .detach();
+
+        let (ours_branch_name, theirs_branch_name) = conflicts
+            .first()
+            .map(|conflict| {
+                (
+                    conflict.ours_branch_name.clone(),
+                    conflict.theirs_branch_name.clone(),
+                )
+            })
+            .unwrap_or_else(|| ("HEAD".into(), "Origin".into()));
+
+        let mut this = Self {
+            project,
+            result_buffer,
+            result_editor,
+            base_editor,
+            ours_editor,
+            theirs_editor,
+            ours_branch_name,
+            theirs_branch_name,
+            conflicts,
+            staged: false,
+            _reparse_conflicts: Task::ready(()),
+        };
+        this.select_conflict(0, window, cx);
+        this
+    }
+
+    fn reparse_conflicts(&mut self, cx: &mut Context<Self>) {
+        let snapshot = self.result_buffer.read(cx).text_snapshot();
+        self._reparse_conflicts = cx.spawn(async move |this, cx| {
+            let conflicts = cx
+                .background_spawn(async move { ConflictSet::parse(&snapshot).conflicts })
+                .await;
+            this.update(cx, |this, cx| {
+                this.conflicts = conflicts;
+                this.staged = false;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    pub fn remaining_conflicts(&self) -> usize {
+        self.conflicts.len()
+    }
+
+    fn cursor_offset(&self, cx: &App) -> usize {
+        let cursor = self
+            .result_editor
+            .read(cx)
+            .selections
+            .newest_anchor()
+            .head()
+            .text_anchor;
+        cursor.to_offset(&self.result_buffer.read(cx).snapshot())
+    }
+
+    /// Returns the index of the conflict containing the cursor or, failing that, of the first
+    /// conflict after it.
+    fn current_conflict_ix(&self, cx: &App) -> Option<usize> {
+        let snapshot = self.result_buffer.read(cx).snapshot();
+        let cursor = self.cursor_offset(cx);
+        self.conflicts
+            .iter()
+            .position(|conflict| conflict.range.end.to_offset(&snapshot) >= cursor)
+    }
+
+    fn select_conflict(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(conflict) = self.conflicts.get(ix) else {
+            return;
+        };
+        let point = conflict
+            .range
+            .start
+            .to_point(&self.result_buffer.read(cx).snapshot());
+        self.result_editor.update(cx, |editor, cx| {
+            editor.change_selections(
+                SelectionEffects::scroll(Autoscroll::center()),
+                window,
+                cx,
+                |selections| selections.select_ranges([point..point]),
+            );
+        });
+    }
+
+    fn next_conflict(&mut self, _: &NextConflict, window: &mut Window, cx: &mut Context<Self>) {
+        let snapshot = self.result_buffer.read(cx).snapshot();
+        let cursor = self.cursor_offset(cx);
+        let ix = self
+            .conflicts
+            .iter()
+            .position(|conflict| conflict.range.start.to_offset(&snapshot) > cursor)
+            .unwrap_or(0);
+        self.select_conflict(ix, window, cx);
+    }
+
+    fn previous_conflict(
+        &mut self,
+        _: &PreviousConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.result_buffer.read(cx).snapshot();
+        let cursor = self.cursor_offset(cx);
+        let ix = self
+            .conflicts
+            .iter()
+            .rposition(|conflict| conflict.range.start.to_offset(&snapshot) < cursor)
+            .unwrap_or(self.conflicts.len().saturating_sub(1));
+        self.select_conflict(ix, window, cx);
+    }
+
+    fn accept_ours(&mut self, _: &AcceptOurs, window: &mut Window, cx: &mut Context<Self>) {
+        self.resolve_current_conflict(|conflict| vec![conflict.ours.clone()], window, cx);
+    }
+
+    fn accept_theirs(&mut self, _: &AcceptTheirs, window: &mut Window, cx: &mut Context<Self>) {
+        self.resolve_current_conflict(|conflict| vec![conflict.theirs.clone()], window, cx);
+    }
+
+    fn accept_both(&mut self, _: &AcceptBoth, window: &mut Window, cx: &mut Context<Self>) {
+        self.resolve_current_conflict(
+            |conflict| vec![conflict.ours.clone(), conflict.theirs.clone()],
+            window,
+            cx,
+        );
+    }
+
+    /// Resolves the current conflict by keeping the given sections of it, then moves to the
+    /// next conflict.
+    fn resolve_current_conflict(
+        &mut self,
+        kept_ranges: impl FnOnce(&ConflictRegion) -> Vec<Range<Anchor>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ix) = self.current_conflict_ix(cx) else {
+            return;
+        };
+        let conflict = self.conflicts[ix].clone();
+        conflict.resolve(self.result_buffer.clone(), &kept_ranges(&conflict), cx);
+        self.conflicts = self
+            .conflicts
+            .iter()
+            .filter(|other| **other != conflict)
+            .cloned()
+            .collect();
+        self.select_conflict(ix.min(self.conflicts.len().saturating_sub(1)), window, cx);
+        cx.notify();
+    }
+
+    /// Saves the result and stages it, which tells git its conflicts are resolved.
+    pub fn mark_resolved(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        if !self.conflicts.is_empty() {
+            return Task::ready(Err(anyhow::anyhow!(
+                "The file still has {} unresolved conflicts",
+                self.conflicts.len()
+            )));
+        }
+        let project = self.project.clone();
+        let buffer = self.result_buffer.clone();
+        cx.spawn(async move |this, cx| {
+            project
+                .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+                .await?;
+            let stage = project.update(cx, |project, cx| {
+                let buffer_id = buffer.read(cx).remote_id();
+                let (repository, repo_path) = project
+                    .git_store()
+                    .read(cx)
+                    .repository_and_path_for_buffer_id(buffer_id, cx)
+                    .context("The file is not in a git repository")?;
+                anyhow::Ok(repository.update(cx, |repository, cx| {
+                    repository.stage_entries(vec![repo_path], cx)
+                }))
+            })?;
+            stage.await?;
+            this.update(cx, |this, cx| {
+                this.staged = true;
+                cx.notify();
+            })
+        })
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let remaining = self.conflicts.len();
+        let status = if self.staged {
+            "Resolved and staged".to_string()
+        } else if remaining == 0 {
+            "No conflicts remaining".to_string()
+        } else if remaining == 1 {
+            "1 conflict remaining".to_string()
+        } else {
+            format!("{remaining} conflicts remaining")
+        };
+
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                div()
+                    .mr_2()
+                    .child(Label::new(status).size(LabelSize::Small)),
+            )
+            .child(
+                IconButton::new("previous-conflict", IconName::ArrowUp)
+                    .icon_size(IconSize::Small)
+                    .disabled(remaining == 0)
+                    .tooltip(Tooltip::for_action_title(
+                        "Previous Conflict",
+                        &PreviousConflict,
+                    ))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.previous_conflict(&PreviousConflict, window, cx)
+                    })),
+            )
+            .child(
+                IconButton::new("next-conflict", IconName::ArrowDown)
+                    .icon_size(IconSize::Small)
+                    .disabled(remaining == 0)
+                    .tooltip(Tooltip::for_action_title("Next Conflict", &NextConflict))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.next_conflict(&NextConflict, window, cx)
+                    })),
+            )
+            .child(Divider::vertical())
+            .child(
+                Button::new("accept-ours", format!("Accept {}", self.ours_branch_name))
+                    .label_size(LabelSize::Small)
+                    .disabled(remaining == 0)
+                    .on_click(
+                        cx.listener(|this, _, window, cx| {
+                            this.accept_ours(&AcceptOurs, window, cx)
+                        }),
+                    ),
+            )
+            .child(
+                Button::new(
+                    "accept-theirs",
+                    format!("Accept {}", self.theirs_branch_name),
+                )
+                .label_size(LabelSize::Small)
+                .disabled(remaining == 0)
+                .on_click(
+                    cx.listener(|this, _, window, cx| {
+                        this.accept_theirs(&AcceptTheirs, window, cx)
+                    }),
+                ),
+            )
+            .child(
+                Button::new("accept-both", "Accept Both")
+                    .label_size(LabelSize::Small)
+                    .disabled(remaining == 0)
+                    .on_click(
+                        cx.listener(|this, _, window, cx| {
+                            this.accept_both(&AcceptBoth, window, cx)
+                        }),
+                    ),
+            )
+            .child(div().flex_1())
+            .child(
+                Button::new("mark-resolved", "Mark as Resolved")
+                    .label_size(LabelSize::Small)
+                    .disabled(remaining > 0 || self.staged)
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.mark_resolved(cx).detach_and_prompt_err(
+                            "Failed to mark conflicts as resolved",
+                            window,
+                            cx,
+                            |_, _, _| None,
+                        )
+                    })),
+            )
+    }
+
+    fn render_pane(&self, title: SharedString, editor: &Entity<Editor>, cx: &Context<Self>) -> Div {
+        v_flex()
+            .flex_1()
+            .min_w_0()
+            .min_h_0()
+            .child(
+                div()
+                    .px_2()
+                    .py_0p5()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(title).size(LabelSize::Small).color(Color::Muted)),
+            )
+            .child(div().flex_1().min_h_0().child(editor.clone()))
+    }
+}
+
+async fn build_side_diff(
+    buffer: &Entity<Buffer>,
+    base_text: &str,
+    language_registry: &Arc<LanguageRegistry>,
+    cx: &mut AsyncApp,
+) -> Entity<BufferDiff> {
+    let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+    let language: Option<Arc<Language>> = snapshot.language().cloned();
+    let diff = cx.new(|cx| BufferDiff::new(&snapshot.text, cx));
+    let update = diff
+        .update(cx, |diff, cx| {
+            diff.update_diff(
+                snapshot.text.clone(),
+                Some(base_text.into()),
+                Some(true),
+                language.clone(),
+                cx,
+            )
+        })
+        .await;
+    diff.update(cx, |diff, cx| {
+        diff.language_changed(language, Some(language_registry.clone()), cx);
+        diff.set_snapshot(update, &snapshot.text, cx)
+    })
+    .await;
+    diff
+}
+
+fn side_editor(
+    buffer: Entity<Buffer>,
+    diff: Option<Entity<BufferDiff>>,
+    window: &mut Window,
+    cx: &mut App,
+) -> Entity<Editor> {
+    let has_diff = diff.is_some();
+    let multibuffer = cx.new(|cx| {
+        let mut multibuffer = MultiBuffer::singleton(buffer, cx);
+        if let Some(diff) = diff {
+            multibuffer.add_diff(diff, cx);
+        }
+        multibuffer
+    });
+    cx.new(|cx| {
+        let mut editor = Editor::for_multibuffer(multibuffer, None, window, cx);
+        editor.disable_diagnostics(cx);
+        if has_diff {
+            editor.start_temporary_diff_override();
+            editor.set_expand_all_diff_hunks(cx);
+            editor.set_render_diff_hunk_controls(
+                Arc::new(|_, _, _, _, _, _, _, _| gpui::Empty.into_any_element()),
+                cx,
+            );
+        }
+        editor
+    })
+}
+
+impl EventEmitter<EditorEvent> for MergeEditor {}
+
+impl Focusable for MergeEditor {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.result_editor.focus_handle(cx)
+    }
+}
+
+impl Item for MergeEditor {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitMergeConflict).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        let file_name = self
+            .result_buffer
+            .read(cx)
+            .file()
+            .and_then(|file| {
+                Some(
+                    file.full_path(cx)
+                        .file_name()?
+                        .to_string_lossy()
+                        .to_string(),
+                )
+            })
+            .unwrap_or_else(|| "untitled".into());
+        format!("Merging {file_name}").into()
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let file = self.result_buffer.read(cx).file()?;
+        Some(file.full_path(cx).to_string_lossy().into_owned().into())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: &mut dyn FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Merge Editor Opened")
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.result_editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<gpui::AnyEntity> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.result_editor.clone().into())
+        } else {
+            None
+        }
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>, _: &App) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.result_editor.clone()))
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        self.result_editor.for_each_project_item(cx, f)
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.result_editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn navigate(
+        &mut self,
+        data: Arc<dyn Any + Send>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.result_editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn breadcrumb_location(&self, _: &App) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, cx: &App) -> Option<(Vec<HighlightedText>, Option<Font>)> {
+        self.result_editor.breadcrumbs(cx)
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.result_editor.update(cx, |editor, cx| {
+            editor.added_to_workspace(workspace, window, cx)
+        });
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.result_buffer.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &App) -> bool {
+        self.result_buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.result_editor.read(cx).can_save(cx)
+    }
+
+    fn save(
+        &mut self,
+        options: SaveOptions,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.result_editor
+            .update(cx, |editor, cx| editor.save(options, project, window, cx))
+    }
+
+    fn reload(
+        &mut self,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.result_editor
+            .update(cx, |editor, cx| editor.reload(project, window, cx))
+    }
+}
+
+impl Render for MergeEditor {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let border_color = cx.theme().colors().border;
+        v_flex()
+            .key_context("MergeEditor")
+            .on_action(cx.listener(Self::accept_ours))
+            .on_action(cx.listener(Self::accept_theirs))
+            .on_action(cx.listener(Self::accept_both))
+            .on_action(cx.listener(Self::next_conflict))
+            .on_action(cx.listener(Self::previous_conflict))
+            .on_action(cx.listener(|this, _: &MarkResolved, window, cx| {
+                this.mark_resolved(cx).detach_and_prompt_err(
+                    "Failed to mark conflicts as resolved",
+                    window,
+                    cx,
+                    |_, _, _| None,
+                )
+            }))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_toolbar(cx))
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .border_b_1()
+                    .border_color(border_color)
+                    .child(self.render_pane(
+                        format!("Ours ({})", self.ours_branch_name).into(),
+                        &self.ours_editor,
+                        cx,
+                    ))
+                    .child(
+                        self.render_pane("Base".into(), &self.base_editor, cx)
+                            .border_x_1()
+                            .border_color(border_color),
+                    )
+                    .child(self.render_pane(
+                        format!("Theirs ({})", self.theirs_branch_name).into(),
+                        &self.theirs_editor,
+                        cx,
+                    )),
+            )
+            .child(self.render_pane("Result".into(), &self.result_editor, cx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt as _;
+    use git::repository::repo_path;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use unindent::Unindent as _;
+    use util::path;
+    use workspace::MultiWorkspace;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+        });
+    }
+
+    #[gpui::test]
+    fn test_merge_sides(cx: &mut TestAppContext) {
+        let text = "
+            one
+            <<<<<<< HEAD
+            two
+            ||||||| base
+            2
+            =======
+            TWO
+            >>>>>>> feature
+            three
+            <<<<<<< HEAD
+            four
+            =======
+            FOUR
+            >>>>>>> feature
+            five
+        "
+        .unindent();
+        let buffer = cx.new(|cx| Buffer::local(text, cx));
+        let sides = MergeSides::new(&buffer.read_with(cx, |buffer, _| buffer.snapshot()));
+        assert_eq!(sides.conflicts.len(), 2);
+        assert_eq!(sides.base, "one\n2\nthree\nfive\n");
+        assert_eq!(sides.ours, "one\ntwo\nthree\nfour\nfive\n");
+        assert_eq!(sides.theirs, "one\nTWO\nthree\nFOUR\nfive\n");
+    }
+
+    #[gpui::test]
+    async fn test_sides_from_merge_stages(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let text = "
+            one
+            <<<<<<< HEAD
+            two
+            =======
+            TWO
+            >>>>>>> feature
+            three
+        "
+        .unindent();
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "a.txt": text,
+            }),
+        )
+        .await;
+        fs.set_merge_stages_for_repo(
+            path!("/project/.git").as_ref(),
+            &[(
+                "a.txt",
+                MergeStages {
+                    base: Some("one\n2\nthree\n".into()),
+                    ours: Some("one\ntwo\nthree\n".into()),
+                    theirs: Some("one\nTWO\nthree\n".into()),
+                },
+            )],
+        );
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let (multi_workspace, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let workspace = multi_workspace.read_with(cx, |mw, _| mw.workspace().clone());
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/project/a.txt"), cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        // The base comes from the index, even though the conflict markers don't include it.
+        let merge_editor = workspace
+            .update_in(cx, |workspace, window, cx| {
+                MergeEditor::open(buffer.clone(), workspace.weak_handle(), window, cx)
+            })
+            .await
+            .unwrap();
+        merge_editor.read_with(cx, |merge_editor, cx| {
+            assert_eq!(merge_editor.remaining_conflicts(), 1);
+            assert_eq!(
+                merge_editor.base_editor.read(cx).text(cx),
+                "one\n2\nthree\n"
+            );
+            assert_eq!(
+                merge_editor.ours_editor.read(cx).text(cx),
+                "one\ntwo\nthree\n"
+            );
+            assert_eq!(
+                merge_editor.theirs_editor.read(cx).text(cx),
+                "one\nTWO\nthree\n"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_resolving_conflicts(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let text = "
+            one
+            <<<<<<< HEAD
+            two
+            =======
+            TWO
+            >>>>>>> feature
+            three
+            <<<<<<< HEAD
+            four
+            =======
+            FOUR
+            >>>>>>> feature
+        "
+        .unindent();
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "a.txt": text,
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let (multi_workspace, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let workspace = multi_workspace.read_with(cx, |mw, _| mw.workspace().clone());
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/project/a.txt"), cx)
+            })
+            .await
+            .unwrap();
+
+        let merge_editor = workspace
+            .update_in(cx, |workspace, window, cx| {
+                MergeEditor::open(buffer.clone(), workspace.weak_handle(), window, cx)
+            })
+            .await
+            .unwrap();
+        merge_editor.read_with(cx, |merge_editor, cx| {
+            assert_eq!(merge_editor.remaining_conflicts(), 2);
+            assert_eq!(
+                merge_editor.ours_editor.read(cx).text(cx),
+                "one\ntwo\nthree\nfour\n"
+            );
+            assert_eq!(
+                merge_editor.theirs_editor.read(cx).text(cx),
+                "one\nTWO\nthree\nFOUR\n"
+            );
+            assert_eq!(merge_editor.tab_content_text(0, cx), "Merging a.txt");
+        });
+
+        merge_editor.update_in(cx, |merge_editor, window, cx| {
+            assert!(matches!(
+                merge_editor.mark_resolved(cx).now_or_never(),
+                Some(Err(_))
+            ));
+            merge_editor.accept_theirs(&AcceptTheirs, window, cx);
+        });
+        cx.run_until_parked();
+        merge_editor.update_in(cx, |merge_editor, window, cx| {
+            assert_eq!(merge_editor.remaining_conflicts(), 1);
+            merge_editor.accept_both(&AcceptBoth, window, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "one\nTWO\nthree\nfour\nFOUR\n"
+        );
+
+        merge_editor
+            .update(cx, |merge_editor, cx| {
+                assert_eq!(merge_editor.remaining_conflicts(), 0);
+                merge_editor.mark_resolved(cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.load(path!("/project/a.txt").as_ref()).await.unwrap(),
+            "one\nTWO\nthree\nfour\nFOUR\n"
+        );
+        let index_text = fs
+            .with_git_state(path!("/project/.git").as_ref(), false, |state| {
+                state.index_contents.get(&repo_path("a.txt")).cloned()
+            })
+            .unwrap();
+        assert_eq!(index_text.as_deref(), Some("one\nTWO\nthree\nfour\nFOUR\n"));
+    }
+}
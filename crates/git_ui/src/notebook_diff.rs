@@ -0,0 +1,736 @@
+//! Renders Jupyter notebooks as text for diffing.
+//!
+//! Both sides of a notebook diff are rendered in a format close to jupytext's
+//! "percent" scripts: each cell starts with a `%%` comment line, followed by
+//! its source. Cells are aligned by ID, so the line diff of the two renderings
+//! shows per-cell source changes rather than the churn of the underlying JSON.
+//! Outputs and metadata are summarized, expanded or left out depending on the
+//! `git_panel.notebook_diff_outputs` setting.
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::Path,
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use gpui::{App, AppContext as _, AsyncApp, Entity};
+use language::{Buffer, Capability, Language, LanguageRegistry};
+use serde_json::Value;
+use settings::NotebookDiffOutputs;
+use util::ResultExt as _;
+
+use crate::git_panel_settings::GitPanelSettings;
+
+const DEFAULT_COMMENT_PREFIX: &str = "# ";
+
+pub(crate) fn is_notebook(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ipynb"))
+}
+
+/// The old and new versions of a notebook. A missing side is a notebook that
+/// was created or deleted.
+pub(crate) struct NotebookPair {
+    old: Option<Notebook>,
+    new: Option<Notebook>,
+}
+
+/// The rendered text of both sides of a [`NotebookPair`].
+pub(crate) struct RenderedNotebookPair {
+    pub old: Option<String>,
+    pub new: String,
+}
+
+impl NotebookPair {
+    /// Parses both sides, returning `None` if either isn't a valid notebook,
+    /// in which case the raw text should be diffed instead.
+    pub fn parse(old: Option<&str>, new: Option<&str>) -> Option<Self> {
+        let parse = |text: Option<&str>| -> Option<Option<Notebook>> {
+            match text {
+                Some(text) => Notebook::parse(text).log_err().map(Some),
+                None => Some(None),
+            }
+        };
+        Some(Self {
+            old: parse(old)?,
+            new: parse(new)?,
+        })
+    }
+
+    /// The name of the notebook's kernel language, preferring the new side.
+    pub fn language_name(&self) -> Option<&str> {
+        self.new
+            .as_ref()
+            .and_then(|notebook| notebook.language.as_deref())
+            .or_else(|| {
+                self.old
+                    .as_ref()
+                    .and_then(|notebook| notebook.language.as_deref())
+            })
+    }
+
+    /// Loads the language that the cells' source is highlighted with.
+    pub async fn load_language(
+        &self,
+        language_registry: &Arc<LanguageRegistry>,
+    ) -> Option<Arc<Language>> {
+        let name = self.language_name()?;
+        language_registry.language_for_name(name).await.ok()
+    }
+
+    pub fn render(
+        &self,
+        language: Option<&Arc<Language>>,
+        outputs: NotebookDiffOutputs,
+    ) -> RenderedNotebookPair {
+        let comment_prefix = language
+            .and_then(|language| language.line_comment_prefixes().first())
+            .map(|prefix| prefix.as_ref())
+            .unwrap_or(DEFAULT_COMMENT_PREFIX);
+        let renderer = Renderer {
+            comment_prefix,
+            outputs,
+        };
+
+        let empty = Notebook::default();
+        let old = self.old.as_ref().unwrap_or(&empty);
+        let new = self.new.as_ref().unwrap_or(&empty);
+        let alignment = align_cells(old, new);
+
+        let old_cells = alignment
+            .old_order
+            .iter()
+            .map(|&ix| (&old.cells[ix], false))
+            .collect::<Vec<_>>();
+        let new_cells = new
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(ix, cell)| (cell, alignment.moved.contains(&ix)))
+            .collect::<Vec<_>>();
+
+        RenderedNotebookPair {
+            old: self
+                .old
+                .as_ref()
+                .map(|old| renderer.render(old, &old_cells)),
+            new: self
+                .new
+                .as_ref()
+                .map(|new| renderer.render(new, &new_cells))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Both sides of a Jupyter notebook diff, rendered cell by cell.
+pub(crate) struct RenderedNotebook {
+    pub old_text: Option<String>,
+    /// A read-only buffer that editors show in place of the notebook's own.
+    pub new_buffer: Entity<Buffer>,
+    language: Option<Arc<Language>>,
+}
+
+impl RenderedNotebook {
+    /// Renders both sides, returning `None` if either isn't a valid notebook.
+    pub async fn new(
+        old_text: Option<String>,
+        new_text: String,
+        language_registry: &Arc<LanguageRegistry>,
+        cx: &mut AsyncApp,
+    ) -> Option<Self> {
+        let pair = NotebookPair::parse(old_text.as_deref(), Some(&new_text))?;
+        let language = pair.load_language(language_registry).await;
+        let outputs = cx.update(|cx| GitPanelSettings::get_global(cx).notebook_diff_outputs);
+        let rendered = pair.render(language.as_ref(), outputs);
+        let new_buffer = cx.new(|cx| {
+            let mut buffer = Buffer::local(rendered.new, cx);
+            buffer.set_language_async(language.clone(), cx);
+            buffer.set_capability(Capability::ReadOnly, cx);
+            buffer
+        });
+
+        Some(Self {
+            old_text: rendered.old,
+            new_buffer,
+            language,
+        })
+    }
+
+    /// Re-renders both sides, keeping the previous rendering while either
+    /// doesn't parse, such as in the middle of an edit.
+    pub fn update(&mut self, old_text: Option<String>, new_text: &str, cx: &mut App) {
+        let Some(pair) = NotebookPair::parse(old_text.as_deref(), Some(new_text)) else {
+            return;
+        };
+        let outputs = GitPanelSettings::get_global(cx).notebook_diff_outputs;
+        let rendered = pair.render(self.language.as_ref(), outputs);
+        self.old_text = rendered.old;
+        self.new_buffer.update(cx, |buffer, cx| {
+            if buffer.text() != rendered.new {
+                buffer.set_text(rendered.new, cx);
+            }
+        });
+    }
+}
+
+#[derive(Default)]
+struct Notebook {
+    cells: Vec<Cell>,
+    metadata: Value,
+    language: Option<String>,
+    /// Whether cells carry IDs of their own. Notebooks older than nbformat 4.5
+    /// are aligned by cell index instead.
+    has_cell_ids: bool,
+}
+
+struct Cell {
+    id: Option<String>,
+    cell_type: String,
+    source: String,
+    outputs: Vec<Value>,
+    metadata: Value,
+}
+
+impl Notebook {
+    fn parse(text: &str) -> Result<Self> {
+        let (notebook, has_cell_ids) = match nbformat::parse_notebook(text) {
+            Ok(nbformat::Notebook::V4(notebook)) => (notebook, true),
+            Ok(nbformat::Notebook::Legacy(notebook)) => {
+                (nbformat::upgrade_legacy_notebook(notebook)?, false)
+            }
+            Ok(nbformat::Notebook::V3(notebook)) => {
+                (nbformat::upgrade_v3_notebook(notebook)?, false)
+            }
+            // Notebooks that claim to be 4.5 but lack cell IDs are common
+            // enough to accept, aligning their cells by index.
+            Err(error) => {
+                let mut json: Value =
+                    serde_json::from_str(text).context("notebook is not valid JSON")?;
+                let cells = json
+                    .get_mut("cells")
+                    .and_then(|cells| cells.as_array_mut())
+                    .with_context(|| format!("failed to parse notebook: {error:?}"))?;
+                for (ix, cell) in cells.iter_mut().enumerate() {
+                    if cell.get("id").is_none() {
+                        cell["id"] = Value::String(format!("cell-{ix}"));
+                    }
+                }
+                match nbformat::parse_notebook(&json.to_string()) {
+                    Ok(nbformat::Notebook::V4(notebook)) => (notebook, false),
+                    _ => anyhow::bail!("failed to parse notebook: {error:?}"),
+                }
+            }
+        };
+
+        let json = serde_json::to_value(&notebook).context("failed to serialize notebook")?;
+        Ok(Self::from_json(&json, has_cell_ids))
+    }
+
+    fn from_json(json: &Value, has_cell_ids: bool) -> Self {
+        let metadata = without_nulls(json.get("metadata").cloned().unwrap_or_default());
+        let language = metadata
+            .pointer("/kernelspec/language")
+            .or_else(|| metadata.pointer("/language_info/name"))
+            .and_then(|language| language.as_str())
+            .map(ToString::to_string);
+        let cells = json
+            .get("cells")
+            .and_then(|cells| cells.as_array())
+            .map(|cells| {
+                cells
+                    .iter()
+                    .map(|cell| Cell {
+                        id: has_cell_ids
+                            .then(|| cell.get("id")?.as_str().map(ToString::to_string))
+                            .flatten(),
+                        cell_type: cell
+                            .get("cell_type")
+                            .and_then(|cell_type| cell_type.as_str())
+                            .unwrap_or("code")
+                            .to_string(),
+                        source: cell.get("source").map(multiline).unwrap_or_default(),
+                        outputs: cell
+                            .get("outputs")
+                            .and_then(|outputs| outputs.as_array())
+                            .cloned()
+                            .unwrap_or_default(),
+                        metadata: without_nulls(cell.get("metadata").cloned().unwrap_or_default()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            cells,
+            metadata,
+            language,
+            has_cell_ids,
+        }
+    }
+}
+
+struct CellAlignment {
+    /// Indices of the old cells in the order they're rendered: matching cells
+    /// follow the new notebook's order, and removed cells stay after the cell
+    /// that preceded them.
+    old_order: Vec<usize>,
+    /// Indices of the new cells that changed position relative to the others.
+    moved: HashSet<usize>,
+}
+
+fn align_cells(old: &Notebook, new: &Notebook) -> CellAlignment {
+    // Cell IDs are only comparable when both sides have them.
+    let by_id = old.has_cell_ids && new.has_cell_ids;
+    let key = |ix: usize, cell: &Cell| match &cell.id {
+        Some(id) if by_id => id.clone(),
+        _ => format!("#{ix}"),
+    };
+    let old_keys = old
+        .cells
+        .iter()
+        .enumerate()
+        .map(|(ix, cell)| (key(ix, cell), ix))
+        .collect::<HashMap<_, _>>();
+    let matches = new
+        .cells
+        .iter()
+        .enumerate()
+        .filter_map(|(new_ix, cell)| Some((new_ix, *old_keys.get(&key(new_ix, cell))?)))
+        .collect::<Vec<_>>();
+
+    let mut removed_after = HashMap::<Option<usize>, Vec<usize>>::default();
+    let matched_old = matches
+        .iter()
+        .map(|&(_, old_ix)| old_ix)
+        .collect::<HashSet<_>>();
+    let mut previous = None;
+    for old_ix in 0..old.cells.len() {
+        if matched_old.contains(&old_ix) {
+            previous = Some(old_ix);
+        } else {
+            removed_after.entry(previous).or_default().push(old_ix);
+        }
+    }
+
+    let mut old_order = removed_after.remove(&None).unwrap_or_default();
+    for &(_, old_ix) in &matches {
+        old_order.push(old_ix);
+        old_order.extend(removed_after.remove(&Some(old_ix)).unwrap_or_default());
+    }
+
+    let mut in_old_order = matches.clone();
+    in_old_order.sort_by_key(|&(_, old_ix)| old_ix);
+    let stationary = longest_common_subsequence(&matches, &in_old_order);
+    let moved = matches
+        .iter()
+        .map(|&(new_ix, _)| new_ix)
+        .filter(|new_ix| !stationary.contains(new_ix))
+        .collect();
+
+    CellAlignment { old_order, moved }
+}
+
+/// Returns the new indices of the matches that appear in the same relative
+/// order in both sequences.
+fn longest_common_subsequence(a: &[(usize, usize)], b: &[(usize, usize)]) -> HashSet<usize> {
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut common = HashSet::default();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            common.insert(a[i].0);
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    common
+}
+
+struct Renderer<'a> {
+    comment_prefix: &'a str,
+    outputs: NotebookDiffOutputs,
+}
+
+impl Renderer<'_> {
+    fn render(&self, notebook: &Notebook, cells: &[(&Cell, bool)]) -> String {
+        let mut text = String::new();
+        self.render_metadata("notebook metadata", &notebook.metadata, &mut text);
+        for (cell, moved) in cells {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            self.render_cell(cell, *moved, &mut text);
+        }
+        text
+    }
+
+    fn render_cell(&self, cell: &Cell, moved: bool, text: &mut String) {
+        text.push_str(self.comment_prefix);
+        text.push_str("%%");
+        if cell.cell_type != "code" {
+            text.push_str(&format!(" [{}]", cell.cell_type));
+        }
+        if let Some(id) = &cell.id {
+            text.push_str(&format!(" id=\"{id}\""));
+        }
+        if moved {
+            text.push_str(" (moved)");
+        }
+        text.push('\n');
+
+        push_lines(text, "", &cell.source);
+        self.render_outputs(&cell.outputs, text);
+        self.render_metadata("metadata", &cell.metadata, text);
+    }
+
+    fn render_outputs(&self, outputs: &[Value], text: &mut String) {
+        if outputs.is_empty() {
+            return;
+        }
+        match self.outputs {
+            NotebookDiffOutputs::Hidden => {}
+            NotebookDiffOutputs::Collapsed => {
+                let noun = if outputs.len() == 1 {
+                    "output"
+                } else {
+                    "outputs"
+                };
+                let hash = short_hash(&Value::Array(outputs.to_vec()));
+                text.push_str(&format!(
+                    "{}[{} {noun}, {hash}]\n",
+                    self.comment_prefix,
+                    outputs.len()
+                ));
+            }
+            NotebookDiffOutputs::Expanded => {
+                text.push_str(&format!("{}[outputs]\n", self.comment_prefix));
+                let prefix = format!("{}> ", self.comment_prefix);
+                for output in outputs {
+                    push_lines(text, &prefix, &output_text(output));
+                }
+            }
+        }
+    }
+
+    fn render_metadata(&self, label: &str, metadata: &Value, text: &mut String) {
+        if metadata
+            .as_object()
+            .is_none_or(|metadata| metadata.is_empty())
+        {
+            return;
+        }
+        match self.outputs {
+            NotebookDiffOutputs::Hidden => {}
+            NotebookDiffOutputs::Collapsed => {
+                text.push_str(&format!(
+                    "{}[{label}, {}]\n",
+                    self.comment_prefix,
+                    short_hash(metadata)
+                ));
+            }
+            NotebookDiffOutputs::Expanded => {
+                text.push_str(&format!("{}[{label}]\n", self.comment_prefix));
+                let pretty = serde_json::to_string_pretty(metadata).unwrap_or_default();
+                push_lines(text, self.comment_prefix, &pretty);
+            }
+        }
+    }
+}
+
+/// A plain-text rendering of an output, with rich data replaced by its MIME type.
+fn output_text(output: &Value) -> String {
+    match output.get("output_type").and_then(|kind| kind.as_str()) {
+        Some("stream") => output.get("text").map(multiline).unwrap_or_default(),
+        Some("error") => {
+            let field = |name| {
+                output
+                    .get(name)
+                    .and_then(|value: &Value| value.as_str())
+                    .unwrap_or_default()
+            };
+            format!("{}: {}", field("ename"), field("evalue"))
+        }
+        Some("execute_result" | "display_data") => {
+            let Some(data) = output.get("data").and_then(|data| data.as_object()) else {
+                return String::new();
+            };
+            let mut text = data.get("text/plain").map(multiline).unwrap_or_default();
+            for mime_type in data.keys().filter(|mime_type| *mime_type != "text/plain") {
+                if !text.is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(&format!("<{mime_type}>"));
+            }
+            text
+        }
+        _ => String::new(),
+    }
+}
+
+/// Appends `content` to `text` line by line, with each line prefixed.
+fn push_lines(text: &mut String, prefix: &str, content: &str) {
+    for line in content.lines() {
+        text.push_str(prefix);
+        text.push_str(line);
+        text.push('\n');
+    }
+}
+
+/// Notebook text fields are either a string or an array of lines.
+fn multiline(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(lines) => lines.iter().filter_map(|line| line.as_str()).collect(),
+        _ => String::new(),
+    }
+}
+
+/// Drops unset fields, which nbformat serializes as `null`, so that metadata
+/// with nothing in it isn't rendered.
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, without_nulls(value)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+fn short_hash(value: &Value) -> String {
+    let mut hasher = DefaultHasher::new();
+    value.to_string().hash(&mut hasher);
+    format!("{:08x}", hasher.finish() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn code_cell(id: &str, source: &str, outputs: Value) -> Value {
+        json!({
+            "id": id,
+            "cell_type": "code",
+            "execution_count": null,
+            "metadata": {},
+            "source": source,
+            "outputs": outputs,
+        })
+    }
+
+    fn notebook(cells: Vec<Value>) -> Notebook {
+        Notebook::from_json(
+            &json!({
+                "metadata": {"kernelspec": {"display_name": "Python 3", "language": "python", "name": "python3"}},
+                "cells": cells,
+            }),
+            true,
+        )
+    }
+
+    fn render(old: Notebook, new: Notebook, outputs: NotebookDiffOutputs) -> (String, String) {
+        let pair = NotebookPair {
+            old: Some(old),
+            new: Some(new),
+        };
+        let rendered = pair.render(None, outputs);
+        (rendered.old.unwrap(), rendered.new)
+    }
+
+    #[test]
+    fn test_render_cells() {
+        let notebook = notebook(vec![
+            json!({"id": "intro", "cell_type": "markdown", "metadata": {}, "source": ["# Title\n", "Some text"]}),
+            code_cell(
+                "load",
+                "import pandas as pd\ndf = pd.read_csv('data.csv')\ndf",
+                json!([
+                    {"output_type": "stream", "name": "stdout", "text": ["loading\n"]},
+                    {"output_type": "execute_result", "execution_count": 1, "metadata": {}, "data": {"text/plain": "   a  b\n0  1  2", "text/html": "<table/>"}},
+                    {"output_type": "error", "ename": "ValueError", "evalue": "bad value", "traceback": []},
+                ]),
+            ),
+        ]);
+        let pair = NotebookPair {
+            old: None,
+            new: Some(notebook),
+        };
+        assert_eq!(pair.language_name(), Some("python"));
+
+        let rendered = pair.render(None, NotebookDiffOutputs::Expanded);
+        assert_eq!(rendered.old, None);
+        assert_eq!(
+            rendered.new,
+            indoc! {r#"
+                # [notebook metadata]
+                # {
+                #   "kernelspec": {
+                #     "display_name": "Python 3",
+                #     "language": "python",
+                #     "name": "python3"
+                #   }
+                # }
+
+                # %% [markdown] id="intro"
+                # Title
+                Some text
+
+                # %% id="load"
+                import pandas as pd
+                df = pd.read_csv('data.csv')
+                df
+                # [outputs]
+                # > loading
+                # >    a  b
+                # > 0  1  2
+                # > <text/html>
+                # > ValueError: bad value
+            "#}
+        );
+
+        let rendered = pair.render(None, NotebookDiffOutputs::Hidden);
+        assert_eq!(
+            rendered.new,
+            indoc! {r#"
+                # %% [markdown] id="intro"
+                # Title
+                Some text
+
+                # %% id="load"
+                import pandas as pd
+                df = pd.read_csv('data.csv')
+                df
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_collapsed_outputs_change_with_content() {
+        let old = notebook(vec![code_cell(
+            "a",
+            "print(1)",
+            json!([{"output_type": "stream", "name": "stdout", "text": "1\n"}]),
+        )]);
+        let same = notebook(vec![code_cell(
+            "a",
+            "print(1)",
+            json!([{"output_type": "stream", "name": "stdout", "text": "1\n"}]),
+        )]);
+        let changed = notebook(vec![code_cell(
+            "a",
+            "print(1)",
+            json!([{"output_type": "stream", "name": "stdout", "text": "2\n"}]),
+        )]);
+
+        let (old_text, same_text) = render(old, same, NotebookDiffOutputs::Collapsed);
+        assert_eq!(old_text, same_text);
+        assert!(old_text.contains("# [1 output, "));
+
+        let old = notebook(vec![code_cell(
+            "a",
+            "print(1)",
+            json!([{"output_type": "stream", "name": "stdout", "text": "1\n"}]),
+        )]);
+        let (old_text, changed_text) = render(old, changed, NotebookDiffOutputs::Collapsed);
+        assert_ne!(old_text, changed_text);
+        assert_eq!(
+            old_text
+                .lines()
+                .filter(|line| !line.starts_with("# ["))
+                .collect::<Vec<_>>(),
+            changed_text
+                .lines()
+                .filter(|line| !line.starts_with("# ["))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_align_cells_by_id() {
+        let old = notebook(vec![
+            code_cell("a", "a", json!([])),
+            code_cell("removed", "removed", json!([])),
+            code_cell("b", "b", json!([])),
+            code_cell("c", "c", json!([])),
+        ]);
+        let new = notebook(vec![
+            code_cell("c", "c", json!([])),
+            code_cell("a", "a", json!([])),
+            code_cell("added", "added", json!([])),
+            code_cell("b", "b", json!([])),
+        ]);
+
+        let (old_text, new_text) = render(old, new, NotebookDiffOutputs::Hidden);
+        assert_eq!(
+            old_text,
+            indoc! {r#"
+                # %% id="c"
+                c
+
+                # %% id="a"
+                a
+
+                # %% id="removed"
+                removed
+
+                # %% id="b"
+                b
+            "#}
+        );
+        assert_eq!(
+            new_text,
+            indoc! {r#"
+                # %% id="c" (moved)
+                c
+
+                # %% id="a"
+                a
+
+                # %% id="added"
+                added
+
+                # %% id="b"
+                b
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_align_cells_by_index_without_ids() {
+        let old = Notebook::from_json(
+            &json!({"cells": [
+                {"cell_type": "code", "source": "x = 1", "outputs": []},
+                {"cell_type": "code", "source": "y = 2", "outputs": []},
+            ]}),
+            false,
+        );
+        let new = notebook(vec![code_cell("x", "x = 10", json!([]))]);
+
+        let (old_text, new_text) = render(old, new, NotebookDiffOutputs::Hidden);
+        assert_eq!(old_text, "# %%\nx = 1\n\n# %%\ny = 2\n");
+        assert_eq!(new_text, "# %% id=\"x\"\nx = 10\n");
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{
     conflict_view::ConflictAddon,
+    file_diff_view::build_buffer_diff,
     git_panel::{GitPanel, GitPanelAddon, GitStatusEntry},
     git_panel_settings::GitPanelSettings,
+    notebook_diff::{RenderedNotebook, is_notebook},
     resolve_active_repository,
 };
 use agent_settings::AgentSettings;
@@ -72,6 +74,7 @@ pub struct ProjectDiff {
     branch_diff: Entity<branch_diff::BranchDiff>,
     editor: Entity<SplittableEditor>,
     buffer_diff_subscriptions: HashMap<Arc<RelPath>, (Entity<BufferDiff>, Subscription)>,
+    notebooks: HashMap<Arc<RelPath>, NotebookExcerpt>,
     workspace: WeakEntity<Workspace>,
     focus_handle: FocusHandle,
     pending_scroll: Option<PathKey>,
@@ -80,6 +83,14 @@ pub struct ProjectDiff {
     _subscription: Subscription,
 }
 
+/// A Jupyter notebook that is shown rendered cell by cell, in place of its JSON.
+struct NotebookExcerpt {
+    /// The notebook's own buffer.
+    buffer: Entity<Buffer>,
+    rendered: RenderedNotebook,
+    diff: Entity<BufferDiff>,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RefreshReason {
     DiffChanged,
@@ -412,12 +423,15 @@ impl ProjectDiff {
         let mut was_sort_by_path = GitPanelSettings::get_global(cx).sort_by_path;
         let mut was_collapse_untracked_diff =
             GitPanelSettings::get_global(cx).collapse_untracked_diff;
+        let mut was_notebook_diff_outputs = GitPanelSettings::get_global(cx).notebook_diff_outputs;
         cx.observe_global_in::<SettingsStore>(window, move |this, window, cx| {
             let is_sort_by_path = GitPanelSettings::get_global(cx).sort_by_path;
             let is_collapse_untracked_diff =
                 GitPanelSettings::get_global(cx).collapse_untracked_diff;
+            let notebook_diff_outputs = GitPanelSettings::get_global(cx).notebook_diff_outputs;
             if is_sort_by_path != was_sort_by_path
                 || is_collapse_untracked_diff != was_collapse_untracked_diff
+                || notebook_diff_outputs != was_notebook_diff_outputs
             {
                 this._task = {
                     window.spawn(cx, {
@@ -428,6 +442,7 @@ impl ProjectDiff {
             }
             was_sort_by_path = is_sort_by_path;
             was_collapse_untracked_diff = is_collapse_untracked_diff;
+            was_notebook_diff_outputs = notebook_diff_outputs;
         })
         .detach();
 
@@ -444,6 +459,7 @@ impl ProjectDiff {
             editor,
             multibuffer,
             buffer_diff_subscriptions: Default::default(),
+            notebooks: Default::default(),
             pending_scroll: None,
             review_comment_count: 0,
             _task: task,
@@ -504,6 +520,11 @@ impl ProjectDiff {
         let position = editor.selections.newest_anchor().head();
         let multi_buffer = editor.buffer().read(cx);
         let (_, buffer, _) = multi_buffer.excerpt_containing(position, cx)?;
+        let buffer = self
+            .notebooks
+            .values()
+            .find(|notebook| notebook.rendered.new_buffer == buffer)
+            .map_or(buffer, |notebook| notebook.buffer.clone());
 
         let file = buffer.read(cx).file()?;
         Some(ProjectPath {
@@ -695,17 +716,19 @@ impl ProjectDiff {
         file_status: FileStatus,
         buffer: Entity<Buffer>,
         diff: Entity<BufferDiff>,
+        source_diff: Entity<BufferDiff>,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Option<BufferId> {
-        let subscription = cx.subscribe_in(&diff, window, move |this, _, _, window, cx| {
+        // Rendered notebooks are refreshed when the diff of the notebook itself changes.
+        let subscription = cx.subscribe_in(&source_diff, window, move |this, _, _, window, cx| {
             this._task = window.spawn(cx, {
                 let this = cx.weak_entity();
                 async |cx| Self::refresh(this, RefreshReason::DiffChanged, cx).await
             })
         });
         self.buffer_diff_subscriptions
-            .insert(path_key.path.clone(), (diff.clone(), subscription));
+            .insert(path_key.path.clone(), (source_diff, subscription));
 
         // TODO(split-diff) we shouldn't have a conflict addon when split
         let conflict_addon = self
@@ -848,6 +871,7 @@ impl ProjectDiff {
                     }
 
                     this.buffer_diff_subscriptions.remove(&path.path);
+                    this.notebooks.remove(&path.path);
                     editor.remove_excerpts_for_path(path, cx);
                 }
             });
@@ -861,10 +885,14 @@ impl ProjectDiff {
                 // We might be lagging behind enough that all future entry.load futures are no longer pending.
                 // If that is the case, this task will never yield, starving the foreground thread of execution time.
                 yield_now().await;
+                let notebook =
+                    Self::render_notebook(&this, &path_key.path, &buffer, &diff, cx).await;
                 cx.update(|window, cx| {
                     this.update(cx, |this, cx| {
                         let multibuffer = this.multibuffer.read(cx);
-                        let skip = multibuffer.buffer(buffer.read(cx).remote_id()).is_some()
+                        // Rendered notebooks are read-only, so there are no edits to preserve.
+                        let skip = notebook.is_none()
+                            && multibuffer.buffer(buffer.read(cx).remote_id()).is_some()
                             && multibuffer
                                 .diff_for(buffer.read(cx).remote_id())
                                 .is_some_and(|prev_diff| prev_diff.entity_id() == diff.entity_id())
@@ -875,10 +903,13 @@ impl ProjectDiff {
                                 RefreshReason::StatusesChanged => false,
                             };
                         if !skip {
+                            let (displayed_buffer, displayed_diff) =
+                                notebook.unwrap_or_else(|| (buffer, diff.clone()));
                             if let Some(buffer_id) = this.register_buffer(
                                 path_key,
                                 entry.file_status,
-                                buffer,
+                                displayed_buffer,
+                                displayed_diff,
                                 diff,
                                 window,
                                 cx,
@@ -906,6 +937,87 @@ impl ProjectDiff {
         Ok(())
     }
 
+    /// Renders a Jupyter notebook cell by cell, returning the buffer and diff to
+    /// show in place of its own. Returns `None` for other files, and for
+    /// notebooks that don't parse, which are shown as they are.
+    async fn render_notebook(
+        this: &WeakEntity<Self>,
+        path: &Arc<RelPath>,
+        buffer: &Entity<Buffer>,
+        diff: &Entity<BufferDiff>,
+        cx: &mut AsyncWindowContext,
+    ) -> Option<(Entity<Buffer>, Entity<BufferDiff>)> {
+        let (old_text, new_text, language_registry) = this
+            .update(cx, |this, cx| {
+                let is_notebook_file = buffer
+                    .read(cx)
+                    .file()
+                    .is_some_and(|file| is_notebook(file.path().as_std_path()));
+                if !is_notebook_file {
+                    this.notebooks.remove(path);
+                    return None;
+                }
+                Some((
+                    diff.read(cx).base_text_string(cx),
+                    buffer.read(cx).text(),
+                    this.project.read(cx).languages().clone(),
+                ))
+            })
+            .ok()??;
+
+        let rerendered = this
+            .update(cx, |this, cx| {
+                let notebook = this
+                    .notebooks
+                    .get_mut(path)
+                    .filter(|notebook| &notebook.buffer == buffer)?;
+                notebook.rendered.update(old_text.clone(), &new_text, cx);
+                let snapshot = notebook.rendered.new_buffer.read(cx).snapshot();
+                let base_text = notebook.rendered.old_text.as_deref().map(Arc::from);
+                let diff_updated = notebook.diff.update(cx, |diff, cx| {
+                    diff.set_base_text(
+                        base_text,
+                        snapshot.language().cloned(),
+                        snapshot.text.clone(),
+                        cx,
+                    )
+                });
+                Some((
+                    notebook.rendered.new_buffer.clone(),
+                    notebook.diff.clone(),
+                    diff_updated,
+                ))
+            })
+            .ok()?;
+        if let Some((rendered_buffer, rendered_diff, diff_updated)) = rerendered {
+            diff_updated.await.ok();
+            return Some((rendered_buffer, rendered_diff));
+        }
+
+        let rendered = RenderedNotebook::new(old_text, new_text, &language_registry, cx).await?;
+        let rendered_buffer = rendered.new_buffer.clone();
+        let rendered_diff = build_buffer_diff(
+            rendered.old_text.clone(),
+            &rendered_buffer,
+            language_registry,
+            cx,
+        )
+        .await
+        .log_err()?;
+        this.update(cx, |this, _| {
+            this.notebooks.insert(
+                path.clone(),
+                NotebookExcerpt {
+                    buffer: buffer.clone(),
+                    rendered,
+                    diff: rendered_diff.clone(),
+                },
+            );
+        })
+        .ok()?;
+        Some((rendered_buffer, rendered_diff))
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn excerpt_paths(&self, cx: &App) -> Vec<std::sync::Arc<util::rel_path::RelPath>> {
         self.multibuffer
@@ -2808,4 +2920,82 @@ mod tests {
         assert_eq!(paths_b.len(), 1);
         assert_eq!(*paths_b[0], *"b.txt");
     }
+
+    #[gpui::test]
+    async fn test_notebook_excerpts(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let notebook = |source: &str| {
+            json!({
+                "nbformat": 4,
+                "nbformat_minor": 5,
+                "metadata": {},
+                "cells": [
+                    {
+                        "id": "assign",
+                        "cell_type": "code",
+                        "execution_count": 1,
+                        "metadata": {},
+                        "source": [source],
+                        "outputs": [],
+                    },
+                ],
+            })
+            .to_string()
+        };
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "analysis.ipynb": notebook("x = 2"),
+            }),
+        )
+        .await;
+        fs.set_head_and_index_for_repo(
+            path!("/project/.git").as_ref(),
+            &[("analysis.ipynb", notebook("x = 1"))],
+        );
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let (multi_workspace, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let workspace = multi_workspace.read_with(cx, |mw, _| mw.workspace().clone());
+        let diff = cx.new_window_entity(|window, cx| {
+            ProjectDiff::new(project.clone(), workspace, window, cx)
+        });
+        cx.run_until_parked();
+
+        // The notebook is diffed cell by cell rather than as JSON.
+        let editor = diff.read_with(cx, |diff, cx| diff.editor.read(cx).rhs_editor().clone());
+        assert_state_with_diff(
+            &editor,
+            cx,
+            &r#"
+                  ˇ# %% id="assign"
+                - x = 1
+                + x = 2
+            "#
+            .unindent(),
+        );
+        let active_path = diff.read_with(cx, |diff, cx| diff.active_path(cx)).unwrap();
+        assert_eq!(active_path.path.as_ref(), rel_path("analysis.ipynb"));
+
+        // The rendering follows changes to the notebook's diff.
+        fs.set_head_and_index_for_repo(
+            path!("/project/.git").as_ref(),
+            &[("analysis.ipynb", notebook("x = 3"))],
+        );
+        cx.run_until_parked();
+        assert_state_with_diff(
+            &editor,
+            cx,
+            &r#"
+                  ˇ# %% id="assign"
+                - x = 3
+                + x = 2
+            "#
+            .unindent(),
+        );
+    }
 }
@@ -2,12 +2,13 @@ use crate::{
     AssetSource, DevicePixels, IsZero, RenderImage, Result, SharedString, Size,
     swap_rgba_pa_to_bgra,
 };
+use anyhow::Context as _;
 use image::Frame;
 use resvg::tiny_skia::Pixmap;
 use smallvec::SmallVec;
 use std::{
     hash::Hash,
-    sync::{Arc, LazyLock},
+    sync::{Arc, LazyLock, OnceLock},
 };
 
 /// When rendering SVGs, we render them at twice the size to get a higher-quality result.
@@ -44,10 +45,18 @@ impl SvgRenderer {
             Arc::new(db)
         });
         let default_font_resolver = usvg::FontResolver::default_font_selector();
+        let font_db_with_embedded_fonts = OnceLock::<Arc<usvg::fontdb::Database>>::new();
+        let font_asset_source = asset_source.clone();
         let font_resolver = Box::new(
             move |font: &usvg::Font, db: &mut Arc<usvg::fontdb::Database>| {
                 if db.is_empty() {
-                    *db = FONT_DB.clone();
+                    *db = font_db_with_embedded_fonts
+                        .get_or_init(|| {
+                            let mut db = FONT_DB.as_ref().clone();
+                            load_embedded_fonts(&mut db, font_asset_source.as_ref());
+                            Arc::new(db)
+                        })
+                        .clone();
                 }
                 default_font_resolver(font, db)
             },
@@ -93,6 +102,25 @@ impl SvgRenderer {
         })
     }
 
+    /// Renders the given bytes into a PNG image, without using the GPU.
+    pub fn render_png(&self, bytes: &[u8], scale_factor: f32) -> Result<Vec<u8>> {
+        let pixmap = self.render_pixmap(bytes, SvgSize::ScaleFactor(scale_factor))?;
+        let pixels = pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.demultiply();
+                [color.red(), color.green(), color.blue(), color.alpha()]
+            })
+            .collect();
+        let buffer = image::RgbaImage::from_raw(pixmap.width(), pixmap.height(), pixels)
+            .context("invalid pixmap size")?;
+
+        let mut png = Vec::new();
+        buffer.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)?;
+        Ok(png)
+    }
+
     pub(crate) fn render_alpha_mask(
         &self,
         params: &RenderSvgParams,
@@ -148,3 +176,19 @@ impl SvgRenderer {
         Ok(pixmap)
     }
 }
+
+/// Loads the fonts bundled with the application's assets, so that SVGs can use
+/// the same fonts as the rest of the UI.
+fn load_embedded_fonts(db: &mut usvg::fontdb::Database, asset_source: &dyn AssetSource) {
+    let Ok(font_paths) = asset_source.list("fonts") else {
+        return;
+    };
+    for font_path in font_paths {
+        if !font_path.ends_with(".ttf") {
+            continue;
+        }
+        if let Ok(Some(font_bytes)) = asset_source.load(&font_path) {
+            db.load_font_data(font_bytes.into_owned());
+        }
+    }
+}
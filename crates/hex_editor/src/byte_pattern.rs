@@ -0,0 +1,114 @@
+use anyhow::{Result, anyhow};
+
+/// A sequence of bytes to search for, where `None` matches any byte.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BytePattern(Vec<Option<u8>>);
+
+impl BytePattern {
+    /// Parses hex bytes such as `DE AD be ef`, `0xdeadbeef` or `de ?? ef`,
+    /// where `??` matches any byte.
+    pub fn parse_hex(query: &str) -> Result<Self> {
+        let mut bytes = Vec::new();
+        for word in query.split(|c: char| c.is_whitespace() || c == ',') {
+            let word = word
+                .strip_prefix("0x")
+                .or_else(|| word.strip_prefix("0X"))
+                .unwrap_or(word);
+            if word.len() % 2 != 0 {
+                return Err(anyhow!("`{word}` has an odd number of hex digits"));
+            }
+            for pair in word.as_bytes().chunks(2) {
+                let byte = match pair {
+                    b"??" => None,
+                    _ => {
+                        let pair = str::from_utf8(pair)?;
+                        let byte = u8::from_str_radix(pair, 16)
+                            .map_err(|_| anyhow!("`{pair}` is not a hex byte"))?;
+                        Some(byte)
+                    }
+                };
+                bytes.push(byte);
+            }
+        }
+        Ok(Self(bytes))
+    }
+
+    pub fn text(query: &str) -> Self {
+        Self(query.bytes().map(Some).collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn matches_at(&self, bytes: &[u8], offset: usize) -> bool {
+        bytes
+            .get(offset..offset + self.len())
+            .is_some_and(|window| {
+                self.0
+                    .iter()
+                    .zip(window)
+                    .all(|(expected, byte)| expected.is_none_or(|expected| expected == *byte))
+            })
+    }
+
+    /// Returns the first match starting at or after `offset`.
+    pub fn find_next(&self, bytes: &[u8], offset: usize) -> Option<usize> {
+        if self.is_empty() {
+            return None;
+        }
+        let last_start = bytes.len().checked_sub(self.len())?;
+        (offset..=last_start).find(|&start| self.matches_at(bytes, start))
+    }
+
+    /// Returns the last match starting before `offset`.
+    pub fn find_previous(&self, bytes: &[u8], offset: usize) -> Option<usize> {
+        if self.is_empty() {
+            return None;
+        }
+        let last_start = bytes.len().checked_sub(self.len())?;
+        (0..offset.min(last_start + 1))
+            .rev()
+            .find(|&start| self.matches_at(bytes, start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(
+            BytePattern::parse_hex("DE ad 0xBEEF").unwrap(),
+            BytePattern(vec![Some(0xde), Some(0xad), Some(0xbe), Some(0xef)])
+        );
+        assert_eq!(
+            BytePattern::parse_hex("01,??,03").unwrap(),
+            BytePattern(vec![Some(1), None, Some(3)])
+        );
+        assert!(BytePattern::parse_hex("abc").is_err());
+        assert!(BytePattern::parse_hex("zz").is_err());
+        assert!(BytePattern::parse_hex("  ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find() {
+        let bytes = b"\x00ELF\x01\x00ELF\x02";
+        let pattern = BytePattern::parse_hex("45 4c 46 ??").unwrap();
+        assert_eq!(pattern.find_next(bytes, 0), Some(1));
+        assert_eq!(pattern.find_next(bytes, 2), Some(6));
+        assert_eq!(pattern.find_next(bytes, 7), None);
+        assert_eq!(pattern.find_previous(bytes, 6), Some(1));
+        assert_eq!(pattern.find_previous(bytes, bytes.len()), Some(6));
+        assert_eq!(pattern.find_previous(bytes, 1), None);
+
+        let pattern = BytePattern::text("ELF");
+        assert_eq!(pattern.find_next(bytes, 5), Some(6));
+        assert_eq!(BytePattern::text("too long").find_next(b"short", 0), None);
+    }
+}
@@ -0,0 +1,512 @@
+use std::{
+    collections::BTreeMap,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, SharedString, Task};
+use project::{Fs, Project, ProjectEntryId, ProjectPath, WindowedFile};
+
+/// The number of bytes at the start of a file that are checked to decide
+/// whether it is opened in the hex editor instead of a text buffer.
+const SNIFF_LEN: usize = 8 * 1024;
+
+/// Writes the whole contents of a buffer that isn't backed by a file.
+pub type SaveBytes = Box<dyn Fn(Arc<[u8]>, &mut App) -> Task<Result<()>>>;
+
+pub enum HexBufferEvent {
+    /// The bytes were edited, undone or redone.
+    Edited { range: Range<usize> },
+    /// The buffer was saved or reloaded, changing its dirty state.
+    Saved,
+}
+
+struct Edit {
+    offset: usize,
+    old: Vec<u8>,
+    new: Vec<u8>,
+}
+
+/// The bytes of a buffer as they were loaded, before any edits.
+enum Contents {
+    Memory(Vec<u8>),
+    /// A file, which is read in windows so that it is never loaded at once.
+    File(WindowedFile),
+}
+
+enum Source {
+    File {
+        project_path: ProjectPath,
+        entry_id: Option<ProjectEntryId>,
+        abs_path: PathBuf,
+        fs: Arc<dyn Fs>,
+    },
+    Memory {
+        save: Option<SaveBytes>,
+    },
+}
+
+/// A fixed-size byte buffer edited in overwrite mode, either loaded from a
+/// file or holding a memory dump.
+pub struct HexBuffer {
+    title: SharedString,
+    contents: Contents,
+    /// The bytes written since the contents were loaded, by offset. They take
+    /// precedence over the contents.
+    changes: BTreeMap<usize, u8>,
+    /// The address of the first byte, shown in the offset column.
+    base_address: u64,
+    source: Source,
+    undo_stack: Vec<Edit>,
+    redo_stack: Vec<Edit>,
+    /// The depth of the undo stack at which the buffer matches what was last
+    /// saved, or `None` when that state can no longer be reached.
+    saved_depth: Option<usize>,
+}
+
+impl EventEmitter<HexBufferEvent> for HexBuffer {}
+
+impl HexBuffer {
+    /// Creates a buffer holding a memory dump. Without a `save` callback the
+    /// buffer can still be edited, but not written back.
+    pub fn from_bytes(
+        title: impl Into<SharedString>,
+        base_address: u64,
+        bytes: Vec<u8>,
+        save: Option<SaveBytes>,
+    ) -> Self {
+        Self::new(
+            title.into(),
+            base_address,
+            Contents::Memory(bytes),
+            Source::Memory { save },
+        )
+    }
+
+    fn new(title: SharedString, base_address: u64, contents: Contents, source: Source) -> Self {
+        Self {
+            title,
+            contents,
+            changes: BTreeMap::new(),
+            base_address,
+            source,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            saved_depth: Some(0),
+        }
+    }
+
+    pub fn open(
+        project: &Entity<Project>,
+        project_path: ProjectPath,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        let project = project.read(cx);
+        let entry_id = project
+            .entry_for_path(&project_path, cx)
+            .map(|entry| entry.id);
+        let Some(abs_path) = project.absolute_path(&project_path, cx) else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "no absolute path for {project_path:?}"
+            )));
+        };
+        let fs = project.fs().clone();
+        cx.spawn(async move |cx| {
+            let file = cx
+                .background_spawn({
+                    let fs = fs.clone();
+                    let abs_path = abs_path.clone();
+                    async move { fs.open_windowed(&abs_path) }
+                })
+                .await?;
+            let title = abs_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let source = Source::File {
+                project_path,
+                entry_id,
+                abs_path,
+                fs,
+            };
+            Ok(cx.new(|_| Self::new(title.into(), 0, Contents::File(file), source)))
+        })
+    }
+
+    pub fn title(&self) -> &SharedString {
+        &self.title
+    }
+
+    /// Reads the bytes in the given range, clipped to the end of the buffer.
+    pub fn read(&self, range: Range<usize>) -> Vec<u8> {
+        let end = range.end.min(self.len());
+        let start = range.start.min(end);
+        let mut bytes = match &self.contents {
+            Contents::Memory(bytes) => bytes[start..end].to_vec(),
+            Contents::File(file) => file.read(start..end),
+        };
+        for (&offset, &byte) in self.changes.range(start..end) {
+            if let Some(slot) = bytes.get_mut(offset - start) {
+                *slot = byte;
+            }
+        }
+        bytes
+    }
+
+    pub fn byte_at(&self, offset: usize) -> Option<u8> {
+        self.read(offset..offset.saturating_add(1)).first().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.contents {
+            Contents::Memory(bytes) => bytes.len(),
+            Contents::File(file) => file.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn base_address(&self) -> u64 {
+        self.base_address
+    }
+
+    pub fn abs_path(&self) -> Option<&Path> {
+        match &self.source {
+            Source::File { abs_path, .. } => Some(abs_path),
+            Source::Memory { .. } => None,
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.saved_depth != Some(self.undo_stack.len())
+    }
+
+    pub fn can_save(&self) -> bool {
+        match &self.source {
+            Source::File { .. } => true,
+            Source::Memory { save } => save.is_some(),
+        }
+    }
+
+    /// Overwrites the bytes at `offset`, dropping those past the end of the
+    /// buffer. With `merge`, the write is undone together with the previous
+    /// one, such as the two nibbles of a byte typed in the hex column.
+    pub fn write(&mut self, offset: usize, bytes: &[u8], merge: bool, cx: &mut Context<Self>) {
+        let end = (offset + bytes.len()).min(self.len());
+        if offset >= end {
+            return;
+        }
+        let new = &bytes[..end - offset];
+        let old = self.read(offset..end);
+        self.set_bytes(offset, new);
+
+        if self
+            .saved_depth
+            .is_some_and(|depth| depth > self.undo_stack.len())
+        {
+            self.saved_depth = None;
+        }
+        self.redo_stack.clear();
+        let can_merge = merge && self.saved_depth != Some(self.undo_stack.len());
+        match self.undo_stack.last_mut() {
+            Some(last)
+                if can_merge && offset <= last.offset + last.new.len() && end >= last.offset =>
+            {
+                let last_end = last.offset + last.new.len();
+                let start = last.offset.min(offset);
+                let merged_end = last_end.max(end);
+                let merged_new = self.read(start..merged_end);
+                // The last edit's old bytes take precedence, since this write
+                // overwrote what that edit had written.
+                let mut merged_old = merged_new.clone();
+                merged_old[offset - start..end - start].copy_from_slice(&old);
+                merged_old[last.offset - start..last_end - start].copy_from_slice(&last.old);
+                last.offset = start;
+                last.old = merged_old;
+                last.new = merged_new;
+            }
+            _ => self.undo_stack.push(Edit {
+                offset,
+                old,
+                new: new.to_vec(),
+            }),
+        }
+        cx.emit(HexBufferEvent::Edited { range: offset..end });
+    }
+
+    /// Reverts the last edit, returning the range it covered.
+    pub fn undo(&mut self, cx: &mut Context<Self>) -> Option<Range<usize>> {
+        let edit = self.undo_stack.pop()?;
+        let range = edit.offset..edit.offset + edit.old.len();
+        self.set_bytes(edit.offset, &edit.old);
+        self.redo_stack.push(edit);
+        cx.emit(HexBufferEvent::Edited {
+            range: range.clone(),
+        });
+        Some(range)
+    }
+
+    /// Reapplies the last undone edit, returning the range it covered.
+    pub fn redo(&mut self, cx: &mut Context<Self>) -> Option<Range<usize>> {
+        let edit = self.redo_stack.pop()?;
+        let range = edit.offset..edit.offset + edit.new.len();
+        self.set_bytes(edit.offset, &edit.new);
+        self.undo_stack.push(edit);
+        cx.emit(HexBufferEvent::Edited {
+            range: range.clone(),
+        });
+        Some(range)
+    }
+
+    fn set_bytes(&mut self, offset: usize, bytes: &[u8]) {
+        for (ix, &byte) in bytes.iter().enumerate() {
+            self.changes.insert(offset + ix, byte);
+        }
+    }
+
+    /// Returns the runs of consecutive changed bytes, with their offsets.
+    fn changed_runs(&self) -> Vec<(usize, Vec<u8>)> {
+        let mut runs: Vec<(usize, Vec<u8>)> = Vec::new();
+        for (&offset, &byte) in &self.changes {
+            match runs.last_mut() {
+                Some((start, bytes)) if *start + bytes.len() == offset => bytes.push(byte),
+                _ => runs.push((offset, vec![byte])),
+            }
+        }
+        runs
+    }
+
+    /// Saves the buffer. Files are overwritten in place, so that only the
+    /// changed bytes are written.
+    pub fn save(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let write = match &self.source {
+            Source::File { abs_path, fs, .. } => {
+                let abs_path = abs_path.clone();
+                let fs = fs.clone();
+                let runs = self.changed_runs();
+                cx.background_spawn(async move { fs.overwrite_ranges(&abs_path, runs).await })
+            }
+            Source::Memory { save: Some(save) } => save(self.read(0..self.len()).into(), cx),
+            Source::Memory { save: None } => {
+                return Task::ready(Err(anyhow::anyhow!("{} can't be saved", self.title)));
+            }
+        };
+        let depth = self.undo_stack.len();
+        cx.spawn(async move |this, cx| {
+            write.await?;
+            this.update(cx, |this, cx| {
+                this.saved_depth = Some(depth);
+                cx.emit(HexBufferEvent::Saved);
+            })
+        })
+    }
+
+    /// Discards the edits and loads the file again.
+    pub fn reload(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Source::File { abs_path, fs, .. } = &self.source else {
+            return Task::ready(Ok(()));
+        };
+        let abs_path = abs_path.clone();
+        let fs = fs.clone();
+        cx.spawn(async move |this, cx| {
+            let file = cx
+                .background_spawn(async move { fs.open_windowed(&abs_path) })
+                .await?;
+            this.update(cx, |this, cx| {
+                let len = file.len().max(this.len());
+                this.contents = Contents::File(file);
+                this.changes.clear();
+                this.undo_stack.clear();
+                this.redo_stack.clear();
+                this.saved_depth = Some(0);
+                cx.emit(HexBufferEvent::Edited { range: 0..len });
+                cx.emit(HexBufferEvent::Saved);
+            })
+        })
+    }
+}
+
+/// Returns whether the start of a file looks like binary data rather than
+/// text, because it contains NUL bytes or isn't valid UTF-8. UTF-16 text is
+/// recognized by its byte order mark.
+pub fn is_binary_content(prefix: &[u8]) -> bool {
+    if prefix.starts_with(&[0xff, 0xfe]) || prefix.starts_with(&[0xfe, 0xff]) {
+        return false;
+    }
+    if prefix.contains(&0) {
+        return true;
+    }
+    // A character may have been cut off at the end of the prefix.
+    str::from_utf8(prefix).is_err_and(|error| error.error_len().is_some())
+}
+
+fn is_binary_file(fs: &dyn Fs, path: &Path) -> bool {
+    fs.open_windowed(path)
+        .is_ok_and(|file| is_binary_content(&file.read(0..SNIFF_LEN)))
+}
+
+impl project::ProjectItem for HexBuffer {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        let project_ref = project.read(cx);
+        if !project_ref.is_local()
+            || !project_ref
+                .entry_for_path(path, cx)
+                .is_some_and(|entry| entry.is_file())
+        {
+            return None;
+        }
+        // Only the start of the file is read, which keeps this check cheap
+        // enough to make while choosing how to open it.
+        let abs_path = project_ref.absolute_path(path, cx)?;
+        if !is_binary_file(project_ref.fs().as_ref(), &abs_path) {
+            return None;
+        }
+        Some(Self::open(project, path.clone(), cx))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        match &self.source {
+            Source::File { entry_id, .. } => *entry_id,
+            Source::Memory { .. } => None,
+        }
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        match &self.source {
+            Source::File { project_path, .. } => Some(project_path.clone()),
+            Source::Memory { .. } => None,
+        }
+    }
+
+    fn is_dirty(&self) -> bool {
+        HexBuffer::is_dirty(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use project::WorktreeId;
+    use util::{path, rel_path::RelPath};
+
+    #[gpui::test]
+    fn test_undo_redo(cx: &mut TestAppContext) {
+        let buffer = cx.new(|_| HexBuffer::from_bytes("memory", 0x1000, vec![0; 8], None));
+        buffer.update(cx, |buffer, cx| {
+            assert!(!buffer.is_dirty());
+
+            // Two nibbles typed into the same byte are undone together.
+            buffer.write(2, &[0xa0], false, cx);
+            buffer.write(2, &[0xab], true, cx);
+            buffer.write(3, &[0xcd], false, cx);
+            assert_eq!(
+                buffer.read(0..buffer.len()),
+                &[0, 0, 0xab, 0xcd, 0, 0, 0, 0]
+            );
+            assert!(buffer.is_dirty());
+
+            assert_eq!(buffer.undo(cx), Some(3..4));
+            assert_eq!(buffer.undo(cx), Some(2..3));
+            assert_eq!(buffer.read(0..buffer.len()), &[0; 8]);
+            assert!(!buffer.is_dirty());
+            assert_eq!(buffer.undo(cx), None);
+
+            assert_eq!(buffer.redo(cx), Some(2..3));
+            assert_eq!(buffer.read(0..buffer.len()), &[0, 0, 0xab, 0, 0, 0, 0, 0]);
+
+            // Writes past the end of the buffer are truncated.
+            buffer.write(6, b"xyz", false, cx);
+            assert_eq!(
+                buffer.read(0..buffer.len()),
+                &[0, 0, 0xab, 0, 0, 0, b'x', b'y']
+            );
+            assert_eq!(buffer.redo(cx), None);
+        });
+    }
+
+    #[gpui::test]
+    fn test_merged_writes(cx: &mut TestAppContext) {
+        let buffer = cx.new(|_| HexBuffer::from_bytes("memory", 0, vec![1, 2, 3, 4], None));
+        buffer.update(cx, |buffer, cx| {
+            buffer.write(1, &[9], false, cx);
+            buffer.write(2, &[8, 7], true, cx);
+            buffer.write(0, &[6, 5], true, cx);
+            assert_eq!(buffer.read(0..buffer.len()), &[6, 5, 8, 7]);
+            assert_eq!(buffer.undo(cx), Some(0..4));
+            assert_eq!(buffer.read(0..buffer.len()), &[1, 2, 3, 4]);
+            assert_eq!(buffer.redo(cx), Some(0..4));
+            assert_eq!(buffer.read(0..buffer.len()), &[6, 5, 8, 7]);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_file_edits(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let abs_path = PathBuf::from(path!("/root/data.bin"));
+        fs.insert_file(&abs_path, vec![0, 1, 2, 3, 4, 5]).await;
+        fs.insert_file(path!("/root/main.rs"), b"fn main() {}\n".to_vec())
+            .await;
+        assert!(is_binary_file(fs.as_ref(), &abs_path));
+        assert!(!is_binary_file(
+            fs.as_ref(),
+            path!("/root/main.rs").as_ref()
+        ));
+
+        let source = Source::File {
+            project_path: ProjectPath {
+                worktree_id: WorktreeId::from_usize(1),
+                path: RelPath::unix("data.bin").unwrap().into(),
+            },
+            entry_id: None,
+            abs_path: abs_path.clone(),
+            fs: fs.clone(),
+        };
+        let contents = Contents::File(fs.open_windowed(&abs_path).unwrap());
+        let buffer = cx.new(|_| HexBuffer::new("data.bin".into(), 0, contents, source));
+
+        buffer.update(cx, |buffer, cx| {
+            buffer.write(1, &[0xaa, 0xbb], false, cx);
+            buffer.write(5, &[0xcc, 0xdd], false, cx);
+            assert_eq!(buffer.read(0..6), &[0, 0xaa, 0xbb, 3, 4, 0xcc]);
+            assert_eq!(buffer.read(2..4), &[0xbb, 3]);
+            assert_eq!(buffer.byte_at(5), Some(0xcc));
+            assert_eq!(buffer.byte_at(6), None);
+        });
+        buffer
+            .update(cx, |buffer, cx| buffer.save(cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.read_file_sync(&abs_path).unwrap(),
+            [0, 0xaa, 0xbb, 3, 4, 0xcc]
+        );
+
+        buffer.update(cx, |buffer, cx| {
+            assert!(!buffer.is_dirty());
+            assert_eq!(buffer.undo(cx), Some(5..6));
+            assert_eq!(buffer.read(0..6), &[0, 0xaa, 0xbb, 3, 4, 5]);
+            assert!(buffer.is_dirty());
+        });
+    }
+
+    #[test]
+    fn test_is_binary_content() {
+        assert!(is_binary_content(b"ELF\x02\x01\x00\x00"));
+        assert!(is_binary_content(b"caf\xe9 au lait"));
+        assert!(!is_binary_content(b"fn main() {}\n"));
+        assert!(!is_binary_content("caf\u{e9}".as_bytes()));
+        // A character cut off by the end of the sniffed prefix is still text.
+        assert!(!is_binary_content(&"caf\u{e9}".as_bytes()[..4]));
+        assert!(!is_binary_content(b"\xff\xfeh\x00i\x00"));
+    }
+}
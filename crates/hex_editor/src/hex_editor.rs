@@ -0,0 +1,1246 @@
+mod byte_pattern;
+mod hex_buffer;
+mod inspector;
+
+use std::{ops::Range, path::Path};
+
+use anyhow::Result;
+use editor::{Editor, EditorEvent};
+use file_icons::FileIcons;
+use gpui::{
+    App, Context, Entity, EventEmitter, FocusHandle, Focusable, InteractiveElement, IntoElement,
+    KeyDownEvent, MouseButton, MouseDownEvent, MouseMoveEvent, ParentElement, Pixels, Render,
+    ScrollStrategy, SharedString, Subscription, Task, UniformListScrollHandle, Window, actions,
+    div, px, uniform_list,
+};
+use project::Project;
+use settings::Settings;
+use theme::ThemeSettings;
+use ui::{Tooltip, prelude::*};
+use util::paths::PathExt;
+use workspace::{
+    ItemSettings, Pane, SaveIntent, Workspace, WorkspaceId,
+    invalid_item_view::InvalidItemView,
+    item::{Item, ItemBufferKind, ItemEvent, ProjectItem, SaveOptions},
+    searchable::Direction,
+};
+use zed_actions::hex_editor::OpenInHexEditor;
+
+use crate::byte_pattern::BytePattern;
+pub use crate::hex_buffer::{HexBuffer, HexBufferEvent, SaveBytes, is_binary_content};
+pub use crate::inspector::{Endianness, inspect};
+
+actions!(
+    hex_editor,
+    [
+        /// Focuses the search input.
+        Search,
+        /// Focuses the go to offset input.
+        GoToOffset,
+        /// Selects the next search match.
+        SelectNextMatch,
+        /// Selects the previous search match.
+        SelectPreviousMatch,
+        /// Toggles between searching for hex bytes and for text.
+        ToggleSearchMode,
+        /// Toggles between decoding the text column as ASCII and as UTF-8.
+        ToggleTextEncoding,
+        /// Toggles the byte order used by the inspector.
+        ToggleEndianness,
+        /// Moves the cursor between the hex and text columns.
+        SwitchColumn,
+        /// Moves the cursor to the previous byte.
+        MoveLeft,
+        /// Moves the cursor to the next byte.
+        MoveRight,
+        /// Moves the cursor to the previous row.
+        MoveUp,
+        /// Moves the cursor to the next row.
+        MoveDown,
+        /// Moves the cursor up by a page.
+        PageUp,
+        /// Moves the cursor down by a page.
+        PageDown,
+        /// Moves the cursor to the start of its row.
+        MoveToRowStart,
+        /// Moves the cursor to the end of its row.
+        MoveToRowEnd,
+        /// Moves the cursor to the first byte.
+        MoveToStart,
+        /// Moves the cursor to the last byte.
+        MoveToEnd,
+        /// Extends the selection to the previous byte.
+        SelectLeft,
+        /// Extends the selection to the next byte.
+        SelectRight,
+        /// Extends the selection to the previous row.
+        SelectUp,
+        /// Extends the selection to the next row.
+        SelectDown,
+        /// Undoes the last edit.
+        Undo,
+        /// Redoes the last undone edit.
+        Redo,
+    ]
+);
+
+const BYTES_PER_ROW: usize = 16;
+const HEX_PLACEHOLDER: &str = "Search hex bytes, like `DE AD ?? EF`…";
+/// The number of bytes inspected at the cursor, enough for an `f64`.
+const INSPECTED_LEN: usize = 8;
+/// The number of bytes read at once when searching.
+const SEARCH_WINDOW_LEN: usize = 1024 * 1024;
+
+pub fn init(cx: &mut App) {
+    workspace::register_project_item::<HexEditor>(cx);
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(open_in_hex_editor);
+    })
+    .detach();
+}
+
+/// Opens a file in the hex editor, replacing the active item when it failed
+/// to open that file.
+fn open_in_hex_editor(
+    workspace: &mut Workspace,
+    action: &OpenInHexEditor,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let project = workspace.project().clone();
+    let active_item = workspace.active_item(cx);
+    let active_abs_path = active_item.as_ref().and_then(|item| {
+        if let Some(invalid_item) = item.downcast::<InvalidItemView>() {
+            Some(invalid_item.read(cx).abs_path.to_path_buf())
+        } else {
+            let project_path = item.project_path(cx)?;
+            project.read(cx).absolute_path(&project_path, cx)
+        }
+    });
+    let Some(abs_path) = action.abs_path.clone().or_else(|| active_abs_path.clone()) else {
+        return;
+    };
+    let replaced_item = active_item
+        .filter(|item| {
+            item.downcast::<InvalidItemView>().is_some()
+                && active_abs_path.as_ref() == Some(&abs_path)
+        })
+        .map(|item| item.item_id());
+
+    let pane = workspace.active_pane().clone();
+    let existing = pane
+        .read(cx)
+        .items_of_type::<HexEditor>()
+        .find(|editor| editor.read(cx).buffer.read(cx).abs_path() == Some(abs_path.as_path()));
+    if let Some(existing) = existing {
+        workspace.activate_item(&existing, true, true, window, cx);
+        return;
+    }
+
+    let project_path = Workspace::project_path_for_path(project.clone(), &abs_path, false, cx);
+    cx.spawn_in(window, async move |workspace, cx| {
+        let (_, project_path) = project_path.await?;
+        let buffer = cx
+            .update(|_, cx| HexBuffer::open(&project, project_path, cx))?
+            .await?;
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.open_project_item::<HexEditor>(
+                pane.clone(),
+                buffer,
+                true,
+                true,
+                false,
+                false,
+                window,
+                cx,
+            );
+            if let Some(item_id) = replaced_item {
+                pane.update(cx, |pane, cx| {
+                    pane.close_item_by_id(item_id, SaveIntent::Skip, window, cx)
+                })
+                .detach_and_log_err(cx);
+            }
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Column {
+    Hex,
+    Text,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextEncoding {
+    #[default]
+    Ascii,
+    Utf8,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum SearchMode {
+    #[default]
+    Hex,
+    Text,
+}
+
+pub enum HexEditorEvent {
+    Edited,
+    Saved,
+}
+
+/// Shows a [`HexBuffer`] as rows of offsets, hex bytes and text, sharing a
+/// single cursor between the hex and text columns.
+pub struct HexEditor {
+    buffer: Entity<HexBuffer>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    query_editor: Entity<Editor>,
+    offset_editor: Entity<Editor>,
+    cursor: usize,
+    /// The other end of the selection, which otherwise only covers the cursor.
+    selection_tail: Option<usize>,
+    column: Column,
+    /// Whether the high nibble of the byte at the cursor was typed, so the
+    /// next hex digit completes that byte.
+    pending_low_nibble: bool,
+    text_encoding: TextEncoding,
+    endianness: Endianness,
+    search_mode: SearchMode,
+    pattern: Option<BytePattern>,
+    search_error: Option<SharedString>,
+    visible_rows: usize,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl HexEditor {
+    pub fn new(buffer: Entity<HexBuffer>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let query_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text(HEX_PLACEHOLDER, window, cx);
+            editor
+        });
+        let offset_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Go to offset…", window, cx);
+            editor
+        });
+        let subscriptions = vec![
+            cx.subscribe(&buffer, |this, _, event, cx| {
+                this.on_buffer_event(event, cx)
+            }),
+            cx.subscribe(&query_editor, |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::Edited { .. } = event {
+                    this.update_query(cx);
+                }
+            }),
+        ];
+
+        Self {
+            buffer,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            query_editor,
+            offset_editor,
+            cursor: 0,
+            selection_tail: None,
+            column: Column::Hex,
+            pending_low_nibble: false,
+            text_encoding: TextEncoding::default(),
+            endianness: Endianness::default(),
+            search_mode: SearchMode::default(),
+            pattern: None,
+            search_error: None,
+            visible_rows: 0,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn buffer(&self) -> &Entity<HexBuffer> {
+        &self.buffer
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The selected byte range, which always includes the byte at the cursor.
+    pub fn selection(&self) -> Range<usize> {
+        let tail = self.selection_tail.unwrap_or(self.cursor);
+        self.cursor.min(tail)..self.cursor.max(tail) + 1
+    }
+
+    fn on_buffer_event(&mut self, event: &HexBufferEvent, cx: &mut Context<Self>) {
+        match event {
+            HexBufferEvent::Edited { .. } => cx.emit(HexEditorEvent::Edited),
+            HexBufferEvent::Saved => cx.emit(HexEditorEvent::Saved),
+        }
+        cx.notify();
+    }
+
+    fn last_offset(&self, cx: &App) -> usize {
+        self.buffer.read(cx).len().saturating_sub(1)
+    }
+
+    fn set_cursor(&mut self, offset: usize, select: bool, cx: &mut Context<Self>) {
+        if select {
+            self.selection_tail.get_or_insert(self.cursor);
+        } else {
+            self.selection_tail = None;
+        }
+        self.cursor = offset.min(self.last_offset(cx));
+        self.pending_low_nibble = false;
+        self.scroll_handle
+            .scroll_to_item(self.cursor / BYTES_PER_ROW, ScrollStrategy::Nearest);
+        cx.notify();
+    }
+
+    fn move_cursor(&mut self, delta: isize, select: bool, cx: &mut Context<Self>) {
+        let offset = self.cursor.saturating_add_signed(delta);
+        self.set_cursor(offset, select, cx);
+    }
+
+    /// The number of bytes in all but one of the visible rows.
+    fn page_len(&self) -> usize {
+        self.visible_rows.saturating_sub(1).max(1) * BYTES_PER_ROW
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(-1, false, cx);
+    }
+
+    fn move_right(&mut self, _: &MoveRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(1, false, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(-(BYTES_PER_ROW as isize), false, cx);
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(BYTES_PER_ROW as isize, false, cx);
+    }
+
+    fn select_left(&mut self, _: &SelectLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(-1, true, cx);
+    }
+
+    fn select_right(&mut self, _: &SelectRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(1, true, cx);
+    }
+
+    fn select_up(&mut self, _: &SelectUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(-(BYTES_PER_ROW as isize), true, cx);
+    }
+
+    fn select_down(&mut self, _: &SelectDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor(BYTES_PER_ROW as isize, true, cx);
+    }
+
+    fn page_up(&mut self, _: &PageUp, _: &mut Window, cx: &mut Context<Self>) {
+        let offset = self.cursor.saturating_sub(self.page_len());
+        self.set_cursor(offset, false, cx);
+    }
+
+    fn page_down(&mut self, _: &PageDown, _: &mut Window, cx: &mut Context<Self>) {
+        let offset = self.cursor.saturating_add(self.page_len());
+        self.set_cursor(offset, false, cx);
+    }
+
+    fn move_to_row_start(&mut self, _: &MoveToRowStart, _: &mut Window, cx: &mut Context<Self>) {
+        let offset = self.cursor - self.cursor % BYTES_PER_ROW;
+        self.set_cursor(offset, false, cx);
+    }
+
+    fn move_to_row_end(&mut self, _: &MoveToRowEnd, _: &mut Window, cx: &mut Context<Self>) {
+        let offset = self.cursor - self.cursor % BYTES_PER_ROW + BYTES_PER_ROW - 1;
+        self.set_cursor(offset, false, cx);
+    }
+
+    fn move_to_start(&mut self, _: &MoveToStart, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_cursor(0, false, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_cursor(usize::MAX, false, cx);
+    }
+
+    fn switch_column(&mut self, _: &SwitchColumn, _: &mut Window, cx: &mut Context<Self>) {
+        self.column = match self.column {
+            Column::Hex => Column::Text,
+            Column::Text => Column::Hex,
+        };
+        self.pending_low_nibble = false;
+        cx.notify();
+    }
+
+    fn undo(&mut self, _: &Undo, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(range) = self.buffer.update(cx, |buffer, cx| buffer.undo(cx)) {
+            self.set_cursor(range.start, false, cx);
+        }
+    }
+
+    fn redo(&mut self, _: &Redo, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(range) = self.buffer.update(cx, |buffer, cx| buffer.redo(cx)) {
+            self.set_cursor(range.start, false, cx);
+        }
+    }
+
+    fn handle_key_down(
+        &mut self,
+        event: &KeyDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // The inputs in the header handle their own keystrokes.
+        if !self.focus_handle.is_focused(window) {
+            return;
+        }
+        let keystroke = &event.keystroke;
+        let modifiers = keystroke.modifiers;
+        if modifiers.control || modifiers.platform || modifiers.function {
+            return;
+        }
+        let Some(text) = keystroke.key_char.as_deref() else {
+            return;
+        };
+        let handled = match self.column {
+            Column::Hex => {
+                let mut handled = false;
+                for digit in text.chars().filter_map(|c| c.to_digit(16)) {
+                    handled |= self.type_nibble(digit as u8, cx);
+                }
+                handled
+            }
+            Column::Text => self.type_text(text, cx),
+        };
+        if handled {
+            cx.stop_propagation();
+        }
+    }
+
+    fn type_nibble(&mut self, digit: u8, cx: &mut Context<Self>) -> bool {
+        let Some(byte) = self.buffer.read(cx).byte_at(self.cursor) else {
+            return false;
+        };
+        let cursor = self.cursor;
+        if self.pending_low_nibble {
+            let byte = (byte & 0xf0) | digit;
+            self.buffer
+                .update(cx, |buffer, cx| buffer.write(cursor, &[byte], true, cx));
+            if cursor < self.last_offset(cx) {
+                self.set_cursor(cursor + 1, false, cx);
+            } else {
+                self.pending_low_nibble = false;
+            }
+        } else {
+            let byte = (digit << 4) | (byte & 0x0f);
+            self.buffer
+                .update(cx, |buffer, cx| buffer.write(cursor, &[byte], false, cx));
+            self.selection_tail = None;
+            self.pending_low_nibble = true;
+        }
+        true
+    }
+
+    fn type_text(&mut self, text: &str, cx: &mut Context<Self>) -> bool {
+        if text.chars().any(char::is_control) {
+            return false;
+        }
+        let bytes = match self.text_encoding {
+            TextEncoding::Ascii if text.is_ascii() => text.as_bytes(),
+            TextEncoding::Ascii => return false,
+            TextEncoding::Utf8 => text.as_bytes(),
+        };
+        if bytes.is_empty() || self.buffer.read(cx).is_empty() {
+            return false;
+        }
+        let cursor = self.cursor;
+        self.buffer
+            .update(cx, |buffer, cx| buffer.write(cursor, bytes, false, cx));
+        self.set_cursor(cursor + bytes.len(), false, cx);
+        true
+    }
+
+    fn handle_cell_mouse_down(
+        &mut self,
+        offset: usize,
+        column: Column,
+        event: &MouseDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.focus(&self.focus_handle, cx);
+        self.column = column;
+        self.set_cursor(offset, event.modifiers.shift, cx);
+    }
+
+    fn handle_cell_mouse_move(
+        &mut self,
+        offset: usize,
+        column: Column,
+        event: &MouseMoveEvent,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if event.pressed_button != Some(MouseButton::Left) || offset == self.cursor {
+            return;
+        }
+        self.column = column;
+        self.set_cursor(offset, true, cx);
+    }
+
+    fn focus_search(&mut self, _: &Search, window: &mut Window, cx: &mut Context<Self>) {
+        let focus_handle = self.query_editor.focus_handle(cx);
+        window.focus(&focus_handle, cx);
+    }
+
+    fn focus_offset_input(&mut self, _: &GoToOffset, window: &mut Window, cx: &mut Context<Self>) {
+        let focus_handle = self.offset_editor.focus_handle(cx);
+        window.focus(&focus_handle, cx);
+    }
+
+    fn confirm_go_to_offset(
+        &mut self,
+        _: &menu::Confirm,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let text = self.offset_editor.read(cx).text(cx);
+        let base_address = self.buffer.read(cx).base_address();
+        let Some(offset) = parse_offset(&text, base_address) else {
+            return;
+        };
+        self.set_cursor(offset, false, cx);
+        self.offset_editor
+            .update(cx, |editor, cx| editor.clear(window, cx));
+        window.focus(&self.focus_handle, cx);
+    }
+
+    fn toggle_search_mode(
+        &mut self,
+        _: &ToggleSearchMode,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let (search_mode, placeholder) = match self.search_mode {
+            SearchMode::Hex => (SearchMode::Text, "Search text…"),
+            SearchMode::Text => (SearchMode::Hex, HEX_PLACEHOLDER),
+        };
+        self.search_mode = search_mode;
+        self.query_editor.update(cx, |editor, cx| {
+            editor.set_placeholder_text(placeholder, window, cx)
+        });
+        self.update_query(cx);
+    }
+
+    fn toggle_text_encoding(
+        &mut self,
+        _: &ToggleTextEncoding,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.text_encoding = match self.text_encoding {
+            TextEncoding::Ascii => TextEncoding::Utf8,
+            TextEncoding::Utf8 => TextEncoding::Ascii,
+        };
+        cx.notify();
+    }
+
+    fn toggle_endianness(&mut self, _: &ToggleEndianness, _: &mut Window, cx: &mut Context<Self>) {
+        self.endianness = match self.endianness {
+            Endianness::Little => Endianness::Big,
+            Endianness::Big => Endianness::Little,
+        };
+        cx.notify();
+    }
+
+    fn update_query(&mut self, cx: &mut Context<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        let pattern = match self.search_mode {
+            SearchMode::Hex => BytePattern::parse_hex(&query),
+            SearchMode::Text => Ok(BytePattern::text(&query)),
+        };
+        (self.pattern, self.search_error) = match pattern {
+            Ok(pattern) if pattern.is_empty() => (None, None),
+            Ok(pattern) => (Some(pattern), None),
+            Err(error) => (None, Some(error.to_string().into())),
+        };
+        cx.notify();
+    }
+
+    fn select_next_match(&mut self, _: &SelectNextMatch, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_match(Direction::Next, cx);
+    }
+
+    fn select_previous_match(
+        &mut self,
+        _: &SelectPreviousMatch,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.select_match(Direction::Prev, cx);
+    }
+
+    fn confirm_search(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_match(Direction::Next, cx);
+    }
+
+    /// Selects the closest match in the given direction, wrapping around the
+    /// ends of the buffer.
+    fn select_match(&mut self, direction: Direction, cx: &mut Context<Self>) {
+        let Some(pattern) = &self.pattern else {
+            return;
+        };
+        let buffer = self.buffer.read(cx);
+        let start = match direction {
+            Direction::Next => find_next(buffer, pattern, self.selection().start + 1)
+                .or_else(|| find_next(buffer, pattern, 0)),
+            Direction::Prev => find_previous(buffer, pattern, self.selection().start)
+                .or_else(|| find_previous(buffer, pattern, buffer.len())),
+        };
+        let Some(start) = start else {
+            self.search_error = Some("No matches".into());
+            cx.notify();
+            return;
+        };
+        let end = start + pattern.len() - 1;
+        self.search_error = None;
+        self.set_cursor(start, false, cx);
+        self.selection_tail = Some(self.cursor);
+        self.cursor = end;
+        cx.notify();
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = cx.theme().colors();
+        let (border, border_variant, toolbar_background) = (
+            colors.border,
+            colors.border_variant,
+            colors.toolbar_background,
+        );
+        let has_pattern = self.pattern.is_some();
+
+        h_flex()
+            .w_full()
+            .gap_2()
+            .px_2()
+            .py_1()
+            .border_b_1()
+            .border_color(border_variant)
+            .bg(toolbar_background)
+            .child(
+                h_flex()
+                    .key_context("HexEditorSearch")
+                    .on_action(cx.listener(Self::confirm_search))
+                    .w(px(320.))
+                    .px_2()
+                    .py_0p5()
+                    .border_1()
+                    .border_color(border)
+                    .rounded_md()
+                    .child(self.query_editor.clone()),
+            )
+            .child(
+                IconButton::new("search-text", IconName::CaseSensitive)
+                    .icon_size(IconSize::Small)
+                    .toggle_state(self.search_mode == SearchMode::Text)
+                    .tooltip(|_window, cx| {
+                        Tooltip::for_action("Search Text", &ToggleSearchMode, cx)
+                    })
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.toggle_search_mode(&ToggleSearchMode, window, cx);
+                    })),
+            )
+            .child(
+                IconButton::new("previous-match", IconName::ChevronUp)
+                    .icon_size(IconSize::Small)
+                    .disabled(!has_pattern)
+                    .tooltip(|_window, cx| {
+                        Tooltip::for_action("Previous Match", &SelectPreviousMatch, cx)
+                    })
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.select_match(Direction::Prev, cx);
+                    })),
+            )
+            .child(
+                IconButton::new("next-match", IconName::ChevronDown)
+                    .icon_size(IconSize::Small)
+                    .disabled(!has_pattern)
+                    .tooltip(|_window, cx| Tooltip::for_action("Next Match", &SelectNextMatch, cx))
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.select_match(Direction::Next, cx);
+                    })),
+            )
+            .children(
+                self.search_error
+                    .clone()
+                    .map(|error| Label::new(error).size(LabelSize::Small).color(Color::Error)),
+            )
+            .child(div().flex_1())
+            .child(
+                h_flex()
+                    .key_context("HexEditorGoToOffset")
+                    .on_action(cx.listener(Self::confirm_go_to_offset))
+                    .w(px(160.))
+                    .px_2()
+                    .py_0p5()
+                    .border_1()
+                    .border_color(border)
+                    .rounded_md()
+                    .child(self.offset_editor.clone()),
+            )
+            .child(
+                Button::new(
+                    "text-encoding",
+                    match self.text_encoding {
+                        TextEncoding::Ascii => "ASCII",
+                        TextEncoding::Utf8 => "UTF-8",
+                    },
+                )
+                .label_size(LabelSize::Small)
+                .tooltip(|_window, cx| {
+                    Tooltip::for_action("Toggle Text Encoding", &ToggleTextEncoding, cx)
+                })
+                .on_click(cx.listener(|this, _, window, cx| {
+                    this.toggle_text_encoding(&ToggleTextEncoding, window, cx);
+                })),
+            )
+    }
+
+    fn render_rows(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let font = settings.buffer_font.clone();
+        let font_size = settings.buffer_font_size(cx);
+        let line_height = font_size * settings.line_height();
+        let colors = cx.theme().colors();
+        let (text_color, background) = (colors.editor_foreground, colors.editor_background);
+        let row_count = self.buffer.read(cx).len().div_ceil(BYTES_PER_ROW);
+
+        uniform_list(
+            "hex-editor-rows",
+            row_count,
+            cx.processor(move |this, range: Range<usize>, window, cx| {
+                this.visible_rows = range.len();
+                range
+                    .map(|row| this.render_row(row, line_height, window, cx))
+                    .collect()
+            }),
+        )
+        .track_scroll(&self.scroll_handle)
+        .flex_1()
+        .size_full()
+        .font(font)
+        .text_size(font_size)
+        .text_color(text_color)
+        .bg(background)
+    }
+
+    fn render_row(
+        &self,
+        row: usize,
+        line_height: Pixels,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let settings = ThemeSettings::get_global(cx);
+        let font_id = window.text_system().resolve_font(&settings.buffer_font);
+        let font_size = settings.buffer_font_size(cx);
+        let em_advance = window
+            .text_system()
+            .em_advance(font_id, font_size)
+            .unwrap_or(font_size);
+        let colors = cx.theme().colors();
+        let player = cx.theme().players().local();
+        let (line_number_color, match_background, editor_background) = (
+            colors.editor_line_number,
+            colors.search_match_background,
+            colors.editor_background,
+        );
+
+        let buffer = self.buffer.read(cx);
+        let row_range = row * BYTES_PER_ROW..((row + 1) * BYTES_PER_ROW).min(buffer.len());
+        let address_width = address_width(buffer.base_address(), buffer.len());
+        let address = buffer.base_address() + row_range.start as u64;
+        let selection = self.selection();
+        let matches = self.matches_in_range(row_range.clone(), cx);
+        // UTF-8 sequences may cross the boundaries of the row.
+        let bytes_start = row_range.start.saturating_sub(3);
+        let bytes = buffer.read(bytes_start..row_range.end + 3);
+        // The file may have been truncated since it was opened.
+        let bytes_row_end = (row_range.end - bytes_start).min(bytes.len());
+        let bytes_row_range = (row_range.start - bytes_start).min(bytes_row_end)..bytes_row_end;
+        let text = decode_text(&bytes, bytes_row_range.clone(), self.text_encoding);
+        let row_bytes = bytes[bytes_row_range].to_vec();
+        let focused = self.focus_handle.is_focused(window);
+
+        let cell = |offset: usize, column: Column, label: SharedString, cx: &Context<Self>| {
+            let is_cursor = offset == self.cursor;
+            let background = if is_cursor && column == self.column && focused {
+                Some(player.cursor)
+            } else if selection.contains(&offset) && selection.len() > 1 {
+                Some(player.selection)
+            } else if matches.iter().any(|mat| mat.contains(&offset)) {
+                Some(match_background)
+            } else {
+                None
+            };
+            let width = match column {
+                Column::Hex => em_advance * 2.,
+                Column::Text => em_advance,
+            };
+            div()
+                .id(match column {
+                    Column::Hex => ("hex", offset),
+                    Column::Text => ("text", offset),
+                })
+                .flex_none()
+                .w(width)
+                .when_some(background, |this, background| this.bg(background))
+                .when(is_cursor && column == self.column && focused, |this| {
+                    this.text_color(editor_background)
+                })
+                .when(is_cursor && column != self.column, |this| {
+                    this.border_b_1().border_color(player.cursor)
+                })
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |this, event, window, cx| {
+                        this.handle_cell_mouse_down(offset, column, event, window, cx)
+                    }),
+                )
+                .on_mouse_move(cx.listener(move |this, event, window, cx| {
+                    this.handle_cell_mouse_move(offset, column, event, window, cx)
+                }))
+                .child(label)
+        };
+
+        let mut hex_cells = Vec::with_capacity(BYTES_PER_ROW);
+        for (ix, byte) in row_bytes.iter().enumerate() {
+            let offset = row_range.start + ix;
+            let gap = if ix + 1 == BYTES_PER_ROW / 2 {
+                em_advance * 2.
+            } else {
+                em_advance
+            };
+            hex_cells.push(
+                cell(offset, Column::Hex, format!("{byte:02X}").into(), cx)
+                    .mr(gap)
+                    .into_any_element(),
+            );
+        }
+        let mut text_cells = Vec::with_capacity(BYTES_PER_ROW);
+        for (ix, c) in text.into_iter().enumerate() {
+            let offset = row_range.start + ix;
+            text_cells
+                .push(cell(offset, Column::Text, c.to_string().into(), cx).into_any_element());
+        }
+
+        h_flex()
+            .h(line_height)
+            .px_2()
+            .child(
+                div()
+                    .flex_none()
+                    .w(em_advance * (address_width + 2) as f32)
+                    .text_color(line_number_color)
+                    .child(format!("{address:0address_width$X}")),
+            )
+            .child(
+                h_flex()
+                    .flex_none()
+                    .w(em_advance * (BYTES_PER_ROW * 3 + 2) as f32)
+                    .children(hex_cells),
+            )
+            .child(h_flex().flex_none().children(text_cells))
+            .into_any_element()
+    }
+
+    /// Returns the matches of the search pattern overlapping the given range.
+    fn matches_in_range(&self, range: Range<usize>, cx: &App) -> Vec<Range<usize>> {
+        let Some(pattern) = &self.pattern else {
+            return Vec::new();
+        };
+        let window_start = range.start.saturating_sub(pattern.len() - 1);
+        let window = self
+            .buffer
+            .read(cx)
+            .read(window_start..range.end + pattern.len() - 1);
+        let mut matches = Vec::new();
+        let mut offset = 0;
+        while let Some(start) = pattern.find_next(&window, offset)
+            && window_start + start < range.end
+        {
+            matches.push(window_start + start..window_start + start + pattern.len());
+            offset = start + 1;
+        }
+        matches
+    }
+
+    fn render_inspector(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let buffer = self.buffer.read(cx);
+        let selection = self.selection();
+        let inspected = selection.start..(selection.start + INSPECTED_LEN).min(buffer.len());
+        let values = inspect(&buffer.read(inspected), self.endianness);
+        let address = buffer.base_address() + selection.start as u64;
+        let position = if selection.len() > 1 {
+            format!("{address:#X} ({} bytes selected)", selection.len())
+        } else {
+            format!("{address:#X}")
+        };
+        let colors = cx.theme().colors();
+        let (border_variant, toolbar_background) =
+            (colors.border_variant, colors.toolbar_background);
+
+        h_flex()
+            .w_full()
+            .flex_wrap()
+            .gap_x_4()
+            .px_2()
+            .py_1()
+            .border_t_1()
+            .border_color(border_variant)
+            .bg(toolbar_background)
+            .child(Label::new(position).size(LabelSize::Small))
+            .children(values.into_iter().map(|(name, value)| {
+                h_flex()
+                    .gap_1()
+                    .child(Label::new(name).size(LabelSize::Small).color(Color::Muted))
+                    .child(Label::new(value).size(LabelSize::Small).buffer_font(cx))
+            }))
+            .child(div().flex_1())
+            .child(
+                Button::new("endianness", self.endianness.label())
+                    .label_size(LabelSize::Small)
+                    .tooltip(|_window, cx| {
+                        Tooltip::for_action("Toggle Endianness", &ToggleEndianness, cx)
+                    })
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.toggle_endianness(&ToggleEndianness, window, cx);
+                    })),
+            )
+    }
+}
+
+/// Parses a decimal offset, or a hex one with a `0x` prefix or an `h` suffix.
+/// Offsets at or past the base address of a memory dump are treated as
+/// addresses.
+fn parse_offset(text: &str, base_address: u64) -> Option<usize> {
+    let text = text.trim();
+    let value = if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()?
+    } else if let Some(hex) = text.strip_suffix('h').or_else(|| text.strip_suffix('H')) {
+        u64::from_str_radix(hex, 16).ok()?
+    } else {
+        text.parse().ok()?
+    };
+    let offset = if base_address > 0 && value >= base_address {
+        value - base_address
+    } else {
+        value
+    };
+    usize::try_from(offset).ok()
+}
+
+/// The number of hex digits used to display addresses.
+fn address_width(base_address: u64, len: usize) -> usize {
+    let max_address = base_address.saturating_add(len as u64);
+    if max_address > u64::from(u32::MAX) {
+        16
+    } else {
+        8
+    }
+}
+
+/// Returns the first match starting at or after `offset`, searching the
+/// buffer one window at a time.
+fn find_next(buffer: &HexBuffer, pattern: &BytePattern, offset: usize) -> Option<usize> {
+    let mut window_start = offset;
+    while window_start < buffer.len() {
+        let window_end = window_start.saturating_add(SEARCH_WINDOW_LEN);
+        let window = buffer.read(window_start..window_end + pattern.len() - 1);
+        if let Some(start) = pattern.find_next(&window, 0) {
+            return Some(window_start + start);
+        }
+        window_start = window_end;
+    }
+    None
+}
+
+/// Returns the last match starting before `offset`, searching the buffer one
+/// window at a time.
+fn find_previous(buffer: &HexBuffer, pattern: &BytePattern, offset: usize) -> Option<usize> {
+    let mut window_end = offset.min(buffer.len());
+    while window_end > 0 {
+        let window_start = window_end.saturating_sub(SEARCH_WINDOW_LEN);
+        let window = buffer.read(window_start..window_end + pattern.len() - 1);
+        if let Some(start) = pattern.find_previous(&window, window_end - window_start) {
+            return Some(window_start + start);
+        }
+        window_end = window_start;
+    }
+    None
+}
+
+/// Decodes the bytes in `range` to one character per byte. Bytes that aren't
+/// printable are shown as dots, and the trailing bytes of a UTF-8 sequence as
+/// blanks.
+fn decode_text(bytes: &[u8], range: Range<usize>, encoding: TextEncoding) -> Vec<char> {
+    let printable = |c: char| if c.is_control() { '.' } else { c };
+    match encoding {
+        TextEncoding::Ascii => bytes[range]
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect(),
+        TextEncoding::Utf8 => {
+            let mut chars = Vec::with_capacity(range.len());
+            // A sequence starting in a previous row may spill into this one.
+            let mut offset = range.start.saturating_sub(3);
+            while offset < range.end {
+                let len = utf8_sequence_len(bytes, offset);
+                let decoded = str::from_utf8(&bytes[offset..offset + len])
+                    .ok()
+                    .and_then(|text| text.chars().next());
+                for ix in offset..offset + len {
+                    if range.contains(&ix) {
+                        chars.push(match decoded {
+                            Some(c) if ix == offset => printable(c),
+                            Some(_) => ' ',
+                            None => '.',
+                        });
+                    }
+                }
+                offset += len;
+            }
+            chars
+        }
+    }
+}
+
+/// The length of the UTF-8 sequence starting at `offset`, or 1 for bytes
+/// that don't start a complete sequence.
+fn utf8_sequence_len(bytes: &[u8], offset: usize) -> usize {
+    let len = match bytes[offset] {
+        0xc2..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf4 => 4,
+        _ => 1,
+    };
+    match bytes.get(offset..offset + len) {
+        Some(sequence) if str::from_utf8(sequence).is_ok() => len,
+        _ => 1,
+    }
+}
+
+impl EventEmitter<HexEditorEvent> for HexEditor {}
+
+impl Focusable for HexEditor {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for HexEditor {
+    type Event = HexEditorEvent;
+
+    fn to_item_events(event: &Self::Event, f: &mut dyn FnMut(ItemEvent)) {
+        match event {
+            HexEditorEvent::Edited => {
+                f(ItemEvent::Edit);
+                f(ItemEvent::UpdateTab);
+            }
+            HexEditorEvent::Saved => f(ItemEvent::UpdateTab),
+        }
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        let buffer = self.buffer.read(cx);
+        if buffer.abs_path().is_some() {
+            f(self.buffer.entity_id(), buffer)
+        }
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let buffer = self.buffer.read(cx);
+        match buffer.abs_path() {
+            Some(abs_path) => Some(abs_path.compact().to_string_lossy().into_owned().into()),
+            None => Some(buffer.title().clone()),
+        }
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.buffer.read(cx).title().clone()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let icon = self
+            .buffer
+            .read(cx)
+            .abs_path()
+            .filter(|_| ItemSettings::get_global(cx).file_icons)
+            .and_then(|path| FileIcons::get_icon(path, cx))
+            .map(Icon::from_path);
+        Some(icon.unwrap_or_else(|| Icon::new(IconName::Binary)))
+    }
+
+    fn buffer_kind(&self, _: &App) -> ItemBufferKind {
+        ItemBufferKind::Singleton
+    }
+
+    fn can_split(&self) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Entity<Self>>> {
+        let buffer = self.buffer.clone();
+        Task::ready(Some(cx.new(|cx| Self::new(buffer, window, cx))))
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.buffer.read(cx).can_save()
+    }
+
+    fn save(
+        &mut self,
+        _: SaveOptions,
+        _: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.buffer.update(cx, |buffer, cx| buffer.save(cx))
+    }
+
+    fn reload(
+        &mut self,
+        _: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.buffer.update(cx, |buffer, cx| buffer.reload(cx))
+    }
+}
+
+impl Render for HexEditor {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .track_focus(&self.focus_handle(cx))
+            .key_context("HexEditor")
+            .on_key_down(cx.listener(Self::handle_key_down))
+            .on_action(cx.listener(Self::focus_search))
+            .on_action(cx.listener(Self::focus_offset_input))
+            .on_action(cx.listener(Self::select_next_match))
+            .on_action(cx.listener(Self::select_previous_match))
+            .on_action(cx.listener(Self::toggle_search_mode))
+            .on_action(cx.listener(Self::toggle_text_encoding))
+            .on_action(cx.listener(Self::toggle_endianness))
+            .on_action(cx.listener(Self::switch_column))
+            .on_action(cx.listener(Self::move_left))
+            .on_action(cx.listener(Self::move_right))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::select_left))
+            .on_action(cx.listener(Self::select_right))
+            .on_action(cx.listener(Self::select_up))
+            .on_action(cx.listener(Self::select_down))
+            .on_action(cx.listener(Self::page_up))
+            .on_action(cx.listener(Self::page_down))
+            .on_action(cx.listener(Self::move_to_row_start))
+            .on_action(cx.listener(Self::move_to_row_end))
+            .on_action(cx.listener(Self::move_to_start))
+            .on_action(cx.listener(Self::move_to_end))
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
+            .size_full()
+            .child(self.render_header(cx))
+            .child(self.render_rows(cx))
+            .child(self.render_inspector(cx))
+    }
+}
+
+impl ProjectItem for HexEditor {
+    type Item = HexBuffer;
+
+    fn for_project_item(
+        _: Entity<Project>,
+        _: Option<&Pane>,
+        item: Entity<Self::Item>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, window, cx)
+    }
+
+    fn for_broken_project_item(
+        abs_path: &Path,
+        is_local: bool,
+        e: &anyhow::Error,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<InvalidItemView>
+    where
+        Self: Sized,
+    {
+        Some(InvalidItemView::new(abs_path, is_local, e, window, cx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("42", 0), Some(42));
+        assert_eq!(parse_offset(" 0x2A ", 0), Some(42));
+        assert_eq!(parse_offset("2ah", 0), Some(42));
+        assert_eq!(parse_offset("0x1010", 0x1000), Some(0x10));
+        assert_eq!(parse_offset("16", 0x1000), Some(16));
+        assert_eq!(parse_offset("0xzz", 0), None);
+        assert_eq!(parse_offset("", 0), None);
+    }
+
+    #[test]
+    fn test_decode_text() {
+        let bytes = "a\u{e9}b\u{1}\u{20ac}".as_bytes();
+        assert_eq!(
+            decode_text(bytes, 0..bytes.len(), TextEncoding::Ascii),
+            vec!['a', '.', '.', 'b', '.', '.', '.', '.']
+        );
+        assert_eq!(
+            decode_text(bytes, 0..bytes.len(), TextEncoding::Utf8),
+            vec!['a', '\u{e9}', ' ', 'b', '.', '\u{20ac}', ' ', ' ']
+        );
+        // Rows starting within a sequence show its trailing bytes as blanks.
+        assert_eq!(
+            decode_text(bytes, 6..bytes.len(), TextEncoding::Utf8),
+            vec![' ', ' ']
+        );
+        assert_eq!(
+            decode_text(&[0xff, 0xe2, 0x82], 0..3, TextEncoding::Utf8),
+            vec!['.', '.', '.']
+        );
+    }
+}
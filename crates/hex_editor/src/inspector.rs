@@ -0,0 +1,80 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub fn label(self) -> &'static str {
+        match self {
+            Endianness::Little => "Little Endian",
+            Endianness::Big => "Big Endian",
+        }
+    }
+}
+
+macro_rules! decode {
+    ($bytes:expr, $endianness:expr, $ty:ty) => {{
+        const SIZE: usize = std::mem::size_of::<$ty>();
+        $bytes
+            .get(..SIZE)
+            .and_then(|bytes| <[u8; SIZE]>::try_from(bytes).ok())
+            .map(|bytes| match $endianness {
+                Endianness::Little => <$ty>::from_le_bytes(bytes),
+                Endianness::Big => <$ty>::from_be_bytes(bytes),
+            })
+    }};
+}
+
+/// Decodes the bytes at the cursor as each of the primitive types that fit,
+/// returning the type names and values.
+pub fn inspect(bytes: &[u8], endianness: Endianness) -> Vec<(&'static str, String)> {
+    let mut values = Vec::new();
+    if let Some(&byte) = bytes.first() {
+        values.push(("binary", format!("{byte:08b}")));
+        values.push(("u8", byte.to_string()));
+        values.push(("i8", (byte as i8).to_string()));
+    }
+    macro_rules! push {
+        ($($ty:ty),*) => {
+            $(
+                if let Some(value) = decode!(bytes, endianness, $ty) {
+                    values.push((stringify!($ty), value.to_string()));
+                }
+            )*
+        };
+    }
+    push!(u16, i16, u32, i32, u64, i64, f32, f64);
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(values: &[(&'static str, String)], name: &str) -> Option<String> {
+        values
+            .iter()
+            .find(|(ty, _)| *ty == name)
+            .map(|(_, value)| value.clone())
+    }
+
+    #[test]
+    fn test_inspect() {
+        let bytes = 1.5f32.to_le_bytes();
+        let values = inspect(&bytes, Endianness::Little);
+        assert_eq!(value(&values, "f32").as_deref(), Some("1.5"));
+        assert_eq!(value(&values, "u16").as_deref(), Some("0"));
+        assert_eq!(value(&values, "u64"), None);
+
+        let values = inspect(&[0xff, 0x01], Endianness::Big);
+        assert_eq!(value(&values, "binary").as_deref(), Some("11111111"));
+        assert_eq!(value(&values, "i8").as_deref(), Some("-1"));
+        assert_eq!(value(&values, "u16").as_deref(), Some("65281"));
+        assert_eq!(value(&values, "i16").as_deref(), Some("-255"));
+        assert_eq!(value(&values, "u32"), None);
+
+        assert!(inspect(&[], Endianness::Little).is_empty());
+    }
+}
@@ -27,7 +27,8 @@ use language::{Language, LanguageConfig, ToOffset as _};
 use notifications::status_toast::{StatusToast, ToastIcon};
 use project::{CompletionDisplayOptions, Project};
 use settings::{
-    BaseKeymap, KeybindSource, KeymapFile, Settings as _, SettingsAssets, infer_json_indent_size,
+    BaseKeymap, KeybindSource, KeymapFile, Settings as _, SettingsAssets, UnmappedVsCodeKeybinding,
+    VsCodeKeybindings, VsCodeSettingsSource, infer_json_indent_size,
 };
 use ui::{
     ActiveTheme as _, App, Banner, BorrowAppContext, ContextMenu, IconButtonShape, IconPosition,
@@ -38,7 +39,8 @@ use ui::{
 use ui_input::InputField;
 use util::ResultExt;
 use workspace::{
-    Item, ModalView, SerializableItem, Workspace, notifications::NotifyTaskExt as _,
+    Item, ModalView, SerializableItem, Workspace,
+    notifications::{DetachAndPromptErr as _, NotifyTaskExt as _},
     register_serializable_item, with_active_or_new_workspace,
 };
 
@@ -80,7 +82,9 @@ actions!(
         /// Toggles exact matching for keystroke search
         ToggleExactKeystrokeMatching,
         /// Shows matching keystrokes for the currently selected binding
-        ShowMatchingKeybinds
+        ShowMatchingKeybinds,
+        /// Imports key bindings from the Visual Studio Code `keybindings.json` file.
+        ImportVsCodeKeybindings
     ]
 );
 
@@ -88,46 +92,6 @@ pub fn init(cx: &mut App) {
     let keymap_event_channel = KeymapEventChannel::new();
     cx.set_global(keymap_event_channel);
 
-    fn open_keymap_editor(
-        filter: Option<String>,
-        workspace: &mut Workspace,
-        window: &mut Window,
-        cx: &mut Context<Workspace>,
-    ) {
-        let existing = workspace
-            .active_pane()
-            .read(cx)
-            .items()
-            .find_map(|item| item.downcast::<KeymapEditor>());
-
-        let keymap_editor = if let Some(existing) = existing {
-            workspace.activate_item(&existing, true, true, window, cx);
-            existing
-        } else {
-            let keymap_editor = cx.new(|cx| KeymapEditor::new(workspace.weak_handle(), window, cx));
-            workspace.add_item_to_active_pane(
-                Box::new(keymap_editor.clone()),
-                None,
-                true,
-                window,
-                cx,
-            );
-            keymap_editor
-        };
-
-        if let Some(filter) = filter {
-            keymap_editor.update(cx, |editor, cx| {
-                editor.filter_editor.update(cx, |editor, cx| {
-                    editor.clear(window, cx);
-                    editor.insert(&filter, window, cx);
-                });
-                if !editor.has_binding_for(&filter) {
-                    open_binding_modal_after_loading(cx)
-                }
-            })
-        }
-    }
-
     cx.on_action(|_: &OpenKeymap, cx| {
         with_active_or_new_workspace(cx, |workspace, window, cx| {
             open_keymap_editor(None, workspace, window, cx);
@@ -135,15 +99,105 @@ pub fn init(cx: &mut App) {
     });
 
     cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
-        workspace.register_action(|workspace, action: &ChangeKeybinding, window, cx| {
-            open_keymap_editor(Some(action.action.clone()), workspace, window, cx);
-        });
+        workspace
+            .register_action(|workspace, action: &ChangeKeybinding, window, cx| {
+                open_keymap_editor(Some(action.action.clone()), workspace, window, cx);
+            })
+            .register_action(import_vscode_keybindings);
     })
     .detach();
 
     register_serializable_item::<KeymapEditor>(cx);
 }
 
+fn open_keymap_editor(
+    filter: Option<String>,
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Entity<KeymapEditor> {
+    let existing = workspace
+        .active_pane()
+        .read(cx)
+        .items()
+        .find_map(|item| item.downcast::<KeymapEditor>());
+
+    let keymap_editor = if let Some(existing) = existing {
+        workspace.activate_item(&existing, true, true, window, cx);
+        existing
+    } else {
+        let keymap_editor = cx.new(|cx| KeymapEditor::new(workspace.weak_handle(), window, cx));
+        workspace.add_item_to_active_pane(Box::new(keymap_editor.clone()), None, true, window, cx);
+        keymap_editor
+    };
+
+    if let Some(filter) = filter {
+        keymap_editor.update(cx, |editor, cx| {
+            editor.filter_editor.update(cx, |editor, cx| {
+                editor.clear(window, cx);
+                editor.insert(&filter, window, cx);
+            });
+            if !editor.has_binding_for(&filter) {
+                open_binding_modal_after_loading(cx)
+            }
+        })
+    }
+
+    keymap_editor
+}
+
+fn import_vscode_keybindings(
+    workspace: &mut Workspace,
+    _: &ImportVsCodeKeybindings,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let fs = workspace.app_state().fs.clone();
+    cx.spawn_in(window, async move |workspace, cx| {
+        let keybindings =
+            VsCodeKeybindings::load_user_keybindings(VsCodeSettingsSource::VsCode, fs.clone())
+                .await?;
+        let import = keybindings.import();
+
+        let keymap_contents = KeymapFile::load_keymap_file(&fs)
+            .await
+            .context("Failed to load keymap file")?;
+        let tab_size = infer_json_indent_size(&keymap_contents);
+        let updated_keymap_contents = import
+            .apply_to_keymap(keymap_contents, tab_size)
+            .context("Failed to update keymap file")?;
+        fs.write(
+            paths::keymap_file().as_path(),
+            updated_keymap_contents.as_bytes(),
+        )
+        .await
+        .context("Failed to write keymap file")?;
+        log::info!(
+            "Imported {} key bindings from {}",
+            import.binding_count(),
+            keybindings.path.display()
+        );
+
+        workspace.update_in(cx, |workspace, window, cx| {
+            let keymap_editor = open_keymap_editor(None, workspace, window, cx);
+            keymap_editor.update(cx, |keymap_editor, cx| {
+                keymap_editor.import_report = Some(ImportReport {
+                    source: keybindings.source,
+                    imported_count: import.binding_count(),
+                    unmapped: import.unmapped,
+                });
+                cx.notify();
+            });
+        })
+    })
+    .detach_and_prompt_err(
+        "Failed to import VS Code key bindings",
+        window,
+        cx,
+        |_, _, _| None,
+    );
+}
+
 fn open_binding_modal_after_loading(cx: &mut Context<KeymapEditor>) {
     let started_at = Instant::now();
     let observer = Rc::new(RefCell::new(None));
@@ -460,6 +514,14 @@ struct KeymapEditor {
     /// worktree and directory once, although the perf improvement is negligible.
     action_args_temp_dir_worktree: Option<Entity<project::Worktree>>,
     action_args_temp_dir: Option<tempfile::TempDir>,
+    import_report: Option<ImportReport>,
+}
+
+/// The outcome of importing key bindings from VS Code, shown until it's dismissed.
+struct ImportReport {
+    source: VsCodeSettingsSource,
+    imported_count: usize,
+    unmapped: Vec<UnmappedVsCodeKeybinding>,
 }
 
 enum PreviousEdit {
@@ -588,6 +650,7 @@ impl KeymapEditor {
             action_args_temp_dir: None,
             action_args_temp_dir_worktree: None,
             current_widths: cx.new(|cx| TableColumnWidths::new(COLS, cx)),
+            import_report: None,
         };
 
         this.on_keymap_changed(window, cx);
@@ -1181,6 +1244,79 @@ impl KeymapEditor {
         Label::new(hint).color(Color::Muted).into_any_element()
     }
 
+    fn render_import_report(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let report = self.import_report.as_ref()?;
+        let summary = if report.unmapped.is_empty() {
+            format!(
+                "Imported {} key bindings from {}.",
+                report.imported_count, report.source
+            )
+        } else {
+            format!(
+                "Imported {} key bindings from {}. These {} couldn't be imported:",
+                report.imported_count,
+                report.source,
+                report.unmapped.len()
+            )
+        };
+
+        Some(
+            Banner::new()
+                .severity(if report.unmapped.is_empty() {
+                    Severity::Success
+                } else {
+                    Severity::Warning
+                })
+                .child(v_flex().gap_1().child(Label::new(summary)).when(
+                    !report.unmapped.is_empty(),
+                    |this| {
+                        this.child(
+                            v_flex()
+                                .id("vscode-import-unmapped")
+                                .max_h_32()
+                                .overflow_y_scroll()
+                                .children(report.unmapped.iter().map(|binding| {
+                                    h_flex()
+                                        .gap_2()
+                                        .child(
+                                            Label::new(binding.key.clone())
+                                                .size(LabelSize::Small)
+                                                .buffer_font(cx),
+                                        )
+                                        .child(
+                                            Label::new(binding.command.clone())
+                                                .size(LabelSize::Small)
+                                                .buffer_font(cx),
+                                        )
+                                        .when_some(binding.when.clone(), |this, when| {
+                                            this.child(
+                                                Label::new(when)
+                                                    .size(LabelSize::Small)
+                                                    .color(Color::Muted)
+                                                    .buffer_font(cx),
+                                            )
+                                        })
+                                        .child(
+                                            Label::new(format!("({})", binding.reason))
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted),
+                                        )
+                                })),
+                        )
+                    },
+                ))
+                .action_slot(
+                    IconButton::new("dismiss-import-report", IconName::Close)
+                        .icon_size(IconSize::Small)
+                        .tooltip(Tooltip::text("Dismiss"))
+                        .on_click(cx.listener(|this, _, _, cx| {
+                            this.import_report = None;
+                            cx.notify();
+                        })),
+                ),
+        )
+    }
+
     fn select_next(&mut self, _: &menu::SelectNext, window: &mut Window, cx: &mut Context<Self>) {
         self.show_hover_menus = false;
         if let Some(selected) = self.selected_index {
@@ -2016,6 +2152,7 @@ impl Render for KeymapEditor {
                         },
                     ),
             )
+            .children(self.render_import_report(cx))
             .child(
                 Table::new(COLS)
                     .interactable(&self.table_interaction_state)
@@ -0,0 +1,181 @@
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use collections::HashMap;
+use gpui::{App, Global};
+use parking_lot::RwLock;
+
+use crate::{LanguageName, LspAdapterDelegate};
+
+/// A request to format the contents of a buffer.
+#[derive(Debug, Clone)]
+pub struct ExternalFormatRequest {
+    pub language: LanguageName,
+    /// The absolute path of the buffer, if it is backed by a file.
+    pub path: Option<PathBuf>,
+    pub text: String,
+    pub tab_size: u32,
+    pub hard_tabs: bool,
+}
+
+/// The outcome of an [`ExternalFormatter`] run.
+#[derive(Debug, Clone)]
+pub enum ExternalFormatOutput {
+    /// The buffer is already formatted.
+    Unchanged,
+    /// The formatted contents of the buffer.
+    Text(String),
+    /// A command that reads the buffer on stdin and writes the formatted buffer to stdout.
+    Command(ExternalFormatCommand),
+}
+
+#[derive(Debug, Clone)]
+pub struct ExternalFormatCommand {
+    pub path: PathBuf,
+    pub arguments: Vec<String>,
+    pub env: HashMap<String, String>,
+}
+
+/// A request to lint the contents of a buffer.
+#[derive(Debug, Clone)]
+pub struct ExternalLintRequest {
+    pub language: LanguageName,
+    /// The absolute path of the buffer.
+    pub path: PathBuf,
+    pub text: String,
+}
+
+/// A formatter that is not backed by a language server or Prettier, such as one provided by an extension.
+#[async_trait]
+pub trait ExternalFormatter: Send + Sync {
+    async fn format(
+        &self,
+        request: ExternalFormatRequest,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<ExternalFormatOutput>;
+}
+
+/// A linter that reports diagnostics for a buffer without running a language server.
+#[async_trait]
+pub trait ExternalLinter: Send + Sync {
+    async fn lint(
+        &self,
+        request: ExternalLintRequest,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<Vec<lsp::Diagnostic>>;
+}
+
+struct ExternalTool<T: ?Sized> {
+    languages: Vec<LanguageName>,
+    tool: Arc<T>,
+}
+
+#[derive(Default)]
+struct ExternalToolRegistryState {
+    formatters: BTreeMap<Arc<str>, ExternalTool<dyn ExternalFormatter>>,
+    linters: BTreeMap<Arc<str>, ExternalTool<dyn ExternalLinter>>,
+}
+
+/// Stores the available [`ExternalFormatter`]s and [`ExternalLinter`]s.
+#[derive(Clone, Default)]
+pub struct ExternalToolRegistry(Arc<RwLock<ExternalToolRegistryState>>);
+
+impl Global for ExternalToolRegistry {}
+
+impl ExternalToolRegistry {
+    pub fn global(cx: &mut App) -> &mut Self {
+        cx.default_global::<Self>()
+    }
+
+    pub fn try_global(cx: &App) -> Option<Self> {
+        cx.try_global::<Self>().cloned()
+    }
+
+    pub fn register_formatter(
+        &self,
+        name: Arc<str>,
+        languages: Vec<LanguageName>,
+        formatter: Arc<dyn ExternalFormatter>,
+    ) {
+        self.0.write().formatters.insert(
+            name,
+            ExternalTool {
+                languages,
+                tool: formatter,
+            },
+        );
+    }
+
+    pub fn register_linter(
+        &self,
+        name: Arc<str>,
+        languages: Vec<LanguageName>,
+        linter: Arc<dyn ExternalLinter>,
+    ) {
+        self.0.write().linters.insert(
+            name,
+            ExternalTool {
+                languages,
+                tool: linter,
+            },
+        );
+    }
+
+    pub fn remove_formatter(&self, name: &str) {
+        self.0.write().formatters.remove(name);
+    }
+
+    pub fn remove_linter(&self, name: &str) {
+        self.0.write().linters.remove(name);
+    }
+
+    pub fn formatter(&self, name: &str) -> Option<Arc<dyn ExternalFormatter>> {
+        self.0
+            .read()
+            .formatters
+            .get(name)
+            .map(|formatter| formatter.tool.clone())
+    }
+
+    /// Returns the names of the formatters that support the given language,
+    /// or of all formatters if no language is given.
+    pub fn formatter_names(&self, language: Option<&LanguageName>) -> Vec<Arc<str>> {
+        self.0
+            .read()
+            .formatters
+            .iter()
+            .filter(|(_, formatter)| {
+                language.is_none_or(|language| formatter.languages.contains(language))
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Returns the names of the linters that support the given language,
+    /// or of all linters if no language is given.
+    pub fn linter_names(&self, language: Option<&LanguageName>) -> Vec<Arc<str>> {
+        self.0
+            .read()
+            .linters
+            .iter()
+            .filter(|(_, linter)| {
+                language.is_none_or(|language| linter.languages.contains(language))
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    pub fn linters_for_language(
+        &self,
+        language: &LanguageName,
+    ) -> Vec<(Arc<str>, Arc<dyn ExternalLinter>)> {
+        self.0
+            .read()
+            .linters
+            .iter()
+            .filter(|(_, linter)| linter.languages.contains(language))
+            .map(|(name, linter)| (name.clone(), linter.tool.clone()))
+            .collect()
+    }
+}
@@ -8,12 +8,14 @@
 //! Notably we do *not* assign a single language to a single file; in real world a single file can consist of multiple programming languages - HTML is a good example of that - and `language` crate tends to reflect that status quo in its API.
 mod buffer;
 mod diagnostic_set;
+mod external_tools;
 mod highlight_map;
 mod language_registry;
 pub mod language_settings;
 mod manifest;
 mod outline;
 pub mod proto;
+mod structural_pattern;
 mod syntax_map;
 mod task_context;
 mod text_diff;
@@ -27,6 +29,10 @@ pub use crate::language_settings::{AutoIndentMode, EditPredictionsMode, IndentGu
 use anyhow::{Context as _, Result};
 use async_trait::async_trait;
 use collections::{HashMap, HashSet, IndexSet};
+pub use external_tools::{
+    ExternalFormatCommand, ExternalFormatOutput, ExternalFormatRequest, ExternalFormatter,
+    ExternalLintRequest, ExternalLinter, ExternalToolRegistry,
+};
 use futures::Future;
 use futures::future::LocalBoxFuture;
 use futures::lock::OwnedMutexGuard;
@@ -62,6 +68,7 @@ use std::{
         atomic::{AtomicUsize, Ordering::SeqCst},
     },
 };
+pub use structural_pattern::{StructuralMatch, StructuralPattern};
 use syntax_map::{QueryCursorHandle, SyntaxSnapshot};
 use task::RunnableTag;
 pub use task_context::{ContextLocation, ContextProvider, RunnableRange};
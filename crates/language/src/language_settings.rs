@@ -107,6 +107,10 @@ pub struct LanguageSettings {
     /// - `"!<language_server_id>"` - A language server ID prefixed with a `!` will be disabled.
     /// - `"..."` - A placeholder to refer to the **rest** of the registered language servers for this language.
     pub language_servers: Vec<String>,
+    /// The list of extension linters to run (or disable) for this language.
+    ///
+    /// Uses the same `"!<linter_name>"` and `"..."` tokens as [`Self::language_servers`].
+    pub linters: Vec<String>,
     /// Controls how semantic tokens from language servers are used for syntax highlighting.
     pub semantic_tokens: SemanticTokens,
     /// Controls whether folding ranges from language servers are used instead of
@@ -297,32 +301,52 @@ impl LanguageSettings {
         configured_language_servers: &[String],
         available_language_servers: &[LanguageServerName],
     ) -> Vec<LanguageServerName> {
-        let (disabled_language_servers, enabled_language_servers): (
-            Vec<LanguageServerName>,
-            Vec<LanguageServerName>,
-        ) = configured_language_servers.iter().partition_map(
-            |language_server| match language_server.strip_prefix('!') {
-                Some(disabled) => Either::Left(LanguageServerName(disabled.to_string().into())),
-                None => Either::Right(LanguageServerName(language_server.clone().into())),
-            },
-        );
+        Self::resolve_customized_list(
+            configured_language_servers,
+            available_language_servers,
+            |language_server| LanguageServerName(language_server.to_string().into()),
+        )
+    }
+
+    /// Returns the customized list of extension linters from the list of
+    /// available linters.
+    pub fn customized_linters(&self, available_linters: &[Arc<str>]) -> Vec<Arc<str>> {
+        Self::resolve_customized_list(&self.linters, available_linters, Arc::<str>::from)
+    }
 
-        let rest = available_language_servers
+    /// Resolves a list of configured names, in which `"!<name>"` disables an
+    /// item and `"..."` stands for the rest of the available items.
+    fn resolve_customized_list<T: Clone + PartialEq>(
+        configured: &[String],
+        available: &[T],
+        to_item: impl Fn(&str) -> T,
+    ) -> Vec<T> {
+        let (disabled, enabled): (Vec<T>, Vec<&String>) =
+            configured
+                .iter()
+                .partition_map(|name| match name.strip_prefix('!') {
+                    Some(disabled) => Either::Left(to_item(disabled)),
+                    None => Either::Right(name),
+                });
+        let explicitly_enabled = enabled
             .iter()
-            .filter(|&available_language_server| {
-                !disabled_language_servers.contains(available_language_server)
-                    && !enabled_language_servers.contains(available_language_server)
-            })
+            .filter(|name| name.as_str() != Self::REST_OF_LANGUAGE_SERVERS)
+            .map(|name| to_item(name))
+            .collect::<Vec<_>>();
+
+        let rest = available
+            .iter()
+            .filter(|&item| !disabled.contains(item) && !explicitly_enabled.contains(item))
             .cloned()
             .collect::<Vec<_>>();
 
-        enabled_language_servers
+        enabled
             .into_iter()
-            .flat_map(|language_server| {
-                if language_server.0.as_ref() == Self::REST_OF_LANGUAGE_SERVERS {
+            .flat_map(|name| {
+                if name == Self::REST_OF_LANGUAGE_SERVERS {
                     rest.clone()
                 } else {
-                    vec![language_server]
+                    vec![to_item(name)]
                 }
             })
             .collect::<Vec<_>>()
@@ -616,6 +640,7 @@ impl settings::Settings for AllLanguageSettings {
                 jsx_tag_auto_close: settings.jsx_tag_auto_close.unwrap().enabled.unwrap(),
                 enable_language_server: settings.enable_language_server.unwrap(),
                 language_servers: settings.language_servers.unwrap(),
+                linters: settings.linters.unwrap().0,
                 semantic_tokens: settings.semantic_tokens.unwrap(),
                 document_folding_ranges: settings.document_folding_ranges.unwrap(),
                 document_symbols: settings.document_symbols.unwrap(),
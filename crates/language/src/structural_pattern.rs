@@ -0,0 +1,563 @@
+//! Syntax-aware search patterns.
+//!
+//! A [`StructuralPattern`] is either a snippet of code containing metavariables,
+//! such as `foo($A, $$$REST)`, or a raw tree-sitter query. Code patterns are
+//! parsed with the grammar of each buffer they are matched against, and match
+//! the syntax nodes that have the same shape.
+use crate::{BufferSnapshot, Grammar, GrammarId, syntax_map::TextProvider, with_parser};
+use anyhow::{Result, anyhow};
+use collections::HashMap;
+use parking_lot::Mutex;
+use std::{ops::Range, sync::Arc};
+use streaming_iterator::StreamingIterator as _;
+use tree_sitter::{Node, Query, QueryCursor, Tree};
+
+/// Metavariables are replaced with identifiers before the pattern is parsed, so
+/// that they are valid code in most languages.
+const METAVARIABLE_PREFIX: &str = "zed_metavar_";
+const MULTI_METAVARIABLE_PREFIX: &str = "zed_metavars_";
+
+/// The name of the query capture that determines the range of a match. When a
+/// raw query doesn't have it, the capture with the largest range is used.
+const MATCH_CAPTURE_NAME: &str = "match";
+
+pub struct StructuralPattern {
+    source: String,
+    compiled: Mutex<HashMap<GrammarId, Option<Arc<CompiledPattern>>>>,
+}
+
+impl std::fmt::Debug for StructuralPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StructuralPattern")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructuralMatch {
+    pub range: Range<usize>,
+    /// The ranges bound to each metavariable, or to each capture of a raw query.
+    pub captures: HashMap<String, Range<usize>>,
+}
+
+enum CompiledPattern {
+    Query(Query),
+    Code { tree: Tree, source: String },
+}
+
+enum Metavariable<'a> {
+    Single(&'a str),
+    Multiple(&'a str),
+}
+
+impl StructuralPattern {
+    pub fn new(source: impl Into<String>) -> Result<Self> {
+        let source = source.into();
+        if source.trim().is_empty() {
+            return Err(anyhow!("structural pattern is empty"));
+        }
+        Ok(Self {
+            source,
+            compiled: Mutex::default(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the words that any text matched by a code pattern must contain,
+    /// which allows files to be skipped without parsing them. Raw queries don't
+    /// have any required words.
+    pub fn required_words(&self) -> Vec<&str> {
+        if self.looks_like_query() {
+            return Vec::new();
+        }
+        let mut words = Vec::new();
+        let mut rest = self.source.as_str();
+        while let Some(start) = rest.find(|c: char| c.is_alphanumeric() || c == '_' || c == '$') {
+            rest = &rest[start..];
+            let is_metavariable = rest.starts_with('$');
+            let word_start = rest.find(|c: char| c != '$').unwrap_or(rest.len());
+            let word_len = rest[word_start..]
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len() - word_start);
+            if !is_metavariable {
+                words.push(&rest[word_start..word_start + word_len]);
+            }
+            rest = &rest[word_start + word_len..];
+        }
+        words
+    }
+
+    /// Finds the outermost nodes within the given range that match this pattern.
+    pub fn find_matches(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Vec<StructuralMatch> {
+        let mut matches = Vec::new();
+        for layer in buffer.syntax_layers() {
+            let Some(grammar) = layer.language.grammar() else {
+                continue;
+            };
+            let Some(compiled) = self.compiled_for(grammar) else {
+                continue;
+            };
+            match compiled.as_ref() {
+                CompiledPattern::Query(query) => {
+                    find_query_matches(query, layer.node(), buffer, &range, &mut matches)
+                }
+                CompiledPattern::Code { tree, source } => {
+                    let pattern = pattern_root(tree.root_node());
+                    let mut matcher = NodeMatcher {
+                        pattern_source: source,
+                        buffer,
+                        bindings: HashMap::default(),
+                    };
+                    matcher.find_matches(pattern, layer.node(), &range, &mut matches);
+                }
+            }
+        }
+        matches.sort_by_key(|mat| (mat.range.start, std::cmp::Reverse(mat.range.end)));
+        // Matches in injected languages may overlap with matches in their parent
+        // layer, and overlapping matches can't all be replaced.
+        let mut last_end = 0;
+        matches.retain(|mat| {
+            let keep = mat.range.start >= last_end;
+            if keep {
+                last_end = mat.range.end;
+            }
+            keep
+        });
+        matches
+    }
+
+    /// Returns the replacement for the match with exactly the given range.
+    ///
+    /// In the template, `$NAME` and `$$$NAME` are replaced with the text bound
+    /// to that metavariable or query capture. Returns `None` when the match
+    /// contains comments outside of any binding, since replacing it would
+    /// delete them.
+    pub fn replacement_at(
+        &self,
+        template: &str,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        let mat = self
+            .find_matches(buffer, range.clone())
+            .into_iter()
+            .find(|mat| mat.range == range)?;
+        if has_unbound_extras(&mat, buffer) {
+            return None;
+        }
+        Some(expand_template(template, &mat, buffer))
+    }
+
+    fn looks_like_query(&self) -> bool {
+        let source = self.source.trim_start();
+        source.starts_with('(') || source.starts_with('[')
+    }
+
+    fn compiled_for(&self, grammar: &Grammar) -> Option<Arc<CompiledPattern>> {
+        self.compiled
+            .lock()
+            .entry(grammar.id())
+            .or_insert_with(|| self.compile(grammar).map(Arc::new))
+            .clone()
+    }
+
+    fn compile(&self, grammar: &Grammar) -> Option<CompiledPattern> {
+        if self.looks_like_query()
+            && let Ok(query) = Query::new(&grammar.ts_language, &self.source)
+        {
+            return Some(CompiledPattern::Query(query));
+        }
+
+        let source = replace_metavariables(&self.source);
+        with_parser(|parser| {
+            parser.set_language(&grammar.ts_language).ok()?;
+            // Statements are often written without their terminating semicolon.
+            [source.clone(), format!("{source};")]
+                .into_iter()
+                .find_map(|source| {
+                    let tree = parser.parse(&source, None)?;
+                    (!tree.root_node().has_error())
+                        .then_some(CompiledPattern::Code { tree, source })
+                })
+        })
+    }
+}
+
+fn replace_metavariables(source: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(ix) = rest.find('$') {
+        result.push_str(&rest[..ix]);
+        rest = &rest[ix..];
+        let (prefix, name_start) = if rest.starts_with("$$$") {
+            (MULTI_METAVARIABLE_PREFIX, 3)
+        } else {
+            (METAVARIABLE_PREFIX, 1)
+        };
+        let name_len = rest[name_start..]
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len() - name_start);
+        if name_len == 0 {
+            result.push('$');
+            rest = &rest[1..];
+        } else {
+            result.push_str(prefix);
+            result.push_str(&rest[name_start..name_start + name_len]);
+            rest = &rest[name_start + name_len..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Skips the nodes that wrap the pattern, such as the root `source_file` and an
+/// `expression_statement`, to find the node that the pattern describes.
+fn pattern_root(mut node: Node) -> Node {
+    loop {
+        let mut cursor = node.walk();
+        let mut children = node
+            .named_children(&mut cursor)
+            .filter(|child| !child.is_extra());
+        match (children.next(), children.next()) {
+            (Some(child), None) => node = child,
+            _ => return node,
+        }
+    }
+}
+
+fn find_query_matches(
+    query: &Query,
+    root: Node,
+    buffer: &BufferSnapshot,
+    range: &Range<usize>,
+    matches: &mut Vec<StructuralMatch>,
+) {
+    let mut cursor = QueryCursor::new();
+    cursor.set_byte_range(range.clone());
+    let mut query_matches = cursor.matches(query, root, TextProvider(buffer.as_rope()));
+    while let Some(query_match) = query_matches.next() {
+        let captures = query_match
+            .captures
+            .iter()
+            .map(|capture| {
+                (
+                    query.capture_names()[capture.index as usize].to_string(),
+                    capture.node.byte_range(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let match_range = captures
+            .get(MATCH_CAPTURE_NAME)
+            .or_else(|| captures.values().max_by_key(|range| range.len()))
+            .cloned();
+        if let Some(match_range) = match_range
+            && match_range.start >= range.start
+            && match_range.end <= range.end
+        {
+            matches.push(StructuralMatch {
+                range: match_range,
+                captures,
+            });
+        }
+    }
+}
+
+struct NodeMatcher<'a> {
+    pattern_source: &'a str,
+    buffer: &'a BufferSnapshot,
+    bindings: HashMap<String, Range<usize>>,
+}
+
+impl<'a> NodeMatcher<'a> {
+    fn find_matches(
+        &mut self,
+        pattern: Node,
+        node: Node,
+        range: &Range<usize>,
+        matches: &mut Vec<StructuralMatch>,
+    ) {
+        if node.end_byte() <= range.start || node.start_byte() >= range.end {
+            return;
+        }
+        if node.start_byte() >= range.start && node.end_byte() <= range.end && node.is_named() {
+            self.bindings.clear();
+            if self.match_node(pattern, node) {
+                matches.push(StructuralMatch {
+                    range: node.byte_range(),
+                    captures: std::mem::take(&mut self.bindings),
+                });
+                return;
+            }
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.find_matches(pattern, child, range, matches);
+        }
+    }
+
+    fn match_node(&mut self, pattern: Node, node: Node) -> bool {
+        if let Some(metavariable) = self.metavariable(pattern) {
+            let (Metavariable::Single(name) | Metavariable::Multiple(name)) = metavariable;
+            return self.bind(name, node.byte_range());
+        }
+        if pattern.kind_id() != node.kind_id() {
+            return false;
+        }
+        let pattern_children = significant_children(pattern);
+        let mut children = significant_children(node);
+        if pattern_children.is_empty() {
+            return children.is_empty()
+                && self.pattern_text(pattern) == self.buffer_text(node.byte_range());
+        }
+        if self.match_sequence(&pattern_children, &children) {
+            return true;
+        }
+
+        // Allow a trailing comma that the pattern omits, as in `foo(a, b,)`.
+        if let [.., comma, _] = children.as_slice()
+            && !comma.is_named()
+            && comma.kind() == ","
+        {
+            children.remove(children.len() - 2);
+            return self.match_sequence(&pattern_children, &children);
+        }
+        false
+    }
+
+    fn match_sequence(&mut self, patterns: &[Node], nodes: &[Node]) -> bool {
+        let Some((pattern, remaining_patterns)) = patterns.split_first() else {
+            return nodes.is_empty();
+        };
+
+        if let Some(Metavariable::Multiple(name)) = self.metavariable(*pattern) {
+            for count in 0..=nodes.len() {
+                let bindings = self.bindings.clone();
+                let range = match (nodes.first(), count.checked_sub(1)) {
+                    (Some(first), Some(last)) => first.start_byte()..nodes[last].end_byte(),
+                    _ => 0..0,
+                };
+                if self.bind(name, range)
+                    && self.match_sequence(remaining_patterns, &nodes[count..])
+                {
+                    return true;
+                }
+                self.bindings = bindings;
+            }
+            return false;
+        }
+
+        let Some((node, remaining_nodes)) = nodes.split_first() else {
+            return false;
+        };
+        let bindings = self.bindings.clone();
+        if self.match_node(*pattern, *node)
+            && self.match_sequence(remaining_patterns, remaining_nodes)
+        {
+            return true;
+        }
+        self.bindings = bindings;
+        false
+    }
+
+    /// Binds a metavariable, or checks that the text matches its earlier binding.
+    fn bind(&mut self, name: &str, range: Range<usize>) -> bool {
+        if name == "_" {
+            return true;
+        }
+        match self.bindings.get(name) {
+            Some(bound) => self.buffer_text(bound.clone()) == self.buffer_text(range),
+            None => {
+                self.bindings.insert(name.to_string(), range);
+                true
+            }
+        }
+    }
+
+    fn metavariable(&self, pattern: Node) -> Option<Metavariable<'a>> {
+        let text = self.pattern_text(pattern);
+        let text = text.trim().trim_end_matches(';').trim_end();
+        let (name, multiple) = if let Some(name) = text.strip_prefix(MULTI_METAVARIABLE_PREFIX) {
+            (name, true)
+        } else {
+            (text.strip_prefix(METAVARIABLE_PREFIX)?, false)
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return None;
+        }
+        Some(if multiple {
+            Metavariable::Multiple(name)
+        } else {
+            Metavariable::Single(name)
+        })
+    }
+
+    fn pattern_text(&self, node: Node) -> &'a str {
+        let source: &'a str = self.pattern_source;
+        &source[node.byte_range()]
+    }
+
+    fn buffer_text(&self, range: Range<usize>) -> String {
+        self.buffer.text_for_range(range).collect()
+    }
+}
+
+/// The children that take part in matching, which excludes comments.
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra())
+        .collect()
+}
+
+/// Returns whether the match contains extra nodes, such as comments, that
+/// aren't part of the text bound to any metavariable or capture.
+fn has_unbound_extras(mat: &StructuralMatch, buffer: &BufferSnapshot) -> bool {
+    fn visit(node: Node, mat: &StructuralMatch) -> bool {
+        if node.end_byte() <= mat.range.start || node.start_byte() >= mat.range.end {
+            return false;
+        }
+        if mat
+            .captures
+            .values()
+            .any(|capture| capture.start <= node.start_byte() && node.end_byte() <= capture.end)
+        {
+            return false;
+        }
+        if node.is_extra() {
+            return true;
+        }
+        let mut cursor = node.walk();
+        node.children(&mut cursor).any(|child| visit(child, mat))
+    }
+
+    buffer.syntax_layers().any(|layer| visit(layer.node(), mat))
+}
+
+fn expand_template(template: &str, mat: &StructuralMatch, buffer: &BufferSnapshot) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(ix) = rest.find('$') {
+        result.push_str(&rest[..ix]);
+        rest = &rest[ix..];
+        let name_start = rest.find(|c: char| c != '$').unwrap_or(rest.len()).min(3);
+        let name_len = rest[name_start..]
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(rest.len() - name_start);
+        let name = &rest[name_start..name_start + name_len];
+        match mat.captures.get(name) {
+            Some(range) if !name.is_empty() => {
+                result.extend(buffer.text_for_range(range.clone()));
+                rest = &rest[name_start + name_len..];
+            }
+            _ => {
+                result.push('$');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Buffer, rust_lang};
+    use gpui::{AppContext as _, TestAppContext};
+    use indoc::indoc;
+
+    async fn rust_buffer(text: &str, cx: &mut TestAppContext) -> BufferSnapshot {
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(rust_lang(), cx));
+        buffer
+            .read_with(cx, |buffer, _| buffer.parsing_idle())
+            .await;
+        buffer.read_with(cx, |buffer, _| buffer.snapshot())
+    }
+
+    fn replace_all(pattern: &StructuralPattern, template: &str, buffer: &BufferSnapshot) -> String {
+        let mut text = buffer.text();
+        for mat in pattern.find_matches(buffer, 0..buffer.len()).iter().rev() {
+            if let Some(replacement) = pattern.replacement_at(template, buffer, mat.range.clone()) {
+                text.replace_range(mat.range.clone(), &replacement);
+            }
+        }
+        text
+    }
+
+    #[gpui::test]
+    async fn test_code_pattern(cx: &mut TestAppContext) {
+        let buffer = rust_buffer(
+            indoc! {"
+                fn main() {
+                    foo(1, bar(2, 3));
+                    foo(
+                        a, // comment
+                        b,
+                    );
+                    foo(1);
+                    other(1, 2);
+                }
+            "},
+            cx,
+        )
+        .await;
+
+        let pattern = StructuralPattern::new("foo($A, $B)").unwrap();
+        assert_eq!(pattern.required_words(), ["foo"]);
+        assert_eq!(pattern.find_matches(&buffer, 0..buffer.len()).len(), 2);
+        // The second call isn't replaced, because its comment would be lost.
+        assert_eq!(
+            replace_all(&pattern, "foo($B, $A)", &buffer),
+            indoc! {"
+                fn main() {
+                    foo(bar(2, 3), 1);
+                    foo(
+                        a, // comment
+                        b,
+                    );
+                    foo(1);
+                    other(1, 2);
+                }
+            "}
+        );
+
+        let pattern = StructuralPattern::new("$F($X, $X)").unwrap();
+        assert!(pattern.find_matches(&buffer, 0..buffer.len()).is_empty());
+
+        let pattern = StructuralPattern::new("foo($$$ARGS)").unwrap();
+        let matches = pattern.find_matches(&buffer, 0..buffer.len());
+        assert_eq!(matches.len(), 3);
+        assert_eq!(
+            replace_all(&pattern, "baz($$$ARGS)", &buffer),
+            indoc! {"
+                fn main() {
+                    baz(1, bar(2, 3));
+                    baz(a, // comment
+                        b,);
+                    baz(1);
+                    other(1, 2);
+                }
+            "}
+        );
+    }
+
+    #[gpui::test]
+    async fn test_query_pattern(cx: &mut TestAppContext) {
+        let buffer = rust_buffer("fn one() {}\nfn two() { one() }\n", cx).await;
+        let pattern =
+            StructuralPattern::new("(function_item name: (identifier) @name) @match").unwrap();
+        assert!(pattern.required_words().is_empty());
+        assert_eq!(
+            replace_all(&pattern, "fn $name() {}", &buffer),
+            "fn one() {}\nfn two() {}\n"
+        );
+    }
+}
@@ -23,7 +23,7 @@ use util::{ResultExt, fs::make_file_executable, maybe, rel_path::RelPath};
 use crate::{LanguageServerRegistryProxy, LspAccess};
 
 /// An adapter that allows an [`LspAdapterDelegate`] to be used as a [`WorktreeDelegate`].
-struct WorktreeDelegateAdapter(pub Arc<dyn LspAdapterDelegate>);
+pub(crate) struct WorktreeDelegateAdapter(pub Arc<dyn LspAdapterDelegate>);
 
 #[async_trait]
 impl WorktreeDelegate for WorktreeDelegateAdapter {
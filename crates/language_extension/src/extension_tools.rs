@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use extension::{
+    Extension, ExtensionFormatterProxy, ExtensionLinterProxy, FormatOutput, FormatRequest,
+    LintDiagnostic, LintRequest, LintSeverity,
+};
+use gpui::App;
+use language::{
+    ExternalFormatCommand, ExternalFormatOutput, ExternalFormatRequest, ExternalFormatter,
+    ExternalLintRequest, ExternalLinter, ExternalToolRegistry, LanguageName, LspAdapterDelegate,
+};
+
+use crate::LanguageServerRegistryProxy;
+use crate::extension_lsp_adapter::WorktreeDelegateAdapter;
+
+impl ExtensionFormatterProxy for LanguageServerRegistryProxy {
+    fn register_formatter(
+        &self,
+        extension: Arc<dyn Extension>,
+        formatter_id: Arc<str>,
+        languages: Vec<LanguageName>,
+        cx: &mut App,
+    ) {
+        ExternalToolRegistry::global(cx).register_formatter(
+            formatter_id.clone(),
+            languages,
+            Arc::new(ExtensionFormatter {
+                extension,
+                formatter_id,
+            }),
+        );
+    }
+
+    fn unregister_formatter(&self, formatter_id: Arc<str>, cx: &mut App) {
+        ExternalToolRegistry::global(cx).remove_formatter(&formatter_id);
+    }
+}
+
+impl ExtensionLinterProxy for LanguageServerRegistryProxy {
+    fn register_linter(
+        &self,
+        extension: Arc<dyn Extension>,
+        linter_id: Arc<str>,
+        languages: Vec<LanguageName>,
+        cx: &mut App,
+    ) {
+        ExternalToolRegistry::global(cx).register_linter(
+            linter_id.clone(),
+            languages,
+            Arc::new(ExtensionLinter {
+                extension,
+                linter_id,
+            }),
+        );
+    }
+
+    fn unregister_linter(&self, linter_id: Arc<str>, cx: &mut App) {
+        ExternalToolRegistry::global(cx).remove_linter(&linter_id);
+    }
+}
+
+struct ExtensionFormatter {
+    extension: Arc<dyn Extension>,
+    formatter_id: Arc<str>,
+}
+
+#[async_trait]
+impl ExternalFormatter for ExtensionFormatter {
+    async fn format(
+        &self,
+        request: ExternalFormatRequest,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<ExternalFormatOutput> {
+        let delegate = Arc::new(WorktreeDelegateAdapter(delegate)) as _;
+        let output = self
+            .extension
+            .format(
+                self.formatter_id.clone(),
+                FormatRequest {
+                    language_name: request.language.to_string(),
+                    path: request.path.map(|path| path.to_string_lossy().into_owned()),
+                    text: request.text,
+                    tab_size: request.tab_size,
+                    hard_tabs: request.hard_tabs,
+                },
+                delegate,
+            )
+            .await?;
+
+        Ok(match output {
+            FormatOutput::Unchanged => ExternalFormatOutput::Unchanged,
+            FormatOutput::Text(text) => ExternalFormatOutput::Text(text),
+            FormatOutput::Command(command) => {
+                ExternalFormatOutput::Command(ExternalFormatCommand {
+                    path: self.extension.path_from_extension(&command.command),
+                    arguments: command.args,
+                    env: command.env.into_iter().collect(),
+                })
+            }
+        })
+    }
+}
+
+struct ExtensionLinter {
+    extension: Arc<dyn Extension>,
+    linter_id: Arc<str>,
+}
+
+#[async_trait]
+impl ExternalLinter for ExtensionLinter {
+    async fn lint(
+        &self,
+        request: ExternalLintRequest,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<Vec<lsp::Diagnostic>> {
+        let delegate = Arc::new(WorktreeDelegateAdapter(delegate)) as _;
+        let diagnostics = self
+            .extension
+            .lint(
+                self.linter_id.clone(),
+                LintRequest {
+                    language_name: request.language.to_string(),
+                    path: request.path.to_string_lossy().into_owned(),
+                    text: request.text,
+                },
+                delegate,
+            )
+            .await?;
+
+        Ok(diagnostics
+            .into_iter()
+            .map(|diagnostic| lsp_diagnostic(&self.linter_id, diagnostic))
+            .collect())
+    }
+}
+
+fn lsp_diagnostic(linter_id: &str, diagnostic: LintDiagnostic) -> lsp::Diagnostic {
+    let severity = match diagnostic.severity {
+        LintSeverity::Error => lsp::DiagnosticSeverity::ERROR,
+        LintSeverity::Warning => lsp::DiagnosticSeverity::WARNING,
+        LintSeverity::Information => lsp::DiagnosticSeverity::INFORMATION,
+        LintSeverity::Hint => lsp::DiagnosticSeverity::HINT,
+    };
+
+    lsp::Diagnostic {
+        range: lsp::Range::new(
+            lsp::Position::new(diagnostic.start.line, diagnostic.start.column),
+            lsp::Position::new(diagnostic.end.line, diagnostic.end.column),
+        ),
+        severity: Some(severity),
+        code: diagnostic.code.map(lsp::NumberOrString::String),
+        source: Some(linter_id.to_string()),
+        message: diagnostic.message,
+        ..Default::default()
+    }
+}
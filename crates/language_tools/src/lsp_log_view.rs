@@ -18,11 +18,12 @@ use project::{
 };
 use proto::toggle_lsp_logs::LogType;
 use std::{any::TypeId, borrow::Cow, sync::Arc};
-use ui::{Checkbox, ContextMenu, PopoverMenu, ToggleState, prelude::*};
+use ui::{Checkbox, ContextMenu, PopoverMenu, ToggleState, Tooltip, prelude::*};
 use util::ResultExt as _;
 use workspace::{
     SplitDirection, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView, Workspace, WorkspaceId,
     item::{Item, ItemHandle},
+    notifications::DetachAndPromptErr as _,
     searchable::{Direction, SearchEvent, SearchToken, SearchableItem, SearchableItemHandle},
 };
 
@@ -631,6 +632,51 @@ impl LspLogView {
         }
     }
 
+    /// Stops recording the server's messages, or asks where to save a new
+    /// recording and starts it.
+    fn toggle_recording(
+        &mut self,
+        server_id: LanguageServerId,
+        server_name: &LanguageServerName,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let stopped = self
+            .log_store
+            .update(cx, |log_store, cx| log_store.stop_recording(server_id, cx));
+        if stopped.is_some() {
+            return;
+        }
+
+        let directory = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .unwrap_or_default();
+        let save_dialog =
+            cx.prompt_for_new_path(&directory, Some(&format!("{server_name}.lsp.jsonl")));
+        let log_store = self.log_store.clone();
+        cx.spawn(async move |_, cx| {
+            let Ok(path) = save_dialog.await else {
+                return Ok(());
+            };
+            let Some(path) = path? else {
+                return Ok(());
+            };
+            log_store.update(cx, |log_store, cx| {
+                log_store.start_recording(server_id, path, cx)
+            })
+        })
+        .detach_and_prompt_err(
+            "Failed to record language server messages",
+            window,
+            cx,
+            |_, _, _| None,
+        );
+    }
+
     fn show_server_info(
         &mut self,
         server_id: LanguageServerId,
@@ -1019,6 +1065,9 @@ impl Render for LspLogToolbarItemView {
                 }
             });
 
+        let recorded_server = current_server
+            .as_ref()
+            .map(|server| (server.server_id, server.server_name.clone()));
         let view_selector = current_server.map(|server| {
             let server_id = server.server_id;
             let rpc_trace_enabled = server.rpc_trace_enabled;
@@ -1263,6 +1312,32 @@ impl Render for LspLogToolbarItemView {
                         }),
                     ),
             )
+            .when_some(recorded_server, |this, (server_id, server_name)| {
+                let is_recording = log_view
+                    .read(cx)
+                    .log_store
+                    .read(cx)
+                    .recording_path(server_id)
+                    .is_some();
+                let label = if is_recording {
+                    "Stop Recording"
+                } else {
+                    "Record"
+                };
+                this.child(
+                    Button::new("record_log_button", label)
+                        .tooltip(Tooltip::text(
+                            "Save the server's JSON-RPC messages to a transcript",
+                        ))
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            if let Some(log_view) = this.log_view.as_ref() {
+                                log_view.update(cx, |log_view, cx| {
+                                    log_view.toggle_recording(server_id, &server_name, window, cx);
+                                });
+                            }
+                        })),
+                )
+            })
             .child(
                 Button::new("clear_log_button", "Clear").on_click(cx.listener(
                     |this, _, window, cx| {
@@ -0,0 +1,407 @@
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use futures::StreamExt as _;
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Task};
+use project::{
+    FS_WATCH_LATENCY, Fs, MAX_LOADABLE_FILE_SIZE, Project, ProjectEntryId, ProjectPath,
+    WindowedFile,
+};
+use regex::bytes::Regex;
+
+use crate::line_index::LineIndex;
+
+/// The number of bytes indexed between two updates of the views.
+const INDEX_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+/// The number of bytes compared at each end of the indexed part of the file
+/// to tell whether the file was appended to or rewritten.
+const PREFIX_CHECK_LEN: usize = 4 * 1024;
+
+pub enum LargeFileEvent {
+    /// More lines were indexed, or the file changed on disk.
+    Changed,
+}
+
+/// A read-only file that is too large to be loaded into a buffer.
+pub struct LargeFile {
+    project_path: ProjectPath,
+    entry_id: ProjectEntryId,
+    abs_path: PathBuf,
+    fs: Arc<dyn Fs>,
+    /// The inode of the file, which changes when the file is replaced.
+    inode: u64,
+    snapshot: LargeFileSnapshot,
+    indexed_prefix: IndexedPrefix,
+    index_lines: Task<()>,
+    _watch_file: Task<()>,
+}
+
+/// The bytes at both ends of the indexed part of a file.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct IndexedPrefix {
+    head: Vec<u8>,
+    tail: Vec<u8>,
+}
+
+impl IndexedPrefix {
+    fn read(text: &WindowedFile, len: usize) -> Self {
+        Self {
+            head: text.read(0..len.min(PREFIX_CHECK_LEN)),
+            tail: text.read(len.saturating_sub(PREFIX_CHECK_LEN)..len),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LargeFileSnapshot {
+    text: WindowedFile,
+    line_index: LineIndex,
+}
+
+impl LargeFileSnapshot {
+    pub(crate) fn text(&self) -> &WindowedFile {
+        &self.text
+    }
+
+    /// The length of the file when it was last loaded.
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Reads the given range, which is cut short if the file was truncated.
+    pub fn read(&self, range: Range<usize>) -> Vec<u8> {
+        self.text.read(range)
+    }
+
+    pub fn line_index(&self) -> &LineIndex {
+        &self.line_index
+    }
+
+    pub fn is_indexed(&self) -> bool {
+        self.line_index.indexed_len() == self.text.len()
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_index.line_count()
+    }
+
+    pub fn line_range(&self, row: usize) -> Option<Range<usize>> {
+        self.line_index.line_range(&self.text, row)
+    }
+
+    pub fn line_ranges(&self, rows: Range<usize>) -> Vec<Range<usize>> {
+        self.line_index.line_ranges(&self.text, rows)
+    }
+
+    pub fn row_for_offset(&self, offset: usize) -> usize {
+        self.line_index.row_for_offset(&self.text, offset)
+    }
+}
+
+impl EventEmitter<LargeFileEvent> for LargeFile {}
+
+impl LargeFile {
+    fn new(
+        project_path: ProjectPath,
+        entry_id: ProjectEntryId,
+        abs_path: PathBuf,
+        fs: Arc<dyn Fs>,
+        inode: u64,
+        text: WindowedFile,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut this = Self {
+            project_path,
+            entry_id,
+            abs_path,
+            fs,
+            inode,
+            snapshot: LargeFileSnapshot {
+                text,
+                line_index: LineIndex::default(),
+            },
+            indexed_prefix: IndexedPrefix::default(),
+            index_lines: Task::ready(()),
+            _watch_file: Task::ready(()),
+        };
+        this.index_lines(cx);
+        this._watch_file = this.watch_file(cx);
+        this
+    }
+
+    pub fn snapshot(&self) -> &LargeFileSnapshot {
+        &self.snapshot
+    }
+
+    pub fn abs_path(&self) -> &Path {
+        &self.abs_path
+    }
+
+    pub fn project_path(&self) -> &ProjectPath {
+        &self.project_path
+    }
+
+    fn index_lines(&mut self, cx: &mut Context<Self>) {
+        let text = self.snapshot.text.clone();
+        let mut line_index = self.snapshot.line_index.clone();
+        self.index_lines = cx.spawn(async move |this, cx| {
+            loop {
+                let (done, indexed_prefix);
+                (line_index, indexed_prefix, done) = cx
+                    .background_spawn({
+                        let text = text.clone();
+                        async move {
+                            let done = line_index.index(&text, INDEX_CHUNK_SIZE);
+                            let indexed_prefix =
+                                IndexedPrefix::read(&text, line_index.indexed_len());
+                            (line_index, indexed_prefix, done)
+                        }
+                    })
+                    .await;
+                let updated = this.update(cx, |this, cx| {
+                    this.snapshot.line_index = line_index.clone();
+                    this.indexed_prefix = indexed_prefix;
+                    cx.emit(LargeFileEvent::Changed);
+                });
+                if updated.is_err() || done {
+                    break;
+                }
+            }
+        });
+    }
+
+    fn watch_file(&self, cx: &mut Context<Self>) -> Task<()> {
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        cx.spawn(async move |this, cx| {
+            let (mut events, _watcher) = fs.watch(&abs_path, FS_WATCH_LATENCY).await;
+            while events.next().await.is_some() {
+                // Check again if more lines were indexed while the file was being checked.
+                loop {
+                    let Ok((inode, indexed_len, indexed_prefix)) = this.read_with(cx, |this, _| {
+                        (
+                            this.inode,
+                            this.snapshot.line_index.indexed_len(),
+                            this.indexed_prefix.clone(),
+                        )
+                    }) else {
+                        return;
+                    };
+                    let reloaded = cx
+                        .background_spawn({
+                            let fs = fs.clone();
+                            let abs_path = abs_path.clone();
+                            async move {
+                                let (new_inode, text) = load_file(&fs, &abs_path).await?;
+                                let appended = new_inode == inode
+                                    && text.len() >= indexed_len
+                                    && IndexedPrefix::read(&text, indexed_len) == indexed_prefix;
+                                anyhow::Ok((new_inode, text, appended))
+                            }
+                        })
+                        .await;
+                    let (inode, text, appended) = match reloaded {
+                        Ok(reloaded) => reloaded,
+                        Err(error) => {
+                            log::error!("failed to reload large file: {error:#}");
+                            break;
+                        }
+                    };
+                    let Ok(reloaded) = this.update(cx, |this, cx| {
+                        if this.snapshot.line_index.indexed_len() != indexed_len {
+                            return false;
+                        }
+                        this.reload(inode, text, appended, cx);
+                        true
+                    }) else {
+                        return;
+                    };
+                    if reloaded {
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Replaces the file's contents, indexing only the appended lines if the
+    /// indexed part of the file is unchanged.
+    fn reload(&mut self, inode: u64, text: WindowedFile, appended: bool, cx: &mut Context<Self>) {
+        if !appended {
+            self.snapshot.line_index = LineIndex::default();
+            self.indexed_prefix = IndexedPrefix::default();
+        }
+        self.inode = inode;
+        self.snapshot.text = text;
+        self.index_lines(cx);
+        cx.emit(LargeFileEvent::Changed);
+    }
+}
+
+impl project::ProjectItem for LargeFile {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        let project = project.read(cx);
+        if !project.is_local() {
+            return None;
+        }
+        let entry = project.entry_for_path(path, cx)?;
+        if !entry.is_file() || entry.size < MAX_LOADABLE_FILE_SIZE {
+            return None;
+        }
+        let entry_id = entry.id;
+        let abs_path = project.absolute_path(path, cx)?;
+        let fs = project.fs().clone();
+        let path = path.clone();
+        Some(cx.spawn(async move |cx| {
+            let (inode, text) = cx
+                .background_spawn({
+                    let fs = fs.clone();
+                    let abs_path = abs_path.clone();
+                    async move { load_file(&fs, &abs_path).await }
+                })
+                .await?;
+            Ok(cx.new(|cx| LargeFile::new(path, entry_id, abs_path, fs, inode, text, cx)))
+        }))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        Some(self.entry_id)
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+
+    fn is_dirty(&self) -> bool {
+        false
+    }
+}
+
+/// Opens the file at `abs_path`, returning its inode along with it.
+async fn load_file(fs: &Arc<dyn Fs>, abs_path: &Path) -> Result<(u64, WindowedFile)> {
+    let metadata = fs
+        .metadata(abs_path)
+        .await?
+        .with_context(|| format!("{} does not exist", abs_path.display()))?;
+    let text = fs.open_windowed(abs_path)?;
+    Ok((metadata.inode, text))
+}
+
+/// Appends the matches of `regex` within each line of `text` to `matches`,
+/// where `text` was read from the given offset at the beginning of a line.
+pub fn search_lines(regex: &Regex, text: &[u8], offset: usize, matches: &mut Vec<Range<usize>>) {
+    let mut line_start = offset;
+    for line in text.split(|&byte| byte == b'\n') {
+        matches.extend(
+            regex
+                .find_iter(line)
+                .map(|mat| line_start + mat.start()..line_start + mat.end()),
+        );
+        line_start += line.len() + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use project::WorktreeId;
+    use util::{path, rel_path::RelPath};
+
+    #[test]
+    fn test_search_lines() {
+        let text = b"foo bar\nbaz foo\n\nfoofoo";
+        let regex = Regex::new("fo+").unwrap();
+
+        let mut matches = Vec::new();
+        search_lines(&regex, text, 0, &mut matches);
+        assert_eq!(matches, vec![0..3, 12..15, 17..20, 20..23]);
+
+        let mut matches = Vec::new();
+        search_lines(&regex, &text[8..16], 8, &mut matches);
+        assert_eq!(matches, vec![12..15]);
+
+        // Matches never span lines.
+        let regex = Regex::new(r"bar\s+baz").unwrap();
+        let mut matches = Vec::new();
+        search_lines(&regex, text, 0, &mut matches);
+        assert_eq!(matches, Vec::<Range<usize>>::new());
+    }
+
+    #[gpui::test]
+    async fn test_indexed_prefix(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let path = Path::new(path!("/root/app.log"));
+        let read_prefix = |len| IndexedPrefix::read(&fs.open_windowed(path).unwrap(), len);
+        fs.insert_file(path, b"one\ntwo\n".to_vec()).await;
+        let prefix = read_prefix(8);
+
+        fs.insert_file(path, b"one\ntwo\nthree\n".to_vec()).await;
+        assert_eq!(read_prefix(8), prefix);
+
+        // Rewrites are told apart from appends whatever the new length.
+        fs.insert_file(path, b"uno\ntwo\n".to_vec()).await;
+        assert_ne!(read_prefix(8), prefix);
+        fs.insert_file(path, "one\ndos\nthree\n".repeat(1000).into_bytes())
+            .await;
+        assert_ne!(read_prefix(8), prefix);
+    }
+
+    #[gpui::test]
+    async fn test_reload(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        let abs_path = PathBuf::from(path!("/root/app.log"));
+        fs.insert_file(&abs_path, b"one\ntwo\n".to_vec()).await;
+        let inode = fs.metadata(&abs_path).await.unwrap().unwrap().inode;
+        let text = fs.open_windowed(&abs_path).unwrap();
+        let project_path = ProjectPath {
+            worktree_id: WorktreeId::from_usize(1),
+            path: RelPath::unix("app.log").unwrap().into(),
+        };
+        let entry_id = ProjectEntryId::from_proto(1);
+        let file = cx.new(|cx| {
+            LargeFile::new(
+                project_path,
+                entry_id,
+                abs_path.clone(),
+                fs.clone(),
+                inode,
+                text,
+                cx,
+            )
+        });
+        cx.run_until_parked();
+        let lines = |cx: &mut TestAppContext| {
+            file.read_with(cx, |file, _| {
+                let snapshot = file.snapshot();
+                (0..snapshot.line_count())
+                    .map(|row| String::from_utf8(snapshot.read(snapshot.line_range(row).unwrap())))
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap()
+            })
+        };
+        assert_eq!(lines(cx), ["one", "two", ""]);
+
+        fs.write(&abs_path, b"one\ntwo\nthree\n").await.unwrap();
+        cx.run_until_parked();
+        assert_eq!(lines(cx), ["one", "two", "three", ""]);
+
+        // A rewrite of the same length is reindexed rather than skipped.
+        fs.write(&abs_path, b"uno\ndos\ntres\n\n").await.unwrap();
+        cx.run_until_parked();
+        assert_eq!(lines(cx), ["uno", "dos", "tres", "", ""]);
+    }
+}
@@ -0,0 +1,750 @@
+mod large_file;
+mod line_index;
+
+use std::{ops::Range, path::Path};
+
+use editor::{Editor, EditorEvent};
+use file_icons::FileIcons;
+use gpui::{
+    App, Context, Entity, EventEmitter, FocusHandle, Focusable, HighlightStyle, InteractiveElement,
+    IntoElement, ParentElement, Pixels, Render, ScrollWheelEvent, SharedString, StyledText,
+    Subscription, Task, Window, actions, canvas, div, px,
+};
+use project::Project;
+use regex::bytes::Regex;
+use settings::Settings;
+use theme::ThemeSettings;
+use ui::{Tooltip, prelude::*};
+use util::paths::PathExt;
+use workspace::{
+    ItemSettings, Pane,
+    invalid_item_view::InvalidItemView,
+    item::{Item, ItemBufferKind, ItemEvent, ProjectItem},
+    searchable::Direction,
+};
+
+pub use crate::large_file::{LargeFile, LargeFileEvent, LargeFileSnapshot};
+use crate::{large_file::search_lines, line_index::find_newline};
+
+actions!(
+    large_file_viewer,
+    [
+        /// Focuses the search input.
+        Search,
+        /// Focuses the go to line input.
+        GoToLine,
+        /// Scrolls to the next search match.
+        SelectNextMatch,
+        /// Scrolls to the previous search match.
+        SelectPreviousMatch,
+        /// Toggles following lines appended to the file.
+        ToggleFollow,
+    ]
+);
+
+/// Longer lines are truncated when rendered.
+const MAX_RENDERED_LINE_LEN: usize = 4096;
+/// The number of bytes searched between two updates of the match count.
+const SEARCH_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+const MAX_MATCHES: usize = 100_000;
+
+pub fn init(cx: &mut App) {
+    workspace::register_project_item::<LargeFileView>(cx);
+}
+
+/// A read-only view of a [`LargeFile`] that only renders the visible lines.
+pub struct LargeFileView {
+    file: Entity<LargeFile>,
+    focus_handle: FocusHandle,
+    query_editor: Entity<Editor>,
+    line_editor: Entity<Editor>,
+    scroll_row: usize,
+    scroll_remainder: Pixels,
+    visible_rows: usize,
+    visible_lines: VisibleLines,
+    load_visible_lines: Task<()>,
+    following: bool,
+    search: SearchState,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// The lines in view, which are read on a background thread whenever the view
+/// scrolls or the file changes.
+#[derive(Default)]
+struct VisibleLines {
+    first_row: usize,
+    lines: Vec<VisibleLine>,
+}
+
+struct VisibleLine {
+    range: Range<usize>,
+    /// The start of the line, up to `MAX_RENDERED_LINE_LEN` bytes.
+    bytes: Vec<u8>,
+}
+
+#[derive(Default)]
+struct SearchState {
+    regex: Option<Regex>,
+    error: Option<SharedString>,
+    /// Sorted byte ranges of the matches found so far.
+    matches: Vec<Range<usize>>,
+    active_match: Option<usize>,
+    searched_len: usize,
+    limit_reached: bool,
+    pending: Option<Task<()>>,
+}
+
+impl LargeFileView {
+    pub fn new(file: Entity<LargeFile>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let query_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Search with a regular expression…", window, cx);
+            editor
+        });
+        let line_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Go to line…", window, cx);
+            editor
+        });
+        let subscriptions = vec![
+            cx.subscribe(&file, |this, _, event, cx| this.on_file_event(event, cx)),
+            cx.subscribe(&query_editor, |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::Edited { .. } = event {
+                    this.update_query(cx);
+                }
+            }),
+        ];
+
+        Self {
+            file,
+            focus_handle: cx.focus_handle(),
+            query_editor,
+            line_editor,
+            scroll_row: 0,
+            scroll_remainder: Pixels::ZERO,
+            visible_rows: 0,
+            visible_lines: VisibleLines::default(),
+            load_visible_lines: Task::ready(()),
+            following: false,
+            search: SearchState::default(),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn on_file_event(&mut self, event: &LargeFileEvent, cx: &mut Context<Self>) {
+        match event {
+            LargeFileEvent::Changed => {
+                let indexed_len = self.file.read(cx).snapshot().line_index().indexed_len();
+                if indexed_len < self.search.searched_len {
+                    // The file was rewritten, so the matches are no longer valid.
+                    self.update_query(cx);
+                }
+                if self.following {
+                    self.scroll_to_end(cx);
+                } else {
+                    self.scroll_row = self.scroll_row.min(self.max_scroll_row(cx));
+                }
+                self.continue_search(cx);
+                self.load_visible_lines(cx);
+            }
+        }
+    }
+
+    fn load_visible_lines(&mut self, cx: &mut Context<Self>) {
+        let snapshot = self.file.read(cx).snapshot().clone();
+        let rows = self.scroll_row..self.scroll_row + self.visible_rows + 1;
+        self.load_visible_lines = cx.spawn(async move |this, cx| {
+            let visible_lines = cx
+                .background_spawn(async move {
+                    let first_row = rows.start;
+                    let lines = snapshot
+                        .line_ranges(rows)
+                        .into_iter()
+                        .map(|range| VisibleLine {
+                            bytes: snapshot.read(
+                                range.start..range.end.min(range.start + MAX_RENDERED_LINE_LEN),
+                            ),
+                            range,
+                        })
+                        .collect();
+                    VisibleLines { first_row, lines }
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                this.visible_lines = visible_lines;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn max_scroll_row(&self, cx: &App) -> usize {
+        let line_count = self.file.read(cx).snapshot().line_count();
+        line_count.saturating_sub(self.visible_rows.max(1))
+    }
+
+    fn scroll_to_row(&mut self, row: usize, cx: &mut Context<Self>) {
+        self.scroll_row = row
+            .saturating_sub(self.visible_rows / 2)
+            .min(self.max_scroll_row(cx));
+        self.scroll_remainder = Pixels::ZERO;
+        self.following = false;
+        self.load_visible_lines(cx);
+        cx.notify();
+    }
+
+    fn scroll_to_end(&mut self, cx: &mut Context<Self>) {
+        self.scroll_row = self.max_scroll_row(cx);
+        self.scroll_remainder = Pixels::ZERO;
+        self.load_visible_lines(cx);
+        cx.notify();
+    }
+
+    fn handle_scroll_wheel(
+        &mut self,
+        event: &ScrollWheelEvent,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let line_height = buffer_line_height(cx);
+        self.scroll_remainder -= event.delta.pixel_delta(line_height).y;
+        let rows = (self.scroll_remainder / line_height).trunc();
+        self.scroll_remainder -= line_height * rows;
+        let scroll_row = if rows < 0. {
+            self.following = false;
+            self.scroll_row.saturating_sub(-rows as usize)
+        } else {
+            self.scroll_row.saturating_add(rows as usize)
+        };
+        let scroll_row = scroll_row.min(self.max_scroll_row(cx));
+        if scroll_row != self.scroll_row {
+            self.scroll_row = scroll_row;
+            self.load_visible_lines(cx);
+        }
+        cx.notify();
+    }
+
+    fn focus_search(&mut self, _: &Search, window: &mut Window, cx: &mut Context<Self>) {
+        let focus_handle = self.query_editor.focus_handle(cx);
+        window.focus(&focus_handle, cx);
+    }
+
+    fn focus_line_input(&mut self, _: &GoToLine, window: &mut Window, cx: &mut Context<Self>) {
+        let focus_handle = self.line_editor.focus_handle(cx);
+        window.focus(&focus_handle, cx);
+    }
+
+    fn confirm_go_to_line(
+        &mut self,
+        _: &menu::Confirm,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let text = self.line_editor.read(cx).text(cx);
+        let Some(row) = text
+            .trim()
+            .split(':')
+            .next()
+            .and_then(|row| row.parse::<usize>().ok())
+        else {
+            return;
+        };
+        self.scroll_to_row(row.saturating_sub(1), cx);
+        self.line_editor
+            .update(cx, |editor, cx| editor.clear(window, cx));
+        window.focus(&self.focus_handle, cx);
+    }
+
+    fn toggle_follow(&mut self, _: &ToggleFollow, _: &mut Window, cx: &mut Context<Self>) {
+        self.following = !self.following;
+        if self.following {
+            self.scroll_to_end(cx);
+        }
+        cx.notify();
+    }
+
+    fn update_query(&mut self, cx: &mut Context<Self>) {
+        self.search = SearchState::default();
+        let query = self.query_editor.read(cx).text(cx);
+        if !query.is_empty() {
+            match Regex::new(&query) {
+                Ok(regex) => {
+                    self.search.regex = Some(regex);
+                    self.continue_search(cx);
+                }
+                Err(error) => self.search.error = Some(error.to_string().into()),
+            }
+        }
+        cx.notify();
+    }
+
+    /// Searches the lines indexed since the last search, so that matches can
+    /// always be mapped to their rows.
+    fn continue_search(&mut self, cx: &mut Context<Self>) {
+        if self.search.pending.is_some() || self.search.limit_reached {
+            return;
+        }
+        let Some(regex) = self.search.regex.clone() else {
+            return;
+        };
+        let snapshot = self.file.read(cx).snapshot().clone();
+        let end = snapshot.line_index().indexed_len();
+        if self.search.searched_len >= end {
+            return;
+        }
+
+        let searched_len = self.search.searched_len;
+        self.search.pending = Some(cx.spawn(async move |this, cx| {
+            // The last searched line may have been extended since, so search it again.
+            let start = cx
+                .background_spawn({
+                    let snapshot = snapshot.clone();
+                    async move {
+                        snapshot
+                            .line_range(snapshot.row_for_offset(searched_len))
+                            .map_or(0, |range| range.start)
+                    }
+                })
+                .await;
+            let updated = this.update(cx, |this, _| {
+                let stale_matches = this.search.matches.partition_point(|mat| mat.start < start);
+                this.search.matches.truncate(stale_matches);
+            });
+            if updated.is_err() {
+                return;
+            }
+
+            let mut chunk_start = start;
+            while chunk_start < end {
+                let (chunk_end, matches) = cx
+                    .background_spawn({
+                        let snapshot = snapshot.clone();
+                        let regex = regex.clone();
+                        async move {
+                            let chunk_end = (chunk_start + SEARCH_CHUNK_SIZE).min(end);
+                            let chunk_end = find_newline(snapshot.text(), chunk_end..end)
+                                .map_or(end, |newline_ix| newline_ix + 1);
+                            let text = snapshot.read(chunk_start..chunk_end);
+                            let mut matches = Vec::new();
+                            search_lines(&regex, &text, chunk_start, &mut matches);
+                            (chunk_end, matches)
+                        }
+                    })
+                    .await;
+                let limit_reached = this.update(cx, |this, cx| {
+                    let search = &mut this.search;
+                    search.searched_len = chunk_end;
+                    search.matches.extend(matches);
+                    if search.matches.len() >= MAX_MATCHES {
+                        search.matches.truncate(MAX_MATCHES);
+                        search.limit_reached = true;
+                    }
+                    cx.notify();
+                    search.limit_reached
+                });
+                if limit_reached.unwrap_or(true) {
+                    break;
+                }
+                chunk_start = chunk_end;
+            }
+            this.update(cx, |this, cx| {
+                this.search.pending = None;
+                this.continue_search(cx);
+            })
+            .ok();
+        }));
+    }
+
+    fn select_next_match(&mut self, _: &SelectNextMatch, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_match(Direction::Next, cx);
+    }
+
+    fn select_previous_match(
+        &mut self,
+        _: &SelectPreviousMatch,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.select_match(Direction::Prev, cx);
+    }
+
+    fn confirm_search(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        self.select_match(Direction::Next, cx);
+    }
+
+    fn select_match(&mut self, direction: Direction, cx: &mut Context<Self>) {
+        let match_count = self.search.matches.len();
+        if match_count == 0 {
+            return;
+        }
+        let snapshot = self.file.read(cx).snapshot();
+        let ix = match (self.search.active_match, direction) {
+            (Some(ix), Direction::Next) => (ix + 1) % match_count,
+            (Some(ix), Direction::Prev) => (ix + match_count - 1) % match_count,
+            (None, direction) => {
+                let offset = self
+                    .visible_lines
+                    .lines
+                    .first()
+                    .map_or(0, |line| line.range.start);
+                let ix = self
+                    .search
+                    .matches
+                    .partition_point(|mat| mat.start < offset);
+                match direction {
+                    Direction::Next => ix % match_count,
+                    Direction::Prev => (ix + match_count - 1) % match_count,
+                }
+            }
+        };
+        let row = snapshot.row_for_offset(self.search.matches[ix].start);
+        self.search.active_match = Some(ix);
+        self.scroll_to_row(row, cx);
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let snapshot = self.file.read(cx).snapshot();
+        let status = if snapshot.is_indexed() {
+            format!("{} lines", snapshot.line_count())
+        } else {
+            let text_len = snapshot.len().max(1);
+            let progress = snapshot.line_index().indexed_len() * 100 / text_len;
+            format!("Indexing… {progress}%")
+        };
+
+        let match_count = self.search.matches.len();
+        let match_label = if let Some(error) = &self.search.error {
+            Some(
+                Label::new(error.clone())
+                    .size(LabelSize::Small)
+                    .color(Color::Error),
+            )
+        } else if self.search.regex.is_some() {
+            let count = if self.search.limit_reached {
+                format!("{match_count}+")
+            } else {
+                match_count.to_string()
+            };
+            let text = match self.search.active_match {
+                Some(ix) => format!("{} of {count}", ix + 1),
+                None => format!("{count} matches"),
+            };
+            Some(Label::new(text).size(LabelSize::Small).color(Color::Muted))
+        } else {
+            None
+        };
+
+        let colors = cx.theme().colors();
+        let (border, border_variant, toolbar_background) = (
+            colors.border,
+            colors.border_variant,
+            colors.toolbar_background,
+        );
+
+        h_flex()
+            .w_full()
+            .gap_2()
+            .px_2()
+            .py_1()
+            .border_b_1()
+            .border_color(border_variant)
+            .bg(toolbar_background)
+            .child(
+                h_flex()
+                    .key_context("LargeFileSearch")
+                    .on_action(cx.listener(Self::confirm_search))
+                    .w(px(320.))
+                    .px_2()
+                    .py_0p5()
+                    .border_1()
+                    .border_color(border)
+                    .rounded_md()
+                    .child(self.query_editor.clone()),
+            )
+            .child(
+                IconButton::new("previous-match", IconName::ChevronUp)
+                    .icon_size(IconSize::Small)
+                    .disabled(match_count == 0)
+                    .tooltip(|_window, cx| {
+                        Tooltip::for_action("Previous Match", &SelectPreviousMatch, cx)
+                    })
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.select_match(Direction::Prev, cx);
+                    })),
+            )
+            .child(
+                IconButton::new("next-match", IconName::ChevronDown)
+                    .icon_size(IconSize::Small)
+                    .disabled(match_count == 0)
+                    .tooltip(|_window, cx| Tooltip::for_action("Next Match", &SelectNextMatch, cx))
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.select_match(Direction::Next, cx);
+                    })),
+            )
+            .children(match_label)
+            .child(div().flex_1())
+            .child(
+                h_flex()
+                    .key_context("LargeFileGoToLine")
+                    .on_action(cx.listener(Self::confirm_go_to_line))
+                    .w(px(120.))
+                    .px_2()
+                    .py_0p5()
+                    .border_1()
+                    .border_color(border)
+                    .rounded_md()
+                    .child(self.line_editor.clone()),
+            )
+            .child(
+                IconButton::new("follow", IconName::ArrowDown)
+                    .icon_size(IconSize::Small)
+                    .toggle_state(self.following)
+                    .tooltip(|_window, cx| {
+                        Tooltip::for_action("Follow Appended Lines", &ToggleFollow, cx)
+                    })
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.toggle_follow(&ToggleFollow, window, cx);
+                    })),
+            )
+            .child(
+                Label::new(status)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+    }
+
+    fn render_lines(&self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let font = settings.buffer_font.clone();
+        let font_size = settings.buffer_font_size(cx);
+        let line_height = buffer_line_height(cx);
+        let colors = cx.theme().colors().clone();
+        let line_count = self.file.read(cx).snapshot().line_count();
+        let font_id = window.text_system().resolve_font(&font);
+        let em_advance = window
+            .text_system()
+            .em_advance(font_id, font_size)
+            .unwrap_or(font_size);
+        let gutter_width = em_advance * (line_count.to_string().len() + 2) as f32;
+
+        let match_style = HighlightStyle {
+            background_color: Some(colors.search_match_background),
+            ..HighlightStyle::default()
+        };
+        let active_match_style = HighlightStyle {
+            background_color: Some(colors.search_active_match_background),
+            ..HighlightStyle::default()
+        };
+        let active_match = self
+            .search
+            .active_match
+            .and_then(|ix| self.search.matches.get(ix));
+
+        let first_row = self.visible_lines.first_row;
+        let lines = self.visible_lines.lines.iter().enumerate();
+        let rows = lines.map(|(ix, line)| {
+            let row = first_row + ix;
+            let range = line.range.clone();
+            let line = line.bytes.as_slice();
+            // Truncating the line may have split its last character.
+            let valid_text = match str::from_utf8(line) {
+                Ok(text) => Some(text),
+                Err(error) if error.error_len().is_none() => {
+                    str::from_utf8(&line[..error.valid_up_to()]).ok()
+                }
+                Err(_) => None,
+            };
+            let mut highlights = Vec::new();
+            // Highlights are only shown for valid UTF-8, since lossy
+            // conversion shifts the byte offsets.
+            let text: SharedString = match valid_text {
+                Some(text) => {
+                    let first_match = self
+                        .search
+                        .matches
+                        .partition_point(|mat| mat.start < range.start);
+                    for mat in self.search.matches[first_match..]
+                        .iter()
+                        .take_while(|mat| mat.start <= range.end)
+                    {
+                        let start = mat.start - range.start;
+                        let end = (mat.end - range.start).min(text.len());
+                        if start < end && text.is_char_boundary(start) && text.is_char_boundary(end)
+                        {
+                            let style = if Some(mat) == active_match {
+                                active_match_style
+                            } else {
+                                match_style
+                            };
+                            highlights.push((start..end, style));
+                        }
+                    }
+                    text.to_string().into()
+                }
+                None => String::from_utf8_lossy(line).into_owned().into(),
+            };
+            let truncated = range.len() > MAX_RENDERED_LINE_LEN;
+
+            h_flex()
+                .h(line_height)
+                .child(
+                    div()
+                        .flex_none()
+                        .w(gutter_width)
+                        .pr(em_advance)
+                        .text_right()
+                        .text_color(colors.editor_line_number)
+                        .child((row + 1).to_string()),
+                )
+                .child(
+                    div()
+                        .flex_none()
+                        .whitespace_nowrap()
+                        .child(StyledText::new(text).with_highlights(highlights)),
+                )
+                .when(truncated, |this| {
+                    this.child(div().text_color(colors.text_muted).child(" …"))
+                })
+        });
+        let rows = rows.collect::<Vec<_>>();
+
+        div()
+            .id("large-file-lines")
+            .relative()
+            .flex_1()
+            .w_full()
+            .overflow_x_scroll()
+            .font(font)
+            .text_size(font_size)
+            .line_height(line_height)
+            .text_color(colors.editor_foreground)
+            .bg(colors.editor_background)
+            .on_scroll_wheel(cx.listener(Self::handle_scroll_wheel))
+            .child(
+                canvas(
+                    {
+                        let this = cx.entity().downgrade();
+                        move |bounds, _, cx| {
+                            let visible_rows = (bounds.size.height / line_height).floor() as usize;
+                            this.update(cx, |this, cx| {
+                                if this.visible_rows != visible_rows {
+                                    this.visible_rows = visible_rows;
+                                    if this.following {
+                                        this.scroll_to_end(cx);
+                                    } else {
+                                        this.load_visible_lines(cx);
+                                    }
+                                    cx.notify();
+                                }
+                            })
+                            .ok();
+                        }
+                    },
+                    |_, _, _, _| {},
+                )
+                .absolute()
+                .size_full(),
+            )
+            .child(v_flex().children(rows))
+    }
+}
+
+fn buffer_line_height(cx: &App) -> Pixels {
+    let settings = ThemeSettings::get_global(cx);
+    settings.buffer_font_size(cx) * settings.line_height()
+}
+
+impl EventEmitter<()> for LargeFileView {}
+
+impl Focusable for LargeFileView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for LargeFileView {
+    type Event = ();
+
+    fn to_item_events(_: &Self::Event, _: &mut dyn FnMut(ItemEvent)) {}
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.file.entity_id(), self.file.read(cx))
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.file.read(cx).abs_path();
+        Some(abs_path.compact().to_string_lossy().into_owned().into())
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.file
+            .read(cx)
+            .abs_path()
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned().into())
+            .unwrap_or_default()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.file.read(cx).abs_path();
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn buffer_kind(&self, _: &App) -> ItemBufferKind {
+        ItemBufferKind::Singleton
+    }
+}
+
+impl Render for LargeFileView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .track_focus(&self.focus_handle(cx))
+            .key_context("LargeFileViewer")
+            .on_action(cx.listener(Self::focus_search))
+            .on_action(cx.listener(Self::focus_line_input))
+            .on_action(cx.listener(Self::select_next_match))
+            .on_action(cx.listener(Self::select_previous_match))
+            .on_action(cx.listener(Self::toggle_follow))
+            .size_full()
+            .child(self.render_header(cx))
+            .child(self.render_lines(window, cx))
+    }
+}
+
+impl ProjectItem for LargeFileView {
+    type Item = LargeFile;
+
+    fn for_project_item(
+        _: Entity<Project>,
+        _: Option<&Pane>,
+        item: Entity<Self::Item>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, window, cx)
+    }
+
+    fn for_broken_project_item(
+        abs_path: &Path,
+        is_local: bool,
+        e: &anyhow::Error,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<InvalidItemView>
+    where
+        Self: Sized,
+    {
+        Some(InvalidItemView::new(abs_path, is_local, e, window, cx))
+    }
+}
@@ -0,0 +1,286 @@
+use std::{borrow::Cow, ops::Range};
+
+use project::WindowedFile;
+
+/// The number of lines between two checkpoints. Locating a line scans at most
+/// this many newlines, while the index stays small even for huge files.
+const LINES_PER_CHECKPOINT: usize = 1024;
+
+/// The number of bytes read at once when indexing text.
+const READ_WINDOW_SIZE: usize = 1024 * 1024;
+
+/// The number of bytes first read when looking for a newline. Lines are
+/// usually short, so the window only grows for long lines.
+const MIN_SEARCH_WINDOW_SIZE: usize = 4 * 1024;
+
+/// Text that is read in windows, rather than being held in memory at once.
+pub(crate) trait TextSource {
+    fn len(&self) -> usize;
+
+    /// Reads the given range, which is cut short if the text shrank since its
+    /// length was determined.
+    fn read(&self, range: Range<usize>) -> Cow<'_, [u8]>;
+}
+
+impl TextSource for [u8] {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn read(&self, range: Range<usize>) -> Cow<'_, [u8]> {
+        let end = range.end.min(self.len());
+        Cow::Borrowed(&self[range.start.min(end)..end])
+    }
+}
+
+impl TextSource for WindowedFile {
+    fn len(&self) -> usize {
+        WindowedFile::len(self)
+    }
+
+    fn read(&self, range: Range<usize>) -> Cow<'_, [u8]> {
+        Cow::Owned(WindowedFile::read(self, range))
+    }
+}
+
+/// Returns the offset of the first newline in `range` for which `f` returns
+/// true, reading the text in windows that double in size up to
+/// `READ_WINDOW_SIZE`.
+fn find_newline_where<T: TextSource + ?Sized>(
+    text: &T,
+    range: Range<usize>,
+    mut f: impl FnMut(usize) -> bool,
+) -> Option<usize> {
+    let mut window_start = range.start;
+    let mut window_size = MIN_SEARCH_WINDOW_SIZE;
+    while window_start < range.end {
+        let window_end = window_start.saturating_add(window_size).min(range.end);
+        window_size = (window_size * 2).min(READ_WINDOW_SIZE);
+        let window = text.read(window_start..window_end);
+        for newline_ix in memchr::memchr_iter(b'\n', &window) {
+            let offset = window_start + newline_ix;
+            if f(offset) {
+                return Some(offset);
+            }
+        }
+        if window.len() < window_end - window_start {
+            break;
+        }
+        window_start = window_end;
+    }
+    None
+}
+
+/// Returns the offset of the first newline in `range`.
+pub(crate) fn find_newline<T: TextSource + ?Sized>(text: &T, range: Range<usize>) -> Option<usize> {
+    find_newline_where(text, range, |_| true)
+}
+
+/// A sparse index of line start offsets, built incrementally so that files can
+/// be displayed while they are still being indexed.
+#[derive(Clone, Debug)]
+pub struct LineIndex {
+    /// The offset at which every `LINES_PER_CHECKPOINT`th line starts.
+    checkpoints: Vec<usize>,
+    newline_count: usize,
+    indexed_len: usize,
+}
+
+impl Default for LineIndex {
+    fn default() -> Self {
+        Self {
+            checkpoints: vec![0],
+            newline_count: 0,
+            indexed_len: 0,
+        }
+    }
+}
+
+impl LineIndex {
+    /// Indexes at most `max_bytes` of `text` past the already indexed prefix,
+    /// returning whether all of `text` has been indexed. Indexing stops early
+    /// when the text turns out to be shorter than its length.
+    pub(crate) fn index<T: TextSource + ?Sized>(&mut self, text: &T, max_bytes: usize) -> bool {
+        let start = self.indexed_len.min(text.len());
+        let end = start.saturating_add(max_bytes).min(text.len());
+        let mut window_start = start;
+        while window_start < end {
+            let window_end = window_start.saturating_add(READ_WINDOW_SIZE).min(end);
+            let window = text.read(window_start..window_end);
+            for newline_ix in memchr::memchr_iter(b'\n', &window) {
+                self.newline_count += 1;
+                if self.newline_count.is_multiple_of(LINES_PER_CHECKPOINT) {
+                    self.checkpoints.push(window_start + newline_ix + 1);
+                }
+            }
+            self.indexed_len = window_start + window.len();
+            if window.len() < window_end - window_start {
+                return true;
+            }
+            window_start = window_end;
+        }
+        self.indexed_len = end;
+        end == text.len()
+    }
+
+    pub fn indexed_len(&self) -> usize {
+        self.indexed_len
+    }
+
+    /// The number of lines in the indexed prefix. Like in the editor, a file
+    /// ending with a newline has an empty last line.
+    pub fn line_count(&self) -> usize {
+        self.newline_count + 1
+    }
+
+    /// Returns the byte range of the given line, without its newline.
+    pub(crate) fn line_range<T: TextSource + ?Sized>(
+        &self,
+        text: &T,
+        row: usize,
+    ) -> Option<Range<usize>> {
+        if row >= self.line_count() {
+            return None;
+        }
+        let mut start = self.checkpoints[row / LINES_PER_CHECKPOINT];
+        let lines_to_skip = row % LINES_PER_CHECKPOINT;
+        if lines_to_skip > 0 {
+            let mut newlines_left = lines_to_skip;
+            let newline_ix = find_newline_where(text, start..self.indexed_len, |_| {
+                newlines_left -= 1;
+                newlines_left == 0
+            })?;
+            start = newline_ix + 1;
+        }
+        let end = find_newline(text, start..text.len()).unwrap_or(text.len());
+        Some(start..end)
+    }
+
+    /// Returns the byte ranges of the given lines, without their newlines.
+    pub(crate) fn line_ranges<T: TextSource + ?Sized>(
+        &self,
+        text: &T,
+        rows: Range<usize>,
+    ) -> Vec<Range<usize>> {
+        let rows = rows.start..rows.end.min(self.line_count());
+        let mut ranges = Vec::with_capacity(rows.len());
+        let Some(mut range) = self.line_range(text, rows.start) else {
+            return ranges;
+        };
+        for _ in rows.skip(1) {
+            let start = range.end + 1;
+            let end = find_newline(text, start..text.len()).unwrap_or(text.len());
+            ranges.push(std::mem::replace(&mut range, start..end));
+        }
+        ranges.push(range);
+        ranges
+    }
+
+    /// Returns the line containing the given offset.
+    pub(crate) fn row_for_offset<T: TextSource + ?Sized>(&self, text: &T, offset: usize) -> usize {
+        let offset = offset.min(self.indexed_len);
+        let checkpoint_ix = self
+            .checkpoints
+            .partition_point(|&checkpoint| checkpoint <= offset)
+            .saturating_sub(1);
+        let checkpoint = self.checkpoints[checkpoint_ix];
+        let mut newline_count = 0;
+        find_newline_where(text, checkpoint..offset, |_| {
+            newline_count += 1;
+            false
+        });
+        checkpoint_ix * LINES_PER_CHECKPOINT + newline_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_index() {
+        let text = (0..5000)
+            .map(|row| format!("line {row}\n"))
+            .collect::<String>();
+        let text = text.as_bytes();
+
+        let mut index = LineIndex::default();
+        assert!(!index.index(text, 1000));
+        assert!(index.line_count() < 5000);
+        while !index.index(text, 1000) {}
+        assert_eq!(index.line_count(), 5001);
+
+        for row in [0, 1, 1023, 1024, 1025, 4999] {
+            let range = index.line_range(text, row).unwrap();
+            assert_eq!(&text[range.clone()], format!("line {row}").as_bytes());
+            assert_eq!(index.row_for_offset(text, range.start), row);
+            assert_eq!(index.row_for_offset(text, range.end), row);
+        }
+        assert_eq!(index.line_range(text, 5000), Some(text.len()..text.len()));
+        assert_eq!(index.line_range(text, 5001), None);
+
+        let ranges = index.line_ranges(text, 1022..1026);
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|range| &text[range.clone()])
+                .collect::<Vec<_>>(),
+            [
+                b"line 1022".as_slice(),
+                b"line 1023",
+                b"line 1024",
+                b"line 1025"
+            ]
+        );
+        assert_eq!(
+            index.line_ranges(text, 4999..5005),
+            [
+                index.line_range(text, 4999).unwrap(),
+                text.len()..text.len()
+            ]
+        );
+        assert!(index.line_ranges(text, 5001..5005).is_empty());
+    }
+
+    #[test]
+    fn test_line_index_truncated() {
+        /// Text whose length was determined before the last half was removed.
+        struct TruncatedText<'a>(&'a [u8]);
+
+        impl TextSource for TruncatedText<'_> {
+            fn len(&self) -> usize {
+                self.0.len() * 2
+            }
+
+            fn read(&self, range: Range<usize>) -> Cow<'_, [u8]> {
+                TextSource::read(self.0, range)
+            }
+        }
+
+        let text = "line\n".repeat(READ_WINDOW_SIZE / 2);
+        let text = TruncatedText(text.as_bytes());
+        let mut index = LineIndex::default();
+        assert!(index.index(&text, usize::MAX));
+        assert_eq!(index.indexed_len(), text.0.len());
+        assert_eq!(index.line_count(), READ_WINDOW_SIZE / 2 + 1);
+        assert_eq!(index.line_range(&text, 1), Some(5..9));
+        assert_eq!(index.line_range(&text, READ_WINDOW_SIZE / 2 + 1), None);
+    }
+
+    #[test]
+    fn test_line_index_appends() {
+        let mut text = b"first\nsecond".to_vec();
+        let mut index = LineIndex::default();
+        assert!(index.index(text.as_slice(), usize::MAX));
+        assert_eq!(index.line_count(), 2);
+
+        text.extend_from_slice(b" line\nthird\n");
+        assert!(!index.index(text.as_slice(), 0));
+        assert!(index.index(text.as_slice(), usize::MAX));
+        assert_eq!(index.line_count(), 4);
+        let range = index.line_range(text.as_slice(), 1).unwrap();
+        assert_eq!(&text[range], b"second line");
+        let range = index.line_range(text.as_slice(), 2).unwrap();
+        assert_eq!(&text[range], b"third");
+    }
+}
@@ -1,7 +1,11 @@
 mod input_handler;
+mod transcript;
 
 pub use lsp_types::request::*;
 pub use lsp_types::*;
+pub use transcript::{
+    LspReplay, LspTranscript, TranscriptDirection, TranscriptEntry, TranscriptWriter,
+};
 
 use anyhow::{Context as _, Result, anyhow};
 use collections::{BTreeMap, HashMap};
@@ -1819,6 +1823,13 @@ pub struct FakeLanguageServer {
     pub binary: LanguageServerBinary,
     pub server: Arc<LanguageServer>,
     notifications_rx: channel::Receiver<(String, String)>,
+    replay: Arc<Mutex<Option<ReplayState>>>,
+}
+
+#[cfg(any(test, feature = "test-support"))]
+struct ReplayState {
+    replay: LspReplay,
+    outbound_tx: channel::Sender<String>,
 }
 
 #[cfg(any(test, feature = "test-support"))]
@@ -1834,6 +1845,7 @@ impl FakeLanguageServer {
         let (stdin_writer, stdin_reader) = async_pipe::pipe();
         let (stdout_writer, stdout_reader) = async_pipe::pipe();
         let (notifications_tx, notifications_rx) = channel::unbounded();
+        let replay = Arc::new(Mutex::new(None::<ReplayState>));
 
         let server_name = LanguageServerName(name.clone().into());
         let process_name = Arc::from(name.as_str());
@@ -1871,20 +1883,37 @@ impl FakeLanguageServer {
                     Self::root_path(),
                     Some(workspace_folders),
                     cx,
-                    move |msg| {
-                        notifications_tx
-                            .try_send((
-                                msg.method.to_string(),
-                                msg.params.as_ref().unwrap_or(&Value::Null).to_string(),
-                            ))
-                            .ok();
-                        true
+                    {
+                        let replay = replay.clone();
+                        move |msg| {
+                            notifications_tx
+                                .try_send((
+                                    msg.method.to_string(),
+                                    msg.params.as_ref().unwrap_or(&Value::Null).to_string(),
+                                ))
+                                .ok();
+                            if let Some(state) = replay.lock().as_mut() {
+                                let mut message =
+                                    json!({ "jsonrpc": JSON_RPC_VERSION, "method": msg.method });
+                                if let Some(id) = &msg.id {
+                                    message["id"] = json!(id);
+                                }
+                                if let Some(params) = &msg.params {
+                                    message["params"] = params.clone();
+                                }
+                                for reply in state.replay.handle_client_message(&message) {
+                                    state.outbound_tx.try_send(reply.to_string()).ok();
+                                }
+                            }
+                            true
+                        }
                     },
                 );
                 server.process_name = name.as_str().into();
                 server
             }),
             notifications_rx,
+            replay,
         };
         fake.set_request_handler::<request::Initialize, _, _>({
             let capabilities = capabilities;
@@ -2028,6 +2057,19 @@ impl FakeLanguageServer {
         handled_rx
     }
 
+    /// Answers the messages that have no handler from a recorded transcript,
+    /// see [`LspReplay`]. When the transcript contains the server's response
+    /// to `initialize`, it replaces the default one.
+    pub fn replay(&self, transcript: LspTranscript) {
+        if transcript.has_response_to(request::Initialize::METHOD) {
+            self.server.remove_request_handler::<request::Initialize>();
+        }
+        *self.replay.lock() = Some(ReplayState {
+            replay: LspReplay::new(transcript),
+            outbound_tx: self.server.outbound_tx.clone(),
+        });
+    }
+
     /// Removes any existing handler for specified notification type.
     pub fn remove_request_handler<T>(&mut self)
     where
@@ -2170,6 +2212,95 @@ mod tests {
         fake.receive_notification::<notification::Exit>().await;
     }
 
+    #[gpui::test]
+    async fn test_fake_replay(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            release_channel::init(semver::Version::new(0, 0, 0), cx);
+        });
+        let (server, fake) = FakeLanguageServer::new(
+            LanguageServerId(0),
+            LanguageServerBinary {
+                path: "path/to/language-server".into(),
+                arguments: vec![],
+                env: None,
+            },
+            "the-lsp".to_string(),
+            Default::default(),
+            &mut cx.to_async(),
+        );
+        let transcript = [
+            json!({"direction": "send", "message": {"jsonrpc": "2.0", "id": 41, "method": "initialize", "params": {}}}),
+            json!({"direction": "receive", "message": {"jsonrpc": "2.0", "id": 41, "result": {"capabilities": {"completionProvider": {}}}}}),
+            json!({"direction": "send", "message": {"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {}}}),
+            json!({"direction": "receive", "message": {"jsonrpc": "2.0", "method": "textDocument/publishDiagnostics", "params": {"uri": "file:///a/b.rs", "diagnostics": []}}}),
+            json!({"direction": "send", "message": {"jsonrpc": "2.0", "id": 42, "method": "textDocument/completion", "params": {}}}),
+            json!({"direction": "receive", "message": {"jsonrpc": "2.0", "id": 42, "result": [{"label": "recorded"}]}}),
+        ]
+        .map(|entry| entry.to_string())
+        .join("\n");
+        fake.replay(LspTranscript::parse(&transcript).unwrap());
+
+        let (diagnostics_tx, diagnostics_rx) = channel::unbounded();
+        server
+            .on_notification::<notification::PublishDiagnostics, _>(move |params, _| {
+                diagnostics_tx.try_send(params).unwrap()
+            })
+            .detach();
+
+        let server = cx
+            .update(|cx| {
+                let params = server.default_initialize_params(false, false, cx);
+                let configuration = DidChangeConfigurationParams {
+                    settings: Default::default(),
+                };
+                server.initialize(
+                    params,
+                    configuration.into(),
+                    DEFAULT_LSP_REQUEST_TIMEOUT,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert!(server.capabilities().completion_provider.is_some());
+
+        server
+            .notify::<notification::DidOpenTextDocument>(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(
+                    Uri::from_str("file:///a/b.rs").unwrap(),
+                    "rust".to_string(),
+                    0,
+                    "".to_string(),
+                ),
+            })
+            .unwrap();
+        assert_eq!(
+            diagnostics_rx.recv().await.unwrap().uri.as_str(),
+            "file:///a/b.rs"
+        );
+
+        let completions = server
+            .request::<request::Completion>(
+                CompletionParams {
+                    text_document_position: TextDocumentPositionParams::new(
+                        TextDocumentIdentifier::new(Uri::from_str("file:///a/b.rs").unwrap()),
+                        Position::new(0, 0),
+                    ),
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                    context: None,
+                },
+                DEFAULT_LSP_REQUEST_TIMEOUT,
+            )
+            .await
+            .into_response()
+            .unwrap();
+        let Some(CompletionResponse::Array(items)) = completions else {
+            panic!("unexpected completions: {completions:?}");
+        };
+        assert_eq!(items[0].label, "recorded");
+    }
+
     #[gpui::test]
     fn test_deserialize_string_digit_id() {
         let json = r#"{"jsonrpc":"2.0","id":"2","method":"workspace/configuration","params":{"items":[{"scopeUri":"file:///Users/mph/Devel/personal/hello-scala/","section":"metals"}]}}"#;
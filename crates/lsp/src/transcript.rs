@@ -0,0 +1,466 @@
+//! Recording of the JSON-RPC traffic between Zed and a language server, and
+//! replay of those recordings.
+//!
+//! Transcripts are stored as JSON lines, with one message per line:
+//!
+//! ```json
+//! {"direction":"send","message":{"jsonrpc":"2.0","id":3,"method":"textDocument/completion","params":{...}}}
+//! {"direction":"receive","message":{"jsonrpc":"2.0","id":3,"result":[...]}}
+//! ```
+//!
+//! `send` messages were sent by Zed to the server, and `receive` messages were
+//! sent by the server to Zed.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write as _},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result};
+use futures::{StreamExt as _, channel::mpsc};
+use gpui::App;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::{IoKind, JSON_RPC_VERSION};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptDirection {
+    /// From Zed to the language server.
+    Send,
+    /// From the language server to Zed.
+    Receive,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub direction: TranscriptDirection,
+    pub message: Value,
+}
+
+impl TranscriptEntry {
+    fn method(&self) -> Option<&str> {
+        self.message.get("method").and_then(Value::as_str)
+    }
+
+    fn id(&self) -> Option<&Value> {
+        self.message.get("id")
+    }
+
+    fn is_response_to(&self, id: &Value) -> bool {
+        self.direction == TranscriptDirection::Receive
+            && self.method().is_none()
+            && self.id() == Some(id)
+    }
+}
+
+/// The messages exchanged with a language server, in the order they were sent.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LspTranscript {
+    pub entries: Vec<TranscriptEntry>,
+}
+
+impl LspTranscript {
+    pub fn parse(contents: &str) -> Result<Self> {
+        let entries = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(ix, line)| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("invalid transcript entry on line {}", ix + 1))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { entries })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read transcript {path:?}"))?;
+        Self::parse(&contents)
+    }
+
+    pub fn to_json_lines(&self) -> Result<String> {
+        let mut output = String::new();
+        for entry in &self.entries {
+            output.push_str(&serde_json::to_string(entry)?);
+            output.push('\n');
+        }
+        Ok(output)
+    }
+
+    /// Replaces `from` with `to` in every string and object key of every
+    /// message, e.g. to move the recorded project's root to the one used by a
+    /// test.
+    pub fn replace_root(&mut self, from: &str, to: &str) {
+        fn replace(value: &mut Value, from: &str, to: &str) {
+            match value {
+                Value::String(string) => {
+                    if string.contains(from) {
+                        *string = string.replace(from, to);
+                    }
+                }
+                Value::Array(items) => {
+                    for item in items {
+                        replace(item, from, to);
+                    }
+                }
+                Value::Object(map) => {
+                    *map = std::mem::take(map)
+                        .into_iter()
+                        .map(|(key, mut value)| {
+                            replace(&mut value, from, to);
+                            (key.replace(from, to), value)
+                        })
+                        .collect::<Map<_, _>>();
+                }
+                Value::Null | Value::Bool(_) | Value::Number(_) => {}
+            }
+        }
+
+        for entry in &mut self.entries {
+            replace(&mut entry.message, from, to);
+        }
+    }
+
+    /// Whether the server answered a request with the given method.
+    pub fn has_response_to(&self, method: &str) -> bool {
+        self.entries.iter().any(|request| {
+            request.direction == TranscriptDirection::Send
+                && request.method() == Some(method)
+                && request.id().is_some_and(|id| {
+                    self.entries
+                        .iter()
+                        .any(|response| response.is_response_to(id))
+                })
+        })
+    }
+}
+
+/// Appends the messages exchanged with a language server to a transcript file.
+///
+/// Feed it the messages observed through [`crate::LanguageServer::on_io`].
+/// Messages are parsed and written on a background thread, which finishes
+/// writing the messages already recorded once the writer is dropped.
+pub struct TranscriptWriter {
+    entries_tx: mpsc::UnboundedSender<(TranscriptDirection, String)>,
+}
+
+impl TranscriptWriter {
+    pub fn create(path: &Path, cx: &App) -> Result<Self> {
+        let file =
+            File::create(path).with_context(|| format!("failed to create transcript {path:?}"))?;
+        let (entries_tx, entries_rx) = mpsc::unbounded();
+        cx.background_spawn(write_transcript(
+            BufWriter::new(file),
+            path.to_path_buf(),
+            entries_rx,
+        ))
+        .detach();
+        Ok(Self { entries_tx })
+    }
+
+    /// Records a message. The server's stderr output is not recorded.
+    pub fn record(&self, kind: IoKind, message: &str) {
+        let direction = match kind {
+            IoKind::StdIn => TranscriptDirection::Send,
+            IoKind::StdOut => TranscriptDirection::Receive,
+            IoKind::StdErr => return,
+        };
+        self.entries_tx
+            .unbounded_send((direction, message.to_string()))
+            .ok();
+    }
+}
+
+/// Writes the recorded messages as they arrive, flushing after each batch so
+/// that the transcript is complete even if Zed exits unexpectedly.
+async fn write_transcript(
+    mut writer: BufWriter<File>,
+    path: PathBuf,
+    mut entries_rx: mpsc::UnboundedReceiver<(TranscriptDirection, String)>,
+) {
+    while let Some(entry) = entries_rx.next().await {
+        let mut entry = Some(entry);
+        while let Some((direction, message)) = entry {
+            if let Err(error) = write_entry(&mut writer, direction, &message) {
+                log::error!("failed to record message to {path:?}: {error:#}");
+            }
+            entry = entries_rx.try_next().ok().flatten();
+        }
+        if let Err(error) = writer.flush() {
+            log::error!("failed to flush transcript {path:?}: {error}");
+        }
+    }
+}
+
+fn write_entry(
+    writer: &mut BufWriter<File>,
+    direction: TranscriptDirection,
+    message: &str,
+) -> Result<()> {
+    let entry = TranscriptEntry {
+        direction,
+        message: serde_json::from_str(message).context("invalid JSON-RPC message")?,
+    };
+    serde_json::to_writer(&mut *writer, &entry)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Answers the messages Zed sends to a language server from a transcript.
+///
+/// Each message from Zed is matched with the first message in the transcript
+/// that has the same method and hasn't been replayed yet, preferring one with
+/// identical params. It is answered with the recorded response, followed by
+/// the notifications and requests that the server sent before the next
+/// recorded message from Zed.
+pub struct LspReplay {
+    entries: Vec<TranscriptEntry>,
+    replayed: Vec<bool>,
+}
+
+impl LspReplay {
+    pub fn new(transcript: LspTranscript) -> Self {
+        let replayed = vec![false; transcript.entries.len()];
+        Self {
+            entries: transcript.entries,
+            replayed,
+        }
+    }
+
+    /// Returns the messages the server should send in reply to `message`.
+    pub fn handle_client_message(&mut self, message: &Value) -> Vec<Value> {
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // Responses to the server's requests don't need to be answered.
+            return Vec::new();
+        };
+        let live_id = message.get("id");
+        let Some(index) = self.find_client_message(method, message.get("params")) else {
+            log::warn!("no message in the LSP transcript matches {method}");
+            return live_id.map(null_response).into_iter().collect();
+        };
+        self.replayed[index] = true;
+
+        let recorded_id = self.entries[index].id().cloned();
+        let mut responded = live_id.is_none();
+        let mut output = Vec::new();
+        for ix in 0..self.entries.len() {
+            if self.replayed[ix] {
+                continue;
+            }
+            let entry = &self.entries[ix];
+            match entry.direction {
+                TranscriptDirection::Send => {
+                    if ix > index && entry.method().is_some() {
+                        break;
+                    }
+                }
+                TranscriptDirection::Receive => {
+                    if entry.method().is_some() {
+                        output.push(entry.message.clone());
+                        self.replayed[ix] = true;
+                    } else if !responded
+                        && recorded_id
+                            .as_ref()
+                            .is_some_and(|id| entry.is_response_to(id))
+                    {
+                        output.push(with_id(entry.message.clone(), live_id));
+                        self.replayed[ix] = true;
+                        responded = true;
+                    }
+                }
+            }
+        }
+
+        if !responded {
+            // The server may have answered after Zed sent its next message.
+            let response_ix = recorded_id.as_ref().and_then(|id| {
+                (0..self.entries.len())
+                    .find(|&ix| !self.replayed[ix] && self.entries[ix].is_response_to(id))
+            });
+            match response_ix {
+                Some(ix) => {
+                    self.replayed[ix] = true;
+                    output.insert(0, with_id(self.entries[ix].message.clone(), live_id));
+                }
+                None => {
+                    log::warn!("no response to {method} in the LSP transcript");
+                    output.extend(live_id.map(null_response));
+                }
+            }
+        }
+
+        output
+    }
+
+    /// The number of messages from the server that haven't been replayed yet.
+    pub fn pending_server_messages(&self) -> usize {
+        self.entries
+            .iter()
+            .zip(&self.replayed)
+            .filter(|(entry, replayed)| {
+                !**replayed && entry.direction == TranscriptDirection::Receive
+            })
+            .count()
+    }
+
+    fn find_client_message(&self, method: &str, params: Option<&Value>) -> Option<usize> {
+        let candidates = || {
+            self.entries.iter().enumerate().filter(|(ix, entry)| {
+                !self.replayed[*ix]
+                    && entry.direction == TranscriptDirection::Send
+                    && entry.method() == Some(method)
+            })
+        };
+        candidates()
+            .find(|(_, entry)| entry.message.get("params") == params)
+            .or_else(|| candidates().next())
+            .map(|(ix, _)| ix)
+    }
+}
+
+fn with_id(mut message: Value, id: Option<&Value>) -> Value {
+    if let (Value::Object(map), Some(id)) = (&mut message, id) {
+        map.insert("id".to_string(), id.clone());
+    }
+    message
+}
+
+fn null_response(id: &Value) -> Value {
+    json!({ "jsonrpc": JSON_RPC_VERSION, "id": id, "result": null })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send(message: Value) -> TranscriptEntry {
+        TranscriptEntry {
+            direction: TranscriptDirection::Send,
+            message,
+        }
+    }
+
+    fn receive(message: Value) -> TranscriptEntry {
+        TranscriptEntry {
+            direction: TranscriptDirection::Receive,
+            message,
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let transcript = LspTranscript {
+            entries: vec![
+                send(json!({"id": 0, "method": "initialize", "params": {}})),
+                receive(json!({"id": 0, "result": {"capabilities": {}}})),
+                send(json!({"method": "textDocument/didOpen", "params": {"uri": "file:///a.rs"}})),
+                receive(
+                    json!({"method": "textDocument/publishDiagnostics", "params": {"uri": "file:///a.rs"}}),
+                ),
+                receive(json!({"id": "config", "method": "workspace/configuration", "params": {}})),
+                send(json!({"id": "config", "result": [null]})),
+                send(json!({"id": 1, "method": "textDocument/completion", "params": {"line": 1}})),
+                send(json!({"id": 2, "method": "textDocument/completion", "params": {"line": 2}})),
+                receive(json!({"id": 2, "result": ["second"]})),
+                receive(json!({"id": 1, "result": ["first"]})),
+                receive(json!({"method": "$/progress", "params": {}})),
+            ],
+        };
+        let mut replay = LspReplay::new(transcript);
+
+        assert_eq!(
+            replay.handle_client_message(&json!({"id": 7, "method": "initialize", "params": {}})),
+            [json!({"id": 7, "result": {"capabilities": {}}})]
+        );
+        assert_eq!(
+            replay.handle_client_message(
+                &json!({"method": "textDocument/didOpen", "params": {"uri": "file:///a.rs"}})
+            ),
+            [
+                json!({"method": "textDocument/publishDiagnostics", "params": {"uri": "file:///a.rs"}}),
+                json!({"id": "config", "method": "workspace/configuration", "params": {}}),
+            ]
+        );
+        assert!(
+            replay
+                .handle_client_message(&json!({"id": "config", "result": [null]}))
+                .is_empty()
+        );
+
+        // Requests are matched by params, even when sent in a different order.
+        assert_eq!(
+            replay.handle_client_message(
+                &json!({"id": 9, "method": "textDocument/completion", "params": {"line": 2}})
+            ),
+            [
+                json!({"id": 9, "result": ["second"]}),
+                json!({"method": "$/progress", "params": {}}),
+            ]
+        );
+        assert_eq!(
+            replay.handle_client_message(
+                &json!({"id": 8, "method": "textDocument/completion", "params": {"line": 5}})
+            ),
+            [json!({"id": 8, "result": ["first"]})]
+        );
+        assert_eq!(replay.pending_server_messages(), 0);
+
+        // Messages that weren't recorded are answered with an empty result.
+        assert_eq!(
+            replay.handle_client_message(&json!({"id": 10, "method": "textDocument/hover"})),
+            [json!({"jsonrpc": "2.0", "id": 10, "result": null})]
+        );
+        assert!(
+            replay
+                .handle_client_message(&json!({"method": "textDocument/didClose"}))
+                .is_empty()
+        );
+    }
+
+    #[gpui::test]
+    fn test_transcript_round_trip(cx: &mut gpui::TestAppContext) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcript.jsonl");
+        let writer = cx.update(|cx| TranscriptWriter::create(&path, cx)).unwrap();
+        writer.record(
+            IoKind::StdIn,
+            r#"{"jsonrpc":"2.0","id":1,"method":"textDocument/hover","params":{"textDocument":{"uri":"file:///Users/me/project/src/main.rs"}}}"#,
+        );
+        writer.record(IoKind::StdErr, "server log line");
+        // Invalid messages are logged and left out of the transcript.
+        writer.record(IoKind::StdOut, "not json");
+        writer.record(
+            IoKind::StdOut,
+            r#"{"jsonrpc":"2.0","id":1,"result":{"changes":{"file:///Users/me/project/src/main.rs":[]}}}"#,
+        );
+        // Messages recorded before the writer is dropped are still written.
+        drop(writer);
+        cx.run_until_parked();
+
+        let mut transcript = LspTranscript::load(&path).unwrap();
+        assert_eq!(transcript.entries.len(), 2);
+        assert_eq!(transcript.entries[0].direction, TranscriptDirection::Send);
+        assert!(transcript.has_response_to("textDocument/hover"));
+        assert!(!transcript.has_response_to("initialize"));
+
+        transcript.replace_root("file:///Users/me/project", "file:///root");
+        assert_eq!(
+            transcript.entries[0].message["params"]["textDocument"]["uri"],
+            "file:///root/src/main.rs"
+        );
+        assert!(
+            transcript.entries[1].message["result"]["changes"]
+                .get("file:///root/src/main.rs")
+                .is_some()
+        );
+        assert_eq!(
+            LspTranscript::parse(&transcript.to_json_lines().unwrap()).unwrap(),
+            transcript
+        );
+        assert!(LspTranscript::parse("{}\n").is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use std::{path::PathBuf, sync::Arc};
+
+use editor::{Editor, SelectionEffects, scroll::Autoscroll};
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render, SharedString,
+    Task, WeakEntity, Window,
+};
+use picker::{Picker, PickerDelegate};
+use text::Point;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use workspace::{ModalView, OpenOptions, Workspace};
+
+use crate::{NoteIndex, ShowBacklinks, note_index::note_path};
+
+/// Lists the notes linking to the active note.
+pub struct BacklinksPicker {
+    picker: Entity<Picker<BacklinksPickerDelegate>>,
+}
+
+impl BacklinksPicker {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        note_index: &Entity<NoteIndex>,
+        _: &ShowBacklinks,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        if !note_index.read(cx).is_enabled() {
+            return;
+        }
+        let Some(path) = workspace
+            .active_item_as::<Editor>(cx)
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+            .and_then(|buffer| note_path(&buffer, cx))
+        else {
+            return;
+        };
+        let entries = backlink_entries(workspace, note_index, &path, cx);
+        let note_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let weak_workspace = workspace.weak_handle();
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            let delegate = BacklinksPickerDelegate::new(
+                cx.entity().downgrade(),
+                weak_workspace,
+                note_name,
+                entries,
+            );
+            let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+            Self { picker }
+        });
+    }
+}
+
+impl ModalView for BacklinksPicker {}
+
+impl EventEmitter<DismissEvent> for BacklinksPicker {}
+
+impl Focusable for BacklinksPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for BacklinksPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("BacklinksPicker")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+struct BacklinkEntry {
+    path: PathBuf,
+    position: Point,
+    /// The line containing the link.
+    title: SharedString,
+    /// The path and line number of the link.
+    location: SharedString,
+}
+
+fn backlink_entries(
+    workspace: &Workspace,
+    note_index: &Entity<NoteIndex>,
+    path: &std::path::Path,
+    cx: &App,
+) -> Vec<BacklinkEntry> {
+    let project = workspace.project().read(cx);
+    note_index
+        .read(cx)
+        .backlinks(path)
+        .into_iter()
+        .map(|backlink| {
+            let display_path = project
+                .project_path_for_absolute_path(&backlink.path, cx)
+                .and_then(|project_path| {
+                    project.short_full_path_for_project_path(&project_path, cx)
+                })
+                .unwrap_or_else(|| backlink.path.display().to_string());
+            BacklinkEntry {
+                title: backlink.line.into(),
+                location: format!("{display_path}:{}", backlink.position.row + 1).into(),
+                path: backlink.path,
+                position: backlink.position,
+            }
+        })
+        .collect()
+}
+
+pub struct BacklinksPickerDelegate {
+    backlinks_picker: WeakEntity<BacklinksPicker>,
+    workspace: WeakEntity<Workspace>,
+    note_name: String,
+    entries: Vec<BacklinkEntry>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl BacklinksPickerDelegate {
+    fn new(
+        backlinks_picker: WeakEntity<BacklinksPicker>,
+        workspace: WeakEntity<Workspace>,
+        note_name: String,
+        entries: Vec<BacklinkEntry>,
+    ) -> Self {
+        let candidates = entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| {
+                StringMatchCandidate::new(id, &format!("{} {}", entry.title, entry.location))
+            })
+            .collect();
+        Self {
+            backlinks_picker,
+            workspace,
+            note_name,
+            entries,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for BacklinksPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _: &mut App) -> Arc<str> {
+        format!("Search links to {}...", self.note_name).into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some(if self.entries.is_empty() {
+            "No notes link to this note".into()
+        } else {
+            "No matching links".into()
+        })
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.entries.get(mat.candidate_id))
+        else {
+            return;
+        };
+        let path = entry.path.clone();
+        let position = entry.position;
+
+        let Some(open) = self
+            .workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(path, OpenOptions::default(), window, cx)
+            })
+            .log_err()
+        else {
+            return;
+        };
+        cx.spawn_in(window, async move |_, cx| {
+            let item = open.await?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                editor.update_in(cx, |editor, window, cx| {
+                    editor.change_selections(
+                        SelectionEffects::scroll(Autoscroll::center()),
+                        window,
+                        cx,
+                        |s| s.select_ranges([position..position]),
+                    );
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.backlinks_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let entry = self.entries.get(mat.candidate_id)?;
+        let location_start = entry.title.len() + 1;
+        let title_positions = mat
+            .positions
+            .iter()
+            .copied()
+            .filter(|position| *position < entry.title.len())
+            .collect();
+        let location_positions = mat
+            .positions
+            .iter()
+            .filter_map(|position| position.checked_sub(location_start))
+            .collect();
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(
+                    Icon::new(IconName::ArrowUpRight)
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    h_flex()
+                        .gap_x_2()
+                        .child(HighlightedLabel::new(entry.title.clone(), title_positions))
+                        .child(
+                            HighlightedLabel::new(entry.location.clone(), location_positions)
+                                .color(Color::Muted)
+                                .size(LabelSize::Small),
+                        ),
+                ),
+        )
+    }
+}
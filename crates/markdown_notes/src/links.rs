@@ -0,0 +1,400 @@
+//! Resolving links between notes, and updating them when notes move.
+
+use std::{
+    cmp::Reverse,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+};
+
+use markdown_preview::markdown_parser::{NoteHeading, NoteLink, NoteLinkTarget, heading_anchor};
+
+/// The extensions of the files that are indexed as notes.
+pub const NOTE_EXTENSIONS: &[&str] = &["md", "markdown"];
+
+pub fn is_note_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            NOTE_EXTENSIONS
+                .iter()
+                .any(|note_extension| extension.eq_ignore_ascii_case(note_extension))
+        })
+}
+
+/// Resolves a `[[wiki-link]]` to one of `notes`.
+///
+/// The link names a note by its file name, with or without its extension, optionally preceded
+/// by as many of its parent directories as are needed to tell it apart, such as
+/// `[[journal/2024-01-01]]`. Notes next to the linking note win, then the ones closest to the
+/// root.
+pub fn resolve_wiki_link<'a>(
+    name: &str,
+    from: &Path,
+    notes: impl IntoIterator<Item = &'a Path>,
+) -> Option<PathBuf> {
+    let components = name
+        .trim()
+        .split('/')
+        .filter(|component| !component.is_empty())
+        .collect::<Vec<_>>();
+    let (file_name, directories) = components.split_last()?;
+    let has_extension = is_note_path(Path::new(file_name));
+
+    notes
+        .into_iter()
+        .filter(|path| {
+            let matches_file_name = if has_extension {
+                path.file_name()
+            } else {
+                path.file_stem()
+            }
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.to_lowercase() == file_name.to_lowercase());
+            matches_file_name
+                && directories
+                    .iter()
+                    .rev()
+                    .zip(path.parent().into_iter().flat_map(Path::iter).rev())
+                    .filter(|(directory, component)| {
+                        component.to_str().is_some_and(|component| {
+                            component.to_lowercase() == directory.to_lowercase()
+                        })
+                    })
+                    .count()
+                    == directories.len()
+        })
+        .min_by_key(|path| {
+            (
+                Reverse(path.parent() == from.parent()),
+                path.components().count(),
+                *path,
+            )
+        })
+        .map(Path::to_path_buf)
+}
+
+/// Resolves a link relative to the note at `from`, such as `../guides/setup.md`.
+pub fn resolve_relative_link(from: &Path, path: &str) -> PathBuf {
+    let directory = from.parent().unwrap_or(from);
+    normalize(&directory.join(path))
+}
+
+/// Removes the `.` and `..` components of a path without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// The path to write in a link from a note in `from_directory` to `to`, using `/` separators.
+pub fn relative_link_path(from_directory: &Path, to: &Path) -> String {
+    let from = from_directory.components().collect::<Vec<_>>();
+    let to_components = to.components().collect::<Vec<_>>();
+    let common = from
+        .iter()
+        .zip(&to_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+    std::iter::repeat_n("..".to_string(), from.len() - common)
+        .chain(
+            to_components[common..]
+                .iter()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned()),
+        )
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Finds the heading a link's anchor points to, which may be written either as the heading's
+/// text, as in `[[Note#Getting Started]]`, or as its anchor, as in `[text](note.md#getting-started)`.
+pub fn find_heading<'a>(headings: &'a [NoteHeading], anchor: &str) -> Option<&'a NoteHeading> {
+    let normalized = heading_anchor(anchor);
+    headings
+        .iter()
+        .find(|heading| heading.anchor == anchor || heading.anchor == normalized)
+}
+
+/// The part of a link that is being typed, as found by [`link_query`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkQuery {
+    /// Whether the link is a `[[wiki-link]]` rather than a relative link.
+    pub wiki: bool,
+    /// The note whose headings are being typed, or `None` when the note itself is being typed. An
+    /// empty string refers to the note containing the link.
+    pub heading_of: Option<String>,
+    /// The offset in the line at which the typed text starts.
+    pub start: usize,
+}
+
+/// Finds the link that is being typed at the end of `line`, such as `[[Daily` or `[text](notes.md#se`.
+pub fn link_query(line: &str) -> Option<LinkQuery> {
+    let wiki_start = line.rfind("[[").map(|ix| ix + "[[".len());
+    let relative_start = line.rfind("](").map(|ix| ix + "](".len());
+    let (wiki, start) = match (wiki_start, relative_start) {
+        (Some(wiki_start), Some(relative_start)) if relative_start > wiki_start => {
+            (false, relative_start)
+        }
+        (Some(wiki_start), _) => (true, wiki_start),
+        (None, Some(relative_start)) => (false, relative_start),
+        (None, None) => return None,
+    };
+    let typed = &line[start..];
+    let terminators: &[char] = if wiki { &['|', ']'] } else { &[')', ' ', '"'] };
+    if typed.contains(terminators) {
+        return None;
+    }
+    Some(match typed.find('#') {
+        Some(hash) => LinkQuery {
+            wiki,
+            heading_of: Some(typed[..hash].to_string()),
+            start: start + hash + 1,
+        },
+        None => LinkQuery {
+            wiki,
+            heading_of: None,
+            start,
+        },
+    })
+}
+
+/// A file or directory that moved from `old_path` to `new_path`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rename {
+    pub old_path: PathBuf,
+    pub new_path: PathBuf,
+}
+
+impl Rename {
+    /// Where `path` is after the rename, if the rename moved it.
+    pub fn new_location(&self, path: &Path) -> Option<PathBuf> {
+        let suffix = path.strip_prefix(&self.old_path).ok()?;
+        Some(if suffix.as_os_str().is_empty() {
+            self.new_path.clone()
+        } else {
+            self.new_path.join(suffix)
+        })
+    }
+
+    /// Where `path` was before the rename, if the rename moved it.
+    pub fn old_location(&self, path: &Path) -> Option<PathBuf> {
+        let suffix = path.strip_prefix(&self.new_path).ok()?;
+        Some(if suffix.as_os_str().is_empty() {
+            self.old_path.clone()
+        } else {
+            self.old_path.join(suffix)
+        })
+    }
+}
+
+/// Returns the edits that keep the links of the note at `note_path` pointing to the same notes
+/// and files after `rename`, which may have moved the note itself.
+///
+/// `notes_before_rename` holds the paths of all notes as they were before the rename, against
+/// which the note's wiki-links are resolved.
+pub fn rewrite_links(
+    note_path: &Path,
+    source: &str,
+    links: &[NoteLink],
+    rename: &Rename,
+    notes_before_rename: &[PathBuf],
+) -> Vec<(Range<usize>, String)> {
+    let old_note_path = rename
+        .old_location(note_path)
+        .unwrap_or_else(|| note_path.to_path_buf());
+    let note_directory = note_path.parent().unwrap_or(note_path);
+
+    let mut edits = Vec::new();
+    for link in links {
+        let written = &source[link.target_range.clone()];
+        let replacement = match &link.target {
+            NoteLinkTarget::RelativePath(path) if !path.is_empty() => {
+                let old_target = resolve_relative_link(&old_note_path, path);
+                let new_target = rename.new_location(&old_target).unwrap_or(old_target);
+                if resolve_relative_link(note_path, path) == new_target {
+                    continue;
+                }
+                let mut replacement = relative_link_path(note_directory, &new_target);
+                if written.starts_with("./") && !replacement.starts_with("..") {
+                    replacement.insert_str(0, "./");
+                }
+                // Link destinations can only contain spaces when wrapped in angle brackets.
+                if !written.contains(' ') {
+                    replacement = replacement.replace(' ', "%20");
+                }
+                replacement
+            }
+            NoteLinkTarget::WikiLink(name) if !name.trim().is_empty() => {
+                let Some(new_target) = resolve_wiki_link(
+                    name,
+                    &old_note_path,
+                    notes_before_rename.iter().map(PathBuf::as_path),
+                )
+                .and_then(|old_target| rename.new_location(&old_target)) else {
+                    continue;
+                };
+                wiki_link_name(name, &new_target)
+            }
+            NoteLinkTarget::RelativePath(_) | NoteLinkTarget::WikiLink(_) => continue,
+        };
+        if replacement != written {
+            edits.push((link.target_range.clone(), replacement));
+        }
+    }
+    edits
+}
+
+/// The name a wiki-link written as `name` should use for the note at `path`, keeping as many
+/// parent directories and the extension only if `name` had them.
+fn wiki_link_name(name: &str, path: &Path) -> String {
+    let name = name.trim();
+    let component_count = name.split('/').filter(|c| !c.is_empty()).count();
+    let path = if is_note_path(Path::new(name)) {
+        path.to_path_buf()
+    } else {
+        path.with_extension("")
+    };
+    let components = path
+        .iter()
+        .map(|component| component.to_string_lossy())
+        .collect::<Vec<_>>();
+    components[components.len().saturating_sub(component_count)..].join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use markdown_preview::markdown_parser::parse_note_links;
+    use pretty_assertions::assert_eq;
+    use util::path;
+
+    #[test]
+    fn test_resolve_links() {
+        let notes = [
+            path!("/notes/index.md"),
+            path!("/notes/journal/2024-01-01.md"),
+            path!("/notes/journal/Ideas.md"),
+            path!("/notes/projects/Ideas.md"),
+            path!("/notes/projects/zed/Ideas.md"),
+        ]
+        .map(PathBuf::from);
+        let resolve = |name: &str, from: &str| {
+            resolve_wiki_link(name, Path::new(from), notes.iter().map(PathBuf::as_path))
+        };
+
+        let from_index = path!("/notes/index.md");
+        assert_eq!(resolve("2024-01-01", from_index), Some(notes[1].clone()));
+        assert_eq!(resolve("ideas.md", from_index), Some(notes[2].clone()));
+        assert_eq!(resolve("zed/Ideas", from_index), Some(notes[4].clone()));
+        assert_eq!(
+            resolve("Ideas", path!("/notes/projects/zed/todo.md")),
+            Some(notes[4].clone())
+        );
+        assert_eq!(resolve("Missing", from_index), None);
+
+        assert_eq!(
+            resolve_relative_link(
+                Path::new(path!("/notes/journal/a.md")),
+                "../projects/./b.md"
+            ),
+            PathBuf::from(path!("/notes/projects/b.md"))
+        );
+        assert_eq!(
+            relative_link_path(
+                Path::new(path!("/notes/journal")),
+                Path::new(path!("/notes/projects/b.md"))
+            ),
+            "../projects/b.md"
+        );
+    }
+
+    #[test]
+    fn test_link_query() {
+        assert_eq!(
+            link_query("See [[Dai"),
+            Some(LinkQuery {
+                wiki: true,
+                heading_of: None,
+                start: 6,
+            })
+        );
+        assert_eq!(
+            link_query("See [[Daily#Mon"),
+            Some(LinkQuery {
+                wiki: true,
+                heading_of: Some("Daily".into()),
+                start: 12,
+            })
+        );
+        assert_eq!(
+            link_query("[[a]] and [b](notes.md#se"),
+            Some(LinkQuery {
+                wiki: false,
+                heading_of: Some("notes.md".into()),
+                start: 23,
+            })
+        );
+        assert_eq!(link_query("[[a]] and more"), None);
+        assert_eq!(link_query("[b](notes.md) and more"), None);
+    }
+
+    #[test]
+    fn test_rewrite_links_after_rename() {
+        let notes_before_rename = [
+            path!("/notes/index.md"),
+            path!("/notes/old name.md"),
+            path!("/notes/journal/today.md"),
+        ]
+        .map(PathBuf::from);
+        let rename = Rename {
+            old_path: PathBuf::from(path!("/notes/old name.md")),
+            new_path: PathBuf::from(path!("/notes/archive/new name.md")),
+        };
+        let rewrite = |note_path: &str, source: &str| {
+            let links = parse_note_links(source);
+            let mut source = source.to_string();
+            for (range, replacement) in rewrite_links(
+                Path::new(note_path),
+                &source,
+                &links,
+                &rename,
+                &notes_before_rename,
+            )
+            .into_iter()
+            .rev()
+            {
+                source.replace_range(range, &replacement);
+            }
+            source
+        };
+
+        assert_eq!(
+            rewrite(
+                path!("/notes/index.md"),
+                "[[old name#Intro|see]], [x](./old%20name.md#intro), [y](journal/today.md)"
+            ),
+            "[[new name#Intro|see]], [x](./archive/new%20name.md#intro), [y](journal/today.md)"
+        );
+        assert_eq!(
+            rewrite(path!("/notes/journal/today.md"), "[x](../old%20name.md)"),
+            "[x](../archive/new%20name.md)"
+        );
+        // The moved note's own relative links are updated for its new directory.
+        assert_eq!(
+            rewrite(
+                path!("/notes/archive/new name.md"),
+                "[[today]], [x](journal/today.md), [y](#intro)"
+            ),
+            "[[today]], [x](../journal/today.md), [y](#intro)"
+        );
+    }
+}
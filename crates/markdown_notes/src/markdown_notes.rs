@@ -0,0 +1,127 @@
+//! Support for keeping notes as a folder of linked Markdown files.
+//!
+//! Notes link to each other with `[[wiki-links]]` or relative links, optionally pointing at one
+//! of the target's headings with a `#anchor`. Links are completed and followed in the editor,
+//! links that lead nowhere are reported as diagnostics through the project's [`LspStore`], and
+//! links can be rewritten when a note is renamed or moved from the project panel. All of this is
+//! off unless enabled with the `markdown_notes` settings.
+//!
+//! [`LspStore`]: project::LspStore
+
+mod backlinks;
+pub mod links;
+mod note_index;
+mod providers;
+
+use std::rc::Rc;
+
+use editor::Editor;
+use gpui::{App, AppContext as _, Context, Entity, Window, actions};
+use settings::{RegisterSetting, Settings};
+use workspace::Workspace;
+
+pub use backlinks::BacklinksPicker;
+pub use note_index::{Backlink, Note, NoteCompletion, NoteIndex};
+pub use providers::{NoteCompletionProvider, NoteSemanticsProvider};
+
+/// The source of the diagnostics reported for broken links.
+pub const DIAGNOSTIC_SOURCE: &str = "markdown-notes";
+
+#[derive(Clone, Debug, PartialEq, RegisterSetting)]
+pub struct MarkdownNotesSettings {
+    /// Whether to treat the Markdown files of local projects as linked notes.
+    pub enabled: bool,
+    /// Whether to update the links to a note when it's renamed or moved.
+    pub rewrite_links_on_rename: bool,
+}
+
+impl Settings for MarkdownNotesSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let markdown_notes = content.markdown_notes.clone().unwrap();
+        Self {
+            enabled: markdown_notes.enabled.unwrap(),
+            rewrite_links_on_rename: markdown_notes.rewrite_links_on_rename.unwrap(),
+        }
+    }
+}
+
+actions!(
+    markdown_notes,
+    [
+        /// Opens a picker listing the notes that link to the active note.
+        ShowBacklinks,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(
+        |workspace: &mut Workspace, window: Option<&mut Window>, cx: &mut Context<Workspace>| {
+            let Some(window) = window else {
+                return;
+            };
+            let project = workspace.project().clone();
+            if !project.read(cx).is_local() {
+                return;
+            }
+            let note_index = cx.new(|cx| NoteIndex::new(project, cx));
+            workspace.register_action({
+                let note_index = note_index.clone();
+                move |workspace, action: &ShowBacklinks, window, cx| {
+                    BacklinksPicker::toggle(workspace, &note_index, action, window, cx)
+                }
+            });
+            cx.subscribe_in(
+                &cx.entity(),
+                window,
+                move |_, _, event: &workspace::Event, _, cx| {
+                    if let workspace::Event::ItemAdded { item } = event
+                        && let Some(editor) = item.act_as::<Editor>(cx)
+                    {
+                        let is_note = editor
+                            .read(cx)
+                            .buffer()
+                            .read(cx)
+                            .as_singleton()
+                            .and_then(|buffer| note_index::note_path(&buffer, cx))
+                            .is_some();
+                        if is_note {
+                            install_providers(&editor, &note_index, cx);
+                        }
+                    }
+                },
+            )
+            .detach();
+        },
+    )
+    .detach();
+}
+
+/// Completes and follows links in a note's editor, on top of the providers it already has, while
+/// notes are enabled.
+fn install_providers(editor: &Entity<Editor>, note_index: &Entity<NoteIndex>, cx: &mut App) {
+    let editor_id = editor.entity_id();
+    if !note_index.update(cx, |note_index, _| note_index.track_editor(editor_id)) {
+        return;
+    }
+    let weak_note_index = note_index.downgrade();
+    cx.observe_release(editor, move |_, cx| {
+        weak_note_index
+            .update(cx, |note_index, _| note_index.untrack_editor(editor_id))
+            .ok();
+    })
+    .detach();
+
+    editor.update(cx, |editor, _| {
+        if let Some(semantics_provider) = editor.semantics_provider() {
+            editor.set_semantics_provider(Some(Rc::new(NoteSemanticsProvider::new(
+                note_index.clone(),
+                semantics_provider,
+            ))));
+        }
+        let completion_provider = editor.completion_provider();
+        editor.set_completion_provider(Some(Rc::new(NoteCompletionProvider::new(
+            note_index.clone(),
+            completion_provider,
+        ))));
+    });
+}
@@ -0,0 +1,1028 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use collections::{HashMap, HashSet};
+use gpui::{App, AppContext as _, Context, Entity, EntityId, Subscription, Task, WeakEntity};
+use language::{Buffer, BufferEvent, BufferId, File as _, Point, ToPointUtf16 as _, point_to_lsp};
+use markdown_preview::markdown_parser::{
+    NoteHeading, NoteLink, NoteLinkTarget, parse_note_headings, parse_note_links,
+};
+use project::{
+    Fs, PathChange, Project, WorktreeId,
+    buffer_store::{BufferStore, BufferStoreEvent},
+};
+use settings::{Settings as _, SettingsStore};
+use util::ResultExt as _;
+
+use crate::{
+    DIAGNOSTIC_SOURCE, MarkdownNotesSettings,
+    links::{
+        LinkQuery, Rename, find_heading, is_note_path, relative_link_path, resolve_relative_link,
+        resolve_wiki_link, rewrite_links,
+    },
+};
+
+/// How long to wait after a note changes before parsing it and checking its links again.
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// The links and headings of a note.
+pub struct Note {
+    pub text: Arc<str>,
+    pub links: Vec<NoteLink>,
+    pub headings: Vec<NoteHeading>,
+}
+
+impl Note {
+    pub fn parse(text: impl Into<Arc<str>>) -> Self {
+        let text = text.into();
+        Self {
+            links: parse_note_links(&text),
+            headings: parse_note_headings(&text),
+            text,
+        }
+    }
+}
+
+/// A link to a note from another note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backlink {
+    /// The note containing the link.
+    pub path: PathBuf,
+    pub range: Range<usize>,
+    /// Where the link starts.
+    pub position: Point,
+    /// The line containing the link, trimmed.
+    pub line: String,
+}
+
+/// An item to complete a link being typed with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteCompletion {
+    pub label: String,
+    pub new_text: String,
+    /// Where the note is, relative to the note the link is in.
+    pub detail: Option<String>,
+}
+
+struct OpenNote {
+    buffer: WeakEntity<Buffer>,
+    pending_update: Task<()>,
+    _subscriptions: [Subscription; 2],
+}
+
+/// Indexes the links and headings of the Markdown notes in a project, reports broken links as
+/// diagnostics, and updates links when notes are renamed.
+///
+/// Notes are read from disk, except for the ones open in a buffer, which are indexed as they're
+/// edited. Nothing is indexed while notes are disabled in the settings.
+pub struct NoteIndex {
+    project: WeakEntity<Project>,
+    fs: Arc<dyn Fs>,
+    settings: MarkdownNotesSettings,
+    /// The ID that diagnostics are reported under, as if they came from a language server.
+    server_id: lsp::LanguageServerId,
+    notes: BTreeMap<PathBuf, Arc<Note>>,
+    worktree_roots: HashMap<WorktreeId, Arc<Path>>,
+    open_notes: HashMap<BufferId, OpenNote>,
+    /// The editors that links are completed and followed in.
+    editors: HashSet<EntityId>,
+    pending_check: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl NoteIndex {
+    pub fn new(project: Entity<Project>, cx: &mut Context<Self>) -> Self {
+        let server_id = project
+            .read(cx)
+            .lsp_store()
+            .update(cx, |lsp_store, _| lsp_store.register_diagnostic_source());
+        let (fs, buffer_store) = {
+            let project = project.read(cx);
+            (project.fs().clone(), project.buffer_store().clone())
+        };
+        let subscriptions = vec![
+            cx.subscribe(&buffer_store, Self::on_buffer_store_event),
+            cx.subscribe(&project, Self::on_project_event),
+            cx.observe_global::<SettingsStore>(Self::on_settings_changed),
+        ];
+
+        let settings = MarkdownNotesSettings::get_global(cx).clone();
+        let mut this = Self {
+            project: project.downgrade(),
+            fs,
+            settings: settings.clone(),
+            server_id,
+            notes: BTreeMap::new(),
+            worktree_roots: HashMap::default(),
+            open_notes: HashMap::default(),
+            editors: HashSet::default(),
+            pending_check: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        if settings.enabled {
+            this.index_project(cx);
+        }
+        this
+    }
+
+    /// Whether notes are enabled in the settings.
+    pub fn is_enabled(&self) -> bool {
+        self.settings.enabled
+    }
+
+    /// The ID that broken links are reported under.
+    pub fn server_id(&self) -> lsp::LanguageServerId {
+        self.server_id
+    }
+
+    pub fn project(&self) -> &WeakEntity<Project> {
+        &self.project
+    }
+
+    pub fn notes(&self) -> &BTreeMap<PathBuf, Arc<Note>> {
+        &self.notes
+    }
+
+    /// Records that links are completed and followed in an editor, returning whether it's new.
+    pub(crate) fn track_editor(&mut self, editor: EntityId) -> bool {
+        self.editors.insert(editor)
+    }
+
+    pub(crate) fn untrack_editor(&mut self, editor: EntityId) {
+        self.editors.remove(&editor);
+    }
+
+    /// Resolves a link in the note at `from` to the note it points to.
+    pub fn resolve(&self, from: &Path, target: &NoteLinkTarget) -> Option<PathBuf> {
+        resolve_note(from, target, &self.notes)
+    }
+
+    /// Returns the links to the note at `path` from other notes, ordered by note.
+    pub fn backlinks(&self, path: &Path) -> Vec<Backlink> {
+        let mut backlinks = Vec::new();
+        for (note_path, note) in &self.notes {
+            if note_path == path {
+                continue;
+            }
+            for link in &note.links {
+                if link.target.text().is_empty()
+                    || self.resolve(note_path, &link.target).as_deref() != Some(path)
+                {
+                    continue;
+                }
+                let line_start = note.text[..link.range.start]
+                    .rfind('\n')
+                    .map_or(0, |ix| ix + 1);
+                let line_end = note.text[link.range.start..]
+                    .find('\n')
+                    .map_or(note.text.len(), |ix| link.range.start + ix);
+                backlinks.push(Backlink {
+                    path: note_path.clone(),
+                    range: link.range.clone(),
+                    position: Point::new(
+                        note.text[..line_start].matches('\n').count() as u32,
+                        (link.range.start - line_start) as u32,
+                    ),
+                    line: note.text[line_start..line_end].trim().to_string(),
+                });
+            }
+        }
+        backlinks
+    }
+
+    /// Returns the note names, paths or headings that complete the link being typed in the note
+    /// at `note_path`.
+    pub fn completions(&self, note_path: &Path, query: &LinkQuery) -> Vec<NoteCompletion> {
+        let note_directory = note_path.parent().unwrap_or(note_path);
+
+        if let Some(heading_of) = &query.heading_of {
+            let target = if query.wiki {
+                NoteLinkTarget::WikiLink(heading_of.clone())
+            } else {
+                NoteLinkTarget::RelativePath(
+                    urlencoding::decode(heading_of)
+                        .map(|path| path.into_owned())
+                        .unwrap_or_else(|_| heading_of.clone()),
+                )
+            };
+            let Some(note) = self
+                .resolve(note_path, &target)
+                .and_then(|path| self.notes.get(&path))
+            else {
+                return Vec::new();
+            };
+            return note
+                .headings
+                .iter()
+                .map(|heading| NoteCompletion {
+                    label: heading.text.clone(),
+                    new_text: if query.wiki {
+                        heading.text.clone()
+                    } else {
+                        heading.anchor.clone()
+                    },
+                    detail: None,
+                })
+                .collect();
+        }
+
+        let mut stem_counts = HashMap::<String, usize>::default();
+        for path in self.notes.keys() {
+            *stem_counts
+                .entry(file_stem(path).to_lowercase())
+                .or_default() += 1;
+        }
+        self.notes
+            .keys()
+            .filter(|path| path.as_path() != note_path)
+            .map(|path| {
+                let relative_path = relative_link_path(note_directory, path);
+                if query.wiki {
+                    let stem = file_stem(path);
+                    // Notes that share a name are told apart by their directory.
+                    let name = match path.parent().and_then(|parent| parent.file_name()) {
+                        Some(parent) if stem_counts[&stem.to_lowercase()] > 1 => {
+                            format!("{}/{stem}", parent.to_string_lossy())
+                        }
+                        _ => stem,
+                    };
+                    NoteCompletion {
+                        label: name.clone(),
+                        new_text: name,
+                        detail: Some(relative_path),
+                    }
+                } else {
+                    NoteCompletion {
+                        new_text: relative_path.replace(' ', "%20"),
+                        label: relative_path,
+                        detail: None,
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn on_settings_changed(&mut self, cx: &mut Context<Self>) {
+        let settings = MarkdownNotesSettings::get_global(cx).clone();
+        if settings == self.settings {
+            return;
+        }
+        let was_enabled = self.settings.enabled;
+        self.settings = settings;
+        if self.settings.enabled && !was_enabled {
+            self.index_project(cx);
+        } else if !self.settings.enabled && was_enabled {
+            self.clear(cx);
+        }
+    }
+
+    fn index_project(&mut self, cx: &mut Context<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let (buffers, worktree_ids) = {
+            let project = project.read(cx);
+            (
+                project
+                    .buffer_store()
+                    .read(cx)
+                    .buffers()
+                    .collect::<Vec<_>>(),
+                project
+                    .worktrees(cx)
+                    .map(|worktree| worktree.read(cx).id())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        for worktree_id in worktree_ids {
+            self.index_worktree(worktree_id, cx);
+        }
+        for buffer in buffers {
+            self.register_buffer(&buffer, cx);
+        }
+    }
+
+    /// Forgets every note and clears the diagnostics of broken links.
+    fn clear(&mut self, cx: &mut Context<Self>) {
+        self.notes.clear();
+        self.worktree_roots.clear();
+        self.open_notes.clear();
+        self.pending_check = Task::ready(());
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let server_id = self.server_id;
+        project.read(cx).lsp_store().update(cx, |lsp_store, cx| {
+            lsp_store.clear_diagnostic_source(server_id, cx);
+        });
+    }
+
+    fn on_buffer_store_event(
+        &mut self,
+        _: Entity<BufferStore>,
+        event: &BufferStoreEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.settings.enabled {
+            return;
+        }
+        if let BufferStoreEvent::BufferAdded(buffer) = event {
+            self.register_buffer(buffer, cx);
+        }
+    }
+
+    fn on_project_event(
+        &mut self,
+        _: Entity<Project>,
+        event: &project::Event,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.settings.enabled {
+            return;
+        }
+        match event {
+            project::Event::WorktreeAdded(worktree_id) => {
+                self.index_worktree(*worktree_id, cx);
+            }
+            project::Event::WorktreeRemoved(worktree_id) => {
+                if let Some(root) = self.worktree_roots.remove(worktree_id) {
+                    self.notes.retain(|path, _| !path.starts_with(&root));
+                    self.schedule_check(cx);
+                }
+            }
+            project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                let Some(root) = self.worktree_roots.get(worktree_id).cloned() else {
+                    return;
+                };
+                let mut changed_paths = Vec::new();
+                for (path, _, change) in changes.iter() {
+                    let abs_path = root.join(path.as_std_path());
+                    if !is_note_path(&abs_path) || self.is_open(&abs_path, cx) {
+                        continue;
+                    }
+                    if let PathChange::Removed = change {
+                        self.notes.remove(&abs_path);
+                    } else {
+                        changed_paths.push(abs_path);
+                    }
+                }
+                self.load_notes(changed_paths, cx);
+            }
+            project::Event::EntryRenamed(_, _, new_abs_path, old_abs_path)
+                if self.settings.rewrite_links_on_rename =>
+            {
+                self.rewrite_links_after_rename(
+                    Rename {
+                        old_path: old_abs_path.clone(),
+                        new_path: new_abs_path.clone(),
+                    },
+                    cx,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn index_worktree(&mut self, worktree_id: WorktreeId, cx: &mut Context<Self>) {
+        let Some(worktree) = self
+            .project
+            .upgrade()
+            .and_then(|project| project.read(cx).worktree_for_id(worktree_id, cx))
+        else {
+            return;
+        };
+        let worktree = worktree.read(cx);
+        if !worktree.is_local() {
+            return;
+        }
+        let root = worktree.abs_path();
+        // Notes found by the worktree's initial scan arrive as updated entries.
+        let paths = worktree
+            .snapshot()
+            .files(false, 0)
+            .map(|entry| root.join(entry.path.as_std_path()))
+            .filter(|path| is_note_path(path))
+            .collect::<Vec<_>>();
+        self.worktree_roots.insert(worktree_id, root);
+        self.load_notes(paths, cx);
+    }
+
+    fn load_notes(&mut self, paths: Vec<PathBuf>, cx: &mut Context<Self>) {
+        if paths.is_empty() {
+            self.schedule_check(cx);
+            return;
+        }
+        let fs = self.fs.clone();
+        cx.spawn(async move |this, cx| {
+            let mut contents = Vec::new();
+            for path in paths {
+                match fs.load(&path).await {
+                    Ok(text) => contents.push((path, text)),
+                    Err(error) => log::debug!("failed to load note {path:?}: {error:#}"),
+                }
+            }
+            let notes = cx
+                .background_spawn(async move {
+                    contents
+                        .into_iter()
+                        .map(|(path, text)| (path, Arc::new(Note::parse(text))))
+                        .collect::<Vec<_>>()
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                // Notes were disabled while they loaded.
+                if !this.settings.enabled {
+                    return;
+                }
+                for (path, note) in notes {
+                    // Open notes are indexed from their buffer, which may have unsaved changes.
+                    if !this.is_open(&path, cx) {
+                        this.notes.insert(path, note);
+                    }
+                }
+                this.schedule_check(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn is_open(&self, path: &Path, cx: &App) -> bool {
+        self.open_notes.values().any(|open_note| {
+            open_note
+                .buffer
+                .upgrade()
+                .and_then(|buffer| note_path(&buffer, cx))
+                .is_some_and(|note_path| note_path == path)
+        })
+    }
+
+    fn register_buffer(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let buffer_id = buffer.read(cx).remote_id();
+        if self.open_notes.contains_key(&buffer_id) {
+            return;
+        }
+        let subscriptions = [
+            cx.subscribe(buffer, |this, buffer, event, cx| match event {
+                BufferEvent::Edited { .. }
+                | BufferEvent::Reloaded
+                | BufferEvent::FileHandleChanged => this.update_note(&buffer, cx),
+                _ => {}
+            }),
+            // The project clears the buffer's diagnostics when it's released.
+            cx.observe_release(buffer, move |this, buffer, cx| {
+                this.open_notes.remove(&buffer_id);
+                // Unsaved changes are gone with the buffer.
+                if let Some(path) = note_path(buffer, cx) {
+                    this.load_notes(vec![path], cx);
+                }
+            }),
+        ];
+        self.open_notes.insert(
+            buffer_id,
+            OpenNote {
+                buffer: buffer.downgrade(),
+                pending_update: Task::ready(()),
+                _subscriptions: subscriptions,
+            },
+        );
+        self.update_note(buffer, cx);
+    }
+
+    /// Indexes a note from its buffer once it has stopped changing.
+    fn update_note(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let buffer_id = buffer.read(cx).remote_id();
+        let Some(open_note) = self.open_notes.get_mut(&buffer_id) else {
+            return;
+        };
+        let buffer = buffer.downgrade();
+        open_note.pending_update = cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+            let Ok(Some((path, text))) = buffer.read_with(cx, |buffer, cx| {
+                let path = buffer.file()?.as_local()?.abs_path(cx);
+                is_note_path(&path).then(|| (path, buffer.text()))
+            }) else {
+                return;
+            };
+            let note = cx
+                .background_spawn(async move { Arc::new(Note::parse(text)) })
+                .await;
+            this.update(cx, |this, cx| {
+                this.notes.insert(path, note);
+                this.schedule_check(cx);
+            })
+            .ok();
+        });
+    }
+
+    /// Checks the links of every open note once the index has stopped changing, replacing their
+    /// previous diagnostics.
+    fn schedule_check(&mut self, cx: &mut Context<Self>) {
+        self.pending_check = cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+            let Ok((buffers, notes, fs)) = this.read_with(cx, |this, cx| {
+                let buffers = this
+                    .open_notes
+                    .values()
+                    .filter_map(|open_note| {
+                        let buffer = open_note.buffer.upgrade()?;
+                        let path = note_path(&buffer, cx)?;
+                        Some((buffer, path))
+                    })
+                    .collect::<Vec<_>>();
+                (buffers, this.notes.clone(), this.fs.clone())
+            }) else {
+                return;
+            };
+
+            for (buffer, path) in buffers {
+                let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+                let diagnostics = cx
+                    .background_spawn({
+                        let snapshot = snapshot.clone();
+                        let notes = notes.clone();
+                        let fs = fs.clone();
+                        let path = path.clone();
+                        async move {
+                            broken_links(&path, &snapshot.text(), &notes, fs.as_ref())
+                                .await
+                                .into_iter()
+                                .map(|(range, message)| lsp::Diagnostic {
+                                    range: lsp::Range::new(
+                                        point_to_lsp(range.start.to_point_utf16(&snapshot)),
+                                        point_to_lsp(range.end.to_point_utf16(&snapshot)),
+                                    ),
+                                    severity: Some(lsp::DiagnosticSeverity::WARNING),
+                                    source: Some(DIAGNOSTIC_SOURCE.to_string()),
+                                    message,
+                                    ..Default::default()
+                                })
+                                .collect::<Vec<_>>()
+                        }
+                    })
+                    .await;
+
+                let updated = this.update(cx, |this, cx| {
+                    // The buffer changed while it was being checked, and another check is pending.
+                    if buffer.read(cx).version() != snapshot.version() {
+                        return;
+                    }
+                    this.publish_diagnostics(&buffer, diagnostics, cx);
+                });
+                if updated.is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    fn publish_diagnostics(
+        &self,
+        buffer: &Entity<Buffer>,
+        diagnostics: Vec<lsp::Diagnostic>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let server_id = self.server_id;
+        project.read(cx).lsp_store().update(cx, |lsp_store, cx| {
+            lsp_store
+                .publish_buffer_diagnostics(server_id, buffer, diagnostics, cx)
+                .log_err();
+        });
+    }
+
+    /// Updates the links to a note or directory that was renamed, and the links of the notes it
+    /// moved, saving the notes that had no unsaved changes.
+    fn rewrite_links_after_rename(&mut self, rename: Rename, cx: &mut Context<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        // The index may or may not have caught up with the rename yet.
+        let notes_before_rename = self
+            .notes
+            .keys()
+            .map(|path| rename.old_location(path).unwrap_or_else(|| path.clone()))
+            .chain(is_note_path(&rename.old_path).then(|| rename.old_path.clone()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let mut affected_notes = BTreeSet::new();
+        for (path, note) in &self.notes {
+            let path = rename
+                .new_location(path)
+                .unwrap_or_else(|| path.to_path_buf());
+            if !rewrite_links(
+                &path,
+                &note.text,
+                &note.links,
+                &rename,
+                &notes_before_rename,
+            )
+            .is_empty()
+            {
+                affected_notes.insert(path);
+            }
+        }
+        if is_note_path(&rename.new_path) {
+            affected_notes.insert(rename.new_path.clone());
+        }
+
+        cx.spawn(async move |_, cx| {
+            for path in affected_notes {
+                let Some(buffer) = project
+                    .update(cx, |project, cx| project.open_local_buffer(&path, cx))
+                    .await
+                    .log_err()
+                else {
+                    continue;
+                };
+                let (edits, was_dirty) = buffer.read_with(cx, |buffer, _| {
+                    let text = buffer.text();
+                    let links = parse_note_links(&text);
+                    let edits = rewrite_links(&path, &text, &links, &rename, &notes_before_rename);
+                    (edits, buffer.is_dirty())
+                });
+                if edits.is_empty() {
+                    continue;
+                }
+                buffer.update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+                if !was_dirty {
+                    project
+                        .update(cx, |project, cx| project.save_buffer(buffer, cx))
+                        .await
+                        .log_err();
+                }
+            }
+        })
+        .detach();
+    }
+}
+
+/// The path of the note open in `buffer`, if it's a note on the local file system.
+pub(crate) fn note_path(buffer: &Entity<Buffer>, cx: &App) -> Option<PathBuf> {
+    let path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+    is_note_path(&path).then_some(path)
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn resolve_note(
+    from: &Path,
+    target: &NoteLinkTarget,
+    notes: &BTreeMap<PathBuf, Arc<Note>>,
+) -> Option<PathBuf> {
+    match target {
+        _ if target.text().is_empty() => Some(from.to_path_buf()),
+        NoteLinkTarget::WikiLink(name) => {
+            resolve_wiki_link(name, from, notes.keys().map(PathBuf::as_path))
+        }
+        NoteLinkTarget::RelativePath(path) => {
+            let path = resolve_relative_link(from, path);
+            notes.contains_key(&path).then_some(path)
+        }
+    }
+}
+
+/// Returns the links in the note at `path` that point to a note, file or heading that doesn't
+/// exist, with a message describing what's missing.
+async fn broken_links(
+    path: &Path,
+    text: &str,
+    notes: &BTreeMap<PathBuf, Arc<Note>>,
+    fs: &dyn Fs,
+) -> Vec<(Range<usize>, String)> {
+    let own_headings = parse_note_headings(text);
+    let mut broken_links = Vec::new();
+    for link in parse_note_links(text) {
+        let target_text = link.target.text();
+        let headings = match resolve_note(path, &link.target, notes) {
+            Some(target) if target == path => &own_headings,
+            Some(target) => &notes[&target].headings,
+            None => {
+                let message = match &link.target {
+                    NoteLinkTarget::WikiLink(_) => format!("No note named \"{target_text}\""),
+                    NoteLinkTarget::RelativePath(relative_path) => {
+                        let target = resolve_relative_link(path, relative_path);
+                        if let Ok(Some(_)) = fs.metadata(&target).await {
+                            continue;
+                        }
+                        format!("No file at \"{target_text}\"")
+                    }
+                };
+                broken_links.push((link.range, message));
+                continue;
+            }
+        };
+        if let Some(anchor) = link.anchor.as_deref().filter(|anchor| !anchor.is_empty())
+            && find_heading(headings, anchor).is_none()
+        {
+            let message = if target_text.is_empty() {
+                format!("No heading \"#{anchor}\" in this note")
+            } else {
+                format!("No heading \"#{anchor}\" in \"{target_text}\"")
+            };
+            broken_links.push((link.range, message));
+        }
+    }
+    broken_links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use language::DiagnosticEntry;
+    use project::FakeFs;
+    use serde_json::json;
+    use util::{path, rel_path::rel_path};
+    use workspace::AppState;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+        });
+        update_settings(true, true, cx);
+    }
+
+    fn update_settings(enabled: bool, rewrite_links_on_rename: bool, cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    let markdown_notes = settings.markdown_notes.get_or_insert_default();
+                    markdown_notes.enabled = Some(enabled);
+                    markdown_notes.rewrite_links_on_rename = Some(rewrite_links_on_rename);
+                });
+            });
+        });
+    }
+
+    fn diagnostics(buffer: &Entity<Buffer>, cx: &mut TestAppContext) -> Vec<(String, String)> {
+        buffer.read_with(cx, |buffer, _| {
+            let snapshot = buffer.snapshot();
+            snapshot
+                .diagnostics_in_range::<_, usize>(0..snapshot.len(), false)
+                .map(|entry| {
+                    let entry: DiagnosticEntry<usize> = entry.to_owned();
+                    (
+                        snapshot.text_for_range(entry.range).collect(),
+                        entry.diagnostic.message,
+                    )
+                })
+                .collect()
+        })
+    }
+
+    #[gpui::test]
+    async fn test_broken_links_and_backlinks(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/notes"),
+            json!({
+                "index.md": "# Index\n\nSee [[Ideas#Later]], [[Ideas#Soon]], [[Missing]], \
+                             [setup](guides/setup.md#install), [logo](logo.png), \
+                             [gone](gone.md) and [top](#index).\n",
+                "Ideas.md": "# Ideas\n\n## Later\n\nBack to [[index]].\n",
+                "guides": { "setup.md": "# Setup\n" },
+                "logo.png": "",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/notes").as_ref()], cx).await;
+        let index = cx.new(|cx| NoteIndex::new(project.clone(), cx));
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/notes/index.md"), cx)
+            })
+            .await
+            .unwrap();
+        cx.executor().advance_clock(UPDATE_DEBOUNCE * 2);
+        cx.run_until_parked();
+
+        assert_eq!(
+            diagnostics(&buffer, cx),
+            [
+                (
+                    "[[Ideas#Soon]]".to_string(),
+                    "No heading \"#Soon\" in \"Ideas\"".to_string()
+                ),
+                (
+                    "[[Missing]]".to_string(),
+                    "No note named \"Missing\"".to_string()
+                ),
+                (
+                    "[setup](guides/setup.md#install)".to_string(),
+                    "No heading \"#install\" in \"guides/setup.md\"".to_string()
+                ),
+                (
+                    "[gone](gone.md)".to_string(),
+                    "No file at \"gone.md\"".to_string()
+                ),
+            ]
+        );
+
+        index.read_with(cx, |index, _| {
+            let backlinks = index.backlinks(Path::new(path!("/notes/Ideas.md")));
+            assert_eq!(
+                backlinks
+                    .iter()
+                    .map(|backlink| (backlink.path.clone(), backlink.position.row))
+                    .collect::<Vec<_>>(),
+                [
+                    (PathBuf::from(path!("/notes/index.md")), 2),
+                    (PathBuf::from(path!("/notes/index.md")), 2)
+                ]
+            );
+
+            let completions = index.completions(
+                Path::new(path!("/notes/index.md")),
+                &LinkQuery {
+                    wiki: true,
+                    heading_of: Some("Ideas".into()),
+                    start: 0,
+                },
+            );
+            assert_eq!(
+                completions
+                    .iter()
+                    .map(|completion| completion.new_text.as_str())
+                    .collect::<Vec<_>>(),
+                ["Ideas", "Later"]
+            );
+        });
+
+        buffer.update(cx, |buffer, cx| {
+            let start = buffer.text().find("[[Missing]]").unwrap();
+            buffer.edit(
+                [(start..start + "[[Missing]]".len(), "[[ideas]]")],
+                None,
+                cx,
+            );
+        });
+        cx.executor().advance_clock(UPDATE_DEBOUNCE * 2);
+        cx.run_until_parked();
+        let broken_links = |cx: &mut TestAppContext| {
+            diagnostics(&buffer, cx)
+                .into_iter()
+                .map(|(link, _)| link)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            broken_links(cx),
+            [
+                "[[Ideas#Soon]]",
+                "[setup](guides/setup.md#install)",
+                "[gone](gone.md)"
+            ]
+        );
+
+        fs.insert_file(path!("/notes/gone.md"), Vec::new()).await;
+        cx.executor().advance_clock(UPDATE_DEBOUNCE * 2);
+        cx.run_until_parked();
+        assert_eq!(
+            broken_links(cx),
+            ["[[Ideas#Soon]]", "[setup](guides/setup.md#install)"]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_rewriting_links_after_rename(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/notes"),
+            json!({
+                "index.md": "[[Ideas]] and [ideas](Ideas.md#later)\n",
+                "Ideas.md": "# Ideas\n\n## Later\n\n[home](index.md)\n",
+                "archive": {},
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/notes").as_ref()], cx).await;
+        let _index = cx.new(|cx| NoteIndex::new(project.clone(), cx));
+        cx.executor().advance_clock(UPDATE_DEBOUNCE * 2);
+        cx.run_until_parked();
+
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let entry_id = project.read_with(cx, |project, cx| {
+            project
+                .entry_for_path(&(worktree_id, rel_path("Ideas.md")).into(), cx)
+                .unwrap()
+                .id
+        });
+        project
+            .update(cx, |project, cx| {
+                project.rename_entry(
+                    entry_id,
+                    (worktree_id, rel_path("archive/Thoughts.md")).into(),
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        cx.executor().advance_clock(UPDATE_DEBOUNCE * 2);
+        cx.run_until_parked();
+
+        assert_eq!(
+            fs.load(path!("/notes/index.md").as_ref()).await.unwrap(),
+            "[[Thoughts]] and [ideas](archive/Thoughts.md#later)\n"
+        );
+        assert_eq!(
+            fs.load(path!("/notes/archive/Thoughts.md").as_ref())
+                .await
+                .unwrap(),
+            "# Ideas\n\n## Later\n\n[home](../index.md)\n"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_settings(cx: &mut TestAppContext) {
+        init_test(cx);
+        update_settings(false, false, cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/notes"),
+            json!({
+                "index.md": "[[Ideas]] and [[Missing]]\n",
+                "Ideas.md": "# Ideas\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/notes").as_ref()], cx).await;
+        let index = cx.new(|cx| NoteIndex::new(project.clone(), cx));
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/notes/index.md"), cx)
+            })
+            .await
+            .unwrap();
+        cx.executor().advance_clock(UPDATE_DEBOUNCE * 2);
+        cx.run_until_parked();
+        assert!(index.read_with(cx, |index, _| index.notes().is_empty()));
+        assert!(diagnostics(&buffer, cx).is_empty());
+
+        update_settings(true, false, cx);
+        cx.executor().advance_clock(UPDATE_DEBOUNCE * 2);
+        cx.run_until_parked();
+        assert_eq!(
+            diagnostics(&buffer, cx),
+            [(
+                "[[Missing]]".to_string(),
+                "No note named \"Missing\"".to_string()
+            )]
+        );
+
+        // Links are left alone when a note is renamed, unless rewriting them is enabled.
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let entry_id = project.read_with(cx, |project, cx| {
+            project
+                .entry_for_path(&(worktree_id, rel_path("Ideas.md")).into(), cx)
+                .unwrap()
+                .id
+        });
+        project
+            .update(cx, |project, cx| {
+                project.rename_entry(entry_id, (worktree_id, rel_path("Thoughts.md")).into(), cx)
+            })
+            .await
+            .unwrap();
+        cx.executor().advance_clock(UPDATE_DEBOUNCE * 2);
+        cx.run_until_parked();
+        assert_eq!(
+            fs.load(path!("/notes/index.md").as_ref()).await.unwrap(),
+            "[[Ideas]] and [[Missing]]\n"
+        );
+        assert_eq!(
+            diagnostics(&buffer, cx),
+            [
+                (
+                    "[[Ideas]]".to_string(),
+                    "No note named \"Ideas\"".to_string()
+                ),
+                (
+                    "[[Missing]]".to_string(),
+                    "No note named \"Missing\"".to_string()
+                ),
+            ]
+        );
+
+        update_settings(false, false, cx);
+        cx.run_until_parked();
+        assert!(index.read_with(cx, |index, _| index.notes().is_empty()));
+        assert!(diagnostics(&buffer, cx).is_empty());
+    }
+}
@@ -0,0 +1,342 @@
+use std::{cell::RefCell, ops::Range, path::PathBuf, rc::Rc, sync::Arc};
+
+use anyhow::Result;
+use collections::{HashMap, HashSet};
+use editor::{
+    CompletionContext, CompletionProvider, Editor, ExcerptId, GotoDefinitionKind, SemanticsProvider,
+};
+use futures::future::Shared;
+use gpui::{App, AppContext as _, Context, Entity, Task, Window};
+use language::{
+    Anchor, Buffer, BufferRow, CodeLabel, Location, Point, ToOffset as _, ToPoint as _,
+};
+use markdown_preview::markdown_parser::{parse_note_headings, parse_note_links};
+use project::{
+    Completion, CompletionDisplayOptions, CompletionResponse, CompletionSource, DocumentHighlight,
+    InlayHint, InvalidationStrategy, LocationLink, ProjectTransaction,
+    lsp_store::{BufferSemanticTokens, CacheInlayHints, CompletionDocumentation, RefreshForServer},
+};
+
+use crate::{
+    NoteIndex,
+    links::{LinkQuery, find_heading, link_query},
+    note_index::note_path,
+};
+
+/// Completes note names, paths and headings in links, deferring to the editor's previous
+/// provider everywhere else.
+pub struct NoteCompletionProvider {
+    note_index: Entity<NoteIndex>,
+    fallback: Option<Rc<dyn CompletionProvider>>,
+}
+
+impl NoteCompletionProvider {
+    pub fn new(
+        note_index: Entity<NoteIndex>,
+        fallback: Option<Rc<dyn CompletionProvider>>,
+    ) -> Self {
+        Self {
+            note_index,
+            fallback,
+        }
+    }
+
+    /// The link being typed before `position`, along with the note it's in and the offset at
+    /// which the typed text starts.
+    fn link_query(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &App,
+    ) -> Option<(PathBuf, LinkQuery, usize)> {
+        if !self.note_index.read(cx).is_enabled() {
+            return None;
+        }
+        let path = note_path(buffer, cx)?;
+        let snapshot = buffer.read(cx).snapshot();
+        let position = position.to_point(&snapshot);
+        let line_start = Point::new(position.row, 0);
+        let line = snapshot
+            .text_for_range(line_start..position)
+            .collect::<String>();
+        let query = link_query(&line)?;
+        let start = line_start.to_offset(&snapshot) + query.start;
+        Some((path, query, start))
+    }
+}
+
+impl CompletionProvider for NoteCompletionProvider {
+    fn completions(
+        &self,
+        excerpt_id: ExcerptId,
+        buffer: &Entity<Buffer>,
+        buffer_position: Anchor,
+        trigger: CompletionContext,
+        window: &mut Window,
+        cx: &mut Context<Editor>,
+    ) -> Task<Result<Vec<CompletionResponse>>> {
+        let Some((path, query, start)) = self.link_query(buffer, buffer_position, cx) else {
+            return match &self.fallback {
+                Some(fallback) => {
+                    fallback.completions(excerpt_id, buffer, buffer_position, trigger, window, cx)
+                }
+                None => Task::ready(Ok(Vec::new())),
+            };
+        };
+
+        let start = buffer.read(cx).anchor_before(start);
+        let completions = self
+            .note_index
+            .read(cx)
+            .completions(&path, &query)
+            .into_iter()
+            .map(|completion| Completion {
+                replace_range: start..buffer_position,
+                new_text: completion.new_text,
+                label: CodeLabel::plain(completion.label, None),
+                documentation: completion
+                    .detail
+                    .map(|detail| CompletionDocumentation::SingleLine(detail.into())),
+                source: CompletionSource::Custom,
+                icon_path: None,
+                match_start: Some(start),
+                snippet_deduplication_key: None,
+                insert_text_mode: None,
+                confirm: None,
+            })
+            .collect();
+        Task::ready(Ok(vec![CompletionResponse {
+            completions,
+            display_options: CompletionDisplayOptions::default(),
+            is_incomplete: false,
+        }]))
+    }
+
+    fn resolve_completions(
+        &self,
+        buffer: Entity<Buffer>,
+        completion_indices: Vec<usize>,
+        completions: Rc<RefCell<Box<[Completion]>>>,
+        cx: &mut Context<Editor>,
+    ) -> Task<Result<bool>> {
+        match &self.fallback {
+            Some(fallback) => {
+                fallback.resolve_completions(buffer, completion_indices, completions, cx)
+            }
+            None => Task::ready(Ok(false)),
+        }
+    }
+
+    fn apply_additional_edits_for_completion(
+        &self,
+        buffer: Entity<Buffer>,
+        completions: Rc<RefCell<Box<[Completion]>>>,
+        completion_index: usize,
+        push_to_history: bool,
+        all_commit_ranges: Vec<Range<Anchor>>,
+        cx: &mut Context<Editor>,
+    ) -> Task<Result<Option<language::Transaction>>> {
+        match &self.fallback {
+            Some(fallback) => fallback.apply_additional_edits_for_completion(
+                buffer,
+                completions,
+                completion_index,
+                push_to_history,
+                all_commit_ranges,
+                cx,
+            ),
+            None => Task::ready(Ok(None)),
+        }
+    }
+
+    fn is_completion_trigger(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        text: &str,
+        trigger_in_words: bool,
+        cx: &mut Context<Editor>,
+    ) -> bool {
+        self.link_query(buffer, position, cx).is_some()
+            || self.fallback.as_ref().is_some_and(|fallback| {
+                fallback.is_completion_trigger(buffer, position, text, trigger_in_words, cx)
+            })
+    }
+
+    fn show_snippets(&self) -> bool {
+        self.fallback
+            .as_ref()
+            .is_some_and(|fallback| fallback.show_snippets())
+    }
+}
+
+/// Follows links to notes and their headings, deferring to the editor's previous provider for
+/// everything else.
+pub struct NoteSemanticsProvider {
+    note_index: Entity<NoteIndex>,
+    fallback: Rc<dyn SemanticsProvider>,
+}
+
+impl NoteSemanticsProvider {
+    pub fn new(note_index: Entity<NoteIndex>, fallback: Rc<dyn SemanticsProvider>) -> Self {
+        Self {
+            note_index,
+            fallback,
+        }
+    }
+
+    fn link_definition(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Result<Option<Vec<LocationLink>>>>> {
+        if !self.note_index.read(cx).is_enabled() {
+            return None;
+        }
+        let note_path = note_path(buffer, cx)?;
+        let snapshot = buffer.read(cx).snapshot();
+        let offset = position.to_offset(&snapshot);
+        let link = parse_note_links(&snapshot.text())
+            .into_iter()
+            .find(|link| link.range.contains(&offset))?;
+        let note_index = self.note_index.read(cx);
+        let target_path = note_index.resolve(&note_path, &link.target)?;
+        let project = note_index.project().upgrade()?;
+        let open_target = project.update(cx, |project, cx| {
+            project.open_local_buffer(&target_path, cx)
+        });
+
+        let origin = Location {
+            buffer: buffer.clone(),
+            range: snapshot.anchor_after(link.range.start)..snapshot.anchor_before(link.range.end),
+        };
+        Some(cx.spawn(async move |cx| {
+            let target_buffer = open_target.await?;
+            let range = target_buffer.read_with(cx, |target_buffer, _| {
+                let offset = link
+                    .anchor
+                    .as_deref()
+                    .and_then(|anchor| {
+                        let headings = parse_note_headings(&target_buffer.text());
+                        Some(find_heading(&headings, anchor)?.range.start)
+                    })
+                    .unwrap_or(0);
+                let anchor = target_buffer.anchor_after(offset);
+                anchor..anchor
+            });
+            Ok(Some(vec![LocationLink {
+                origin: Some(origin),
+                target: Location {
+                    buffer: target_buffer,
+                    range,
+                },
+            }]))
+        }))
+    }
+}
+
+impl SemanticsProvider for NoteSemanticsProvider {
+    fn hover(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Option<Vec<project::Hover>>>> {
+        self.fallback.hover(buffer, position, cx)
+    }
+
+    fn inline_values(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        range: Range<Anchor>,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<InlayHint>>>> {
+        self.fallback.inline_values(buffer_handle, range, cx)
+    }
+
+    fn applicable_inlay_chunks(
+        &self,
+        buffer: &Entity<Buffer>,
+        ranges: &[Range<Anchor>],
+        cx: &mut App,
+    ) -> Vec<Range<BufferRow>> {
+        self.fallback.applicable_inlay_chunks(buffer, ranges, cx)
+    }
+
+    fn invalidate_inlay_hints(&self, for_buffers: &HashSet<language::BufferId>, cx: &mut App) {
+        self.fallback.invalidate_inlay_hints(for_buffers, cx)
+    }
+
+    fn inlay_hints(
+        &self,
+        invalidate: InvalidationStrategy,
+        buffer: Entity<Buffer>,
+        ranges: Vec<Range<Anchor>>,
+        known_chunks: Option<(clock::Global, HashSet<Range<BufferRow>>)>,
+        cx: &mut App,
+    ) -> Option<HashMap<Range<BufferRow>, Task<Result<CacheInlayHints>>>> {
+        self.fallback
+            .inlay_hints(invalidate, buffer, ranges, known_chunks, cx)
+    }
+
+    fn semantic_tokens(
+        &self,
+        buffer: Entity<Buffer>,
+        refresh: Option<RefreshForServer>,
+        cx: &mut App,
+    ) -> Option<Shared<Task<std::result::Result<BufferSemanticTokens, Arc<anyhow::Error>>>>> {
+        self.fallback.semantic_tokens(buffer, refresh, cx)
+    }
+
+    fn supports_inlay_hints(&self, buffer: &Entity<Buffer>, cx: &mut App) -> bool {
+        self.fallback.supports_inlay_hints(buffer, cx)
+    }
+
+    fn supports_semantic_tokens(&self, buffer: &Entity<Buffer>, cx: &mut App) -> bool {
+        self.fallback.supports_semantic_tokens(buffer, cx)
+    }
+
+    fn document_highlights(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Result<Vec<DocumentHighlight>>>> {
+        self.fallback.document_highlights(buffer, position, cx)
+    }
+
+    fn definitions(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        kind: GotoDefinitionKind,
+        cx: &mut App,
+    ) -> Option<Task<Result<Option<Vec<LocationLink>>>>> {
+        if kind == GotoDefinitionKind::Symbol
+            && let Some(definition) = self.link_definition(buffer, position, cx)
+        {
+            return Some(definition);
+        }
+        self.fallback.definitions(buffer, position, kind, cx)
+    }
+
+    fn range_for_rename(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut App,
+    ) -> Option<Task<Result<Option<Range<Anchor>>>>> {
+        self.fallback.range_for_rename(buffer, position, cx)
+    }
+
+    fn perform_rename(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        new_name: String,
+        cx: &mut App,
+    ) -> Option<Task<Result<ProjectTransaction>>> {
+        self.fallback.perform_rename(buffer, position, new_name, cx)
+    }
+}
@@ -1,3 +1,4 @@
+use crate::markdown_math::MathNode;
 use gpui::{
     DefiniteLength, FontStyle, FontWeight, HighlightStyle, SharedString, StrikethroughStyle,
     UnderlineStyle, px,
@@ -19,6 +20,9 @@ pub enum ParsedMarkdownElement {
     Paragraph(MarkdownParagraph),
     HorizontalRule(Range<usize>),
     Image(Image),
+    /// A GitHub alert, such as `> [!NOTE]`.
+    Alert(ParsedMarkdownAlert),
+    FootnoteDefinition(ParsedMarkdownFootnoteDefinition),
 }
 
 impl ParsedMarkdownElement {
@@ -33,9 +37,12 @@ impl ParsedMarkdownElement {
             Self::Paragraph(text) => match text.get(0)? {
                 MarkdownParagraphChunk::Text(t) => t.source_range.clone(),
                 MarkdownParagraphChunk::Image(image) => image.source_range.clone(),
+                MarkdownParagraphChunk::Math(math) => math.source_range.clone(),
             },
             Self::HorizontalRule(range) => range.clone(),
             Self::Image(image) => image.source_range.clone(),
+            Self::Alert(alert) => alert.source_range.clone(),
+            Self::FootnoteDefinition(definition) => definition.source_range.clone(),
         })
     }
 
@@ -51,6 +58,8 @@ pub type MarkdownParagraph = Vec<MarkdownParagraphChunk>;
 pub enum MarkdownParagraphChunk {
     Text(ParsedMarkdownText),
     Image(Image),
+    /// Display math, such as `$$x^2$$`.
+    Math(ParsedMarkdownMath),
 }
 
 #[derive(Debug)]
@@ -178,6 +187,58 @@ pub struct ParsedMarkdownBlockQuote {
     pub children: Vec<ParsedMarkdownElement>,
 }
 
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownAlert {
+    pub source_range: Range<usize>,
+    pub kind: AlertKind,
+    pub children: Vec<ParsedMarkdownElement>,
+}
+
+/// The kind of a GitHub alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl AlertKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            AlertKind::Note => "Note",
+            AlertKind::Tip => "Tip",
+            AlertKind::Important => "Important",
+            AlertKind::Warning => "Warning",
+            AlertKind::Caution => "Caution",
+        }
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownFootnoteDefinition {
+    pub source_range: Range<usize>,
+    pub label: SharedString,
+    /// The number shown for the footnote. Footnotes are numbered in the order they're first
+    /// referenced, followed by the footnotes that aren't referenced.
+    pub number: usize,
+    /// Where the footnote is first referenced in the Markdown document, if it is.
+    pub reference_offset: Option<usize>,
+    pub children: Vec<ParsedMarkdownElement>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownMath {
+    pub source_range: Range<usize>,
+    /// The LaTeX source of the math.
+    pub contents: SharedString,
+    pub node: MathNode,
+}
+
 #[derive(Debug, Clone)]
 pub struct ParsedMarkdownText {
     /// Where the text is located in the source Markdown document.
@@ -272,6 +333,8 @@ pub struct ParsedRegion {
     pub code: bool,
     /// The link contained in this region, if it has one.
     pub link: Option<Link>,
+    /// The inline math shown in this region, if it is one.
+    pub math: Option<MathNode>,
 }
 
 /// A Markdown link.
@@ -290,6 +353,12 @@ pub enum Link {
         /// The absolute path to the item.
         path: PathBuf,
     },
+    /// A link between a footnote reference and the footnote's definition.
+    Footnote {
+        label: SharedString,
+        /// Whether this links back from the definition to the reference.
+        is_back_reference: bool,
+    },
 }
 
 impl Link {
@@ -311,7 +380,7 @@ impl Link {
             });
         }
 
-        if let Some(file_location_directory) = file_location_directory {
+        if let Some(file_location_directory) = &file_location_directory {
             let display_path = path;
             let path = file_location_directory.join(decoded_text);
             if path.exists() {
@@ -319,8 +388,36 @@ impl Link {
             }
         }
 
+        // Links to a heading of another file, such as `notes.md#setup`, open the file.
+        if let Some((path, _anchor)) = decoded_text.split_once('#')
+            && !path.is_empty()
+        {
+            return Self::identify(file_location_directory, path.to_string());
+        }
+
         None
     }
+
+    /// Identifies the note a `[[wiki-link]]` points to, which is a Markdown file next to the
+    /// current one, named with or without its extension.
+    pub fn identify_wiki_link(
+        file_location_directory: Option<PathBuf>,
+        target: &str,
+    ) -> Option<Link> {
+        let name = target.split('#').next()?.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let directory = file_location_directory?;
+        [format!("{name}.md"), name.to_string()]
+            .into_iter()
+            .map(|file_name| directory.join(file_name))
+            .find(|path| path.is_file())
+            .map(|path| Link::Path {
+                display_path: PathBuf::from(name),
+                path,
+            })
+    }
 }
 
 impl Display for Link {
@@ -328,6 +425,7 @@ impl Display for Link {
         match self {
             Link::Web { url } => write!(f, "{}", url),
             Link::Path { display_path, .. } => write!(f, "{}", display_path.display()),
+            Link::Footnote { label, .. } => write!(f, "[^{}]", label),
         }
     }
 }
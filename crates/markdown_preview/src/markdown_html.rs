@@ -0,0 +1,798 @@
+//! Exports a parsed Markdown document as an HTML page, styled after the current theme. Local
+//! images are embedded as data URIs, Mermaid diagrams as SVG, and math as MathML. Images linked by
+//! URL are not downloaded, and keep their URL as their source.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use base64::Engine as _;
+use fs::Fs;
+use gpui::{
+    AbsoluteLength, App, DefiniteLength, FontStyle, HighlightStyle, Hsla, Rgba, SharedString,
+};
+use settings::Settings as _;
+use theme::{ActiveTheme as _, StatusColors, SyntaxTheme, ThemeSettings};
+
+use crate::{
+    markdown_elements::{
+        AlertKind, HeadingLevel, Image, Link, MarkdownParagraph, MarkdownParagraphChunk,
+        ParsedMarkdown, ParsedMarkdownCodeBlock, ParsedMarkdownElement, ParsedMarkdownListItem,
+        ParsedMarkdownListItemType, ParsedMarkdownTable, ParsedMarkdownTableAlignment,
+        ParsedMarkdownText,
+    },
+    markdown_renderer::alert_color,
+};
+
+/// The fonts and colors of an exported page, taken from the current theme.
+pub struct HtmlTheme {
+    pub font_family: SharedString,
+    pub code_font_family: SharedString,
+    pub background: Hsla,
+    pub text: Hsla,
+    pub text_muted: Hsla,
+    pub link: Hsla,
+    pub border: Hsla,
+    pub code_block_background: Hsla,
+    pub code_span_background: Hsla,
+    pub status_colors: StatusColors,
+    pub syntax_theme: Arc<SyntaxTheme>,
+}
+
+impl HtmlTheme {
+    pub fn new(cx: &App) -> Self {
+        let settings = ThemeSettings::get_global(cx);
+        let theme = cx.theme();
+        let colors = theme.colors();
+        Self {
+            font_family: settings.ui_font.family.clone(),
+            code_font_family: settings.buffer_font.family.clone(),
+            background: colors.editor_background,
+            text: colors.text,
+            text_muted: colors.text_muted,
+            link: colors.text_accent,
+            border: colors.border,
+            code_block_background: colors.surface_background,
+            code_span_background: colors.editor_document_highlight_read_background,
+            status_colors: theme.status().clone(),
+            syntax_theme: theme.syntax().clone(),
+        }
+    }
+}
+
+/// Loads the local images of a parsed Markdown document as `data:` URIs, keyed by their path, so
+/// that they can be embedded by [`render_html`].
+pub async fn load_embedded_images(
+    parsed: &ParsedMarkdown,
+    fs: &dyn Fs,
+) -> HashMap<PathBuf, String> {
+    let mut paths = Vec::new();
+    for element in &parsed.children {
+        collect_image_paths(element, &mut paths);
+    }
+    let mut images = HashMap::default();
+    for path in paths {
+        if images.contains_key(&path) {
+            continue;
+        }
+        if let Some(image) = embedded_image(&path, fs).await {
+            images.insert(path, image);
+        }
+    }
+    images
+}
+
+/// Renders a parsed Markdown document as a standalone HTML page, embedding the given images.
+pub fn render_html(
+    parsed: &ParsedMarkdown,
+    title: &str,
+    theme: &HtmlTheme,
+    images: &HashMap<PathBuf, String>,
+) -> String {
+    let mut renderer = HtmlRenderer {
+        out: String::new(),
+        theme,
+        images,
+        footnote_references: HashSet::default(),
+    };
+    for element in &parsed.children {
+        renderer.write_element(element);
+    }
+
+    format!(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n\
+         <style>\n{}</style>\n\
+         </head>\n\
+         <body>\n\
+         <main>\n{}</main>\n\
+         </body>\n\
+         </html>\n",
+        escape_html(title),
+        stylesheet(theme),
+        renderer.out
+    )
+}
+
+fn stylesheet(theme: &HtmlTheme) -> String {
+    let background = css_color(theme.background);
+    let text = css_color(theme.text);
+    let text_muted = css_color(theme.text_muted);
+    let link = css_color(theme.link);
+    let border = css_color(theme.border);
+    let code_block_background = css_color(theme.code_block_background);
+    let code_span_background = css_color(theme.code_span_background);
+    let font_family = escape_html(&theme.font_family);
+    let code_font_family = escape_html(&theme.code_font_family);
+
+    let mut css = format!(
+        "body {{ margin: 0; background: {background}; color: {text}; \
+         font-family: \"{font_family}\", system-ui, sans-serif; line-height: 1.6; }}\n\
+         main {{ max-width: 48rem; margin: 0 auto; padding: 2rem; }}\n\
+         a {{ color: {link}; }}\n\
+         h1, h2 {{ border-bottom: 1px solid {border}; padding-bottom: 0.3em; }}\n\
+         h6 {{ color: {text_muted}; }}\n\
+         code, pre {{ font-family: \"{code_font_family}\", monospace; }}\n\
+         code {{ background: {code_span_background}; border-radius: 4px; padding: 0.1em 0.3em; }}\n\
+         pre {{ background: {code_block_background}; border-radius: 4px; padding: 1rem; \
+         overflow-x: auto; }}\n\
+         pre code {{ background: none; padding: 0; }}\n\
+         blockquote {{ margin: 0 0 1em; padding-left: 1rem; border-left: 4px solid {border}; }}\n\
+         table {{ border-collapse: collapse; margin-bottom: 1em; }}\n\
+         th, td {{ border: 1px solid {border}; padding: 0.3em 0.6em; }}\n\
+         th {{ background: {code_block_background}; }}\n\
+         caption {{ caption-side: bottom; color: {text_muted}; }}\n\
+         hr {{ border: none; border-top: 2px solid {border}; }}\n\
+         img {{ max-width: 100%; }}\n\
+         .list-item {{ display: flex; gap: 0.5em; }}\n\
+         .list-item > div > p {{ margin: 0; }}\n\
+         .mermaid {{ background: {code_block_background}; border-radius: 4px; padding: 1rem; }}\n\
+         .mermaid svg {{ max-width: 100%; height: auto; }}\n\
+         .alert {{ margin: 0 0 1em; padding-left: 1rem; border-left: 4px solid; }}\n\
+         .alert-title {{ font-weight: 600; margin: 0; }}\n\
+         .footnote {{ display: flex; gap: 0.5em; font-size: 0.875em; }}\n\
+         .footnote > div > p {{ margin: 0; }}\n\
+         .footnote-number {{ color: {text_muted}; }}\n\
+         a.footnote-reference, a.footnote-back-reference {{ text-decoration: none; }}\n"
+    );
+    for kind in [
+        AlertKind::Note,
+        AlertKind::Tip,
+        AlertKind::Important,
+        AlertKind::Warning,
+        AlertKind::Caution,
+    ] {
+        let class = alert_class(kind);
+        let color = css_color(alert_color(kind, &theme.status_colors));
+        writeln!(
+            css,
+            ".{class} {{ border-color: {color}; }}\n.{class} .alert-title {{ color: {color}; }}"
+        )
+        .ok();
+    }
+    css
+}
+
+struct HtmlRenderer<'a> {
+    out: String,
+    theme: &'a HtmlTheme,
+    /// The local images, as `data:` URIs.
+    images: &'a HashMap<PathBuf, String>,
+    /// The footnotes whose first reference has been written, and given an anchor.
+    footnote_references: HashSet<SharedString>,
+}
+
+impl HtmlRenderer<'_> {
+    fn write_element(&mut self, element: &ParsedMarkdownElement) {
+        match element {
+            ParsedMarkdownElement::Heading(heading) => {
+                let level = match heading.level {
+                    HeadingLevel::H1 => 1,
+                    HeadingLevel::H2 => 2,
+                    HeadingLevel::H3 => 3,
+                    HeadingLevel::H4 => 4,
+                    HeadingLevel::H5 => 5,
+                    HeadingLevel::H6 => 6,
+                };
+                write!(self.out, "<h{level}>").ok();
+                self.write_paragraph(&heading.contents);
+                writeln!(self.out, "</h{level}>").ok();
+            }
+            ParsedMarkdownElement::ListItem(list_item) => self.write_list_item(list_item),
+            ParsedMarkdownElement::Table(table) => self.write_table(table),
+            ParsedMarkdownElement::BlockQuote(block_quote) => {
+                self.out.push_str("<blockquote>\n");
+                for child in &block_quote.children {
+                    self.write_element(child);
+                }
+                self.out.push_str("</blockquote>\n");
+            }
+            ParsedMarkdownElement::CodeBlock(code_block) => self.write_code_block(code_block),
+            ParsedMarkdownElement::MermaidDiagram(mermaid) => {
+                match mermaid_rs_renderer::render(&mermaid.contents.contents) {
+                    Ok(svg) => {
+                        writeln!(self.out, "<div class=\"mermaid\">{svg}</div>").ok();
+                    }
+                    Err(error) => {
+                        log::error!("failed to render mermaid diagram: {error}");
+                        writeln!(
+                            self.out,
+                            "<pre><code>{}</code></pre>",
+                            escape_html(&mermaid.contents.contents)
+                        )
+                        .ok();
+                    }
+                }
+            }
+            ParsedMarkdownElement::Paragraph(paragraph) => {
+                self.out.push_str("<p>");
+                self.write_paragraph(paragraph);
+                self.out.push_str("</p>\n");
+            }
+            ParsedMarkdownElement::HorizontalRule(_) => self.out.push_str("<hr>\n"),
+            ParsedMarkdownElement::Image(image) => {
+                self.out.push_str("<p>");
+                self.write_image(image);
+                self.out.push_str("</p>\n");
+            }
+            ParsedMarkdownElement::Alert(alert) => {
+                writeln!(
+                    self.out,
+                    "<div class=\"alert {}\">\n<p class=\"alert-title\">{}</p>",
+                    alert_class(alert.kind),
+                    alert.kind.title()
+                )
+                .ok();
+                for child in &alert.children {
+                    self.write_element(child);
+                }
+                self.out.push_str("</div>\n");
+            }
+            ParsedMarkdownElement::FootnoteDefinition(definition) => {
+                let anchor = footnote_anchor(&definition.label);
+                writeln!(
+                    self.out,
+                    "<div class=\"footnote\" id=\"fn-{anchor}\">\
+                     <span class=\"footnote-number\">{}.</span><div>",
+                    definition.number
+                )
+                .ok();
+                for child in &definition.children {
+                    self.write_element(child);
+                }
+                self.out.push_str("</div>");
+                if definition.reference_offset.is_some() {
+                    write!(
+                        self.out,
+                        "<a class=\"footnote-back-reference\" href=\"#fnref-{anchor}\">↩</a>"
+                    )
+                    .ok();
+                }
+                self.out.push_str("</div>\n");
+            }
+        }
+    }
+
+    fn write_paragraph(&mut self, paragraph: &MarkdownParagraph) {
+        for chunk in paragraph {
+            match chunk {
+                MarkdownParagraphChunk::Text(text) => self.write_text(text),
+                MarkdownParagraphChunk::Image(image) => self.write_image(image),
+                MarkdownParagraphChunk::Math(math) => {
+                    self.out.push_str(&math.node.to_mathml(true));
+                }
+            }
+        }
+    }
+
+    fn write_text(&mut self, text: &ParsedMarkdownText) {
+        let contents = text.contents.as_ref();
+        let mut boundaries = vec![0, contents.len()];
+        for range in text
+            .highlights
+            .iter()
+            .map(|(range, _)| range)
+            .chain(text.regions.iter().map(|(range, _)| range))
+        {
+            boundaries.push(range.start.min(contents.len()));
+            boundaries.push(range.end.min(contents.len()));
+        }
+        boundaries.sort_unstable();
+        boundaries.dedup();
+
+        let mut position = 0;
+        for segment in boundaries.windows(2) {
+            let (start, end) = (segment[0], segment[1]);
+            if start < position {
+                continue;
+            }
+            let Some(segment_text) = contents.get(start..end) else {
+                continue;
+            };
+            let region = text
+                .regions
+                .iter()
+                .find(|(range, _)| range.start <= start && end <= range.end);
+
+            if let Some((range, region)) = region
+                && let Some(math) = &region.math
+            {
+                self.out.push_str(&math.to_mathml(false));
+                position = range.end;
+                continue;
+            }
+            position = end;
+
+            let style = text
+                .highlights
+                .iter()
+                .filter(|(range, _)| range.start <= start && end <= range.end)
+                .filter_map(|(_, highlight)| highlight.to_highlight_style(&self.theme.syntax_theme))
+                .map(|style| highlight_css(&style))
+                .collect::<String>();
+            let link = region.and_then(|(_, region)| region.link.as_ref());
+            let code = region.is_some_and(|(_, region)| region.code);
+
+            if let Some(link) = link {
+                self.write_link_start(link);
+            }
+            if code {
+                self.out.push_str("<code>");
+            }
+            if !style.is_empty() {
+                write!(self.out, "<span style=\"{style}\">").ok();
+            }
+            self.out
+                .push_str(&escape_html(segment_text).replace('\n', "<br>\n"));
+            if !style.is_empty() {
+                self.out.push_str("</span>");
+            }
+            if code {
+                self.out.push_str("</code>");
+            }
+            if link.is_some() {
+                self.out.push_str("</a>");
+            }
+        }
+    }
+
+    fn write_link_start(&mut self, link: &Link) {
+        match link {
+            Link::Web { url } => {
+                write!(self.out, "<a href=\"{}\">", escape_html(url)).ok();
+            }
+            Link::Path { display_path, .. } => {
+                write!(
+                    self.out,
+                    "<a href=\"{}\">",
+                    escape_html(&display_path.to_string_lossy())
+                )
+                .ok();
+            }
+            Link::Footnote {
+                label,
+                is_back_reference,
+            } => {
+                let anchor = footnote_anchor(label);
+                if *is_back_reference {
+                    write!(self.out, "<a href=\"#fnref-{anchor}\">").ok();
+                } else if self.footnote_references.insert(label.clone()) {
+                    write!(
+                        self.out,
+                        "<a class=\"footnote-reference\" id=\"fnref-{anchor}\" href=\"#fn-{anchor}\">"
+                    )
+                    .ok();
+                } else {
+                    write!(
+                        self.out,
+                        "<a class=\"footnote-reference\" href=\"#fn-{anchor}\">"
+                    )
+                    .ok();
+                }
+            }
+        }
+    }
+
+    fn write_image(&mut self, image: &Image) {
+        let source = match &image.link {
+            // Remote images aren't fetched, so the page loads them when it is viewed.
+            Link::Web { url } => url.clone(),
+            Link::Path { path, display_path } => self
+                .images
+                .get(path)
+                .cloned()
+                .unwrap_or_else(|| display_path.to_string_lossy().into_owned()),
+            Link::Footnote { .. } => return,
+        };
+        write!(self.out, "<img src=\"{}\"", escape_html(&source)).ok();
+        if let Some(alt_text) = &image.alt_text {
+            write!(self.out, " alt=\"{}\"", escape_html(alt_text)).ok();
+        }
+        let mut style = String::new();
+        if let Some(width) = image.width {
+            write!(style, "width: {};", css_length(width)).ok();
+        }
+        if let Some(height) = image.height {
+            write!(style, "height: {};", css_length(height)).ok();
+        }
+        if !style.is_empty() {
+            write!(self.out, " style=\"{style}\"").ok();
+        }
+        self.out.push('>');
+    }
+
+    fn write_list_item(&mut self, list_item: &ParsedMarkdownListItem) {
+        let indent = list_item.depth.saturating_sub(1) as f32 * 1.5;
+        write!(
+            self.out,
+            "<div class=\"list-item\" style=\"margin-left: {indent}em\"><span>"
+        )
+        .ok();
+        match &list_item.item_type {
+            ParsedMarkdownListItemType::Ordered(order) => {
+                write!(self.out, "{order}.").ok();
+            }
+            ParsedMarkdownListItemType::Task(checked, _) => {
+                let checked = if *checked { " checked" } else { "" };
+                write!(self.out, "<input type=\"checkbox\" disabled{checked}>").ok();
+            }
+            ParsedMarkdownListItemType::Unordered => self.out.push('•'),
+        }
+        self.out.push_str("</span><div>\n");
+        for child in &list_item.content {
+            self.write_element(child);
+        }
+        self.out.push_str("</div></div>\n");
+    }
+
+    fn write_table(&mut self, table: &ParsedMarkdownTable) {
+        self.out.push_str("<table>\n");
+        if let Some(caption) = &table.caption {
+            self.out.push_str("<caption>");
+            self.write_paragraph(caption);
+            self.out.push_str("</caption>\n");
+        }
+        for (rows, section) in [(&table.header, "thead"), (&table.body, "tbody")] {
+            if rows.is_empty() {
+                continue;
+            }
+            writeln!(self.out, "<{section}>").ok();
+            for row in rows {
+                self.out.push_str("<tr>");
+                for column in &row.columns {
+                    let tag = if column.is_header { "th" } else { "td" };
+                    write!(self.out, "<{tag}").ok();
+                    if column.col_span > 1 {
+                        write!(self.out, " colspan=\"{}\"", column.col_span).ok();
+                    }
+                    if column.row_span > 1 {
+                        write!(self.out, " rowspan=\"{}\"", column.row_span).ok();
+                    }
+                    let alignment = match column.alignment {
+                        ParsedMarkdownTableAlignment::None => None,
+                        ParsedMarkdownTableAlignment::Left => Some("left"),
+                        ParsedMarkdownTableAlignment::Center => Some("center"),
+                        ParsedMarkdownTableAlignment::Right => Some("right"),
+                    };
+                    if let Some(alignment) = alignment {
+                        write!(self.out, " style=\"text-align: {alignment}\"").ok();
+                    }
+                    self.out.push('>');
+                    self.write_paragraph(&column.children);
+                    write!(self.out, "</{tag}>").ok();
+                }
+                self.out.push_str("</tr>\n");
+            }
+            writeln!(self.out, "</{section}>").ok();
+        }
+        self.out.push_str("</table>\n");
+    }
+
+    fn write_code_block(&mut self, code_block: &ParsedMarkdownCodeBlock) {
+        self.out.push_str("<pre><code>");
+        let contents = code_block.contents.as_ref();
+        let mut position = 0;
+        for (range, highlight_id) in code_block.highlights.iter().flatten() {
+            let (Some(before), Some(highlighted)) = (
+                contents.get(position..range.start),
+                contents.get(range.clone()),
+            ) else {
+                continue;
+            };
+            self.out.push_str(&escape_html(before));
+            match highlight_id.style(&self.theme.syntax_theme) {
+                Some(style) => {
+                    write!(
+                        self.out,
+                        "<span style=\"{}\">{}</span>",
+                        highlight_css(&style),
+                        escape_html(highlighted)
+                    )
+                    .ok();
+                }
+                None => self.out.push_str(&escape_html(highlighted)),
+            }
+            position = range.end;
+        }
+        self.out
+            .push_str(&escape_html(contents.get(position..).unwrap_or_default()));
+        self.out.push_str("</code></pre>\n");
+    }
+}
+
+fn alert_class(kind: AlertKind) -> &'static str {
+    match kind {
+        AlertKind::Note => "alert-note",
+        AlertKind::Tip => "alert-tip",
+        AlertKind::Important => "alert-important",
+        AlertKind::Warning => "alert-warning",
+        AlertKind::Caution => "alert-caution",
+    }
+}
+
+/// Returns a footnote label that can be used in an HTML `id`.
+fn footnote_anchor(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn collect_image_paths(element: &ParsedMarkdownElement, paths: &mut Vec<PathBuf>) {
+    let collect_from_paragraph = |paragraph: &MarkdownParagraph, paths: &mut Vec<PathBuf>| {
+        for chunk in paragraph {
+            if let MarkdownParagraphChunk::Image(image) = chunk {
+                collect_image_path(image, paths);
+            }
+        }
+    };
+    match element {
+        ParsedMarkdownElement::Heading(heading) => collect_from_paragraph(&heading.contents, paths),
+        ParsedMarkdownElement::ListItem(list_item) => {
+            for child in &list_item.content {
+                collect_image_paths(child, paths);
+            }
+        }
+        ParsedMarkdownElement::Table(table) => {
+            if let Some(caption) = &table.caption {
+                collect_from_paragraph(caption, paths);
+            }
+            for row in table.header.iter().chain(&table.body) {
+                for column in &row.columns {
+                    collect_from_paragraph(&column.children, paths);
+                }
+            }
+        }
+        ParsedMarkdownElement::BlockQuote(block_quote) => {
+            for child in &block_quote.children {
+                collect_image_paths(child, paths);
+            }
+        }
+        ParsedMarkdownElement::Paragraph(paragraph) => collect_from_paragraph(paragraph, paths),
+        ParsedMarkdownElement::Image(image) => collect_image_path(image, paths),
+        ParsedMarkdownElement::Alert(alert) => {
+            for child in &alert.children {
+                collect_image_paths(child, paths);
+            }
+        }
+        ParsedMarkdownElement::FootnoteDefinition(definition) => {
+            for child in &definition.children {
+                collect_image_paths(child, paths);
+            }
+        }
+        ParsedMarkdownElement::CodeBlock(_)
+        | ParsedMarkdownElement::MermaidDiagram(_)
+        | ParsedMarkdownElement::HorizontalRule(_) => {}
+    }
+}
+
+fn collect_image_path(image: &Image, paths: &mut Vec<PathBuf>) {
+    if let Link::Path { path, .. } = &image.link {
+        paths.push(path.clone());
+    }
+}
+
+/// Reads a local image into a `data:` URI, so that the exported page doesn't depend on it.
+async fn embedded_image(path: &Path, fs: &dyn Fs) -> Option<String> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let mime_type = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        "tif" | "tiff" => "image/tiff",
+        _ => return None,
+    };
+    let bytes = fs
+        .load_bytes(path)
+        .await
+        .inspect_err(|error| log::warn!("failed to embed image {path:?}: {error}"))
+        .ok()?;
+    Some(format!(
+        "data:{mime_type};base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
+
+fn css_length(length: DefiniteLength) -> String {
+    match length {
+        DefiniteLength::Absolute(AbsoluteLength::Pixels(pixels)) => {
+            format!("{}px", f32::from(pixels))
+        }
+        DefiniteLength::Absolute(AbsoluteLength::Rems(rems)) => format!("{}rem", rems.0),
+        DefiniteLength::Fraction(fraction) => format!("{}%", fraction * 100.),
+    }
+}
+
+fn css_color(color: Hsla) -> String {
+    let color = Rgba::from(color);
+    let channel = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}{:02x}",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        channel(color.a)
+    )
+}
+
+fn highlight_css(style: &HighlightStyle) -> String {
+    let mut css = String::new();
+    if let Some(color) = style.color {
+        write!(css, "color: {};", css_color(color)).ok();
+    }
+    if let Some(background_color) = style.background_color {
+        write!(css, "background: {};", css_color(background_color)).ok();
+    }
+    if let Some(font_weight) = style.font_weight {
+        write!(css, "font-weight: {};", font_weight.0).ok();
+    }
+    match style.font_style {
+        Some(FontStyle::Italic) => css.push_str("font-style: italic;"),
+        Some(FontStyle::Oblique) => css.push_str("font-style: oblique;"),
+        Some(FontStyle::Normal) | None => {}
+    }
+    match (style.underline.is_some(), style.strikethrough.is_some()) {
+        (true, true) => css.push_str("text-decoration: underline line-through;"),
+        (true, false) => css.push_str("text-decoration: underline;"),
+        (false, true) => css.push_str("text-decoration: line-through;"),
+        (false, false) => {}
+    }
+    css
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_parser::parse_markdown;
+    use fs::FakeFs;
+    use serde_json::json;
+    use util::path;
+
+    fn test_theme() -> HtmlTheme {
+        HtmlTheme {
+            font_family: "Sans".into(),
+            code_font_family: "Mono".into(),
+            background: gpui::white(),
+            text: gpui::black(),
+            text_muted: gpui::black(),
+            link: gpui::blue(),
+            border: gpui::black(),
+            code_block_background: gpui::white(),
+            code_span_background: gpui::white(),
+            status_colors: StatusColors::dark(),
+            syntax_theme: Arc::new(SyntaxTheme::default()),
+        }
+    }
+
+    #[gpui::test]
+    async fn test_render_html() {
+        let parsed = parse_markdown(
+            "# Title\n\nSome **bold** text with $x^2$ and a note[^1].\n\n\
+             > [!WARNING]\n> Careful & slow.\n\n[^1]: The note.\n",
+            None,
+            None,
+        )
+        .await;
+        let html = render_html(&parsed, "Doc", &test_theme(), &HashMap::default());
+        let body = html
+            .split_once("<main>\n")
+            .and_then(|(_, body)| body.split_once("</main>"))
+            .map(|(body, _)| body)
+            .unwrap();
+        assert_eq!(
+            body,
+            "<h1>Title</h1>\n\
+             <p>Some <span style=\"font-weight: 700;\">bold</span> text with \
+             <math><msup><mi>x</mi><mn>2</mn></msup></math> and a note\
+             <a class=\"footnote-reference\" id=\"fnref-1\" href=\"#fn-1\">\
+             <span style=\"text-decoration: underline;\">¹</span></a>.</p>\n\
+             <div class=\"alert alert-warning\">\n<p class=\"alert-title\">Warning</p>\n\
+             <p>Careful &amp; slow.</p>\n</div>\n\
+             <div class=\"footnote\" id=\"fn-1\"><span class=\"footnote-number\">1.</span><div>\n\
+             <p>The note.</p>\n</div>\
+             <a class=\"footnote-back-reference\" href=\"#fnref-1\">↩</a></div>\n"
+        );
+        assert!(html.contains("<title>Doc</title>"));
+    }
+
+    #[gpui::test]
+    async fn test_embedded_images(cx: &mut gpui::TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/docs"),
+            json!({
+                "logo.png": "png",
+                "notes.txt": "text",
+            }),
+        )
+        .await;
+        let image = |path: &str, alt_text: &str| {
+            MarkdownParagraphChunk::Image(Image {
+                link: Link::Path {
+                    display_path: PathBuf::from(path),
+                    path: PathBuf::from(path!("/docs")).join(path),
+                },
+                source_range: 0..0,
+                alt_text: Some(alt_text.to_string().into()),
+                width: None,
+                height: None,
+            })
+        };
+        let parsed = ParsedMarkdown {
+            children: vec![ParsedMarkdownElement::Paragraph(vec![
+                image("logo.png", "Logo"),
+                image("missing.png", "Missing"),
+                image("notes.txt", "Notes"),
+            ])],
+        };
+        let images = load_embedded_images(&parsed, fs.as_ref()).await;
+        assert_eq!(
+            images,
+            HashMap::from_iter([(
+                PathBuf::from(path!("/docs/logo.png")),
+                "data:image/png;base64,cG5n".to_string()
+            )])
+        );
+
+        let html = render_html(&parsed, "Doc", &test_theme(), &images);
+        assert!(html.contains("<img src=\"data:image/png;base64,cG5n\" alt=\"Logo\">"));
+        assert!(html.contains("<img src=\"missing.png\" alt=\"Missing\">"));
+    }
+
+    #[test]
+    fn test_footnote_anchor() {
+        assert_eq!(footnote_anchor("my note"), "my-note");
+        assert_eq!(footnote_anchor("a\"b"), "a-b");
+    }
+}
@@ -0,0 +1,1477 @@
+//! Parses the subset of LaTeX math commonly found in Markdown documents, such as
+//! `$e^{i\pi} + 1 = 0$`, into a tree of [`MathNode`]s.
+//!
+//! Inline math is rendered as Unicode text, so that it flows with the surrounding paragraph,
+//! display math is laid out natively by the renderer, and HTML exports use MathML.
+
+use std::ops::Range;
+
+use crate::markdown_html::escape_html;
+
+/// How deeply groups and commands may nest before the rest of the group is kept as literal text.
+/// This bounds the depth of the parsed tree, so that neither parsing nor rendering it can
+/// overflow the stack.
+const MAX_DEPTH: usize = 100;
+
+/// A node of a parsed math expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MathNode {
+    /// A variable, such as `x` or `\alpha`.
+    Identifier(String),
+    /// A number, such as `3.14`.
+    Number(String),
+    /// An operator, relation, delimiter or punctuation mark.
+    Operator(String),
+    /// A big operator, such as `\sum` or `\lim`.
+    LargeOperator {
+        symbol: String,
+        /// Whether scripts are placed above and below the operator, rather than beside it.
+        limits: bool,
+    },
+    /// A function name, such as `\sin`, shown upright.
+    Function(String),
+    /// Text from `\text{...}`, shown upright.
+    Text(String),
+    /// Horizontal space, in ems.
+    Space(f32),
+    Row(Vec<MathNode>),
+    Fraction {
+        numerator: Box<MathNode>,
+        denominator: Box<MathNode>,
+    },
+    Scripts {
+        base: Box<MathNode>,
+        subscript: Option<Box<MathNode>>,
+        superscript: Option<Box<MathNode>>,
+    },
+    Root {
+        index: Option<Box<MathNode>>,
+        radicand: Box<MathNode>,
+    },
+    Accent {
+        base: Box<MathNode>,
+        accent: MathAccent,
+    },
+    /// A matrix or another alignment environment, such as `cases`.
+    Matrix {
+        rows: Vec<Vec<MathNode>>,
+        /// The opening delimiter, or an empty string if there is none.
+        left: String,
+        /// The closing delimiter, or an empty string if there is none.
+        right: String,
+        /// Whether the cells are left-aligned rather than centered.
+        left_aligned: bool,
+    },
+}
+
+/// A mark placed over or under a [`MathNode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathAccent {
+    Hat,
+    Bar,
+    Vector,
+    Dot,
+    DoubleDot,
+    Tilde,
+    Underline,
+}
+
+impl MathAccent {
+    /// The Unicode combining character for this accent.
+    fn combining_char(self) -> char {
+        match self {
+            Self::Hat => '\u{302}',
+            Self::Bar => '\u{305}',
+            Self::Vector => '\u{20D7}',
+            Self::Dot => '\u{307}',
+            Self::DoubleDot => '\u{308}',
+            Self::Tilde => '\u{303}',
+            Self::Underline => '\u{332}',
+        }
+    }
+
+    /// The character drawn above (or below) the accented node.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Hat => "^",
+            Self::Bar => "‾",
+            Self::Vector => "→",
+            Self::Dot => "˙",
+            Self::DoubleDot => "¨",
+            Self::Tilde => "~",
+            Self::Underline => "_",
+        }
+    }
+
+    pub fn is_under(self) -> bool {
+        self == Self::Underline
+    }
+}
+
+/// Parses a LaTeX math expression.
+///
+/// Parsing never fails: unknown commands are kept verbatim as text, and unbalanced braces are
+/// closed at the end of the input.
+pub fn parse_math(source: &str) -> MathNode {
+    MathParser::new(source, 0).parse()
+}
+
+fn row(mut nodes: Vec<MathNode>) -> MathNode {
+    if nodes.len() == 1 {
+        nodes.pop().unwrap()
+    } else {
+        MathNode::Row(nodes)
+    }
+}
+
+struct MathParser {
+    chars: Vec<char>,
+    position: usize,
+    /// The number of groups and commands the cursor is nested in.
+    depth: usize,
+}
+
+impl MathParser {
+    fn new(source: &str, depth: usize) -> Self {
+        Self {
+            chars: source.chars().collect(),
+            position: 0,
+            depth,
+        }
+    }
+
+    fn parse(mut self) -> MathNode {
+        let mut nodes = Vec::new();
+        loop {
+            nodes.extend(self.parse_row());
+            if self.peek().is_none() {
+                break;
+            }
+            self.skip_separator();
+        }
+        row(nodes)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    /// Returns the name of the command at the cursor, without consuming it.
+    fn peek_command(&self) -> Option<String> {
+        if self.peek() != Some('\\') {
+            return None;
+        }
+        let first = self.peek_at(1)?;
+        if !first.is_ascii_alphabetic() {
+            return Some(first.to_string());
+        }
+        Some(
+            self.chars[self.position + 1..]
+                .iter()
+                .take_while(|c| c.is_ascii_alphabetic())
+                .collect(),
+        )
+    }
+
+    fn read_command(&mut self) -> Option<String> {
+        let name = self.peek_command()?;
+        self.position += 1 + name.chars().count();
+        Some(name)
+    }
+
+    /// Whether the cursor is at the end of a row: the end of a group, a cell, a line or an
+    /// environment.
+    fn at_row_end(&self) -> bool {
+        match self.peek() {
+            None | Some('}') | Some('&') => true,
+            Some('\\') => matches!(self.peek_command().as_deref(), Some("\\" | "end")),
+            _ => false,
+        }
+    }
+
+    /// Skips the token ending a row, when it doesn't belong to an enclosing group.
+    fn skip_separator(&mut self) {
+        match self.peek() {
+            Some('\\') => {
+                if self.read_command().as_deref() == Some("end") {
+                    self.read_raw_group();
+                }
+            }
+            Some(_) => self.position += 1,
+            None => {}
+        }
+    }
+
+    fn parse_row(&mut self) -> Vec<MathNode> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.at_row_end() {
+                break;
+            }
+            let atom = if matches!(self.peek(), Some('^' | '_' | '\'')) {
+                MathNode::Row(Vec::new())
+            } else {
+                match self.parse_atom() {
+                    Some(atom) => atom,
+                    None => continue,
+                }
+            };
+            nodes.push(self.parse_scripts(atom));
+        }
+        nodes
+    }
+
+    /// Parses the contents of a group whose opening brace has been consumed.
+    fn parse_group(&mut self) -> MathNode {
+        let group = if self.depth >= MAX_DEPTH {
+            MathNode::Text(self.read_rest_of_group())
+        } else {
+            self.depth += 1;
+            let mut nodes = Vec::new();
+            loop {
+                nodes.extend(self.parse_row());
+                match self.peek() {
+                    None | Some('}') => break,
+                    _ => self.skip_separator(),
+                }
+            }
+            self.depth -= 1;
+            row(nodes)
+        };
+        if self.peek() == Some('}') {
+            self.position += 1;
+        }
+        group
+    }
+
+    fn parse_scripts(&mut self, base: MathNode) -> MathNode {
+        let mut subscript = None;
+        let mut superscript: Option<MathNode> = None;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('^') => {
+                    self.position += 1;
+                    let script = self.parse_argument();
+                    superscript = Some(match superscript {
+                        Some(primes) => MathNode::Row(vec![primes, script]),
+                        None => script,
+                    });
+                }
+                Some('_') => {
+                    self.position += 1;
+                    subscript = Some(self.parse_argument());
+                }
+                Some('\'') => {
+                    let mut primes = String::new();
+                    while self.peek() == Some('\'') {
+                        self.position += 1;
+                        primes.push('′');
+                    }
+                    superscript = Some(MathNode::Operator(primes));
+                }
+                _ => break,
+            }
+        }
+        if subscript.is_none() && superscript.is_none() {
+            return base;
+        }
+        MathNode::Scripts {
+            base: Box::new(base),
+            subscript: subscript.map(Box::new),
+            superscript: superscript.map(Box::new),
+        }
+    }
+
+    /// Parses the argument of a command or script: a group, a command, or a single character.
+    fn parse_argument(&mut self) -> MathNode {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => {
+                self.position += 1;
+                self.parse_group()
+            }
+            Some('\\') => self.parse_command().unwrap_or(MathNode::Row(Vec::new())),
+            Some(c) => {
+                self.position += 1;
+                char_atom(c)
+            }
+            None => MathNode::Row(Vec::new()),
+        }
+    }
+
+    /// Reads the contents of a group verbatim, or a single character if there is no group.
+    fn read_raw_group(&mut self) -> String {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => {
+                self.position += 1;
+                let mut depth = 1;
+                let mut text = String::new();
+                while let Some(c) = self.peek() {
+                    self.position += 1;
+                    match c {
+                        '{' => depth += 1,
+                        '}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    text.push(c);
+                }
+                text
+            }
+            Some(c) => {
+                self.position += 1;
+                c.to_string()
+            }
+            None => String::new(),
+        }
+    }
+
+    /// Reads the rest of the current group verbatim, up to but excluding its closing brace.
+    fn read_rest_of_group(&mut self) -> String {
+        let start = self.position;
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn parse_atom(&mut self) -> Option<MathNode> {
+        let c = self.peek()?;
+        match c {
+            '{' => {
+                self.position += 1;
+                Some(self.parse_group())
+            }
+            '\\' => self.parse_command(),
+            c if c.is_ascii_digit()
+                || (c == '.' && self.peek_at(1).is_some_and(|c| c.is_ascii_digit())) =>
+            {
+                let mut number = String::new();
+                while let Some(c) = self.peek() {
+                    let is_decimal_point =
+                        c == '.' && self.peek_at(1).is_some_and(|c| c.is_ascii_digit());
+                    if !c.is_ascii_digit() && !is_decimal_point {
+                        break;
+                    }
+                    number.push(c);
+                    self.position += 1;
+                }
+                Some(MathNode::Number(number))
+            }
+            c => {
+                self.position += 1;
+                Some(char_atom(c))
+            }
+        }
+    }
+
+    fn parse_command(&mut self) -> Option<MathNode> {
+        if self.depth >= MAX_DEPTH {
+            return Some(MathNode::Text(self.read_rest_of_group()));
+        }
+        self.depth += 1;
+        let node = self.parse_command_arguments();
+        self.depth -= 1;
+        node
+    }
+
+    /// Parses a command at the cursor along with its arguments.
+    fn parse_command_arguments(&mut self) -> Option<MathNode> {
+        let Some(name) = self.read_command() else {
+            self.position += 1;
+            return Some(MathNode::Operator("\\".into()));
+        };
+        let node = match name.as_str() {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => MathNode::Fraction {
+                numerator: Box::new(self.parse_argument()),
+                denominator: Box::new(self.parse_argument()),
+            },
+            "binom" | "dbinom" | "tbinom" => MathNode::Matrix {
+                rows: vec![vec![self.parse_argument()], vec![self.parse_argument()]],
+                left: "(".into(),
+                right: ")".into(),
+                left_aligned: false,
+            },
+            "sqrt" => {
+                self.skip_whitespace();
+                let index = if self.peek() == Some('[') {
+                    self.position += 1;
+                    let mut source = String::new();
+                    while let Some(c) = self.peek() {
+                        self.position += 1;
+                        if c == ']' {
+                            break;
+                        }
+                        source.push(c);
+                    }
+                    Some(Box::new(MathParser::new(&source, self.depth).parse()))
+                } else {
+                    None
+                };
+                MathNode::Root {
+                    index,
+                    radicand: Box::new(self.parse_argument()),
+                }
+            }
+            "text" | "textrm" | "textit" | "textnormal" | "mbox" | "mathrm" | "rm" => {
+                MathNode::Text(self.read_raw_group())
+            }
+            "textbf" => MathNode::Text(map_alphanumerics(&self.read_raw_group(), bold_char)),
+            "operatorname" => MathNode::Function(self.read_raw_group()),
+            "mathit" => self.parse_argument(),
+            "mathbf" | "boldsymbol" | "bm" => {
+                let argument = self.parse_argument();
+                map_node(argument, bold_char)
+            }
+            "mathbb" => {
+                let argument = self.parse_argument();
+                map_node(argument, double_struck_char)
+            }
+            "mathcal" | "mathscr" => {
+                let argument = self.parse_argument();
+                map_node(argument, script_char)
+            }
+            "mathfrak" => {
+                let argument = self.parse_argument();
+                map_node(argument, fraktur_char)
+            }
+            "left" | "right" | "middle" | "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr"
+            | "Bigl" | "Bigr" | "biggl" | "biggr" | "Biggl" | "Biggr" => {
+                // Delimiters are sized by the layout, so only the delimiter itself is kept.
+                self.skip_whitespace();
+                if self.peek() == Some('.') {
+                    self.position += 1;
+                }
+                return None;
+            }
+            "displaystyle" | "textstyle" | "scriptstyle" | "limits" | "nolimits" | "nonumber"
+            | "notag" | "!" => return None,
+            "," => MathNode::Space(0.17),
+            ":" | ">" => MathNode::Space(0.22),
+            ";" => MathNode::Space(0.28),
+            " " => MathNode::Space(0.33),
+            "quad" => MathNode::Space(1.),
+            "qquad" => MathNode::Space(2.),
+            "hat" | "widehat" => self.parse_accent(MathAccent::Hat),
+            "bar" | "overline" => self.parse_accent(MathAccent::Bar),
+            "vec" | "overrightarrow" => self.parse_accent(MathAccent::Vector),
+            "dot" => self.parse_accent(MathAccent::Dot),
+            "ddot" => self.parse_accent(MathAccent::DoubleDot),
+            "tilde" | "widetilde" => self.parse_accent(MathAccent::Tilde),
+            "underline" => self.parse_accent(MathAccent::Underline),
+            "not" => match self.parse_argument() {
+                MathNode::Operator(operator) => MathNode::Operator(format!("{operator}\u{338}")),
+                node => node,
+            },
+            "begin" => self.parse_environment(),
+            symbol @ ("{" | "}" | "|" | "%" | "$" | "#" | "&" | "_") => {
+                MathNode::Operator(symbol.into())
+            }
+            name => {
+                if let Some(symbol) = ordinary_symbol(name) {
+                    MathNode::Identifier(symbol.into())
+                } else if let Some(symbol) = operator_symbol(name) {
+                    MathNode::Operator(symbol.into())
+                } else if let Some((symbol, limits)) = large_operator(name) {
+                    MathNode::LargeOperator {
+                        symbol: symbol.into(),
+                        limits,
+                    }
+                } else if FUNCTIONS.contains(&name) {
+                    MathNode::Function(name.into())
+                } else {
+                    MathNode::Text(format!("\\{name}"))
+                }
+            }
+        };
+        Some(node)
+    }
+
+    fn parse_accent(&mut self, accent: MathAccent) -> MathNode {
+        MathNode::Accent {
+            base: Box::new(self.parse_argument()),
+            accent,
+        }
+    }
+
+    /// Parses a `\begin{...}` environment whose `\begin` has been consumed.
+    fn parse_environment(&mut self) -> MathNode {
+        let name = self.read_raw_group();
+        let name = name.trim_end_matches('*');
+        if name == "array" {
+            // Skip the column specification.
+            self.read_raw_group();
+        }
+        let (left, right, left_aligned) = match name {
+            "pmatrix" => ("(", ")", false),
+            "bmatrix" => ("[", "]", false),
+            "Bmatrix" => ("{", "}", false),
+            "vmatrix" => ("|", "|", false),
+            "Vmatrix" => ("‖", "‖", false),
+            "cases" => ("{", "", true),
+            "aligned" | "align" | "alignat" | "split" | "gathered" | "gather" | "eqnarray" => {
+                ("", "", true)
+            }
+            _ => ("", "", false),
+        };
+
+        let mut rows = Vec::new();
+        let mut cells = Vec::new();
+        loop {
+            cells.push(row(self.parse_row()));
+            match self.peek() {
+                Some('&' | '}') => self.position += 1,
+                Some('\\') => {
+                    if self.read_command().as_deref() == Some("end") {
+                        self.read_raw_group();
+                        break;
+                    }
+                    rows.push(std::mem::take(&mut cells));
+                }
+                _ => break,
+            }
+        }
+        // Ignore the empty row after a trailing `\\`.
+        if cells.iter().any(|cell| *cell != MathNode::Row(Vec::new())) {
+            rows.push(cells);
+        }
+
+        MathNode::Matrix {
+            rows,
+            left: left.into(),
+            right: right.into(),
+            left_aligned,
+        }
+    }
+}
+
+fn char_atom(c: char) -> MathNode {
+    match c {
+        c if c.is_ascii_digit() => MathNode::Number(c.to_string()),
+        c if c.is_alphabetic() => MathNode::Identifier(c.to_string()),
+        '-' => MathNode::Operator("−".into()),
+        '*' => MathNode::Operator("∗".into()),
+        c => MathNode::Operator(c.to_string()),
+    }
+}
+
+/// Symbols that behave like variables.
+fn ordinary_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "omicron" => "ο",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "emptyset" | "varnothing" => "∅",
+        "ell" => "ℓ",
+        "hbar" => "ℏ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        "forall" => "∀",
+        "exists" => "∃",
+        "neg" | "lnot" => "¬",
+        "angle" => "∠",
+        "top" => "⊤",
+        "bot" => "⊥",
+        "prime" => "′",
+        "degree" => "°",
+        "ldots" | "dots" | "dotsc" => "…",
+        "cdots" | "dotsb" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        _ => return None,
+    })
+}
+
+/// Symbols that behave like operators, relations and delimiters.
+fn operator_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "times" => "×",
+        "cdot" => "⋅",
+        "pm" => "±",
+        "mp" => "∓",
+        "div" => "÷",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "bullet" => "•",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "setminus" => "∖",
+        "cup" => "∪",
+        "cap" => "∩",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "ll" => "≪",
+        "gg" => "≫",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "mid" => "∣",
+        "models" => "⊨",
+        "vdash" => "⊢",
+        "to" | "rightarrow" => "→",
+        "gets" | "leftarrow" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" => "⇔",
+        "implies" | "Longrightarrow" => "⟹",
+        "impliedby" | "Longleftarrow" => "⟸",
+        "iff" | "Longleftrightarrow" => "⟺",
+        "longrightarrow" => "⟶",
+        "longleftarrow" => "⟵",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "vert" | "lvert" | "rvert" => "|",
+        "Vert" | "lVert" | "rVert" | "|" => "‖",
+        "lbrace" => "{",
+        "rbrace" => "}",
+        "colon" => ":",
+        _ => return None,
+    })
+}
+
+/// Big operators, and whether their scripts are placed above and below them.
+fn large_operator(name: &str) -> Option<(&'static str, bool)> {
+    Some(match name {
+        "sum" => ("∑", true),
+        "prod" => ("∏", true),
+        "coprod" => ("∐", true),
+        "bigcup" => ("⋃", true),
+        "bigcap" => ("⋂", true),
+        "bigoplus" => ("⨁", true),
+        "bigotimes" => ("⨂", true),
+        "bigvee" => ("⋁", true),
+        "bigwedge" => ("⋀", true),
+        "int" => ("∫", false),
+        "iint" => ("∬", false),
+        "iiint" => ("∭", false),
+        "oint" => ("∮", false),
+        "lim" => ("lim", true),
+        "liminf" => ("lim inf", true),
+        "limsup" => ("lim sup", true),
+        "max" => ("max", true),
+        "min" => ("min", true),
+        "sup" => ("sup", true),
+        "inf" => ("inf", true),
+        "det" => ("det", true),
+        "gcd" => ("gcd", true),
+        "Pr" => ("Pr", true),
+        "argmax" => ("arg max", true),
+        "argmin" => ("arg min", true),
+        _ => return None,
+    })
+}
+
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "coth", "log", "ln", "lg", "exp", "arg", "deg", "dim", "hom", "ker", "mod", "bmod",
+];
+
+fn map_node(node: MathNode, map: fn(char) -> Option<char>) -> MathNode {
+    match node {
+        MathNode::Identifier(text) => MathNode::Identifier(map_alphanumerics(&text, map)),
+        MathNode::Number(text) => MathNode::Number(map_alphanumerics(&text, map)),
+        MathNode::Text(text) => MathNode::Text(map_alphanumerics(&text, map)),
+        MathNode::Row(nodes) => {
+            MathNode::Row(nodes.into_iter().map(|node| map_node(node, map)).collect())
+        }
+        MathNode::Scripts {
+            base,
+            subscript,
+            superscript,
+        } => MathNode::Scripts {
+            base: Box::new(map_node(*base, map)),
+            subscript,
+            superscript,
+        },
+        MathNode::Accent { base, accent } => MathNode::Accent {
+            base: Box::new(map_node(*base, map)),
+            accent,
+        },
+        node => node,
+    }
+}
+
+fn map_alphanumerics(text: &str, map: fn(char) -> Option<char>) -> String {
+    text.chars().map(|c| map(c).unwrap_or(c)).collect()
+}
+
+fn offset_char(c: char, upper: u32, lower: Option<u32>, digit: Option<u32>) -> Option<char> {
+    let code = match c {
+        'A'..='Z' => upper + (c as u32 - 'A' as u32),
+        'a'..='z' => lower? + (c as u32 - 'a' as u32),
+        '0'..='9' => digit? + (c as u32 - '0' as u32),
+        _ => return None,
+    };
+    char::from_u32(code)
+}
+
+fn bold_char(c: char) -> Option<char> {
+    offset_char(c, 0x1D400, Some(0x1D41A), Some(0x1D7CE))
+}
+
+fn double_struck_char(c: char) -> Option<char> {
+    // Some letters were encoded in the Letterlike Symbols block before the mathematical
+    // alphanumerics, which leave holes for them.
+    Some(match c {
+        'C' => 'ℂ',
+        'H' => 'ℍ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        c => return offset_char(c, 0x1D538, Some(0x1D552), Some(0x1D7D8)),
+    })
+}
+
+fn script_char(c: char) -> Option<char> {
+    Some(match c {
+        'B' => 'ℬ',
+        'E' => 'ℰ',
+        'F' => 'ℱ',
+        'H' => 'ℋ',
+        'I' => 'ℐ',
+        'L' => 'ℒ',
+        'M' => 'ℳ',
+        'R' => 'ℛ',
+        c => return offset_char(c, 0x1D49C, None, None),
+    })
+}
+
+fn fraktur_char(c: char) -> Option<char> {
+    Some(match c {
+        'C' => 'ℭ',
+        'H' => 'ℌ',
+        'I' => 'ℑ',
+        'R' => 'ℜ',
+        'Z' => 'ℨ',
+        c => return offset_char(c, 0x1D504, Some(0x1D51E), None),
+    })
+}
+
+pub(crate) fn superscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '−' | '-' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'a' => 'ᵃ',
+        'b' => 'ᵇ',
+        'c' => 'ᶜ',
+        'd' => 'ᵈ',
+        'e' => 'ᵉ',
+        'f' => 'ᶠ',
+        'g' => 'ᵍ',
+        'h' => 'ʰ',
+        'i' => 'ⁱ',
+        'j' => 'ʲ',
+        'k' => 'ᵏ',
+        'l' => 'ˡ',
+        'm' => 'ᵐ',
+        'n' => 'ⁿ',
+        'o' => 'ᵒ',
+        'p' => 'ᵖ',
+        'r' => 'ʳ',
+        's' => 'ˢ',
+        't' => 'ᵗ',
+        'u' => 'ᵘ',
+        'v' => 'ᵛ',
+        'w' => 'ʷ',
+        'x' => 'ˣ',
+        'y' => 'ʸ',
+        'z' => 'ᶻ',
+        'T' => 'ᵀ',
+        '′' | '*' | '∗' => c,
+        _ => return None,
+    })
+}
+
+fn subscript_char(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '−' | '-' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        _ => return None,
+    })
+}
+
+/// Whether an identifier is shown in italics, which by convention is the case for single Latin
+/// letters and lowercase Greek letters.
+pub fn is_italic_identifier(identifier: &str) -> bool {
+    let mut chars = identifier.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => {
+            c.is_ascii_alphabetic() || ('α'..='ω').contains(&c) || "ϵϑϖϱϕ".contains(c)
+        }
+        _ => false,
+    }
+}
+
+/// Whether an operator is a binary operator or a relation, which are surrounded by space.
+pub fn is_spaced_operator(operator: &str) -> bool {
+    matches!(
+        operator,
+        "+" | "−"
+            | "="
+            | "<"
+            | ">"
+            | "×"
+            | "⋅"
+            | "±"
+            | "∓"
+            | "÷"
+            | "∘"
+            | "⊕"
+            | "⊗"
+            | "∪"
+            | "∩"
+            | "∧"
+            | "∨"
+            | "∖"
+            | "≤"
+            | "≥"
+            | "≠"
+            | "≪"
+            | "≫"
+            | "≈"
+            | "≡"
+            | "∼"
+            | "≃"
+            | "≅"
+            | "∝"
+            | "∈"
+            | "∉"
+            | "∋"
+            | "⊂"
+            | "⊆"
+            | "⊃"
+            | "⊇"
+            | "∣"
+            | "⊨"
+            | "⊢"
+            | "→"
+            | "←"
+            | "↔"
+            | "⇒"
+            | "⇐"
+            | "⇔"
+            | "⟹"
+            | "⟸"
+            | "⟺"
+            | "⟶"
+            | "⟵"
+            | "↦"
+    ) || operator.ends_with('\u{338}')
+}
+
+impl MathNode {
+    /// Renders this node as plain Unicode text, such as `x² + √(y₁)`.
+    ///
+    /// Returns the text along with the ranges that should be shown in italics.
+    pub fn to_inline_text(&self) -> (String, Vec<Range<usize>>) {
+        let mut text = String::new();
+        let mut italics = Vec::new();
+        self.write_text(&mut text, &mut italics);
+        (text, italics)
+    }
+
+    fn write_text(&self, out: &mut String, italics: &mut Vec<Range<usize>>) {
+        match self {
+            MathNode::Identifier(identifier) => {
+                let start = out.len();
+                out.push_str(identifier);
+                if is_italic_identifier(identifier) {
+                    italics.push(start..out.len());
+                }
+            }
+            MathNode::Number(text)
+            | MathNode::Operator(text)
+            | MathNode::Function(text)
+            | MathNode::Text(text) => out.push_str(text),
+            MathNode::LargeOperator { symbol, .. } => out.push_str(symbol),
+            MathNode::Space(width) => {
+                if *width >= 1. {
+                    out.extend(std::iter::repeat_n(' ', width.round() as usize));
+                } else {
+                    out.push('\u{2009}');
+                }
+            }
+            MathNode::Row(nodes) => {
+                for (ix, node) in nodes.iter().enumerate() {
+                    let previous = ix.checked_sub(1).map(|ix| &nodes[ix]);
+                    match node {
+                        MathNode::Operator(operator)
+                            if is_spaced_operator(operator)
+                                && previous.is_some_and(|previous| {
+                                    !matches!(previous, MathNode::Operator(_))
+                                }) =>
+                        {
+                            out.push(' ');
+                            out.push_str(operator);
+                            out.push(' ');
+                        }
+                        _ => {
+                            if previous.is_some_and(MathNode::is_named_operator)
+                                && !matches!(node, MathNode::Operator(_) | MathNode::Space(_))
+                            {
+                                out.push(' ');
+                            }
+                            node.write_text(out, italics);
+                        }
+                    }
+                }
+            }
+            MathNode::Fraction {
+                numerator,
+                denominator,
+            } => {
+                numerator.write_grouped_text(out, italics);
+                out.push('/');
+                denominator.write_grouped_text(out, italics);
+            }
+            MathNode::Scripts {
+                base,
+                subscript,
+                superscript,
+            } => {
+                base.write_grouped_text(out, italics);
+                if let Some(subscript) = subscript {
+                    subscript.write_script_text('_', subscript_char, out, italics);
+                }
+                if let Some(superscript) = superscript {
+                    superscript.write_script_text('^', superscript_char, out, italics);
+                }
+            }
+            MathNode::Root { index, radicand } => {
+                match index.as_deref().map(|index| index.to_inline_text().0) {
+                    Some(index) if index == "3" => out.push('∛'),
+                    Some(index) if index == "4" => out.push('∜'),
+                    Some(index) => {
+                        out.push_str(
+                            &index
+                                .chars()
+                                .map(superscript_char)
+                                .collect::<Option<String>>()
+                                .unwrap_or_else(|| format!("({index})")),
+                        );
+                        out.push('√');
+                    }
+                    None => out.push('√'),
+                }
+                radicand.write_grouped_text(out, italics);
+            }
+            MathNode::Accent { base, accent } => {
+                let (text, base_italics) = base.to_inline_text();
+                let start = out.len();
+                let mut chars = text.chars().peekable();
+                while let Some(c) = chars.next() {
+                    out.push(c);
+                    // Nested accents stack on the same character, after the inner accent's marks.
+                    if !c.is_whitespace() && !chars.peek().copied().is_some_and(is_combining_mark) {
+                        out.push(accent.combining_char());
+                    }
+                }
+                if text.chars().count() == 1 && !base_italics.is_empty() {
+                    italics.push(start..out.len());
+                }
+            }
+            MathNode::Matrix {
+                rows, left, right, ..
+            } => {
+                out.push_str(left);
+                for (row_ix, row) in rows.iter().enumerate() {
+                    if row_ix > 0 {
+                        out.push_str("; ");
+                    }
+                    for (cell_ix, cell) in row.iter().enumerate() {
+                        if cell_ix > 0 {
+                            out.push_str(", ");
+                        }
+                        cell.write_text(out, italics);
+                    }
+                }
+                out.push_str(right);
+            }
+        }
+    }
+
+    /// Writes the node, wrapped in parentheses unless it reads as a single unit.
+    fn write_grouped_text(&self, out: &mut String, italics: &mut Vec<Range<usize>>) {
+        if self.is_atomic() {
+            self.write_text(out, italics);
+        } else {
+            out.push('(');
+            self.write_text(out, italics);
+            out.push(')');
+        }
+    }
+
+    fn write_script_text(
+        &self,
+        marker: char,
+        map: fn(char) -> Option<char>,
+        out: &mut String,
+        italics: &mut Vec<Range<usize>>,
+    ) {
+        let (text, text_italics) = self.to_inline_text();
+        if let Some(mapped) = text
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(map)
+            .collect::<Option<String>>()
+        {
+            out.push_str(&mapped);
+        } else {
+            // Reuse the text rather than writing the node again, which would take time
+            // exponential in the nesting of scripts.
+            out.push(marker);
+            let grouped = !self.is_atomic();
+            if grouped {
+                out.push('(');
+            }
+            let start = out.len();
+            out.push_str(&text);
+            italics.extend(
+                text_italics
+                    .into_iter()
+                    .map(|range| range.start + start..range.end + start),
+            );
+            if grouped {
+                out.push(')');
+            }
+        }
+    }
+
+    fn is_atomic(&self) -> bool {
+        match self {
+            MathNode::Identifier(_)
+            | MathNode::Number(_)
+            | MathNode::Operator(_)
+            | MathNode::LargeOperator { .. }
+            | MathNode::Function(_)
+            | MathNode::Text(_)
+            | MathNode::Root { .. }
+            | MathNode::Accent { .. } => true,
+            MathNode::Matrix { left, .. } => !left.is_empty(),
+            MathNode::Scripts { base, .. } => base.is_atomic(),
+            MathNode::Row(nodes) => nodes.is_empty() || (nodes.len() == 1 && nodes[0].is_atomic()),
+            MathNode::Space(_) | MathNode::Fraction { .. } => false,
+        }
+    }
+
+    /// Whether this is a named operator, such as `sin` or `lim`, which is separated from its
+    /// argument by a space.
+    fn is_named_operator(&self) -> bool {
+        match self {
+            MathNode::Function(_) => true,
+            MathNode::LargeOperator { symbol, .. } => symbol.chars().count() > 1,
+            MathNode::Scripts { base, .. } => base.is_named_operator(),
+            _ => false,
+        }
+    }
+
+    /// Renders this node as a MathML `<math>` element.
+    pub fn to_mathml(&self, display: bool) -> String {
+        let mut out = String::new();
+        out.push_str(if display {
+            r#"<math display="block">"#
+        } else {
+            "<math>"
+        });
+        self.write_mathml(&mut out);
+        out.push_str("</math>");
+        out
+    }
+
+    fn write_mathml(&self, out: &mut String) {
+        match self {
+            MathNode::Identifier(identifier) => {
+                if identifier.chars().count() == 1 && !is_italic_identifier(identifier) {
+                    out.push_str(r#"<mi mathvariant="normal">"#);
+                } else {
+                    out.push_str("<mi>");
+                }
+                out.push_str(&escape_html(identifier));
+                out.push_str("</mi>");
+            }
+            MathNode::Number(number) => write_element(out, "mn", number),
+            MathNode::Operator(operator) => write_element(out, "mo", operator),
+            MathNode::LargeOperator { symbol, limits } => {
+                if *limits {
+                    out.push_str(r#"<mo movablelimits="true">"#);
+                } else {
+                    out.push_str("<mo>");
+                }
+                out.push_str(&escape_html(symbol));
+                out.push_str("</mo>");
+            }
+            MathNode::Function(name) => write_element(out, "mi", name),
+            MathNode::Text(text) => write_element(out, "mtext", text),
+            MathNode::Space(width) => out.push_str(&format!(r#"<mspace width="{width}em"/>"#)),
+            MathNode::Row(nodes) => {
+                out.push_str("<mrow>");
+                for node in nodes {
+                    node.write_mathml(out);
+                }
+                out.push_str("</mrow>");
+            }
+            MathNode::Fraction {
+                numerator,
+                denominator,
+            } => {
+                out.push_str("<mfrac>");
+                numerator.write_mathml(out);
+                denominator.write_mathml(out);
+                out.push_str("</mfrac>");
+            }
+            MathNode::Scripts {
+                base,
+                subscript,
+                superscript,
+            } => {
+                let limits = matches!(**base, MathNode::LargeOperator { limits: true, .. });
+                let tag = match (subscript.is_some(), superscript.is_some(), limits) {
+                    (true, true, false) => "msubsup",
+                    (true, false, false) => "msub",
+                    (false, _, false) => "msup",
+                    (true, true, true) => "munderover",
+                    (true, false, true) => "munder",
+                    (false, _, true) => "mover",
+                };
+                out.push_str(&format!("<{tag}>"));
+                base.write_mathml(out);
+                if let Some(subscript) = subscript {
+                    subscript.write_mathml(out);
+                }
+                if let Some(superscript) = superscript {
+                    superscript.write_mathml(out);
+                }
+                out.push_str(&format!("</{tag}>"));
+            }
+            MathNode::Root { index, radicand } => match index {
+                Some(index) => {
+                    out.push_str("<mroot>");
+                    radicand.write_mathml(out);
+                    index.write_mathml(out);
+                    out.push_str("</mroot>");
+                }
+                None => {
+                    out.push_str("<msqrt>");
+                    radicand.write_mathml(out);
+                    out.push_str("</msqrt>");
+                }
+            },
+            MathNode::Accent { base, accent } => {
+                let (tag, attribute) = if accent.is_under() {
+                    ("munder", "accentunder")
+                } else {
+                    ("mover", "accent")
+                };
+                out.push_str(&format!(r#"<{tag} {attribute}="true">"#));
+                base.write_mathml(out);
+                write_element(out, "mo", accent.symbol());
+                out.push_str(&format!("</{tag}>"));
+            }
+            MathNode::Matrix {
+                rows,
+                left,
+                right,
+                left_aligned,
+            } => {
+                out.push_str("<mrow>");
+                if !left.is_empty() {
+                    write_element(out, "mo", left);
+                }
+                if *left_aligned {
+                    out.push_str(r#"<mtable columnalign="left">"#);
+                } else {
+                    out.push_str("<mtable>");
+                }
+                for row in rows {
+                    out.push_str("<mtr>");
+                    for cell in row {
+                        out.push_str("<mtd>");
+                        cell.write_mathml(out);
+                        out.push_str("</mtd>");
+                    }
+                    out.push_str("</mtr>");
+                }
+                out.push_str("</mtable>");
+                if !right.is_empty() {
+                    write_element(out, "mo", right);
+                }
+                out.push_str("</mrow>");
+            }
+        }
+    }
+}
+
+fn is_combining_mark(c: char) -> bool {
+    matches!(c, '\u{300}'..='\u{36f}' | '\u{20d0}'..='\u{20ff}')
+}
+
+fn write_element(out: &mut String, tag: &str, text: &str) {
+    out.push_str(&format!("<{tag}>{}</{tag}>", escape_html(text)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inline_text(source: &str) -> String {
+        parse_math(source).to_inline_text().0
+    }
+
+    #[test]
+    fn test_parse_math() {
+        assert_eq!(
+            parse_math(r"\frac{a}{2}"),
+            MathNode::Fraction {
+                numerator: Box::new(MathNode::Identifier("a".into())),
+                denominator: Box::new(MathNode::Number("2".into())),
+            }
+        );
+        assert_eq!(
+            parse_math(r"\sum_{i=0}^n i"),
+            MathNode::Row(vec![
+                MathNode::Scripts {
+                    base: Box::new(MathNode::LargeOperator {
+                        symbol: "∑".into(),
+                        limits: true,
+                    }),
+                    subscript: Some(Box::new(MathNode::Row(vec![
+                        MathNode::Identifier("i".into()),
+                        MathNode::Operator("=".into()),
+                        MathNode::Number("0".into()),
+                    ]))),
+                    superscript: Some(Box::new(MathNode::Identifier("n".into()))),
+                },
+                MathNode::Identifier("i".into()),
+            ])
+        );
+        assert_eq!(
+            parse_math(r"\begin{pmatrix} 1 & 0 \\ 0 & 1 \\ \end{pmatrix}"),
+            MathNode::Matrix {
+                rows: vec![
+                    vec![MathNode::Number("1".into()), MathNode::Number("0".into())],
+                    vec![MathNode::Number("0".into()), MathNode::Number("1".into())],
+                ],
+                left: "(".into(),
+                right: ")".into(),
+                left_aligned: false,
+            }
+        );
+        assert_eq!(
+            parse_math(r"\unknown{x}"),
+            MathNode::Row(vec![
+                MathNode::Text(r"\unknown".into()),
+                MathNode::Identifier("x".into()),
+            ])
+        );
+        // Unbalanced braces are closed at the end of the input.
+        assert_eq!(
+            parse_math(r"\sqrt{x"),
+            MathNode::Root {
+                index: None,
+                radicand: Box::new(MathNode::Identifier("x".into())),
+            }
+        );
+    }
+
+    #[test]
+    fn test_inline_text() {
+        assert_eq!(inline_text(r"e^{i\pi} + 1 = 0"), "e^(iπ) + 1 = 0");
+        assert_eq!(inline_text(r"x^2 + y_1"), "x² + y₁");
+        assert_eq!(inline_text(r"x^{n+1}"), "xⁿ⁺¹");
+        assert_eq!(inline_text(r"\frac{a+b}{c}"), "(a + b)/c");
+        assert_eq!(inline_text(r"\sqrt{2}"), "√2");
+        assert_eq!(inline_text(r"\sqrt[3]{x}"), "∛x");
+        assert_eq!(inline_text(r"\alpha \leq \beta"), "α ≤ β");
+        assert_eq!(inline_text(r"-x"), "−x");
+        assert_eq!(inline_text(r"\sin x"), "sin x");
+        assert_eq!(inline_text(r"\mathbb{R}^n"), "ℝⁿ");
+        assert_eq!(inline_text(r"f'(x)"), "f′(x)");
+        assert_eq!(inline_text(r"\hat{x}"), "x\u{302}");
+        assert_eq!(inline_text(r"\hat{\bar{x}}"), "x\u{305}\u{302}");
+
+        let (text, italics) = parse_math(r"\sin x").to_inline_text();
+        assert_eq!(
+            italics
+                .iter()
+                .map(|range| &text[range.clone()])
+                .collect::<Vec<_>>(),
+            ["x"]
+        );
+    }
+
+    #[test]
+    fn test_to_mathml() {
+        assert_eq!(
+            parse_math(r"x^2 < \frac{1}{2}").to_mathml(false),
+            "<math><mrow><msup><mi>x</mi><mn>2</mn></msup><mo>&lt;</mo>\
+             <mfrac><mn>1</mn><mn>2</mn></mfrac></mrow></math>"
+        );
+        assert_eq!(
+            parse_math(r"\lim_{n \to \infty} a_n").to_mathml(true),
+            "<math display=\"block\"><mrow><munder><mo movablelimits=\"true\">lim</mo>\
+             <mrow><mi>n</mi><mo>→</mo><mi mathvariant=\"normal\">∞</mi></mrow></munder>\
+             <msub><mi>a</mi><mi>n</mi></msub></mrow></math>"
+        );
+    }
+
+    #[test]
+    fn test_deeply_nested_math() {
+        let count = 10_000;
+        for source in [
+            "{".repeat(count),
+            r"\frac".repeat(count),
+            r"\sqrt[".repeat(count),
+            r"x^{\hat{".repeat(count),
+            r"\begin{pmatrix}".repeat(count),
+        ] {
+            let node = parse_math(&source);
+            node.to_inline_text();
+            node.to_mathml(true);
+        }
+
+        // Past the limit, the rest of the group is kept as literal text.
+        let source = format!(
+            "{}x{}",
+            "{".repeat(MAX_DEPTH + 2),
+            "}".repeat(MAX_DEPTH + 2)
+        );
+        assert_eq!(inline_text(&source), "{x}");
+        let source = format!("{}x", r"\mathit".repeat(MAX_DEPTH + 2));
+        assert_eq!(inline_text(&source), r"\mathit\mathitx");
+    }
+}
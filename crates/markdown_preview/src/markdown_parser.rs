@@ -1,5 +1,6 @@
 use crate::{
     markdown_elements::*,
+    markdown_math::{parse_math, superscript_char},
     markdown_minifier::{Minifier, MinifierOptions},
 };
 use async_recursion::async_recursion;
@@ -9,7 +10,7 @@ use html5ever::{ParseOpts, local_name, parse_document, tendril::TendrilSink};
 use language::LanguageRegistry;
 use markdown::parser::PARSE_OPTIONS;
 use markup5ever_rcdom::RcDom;
-use pulldown_cmark::{Alignment, Event, Parser, Tag, TagEnd};
+use pulldown_cmark::{Alignment, BlockQuoteKind, Event, LinkType, Options, Parser, Tag, TagEnd};
 use stacksafe::stacksafe;
 use std::{
     cell::RefCell, collections::HashMap, mem, ops::Range, path::PathBuf, rc::Rc, sync::Arc, vec,
@@ -21,7 +22,10 @@ pub async fn parse_markdown(
     file_location_directory: Option<PathBuf>,
     language_registry: Option<Arc<LanguageRegistry>>,
 ) -> ParsedMarkdown {
-    let parser = Parser::new_ext(markdown_input, PARSE_OPTIONS);
+    let parser = Parser::new_ext(
+        markdown_input,
+        PARSE_OPTIONS | Options::ENABLE_MATH | Options::ENABLE_WIKILINKS,
+    );
     let parser = MarkdownParser::new(
         parser.into_offset_iter().collect(),
         file_location_directory,
@@ -33,6 +37,241 @@ pub async fn parse_markdown(
     }
 }
 
+/// A link from a Markdown note to another note, or to a heading, as found by
+/// [`parse_note_links`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteLink {
+    /// The range of the whole link, such as `[[Note#Heading|alias]]` or `[text](note.md)`.
+    pub range: Range<usize>,
+    /// The range of the note's name or path within the link, not including the anchor. It's
+    /// empty for links to a heading of the same note, such as `[text](#heading)`.
+    pub target_range: Range<usize>,
+    pub target: NoteLinkTarget,
+    /// The heading the link points to, without the leading `#`.
+    pub anchor: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteLinkTarget {
+    /// A `[[wiki-link]]`, which names a note by its file name, with or without its extension, or
+    /// by its path.
+    WikiLink(String),
+    /// A link to a path relative to the note, with percent-encoding decoded.
+    RelativePath(String),
+}
+
+impl NoteLinkTarget {
+    /// The note's name or path, which is empty for links within the same note.
+    pub fn text(&self) -> &str {
+        match self {
+            NoteLinkTarget::WikiLink(text) | NoteLinkTarget::RelativePath(text) => text,
+        }
+    }
+}
+
+/// A heading that links can point to, as found by [`parse_note_headings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteHeading {
+    pub text: String,
+    /// The heading's `{#id}` attribute if it has one, or otherwise its [`heading_anchor`], made
+    /// unique by a numeric suffix the way GitHub does.
+    pub anchor: String,
+    pub range: Range<usize>,
+}
+
+/// Returns the `[[wiki-links]]` and relative links in a Markdown document, in order. Links to
+/// web pages and absolute paths are left out, as are images.
+pub fn parse_note_links(source: &str) -> Vec<NoteLink> {
+    let mut links = Vec::new();
+    let mut events =
+        Parser::new_ext(source, PARSE_OPTIONS | Options::ENABLE_WIKILINKS).into_offset_iter();
+    while let Some((event, range)) = events.next() {
+        let Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            ..
+        }) = event
+        else {
+            continue;
+        };
+        let destination = dest_url.as_ref();
+        let destination_start = match link_type {
+            LinkType::WikiLink { .. } => {
+                let start = range.start + "[[".len();
+                if !source[start..range.end].starts_with(destination) {
+                    continue;
+                }
+                start
+            }
+            LinkType::Inline => {
+                // The destination comes after the link's text, which may mention it too.
+                let mut text_end = range.start;
+                for (event, text_range) in events.by_ref() {
+                    if matches!(event, Event::End(TagEnd::Link)) {
+                        break;
+                    }
+                    text_end = text_end.max(text_range.end);
+                }
+                // Destinations with backslash escapes don't appear verbatim in the source.
+                match source[text_end..range.end].find(destination) {
+                    Some(offset) => text_end + offset,
+                    None => continue,
+                }
+            }
+            _ => continue,
+        };
+
+        let (path, anchor) = match destination.split_once('#') {
+            Some((path, anchor)) => (path, Some(anchor)),
+            None => (destination, None),
+        };
+        if path.is_empty() && anchor.is_none_or(str::is_empty) {
+            continue;
+        }
+        let target = if let LinkType::WikiLink { .. } = link_type {
+            NoteLinkTarget::WikiLink(path.to_string())
+        } else if is_external_link(path) {
+            continue;
+        } else {
+            NoteLinkTarget::RelativePath(
+                urlencoding::decode(path)
+                    .map(|path| path.into_owned())
+                    .unwrap_or_else(|_| path.to_string()),
+            )
+        };
+        links.push(NoteLink {
+            range,
+            target_range: destination_start..destination_start + path.len(),
+            target,
+            anchor: anchor.map(str::to_string),
+        });
+    }
+    links
+}
+
+/// Returns the headings of a Markdown document, in order.
+pub fn parse_note_headings(source: &str) -> Vec<NoteHeading> {
+    let mut headings = Vec::new();
+    let mut anchor_counts = HashMap::<String, usize>::default();
+    let mut current: Option<(Option<String>, String, Range<usize>)> = None;
+    for (event, range) in Parser::new_ext(source, PARSE_OPTIONS).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { id, .. }) => {
+                current = Some((id.map(|id| id.to_string()), String::new(), range));
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, heading_text, _)) = current.as_mut() {
+                    heading_text.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                let Some((id, text, range)) = current.take() else {
+                    continue;
+                };
+                let anchor = id.unwrap_or_else(|| {
+                    let anchor = heading_anchor(&text);
+                    let count = anchor_counts.entry(anchor.clone()).or_default();
+                    *count += 1;
+                    if *count == 1 {
+                        anchor
+                    } else {
+                        format!("{anchor}-{}", *count - 1)
+                    }
+                });
+                headings.push(NoteHeading {
+                    text: text.trim().to_string(),
+                    anchor,
+                    range,
+                });
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+/// Turns a heading's text into the anchor that links to it, the way GitHub does: lowercase, with
+/// spaces replaced by hyphens and punctuation removed.
+///
+/// Anchors are left unchanged, so this also normalizes the anchor of a link before comparing it
+/// with [`NoteHeading::anchor`], whether it was written as the heading's text or as its anchor.
+pub fn heading_anchor(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|c| {
+            if c.is_whitespace() {
+                Some('-')
+            } else if c.is_alphanumeric() || c == '-' || c == '_' {
+                Some(c)
+            } else {
+                None
+            }
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Whether a link's destination is a URL, such as `https://zed.dev` or `mailto:`, or an absolute
+/// path, rather than a path relative to the note.
+fn is_external_link(destination: &str) -> bool {
+    destination.starts_with('/')
+        || destination.split_once(':').is_some_and(|(scheme, _)| {
+            scheme.len() > 1
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        })
+}
+
+/// Whether inline math is an amount of money rather than math, such as the `100` in
+/// `$100$ per unit`.
+fn is_amount(source: &str) -> bool {
+    !source.is_empty()
+        && source
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == ',' || c == '.')
+}
+
+fn alert_kind(kind: &BlockQuoteKind) -> AlertKind {
+    match kind {
+        BlockQuoteKind::Note => AlertKind::Note,
+        BlockQuoteKind::Tip => AlertKind::Tip,
+        BlockQuoteKind::Important => AlertKind::Important,
+        BlockQuoteKind::Warning => AlertKind::Warning,
+        BlockQuoteKind::Caution => AlertKind::Caution,
+    }
+}
+
+/// Numbers the footnote definitions in the order they're referenced, and links them back to
+/// their first reference.
+fn number_footnotes(
+    elements: &mut [ParsedMarkdownElement],
+    numbers: &mut HashMap<String, usize>,
+    references: &HashMap<String, usize>,
+) {
+    for element in elements {
+        match element {
+            ParsedMarkdownElement::FootnoteDefinition(definition) => {
+                let label = definition.label.to_string();
+                let next_number = numbers.len() + 1;
+                definition.number = *numbers.entry(label.clone()).or_insert(next_number);
+                definition.reference_offset = references.get(&label).copied();
+                number_footnotes(&mut definition.children, numbers, references);
+            }
+            ParsedMarkdownElement::BlockQuote(block_quote) => {
+                number_footnotes(&mut block_quote.children, numbers, references);
+            }
+            ParsedMarkdownElement::Alert(alert) => {
+                number_footnotes(&mut alert.children, numbers, references);
+            }
+            ParsedMarkdownElement::ListItem(list_item) => {
+                number_footnotes(&mut list_item.content, numbers, references);
+            }
+            _ => {}
+        }
+    }
+}
+
 fn cleanup_html(source: &str) -> Vec<u8> {
     let mut writer = std::io::Cursor::new(Vec::new());
     let mut reader = std::io::Cursor::new(source);
@@ -59,6 +298,10 @@ struct MarkdownParser<'a> {
     parsed: Vec<ParsedMarkdownElement>,
     file_location_directory: Option<PathBuf>,
     language_registry: Option<Arc<LanguageRegistry>>,
+    /// The number of each footnote, in order of first reference.
+    footnote_numbers: HashMap<String, usize>,
+    /// Where each footnote is first referenced.
+    footnote_references: HashMap<String, usize>,
 }
 
 #[derive(Debug)]
@@ -98,6 +341,8 @@ impl<'a> MarkdownParser<'a> {
             language_registry,
             cursor: 0,
             parsed: vec![],
+            footnote_numbers: HashMap::default(),
+            footnote_references: HashMap::default(),
         }
     }
 
@@ -138,6 +383,8 @@ impl<'a> MarkdownParser<'a> {
             | Event::Html(_)
             | Event::InlineHtml(_)
             | Event::FootnoteReference(_)
+            | Event::InlineMath(_)
+            | Event::DisplayMath(_)
             | Event::Start(Tag::Link { .. })
             | Event::Start(Tag::Emphasis)
             | Event::Start(Tag::Strong)
@@ -157,9 +404,26 @@ impl<'a> MarkdownParser<'a> {
                 self.cursor += 1;
             }
         }
+        number_footnotes(
+            &mut self.parsed,
+            &mut self.footnote_numbers,
+            &self.footnote_references,
+        );
         self
     }
 
+    /// Returns the number of the footnote with the given label, recording the reference.
+    fn footnote_reference(&mut self, label: &str, offset: usize) -> usize {
+        self.footnote_references
+            .entry(label.to_string())
+            .or_insert(offset);
+        let next_number = self.footnote_numbers.len() + 1;
+        *self
+            .footnote_numbers
+            .entry(label.to_string())
+            .or_insert(next_number)
+    }
+
     #[async_recursion]
     async fn parse_block(&mut self) -> Option<Vec<ParsedMarkdownElement>> {
         let (current, source_range) = self.current().unwrap();
@@ -189,10 +453,25 @@ impl<'a> MarkdownParser<'a> {
                     let list = self.parse_list(order).await;
                     Some(list)
                 }
-                Tag::BlockQuote(_kind) => {
+                Tag::BlockQuote(kind) => {
+                    let kind = kind.as_ref().map(alert_kind);
                     self.cursor += 1;
                     let block_quote = self.parse_block_quote().await;
-                    Some(vec![ParsedMarkdownElement::BlockQuote(block_quote)])
+                    if let Some(kind) = kind {
+                        Some(vec![ParsedMarkdownElement::Alert(ParsedMarkdownAlert {
+                            source_range: block_quote.source_range,
+                            kind,
+                            children: block_quote.children,
+                        })])
+                    } else {
+                        Some(vec![ParsedMarkdownElement::BlockQuote(block_quote)])
+                    }
+                }
+                Tag::FootnoteDefinition(label) => {
+                    let label = label.to_string();
+                    self.cursor += 1;
+                    let definition = self.parse_footnote_definition(label).await;
+                    Some(vec![ParsedMarkdownElement::FootnoteDefinition(definition)])
                 }
                 Tag::CodeBlock(kind) => {
                     let (language, scale) = match kind {
@@ -262,7 +541,7 @@ impl<'a> MarkdownParser<'a> {
                 break;
             }
 
-            let (current, _) = self.current().unwrap();
+            let (current, event_range) = self.current().unwrap();
             let prev_len = text.len();
             match current {
                 Event::SoftBreak => {
@@ -302,6 +581,7 @@ impl<'a> MarkdownParser<'a> {
                             ParsedRegion {
                                 code: false,
                                 link: Some(link),
+                                math: None,
                             },
                         ));
                         style.link = true;
@@ -341,6 +621,7 @@ impl<'a> MarkdownParser<'a> {
                                     link: Some(Link::Web {
                                         url: link.as_str().to_string(),
                                     }),
+                                    math: None,
                                 },
                             ));
                             last_link_len = end;
@@ -383,6 +664,74 @@ impl<'a> MarkdownParser<'a> {
                         ParsedRegion {
                             code: true,
                             link: link.clone(),
+                            math: None,
+                        },
+                    ));
+                }
+                // Keep amounts such as `$100$ per unit` as they were written.
+                Event::InlineMath(source) if is_amount(source) => {
+                    text.push('$');
+                    text.push_str(source);
+                    text.push('$');
+                }
+                Event::InlineMath(source) => {
+                    let node = parse_math(source);
+                    let (math_text, italics) = node.to_inline_text();
+                    text.push_str(&math_text);
+                    for range in italics {
+                        highlights.push((
+                            prev_len + range.start..prev_len + range.end,
+                            MarkdownHighlight::Style(MarkdownHighlightStyle {
+                                italic: true,
+                                ..Default::default()
+                            }),
+                        ));
+                    }
+                    regions.push((
+                        prev_len..text.len(),
+                        ParsedRegion {
+                            code: false,
+                            link: link.clone(),
+                            math: Some(node),
+                        },
+                    ));
+                }
+                Event::DisplayMath(source) => {
+                    if !text.is_empty() {
+                        markdown_text_like.push(MarkdownParagraphChunk::Text(ParsedMarkdownText {
+                            source_range: source_range.clone(),
+                            contents: mem::take(&mut text).into(),
+                            highlights: mem::take(&mut highlights),
+                            regions: mem::take(&mut regions),
+                        }));
+                    }
+                    markdown_text_like.push(MarkdownParagraphChunk::Math(ParsedMarkdownMath {
+                        source_range: event_range.clone(),
+                        contents: source.to_string().into(),
+                        node: parse_math(source),
+                    }));
+                }
+                Event::FootnoteReference(label) => {
+                    let label = label.to_string();
+                    let offset = event_range.start;
+                    let number = self.footnote_reference(&label, offset);
+                    text.extend(number.to_string().chars().filter_map(superscript_char));
+                    highlights.push((
+                        prev_len..text.len(),
+                        MarkdownHighlight::Style(MarkdownHighlightStyle {
+                            link: true,
+                            ..Default::default()
+                        }),
+                    ));
+                    regions.push((
+                        prev_len..text.len(),
+                        ParsedRegion {
+                            code: false,
+                            link: Some(Link::Footnote {
+                                label: label.into(),
+                                is_back_reference: false,
+                            }),
+                            math: None,
                         },
                     ));
                 }
@@ -390,6 +739,16 @@ impl<'a> MarkdownParser<'a> {
                     Tag::Emphasis => italic_depth += 1,
                     Tag::Strong => bold_depth += 1,
                     Tag::Strikethrough => strikethrough_depth += 1,
+                    Tag::Link {
+                        link_type: LinkType::WikiLink { .. },
+                        dest_url,
+                        ..
+                    } => {
+                        link = Link::identify_wiki_link(
+                            self.file_location_directory.clone(),
+                            &dest_url,
+                        );
+                    }
                     Tag::Link { dest_url, .. } => {
                         link = Link::identify(
                             self.file_location_directory.clone(),
@@ -757,6 +1116,37 @@ impl<'a> MarkdownParser<'a> {
         }
     }
 
+    #[async_recursion]
+    async fn parse_footnote_definition(
+        &mut self,
+        label: String,
+    ) -> ParsedMarkdownFootnoteDefinition {
+        let (_event, source_range) = self.previous().unwrap();
+        let source_range = source_range.clone();
+        let mut children = Vec::new();
+
+        while !self.eof() {
+            if self.current_event() == Some(&Event::End(TagEnd::FootnoteDefinition)) {
+                self.cursor += 1;
+                break;
+            }
+            if let Some(block) = self.parse_block().await {
+                children.extend(block);
+            } else {
+                self.cursor += 1;
+            }
+        }
+
+        ParsedMarkdownFootnoteDefinition {
+            source_range,
+            label: label.into(),
+            // Footnotes are numbered once the whole document has been parsed.
+            number: 0,
+            reference_offset: None,
+            children,
+        }
+    }
+
     async fn parse_code_block(
         &mut self,
         language: Option<String>,
@@ -1126,6 +1516,7 @@ impl<'a> MarkdownParser<'a> {
                             ParsedRegion {
                                 code: false,
                                 link: Some(link),
+                                math: None,
                             },
                         ));
                     }
@@ -1757,7 +2148,8 @@ mod tests {
                     code: false,
                     link: Some(Link::Web {
                         url: "https://example.com".into()
-                    })
+                    }),
+                    math: None,
                 }
             )],
             text.regions
@@ -3126,6 +3518,100 @@ More text
         );
     }
 
+    #[gpui::test]
+    async fn test_inline_math() {
+        let parsed = parse("Euler: $e^{i\\pi} = -1$").await;
+        let ParsedMarkdownElement::Paragraph(paragraph) = &parsed.children[0] else {
+            panic!("expected a paragraph");
+        };
+        let MarkdownParagraphChunk::Text(text) = &paragraph[0] else {
+            panic!("expected text");
+        };
+        assert_eq!(text.contents.as_str(), "Euler: e^(iπ) = −1");
+        assert_eq!(text.regions.len(), 1);
+        assert_eq!(text.regions[0].0, 7..text.contents.len());
+        assert_eq!(text.regions[0].1.math, Some(parse_math(r"e^{i\pi} = -1")));
+    }
+
+    #[gpui::test]
+    async fn test_display_math() {
+        let parsed = parse("$$\\frac{a}{b}$$").await;
+        assert_eq!(
+            parsed.children,
+            vec![ParsedMarkdownElement::Paragraph(vec![
+                MarkdownParagraphChunk::Math(ParsedMarkdownMath {
+                    source_range: 0..15,
+                    contents: r"\frac{a}{b}".into(),
+                    node: parse_math(r"\frac{a}{b}"),
+                })
+            ])]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_footnotes() {
+        let parsed = parse("Text[^b] and[^a].\n\n[^a]: A.\n\n[^b]: B.\n").await;
+        let ParsedMarkdownElement::Paragraph(paragraph) = &parsed.children[0] else {
+            panic!("expected a paragraph");
+        };
+        let MarkdownParagraphChunk::Text(text) = &paragraph[0] else {
+            panic!("expected text");
+        };
+        assert_eq!(text.contents.as_str(), "Text¹ and².");
+        assert_eq!(
+            text.regions
+                .iter()
+                .map(|(_, region)| region.link.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                Some(Link::Footnote {
+                    label: "b".into(),
+                    is_back_reference: false
+                }),
+                Some(Link::Footnote {
+                    label: "a".into(),
+                    is_back_reference: false
+                }),
+            ]
+        );
+
+        let definitions = parsed.children[1..]
+            .iter()
+            .map(|element| match element {
+                ParsedMarkdownElement::FootnoteDefinition(definition) => (
+                    definition.label.to_string(),
+                    definition.number,
+                    definition.reference_offset,
+                ),
+                _ => panic!("expected a footnote definition"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            definitions,
+            vec![("a".into(), 2, Some(12)), ("b".into(), 1, Some(4))]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_alert() {
+        let parsed = parse("> [!WARNING]\n> Be careful\n\n> Plain quote").await;
+        let ParsedMarkdownElement::Alert(alert) = &parsed.children[0] else {
+            panic!("expected an alert");
+        };
+        assert_eq!(alert.kind, AlertKind::Warning);
+        let ParsedMarkdownElement::Paragraph(paragraph) = &alert.children[0] else {
+            panic!("expected a paragraph");
+        };
+        let MarkdownParagraphChunk::Text(text) = &paragraph[0] else {
+            panic!("expected text");
+        };
+        assert_eq!(text.contents.as_str(), "Be careful");
+        assert!(matches!(
+            parsed.children[1],
+            ParsedMarkdownElement::BlockQuote(_)
+        ));
+    }
+
     #[gpui::test]
     async fn test_code_block() {
         let parsed = parse(
@@ -3179,6 +3665,67 @@ fn main() {
         );
     }
 
+    #[test]
+    fn test_parse_note_links() {
+        let source = "See [[Daily Notes#Monday|today]], [setup](guides/setup%20steps.md#install \"setup\"), \
+                      [top](#intro), [web](https://zed.dev), [mail](mailto:a@b.c) and `[[code]]`.\n";
+        let links = parse_note_links(source)
+            .into_iter()
+            .map(|link| {
+                (
+                    &source[link.range],
+                    &source[link.target_range],
+                    link.target,
+                    link.anchor,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            vec![
+                (
+                    "[[Daily Notes#Monday|today]]",
+                    "Daily Notes",
+                    NoteLinkTarget::WikiLink("Daily Notes".into()),
+                    Some("Monday".into()),
+                ),
+                (
+                    "[setup](guides/setup%20steps.md#install \"setup\")",
+                    "guides/setup%20steps.md",
+                    NoteLinkTarget::RelativePath("guides/setup steps.md".into()),
+                    Some("install".into()),
+                ),
+                (
+                    "[top](#intro)",
+                    "",
+                    NoteLinkTarget::RelativePath("".into()),
+                    Some("intro".into()),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_note_headings() {
+        let source =
+            "# Getting Started\n\n## Install `zed`!\n\n## Install `zed`\n\n## Custom {#my-id}\n";
+        let headings = parse_note_headings(source)
+            .into_iter()
+            .map(|heading| (heading.text, heading.anchor))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            headings,
+            vec![
+                ("Getting Started".to_string(), "getting-started".to_string()),
+                ("Install zed!".to_string(), "install-zed".to_string()),
+                ("Install zed".to_string(), "install-zed-1".to_string()),
+                ("Custom".to_string(), "my-id".to_string()),
+            ]
+        );
+        assert_eq!(heading_anchor("Getting Started"), "getting-started");
+        assert_eq!(heading_anchor("getting-started"), "getting-started");
+    }
+
     fn h1(contents: MarkdownParagraph, source_range: Range<usize>) -> ParsedMarkdownElement {
         ParsedMarkdownElement::Heading(ParsedMarkdownHeading {
             source_range,
@@ -2,6 +2,8 @@ use gpui::{App, actions};
 use workspace::Workspace;
 
 pub mod markdown_elements;
+pub mod markdown_html;
+pub mod markdown_math;
 mod markdown_minifier;
 pub mod markdown_parser;
 pub mod markdown_preview_view;
@@ -31,7 +33,10 @@ actions!(
         /// Scrolls to the bottom of the markdown preview.
         ScrollToBottom,
         /// Opens a following markdown preview that syncs with the editor.
-        OpenFollowingPreview
+        OpenFollowingPreview,
+        /// Exports the markdown preview as an HTML file that embeds its local images. Images
+        /// linked by URL are left as links, so the page needs network access to show them.
+        ExportAsHtml
     ]
 );
 
@@ -1,9 +1,12 @@
 use std::cmp::min;
 use std::sync::Arc;
 use std::time::Duration;
-use std::{ops::Range, path::PathBuf};
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use editor::scroll::Autoscroll;
 use editor::{Editor, EditorEvent, MultiBufferOffset, SelectionEffects};
 use gpui::{
@@ -16,13 +19,16 @@ use settings::Settings;
 use theme::ThemeSettings;
 use ui::{WithScrollbar, prelude::*};
 use workspace::item::{Item, ItemHandle};
+use workspace::notifications::NotifyTaskExt as _;
 use workspace::{Pane, Workspace};
 
 use crate::markdown_elements::ParsedMarkdownElement;
-use crate::markdown_renderer::{CheckboxClickedEvent, MermaidState};
+use crate::markdown_renderer::{CheckboxClickedEvent, FootnoteClickedEvent, MermaidState};
 use crate::{
-    OpenFollowingPreview, OpenPreview, OpenPreviewToTheSide, ScrollPageDown, ScrollPageUp,
+    ExportAsHtml, OpenFollowingPreview, OpenPreview, OpenPreviewToTheSide, ScrollPageDown,
+    ScrollPageUp,
     markdown_elements::ParsedMarkdown,
+    markdown_html::{HtmlTheme, load_embedded_images, render_html},
     markdown_parser::parse_markdown,
     markdown_renderer::{RenderContext, render_markdown_block},
 };
@@ -430,6 +436,87 @@ impl MarkdownPreviewView {
         block_index.unwrap_or_default()
     }
 
+    /// Scrolls to the definition of a clicked footnote reference, or back to the first reference
+    /// of a footnote whose back-link was clicked.
+    fn scroll_to_footnote(&mut self, event: &FootnoteClickedEvent, cx: &mut Context<Self>) {
+        let Some(contents) = &self.contents else {
+            return;
+        };
+        let Some((definition_index, definition)) =
+            contents
+                .children
+                .iter()
+                .enumerate()
+                .find_map(|(ix, block)| match block {
+                    ParsedMarkdownElement::FootnoteDefinition(definition)
+                        if definition.label == event.label =>
+                    {
+                        Some((ix, definition))
+                    }
+                    _ => None,
+                })
+        else {
+            return;
+        };
+
+        let block_index = if event.is_back_reference {
+            let Some(offset) = definition.reference_offset else {
+                return;
+            };
+            self.get_block_index_under_cursor(MultiBufferOffset(offset)..MultiBufferOffset(offset))
+        } else {
+            definition_index
+        };
+        self.list_state.scroll_to_reveal_item(block_index);
+        cx.notify();
+    }
+
+    fn export_as_html(&mut self, _: &ExportAsHtml, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(editor) = self
+            .active_editor
+            .as_ref()
+            .map(|state| state.editor.clone())
+        else {
+            return;
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let fs = workspace.read(cx).app_state().fs.clone();
+        let editor = editor.read(cx);
+        let contents = editor.buffer().read(cx).snapshot(cx).text();
+        let title = editor.buffer().read(cx).title(cx).to_string();
+        let file_location = Self::get_folder_for_active_editor(editor, cx);
+
+        let suggested_name = format!(
+            "{}.html",
+            Path::new(&title)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy())
+                .unwrap_or_else(|| title.as_str().into())
+        );
+        let directory = file_location.clone().unwrap_or_default();
+        let save_dialog = cx.prompt_for_new_path(&directory, Some(&suggested_name));
+        let language_registry = self.language_registry.clone();
+        let theme = HtmlTheme::new(cx);
+
+        let task: Task<Result<()>> = cx.background_spawn(async move {
+            let Ok(path) = save_dialog.await else {
+                return Ok(());
+            };
+            let Some(path) = path? else {
+                return Ok(());
+            };
+            let parsed = parse_markdown(&contents, file_location, Some(language_registry)).await;
+            let images = load_embedded_images(&parsed, fs.as_ref()).await;
+            let html = render_html(&parsed, &title, &theme, &images);
+            fs.atomic_write(path.clone(), html)
+                .await
+                .with_context(|| format!("failed to write markdown preview to {path:?}"))
+        });
+        task.detach_and_notify_err(self.workspace.clone(), window, cx);
+    }
+
     fn should_apply_padding_between(
         current_block: &ParsedMarkdownElement,
         next_block: Option<&ParsedMarkdownElement>,
@@ -588,6 +675,7 @@ impl Render for MarkdownPreviewView {
             .on_action(cx.listener(MarkdownPreviewView::scroll_down_by_item))
             .on_action(cx.listener(MarkdownPreviewView::scroll_to_top))
             .on_action(cx.listener(MarkdownPreviewView::scroll_to_bottom))
+            .on_action(cx.listener(MarkdownPreviewView::export_as_html))
             .size_full()
             .bg(cx.theme().colors().editor_background)
             .p_4()
@@ -630,6 +718,11 @@ impl Render for MarkdownPreviewView {
                                         cx.notify();
                                     }
                                 },
+                            ))
+                            .with_footnote_clicked_callback(cx.listener(
+                                |this, event: &FootnoteClickedEvent, _window, cx| {
+                                    this.scroll_to_footnote(event, cx);
+                                },
                             ));
 
                             let block = contents.children.get(ix).unwrap();
@@ -1,19 +1,21 @@
 use crate::{
     markdown_elements::{
-        HeadingLevel, Image, Link, MarkdownParagraph, MarkdownParagraphChunk, ParsedMarkdown,
-        ParsedMarkdownBlockQuote, ParsedMarkdownCodeBlock, ParsedMarkdownElement,
-        ParsedMarkdownHeading, ParsedMarkdownListItem, ParsedMarkdownListItemType,
+        AlertKind, HeadingLevel, Image, Link, MarkdownParagraph, MarkdownParagraphChunk,
+        ParsedMarkdown, ParsedMarkdownAlert, ParsedMarkdownBlockQuote, ParsedMarkdownCodeBlock,
+        ParsedMarkdownElement, ParsedMarkdownFootnoteDefinition, ParsedMarkdownHeading,
+        ParsedMarkdownListItem, ParsedMarkdownListItemType, ParsedMarkdownMath,
         ParsedMarkdownMermaidDiagram, ParsedMarkdownMermaidDiagramContents, ParsedMarkdownTable,
         ParsedMarkdownTableAlignment, ParsedMarkdownTableRow,
     },
+    markdown_math::{MathNode, is_italic_identifier, is_spaced_operator},
     markdown_preview_view::MarkdownPreviewView,
 };
 use collections::HashMap;
 use gpui::{
     AbsoluteLength, Animation, AnimationExt, AnyElement, App, AppContext as _, Context, Div,
-    Element, ElementId, Entity, HighlightStyle, Hsla, ImageSource, InteractiveText, IntoElement,
-    Keystroke, Modifiers, ParentElement, Render, RenderImage, Resource, SharedString, Styled,
-    StyledText, Task, TextStyle, WeakEntity, Window, div, img, pulsating_between, rems,
+    Element, ElementId, Entity, FontWeight, HighlightStyle, Hsla, ImageSource, InteractiveText,
+    IntoElement, Keystroke, Modifiers, ParentElement, Render, RenderImage, Resource, SharedString,
+    Styled, StyledText, Task, TextStyle, WeakEntity, Window, div, img, pulsating_between, rems,
 };
 use settings::Settings;
 use std::{
@@ -22,8 +24,8 @@ use std::{
     time::Duration,
     vec,
 };
-use theme::{ActiveTheme, SyntaxTheme, ThemeSettings};
-use ui::{CopyButton, LinkPreview, ToggleState, prelude::*, tooltip_container};
+use theme::{ActiveTheme, StatusColors, SyntaxTheme, ThemeSettings};
+use ui::{CopyButton, LinkPreview, ToggleState, Tooltip, prelude::*, tooltip_container};
 use util::normalize_path;
 use workspace::{OpenOptions, OpenVisible, Workspace};
 
@@ -44,6 +46,14 @@ impl CheckboxClickedEvent {
 
 type CheckboxClickedCallback = Arc<Box<dyn Fn(&CheckboxClickedEvent, &mut Window, &mut App)>>;
 
+pub struct FootnoteClickedEvent {
+    pub label: SharedString,
+    /// Whether the back-link of the footnote's definition was clicked, rather than a reference.
+    pub is_back_reference: bool,
+}
+
+type FootnoteClickedCallback = Arc<Box<dyn Fn(&FootnoteClickedEvent, &mut Window, &mut App)>>;
+
 type MermaidDiagramCache = HashMap<ParsedMarkdownMermaidDiagramContents, CachedMermaidDiagram>;
 
 #[derive(Default)]
@@ -187,8 +197,10 @@ pub struct RenderContext<'a> {
     code_block_background_color: Hsla,
     code_span_background_color: Hsla,
     syntax_theme: Arc<SyntaxTheme>,
+    status_colors: StatusColors,
     indent: usize,
     checkbox_clicked_callback: Option<CheckboxClickedCallback>,
+    footnote_clicked_callback: Option<FootnoteClickedCallback>,
     is_last_child: bool,
     mermaid_state: &'a MermaidState,
 }
@@ -218,6 +230,7 @@ impl<'a> RenderContext<'a> {
             buffer_text_style,
             text_style: window.text_style(),
             syntax_theme: theme.syntax().clone(),
+            status_colors: theme.status().clone(),
             border_color: theme.colors().border,
             title_bar_background_color: theme.colors().title_bar_background,
             panel_background_color: theme.colors().panel_background,
@@ -228,6 +241,7 @@ impl<'a> RenderContext<'a> {
             code_block_background_color: theme.colors().surface_background,
             code_span_background_color: theme.colors().editor_document_highlight_read_background,
             checkbox_clicked_callback: None,
+            footnote_clicked_callback: None,
             is_last_child: false,
             mermaid_state,
         }
@@ -241,6 +255,14 @@ impl<'a> RenderContext<'a> {
         self
     }
 
+    pub fn with_footnote_clicked_callback(
+        mut self,
+        callback: impl Fn(&FootnoteClickedEvent, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.footnote_clicked_callback = Some(Arc::new(Box::new(callback)));
+        self
+    }
+
     fn next_id(&mut self, span: &Range<usize>) -> ElementId {
         let id = format!("markdown-{}-{}-{}", self.next_id, span.start, span.end);
         self.next_id += 1;
@@ -320,6 +342,8 @@ pub fn render_markdown_block(block: &ParsedMarkdownElement, cx: &mut RenderConte
         MermaidDiagram(mermaid) => render_mermaid_diagram(mermaid, cx),
         HorizontalRule(_) => render_markdown_rule(cx),
         Image(image) => render_markdown_image(image, cx),
+        Alert(alert) => render_markdown_alert(alert, cx),
+        FootnoteDefinition(definition) => render_markdown_footnote_definition(definition, cx),
     }
 }
 
@@ -742,6 +766,121 @@ fn render_markdown_block_quote(
         .into_any()
 }
 
+/// The color of an alert's border, icon and title.
+pub(crate) fn alert_color(kind: AlertKind, status_colors: &StatusColors) -> Hsla {
+    match kind {
+        AlertKind::Note => status_colors.info,
+        AlertKind::Tip => status_colors.success,
+        AlertKind::Important => status_colors.hint,
+        AlertKind::Warning => status_colors.warning,
+        AlertKind::Caution => status_colors.error,
+    }
+}
+
+fn alert_icon(kind: AlertKind) -> IconName {
+    match kind {
+        AlertKind::Note => IconName::Info,
+        AlertKind::Tip => IconName::Sparkle,
+        AlertKind::Important => IconName::BoltFilled,
+        AlertKind::Warning => IconName::Warning,
+        AlertKind::Caution => IconName::XCircle,
+    }
+}
+
+fn render_markdown_alert(parsed: &ParsedMarkdownAlert, cx: &mut RenderContext) -> AnyElement {
+    cx.indent += 1;
+
+    let children: Vec<AnyElement> = parsed
+        .children
+        .iter()
+        .enumerate()
+        .map(|(ix, child)| {
+            cx.with_last_child(ix + 1 == parsed.children.len(), |cx| {
+                render_markdown_block(child, cx)
+            })
+        })
+        .collect();
+
+    cx.indent -= 1;
+
+    let color = alert_color(parsed.kind, &cx.status_colors);
+    cx.with_common_p(div())
+        .child(
+            v_flex()
+                .gap_1()
+                .border_l_4()
+                .border_color(color)
+                .pl_3()
+                .child(
+                    h_flex()
+                        .gap_1p5()
+                        .text_color(color)
+                        .font_weight(FontWeight::SEMIBOLD)
+                        .child(
+                            Icon::new(alert_icon(parsed.kind))
+                                .size(IconSize::Small)
+                                .color(Color::Custom(color)),
+                        )
+                        .child(parsed.kind.title()),
+                )
+                .children(children),
+        )
+        .into_any()
+}
+
+fn render_markdown_footnote_definition(
+    parsed: &ParsedMarkdownFootnoteDefinition,
+    cx: &mut RenderContext,
+) -> AnyElement {
+    cx.indent += 1;
+
+    let children: Vec<AnyElement> = parsed
+        .children
+        .iter()
+        .enumerate()
+        .map(|(ix, child)| {
+            cx.with_last_child(ix + 1 == parsed.children.len(), |cx| {
+                render_markdown_block(child, cx)
+            })
+        })
+        .collect();
+
+    cx.indent -= 1;
+
+    let back_link = parsed.reference_offset.map(|_| {
+        let event = FootnoteClickedEvent {
+            label: parsed.label.clone(),
+            is_back_reference: true,
+        };
+        let callback = cx.footnote_clicked_callback.clone();
+        div()
+            .id(cx.next_id(&parsed.source_range))
+            .text_color(cx.link_color)
+            .cursor_pointer()
+            .child("↩")
+            .tooltip(Tooltip::text("Back to Reference"))
+            .when_some(callback, |this, callback| {
+                this.on_click(move |_, window, cx| callback(&event, window, cx))
+            })
+    });
+
+    cx.with_common_p(div())
+        .child(
+            h_flex()
+                .items_start()
+                .gap_2()
+                .text_size(cx.scaled_rems(0.875))
+                .child(
+                    div()
+                        .text_color(cx.text_muted_color)
+                        .child(format!("{}.", parsed.number)),
+                )
+                .child(v_flex().flex_1().children(children))
+                .children(back_link),
+        )
+        .into_any()
+}
+
 fn render_markdown_code_block(
     parsed: &ParsedMarkdownCodeBlock,
     cx: &mut RenderContext,
@@ -947,6 +1086,7 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                     }
                 }
                 let workspace = workspace_clone.clone();
+                let footnote_clicked_callback = cx.footnote_clicked_callback.clone();
                 let element = div()
                     .child(
                         InteractiveText::new(
@@ -960,6 +1100,9 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                             move |idx, _, cx| {
                                 for (ix, range) in link_ranges.iter().enumerate() {
                                     if range.contains(&idx) {
+                                        if let Link::Footnote { .. } = links[ix] {
+                                            return None;
+                                        }
                                         return Some(LinkPreview::new(&links[ix].to_string(), cx));
                                     }
                                 }
@@ -987,6 +1130,21 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                                         });
                                     }
                                 }
+                                Link::Footnote {
+                                    label,
+                                    is_back_reference,
+                                } => {
+                                    if let Some(callback) = &footnote_clicked_callback {
+                                        callback(
+                                            &FootnoteClickedEvent {
+                                                label: label.clone(),
+                                                is_back_reference: *is_back_reference,
+                                            },
+                                            window,
+                                            cx,
+                                        );
+                                    }
+                                }
                             },
                         ),
                     )
@@ -997,12 +1155,185 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
             MarkdownParagraphChunk::Image(image) => {
                 any_element.push(render_markdown_image(image, cx));
             }
+
+            MarkdownParagraphChunk::Math(math) => {
+                any_element.push(render_markdown_math(math, cx));
+            }
         }
     }
 
     any_element
 }
 
+fn render_markdown_math(parsed: &ParsedMarkdownMath, cx: &mut RenderContext) -> AnyElement {
+    let text_size = cx.scaled_rems(1.2);
+    div()
+        .id(cx.next_id(&parsed.source_range))
+        .w_full()
+        .py(cx.scaled_rems(0.5))
+        .overflow_x_scroll()
+        .child(
+            h_flex()
+                .justify_center()
+                .child(render_math_node(&parsed.node, text_size, cx)),
+        )
+        .into_any()
+}
+
+/// Lays out display math, placing scripts, fractions and matrices like TeX does.
+fn render_math_node(node: &MathNode, size: Rems, cx: &RenderContext) -> AnyElement {
+    let script_size = size * 0.7;
+    match node {
+        MathNode::Identifier(identifier) => div()
+            .text_size(size)
+            .when(is_italic_identifier(identifier), |this| this.italic())
+            .child(identifier.clone())
+            .into_any(),
+        MathNode::Number(text) | MathNode::Text(text) => {
+            div().text_size(size).child(text.clone()).into_any()
+        }
+        MathNode::Function(name) => div()
+            .text_size(size)
+            .pr(size * 0.17)
+            .child(name.clone())
+            .into_any(),
+        MathNode::Operator(operator) => div()
+            .text_size(size)
+            .when(is_spaced_operator(operator), |this| this.px(size * 0.25))
+            .child(operator.clone())
+            .into_any(),
+        MathNode::LargeOperator { symbol, .. } => {
+            let is_symbol = symbol.chars().count() == 1;
+            div()
+                .text_size(if is_symbol { size * 1.5 } else { size })
+                .pr(size * 0.17)
+                .child(symbol.clone())
+                .into_any()
+        }
+        MathNode::Space(width) => div().w(size * *width).into_any(),
+        MathNode::Row(nodes) => h_flex()
+            .children(nodes.iter().map(|node| render_math_node(node, size, cx)))
+            .into_any(),
+        MathNode::Fraction {
+            numerator,
+            denominator,
+        } => v_flex()
+            .items_center()
+            .px(size * 0.1)
+            .child(render_math_node(numerator, size, cx))
+            .child(div().w_full().h(px(1.)).bg(cx.text_color))
+            .child(render_math_node(denominator, size, cx))
+            .into_any(),
+        MathNode::Scripts {
+            base,
+            subscript,
+            superscript,
+        } => {
+            let base_element = render_math_node(base, size, cx);
+            let superscript = superscript
+                .as_ref()
+                .map(|superscript| render_math_node(superscript, script_size, cx));
+            let subscript = subscript
+                .as_ref()
+                .map(|subscript| render_math_node(subscript, script_size, cx));
+            if let MathNode::LargeOperator { limits: true, .. } = **base {
+                return v_flex()
+                    .items_center()
+                    .children(superscript)
+                    .child(base_element)
+                    .children(subscript)
+                    .into_any();
+            }
+            // A lone script is offset from the base's center by an empty line on its other side.
+            let has_superscript = superscript.is_some();
+            let has_subscript = subscript.is_some();
+            h_flex()
+                .child(base_element)
+                .child(
+                    v_flex()
+                        .children(superscript)
+                        .when(!has_superscript, |this| this.child(div().h(script_size)))
+                        .when(!has_subscript, |this| this.child(div().h(script_size)))
+                        .children(subscript),
+                )
+                .into_any()
+        }
+        MathNode::Root { index, radicand } => h_flex()
+            .children(index.as_ref().map(|index| {
+                div()
+                    .mb(size * 0.5)
+                    .child(render_math_node(index, size * 0.5, cx))
+            }))
+            .child(div().text_size(size).child("√"))
+            .child(
+                div()
+                    .border_t_1()
+                    .border_color(cx.text_color)
+                    .child(render_math_node(radicand, size, cx)),
+            )
+            .into_any(),
+        MathNode::Accent { base, accent } => {
+            let base = render_math_node(base, size, cx);
+            if accent.is_under() {
+                div()
+                    .border_b_1()
+                    .border_color(cx.text_color)
+                    .child(base)
+                    .into_any()
+            } else {
+                v_flex()
+                    .items_center()
+                    .child(
+                        div()
+                            .text_size(script_size)
+                            .line_height(script_size * 0.6)
+                            .child(accent.symbol()),
+                    )
+                    .child(base)
+                    .into_any()
+            }
+        }
+        MathNode::Matrix {
+            rows,
+            left,
+            right,
+            left_aligned,
+        } => {
+            let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+            let delimiter_size = size * (1. + 0.9 * rows.len().saturating_sub(1) as f32);
+            let delimiter = |symbol: &String| {
+                (!symbol.is_empty()).then(|| {
+                    div()
+                        .text_size(delimiter_size)
+                        .line_height(delimiter_size)
+                        .child(symbol.clone())
+                })
+            };
+            let cells = rows.iter().flat_map(|row| {
+                (0..column_count).map(move |ix| {
+                    h_flex()
+                        .when(*left_aligned, |this| this.justify_start())
+                        .when(!*left_aligned, |this| this.justify_center())
+                        .children(row.get(ix).map(|cell| render_math_node(cell, size, cx)))
+                })
+            });
+            h_flex()
+                .gap(size * 0.2)
+                .children(delimiter(left))
+                .child(
+                    div()
+                        .grid()
+                        .grid_cols_max_content(column_count as u16)
+                        .gap_x(size * 0.8)
+                        .gap_y(size * 0.2)
+                        .children(cells),
+                )
+                .children(delimiter(right))
+                .into_any()
+        }
+    }
+}
+
 fn render_markdown_rule(cx: &mut RenderContext) -> AnyElement {
     let rule = div().w_full().h(cx.scaled_rems(0.125)).bg(cx.border_color);
     div().py(cx.scaled_rems(0.5)).child(rule).into_any()
@@ -1012,6 +1343,7 @@ fn render_markdown_image(image: &Image, cx: &mut RenderContext) -> AnyElement {
     let image_resource = match image.link.clone() {
         Link::Web { url } => Resource::Uri(url.into()),
         Link::Path { path, .. } => Resource::Path(Arc::from(path)),
+        Link::Footnote { .. } => return div().children(image.alt_text.clone()).into_any(),
     };
 
     let element_id = cx.next_id(&image.source_range);
@@ -1065,6 +1397,7 @@ fn render_markdown_image(image: &Image, cx: &mut RenderContext) -> AnyElement {
                                 });
                             }
                         }
+                        Link::Footnote { .. } => {}
                     }
                 }
             }
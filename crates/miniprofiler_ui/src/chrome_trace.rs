@@ -0,0 +1,367 @@
+//! Conversion between profiler timings and the Chrome Trace Event format,
+//! which can be opened in Perfetto (https://ui.perfetto.dev) or
+//! `chrome://tracing`.
+//!
+//! Each task timing becomes a complete (`"ph": "X"`) event, named after its
+//! source location. Local and remote timings are exported as separate
+//! processes, and every thread gets a `thread_name` metadata event.
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use gpui::{SerializedLocation, SerializedTaskTiming, SerializedThreadTaskTimings, SharedString};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+const NANOS_PER_MICRO: f64 = 1_000.0;
+const LOCAL_PID: u64 = 1;
+const REMOTE_PID: u64 = 2;
+
+/// Where a thread's timings were recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum TimingSource {
+    Local,
+    Remote,
+}
+
+impl TimingSource {
+    fn pid(self) -> u64 {
+        match self {
+            TimingSource::Local => LOCAL_PID,
+            TimingSource::Remote => REMOTE_PID,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            TimingSource::Local => "local",
+            TimingSource::Remote => "remote",
+        }
+    }
+
+    fn process_name(self) -> &'static str {
+        match self {
+            TimingSource::Local => "Zed",
+            TimingSource::Remote => "Zed remote server",
+        }
+    }
+}
+
+pub(crate) struct CapturedThread {
+    pub source: TimingSource,
+    pub thread: SerializedThreadTaskTimings,
+}
+
+#[derive(Serialize)]
+struct TraceFile<'a> {
+    #[serde(rename = "traceEvents")]
+    trace_events: &'a [TraceEvent],
+    #[serde(rename = "displayTimeUnit")]
+    display_time_unit: &'static str,
+}
+
+/// Traces are either an object with a `traceEvents` array or just the array.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TraceFileContents {
+    Object {
+        #[serde(rename = "traceEvents")]
+        trace_events: Vec<TraceEvent>,
+    },
+    Array(Vec<TraceEvent>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TraceEvent {
+    #[serde(default)]
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cat: Option<String>,
+    ph: String,
+    #[serde(default)]
+    pid: u64,
+    #[serde(default)]
+    tid: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ts: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dur: Option<f64>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    args: Value,
+}
+
+impl TraceEvent {
+    fn metadata(name: &str, pid: u64, tid: u64, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            cat: None,
+            ph: "M".to_string(),
+            pid,
+            tid,
+            ts: None,
+            dur: None,
+            args: json!({ "name": value }),
+        }
+    }
+}
+
+pub(crate) fn to_chrome_trace(threads: &[CapturedThread]) -> Result<String> {
+    let mut events = Vec::new();
+    let mut named_processes = Vec::new();
+    let mut next_tid = HashMap::<TimingSource, u64>::default();
+
+    for CapturedThread { source, thread } in threads {
+        let pid = source.pid();
+        if !named_processes.contains(source) {
+            named_processes.push(*source);
+            events.push(TraceEvent::metadata(
+                "process_name",
+                pid,
+                0,
+                source.process_name(),
+            ));
+        }
+
+        // Thread IDs are 64-bit hashes, which trace viewers can't represent
+        // exactly, so threads are numbered within their process instead.
+        let tid = next_tid.entry(*source).or_insert(0);
+        *tid += 1;
+        let tid = *tid;
+        let thread_name = thread
+            .thread_name
+            .clone()
+            .unwrap_or_else(|| format!("Thread {:x}", thread.thread_id));
+        events.push(TraceEvent::metadata("thread_name", pid, tid, &thread_name));
+
+        events.extend(thread.timings.iter().map(|timing| TraceEvent {
+            name: location_label(&timing.location),
+            cat: Some("task".to_string()),
+            ph: "X".to_string(),
+            pid,
+            tid,
+            ts: Some(timing.start as f64 / NANOS_PER_MICRO),
+            dur: Some(timing.duration as f64 / NANOS_PER_MICRO),
+            args: json!({
+                "file": timing.location.file,
+                "line": timing.location.line,
+                "column": timing.location.column,
+                "source": source.name(),
+            }),
+        }));
+    }
+
+    serde_json::to_string(&TraceFile {
+        trace_events: &events,
+        display_time_unit: "ms",
+    })
+    .context("failed to serialize trace")
+}
+
+pub(crate) fn from_chrome_trace(contents: &str) -> Result<Vec<CapturedThread>> {
+    let events = match serde_json::from_str(contents).context("failed to parse trace")? {
+        TraceFileContents::Object { trace_events } => trace_events,
+        TraceFileContents::Array(trace_events) => trace_events,
+    };
+
+    let mut process_sources = HashMap::<u64, TimingSource>::default();
+    let mut thread_names = HashMap::<(u64, u64), String>::default();
+    for event in events.iter().filter(|event| event.ph == "M") {
+        let Some(name) = event.args.get("name").and_then(|name| name.as_str()) else {
+            continue;
+        };
+        match event.name.as_str() {
+            "process_name" => {
+                let source = if name.to_lowercase().contains("remote") {
+                    TimingSource::Remote
+                } else {
+                    TimingSource::Local
+                };
+                process_sources.insert(event.pid, source);
+            }
+            "thread_name" => {
+                thread_names.insert((event.pid, event.tid), name.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    let mut threads = Vec::<CapturedThread>::new();
+    let mut thread_indices = HashMap::<(u64, u64), usize>::default();
+    for event in &events {
+        if event.ph != "X" {
+            continue;
+        }
+        let (Some(ts), Some(dur)) = (event.ts, event.dur) else {
+            continue;
+        };
+
+        let index = *thread_indices
+            .entry((event.pid, event.tid))
+            .or_insert_with(|| {
+                let source = match event.args.get("source").and_then(|source| source.as_str()) {
+                    Some("remote") => TimingSource::Remote,
+                    Some(_) => TimingSource::Local,
+                    None => process_sources
+                        .get(&event.pid)
+                        .copied()
+                        .unwrap_or(TimingSource::Local),
+                };
+                threads.push(CapturedThread {
+                    source,
+                    thread: SerializedThreadTaskTimings {
+                        thread_name: thread_names.get(&(event.pid, event.tid)).cloned(),
+                        thread_id: (event.pid << 32) | (event.tid & 0xffff_ffff),
+                        timings: Vec::new(),
+                    },
+                });
+                threads.len() - 1
+            });
+
+        threads[index].thread.timings.push(SerializedTaskTiming {
+            location: event_location(event),
+            start: (ts * NANOS_PER_MICRO).round().max(0.0) as u128,
+            duration: (dur * NANOS_PER_MICRO).round().max(0.0) as u128,
+        });
+    }
+
+    for thread in &mut threads {
+        thread.thread.timings.sort_by_key(|timing| timing.start);
+    }
+    Ok(threads)
+}
+
+fn location_label(location: &SerializedLocation) -> String {
+    format!("{}:{}:{}", location.file, location.line, location.column)
+}
+
+/// Reads the location from the event's arguments, as exported by
+/// [`to_chrome_trace`], falling back to the event name for other traces.
+fn event_location(event: &TraceEvent) -> SerializedLocation {
+    let file = event.args.get("file").and_then(|file| file.as_str());
+    let number = |key: &str| {
+        event
+            .args
+            .get(key)
+            .and_then(|value| value.as_u64())
+            .unwrap_or(0) as u32
+    };
+    match file {
+        Some(file) => SerializedLocation {
+            file: SharedString::from(file.to_string()),
+            line: number("line"),
+            column: number("column"),
+        },
+        None => SerializedLocation {
+            file: SharedString::from(event.name.clone()),
+            line: 0,
+            column: 0,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(file: &'static str, line: u32, start: u128, duration: u128) -> SerializedTaskTiming {
+        SerializedTaskTiming {
+            location: SerializedLocation {
+                file: file.into(),
+                line,
+                column: 5,
+            },
+            start,
+            duration,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let threads = vec![
+            CapturedThread {
+                source: TimingSource::Local,
+                thread: SerializedThreadTaskTimings {
+                    thread_name: Some("main".into()),
+                    thread_id: u64::MAX - 1,
+                    timings: vec![
+                        timing("crates/editor/src/editor.rs", 10, 1_500, 2_000_000),
+                        timing("crates/project/src/project.rs", 20, 3_000_000, 500),
+                    ],
+                },
+            },
+            CapturedThread {
+                source: TimingSource::Remote,
+                thread: SerializedThreadTaskTimings {
+                    thread_name: None,
+                    thread_id: 7,
+                    timings: vec![timing("crates/worktree/src/worktree.rs", 30, 42, 1_000)],
+                },
+            },
+        ];
+
+        let trace = to_chrome_trace(&threads).unwrap();
+        let json: Value = serde_json::from_str(&trace).unwrap();
+        let events = json["traceEvents"].as_array().unwrap();
+        let complete = events
+            .iter()
+            .find(|event| event["ph"] == "X")
+            .expect("trace has a complete event");
+        assert_eq!(complete["name"], "crates/editor/src/editor.rs:10:5");
+        assert_eq!(complete["ts"], 1.5);
+        assert_eq!(complete["dur"], 2000.0);
+        assert_eq!(complete["tid"], 1);
+
+        let imported = from_chrome_trace(&trace).unwrap();
+        assert_eq!(imported.len(), 2);
+
+        assert_eq!(imported[0].source, TimingSource::Local);
+        assert_eq!(imported[0].thread.thread_name.as_deref(), Some("main"));
+        let starts = imported[0]
+            .thread
+            .timings
+            .iter()
+            .map(|timing| (timing.start, timing.duration, timing.location.line))
+            .collect::<Vec<_>>();
+        assert_eq!(starts, [(1_500, 2_000_000, 10), (3_000_000, 500, 20)]);
+
+        assert_eq!(imported[1].source, TimingSource::Remote);
+        assert_eq!(imported[1].thread.thread_name.as_deref(), Some("Thread 7"));
+        let location = &imported[1].thread.timings[0].location;
+        assert_eq!(location.file.as_ref(), "crates/worktree/src/worktree.rs");
+        assert_eq!((location.line, location.column), (30, 5));
+    }
+
+    #[test]
+    fn test_import_foreign_trace() {
+        let trace = r#"[
+            {"name": "process_name", "ph": "M", "pid": 9, "args": {"name": "Renderer"}},
+            {"name": "Layout", "ph": "X", "pid": 9, "tid": 3, "ts": 20, "dur": 5},
+            {"name": "Paint", "ph": "X", "pid": 9, "tid": 3, "ts": 10, "dur": 1.5},
+            {"name": "Instant", "ph": "i", "pid": 9, "tid": 3, "ts": 12}
+        ]"#;
+
+        let imported = from_chrome_trace(trace).unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].source, TimingSource::Local);
+        let timings = imported[0]
+            .thread
+            .timings
+            .iter()
+            .map(|timing| {
+                (
+                    timing.location.file.to_string(),
+                    timing.start,
+                    timing.duration,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            timings,
+            [
+                ("Paint".to_string(), 10_000, 1_500),
+                ("Layout".to_string(), 20_000, 5_000),
+            ]
+        );
+
+        assert!(from_chrome_trace("not a trace").is_err());
+    }
+}
@@ -1,3 +1,5 @@
+mod chrome_trace;
+
 use std::{
     hash::{DefaultHasher, Hash, Hasher},
     path::PathBuf,
@@ -7,23 +9,25 @@ use std::{
 
 use gpui::{
     App, AppContext, ClipboardItem, Context, Div, Entity, Hsla, InteractiveElement,
-    ParentElement as _, ProfilingCollector, Render, SerializedLocation, SerializedTaskTiming,
-    SerializedThreadTaskTimings, SharedString, StatefulInteractiveElement, Styled, Task,
-    ThreadTimingsDelta, TitlebarOptions, UniformListScrollHandle, WeakEntity, WindowBounds,
-    WindowOptions, div, prelude::FluentBuilder, px, relative, size, uniform_list,
+    ParentElement as _, PathPromptOptions, ProfilingCollector, Render, SerializedLocation,
+    SerializedTaskTiming, SerializedThreadTaskTimings, SharedString, StatefulInteractiveElement,
+    Styled, Task, ThreadTimingsDelta, TitlebarOptions, UniformListScrollHandle, WeakEntity,
+    WindowBounds, WindowOptions, div, prelude::FluentBuilder, px, relative, size, uniform_list,
 };
 use rpc::{AnyProtoClient, proto};
 use util::ResultExt;
 use workspace::{
     Workspace,
     ui::{
-        ActiveTheme, Button, ButtonCommon, ButtonStyle, Checkbox, Clickable, ContextMenu, Divider,
-        DropdownMenu, ScrollAxes, ScrollableHandle as _, Scrollbars, ToggleState, Tooltip,
-        WithScrollbar, h_flex, v_flex,
+        ActiveTheme, Button, ButtonCommon, ButtonStyle, Checkbox, Clickable, Color, ContextMenu,
+        Disableable as _, Divider, DropdownMenu, Label, LabelCommon as _, ScrollAxes,
+        ScrollableHandle as _, Scrollbars, ToggleState, Tooltip, WithScrollbar, h_flex, v_flex,
     },
 };
 use zed_actions::OpenPerformanceProfiler;
 
+use crate::chrome_trace::{CapturedThread, TimingSource};
+
 const NANOS_PER_MS: u128 = 1_000_000;
 const VISIBLE_WINDOW_NANOS: u128 = 10 * 1_000_000_000;
 const REMOTE_POLL_INTERVAL: Duration = Duration::from_millis(500);
@@ -119,6 +123,13 @@ fn open_performance_profiler(
     });
 }
 
+/// A trace opened from disk, shown in place of live timings.
+struct ImportedTrace {
+    name: SharedString,
+    start_nanos: u128,
+    end_nanos: u128,
+}
+
 struct TimingBar {
     location: SerializedLocation,
     start_nanos: u128,
@@ -139,6 +150,9 @@ pub struct ProfilerWindow {
     has_remote: bool,
     remote_now_nanos: u128,
     remote_received_at: Option<Instant>,
+    /// When the current capture started, if one is being recorded.
+    capture_start_nanos: Option<u128>,
+    imported_trace: Option<ImportedTrace>,
     _remote_poll_task: Option<Task<()>>,
 }
 
@@ -161,6 +175,8 @@ impl ProfilerWindow {
             has_remote: false,
             remote_now_nanos: 0,
             remote_received_at: None,
+            capture_start_nanos: None,
+            imported_trace: None,
             _remote_poll_task: None,
         })
     }
@@ -189,7 +205,7 @@ impl ProfilerWindow {
 
     fn rebuild_display_timings(&mut self) {
         let include_self = self.include_self_timings.selected();
-        let cutoff_nanos = self.now_nanos().saturating_sub(VISIBLE_WINDOW_NANOS);
+        let (cutoff_nanos, _) = self.visible_window();
 
         let per_thread: Vec<Vec<SerializedTaskTiming>> = self
             .timings
@@ -203,7 +219,9 @@ impl ProfilerWindow {
     }
 
     fn now_nanos(&self) -> u128 {
-        if self.source.is_remote() {
+        if let Some(trace) = &self.imported_trace {
+            trace.end_nanos
+        } else if self.source.is_remote() {
             let elapsed_since_poll = self
                 .remote_received_at
                 .map(|at| Instant::now().duration_since(at).as_nanos())
@@ -216,25 +234,217 @@ impl ProfilerWindow {
         }
     }
 
+    /// The start and duration of the time span that the bars are laid out in:
+    /// the last few seconds of live timings, or all of an imported trace.
+    fn visible_window(&self) -> (u128, u128) {
+        if let Some(trace) = &self.imported_trace {
+            (
+                trace.start_nanos,
+                (trace.end_nanos - trace.start_nanos).max(1),
+            )
+        } else {
+            (
+                self.now_nanos().saturating_sub(VISIBLE_WINDOW_NANOS),
+                VISIBLE_WINDOW_NANOS,
+            )
+        }
+    }
+
     fn set_source(&mut self, source: ProfileSource, cx: &mut Context<Self>) {
-        if self.source == source {
+        if self.source == source && self.imported_trace.is_none() {
             return;
         }
 
+        // Picking a source while viewing a trace goes back to live timings.
+        if self.imported_trace.is_some() {
+            self.paused = false;
+        }
         self.source = source;
+        self.reset_timings(cx);
 
+        if source.is_remote() {
+            self.start_remote_polling(cx);
+        } else {
+            self._remote_poll_task = None;
+        }
+    }
+
+    fn reset_timings(&mut self, cx: &App) {
         self.timings.clear();
         self.collector.reset();
         self.display_timings = Rc::new(Vec::new());
         self.remote_now_nanos = 0;
         self.remote_received_at = None;
+        self.capture_start_nanos = None;
+        self.imported_trace = None;
         self.has_remote = self.remote_proto_client(cx).is_some();
+    }
 
-        if source.is_remote() {
+    fn set_paused(&mut self, paused: bool, cx: &mut Context<Self>) {
+        if !paused && self.imported_trace.is_some() {
+            self.reset_timings(cx);
+        }
+        self.paused = paused;
+        if !paused && self.source.is_remote() {
             self.start_remote_polling(cx);
-        } else {
+        } else if paused && self.source.is_remote() {
             self._remote_poll_task = None;
         }
+        cx.notify();
+    }
+
+    fn start_recording(&mut self, cx: &mut Context<Self>) {
+        if self.paused {
+            self.set_paused(false, cx);
+        }
+        self.capture_start_nanos = Some(self.now_nanos());
+        cx.notify();
+    }
+
+    /// Saves the timings recorded since the capture started as a Chrome trace.
+    fn stop_recording(&mut self, cx: &mut Context<Self>) {
+        let Some(capture_start_nanos) = self.capture_start_nanos.take() else {
+            return;
+        };
+        cx.notify();
+
+        let capture_end_nanos = self.now_nanos();
+        let source = if self.source.is_remote() {
+            TimingSource::Remote
+        } else {
+            TimingSource::Local
+        };
+        let threads = self
+            .timings
+            .iter()
+            .map(|thread| CapturedThread {
+                source,
+                thread: SerializedThreadTaskTimings {
+                    thread_name: thread.thread_name.clone(),
+                    thread_id: thread.thread_id,
+                    timings: thread
+                        .timings
+                        .iter()
+                        .filter(|timing| {
+                            timing.start + timing.duration >= capture_start_nanos
+                                && timing.start <= capture_end_nanos
+                        })
+                        .cloned()
+                        .collect(),
+                },
+            })
+            .filter(|captured| !captured.thread.timings.is_empty())
+            .collect::<Vec<_>>();
+        if threads.is_empty() {
+            return;
+        }
+
+        let Some(trace) = chrome_trace::to_chrome_trace(&threads).log_err() else {
+            return;
+        };
+        self.save_to_file(trace, "performance_trace.json", cx);
+    }
+
+    fn open_trace(&mut self, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Open Trace".into()),
+        });
+        cx.spawn(async move |this, cx| {
+            let Some(path) = paths
+                .await
+                .log_err()
+                .and_then(|paths| paths.log_err())
+                .flatten()
+                .and_then(|paths| paths.into_iter().next())
+            else {
+                return;
+            };
+            let Some(contents) = smol::fs::read_to_string(&path).await.log_err() else {
+                return;
+            };
+            let Some(threads) = cx
+                .background_spawn(async move { chrome_trace::from_chrome_trace(&contents) })
+                .await
+                .log_err()
+            else {
+                return;
+            };
+
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            this.update(cx, |this, cx| {
+                this.show_trace(name.into(), threads);
+                cx.notify();
+            })
+            .log_err();
+        })
+        .detach();
+    }
+
+    fn show_trace(&mut self, name: SharedString, threads: Vec<CapturedThread>) {
+        let timings = threads.into_iter().map(|captured| captured.thread);
+        self.timings = timings.collect();
+        let start_nanos = self
+            .timings
+            .iter()
+            .filter_map(|thread| thread.timings.iter().map(|timing| timing.start).min())
+            .min()
+            .unwrap_or(0);
+        let end_nanos = self
+            .timings
+            .iter()
+            .filter_map(|thread| {
+                thread
+                    .timings
+                    .iter()
+                    .map(|timing| timing.start + timing.duration)
+                    .max()
+            })
+            .max()
+            .unwrap_or(0);
+
+        self.paused = true;
+        self._remote_poll_task = None;
+        self.capture_start_nanos = None;
+        self.imported_trace = Some(ImportedTrace {
+            name,
+            start_nanos,
+            end_nanos,
+        });
+        self.rebuild_display_timings();
+    }
+
+    fn save_to_file(&self, contents: String, suggested_name: &str, cx: &mut Context<Self>) {
+        let active_path = self
+            .workspace
+            .as_ref()
+            .and_then(|workspace| {
+                workspace
+                    .read_with(cx, |workspace, cx| workspace.most_recent_active_path(cx))
+                    .log_err()
+                    .flatten()
+            })
+            .and_then(|p| p.parent().map(|p| p.to_owned()))
+            .unwrap_or_else(PathBuf::default);
+
+        let path = cx.prompt_for_new_path(&active_path, Some(suggested_name));
+
+        cx.background_spawn(async move {
+            let path = path.await;
+            let path = path.log_err().and_then(|p| p.log_err()).flatten();
+
+            let Some(path) = path else {
+                return;
+            };
+
+            smol::fs::write(path, &contents).await.log_err();
+        })
+        .detach();
     }
 
     fn remote_proto_client(&self, cx: &App) -> Option<AnyProtoClient> {
@@ -496,24 +706,48 @@ impl Render for ProfilerWindow {
                                 .style(ButtonStyle::Filled)
                                 .on_click(cx.listener(
                                     |this, _, _window, cx| {
-                                        this.paused = !this.paused;
-                                        if !this.paused && this.source.is_remote() {
-                                            this.start_remote_polling(cx);
-                                        } else if this.paused && this.source.is_remote() {
-                                            this._remote_poll_task = None;
+                                        this.set_paused(!this.paused, cx);
+                                    },
+                                )),
+                            )
+                            .child(
+                                Button::new(
+                                    "record-trace",
+                                    if self.capture_start_nanos.is_some() {
+                                        "Stop Recording"
+                                    } else {
+                                        "Record"
+                                    },
+                                )
+                                .style(ButtonStyle::Filled)
+                                .disabled(self.imported_trace.is_some())
+                                .tooltip(Tooltip::text(
+                                    "Record timings and save them as a Chrome trace",
+                                ))
+                                .on_click(cx.listener(
+                                    |this, _, _window, cx| {
+                                        if this.capture_start_nanos.is_some() {
+                                            this.stop_recording(cx);
+                                        } else {
+                                            this.start_recording(cx);
                                         }
-                                        cx.notify();
                                     },
                                 )),
                             )
+                            .child(
+                                Button::new("open-trace", "Open Trace")
+                                    .style(ButtonStyle::Filled)
+                                    .tooltip(Tooltip::text(
+                                        "View a Chrome trace saved by the profiler",
+                                    ))
+                                    .on_click(cx.listener(|this, _, _window, cx| {
+                                        this.open_trace(cx);
+                                    })),
+                            )
                             .child(
                                 Button::new("export-data", "Save")
                                     .style(ButtonStyle::Filled)
                                     .on_click(cx.listener(|this, _, _window, cx| {
-                                        let Some(workspace) = this.workspace.as_ref() else {
-                                            return;
-                                        };
-
                                         if this.timings.iter().all(|t| t.timings.is_empty()) {
                                             return;
                                         }
@@ -533,34 +767,19 @@ impl Render for ProfilerWindow {
                                             return;
                                         };
 
-                                        let active_path = workspace
-                                            .read_with(cx, |workspace, cx| {
-                                                workspace.most_recent_active_path(cx)
-                                            })
-                                            .log_err()
-                                            .flatten()
-                                            .and_then(|p| p.parent().map(|p| p.to_owned()))
-                                            .unwrap_or_else(PathBuf::default);
-
-                                        let path = cx.prompt_for_new_path(
-                                            &active_path,
-                                            Some("performance_profile.miniprof.json"),
+                                        this.save_to_file(
+                                            serialized,
+                                            "performance_profile.miniprof.json",
+                                            cx,
                                         );
-
-                                        cx.background_spawn(async move {
-                                            let path = path.await;
-                                            let path =
-                                                path.log_err().and_then(|p| p.log_err()).flatten();
-
-                                            let Some(path) = path else {
-                                                return;
-                                            };
-
-                                            smol::fs::write(path, &serialized).await.log_err();
-                                        })
-                                        .detach();
                                     })),
-                            ),
+                            )
+                            .when_some(self.imported_trace.as_ref(), |this, trace| {
+                                this.child(
+                                    Label::new(format!("Viewing {}", trace.name))
+                                        .color(Color::Muted),
+                                )
+                            }),
                     )
                     .child(
                         Checkbox::new("include-self", self.include_self_timings)
@@ -572,10 +791,7 @@ impl Render for ProfilerWindow {
                     ),
             )
             .when(!display_timings.is_empty(), |div| {
-                let now_nanos = self.now_nanos();
-
-                let window_start_nanos = now_nanos.saturating_sub(VISIBLE_WINDOW_NANOS);
-                let window_duration_nanos = VISIBLE_WINDOW_NANOS;
+                let (window_start_nanos, window_duration_nanos) = self.visible_window();
 
                 div.child(Divider::horizontal()).child(
                     v_flex()
@@ -260,6 +260,18 @@ pub fn keymap_backup_file() -> &'static PathBuf {
     KEYMAP_FILE.get_or_init(|| config_dir().join("keymap_backup.json"))
 }
 
+/// Returns the path to the `init.vim` file, which is run when vim mode is enabled.
+pub fn vim_script_file() -> &'static PathBuf {
+    static VIM_SCRIPT_FILE: OnceLock<PathBuf> = OnceLock::new();
+    VIM_SCRIPT_FILE.get_or_init(|| config_dir().join("init.vim"))
+}
+
+/// Returns the path to the user's spell checking word list.
+pub fn user_dictionary_file() -> &'static PathBuf {
+    static USER_DICTIONARY_FILE: OnceLock<PathBuf> = OnceLock::new();
+    USER_DICTIONARY_FILE.get_or_init(|| config_dir().join("dictionary.txt"))
+}
+
 /// Returns the path to the `tasks.json` file.
 pub fn tasks_file() -> &'static PathBuf {
     static TASKS_FILE: OnceLock<PathBuf> = OnceLock::new();
@@ -461,6 +473,13 @@ pub fn local_tasks_file_relative_path() -> &'static RelPath {
     *CACHED
 }
 
+/// Returns the relative path to a project's spell checking word list.
+pub fn local_dictionary_file_relative_path() -> &'static RelPath {
+    static CACHED: LazyLock<&'static RelPath> =
+        LazyLock::new(|| RelPath::unix(".zed/dictionary.txt").unwrap());
+    *CACHED
+}
+
 /// Returns the relative path to a `.vscode/tasks.json` file within a project.
 pub fn local_vscode_tasks_file_relative_path() -> &'static RelPath {
     static CACHED: LazyLock<&'static RelPath> =
@@ -521,6 +540,24 @@ pub fn cursor_settings_file_paths() -> Vec<PathBuf> {
     paths
 }
 
+/// Returns candidate paths for the vscode user keybindings file
+pub fn vscode_keybindings_file_paths() -> Vec<PathBuf> {
+    let mut paths = vscode_user_data_paths();
+    for path in paths.iter_mut() {
+        path.push("User/keybindings.json");
+    }
+    paths
+}
+
+/// Returns candidate paths for the cursor user keybindings file
+pub fn cursor_keybindings_file_paths() -> Vec<PathBuf> {
+    let mut paths = cursor_user_data_paths();
+    for path in paths.iter_mut() {
+        path.push("User/keybindings.json");
+    }
+    paths
+}
+
 fn vscode_user_data_paths() -> Vec<PathBuf> {
     // https://github.com/microsoft/vscode/blob/23e7148cdb6d8a27f0109ff77e5b1e019f8da051/src/vs/platform/environment/node/userDataPath.ts#L45
     const VSCODE_PRODUCT_NAMES: &[&str] = &[
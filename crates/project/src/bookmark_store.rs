@@ -0,0 +1,323 @@
+//! Module for managing line bookmarks in a project.
+//!
+//! Bookmarks are anchored to buffer positions so they follow edits, and are keyed by the absolute
+//! path of the file they belong to so that they can be persisted and restored per workspace.
+use anyhow::Result;
+use collections::BTreeMap;
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, SharedString, Subscription, Task};
+use language::{Buffer, BufferEvent, BufferSnapshot};
+use std::{ops::Range, path::Path, sync::Arc};
+use text::{Point, PointUtf16};
+
+use crate::{ProjectPath, buffer_store::BufferStore, worktree_store::WorktreeStore};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bookmark {
+    pub position: text::Anchor,
+    pub label: Option<SharedString>,
+}
+
+/// A bookmark as stored in the database, where positions are rows rather than anchors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializedBookmark {
+    pub row: u32,
+    pub label: Option<SharedString>,
+}
+
+struct BookmarksInFile {
+    buffer: Entity<Buffer>,
+    /// Bookmarks sorted by position, at most one per row.
+    bookmarks: Vec<Bookmark>,
+    _subscription: Subscription,
+}
+
+impl BookmarksInFile {
+    fn new(buffer: Entity<Buffer>, cx: &mut Context<BookmarkStore>) -> Self {
+        let subscription = cx.subscribe(&buffer, |bookmark_store, buffer, event, cx| match event {
+            BufferEvent::Saved => {
+                if let Some(abs_path) = BookmarkStore::abs_path_from_buffer(&buffer, cx) {
+                    cx.emit(BookmarkStoreEvent::BookmarksUpdated(abs_path));
+                }
+            }
+            BufferEvent::FileHandleChanged => {
+                let entity_id = buffer.entity_id();
+                let Some(old_path) = bookmark_store
+                    .bookmarks
+                    .iter()
+                    .find(|(_, in_file)| in_file.buffer.entity_id() == entity_id)
+                    .map(|(path, _)| path.clone())
+                else {
+                    return;
+                };
+
+                if buffer
+                    .read(cx)
+                    .file()
+                    .is_none_or(|f| f.disk_state().is_deleted())
+                {
+                    bookmark_store.bookmarks.remove(&old_path);
+                    cx.emit(BookmarkStoreEvent::BookmarksUpdated(old_path));
+                    cx.notify();
+                    return;
+                }
+
+                if let Some(new_path) = BookmarkStore::abs_path_from_buffer(&buffer, cx)
+                    && new_path != old_path
+                    && !bookmark_store.bookmarks.contains_key(&new_path)
+                    && let Some(in_file) = bookmark_store.bookmarks.remove(&old_path)
+                {
+                    bookmark_store.bookmarks.insert(new_path.clone(), in_file);
+                    cx.emit(BookmarkStoreEvent::BookmarksUpdated(old_path));
+                    cx.emit(BookmarkStoreEvent::BookmarksUpdated(new_path));
+                    cx.notify();
+                }
+            }
+            _ => {}
+        });
+
+        Self {
+            buffer,
+            bookmarks: Vec::new(),
+            _subscription: subscription,
+        }
+    }
+
+    fn index_for_row(&self, row: u32, snapshot: &BufferSnapshot) -> Result<usize, usize> {
+        self.bookmarks
+            .binary_search_by(|bookmark| bookmark.position.summary::<Point>(snapshot).row.cmp(&row))
+    }
+}
+
+pub enum BookmarkStoreEvent {
+    /// The bookmarks for the given path were added, removed, relabeled, or their file was saved.
+    BookmarksUpdated(Arc<Path>),
+}
+
+pub struct BookmarkStore {
+    worktree_store: Entity<WorktreeStore>,
+    buffer_store: Entity<BufferStore>,
+    bookmarks: BTreeMap<Arc<Path>, BookmarksInFile>,
+}
+
+impl EventEmitter<BookmarkStoreEvent> for BookmarkStore {}
+
+impl BookmarkStore {
+    pub fn new(worktree_store: Entity<WorktreeStore>, buffer_store: Entity<BufferStore>) -> Self {
+        Self {
+            worktree_store,
+            buffer_store,
+            bookmarks: BTreeMap::default(),
+        }
+    }
+
+    pub fn abs_path_from_buffer(buffer: &Entity<Buffer>, cx: &App) -> Option<Arc<Path>> {
+        worktree::File::from_dyn(buffer.read(cx).file())
+            .map(|file| file.worktree.read(cx).absolutize(&file.path))
+            .map(Arc::<Path>::from)
+    }
+
+    /// Adds a bookmark at the start of `row`, or removes the one that is already there.
+    pub fn toggle_bookmark(&mut self, buffer: Entity<Buffer>, row: u32, cx: &mut Context<Self>) {
+        let Some(abs_path) = Self::abs_path_from_buffer(&buffer, cx) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let in_file = self
+            .bookmarks
+            .entry(abs_path.clone())
+            .or_insert_with(|| BookmarksInFile::new(buffer, cx));
+
+        match in_file.index_for_row(row, &snapshot) {
+            Ok(ix) => {
+                in_file.bookmarks.remove(ix);
+                if in_file.bookmarks.is_empty() {
+                    self.bookmarks.remove(&abs_path);
+                }
+            }
+            Err(ix) => in_file.bookmarks.insert(
+                ix,
+                Bookmark {
+                    position: snapshot.anchor_before(Point::new(row, 0)),
+                    label: None,
+                },
+            ),
+        }
+
+        cx.emit(BookmarkStoreEvent::BookmarksUpdated(abs_path));
+        cx.notify();
+    }
+
+    /// Sets the label of the bookmark at `row`, creating the bookmark if there is none.
+    pub fn set_label(
+        &mut self,
+        buffer: Entity<Buffer>,
+        row: u32,
+        label: Option<SharedString>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(abs_path) = Self::abs_path_from_buffer(&buffer, cx) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let label = label.filter(|label| !label.trim().is_empty());
+        let in_file = self
+            .bookmarks
+            .entry(abs_path.clone())
+            .or_insert_with(|| BookmarksInFile::new(buffer, cx));
+
+        match in_file.index_for_row(row, &snapshot) {
+            Ok(ix) => in_file.bookmarks[ix].label = label,
+            Err(ix) => in_file.bookmarks.insert(
+                ix,
+                Bookmark {
+                    position: snapshot.anchor_before(Point::new(row, 0)),
+                    label,
+                },
+            ),
+        }
+
+        cx.emit(BookmarkStoreEvent::BookmarksUpdated(abs_path));
+        cx.notify();
+    }
+
+    pub fn bookmark_at_row(
+        &self,
+        buffer: &Entity<Buffer>,
+        row: u32,
+        snapshot: &BufferSnapshot,
+        cx: &App,
+    ) -> Option<&Bookmark> {
+        let abs_path = Self::abs_path_from_buffer(buffer, cx)?;
+        let in_file = self.bookmarks.get(&abs_path)?;
+        let ix = in_file.index_for_row(row, snapshot).ok()?;
+        in_file.bookmarks.get(ix)
+    }
+
+    /// Returns the bookmarks in `buffer`, optionally restricted to those within `range`.
+    pub fn bookmarks<'a>(
+        &'a self,
+        buffer: &'a Entity<Buffer>,
+        range: Option<Range<text::Anchor>>,
+        snapshot: &'a BufferSnapshot,
+        cx: &App,
+    ) -> impl Iterator<Item = &'a Bookmark> + 'a {
+        Self::abs_path_from_buffer(buffer, cx)
+            .and_then(|path| self.bookmarks.get(&path))
+            .into_iter()
+            .flat_map(move |in_file| {
+                let range = range.clone();
+                in_file.bookmarks.iter().filter(move |bookmark| {
+                    snapshot.can_resolve(&bookmark.position)
+                        && range.as_ref().is_none_or(|range| {
+                            bookmark.position.cmp(&range.start, snapshot).is_ge()
+                                && bookmark.position.cmp(&range.end, snapshot).is_le()
+                        })
+                })
+            })
+    }
+
+    /// Returns every bookmark in the project along with the buffer that contains it.
+    pub fn all_bookmarks(&self) -> impl Iterator<Item = (&Arc<Path>, &Entity<Buffer>, &Bookmark)> {
+        self.bookmarks.iter().flat_map(|(path, in_file)| {
+            in_file
+                .bookmarks
+                .iter()
+                .map(move |bookmark| (path, &in_file.buffer, bookmark))
+        })
+    }
+
+    pub fn serialized_bookmarks(&self, path: &Path, cx: &App) -> Vec<SerializedBookmark> {
+        let Some(in_file) = self.bookmarks.get(path) else {
+            return Vec::new();
+        };
+        let snapshot = in_file.buffer.read(cx).snapshot();
+        let mut bookmarks = in_file
+            .bookmarks
+            .iter()
+            .map(|bookmark| SerializedBookmark {
+                row: bookmark.position.summary::<Point>(&snapshot).row,
+                label: bookmark.label.clone(),
+            })
+            .collect::<Vec<_>>();
+        // Edits can collapse several bookmarked rows into one.
+        bookmarks.dedup_by_key(|bookmark| bookmark.row);
+        bookmarks
+    }
+
+    pub fn clear_bookmarks(&mut self, cx: &mut Context<Self>) {
+        let paths = self.bookmarks.keys().cloned().collect::<Vec<_>>();
+        self.bookmarks.clear();
+        for path in paths {
+            cx.emit(BookmarkStoreEvent::BookmarksUpdated(path));
+        }
+        cx.notify();
+    }
+
+    /// Opens the buffers referenced by `bookmarks` and anchors the bookmarks within them.
+    pub fn with_serialized_bookmarks(
+        &self,
+        bookmarks: BTreeMap<Arc<Path>, Vec<SerializedBookmark>>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let worktree_store = self.worktree_store.downgrade();
+        let buffer_store = self.buffer_store.downgrade();
+        cx.spawn(async move |this, cx| {
+            for (path, serialized) in bookmarks {
+                if serialized.is_empty() {
+                    continue;
+                }
+                let worktree = worktree_store
+                    .update(cx, |this, cx| {
+                        this.find_or_create_worktree(&path, false, cx)
+                    })?
+                    .await;
+                let Ok((worktree, relative_path)) = worktree else {
+                    log::debug!(
+                        "skipping bookmarks for {path:?}, which is outside of any worktree"
+                    );
+                    continue;
+                };
+                let buffer = buffer_store
+                    .update(cx, |this, cx| {
+                        let path = ProjectPath {
+                            worktree_id: worktree.read(cx).id(),
+                            path: relative_path,
+                        };
+                        this.open_buffer(path, cx)
+                    })?
+                    .await;
+                let Ok(buffer) = buffer else {
+                    log::debug!("skipping bookmarks for {path:?}, which could not be opened");
+                    continue;
+                };
+                let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+                let max_point = snapshot.max_point_utf16();
+
+                this.update(cx, |this, cx| {
+                    let mut in_file = BookmarksInFile::new(buffer, cx);
+                    for bookmark in serialized {
+                        let point = PointUtf16::new(bookmark.row, 0);
+                        if point > max_point {
+                            log::debug!("skipping a deserialized bookmark that's out of range");
+                            continue;
+                        }
+                        if let Err(ix) = in_file.index_for_row(bookmark.row, &snapshot) {
+                            in_file.bookmarks.insert(
+                                ix,
+                                Bookmark {
+                                    position: snapshot.anchor_before(point),
+                                    label: bookmark.label,
+                                },
+                            );
+                        }
+                    }
+                    if !in_file.bookmarks.is_empty() {
+                        this.bookmarks.insert(path, in_file);
+                    }
+                    cx.notify();
+                })?;
+            }
+            Ok(())
+        })
+    }
+}
@@ -34,7 +34,7 @@ use git::{
     repository::{
         Branch, CommitDetails, CommitDiff, CommitFile, CommitOptions, DiffType, FetchOptions,
         GitRepository, GitRepositoryCheckpoint, GraphCommitData, InitialGraphCommitData, LogOrder,
-        LogSource, PushOptions, Remote, RemoteCommandOutput, RepoPath, ResetMode,
+        LogSource, MergeStages, PushOptions, Remote, RemoteCommandOutput, RepoPath, ResetMode,
         UpstreamTrackingStatus, Worktree as GitWorktree,
     },
     stash::{GitStash, StashEntry},
@@ -570,6 +570,7 @@ impl GitStore {
         client.add_entity_request_handler(Self::handle_git_diff);
         client.add_entity_request_handler(Self::handle_tree_diff);
         client.add_entity_request_handler(Self::handle_get_blob_content);
+        client.add_entity_request_handler(Self::handle_load_merge_stages);
         client.add_entity_request_handler(Self::handle_open_unstaged_diff);
         client.add_entity_request_handler(Self::handle_open_uncommitted_diff);
         client.add_entity_message_handler(Self::handle_update_diff_bases);
@@ -2656,6 +2657,36 @@ impl GitStore {
         })
     }
 
+    async fn handle_load_merge_stages(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitLoadMergeStages>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitLoadMergeStagesResponse> {
+        let repository_id = RepositoryId::from_proto(envelope.payload.repository_id);
+        let repository_handle = Self::repository_for_request(&this, repository_id, &mut cx)?;
+        let path = RepoPath::from_proto(&envelope.payload.path)?;
+
+        let stages = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.load_merge_stages(path)
+            })
+            .await??;
+        Ok(match stages {
+            Some(stages) => proto::GitLoadMergeStagesResponse {
+                has_stages: true,
+                base: stages.base,
+                ours: stages.ours,
+                theirs: stages.theirs,
+            },
+            None => proto::GitLoadMergeStagesResponse {
+                has_stages: false,
+                base: None,
+                ours: None,
+                theirs: None,
+            },
+        })
+    }
+
     async fn handle_reset(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::GitReset>,
@@ -4503,6 +4534,35 @@ impl Repository {
         self.file_history_paginated(path, 0, None)
     }
 
+    /// Loads the base, ours and theirs versions of a conflicted file from the index.
+    pub fn load_merge_stages(
+        &mut self,
+        path: RepoPath,
+    ) -> oneshot::Receiver<Result<Option<MergeStages>>> {
+        let id = self.id;
+        self.send_job(None, move |git_repo, _cx| async move {
+            match git_repo {
+                RepositoryState::Local(LocalRepositoryState { backend, .. }) => {
+                    backend.load_merge_stages(path).await
+                }
+                RepositoryState::Remote(RemoteRepositoryState { client, project_id }) => {
+                    let response = client
+                        .request(proto::GitLoadMergeStages {
+                            project_id: project_id.0,
+                            repository_id: id.to_proto(),
+                            path: path.to_proto(),
+                        })
+                        .await?;
+                    Ok(response.has_stages.then(|| MergeStages {
+                        base: response.base,
+                        ours: response.ours,
+                        theirs: response.theirs,
+                    }))
+                }
+            }
+        })
+    }
+
     pub fn file_history_paginated(
         &mut self,
         path: RepoPath,
@@ -71,10 +71,11 @@ use http_client::HttpClient;
 use itertools::Itertools as _;
 use language::{
     Bias, BinaryStatus, Buffer, BufferRow, BufferSnapshot, CachedLspAdapter, Capability, CodeLabel,
-    Diagnostic, DiagnosticEntry, DiagnosticSet, DiagnosticSourceKind, Diff, File as _, Language,
-    LanguageName, LanguageRegistry, LocalFile, LspAdapter, LspAdapterDelegate, LspInstaller,
-    ManifestDelegate, ManifestName, Patch, PointUtf16, TextBufferSnapshot, ToOffset, ToPointUtf16,
-    Toolchain, Transaction, Unclipped,
+    Diagnostic, DiagnosticEntry, DiagnosticSet, DiagnosticSourceKind, Diff, ExternalFormatOutput,
+    ExternalFormatRequest, ExternalLintRequest, ExternalLinter, ExternalToolRegistry, File as _,
+    Language, LanguageName, LanguageRegistry, LocalFile, LspAdapter, LspAdapterDelegate,
+    LspInstaller, ManifestDelegate, ManifestName, Patch, PointUtf16, TextBufferSnapshot, ToOffset,
+    ToPointUtf16, Toolchain, Transaction, Unclipped,
     language_settings::{
         AllLanguageSettings, FormatOnSave, Formatter, LanguageSettings, all_language_settings,
         language_settings,
@@ -333,6 +334,15 @@ pub struct LocalLspStore {
     buffers_to_refresh_hash_set: HashSet<BufferId>,
     buffers_to_refresh_queue: VecDeque<BufferId>,
     _background_diagnostics_worker: Shared<Task<()>>,
+    /// The paths that each diagnostic source other than a language server
+    /// reported diagnostics for, by buffer.
+    diagnostic_sources: HashMap<LanguageServerId, HashMap<BufferId, PathBuf>>,
+    /// The IDs that diagnostics from each external linter are reported under.
+    external_linter_ids: HashMap<Arc<str>, LanguageServerId>,
+    /// The most recent run of each external linter that linted a buffer,
+    /// which results from earlier runs must not replace.
+    external_lints: HashMap<BufferId, HashMap<Arc<str>, usize>>,
+    next_external_lint_run: usize,
 }
 
 impl LocalLspStore {
@@ -1742,6 +1752,7 @@ impl LocalLspStore {
                         buffer,
                         &command,
                         arguments.as_deref(),
+                        None,
                         cx,
                     )
                     .await
@@ -1762,6 +1773,36 @@ impl LocalLspStore {
                         },
                     )?;
                 }
+                Formatter::Extension(formatter_name) => {
+                    let logger = zlog::scoped!(logger => "extension");
+                    zlog::trace!(logger => "formatting");
+                    let _timer = zlog::time!(logger => "Formatting buffer via extension formatter");
+
+                    let diff = Self::format_via_external_formatter(
+                        &lsp_store,
+                        buffer,
+                        formatter_name,
+                        &settings,
+                        cx,
+                    )
+                    .await
+                    .with_context(|| {
+                        format!("Failed to format buffer via formatter: {formatter_name}")
+                    })?;
+                    let Some(diff) = diff else {
+                        zlog::trace!(logger => "No changes");
+                        continue;
+                    };
+
+                    extend_formatting_transaction(
+                        buffer,
+                        formatting_transaction_id,
+                        cx,
+                        |buffer, cx| {
+                            buffer.apply_diff(diff, cx);
+                        },
+                    )?;
+                }
                 Formatter::LanguageServer(specifier) => {
                     let logger = zlog::scoped!(logger => "language-server");
                     zlog::trace!(logger => "formatting");
@@ -2371,10 +2412,71 @@ impl LocalLspStore {
         }
     }
 
+    async fn format_via_external_formatter(
+        lsp_store: &WeakEntity<LspStore>,
+        buffer: &FormattableBuffer,
+        formatter_name: &str,
+        settings: &LanguageSettings,
+        cx: &mut AsyncApp,
+    ) -> Result<Option<Diff>> {
+        let formatter = cx
+            .update(|cx| {
+                ExternalToolRegistry::try_global(cx)
+                    .and_then(|registry| registry.formatter(formatter_name))
+            })
+            .with_context(|| format!("no formatter named {formatter_name:?} is installed"))?;
+
+        let request_and_delegate = lsp_store.update(cx, |lsp_store, cx| {
+            let local = lsp_store.as_local()?;
+            let buffer = buffer.handle.read(cx);
+            let file = File::from_dyn(buffer.file())?;
+            let request = ExternalFormatRequest {
+                language: buffer
+                    .language()
+                    .map(|language| language.name())
+                    .unwrap_or_else(|| language::PLAIN_TEXT.name()),
+                path: file.as_local().map(|file| file.abs_path(cx)),
+                text: buffer.text(),
+                tab_size: settings.tab_size.get(),
+                hard_tabs: settings.hard_tabs,
+            };
+            let worktree = file.worktree.clone();
+            let delegate = LocalLspAdapterDelegate::from_local_lsp(local, &worktree, cx);
+            Some((request, delegate))
+        })?;
+        let Some((request, delegate)) = request_and_delegate else {
+            log::debug!("Cannot format a buffer outside of a local worktree via {formatter_name}");
+            return Ok(None);
+        };
+
+        let text = match formatter.format(request, delegate).await? {
+            ExternalFormatOutput::Unchanged => return Ok(None),
+            ExternalFormatOutput::Text(text) => text,
+            ExternalFormatOutput::Command(command) => {
+                return Self::format_via_external_command(
+                    buffer,
+                    &command.path.to_string_lossy(),
+                    Some(&command.arguments),
+                    Some(&command.env),
+                    cx,
+                )
+                .await;
+            }
+        };
+
+        Ok(Some(
+            buffer
+                .handle
+                .update(cx, |buffer, cx| buffer.diff(text, cx))
+                .await,
+        ))
+    }
+
     async fn format_via_external_command(
         buffer: &FormattableBuffer,
         command: &str,
         arguments: Option<&[String]>,
+        env: Option<&HashMap<String, String>>,
         cx: &mut AsyncApp,
     ) -> Result<Option<Diff>> {
         let working_dir_path = buffer.handle.update(cx, |buffer, cx| {
@@ -2394,6 +2496,10 @@ impl LocalLspStore {
             child.envs(buffer_env);
         }
 
+        if let Some(env) = env {
+            child.envs(env);
+        }
+
         if let Some(working_dir_path) = working_dir_path {
             child.current_dir(working_dir_path);
         }
@@ -4183,6 +4289,8 @@ impl LspStore {
             .detach();
         cx.observe_global::<SettingsStore>(Self::on_settings_changed)
             .detach();
+        cx.observe_global::<ExternalToolRegistry>(Self::clear_disabled_external_lints)
+            .detach();
         subscribe_to_binary_statuses(&languages, cx).detach();
 
         let _maintain_workspace_config = {
@@ -4207,6 +4315,10 @@ impl LspStore {
                 buffers_to_refresh_hash_set: HashSet::default(),
                 buffers_to_refresh_queue: VecDeque::new(),
                 _background_diagnostics_worker: Task::ready(()).shared(),
+                diagnostic_sources: HashMap::default(),
+                external_linter_ids: HashMap::default(),
+                external_lints: HashMap::default(),
+                next_external_lint_run: 0,
                 buffer_snapshots: Default::default(),
                 prettier_store,
                 environment,
@@ -4326,6 +4438,7 @@ impl LspStore {
             }
             BufferStoreEvent::BufferChangedFilePath { buffer, old_file } => {
                 let buffer_id = buffer.read(cx).remote_id();
+                self.clear_buffer_from_diagnostic_sources(buffer_id, cx);
                 if let Some(local) = self.as_local_mut()
                     && let Some(old_file) = File::from_dyn(old_file.as_ref())
                 {
@@ -4436,6 +4549,7 @@ impl LspStore {
             }
 
             language::BufferEvent::Saved => {
+                self.run_external_linters(&buffer, cx);
                 self.on_buffer_saved(buffer, cx);
             }
 
@@ -4452,6 +4566,11 @@ impl LspStore {
             this.on_buffer_event(buffer, event, cx);
         })
         .detach();
+        let buffer_id = buffer.read(cx).remote_id();
+        cx.observe_release(buffer, move |this, _, cx| {
+            this.clear_buffer_from_diagnostic_sources(buffer_id, cx);
+        })
+        .detach();
 
         self.detect_language_for_buffer(buffer, cx);
         if let Some(local) = self.as_local_mut() {
@@ -4461,6 +4580,153 @@ impl LspStore {
         Ok(())
     }
 
+    /// Runs the external linters enabled for the buffer's language and
+    /// reports their diagnostics, clearing those of the linters that are no
+    /// longer enabled.
+    fn run_external_linters(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let Some(registry) = ExternalToolRegistry::try_global(cx) else {
+            return;
+        };
+        if self.as_local().is_none() {
+            return;
+        }
+
+        let buffer_id = buffer.read(cx).remote_id();
+        let lint_inputs = maybe!({
+            let buffer = buffer.read(cx);
+            let language = buffer.language()?.name();
+            let file = File::from_dyn(buffer.file())?;
+            let abs_path = file.as_local()?.abs_path(cx);
+            Some((
+                language,
+                abs_path,
+                buffer.text(),
+                file.worktree.clone(),
+                enabled_external_linters(&registry, buffer, cx),
+            ))
+        });
+        let Some((language, abs_path, text, worktree, linters)) = lint_inputs else {
+            self.clear_external_lints(buffer_id, |_| false, cx);
+            return;
+        };
+
+        self.clear_external_lints(
+            buffer_id,
+            |name| linters.iter().any(|(linter_name, _)| linter_name == name),
+            cx,
+        );
+        if linters.is_empty() {
+            return;
+        }
+
+        let linters = linters
+            .into_iter()
+            .map(|(linter_name, linter)| {
+                let server_id = self
+                    .as_local()
+                    .and_then(|local| local.external_linter_ids.get(&linter_name))
+                    .copied()
+                    .unwrap_or_else(|| self.register_diagnostic_source());
+                (linter_name, linter, server_id)
+            })
+            .collect::<Vec<_>>();
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        let delegate = LocalLspAdapterDelegate::from_local_lsp(local, &worktree, cx);
+        let weak_buffer = buffer.downgrade();
+        for (linter_name, linter, server_id) in linters {
+            local
+                .external_linter_ids
+                .insert(linter_name.clone(), server_id);
+            let run = post_inc(&mut local.next_external_lint_run);
+            local
+                .external_lints
+                .entry(buffer_id)
+                .or_default()
+                .insert(linter_name.clone(), run);
+            let request = ExternalLintRequest {
+                language: language.clone(),
+                path: abs_path.clone(),
+                text: text.clone(),
+            };
+            let delegate = delegate.clone();
+            let buffer = weak_buffer.clone();
+            cx.spawn(async move |this, cx| {
+                let diagnostics = linter
+                    .lint(request, delegate)
+                    .await
+                    .with_context(|| format!("running linter {linter_name}"))?;
+                this.update(cx, |this, cx| {
+                    let is_latest_run = this
+                        .as_local()
+                        .and_then(|local| local.external_lints.get(&buffer_id)?.get(&linter_name))
+                        .is_some_and(|latest_run| *latest_run == run);
+                    let Some(buffer) = buffer.upgrade().filter(|_| is_latest_run) else {
+                        return Ok(());
+                    };
+                    this.publish_buffer_diagnostics(server_id, &buffer, diagnostics, cx)
+                })?
+            })
+            .detach_and_log_err(cx);
+        }
+    }
+
+    /// Clears the diagnostics of the external linters that are no longer
+    /// enabled for the buffers they linted.
+    fn clear_disabled_external_lints(&mut self, cx: &mut Context<Self>) {
+        let Some(local) = self.as_local() else {
+            return;
+        };
+        let registry = ExternalToolRegistry::try_global(cx).unwrap_or_default();
+        let buffer_ids = local.external_lints.keys().copied().collect::<Vec<_>>();
+        for buffer_id in buffer_ids {
+            let enabled_linters = self
+                .buffer_store
+                .read(cx)
+                .get(buffer_id)
+                .map(|buffer| {
+                    enabled_external_linters(&registry, buffer.read(cx), cx)
+                        .into_iter()
+                        .map(|(name, _)| name)
+                        .collect::<HashSet<_>>()
+                })
+                .unwrap_or_default();
+            self.clear_external_lints(buffer_id, |name| enabled_linters.contains(name), cx);
+        }
+    }
+
+    /// Clears the diagnostics that external linters reported for the buffer,
+    /// except for those of the linters that `keep` returns true for.
+    fn clear_external_lints(
+        &mut self,
+        buffer_id: BufferId,
+        keep: impl Fn(&Arc<str>) -> bool,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        let Some(lints) = local.external_lints.get_mut(&buffer_id) else {
+            return;
+        };
+        let mut cleared = Vec::new();
+        lints.retain(|name, _| {
+            if keep(name) {
+                return true;
+            }
+            cleared.extend(local.external_linter_ids.get(name).copied());
+            false
+        });
+        if lints.is_empty() {
+            local.external_lints.remove(&buffer_id);
+        }
+
+        for server_id in cleared {
+            self.clear_buffer_diagnostics(server_id, buffer_id, cx);
+        }
+    }
+
     pub fn refresh_background_diagnostics_for_buffers(
         &mut self,
         buffers: HashSet<BufferId>,
@@ -4528,9 +4794,13 @@ impl LspStore {
                 return handle;
             }
 
-            if ignore_refcounts || *refcount == 1 {
+            let is_first_registration = *refcount == 1;
+            if ignore_refcounts || is_first_registration {
                 local.register_buffer_with_language_servers(buffer, only_register_servers, cx);
             }
+            if is_first_registration {
+                self.run_external_linters(buffer, cx);
+            }
             if !ignore_refcounts {
                 cx.observe_release(&handle.0, move |lsp_store, buffer, cx| {
                     let refcount = {
@@ -4544,6 +4814,7 @@ impl LspStore {
                         *refcount
                     };
                     if refcount == 0 {
+                        lsp_store.clear_external_lints(buffer_id, |_| false, cx);
                         lsp_store.lsp_data.remove(&buffer_id);
                         let local = lsp_store.as_local_mut().unwrap();
                         local.registered_buffers.remove(&buffer_id);
@@ -4790,6 +5061,7 @@ impl LspStore {
                 && local.registered_buffers.contains_key(&buffer_id)
             {
                 local.register_buffer_with_language_servers(buffer_entity, HashSet::default(), cx);
+                self.run_external_linters(buffer_entity, cx);
             }
             Some(worktree.read(cx).id())
         } else {
@@ -5126,6 +5398,7 @@ impl LspStore {
         {
             self.restart_all_language_servers(cx);
         }
+        self.clear_disabled_external_lints(cx);
 
         cx.notify();
     }
@@ -11084,6 +11357,123 @@ impl LspStore {
         )
     }
 
+    /// Allocates the ID that a source of diagnostics other than a language
+    /// server, such as a spell checker, reports diagnostics under.
+    pub fn register_diagnostic_source(&mut self) -> LanguageServerId {
+        self.languages.next_language_server_id()
+    }
+
+    /// Replaces the diagnostics that a source registered with
+    /// [`Self::register_diagnostic_source`] reported for a buffer.
+    ///
+    /// The diagnostics are cleared when the buffer is released or its file
+    /// moves, and those reported for the file's previous path are cleared.
+    pub fn publish_buffer_diagnostics(
+        &mut self,
+        source_id: LanguageServerId,
+        buffer: &Entity<Buffer>,
+        diagnostics: Vec<lsp::Diagnostic>,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let buffer_id = buffer.read(cx).remote_id();
+        let abs_path = File::from_dyn(buffer.read(cx).file())
+            .and_then(|file| Some(file.as_local()?.abs_path(cx)));
+        let Some(abs_path) = abs_path else {
+            self.clear_buffer_diagnostics(source_id, buffer_id, cx);
+            return Ok(());
+        };
+        let local = self
+            .as_local_mut()
+            .context("publishing diagnostics on remote")?;
+        let previous_path = local
+            .diagnostic_sources
+            .entry(source_id)
+            .or_default()
+            .insert(buffer_id, abs_path.clone());
+        if let Some(previous_path) = previous_path
+            && previous_path != abs_path
+        {
+            self.publish_source_diagnostics(source_id, previous_path, Vec::new(), cx)?;
+        }
+        self.publish_source_diagnostics(source_id, abs_path, diagnostics, cx)
+    }
+
+    /// Clears the diagnostics that a source reported for a buffer.
+    pub fn clear_buffer_diagnostics(
+        &mut self,
+        source_id: LanguageServerId,
+        buffer_id: BufferId,
+        cx: &mut Context<Self>,
+    ) {
+        let abs_path = self.as_local_mut().and_then(|local| {
+            local
+                .diagnostic_sources
+                .get_mut(&source_id)?
+                .remove(&buffer_id)
+        });
+        if let Some(abs_path) = abs_path {
+            self.publish_source_diagnostics(source_id, abs_path, Vec::new(), cx)
+                .log_err();
+        }
+    }
+
+    /// Clears every diagnostic that a source reported.
+    pub fn clear_diagnostic_source(&mut self, source_id: LanguageServerId, cx: &mut Context<Self>) {
+        let abs_paths = self
+            .as_local_mut()
+            .and_then(|local| local.diagnostic_sources.remove(&source_id));
+        for abs_path in abs_paths.into_iter().flat_map(HashMap::into_values) {
+            self.publish_source_diagnostics(source_id, abs_path, Vec::new(), cx)
+                .log_err();
+        }
+    }
+
+    fn clear_buffer_from_diagnostic_sources(
+        &mut self,
+        buffer_id: BufferId,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(local) = self.as_local() else {
+            return;
+        };
+        let source_ids = local
+            .diagnostic_sources
+            .iter()
+            .filter(|(_, abs_paths)| abs_paths.contains_key(&buffer_id))
+            .map(|(source_id, _)| *source_id)
+            .collect::<Vec<_>>();
+        for source_id in source_ids {
+            self.clear_buffer_diagnostics(source_id, buffer_id, cx);
+        }
+    }
+
+    fn publish_source_diagnostics(
+        &mut self,
+        source_id: LanguageServerId,
+        abs_path: PathBuf,
+        diagnostics: Vec<lsp::Diagnostic>,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let uri = lsp::Uri::from_file_path(&abs_path)
+            .map_err(|()| anyhow!("Failed to convert path {abs_path:?} to lsp Uri"))?;
+        self.merge_lsp_diagnostics(
+            DiagnosticSourceKind::Pushed,
+            vec![DocumentDiagnosticsUpdate {
+                diagnostics: lsp::PublishDiagnosticsParams {
+                    uri,
+                    diagnostics,
+                    version: None,
+                },
+                result_id: None,
+                server_id: source_id,
+                disk_based_sources: Cow::Borrowed(&[]),
+                registration_id: None,
+            }],
+            |_, _, _| false,
+            cx,
+        )
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn update_diagnostics(
         &mut self,
@@ -13114,6 +13504,34 @@ fn parse_register_capabilities<T: serde::de::DeserializeOwned>(
     })
 }
 
+/// Returns the external linters enabled for the buffer's language, in the
+/// order they're configured in.
+fn enabled_external_linters(
+    registry: &ExternalToolRegistry,
+    buffer: &Buffer,
+    cx: &App,
+) -> Vec<(Arc<str>, Arc<dyn ExternalLinter>)> {
+    let Some(language) = buffer.language().map(|language| language.name()) else {
+        return Vec::new();
+    };
+    let mut available_linters = registry.linters_for_language(&language);
+    let enabled_linters = language_settings(Some(language), buffer.file(), cx).customized_linters(
+        &available_linters
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>(),
+    );
+    enabled_linters
+        .into_iter()
+        .filter_map(|name| {
+            let ix = available_linters
+                .iter()
+                .position(|(available, _)| *available == name)?;
+            Some(available_linters.swap_remove(ix))
+        })
+        .collect()
+}
+
 fn subscribe_to_binary_statuses(
     languages: &Arc<LanguageRegistry>,
     cx: &mut Context<'_, LspStore>,
@@ -1,11 +1,16 @@
-use std::{collections::VecDeque, sync::Arc};
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
+use anyhow::{Context as _, Result};
 use collections::HashMap;
 use futures::{StreamExt, channel::mpsc};
 use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Global, Subscription, WeakEntity};
 use lsp::{
     IoKind, LanguageServer, LanguageServerId, LanguageServerName, LanguageServerSelector,
-    MessageType, TraceValue,
+    MessageType, TraceValue, TranscriptWriter,
 };
 use rpc::proto;
 use settings::WorktreeId;
@@ -133,9 +138,17 @@ pub struct LanguageServerState {
     pub trace_level: TraceValue,
     pub log_level: MessageType,
     io_logs_subscription: Option<lsp::Subscription>,
+    recording: Option<LspRecording>,
     pub toggled_log_kind: Option<LogKind>,
 }
 
+/// A transcript of the server's JSON-RPC traffic being written to disk, which
+/// can be replayed with `lsp::FakeLanguageServer::replay`.
+struct LspRecording {
+    path: PathBuf,
+    writer: TranscriptWriter,
+}
+
 impl std::fmt::Debug for LanguageServerState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("LanguageServerState")
@@ -148,6 +161,10 @@ impl std::fmt::Debug for LanguageServerState {
             .field("trace_level", &self.trace_level)
             .field("log_level", &self.log_level)
             .field("toggled_log_kind", &self.toggled_log_kind)
+            .field(
+                "recording",
+                &self.recording.as_ref().map(|recording| &recording.path),
+            )
             .finish_non_exhaustive()
     }
 }
@@ -383,6 +400,7 @@ impl LogStore {
                 trace_level: TraceValue::Off,
                 log_level: MessageType::LOG,
                 io_logs_subscription: None,
+                recording: None,
                 toggled_log_kind: None,
             }
         });
@@ -520,10 +538,17 @@ impl LogStore {
         cx: &mut Context<'_, Self>,
     ) {
         let store_logs = !self.on_headless_host;
-        let Some(state) = self
-            .get_language_server_state(language_server_id)
-            .and_then(|state| state.rpc_state.as_mut())
-        else {
+        let Some(server_state) = self.get_language_server_state(language_server_id) else {
+            return;
+        };
+        if let Some(recording) = server_state.recording.as_ref() {
+            let io_kind = match kind {
+                MessageKind::Send => IoKind::StdIn,
+                MessageKind::Receive => IoKind::StdOut,
+            };
+            recording.writer.record(io_kind, message);
+        }
+        let Some(state) = server_state.rpc_state.as_mut() else {
             return;
         };
 
@@ -624,6 +649,44 @@ impl LogStore {
         Some(())
     }
 
+    /// Starts writing the server's JSON-RPC traffic to a transcript at `path`,
+    /// replacing any recording in progress.
+    pub fn start_recording(
+        &mut self,
+        server_id: LanguageServerId,
+        path: PathBuf,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let state = self
+            .language_servers
+            .get_mut(&server_id)
+            .with_context(|| format!("language server {server_id} not found"))?;
+        let writer = TranscriptWriter::create(&path, cx)?;
+        state.recording = Some(LspRecording { path, writer });
+        cx.notify();
+        Ok(())
+    }
+
+    /// Stops recording the server's traffic, returning the transcript's path.
+    pub fn stop_recording(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut Context<Self>,
+    ) -> Option<PathBuf> {
+        let recording = self
+            .language_servers
+            .get_mut(&server_id)?
+            .recording
+            .take()?;
+        cx.notify();
+        Some(recording.path)
+    }
+
+    pub fn recording_path(&self, server_id: LanguageServerId) -> Option<&Path> {
+        let recording = self.language_servers.get(&server_id)?.recording.as_ref()?;
+        Some(&recording.path)
+    }
+
     pub fn has_server_logs(&self, server: &LanguageServerSelector) -> bool {
         match server {
             LanguageServerSelector::Id(id) => self.language_servers.contains_key(id),
@@ -1,5 +1,6 @@
 pub mod agent_registry_store;
 pub mod agent_server_store;
+pub mod bookmark_store;
 pub mod buffer_store;
 pub mod color_extractor;
 pub mod connection_manager;
@@ -53,6 +54,7 @@ pub use manifest_tree::ManifestTree;
 pub use project_search::{Search, SearchResults};
 
 use anyhow::{Context as _, Result, anyhow};
+use bookmark_store::BookmarkStore;
 use buffer_store::{BufferStore, BufferStoreEvent};
 use client::{
     Client, Collaborator, PendingEntitySubscription, ProjectId, TypedEnvelope, UserStore, proto,
@@ -140,8 +142,9 @@ use util::{
 };
 use worktree::{CreatedEntry, Snapshot, Traversal};
 pub use worktree::{
-    Entry, EntryKind, FS_WATCH_LATENCY, File, LocalWorktree, PathChange, ProjectEntryId,
-    UpdatedEntriesSet, UpdatedGitRepositoriesSet, Worktree, WorktreeId, WorktreeSettings,
+    Entry, EntryKind, FS_WATCH_LATENCY, File, LocalWorktree, MAX_LOADABLE_FILE_SIZE, PathChange,
+    ProjectEntryId, UpdatedEntriesSet, UpdatedGitRepositoriesSet, Worktree, WorktreeId,
+    WorktreeSettings,
 };
 use worktree_store::{WorktreeStore, WorktreeStoreEvent};
 
@@ -211,6 +214,7 @@ pub struct Project {
     agent_server_store: Entity<AgentServerStore>,
 
     breakpoint_store: Entity<BreakpointStore>,
+    bookmark_store: Entity<BookmarkStore>,
     collab_client: Arc<client::Client>,
     join_project_response_message_id: u32,
     task_store: Entity<TaskStore>,
@@ -394,7 +398,9 @@ pub enum Event {
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
     ExpandedAllForEntry(WorktreeId, ProjectEntryId),
-    EntryRenamed(ProjectTransaction, ProjectPath, PathBuf),
+    /// An entry was renamed through [`Project::rename_entry`]. Holds the edits language servers
+    /// made in response, the entry's new path, its new absolute path and its old absolute path.
+    EntryRenamed(ProjectTransaction, ProjectPath, PathBuf, PathBuf),
     WorkspaceEditApplied(ProjectTransaction),
     AgentLocationChanged,
     BufferEdited,
@@ -1196,6 +1202,8 @@ impl Project {
 
             let breakpoint_store =
                 cx.new(|_| BreakpointStore::local(worktree_store.clone(), buffer_store.clone()));
+            let bookmark_store =
+                cx.new(|_| BookmarkStore::new(worktree_store.clone(), buffer_store.clone()));
 
             let dap_store = cx.new(|cx| {
                 DapStore::new_local(
@@ -1312,6 +1320,7 @@ impl Project {
                 fs,
                 remote_client: None,
                 breakpoint_store,
+                bookmark_store,
                 dap_store,
                 agent_server_store,
 
@@ -1475,6 +1484,8 @@ impl Project {
                     worktree_store.clone(),
                 )
             });
+            let bookmark_store =
+                cx.new(|_| BookmarkStore::new(worktree_store.clone(), buffer_store.clone()));
 
             let dap_store = cx.new(|cx| {
                 DapStore::new_remote(
@@ -1513,6 +1524,7 @@ impl Project {
                 lsp_store,
                 context_server_store,
                 breakpoint_store,
+                bookmark_store,
                 dap_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
@@ -1697,6 +1709,8 @@ impl Project {
                 worktree_store.clone(),
             )
         });
+        let bookmark_store =
+            cx.new(|_| BookmarkStore::new(worktree_store.clone(), buffer_store.clone()));
         let dap_store = cx.new(|cx| {
             DapStore::new_collab(
                 remote_id,
@@ -1822,6 +1836,7 @@ impl Project {
                     replica_id,
                 },
                 breakpoint_store: breakpoint_store.clone(),
+                bookmark_store,
                 dap_store: dap_store.clone(),
                 git_store: git_store.clone(),
                 agent_server_store,
@@ -2059,6 +2074,11 @@ impl Project {
         self.breakpoint_store.clone()
     }
 
+    #[inline]
+    pub fn bookmark_store(&self) -> Entity<BookmarkStore> {
+        self.bookmark_store.clone()
+    }
+
     pub fn active_debug_session(&self, cx: &App) -> Option<(Entity<Session>, ActiveStackFrame)> {
         let active_position = self.breakpoint_store.read(cx).active_position()?;
         let session = self
@@ -2499,6 +2519,7 @@ impl Project {
                         transaction,
                         new_path.clone(),
                         new_abs_path.clone(),
+                        old_abs_path.clone(),
                     ));
                 })
                 .ok();
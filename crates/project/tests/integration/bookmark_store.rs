@@ -0,0 +1,108 @@
+use std::{path::Path, sync::Arc};
+
+use collections::BTreeMap;
+use fs::FakeFs;
+use gpui::TestAppContext;
+use project::{Project, bookmark_store::SerializedBookmark};
+use serde_json::json;
+use settings::SettingsStore;
+use util::path;
+
+fn init_test(cx: &mut TestAppContext) {
+    zlog::init_test();
+
+    cx.update(|cx| {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+    });
+}
+
+#[gpui::test]
+async fn test_bookmarks_follow_edits(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({ "a.txt": "one\ntwo\nthree\nfour\n" }))
+        .await;
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/a.txt"), cx)
+        })
+        .await
+        .unwrap();
+    let store = project.read_with(cx, |project, _| project.bookmark_store());
+    let abs_path: Arc<Path> = Path::new(path!("/dir/a.txt")).into();
+
+    store.update(cx, |store, cx| {
+        store.toggle_bookmark(buffer.clone(), 2, cx);
+        store.toggle_bookmark(buffer.clone(), 0, cx);
+        store.set_label(buffer.clone(), 2, Some("third".into()), cx);
+    });
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+
+    store.read_with(cx, |store, cx| {
+        assert_eq!(
+            store.serialized_bookmarks(&abs_path, cx),
+            vec![
+                SerializedBookmark {
+                    row: 1,
+                    label: None
+                },
+                SerializedBookmark {
+                    row: 3,
+                    label: Some("third".into())
+                },
+            ]
+        );
+    });
+
+    store.update(cx, |store, cx| {
+        store.toggle_bookmark(buffer.clone(), 1, cx);
+        store.toggle_bookmark(buffer.clone(), 3, cx);
+    });
+    store.read_with(cx, |store, cx| {
+        assert!(store.serialized_bookmarks(&abs_path, cx).is_empty());
+        assert_eq!(store.all_bookmarks().count(), 0);
+    });
+}
+
+#[gpui::test]
+async fn test_restoring_serialized_bookmarks(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({ "a.txt": "one\ntwo\nthree\n" }))
+        .await;
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let store = project.read_with(cx, |project, _| project.bookmark_store());
+    let abs_path: Arc<Path> = Path::new(path!("/dir/a.txt")).into();
+
+    let serialized = vec![
+        SerializedBookmark {
+            row: 1,
+            label: Some("two".into()),
+        },
+        SerializedBookmark {
+            row: 99,
+            label: None,
+        },
+    ];
+    store
+        .update(cx, |store, cx| {
+            store.with_serialized_bookmarks(
+                BTreeMap::from_iter([(abs_path.clone(), serialized)]),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+
+    store.read_with(cx, |store, cx| {
+        assert_eq!(
+            store.serialized_bookmarks(&abs_path, cx),
+            vec![SerializedBookmark {
+                row: 1,
+                label: Some("two".into())
+            }]
+        );
+    });
+}
@@ -0,0 +1,263 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use collections::{HashMap, HashSet};
+use fs::FakeFs;
+use gpui::{TestAppContext, UpdateGlobal as _};
+use language::{
+    ExternalFormatCommand, ExternalFormatOutput, ExternalFormatRequest, ExternalFormatter,
+    ExternalLintRequest, ExternalLinter, ExternalToolRegistry, LspAdapterDelegate, Point,
+    language_settings::LanguageSettingsContent, rust_lang,
+};
+use parking_lot::Mutex;
+use project::{
+    Project,
+    lsp_store::{FormatTrigger, LspFormatTarget},
+};
+use serde_json::json;
+use settings::{Formatter, FormatterList, LinterList, SettingsStore};
+use util::{path, test::TempTree};
+
+use crate::init_test;
+
+/// A linter that reports an error on the first word of the buffer and records
+/// the requests it receives.
+#[derive(Default)]
+struct FakeLinter {
+    requests: Mutex<Vec<ExternalLintRequest>>,
+}
+
+#[async_trait]
+impl ExternalLinter for FakeLinter {
+    async fn lint(
+        &self,
+        request: ExternalLintRequest,
+        _delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<Vec<lsp::Diagnostic>> {
+        let word_len = request.text.split_whitespace().next().unwrap_or("").len();
+        self.requests.lock().push(request);
+        Ok(vec![lsp::Diagnostic {
+            range: lsp::Range::new(
+                lsp::Position::new(0, 0),
+                lsp::Position::new(0, word_len as u32),
+            ),
+            severity: Some(lsp::DiagnosticSeverity::ERROR),
+            message: "fake lint".to_string(),
+            ..Default::default()
+        }])
+    }
+}
+
+/// A formatter that asks Zed to run a command with the given environment.
+struct FakeCommandFormatter {
+    command: ExternalFormatCommand,
+}
+
+#[async_trait]
+impl ExternalFormatter for FakeCommandFormatter {
+    async fn format(
+        &self,
+        _request: ExternalFormatRequest,
+        _delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<ExternalFormatOutput> {
+        Ok(ExternalFormatOutput::Command(self.command.clone()))
+    }
+}
+
+fn update_rust_settings(cx: &mut TestAppContext, settings: LanguageSettingsContent) {
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings(cx, |user_settings| {
+                user_settings
+                    .languages_mut()
+                    .insert("Rust".into(), settings);
+            });
+        })
+    });
+}
+
+#[gpui::test]
+async fn test_external_tool_registry(cx: &mut TestAppContext) {
+    init_test(cx);
+
+    let linter = Arc::new(FakeLinter::default());
+    let registry = cx.update(|cx| ExternalToolRegistry::global(cx).clone());
+    registry.register_linter("rust-lint".into(), vec!["Rust".into()], linter.clone());
+    registry.register_linter("json-lint".into(), vec!["JSON".into()], linter);
+
+    assert_eq!(
+        registry.linter_names(Some(&"Rust".into())),
+        vec![Arc::<str>::from("rust-lint")]
+    );
+    assert_eq!(
+        registry.linter_names(None),
+        vec![Arc::<str>::from("json-lint"), Arc::from("rust-lint")]
+    );
+    assert!(registry.formatter_names(None).is_empty());
+
+    registry.remove_linter("rust-lint");
+    assert!(registry.linters_for_language(&"Rust".into()).is_empty());
+    assert!(
+        cx.read(ExternalToolRegistry::try_global)
+            .unwrap()
+            .linter_names(Some(&"JSON".into()))
+            .contains(&Arc::from("json-lint"))
+    );
+}
+
+#[gpui::test]
+async fn test_external_linter_diagnostics(cx: &mut TestAppContext) {
+    init_test(cx);
+
+    let linter = Arc::new(FakeLinter::default());
+    cx.update(|cx| {
+        ExternalToolRegistry::global(cx).register_linter(
+            "fake-lint".into(),
+            vec!["Rust".into()],
+            linter.clone(),
+        )
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({ "a.rs": "let a = 1;" }))
+        .await;
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    {
+        let requests = linter.requests.lock();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, PathBuf::from(path!("/dir/a.rs")));
+        assert_eq!(requests[0].text, "let a = 1;");
+    }
+    buffer.read_with(cx, |buffer, _| {
+        let diagnostics = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| (entry.range, entry.diagnostic.message.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [(Point::new(0, 0)..Point::new(0, 3), "fake lint".to_string())]
+        );
+    });
+
+    // Linters run again with the new contents when the buffer is saved.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..3, "const")], None, cx));
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    cx.run_until_parked();
+    assert_eq!(linter.requests.lock().len(), 2);
+    assert_eq!(linter.requests.lock()[1].text, "const a = 1;");
+    buffer.read_with(cx, |buffer, _| {
+        let ranges = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+            .map(|entry| entry.range)
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, [Point::new(0, 0)..Point::new(0, 5)]);
+    });
+
+    // Disabled linters are not run, and their diagnostics are cleared.
+    update_rust_settings(
+        cx,
+        LanguageSettingsContent {
+            linters: Some(LinterList(vec!["!fake-lint".into(), "...".into()])),
+            ..Default::default()
+        },
+    );
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    cx.run_until_parked();
+    assert_eq!(linter.requests.lock().len(), 2);
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+                .count(),
+            0
+        );
+    });
+}
+
+#[cfg(not(windows))]
+#[gpui::test]
+async fn test_external_formatter_command_env(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+
+    // The command runs in the worktree root, so it has to exist on disk.
+    let dir = TempTree::new(json!({}));
+    let root = dir.path().to_path_buf();
+
+    cx.update(|cx| {
+        ExternalToolRegistry::global(cx).register_formatter(
+            "fake-format".into(),
+            vec!["Rust".into()],
+            Arc::new(FakeCommandFormatter {
+                command: ExternalFormatCommand {
+                    path: "sh".into(),
+                    arguments: vec![
+                        "-c".into(),
+                        "cat > /dev/null; printf '%s' \"$FAKE_FORMAT_OUTPUT\"".into(),
+                    ],
+                    env: HashMap::from_iter([(
+                        "FAKE_FORMAT_OUTPUT".to_string(),
+                        "let formatted = true;\n".to_string(),
+                    )]),
+                },
+            }),
+        )
+    });
+    update_rust_settings(
+        cx,
+        LanguageSettingsContent {
+            formatter: Some(FormatterList::Single(Formatter::Extension(
+                "fake-format".into(),
+            ))),
+            ..Default::default()
+        },
+    );
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(&root, json!({ "a.rs": "let a=1;\n" })).await;
+    let project = Project::test(fs, [root.as_path()], cx).await;
+    project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(root.join("a.rs"), cx)
+        })
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.format(
+                HashSet::from_iter([buffer.clone()]),
+                LspFormatTarget::Buffers,
+                true,
+                FormatTrigger::Manual,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "let formatted = true;\n");
+    });
+}
@@ -1,10 +1,12 @@
 #![allow(clippy::format_collect)]
 
+mod bookmark_store;
 mod color_extractor;
 mod context_server_store;
 mod debugger;
 mod ext_agent_tests;
 mod extension_agent_tests;
+mod external_tools;
 mod git_store;
 mod image_store;
 mod lsp_command;
@@ -2778,6 +2780,86 @@ async fn test_omitted_diagnostics(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_diagnostic_source(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({ "a.txt": "one two" }))
+        .await;
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let lsp_store = project.read_with(cx, |project, _| project.lsp_store());
+    let worktree_id = project.read_with(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/a.txt"), cx)
+        })
+        .await
+        .unwrap();
+
+    let source_id = lsp_store.update(cx, |lsp_store, _| lsp_store.register_diagnostic_source());
+    let publish = |cx: &mut gpui::TestAppContext| {
+        lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store
+                .publish_buffer_diagnostics(
+                    source_id,
+                    &buffer,
+                    vec![lsp::Diagnostic {
+                        range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 3)),
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        message: "error".to_string(),
+                        ..Default::default()
+                    }],
+                    cx,
+                )
+                .unwrap();
+        });
+    };
+    let summaries = |cx: &mut gpui::TestAppContext| {
+        project.read_with(cx, |project, cx| {
+            project
+                .diagnostic_summaries(false, cx)
+                .map(|(path, server_id, summary)| {
+                    (
+                        path.path.as_unix_str().to_string(),
+                        server_id,
+                        summary.error_count,
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+    };
+
+    publish(cx);
+    assert_eq!(summaries(cx), [("a.txt".to_string(), source_id, 1)]);
+
+    // The diagnostics reported for a file are cleared when it moves.
+    let entry_id = project.read_with(cx, |project, cx| {
+        project
+            .entry_for_path(&(worktree_id, rel_path("a.txt")).into(), cx)
+            .unwrap()
+            .id
+    });
+    project
+        .update(cx, |project, cx| {
+            project.rename_entry(entry_id, (worktree_id, rel_path("b.txt")).into(), cx)
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+    assert!(summaries(cx).is_empty());
+
+    publish(cx);
+    assert_eq!(summaries(cx), [("b.txt".to_string(), source_id, 1)]);
+
+    // They're cleared when the buffer is released.
+    drop(buffer);
+    cx.run_until_parked();
+    assert!(summaries(cx).is_empty());
+}
+
 #[gpui::test]
 async fn test_disk_based_diagnostics_progress(cx: &mut gpui::TestAppContext) {
     init_test(cx);
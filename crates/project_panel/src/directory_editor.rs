@@ -0,0 +1,1061 @@
+//! Edits the contents of a directory as text.
+//!
+//! Each entry in the directory is listed on its own line, prefixed with its entry id, as in
+//! `/42 main.rs`. Editing a name renames the entry, moving its line into another directory's
+//! buffer moves it there, and deleting its line deletes it. Lines without an id create new
+//! entries, which are directories when they end in a `/`.
+//!
+//! Saving applies the changes made in every open directory buffer at once, so that a line cut
+//! from one buffer and pasted into another is a move rather than a deletion and a creation.
+
+use std::{any::TypeId, fmt::Write as _};
+
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use editor::{Editor, EditorEvent};
+use gpui::{
+    AnyElement, App, AppContext as _, AsyncWindowContext, Context, Entity, EventEmitter,
+    FocusHandle, Focusable, IntoElement, PromptLevel, Render, SharedString, Subscription, Task,
+    WeakEntity, Window,
+};
+use language::Buffer;
+use project::{Project, ProjectEntryId, ProjectPath};
+use ui::{Color, Icon, IconName, Label, LabelCommon as _};
+use util::{paths::compare_rel_paths, rel_path::RelPath};
+use workspace::{
+    Item, Workspace,
+    item::{ItemEvent, SaveOptions, TabContentParams},
+    searchable::SearchableItemHandle,
+};
+use worktree::CreatedEntry;
+
+use crate::{
+    ProjectPanel,
+    undo::{DeletedEntry, ProjectPanelOperation},
+};
+
+/// The largest total size of the files in a deleted entry for which the deletion can be undone,
+/// since undoing it recreates the files from contents kept in memory.
+const MAX_UNDOABLE_DELETE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// An entry as it was listed when its directory buffer was last loaded.
+#[derive(Clone, Debug, PartialEq)]
+struct ListedEntry {
+    id: ProjectEntryId,
+    path: ProjectPath,
+    is_dir: bool,
+}
+
+/// A directory buffer's contents, along with the entries it was loaded with.
+struct DirectoryListing {
+    directory: ProjectPath,
+    entries: Vec<ListedEntry>,
+    text: String,
+}
+
+/// A filesystem operation made by editing directory buffers.
+#[derive(Clone, Debug, PartialEq)]
+enum FileChange {
+    Create {
+        path: ProjectPath,
+        is_dir: bool,
+    },
+    Rename {
+        entry_id: ProjectEntryId,
+        old_path: ProjectPath,
+        new_path: ProjectPath,
+    },
+    Delete {
+        entry_id: ProjectEntryId,
+        path: ProjectPath,
+    },
+}
+
+impl FileChange {
+    fn description(&self) -> String {
+        match self {
+            FileChange::Create { path, is_dir } => {
+                let suffix = if *is_dir { "/" } else { "" };
+                format!("Create {}{suffix}", path.path.as_unix_str())
+            }
+            FileChange::Rename {
+                old_path, new_path, ..
+            } => {
+                let verb = if old_path.worktree_id == new_path.worktree_id
+                    && old_path.path.parent() == new_path.path.parent()
+                {
+                    "Rename"
+                } else {
+                    "Move"
+                };
+                format!(
+                    "{verb} {} → {}",
+                    old_path.path.as_unix_str(),
+                    new_path.path.as_unix_str()
+                )
+            }
+            FileChange::Delete { path, .. } => format!("Delete {}", path.path.as_unix_str()),
+        }
+    }
+}
+
+pub struct DirectoryEditor {
+    project: Entity<Project>,
+    workspace: WeakEntity<Workspace>,
+    directory_id: ProjectEntryId,
+    directory: Option<ProjectPath>,
+    entries: Vec<ListedEntry>,
+    buffer: Entity<Buffer>,
+    editor: Entity<Editor>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DirectoryEditor {
+    /// Opens a buffer listing the directory with the given entry id, or activates the one
+    /// that's already open.
+    pub(crate) fn open(
+        workspace: &mut Workspace,
+        directory_id: ProjectEntryId,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        if let Some(existing) = workspace
+            .items_of_type::<DirectoryEditor>(cx)
+            .find(|editor| editor.read(cx).directory_id == directory_id)
+        {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+        let project = workspace.project().clone();
+        let workspace_handle = workspace.weak_handle();
+        let directory_editor =
+            cx.new(|cx| Self::new(project, workspace_handle, directory_id, window, cx));
+        workspace.add_item_to_active_pane(Box::new(directory_editor), None, true, window, cx);
+    }
+
+    fn new(
+        project: Entity<Project>,
+        workspace: WeakEntity<Workspace>,
+        directory_id: ProjectEntryId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let buffer = cx.new(|cx| Buffer::local("", cx));
+        let editor = cx.new(|cx| Editor::for_buffer(buffer.clone(), None, window, cx));
+        let subscriptions = vec![
+            cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+                cx.emit(event.clone())
+            }),
+            cx.subscribe(&project, |this, _, event, cx| {
+                if let project::Event::WorktreeUpdatedEntries(worktree_id, changes) = event
+                    && let Some(directory) = &this.directory
+                    && directory.worktree_id == *worktree_id
+                    && !this.buffer.read(cx).is_dirty()
+                    && changes
+                        .iter()
+                        .any(|(path, _, _)| path.parent() == Some(directory.path.as_ref()))
+                {
+                    this.reload_entries(cx);
+                }
+            }),
+        ];
+        let mut this = Self {
+            project,
+            workspace,
+            directory_id,
+            directory: None,
+            entries: Vec::new(),
+            buffer,
+            editor,
+            _subscriptions: subscriptions,
+        };
+        this.reload_entries(cx);
+        this
+    }
+
+    /// Lists the directory's current contents, discarding any edits.
+    fn reload_entries(&mut self, cx: &mut Context<Self>) {
+        self.directory = self.project.read(cx).path_for_entry(self.directory_id, cx);
+        self.entries = self
+            .directory
+            .as_ref()
+            .map(|directory| list_directory(&self.project, directory, cx))
+            .unwrap_or_default();
+
+        let mut text = String::new();
+        for entry in &self.entries {
+            let name = entry.path.path.file_name().unwrap_or_default();
+            let suffix = if entry.is_dir { "/" } else { "" };
+            writeln!(text, "/{} {name}{suffix}", entry.id.to_proto()).ok();
+        }
+        self.buffer.update(cx, |buffer, cx| {
+            if buffer.text() != text {
+                buffer.set_text(text, cx);
+            }
+            buffer.did_save(buffer.version(), None, cx);
+        });
+        cx.emit(EditorEvent::TitleChanged);
+        cx.notify();
+    }
+
+    fn listing(&self, cx: &App) -> Option<DirectoryListing> {
+        Some(DirectoryListing {
+            directory: self.directory.clone()?,
+            entries: self.entries.clone(),
+            text: self.buffer.read(cx).text(),
+        })
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    /// Applies the edits made to this and every other dirty directory buffer, after asking for
+    /// confirmation.
+    fn apply_edits(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return Task::ready(Ok(()));
+        };
+        let this_id = cx.entity_id();
+        let mut seen = HashSet::from_iter([this_id]);
+        let mut editors = vec![cx.entity()];
+        let mut listings = self.listing(cx).into_iter().collect::<Vec<_>>();
+        for editor in workspace.read(cx).items_of_type::<DirectoryEditor>(cx) {
+            if seen.insert(editor.entity_id()) && editor.read(cx).is_dirty(cx) {
+                listings.extend(editor.read(cx).listing(cx));
+                editors.push(editor);
+            }
+        }
+
+        let changes = match plan_changes(&listings) {
+            Ok(changes) => changes,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        if changes.is_empty() {
+            for editor in &editors {
+                if editor.entity_id() == this_id {
+                    self.reload_entries(cx);
+                } else {
+                    editor.update(cx, |editor, cx| editor.reload_entries(cx));
+                }
+            }
+            return Task::ready(Ok(()));
+        }
+
+        let trash = !self.project.read(cx).is_remote();
+        let deletes = changes
+            .iter()
+            .any(|change| matches!(change, FileChange::Delete { .. }));
+        let mut detail = changes
+            .iter()
+            .map(FileChange::description)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if deletes && !trash {
+            detail.push_str("\n\nDeleted files cannot be restored.");
+        }
+        let message = if changes.len() == 1 {
+            "Apply 1 change?".to_string()
+        } else {
+            format!("Apply {} changes?", changes.len())
+        };
+        let answer = window.prompt(
+            PromptLevel::Info,
+            &message,
+            Some(&detail),
+            &["Apply", "Cancel"],
+            cx,
+        );
+
+        let project = self.project.clone();
+        let editors = editors
+            .into_iter()
+            .map(|editor| editor.downgrade())
+            .collect::<Vec<_>>();
+        cx.spawn_in(window, async move |_, cx| {
+            if answer.await != Ok(0) {
+                return Ok(());
+            }
+            let mut operations = Vec::new();
+            let result = apply_changes(&project, &changes, trash, &mut operations, cx).await;
+            workspace.update(cx, |workspace, cx| {
+                if let Some(panel) = workspace.panel::<ProjectPanel>(cx) {
+                    panel.update(cx, |panel, _| panel.undo_manager.record_batch(operations));
+                }
+            });
+            for editor in editors {
+                editor
+                    .update(cx, |editor, cx| editor.reload_entries(cx))
+                    .ok();
+            }
+            result
+        })
+    }
+}
+
+/// Returns the entries in `directory`, sorted like the project panel sorts them.
+fn list_directory(
+    project: &Entity<Project>,
+    directory: &ProjectPath,
+    cx: &App,
+) -> Vec<ListedEntry> {
+    let Some(worktree) = project.read(cx).worktree_for_id(directory.worktree_id, cx) else {
+        return Vec::new();
+    };
+    let snapshot = worktree.read(cx).snapshot();
+    let mut entries = snapshot
+        .child_entries(&directory.path)
+        .map(|entry| ListedEntry {
+            id: entry.id,
+            path: ProjectPath {
+                worktree_id: directory.worktree_id,
+                path: entry.path.clone(),
+            },
+            is_dir: entry.is_dir(),
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| compare_rel_paths((&a.path.path, !a.is_dir), (&b.path.path, !b.is_dir)));
+    entries
+}
+
+/// A line of a directory buffer.
+struct ListingLine {
+    entry_id: Option<ProjectEntryId>,
+    path: ProjectPath,
+    is_dir: bool,
+}
+
+fn parse_listing(listing: &DirectoryListing) -> Result<Vec<ListingLine>> {
+    let mut lines = Vec::new();
+    for line in listing.text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (entry_id, name) = match line.strip_prefix('/') {
+            Some(rest) => {
+                let (id, name) = rest.split_once(' ').unwrap_or((rest, ""));
+                let id = id
+                    .parse::<u64>()
+                    .with_context(|| format!("Invalid entry id in line {line:?}"))?;
+                (Some(ProjectEntryId::from_proto(id)), name.trim())
+            }
+            None => (None, line),
+        };
+        let is_dir = name.ends_with('/');
+        let name = name.trim_end_matches('/');
+        let relative_path =
+            RelPath::unix(name).with_context(|| format!("Invalid file name {name:?}"))?;
+        anyhow::ensure!(
+            !relative_path.is_empty(),
+            "Missing file name in line {line:?}"
+        );
+        lines.push(ListingLine {
+            entry_id,
+            path: ProjectPath {
+                worktree_id: listing.directory.worktree_id,
+                path: listing.directory.path.join(relative_path),
+            },
+            is_dir,
+        });
+    }
+    Ok(lines)
+}
+
+/// Compares the contents of directory buffers with the entries they were loaded with, and
+/// returns the changes to make, in the order to make them in.
+///
+/// Deletions of entries that are replaced by another entry come first, then the creation of
+/// new directories, so that entries can be moved into them. Renames are made deepest first, so
+/// that an entry is moved before its parent directory is. Other deletions come after renames,
+/// so that entries can be moved out of a deleted directory, and new files come last.
+fn plan_changes(listings: &[DirectoryListing]) -> Result<Vec<FileChange>> {
+    let mut original_entries = HashMap::default();
+    for listing in listings {
+        for entry in &listing.entries {
+            original_entries.insert(entry.id, entry);
+        }
+    }
+
+    let mut new_paths = HashMap::default();
+    let mut targets = HashSet::default();
+    let mut creates = Vec::new();
+    for listing in listings {
+        for line in parse_listing(listing)? {
+            anyhow::ensure!(
+                targets.insert(line.path.clone()),
+                "{} is listed more than once",
+                line.path.path.as_unix_str()
+            );
+            match line.entry_id {
+                Some(entry_id) => {
+                    anyhow::ensure!(
+                        original_entries.contains_key(&entry_id),
+                        "Unknown entry /{}",
+                        entry_id.to_proto()
+                    );
+                    anyhow::ensure!(
+                        new_paths.insert(entry_id, line.path).is_none(),
+                        "Entry /{} is listed more than once",
+                        entry_id.to_proto()
+                    );
+                }
+                None => creates.push(FileChange::Create {
+                    path: line.path,
+                    is_dir: line.is_dir,
+                }),
+            }
+        }
+    }
+
+    let mut renames = Vec::new();
+    let mut deleted_paths = Vec::new();
+    for entry in original_entries.values() {
+        match new_paths.get(&entry.id) {
+            Some(new_path) if *new_path != entry.path => renames.push((
+                entry.path.path.components().count(),
+                FileChange::Rename {
+                    entry_id: entry.id,
+                    old_path: entry.path.clone(),
+                    new_path: new_path.clone(),
+                },
+            )),
+            Some(_) => {}
+            None => deleted_paths.push(*entry),
+        }
+    }
+    renames.sort_by(|(a_depth, a), (b_depth, b)| {
+        b_depth
+            .cmp(a_depth)
+            .then_with(|| a.description().cmp(&b.description()))
+    });
+
+    // Deleting a directory deletes its contents, so they needn't be deleted separately.
+    deleted_paths.sort_by_key(|entry| entry.path.path.components().count());
+    let mut deletes: Vec<&ListedEntry> = Vec::new();
+    for entry in deleted_paths {
+        let within_deleted_directory = deletes.iter().any(|deleted| {
+            deleted.is_dir
+                && deleted.path.worktree_id == entry.path.worktree_id
+                && entry.path.path.starts_with(&deleted.path.path)
+        });
+        if !within_deleted_directory {
+            deletes.push(entry);
+        }
+    }
+    deletes.sort_by(|a, b| a.path.path.cmp(&b.path.path));
+    let (replaced, deletes): (Vec<_>, Vec<_>) = deletes
+        .into_iter()
+        .map(|entry| FileChange::Delete {
+            entry_id: entry.id,
+            path: entry.path.clone(),
+        })
+        .partition(
+            |change| matches!(change, FileChange::Delete { path, .. } if targets.contains(path)),
+        );
+
+    creates.sort_by_key(|change| match change {
+        FileChange::Create { path, .. } => path.path.components().count(),
+        _ => 0,
+    });
+    let (directory_creates, file_creates): (Vec<_>, Vec<_>) = creates
+        .into_iter()
+        .partition(|change| matches!(change, FileChange::Create { is_dir: true, .. }));
+
+    let mut changes = replaced;
+    changes.extend(directory_creates);
+    changes.extend(renames.into_iter().map(|(_, change)| change));
+    changes.extend(deletes);
+    changes.extend(file_creates);
+    Ok(changes)
+}
+
+/// Makes the given changes one at a time, stopping at the first that fails. The changes that
+/// can be undone are appended to `operations`.
+async fn apply_changes(
+    project: &Entity<Project>,
+    changes: &[FileChange],
+    trash: bool,
+    operations: &mut Vec<ProjectPanelOperation>,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    // Entries renamed to where another renamed entry currently is, as when swapping two names,
+    // are first moved out of the way.
+    let sources = changes
+        .iter()
+        .filter_map(|change| match change {
+            FileChange::Rename { old_path, .. } => Some(old_path.clone()),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+    let mut moved_aside = HashMap::default();
+    for change in changes {
+        if let FileChange::Rename {
+            entry_id,
+            old_path,
+            new_path,
+        } = change
+            && sources.contains(new_path)
+        {
+            let file_name = old_path.path.file_name().unwrap_or_default();
+            let temporary_name = format!(".{file_name}.{}.tmp", entry_id.to_proto());
+            let temporary_path = ProjectPath {
+                worktree_id: old_path.worktree_id,
+                path: old_path
+                    .path
+                    .parent()
+                    .unwrap_or(RelPath::empty())
+                    .join(RelPath::unix(&temporary_name)?),
+            };
+            let entry = project
+                .update(cx, |project, cx| {
+                    project.rename_entry(*entry_id, temporary_path.clone(), cx)
+                })
+                .await?;
+            operations.push(ProjectPanelOperation::Rename {
+                old_path: old_path.clone(),
+                new_path: temporary_path.clone(),
+            });
+            if let CreatedEntry::Included(entry) = entry {
+                moved_aside.insert(*entry_id, (entry.id, temporary_path));
+            }
+        }
+    }
+
+    for change in changes {
+        match change {
+            FileChange::Create { path, is_dir } => {
+                project
+                    .update(cx, |project, cx| {
+                        project.create_entry(path.clone(), *is_dir, cx)
+                    })
+                    .await?;
+                operations.push(ProjectPanelOperation::Create {
+                    project_path: path.clone(),
+                });
+            }
+            FileChange::Rename {
+                entry_id,
+                old_path,
+                new_path,
+            } => {
+                let (entry_id, old_path) = match moved_aside.get(entry_id) {
+                    Some((entry_id, temporary_path)) => (*entry_id, temporary_path),
+                    None => (*entry_id, old_path),
+                };
+                project
+                    .update(cx, |project, cx| {
+                        project.rename_entry(entry_id, new_path.clone(), cx)
+                    })
+                    .await?;
+                operations.push(ProjectPanelOperation::Rename {
+                    old_path: old_path.clone(),
+                    new_path: new_path.clone(),
+                });
+            }
+            FileChange::Delete { entry_id, path } => {
+                let deleted_entries = if trash {
+                    read_deleted_entries(project, path, cx).await
+                } else {
+                    None
+                };
+                let delete =
+                    project.update(cx, |project, cx| project.delete_entry(*entry_id, trash, cx));
+                if let Some(delete) = delete {
+                    delete
+                        .await
+                        .with_context(|| format!("Failed to delete {}", path.path.as_unix_str()))?;
+                }
+                if let Some(entries) = deleted_entries {
+                    operations.push(ProjectPanelOperation::Delete {
+                        worktree_id: path.worktree_id,
+                        entries,
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads the entry at `path` and everything in it, so that its deletion can be undone. Returns
+/// `None` when the deletion can't be undone, because the files are too large to keep in memory,
+/// can't be read, or include directories that haven't been loaded.
+async fn read_deleted_entries(
+    project: &Entity<Project>,
+    path: &ProjectPath,
+    cx: &mut AsyncWindowContext,
+) -> Option<Vec<DeletedEntry>> {
+    let worktree = project.read_with(cx, |project, cx| {
+        project.worktree_for_id(path.worktree_id, cx)
+    })?;
+    let entries = worktree.read_with(cx, |worktree, _| {
+        let snapshot = worktree.snapshot();
+        let mut entries = Vec::new();
+        let mut stack = vec![snapshot.entry_for_path(&path.path)?.clone()];
+        let mut size = 0;
+        while let Some(entry) = stack.pop() {
+            if entry.kind.is_unloaded() {
+                return None;
+            }
+            size += entry.size;
+            if size > MAX_UNDOABLE_DELETE_SIZE {
+                return None;
+            }
+            if entry.is_dir() {
+                stack.extend(snapshot.child_entries(&entry.path).cloned());
+            }
+            entries.push(entry);
+        }
+        Some(entries)
+    })?;
+
+    let mut deleted_entries = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let contents = if entry.is_dir() {
+            None
+        } else {
+            let load = worktree.update(cx, |worktree, cx| {
+                worktree.load_binary_file(&entry.path, cx)
+            });
+            Some(load.await.ok()?.content)
+        };
+        deleted_entries.push(DeletedEntry {
+            path: entry.path,
+            contents,
+        });
+    }
+    Some(deleted_entries)
+}
+
+impl EventEmitter<EditorEvent> for DirectoryEditor {}
+
+impl Focusable for DirectoryEditor {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for DirectoryEditor {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Folder).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .color(params.text_color())
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        let Some(directory) = &self.directory else {
+            return "Deleted directory".into();
+        };
+        let name = directory.path.file_name().map_or_else(
+            || {
+                self.project
+                    .read(cx)
+                    .worktree_for_id(directory.worktree_id, cx)
+                    .map(|worktree| worktree.read(cx).root_name_str().to_string())
+                    .unwrap_or_default()
+            },
+            str::to_string,
+        );
+        format!("{name}/").into()
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        let directory = self.directory.as_ref()?;
+        Some(format!("{}/", directory.path.as_unix_str()).into())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: &mut dyn FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Directory Editor Opened")
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<gpui::AnyEntity> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.clone().into())
+        } else {
+            None
+        }
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>, _: &App) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.is_dirty(cx)
+    }
+
+    fn can_save(&self, _: &App) -> bool {
+        self.directory.is_some()
+    }
+
+    fn save(
+        &mut self,
+        _: SaveOptions,
+        _: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.apply_edits(window, cx)
+    }
+
+    fn reload(
+        &mut self,
+        _: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.reload_entries(cx);
+        Task::ready(Ok(()))
+    }
+}
+
+impl Render for DirectoryEditor {
+    fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        self.editor.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_panel_tests::init_test_with_editor;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Fs as _, WorktreeId};
+    use serde_json::json;
+    use util::{path, rel_path::rel_path};
+    use workspace::MultiWorkspace;
+
+    fn project_path(path: &str) -> ProjectPath {
+        ProjectPath {
+            worktree_id: WorktreeId::from_usize(1),
+            path: rel_path(path).into(),
+        }
+    }
+
+    fn entry(id: usize, path: &str, is_dir: bool) -> ListedEntry {
+        ListedEntry {
+            id: ProjectEntryId::from_usize(id),
+            path: project_path(path),
+            is_dir,
+        }
+    }
+
+    fn descriptions(listings: &[DirectoryListing]) -> Vec<String> {
+        plan_changes(listings)
+            .unwrap()
+            .iter()
+            .map(FileChange::description)
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_changes() {
+        let src = || DirectoryListing {
+            directory: project_path("src"),
+            entries: vec![
+                entry(1, "src/old", true),
+                entry(2, "src/a.rs", false),
+                entry(3, "src/b.rs", false),
+                entry(4, "src/c.rs", false),
+            ],
+            text: String::new(),
+        };
+        let docs = || DirectoryListing {
+            directory: project_path("docs"),
+            entries: vec![entry(5, "docs/readme.md", false)],
+            text: "/5 readme.md\n".into(),
+        };
+
+        let listing = DirectoryListing {
+            text: "/1 old/\n/2 a.rs\n/3 b.rs\n/4 c.rs\n".into(),
+            ..src()
+        };
+        assert_eq!(descriptions(&[listing]), Vec::<String>::new());
+
+        let listing = DirectoryListing {
+            text: "/1 new/\n/3 a.rs\n/2 b.rs\n\nd.rs\nnested/\n".into(),
+            ..src()
+        };
+        assert_eq!(
+            descriptions(&[listing, docs()]),
+            [
+                "Create src/nested/",
+                "Rename src/a.rs → src/b.rs",
+                "Rename src/b.rs → src/a.rs",
+                "Rename src/old → src/new",
+                "Delete src/c.rs",
+                "Create src/d.rs",
+            ]
+        );
+
+        let listing = DirectoryListing {
+            text: "/1 old/\n/2 a.rs\nc.rs\n".into(),
+            ..src()
+        };
+        let docs_listing = DirectoryListing {
+            text: "/5 readme.md\n/3 b.rs\n".into(),
+            ..docs()
+        };
+        assert_eq!(
+            descriptions(&[listing, docs_listing]),
+            [
+                "Delete src/c.rs",
+                "Move src/b.rs → docs/b.rs",
+                "Create src/c.rs",
+            ]
+        );
+
+        let listing = DirectoryListing {
+            text: "/1 old/\n/2 a.rs\n/2 b.rs\n".into(),
+            ..src()
+        };
+        assert!(plan_changes(&[listing]).is_err());
+        let listing = DirectoryListing {
+            text: "/9 a.rs\n".into(),
+            ..src()
+        };
+        assert!(plan_changes(&[listing]).is_err());
+        let listing = DirectoryListing {
+            text: "../escape.rs\n".into(),
+            ..src()
+        };
+        assert!(plan_changes(&[listing]).is_err());
+    }
+
+    #[gpui::test]
+    async fn test_editing_directory(cx: &mut TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "src": {
+                    "a.rs": "a",
+                    "b.rs": "b",
+                    "old": { "c.rs": "c" },
+                },
+                "docs": {},
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let window =
+            cx.add_window(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let workspace = window
+            .read_with(cx, |mw, _| mw.workspace().clone())
+            .unwrap();
+        let cx = &mut VisualTestContext::from_window(window.into(), cx);
+        let panel = workspace.update_in(cx, ProjectPanel::new);
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.add_panel(panel.clone(), window, cx);
+        });
+        cx.run_until_parked();
+
+        let entry_id = |path: &str, cx: &mut VisualTestContext| {
+            project.read_with(cx, |project, cx| {
+                let worktree = project.visible_worktrees(cx).next().unwrap();
+                worktree.read(cx).entry_for_path(rel_path(path)).unwrap().id
+            })
+        };
+        let src_id = entry_id("src", cx);
+        let docs_id = entry_id("docs", cx);
+        let [a_id, b_id, old_id] =
+            ["src/a.rs", "src/b.rs", "src/old"].map(|path| entry_id(path, cx));
+
+        for directory_id in [src_id, docs_id] {
+            workspace.update_in(cx, |workspace, window, cx| {
+                DirectoryEditor::open(workspace, directory_id, window, cx);
+            });
+        }
+        let [src_editor, docs_editor] = workspace.read_with(cx, |workspace, cx| {
+            let editors = workspace
+                .items_of_type::<DirectoryEditor>(cx)
+                .collect::<Vec<_>>();
+            [editors[0].clone(), editors[1].clone()]
+        });
+        src_editor.read_with(cx, |editor, cx| {
+            assert_eq!(
+                editor.buffer.read(cx).text(),
+                format!(
+                    "/{} old/\n/{} a.rs\n/{} b.rs\n",
+                    old_id.to_proto(),
+                    a_id.to_proto(),
+                    b_id.to_proto()
+                )
+            );
+            assert_eq!(editor.tab_content_text(0, cx), "src/");
+        });
+
+        src_editor.update(cx, |editor, cx| {
+            let text = format!(
+                "/{} new/\n/{} a2.rs\nnew.rs\n",
+                old_id.to_proto(),
+                a_id.to_proto()
+            );
+            editor
+                .buffer
+                .update(cx, |buffer, cx| buffer.set_text(text, cx));
+        });
+        docs_editor.update(cx, |editor, cx| {
+            let text = format!("/{} b.rs\n", b_id.to_proto());
+            editor
+                .buffer
+                .update(cx, |buffer, cx| buffer.set_text(text, cx));
+        });
+
+        let save = src_editor.update_in(cx, |editor, window, cx| editor.apply_edits(window, cx));
+        cx.run_until_parked();
+        cx.simulate_prompt_answer("Apply");
+        save.await.unwrap();
+        cx.run_until_parked();
+
+        let mut paths = fs.paths(false);
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                path!("/"),
+                path!("/root"),
+                path!("/root/docs"),
+                path!("/root/docs/b.rs"),
+                path!("/root/src"),
+                path!("/root/src/a2.rs"),
+                path!("/root/src/new"),
+                path!("/root/src/new/c.rs"),
+                path!("/root/src/new.rs"),
+            ]
+            .map(std::path::PathBuf::from)
+        );
+        for editor in [&src_editor, &docs_editor] {
+            editor.read_with(cx, |editor, cx| assert!(!editor.is_dirty(cx)));
+        }
+        docs_editor.read_with(cx, |editor, cx| {
+            assert_eq!(
+                editor.buffer.read(cx).text(),
+                format!("/{} b.rs\n", b_id.to_proto())
+            );
+        });
+
+        panel.update(cx, |panel, cx| panel.undo_manager.undo(cx));
+        cx.run_until_parked();
+        assert!(fs.is_file(path!("/root/src/b.rs").as_ref()).await);
+        assert!(fs.is_file(path!("/root/src/a.rs").as_ref()).await);
+        assert!(fs.is_dir(path!("/root/src/old").as_ref()).await);
+        assert!(!fs.is_file(path!("/root/src/new.rs").as_ref()).await);
+    }
+
+    #[gpui::test]
+    async fn test_undoing_swaps_and_deletions(cx: &mut TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "a.rs": "a",
+                "b.rs": "b",
+                "c.rs": "c",
+                "old": { "d.rs": "d" },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let window =
+            cx.add_window(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let workspace = window
+            .read_with(cx, |mw, _| mw.workspace().clone())
+            .unwrap();
+        let cx = &mut VisualTestContext::from_window(window.into(), cx);
+        let panel = workspace.update_in(cx, ProjectPanel::new);
+        workspace.update_in(cx, |workspace, window, cx| {
+            workspace.add_panel(panel.clone(), window, cx);
+        });
+        cx.run_until_parked();
+
+        let entry_id = |path: &str, cx: &mut VisualTestContext| {
+            project.read_with(cx, |project, cx| {
+                let worktree = project.visible_worktrees(cx).next().unwrap();
+                worktree.read(cx).entry_for_path(rel_path(path)).unwrap().id
+            })
+        };
+        let root_id = entry_id("", cx);
+        let [a_id, b_id] = ["a.rs", "b.rs"].map(|path| entry_id(path, cx));
+        workspace.update_in(cx, |workspace, window, cx| {
+            DirectoryEditor::open(workspace, root_id, window, cx);
+        });
+        let editor = workspace.read_with(cx, |workspace, cx| {
+            workspace
+                .items_of_type::<DirectoryEditor>(cx)
+                .next()
+                .unwrap()
+        });
+
+        // Swap a.rs and b.rs, replace c.rs with a new file, and delete old/.
+        editor.update(cx, |editor, cx| {
+            let text = format!(
+                "/{} b.rs\n/{} a.rs\nc.rs\n",
+                a_id.to_proto(),
+                b_id.to_proto()
+            );
+            editor
+                .buffer
+                .update(cx, |buffer, cx| buffer.set_text(text, cx));
+        });
+        let save = editor.update_in(cx, |editor, window, cx| editor.apply_edits(window, cx));
+        cx.run_until_parked();
+        cx.simulate_prompt_answer("Apply");
+        save.await.unwrap();
+        cx.run_until_parked();
+        let load = async |path: &str| fs.load(path.as_ref()).await.unwrap();
+        assert_eq!(load(path!("/root/a.rs")).await, "b");
+        assert_eq!(load(path!("/root/b.rs")).await, "a");
+        assert_eq!(load(path!("/root/c.rs")).await, "");
+        assert!(!fs.is_dir(path!("/root/old").as_ref()).await);
+
+        panel.update(cx, |panel, cx| panel.undo_manager.undo(cx));
+        cx.run_until_parked();
+        let mut paths = fs.paths(false);
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                path!("/"),
+                path!("/root"),
+                path!("/root/a.rs"),
+                path!("/root/b.rs"),
+                path!("/root/c.rs"),
+                path!("/root/old"),
+                path!("/root/old/d.rs"),
+            ]
+            .map(std::path::PathBuf::from)
+        );
+        assert_eq!(load(path!("/root/a.rs")).await, "a");
+        assert_eq!(load(path!("/root/b.rs")).await, "b");
+        assert_eq!(load(path!("/root/c.rs")).await, "c");
+        assert_eq!(load(path!("/root/old/d.rs")).await, "d");
+    }
+}
@@ -0,0 +1,339 @@
+//! Nests related files, such as `foo.js` and `foo.js.map` under `foo.ts`, under a parent file
+//! that can be expanded to show them.
+
+use collections::{HashMap, IndexMap};
+use git::status::GitSummary;
+use project::{Entry, GitEntry, ProjectEntryId, Worktree};
+use util::{paths::compare_rel_paths, rel_path::RelPath};
+
+use crate::NEW_ENTRY_ID;
+
+/// Stands for the text matched by the `*` in a parent pattern, within a nested file pattern.
+const CAPTURE: &str = "${capture}";
+
+/// The rules for nesting files, compiled from the `project_panel.file_nesting.patterns` setting.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FileNestingRules {
+    rules: Vec<NestingRule>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct NestingRule {
+    parent: String,
+    children: Vec<String>,
+}
+
+impl FileNestingRules {
+    pub fn new(patterns: &IndexMap<String, String>) -> Self {
+        let rules = patterns
+            .iter()
+            .filter_map(|(parent, children)| {
+                let parent = parent.trim();
+                // Only one `*` is allowed, as that's what `${capture}` refers to.
+                if parent.is_empty() || parent.matches('*').count() > 1 {
+                    return None;
+                }
+                let children = children
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|child| !child.is_empty())
+                    .map(str::to_string)
+                    .collect::<Vec<_>>();
+                (!children.is_empty()).then(|| NestingRule {
+                    parent: parent.to_string(),
+                    children,
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the index of the file that each of the given sibling files is nested under.
+    ///
+    /// Each file is matched against the first parent pattern it matches, in order. Files are
+    /// only nested one level deep: the files nested under a file that is itself nested are
+    /// nested under its parent instead.
+    pub fn nest(&self, file_names: &[&str]) -> Vec<Option<usize>> {
+        let mut parents = vec![None; file_names.len()];
+        if self.rules.is_empty() {
+            return parents;
+        }
+        for (parent_ix, parent_name) in file_names.iter().enumerate() {
+            if parents[parent_ix].is_some() {
+                continue;
+            }
+            let Some((rule, capture)) = self
+                .rules
+                .iter()
+                .find_map(|rule| Some((rule, capture(&rule.parent, parent_name)?)))
+            else {
+                continue;
+            };
+            let child_patterns = rule
+                .children
+                .iter()
+                .map(|pattern| pattern.replace(CAPTURE, capture))
+                .collect::<Vec<_>>();
+            for (child_ix, child_name) in file_names.iter().enumerate() {
+                if child_ix == parent_ix
+                    || parents[child_ix].is_some()
+                    || !child_patterns
+                        .iter()
+                        .any(|pattern| matches_wildcard(pattern, child_name))
+                {
+                    continue;
+                }
+                parents[child_ix] = Some(parent_ix);
+                for parent in &mut parents {
+                    if *parent == Some(child_ix) {
+                        *parent = Some(parent_ix);
+                    }
+                }
+            }
+        }
+        parents
+    }
+}
+
+/// Returns the text matched by the `*` in `pattern`, or an empty string if `pattern` has no `*`
+/// and is the same as `name`.
+fn capture<'a>(pattern: &str, name: &'a str) -> Option<&'a str> {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            if name.len() < prefix.len() + suffix.len() {
+                return None;
+            }
+            name.strip_prefix(prefix)?.strip_suffix(suffix)
+        }
+        None => (pattern == name).then_some(""),
+    }
+}
+
+/// Returns whether `name` matches `pattern`, where each `*` in `pattern` matches any text.
+fn matches_wildcard(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = name.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        let Some(ix) = rest.find(part) else {
+            return false;
+        };
+        rest = &rest[ix + part.len()..];
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// The files nested under other files among a worktree's visible entries.
+#[derive(Debug, Default)]
+pub(crate) struct NestedFiles {
+    /// Maps each nested file to the file it's nested under.
+    parents: HashMap<ProjectEntryId, ProjectEntryId>,
+    /// Maps each file with nested files to them, along with their combined git status.
+    children: HashMap<ProjectEntryId, (Vec<ProjectEntryId>, GitSummary)>,
+}
+
+impl NestedFiles {
+    pub fn parent(&self, entry_id: ProjectEntryId) -> Option<ProjectEntryId> {
+        self.parents.get(&entry_id).copied()
+    }
+
+    pub fn has_children(&self, entry_id: ProjectEntryId) -> bool {
+        self.children.contains_key(&entry_id)
+    }
+
+    pub fn children(&self, entry_id: ProjectEntryId) -> &[ProjectEntryId] {
+        self.children
+            .get(&entry_id)
+            .map_or(&[], |(children, _)| children.as_slice())
+    }
+
+    /// Returns the combined git status of the files nested under the given file.
+    pub fn children_git_summary(&self, entry_id: ProjectEntryId) -> GitSummary {
+        self.children
+            .get(&entry_id)
+            .map_or(GitSummary::UNCHANGED, |(_, summary)| *summary)
+    }
+}
+
+/// Returns the file that `entry` is nested under among all of its siblings, whether or not
+/// they're visible.
+pub(crate) fn nesting_parent(
+    rules: &FileNestingRules,
+    worktree: &Worktree,
+    entry: &Entry,
+) -> Option<ProjectEntryId> {
+    if rules.is_empty() || !entry.is_file() {
+        return None;
+    }
+    let mut siblings = worktree
+        .child_entries(entry.path.parent()?)
+        .filter(|sibling| sibling.is_file())
+        .collect::<Vec<_>>();
+    siblings.sort_by(|a, b| compare_rel_paths((&a.path, true), (&b.path, true)));
+    let file_names = siblings
+        .iter()
+        .map(|sibling| sibling.path.file_name().unwrap_or_default())
+        .collect::<Vec<_>>();
+    let ix = siblings.iter().position(|sibling| sibling.id == entry.id)?;
+    let parent_ix = rules.nest(&file_names)[ix]?;
+    Some(siblings[parent_ix].id)
+}
+
+/// Moves nested files in the sorted `entries` to directly after the file they're nested under,
+/// removing them if that file isn't expanded.
+pub(crate) fn nest_entries(
+    rules: &FileNestingRules,
+    entries: &mut Vec<GitEntry>,
+    expanded_entry_ids: &[ProjectEntryId],
+) -> NestedFiles {
+    if rules.is_empty() {
+        return NestedFiles::default();
+    }
+
+    let mut parent_indices = vec![None; entries.len()];
+    let mut files_by_directory = HashMap::<&RelPath, Vec<usize>>::default();
+    for (ix, entry) in entries.iter().enumerate() {
+        if entry.is_file()
+            && entry.id != NEW_ENTRY_ID
+            && let Some(directory) = entry.path.parent()
+        {
+            files_by_directory.entry(directory).or_default().push(ix);
+        }
+    }
+    for indices in files_by_directory.values() {
+        let file_names = indices
+            .iter()
+            .map(|ix| entries[*ix].path.file_name().unwrap_or_default())
+            .collect::<Vec<_>>();
+        for (ix, parent) in indices.iter().zip(rules.nest(&file_names)) {
+            parent_indices[*ix] = parent.map(|parent| indices[parent]);
+        }
+    }
+
+    let mut nested_files = NestedFiles::default();
+    let mut child_indices = HashMap::<usize, Vec<usize>>::default();
+    for (ix, parent_ix) in parent_indices.iter().enumerate() {
+        if let Some(parent_ix) = parent_ix {
+            child_indices.entry(*parent_ix).or_default().push(ix);
+            nested_files
+                .parents
+                .insert(entries[ix].id, entries[*parent_ix].id);
+        }
+    }
+    if child_indices.is_empty() {
+        return nested_files;
+    }
+    for (parent_ix, children) in &child_indices {
+        let mut git_summary = GitSummary::UNCHANGED;
+        for child_ix in children {
+            git_summary += entries[*child_ix].git_summary;
+        }
+        let children = children.iter().map(|ix| entries[*ix].id).collect();
+        nested_files
+            .children
+            .insert(entries[*parent_ix].id, (children, git_summary));
+    }
+
+    let mut unnested_entries = std::mem::take(entries)
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+    for ix in 0..unnested_entries.len() {
+        if parent_indices[ix].is_some() {
+            continue;
+        }
+        let Some(entry) = unnested_entries[ix].take() else {
+            continue;
+        };
+        let is_expanded = expanded_entry_ids.binary_search(&entry.id).is_ok();
+        entries.push(entry);
+        if is_expanded && let Some(children) = child_indices.get(&ix) {
+            entries.extend(
+                children
+                    .iter()
+                    .filter_map(|ix| unnested_entries[*ix].take()),
+            );
+        }
+    }
+    nested_files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(patterns: &[(&str, &str)]) -> FileNestingRules {
+        FileNestingRules::new(
+            &patterns
+                .iter()
+                .map(|(parent, children)| (parent.to_string(), children.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_matching_patterns() {
+        assert_eq!(capture("*.ts", "foo.ts"), Some("foo"));
+        assert_eq!(capture("*.ts", ".ts"), Some(""));
+        assert_eq!(capture("*.ts", "foo.js"), None);
+        assert_eq!(capture("a*a", "a"), None);
+        assert_eq!(capture("Cargo.toml", "Cargo.toml"), Some(""));
+        assert_eq!(capture("Cargo.toml", "cargo.toml"), None);
+
+        assert!(matches_wildcard(".env.*", ".env.local"));
+        assert!(!matches_wildcard(".env.*", ".env"));
+        assert!(matches_wildcard("foo.*.map", "foo.js.map"));
+        assert!(matches_wildcard("*a*b", "xaybzb"));
+        assert!(!matches_wildcard("*a*b", "xaybz"));
+        assert!(matches_wildcard("Cargo.lock", "Cargo.lock"));
+        assert!(!matches_wildcard("Cargo.lock", "Cargo.locks"));
+    }
+
+    #[test]
+    fn test_nesting_files() {
+        let nesting = rules(&[
+            ("*.ts", "${capture}.js, ${capture}.js.map"),
+            ("*.js", "${capture}.min.js"),
+            ("Cargo.toml", "Cargo.lock"),
+            (".env", ".env.*"),
+        ]);
+        assert_eq!(
+            nesting.nest(&[
+                ".env",
+                ".env.local",
+                "Cargo.lock",
+                "Cargo.toml",
+                "bar.js",
+                "foo.js",
+                "foo.js.map",
+                "foo.min.js",
+                "foo.ts",
+                "other.js",
+            ]),
+            [
+                None,
+                Some(0),
+                Some(3),
+                None,
+                None,
+                Some(8),
+                Some(8),
+                Some(8),
+                None,
+                None,
+            ]
+        );
+
+        let nesting = rules(&[("*.a", "${capture}.b"), ("*.b", "${capture}.a")]);
+        assert_eq!(nesting.nest(&["x.a", "x.b"]), [None, Some(0)]);
+    }
+}
@@ -1,3 +1,5 @@
+mod directory_editor;
+mod file_nesting;
 pub mod project_panel_settings;
 mod undo;
 mod utils;
@@ -16,6 +18,7 @@ use editor::{
 };
 use feature_flags::{FeatureFlagAppExt, ProjectPanelUndoRedoFeatureFlag};
 use file_icons::FileIcons;
+use file_nesting::NestedFiles;
 use git;
 use git::status::GitSummary;
 use git_ui;
@@ -39,7 +42,7 @@ use project::{
     git_store::{GitStoreEvent, RepositoryEvent, git_traversal::ChildEntriesGitIter},
     project_settings::GoToDiagnosticSeverityFilter,
 };
-use project_panel_settings::ProjectPanelSettings;
+use project_panel_settings::{FileNestingSettings, ProjectPanelSettings};
 use rayon::slice::ParallelSliceMut;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -92,6 +95,24 @@ struct VisibleEntriesForWorktree {
     worktree_id: WorktreeId,
     entries: Vec<GitEntry>,
     index: OnceCell<HashSet<Arc<RelPath>>>,
+    nested_files: NestedFiles,
+}
+
+impl VisibleEntriesForWorktree {
+    fn paths(&self) -> &HashSet<Arc<RelPath>> {
+        self.index
+            .get_or_init(|| self.entries.iter().map(|e| e.path.clone()).collect())
+    }
+
+    /// Returns the depth of the entry, including the level added by nesting it under a file.
+    fn depth(&self, entry: &Entry) -> usize {
+        let (depth, _) = ProjectPanel::calculate_depth_and_difference(entry, self.paths());
+        if self.nested_files.parent(entry.id).is_some() {
+            depth + 1
+        } else {
+            depth
+        }
+    }
 }
 
 struct State {
@@ -272,6 +293,7 @@ struct EntryDetails {
     kind: EntryKind,
     is_ignored: bool,
     is_expanded: bool,
+    has_nested_files: bool,
     is_selected: bool,
     is_marked: bool,
     is_editing: bool,
@@ -373,6 +395,9 @@ actions!(
         ToggleHideHidden,
         /// Starts a new search in the selected directory.
         NewSearchInDirectory,
+        /// Opens the selected directory as a buffer, where editing, moving and deleting lines
+        /// renames, moves and deletes the entries they list.
+        EditDirectory,
         /// Unfolds the selected directory.
         UnfoldDirectory,
         /// Folds the selected directory.
@@ -837,7 +862,13 @@ impl ProjectPanel {
             .detach();
 
             let mut project_panel_settings = *ProjectPanelSettings::get_global(cx);
+            let mut file_nesting_settings = FileNestingSettings::get_global(cx).clone();
             cx.observe_global_in::<SettingsStore>(window, move |this, window, cx| {
+                let new_file_nesting_settings = FileNestingSettings::get_global(cx);
+                if file_nesting_settings != *new_file_nesting_settings {
+                    file_nesting_settings = new_file_nesting_settings.clone();
+                    this.update_visible_entries(None, false, false, window, cx);
+                }
                 let new_settings = *ProjectPanelSettings::get_global(cx);
                 if project_panel_settings != new_settings {
                     if project_panel_settings.hide_gitignore != new_settings.hide_gitignore {
@@ -1220,6 +1251,7 @@ impl ProjectPanel {
                             .when(is_dir, |menu| {
                                 menu.separator()
                                     .action("Find in Folder…", Box::new(NewSearchInDirectory))
+                                    .action("Edit Folder as Text", Box::new(EditDirectory))
                             })
                             .when(is_unfoldable, |menu| {
                                 menu.action("Unfold Directory", Box::new(UnfoldDirectory))
@@ -1372,7 +1404,11 @@ impl ProjectPanel {
                 cx.notify();
                 return;
             }
-            if entry.is_dir() {
+            let is_dir = entry.is_dir();
+            let has_nested_files = self
+                .nested_files(worktree.id())
+                .is_some_and(|nested| nested.has_children(entry.id));
+            if is_dir || has_nested_files {
                 let worktree_id = worktree.id();
                 let entry_id = entry.id;
                 let expanded_dir_ids = if let Some(expanded_dir_ids) =
@@ -1386,9 +1422,11 @@ impl ProjectPanel {
                 match expanded_dir_ids.binary_search(&entry_id) {
                     Ok(_) => self.select_next(&SelectNext, window, cx),
                     Err(ix) => {
-                        self.project.update(cx, |project, cx| {
-                            project.expand_entry(worktree_id, entry_id, cx);
-                        });
+                        if is_dir {
+                            self.project.update(cx, |project, cx| {
+                                project.expand_entry(worktree_id, entry_id, cx);
+                            });
+                        }
 
                         expanded_dir_ids.insert(ix, entry_id);
                         self.update_visible_entries(None, false, false, window, cx);
@@ -1427,6 +1465,11 @@ impl ProjectPanel {
             return;
         }
         let worktree_id = worktree.id();
+        // Nested files collapse the file they're nested under, rather than their directory.
+        let nesting_parent = self
+            .nested_files(worktree_id)
+            .and_then(|nested| nested.parent(entry.id))
+            .and_then(|parent_id| worktree.entry_for_id(parent_id));
         let expanded_dir_ids =
             if let Some(expanded_dir_ids) = self.state.expanded_dir_ids.get_mut(&worktree_id) {
                 expanded_dir_ids
@@ -1434,7 +1477,7 @@ impl ProjectPanel {
                 return;
             };
 
-        let mut entry = &entry;
+        let mut entry = nesting_parent.unwrap_or(&entry);
         loop {
             let entry_id = entry.id;
             match expanded_dir_ids.binary_search(&entry_id) {
@@ -1553,16 +1596,23 @@ impl ProjectPanel {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        if let Some(worktree_id) = self.project.read(cx).worktree_id_for_entry(entry_id, cx)
-            && let Some(expanded_dir_ids) = self.state.expanded_dir_ids.get_mut(&worktree_id)
-        {
+        let Some(worktree_id) = self.project.read(cx).worktree_id_for_entry(entry_id, cx) else {
+            return;
+        };
+        // Files with nested files are expanded to show them, but have no entries to load.
+        let has_nested_files = self
+            .nested_files(worktree_id)
+            .is_some_and(|nested| nested.has_children(entry_id));
+        if let Some(expanded_dir_ids) = self.state.expanded_dir_ids.get_mut(&worktree_id) {
             self.project.update(cx, |project, cx| {
                 match expanded_dir_ids.binary_search(&entry_id) {
                     Ok(ix) => {
                         expanded_dir_ids.remove(ix);
                     }
                     Err(ix) => {
-                        project.expand_entry(worktree_id, entry_id, cx);
+                        if !has_nested_files {
+                            project.expand_entry(worktree_id, entry_id, cx);
+                        }
                         expanded_dir_ids.insert(ix, entry_id);
                     }
                 }
@@ -3643,6 +3693,30 @@ impl ProjectPanel {
         }
     }
 
+    fn edit_directory(&mut self, _: &EditDirectory, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((worktree, entry)) = self.selected_sub_entry(cx) else {
+            return;
+        };
+        let directory_id = if entry.is_dir() {
+            Some(entry.id)
+        } else {
+            let worktree = worktree.read(cx);
+            entry
+                .path
+                .parent()
+                .and_then(|parent| worktree.entry_for_path(parent))
+                .map(|parent| parent.id)
+        };
+        let Some(directory_id) = directory_id else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                directory_editor::DirectoryEditor::open(workspace, directory_id, window, cx);
+            })
+            .ok();
+    }
+
     fn move_entry(
         &mut self,
         entry_to_move: ProjectEntryId,
@@ -3740,6 +3814,14 @@ impl ProjectPanel {
         rename_task
     }
 
+    fn nested_files(&self, worktree_id: WorktreeId) -> Option<&NestedFiles> {
+        self.state
+            .visible_entries
+            .iter()
+            .find(|visible| visible.worktree_id == worktree_id)
+            .map(|visible| &visible.nested_files)
+    }
+
     fn index_for_selection(&self, selection: SelectedEntry) -> Option<(usize, usize, usize)> {
         self.index_for_entry(selection.entry_id, selection.worktree_id)
     }
@@ -3796,6 +3878,28 @@ impl ProjectPanel {
             }
         }
 
+        // Files nested under a collapsed file are hidden, so they go wherever it goes.
+        let hidden_nested_files = sanitized_entries
+            .iter()
+            .flat_map(|entry| {
+                let worktree_id = entry.worktree_id;
+                let is_collapsed = self
+                    .state
+                    .expanded_dir_ids
+                    .get(&worktree_id)
+                    .is_none_or(|ids| ids.binary_search(&entry.entry_id).is_err());
+                self.nested_files(worktree_id)
+                    .filter(|_| is_collapsed)
+                    .map_or(&[][..], |nested| nested.children(entry.entry_id))
+                    .iter()
+                    .map(move |entry_id| SelectedEntry {
+                        worktree_id,
+                        entry_id: *entry_id,
+                    })
+            })
+            .collect::<Vec<_>>();
+        sanitized_entries.extend(hidden_nested_files);
+
         sanitized_entries
     }
 
@@ -3931,6 +4035,7 @@ impl ProjectPanel {
     }
 
     fn expand_to_selection(&mut self, cx: &mut Context<Self>) -> Option<()> {
+        let file_nesting_rules = FileNestingSettings::get_global(cx).active_rules();
         let (worktree, entry) = self.selected_entry(cx)?;
         let expanded_dir_ids = self
             .state
@@ -3938,6 +4043,12 @@ impl ProjectPanel {
             .entry(worktree.id())
             .or_default();
 
+        if let Some(parent_id) = file_nesting::nesting_parent(&file_nesting_rules, worktree, entry)
+            && let Err(idx) = expanded_dir_ids.binary_search(&parent_id)
+        {
+            expanded_dir_ids.insert(idx, parent_id);
+        }
+
         for path in entry.path.ancestors() {
             let Some(entry) = worktree.entry_for_path(path) else {
                 continue;
@@ -4010,6 +4121,7 @@ impl ProjectPanel {
             .collect();
         let hide_root = settings.hide_root && visible_worktrees.len() == 1;
         let hide_hidden = settings.hide_hidden;
+        let file_nesting_rules = FileNestingSettings::get_global(cx).active_rules();
 
         let visible_entries_task = cx.spawn_in(window, async move |this, cx| {
             let new_state = cx
@@ -4226,10 +4338,19 @@ impl ProjectPanel {
                             &mut visible_worktree_entries,
                             sort_mode,
                         );
+                        let nested_files = file_nesting::nest_entries(
+                            &file_nesting_rules,
+                            &mut visible_worktree_entries,
+                            new_state
+                                .expanded_dir_ids
+                                .get(&worktree_id)
+                                .map_or(&[], Vec::as_slice),
+                        );
                         new_state.visible_entries.push(VisibleEntriesForWorktree {
                             worktree_id,
                             entries: visible_worktree_entries,
                             index: OnceCell::new(),
+                            nested_files,
                         })
                     }
                     if let Some((project_entry_id, worktree_id, _)) = max_width_item {
@@ -4305,6 +4426,7 @@ impl ProjectPanel {
         entry_id: ProjectEntryId,
         cx: &mut Context<Self>,
     ) {
+        let file_nesting_rules = FileNestingSettings::get_global(cx).active_rules();
         self.project.update(cx, |project, cx| {
             if let Some((worktree, expanded_dir_ids)) = project
                 .worktree_for_id(worktree_id, cx)
@@ -4314,6 +4436,12 @@ impl ProjectPanel {
                 let worktree = worktree.read(cx);
 
                 if let Some(mut entry) = worktree.entry_for_id(entry_id) {
+                    if let Some(parent_id) =
+                        file_nesting::nesting_parent(&file_nesting_rules, worktree, entry)
+                        && let Err(ix) = expanded_dir_ids.binary_search(&parent_id)
+                    {
+                        expanded_dir_ids.insert(ix, parent_id);
+                    }
                     loop {
                         if let Err(ix) = expanded_dir_ids.binary_search(&entry.id) {
                             expanded_dir_ids.insert(ix, entry.id);
@@ -4767,7 +4895,7 @@ impl ProjectPanel {
         callback: &mut dyn FnMut(
             &Entry,
             usize,
-            &HashSet<Arc<RelPath>>,
+            &VisibleEntriesForWorktree,
             &mut Window,
             &mut Context<ProjectPanel>,
         ),
@@ -4785,13 +4913,10 @@ impl ProjectPanel {
 
             let end_ix = range.end.min(ix + visible.entries.len());
             let entry_range = range.start.saturating_sub(ix)..end_ix - ix;
-            let entries = visible
-                .index
-                .get_or_init(|| visible.entries.iter().map(|e| e.path.clone()).collect());
             let base_index = ix + entry_range.start;
             for (i, entry) in visible.entries[entry_range].iter().enumerate() {
                 let global_index = base_index + i;
-                callback(entry, global_index, entries, window, cx);
+                callback(entry, global_index, visible, window, cx);
             }
             ix = end_ix;
         }
@@ -4839,7 +4964,9 @@ impl ProjectPanel {
                     .get_or_init(|| visible.entries.iter().map(|e| e.path.clone()).collect());
                 for entry in visible.entries[entry_range].iter() {
                     let status = git_status_setting
-                        .then_some(entry.git_summary)
+                        .then(|| {
+                            entry.git_summary + visible.nested_files.children_git_summary(entry.id)
+                        })
                         .unwrap_or_default();
 
                     let mut details = self.details_for_entry(
@@ -5343,6 +5470,7 @@ impl ProjectPanel {
 
         let depth = details.depth;
         let worktree_id = details.worktree_id;
+        let nested_files_toggle = details.has_nested_files.then_some(details.is_expanded);
         let dragged_selection = DraggedSelection {
             active_selection: SelectedEntry {
                 worktree_id: selection.worktree_id,
@@ -5763,6 +5891,13 @@ impl ProjectPanel {
                 ListItem::new(id)
                     .indent_level(depth)
                     .indent_step_size(px(settings.indent_size))
+                    .when_some(nested_files_toggle, |this, is_expanded| {
+                        this.toggle(is_expanded).on_toggle(cx.listener(
+                            move |project_panel, _, window, cx| {
+                                project_panel.toggle_expanded(entry_id, window, cx);
+                            },
+                        ))
+                    })
                     .spacing(match settings.entry_spacing {
                         ProjectPanelEntrySpacing::Comfortable => ListItemSpacing::Dense,
                         ProjectPanelEntrySpacing::Standard => ListItemSpacing::ExtraDense,
@@ -6198,6 +6333,11 @@ impl ProjectPanel {
         let path_style = self.project.read(cx).path_style(cx);
         let (depth, difference) =
             ProjectPanel::calculate_depth_and_difference(entry, entries_paths);
+        let nested_files = self.nested_files(worktree_id);
+        let is_nested_file = sticky.is_none()
+            && nested_files.is_some_and(|nested| nested.parent(entry.id).is_some());
+        let depth = if is_nested_file { depth + 1 } else { depth };
+        let has_nested_files = nested_files.is_some_and(|nested| nested.has_children(entry.id));
 
         let filename = if difference > 1 {
             entry
@@ -6247,6 +6387,7 @@ impl ProjectPanel {
             kind: entry.kind,
             is_ignored: entry.is_ignored,
             is_expanded,
+            has_nested_files,
             is_selected,
             is_marked,
             is_editing: false,
@@ -6694,6 +6835,7 @@ impl Render for ProjectPanel {
                         .on_action(cx.listener(Self::copy))
                         .on_action(cx.listener(Self::paste))
                         .on_action(cx.listener(Self::duplicate))
+                        .on_action(cx.listener(Self::edit_directory))
                         .on_action(cx.listener(Self::restore_file))
                         .when(!project.is_remote(), |el| {
                             el.on_action(cx.listener(Self::trash))
@@ -6745,12 +6887,8 @@ impl Render for ProjectPanel {
                                                 range,
                                                 window,
                                                 cx,
-                                                &mut |entry, _, entries, _, _| {
-                                                    let (depth, _) =
-                                                        Self::calculate_depth_and_difference(
-                                                            entry, entries,
-                                                        );
-                                                    items.push(depth);
+                                                &mut |entry, _, visible, _, _| {
+                                                    items.push(visible.depth(entry));
                                                 },
                                             );
                                             items
@@ -6858,10 +6996,11 @@ impl Render for ProjectPanel {
                                             range,
                                             window,
                                             cx,
-                                            &mut |entry, index, entries, _, _| {
+                                            &mut |entry, index, visible, _, _| {
                                                 let (depth, _) =
                                                     Self::calculate_depth_and_difference(
-                                                        entry, entries,
+                                                        entry,
+                                                        visible.paths(),
                                                     );
                                                 let candidate =
                                                     StickyProjectPanelCandidate { index, depth };
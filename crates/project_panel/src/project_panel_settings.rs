@@ -1,3 +1,4 @@
+use crate::file_nesting::FileNestingRules;
 use editor::EditorSettings;
 use gpui::Pixels;
 use schemars::JsonSchema;
@@ -140,3 +141,34 @@ impl Settings for ProjectPanelSettings {
         }
     }
 }
+
+#[derive(Clone, Debug, PartialEq, RegisterSetting)]
+pub struct FileNestingSettings {
+    pub enabled: bool,
+    pub rules: FileNestingRules,
+}
+
+impl FileNestingSettings {
+    /// Returns the rules to nest files with, which are empty when nesting is disabled.
+    pub fn active_rules(&self) -> FileNestingRules {
+        if self.enabled {
+            self.rules.clone()
+        } else {
+            FileNestingRules::default()
+        }
+    }
+}
+
+impl Settings for FileNestingSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let file_nesting = content
+            .project_panel
+            .as_ref()
+            .and_then(|project_panel| project_panel.file_nesting.clone())
+            .unwrap();
+        Self {
+            enabled: file_nesting.enabled.unwrap(),
+            rules: FileNestingRules::new(&file_nesting.patterns.unwrap().0),
+        }
+    }
+}
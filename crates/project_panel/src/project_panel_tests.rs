@@ -3894,6 +3894,179 @@ async fn test_select_git_entry(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_file_nesting(cx: &mut gpui::TestAppContext) {
+    init_test_with_editor(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings(cx, |settings| {
+                settings
+                    .project_panel
+                    .get_or_insert_default()
+                    .file_nesting
+                    .get_or_insert_default()
+                    .enabled = Some(true);
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/root"),
+        json!({
+            "tree": {
+                ".git": {},
+                "src": {
+                    "bar.ts": "1",
+                    "foo.js": "1",
+                    "foo.js.map": "1",
+                    "foo.ts": "1",
+                },
+                ".env": "1",
+                ".env.local": "1",
+                "Cargo.lock": "1",
+                "Cargo.toml": "1",
+                "readme.md": "1",
+            },
+        }),
+    )
+    .await;
+    fs.set_head_and_index_for_repo(
+        path!("/root/tree/.git").as_ref(),
+        &[("src/foo.js", "modified".into())],
+    );
+
+    let project = Project::test(fs.clone(), [path!("/root/tree").as_ref()], cx).await;
+    let window = cx.add_window(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+    let workspace = window
+        .read_with(cx, |mw, _| mw.workspace().clone())
+        .unwrap();
+    let cx = &mut VisualTestContext::from_window(window.into(), cx);
+    let panel = workspace.update_in(cx, ProjectPanel::new);
+    cx.run_until_parked();
+
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v tree",
+            "    > .git",
+            "    > src",
+            "    > .env",
+            "    > Cargo.toml",
+            "      readme.md",
+        ],
+    );
+
+    toggle_expand_dir(&panel, "tree/.env", cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v tree",
+            "    > .git",
+            "    > src",
+            "    v .env  <== selected",
+            "          .env.local",
+            "    > Cargo.toml",
+            "      readme.md",
+        ],
+    );
+
+    // Revealing a modified file expands the file it's nested under.
+    panel.update_in(cx, |panel, window, cx| {
+        panel.select_next_git_entry(&SelectNextGitEntry, window, cx);
+    });
+    cx.run_until_parked();
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v tree",
+            "    > .git",
+            "    v src",
+            "          bar.ts",
+            "        v foo.ts",
+            "              foo.js  <== selected",
+            "              foo.js.map",
+            "    v .env",
+            "          .env.local",
+            "    > Cargo.toml",
+        ],
+    );
+
+    // Nested files' git status is shown on the file they're nested under.
+    panel.update_in(cx, |panel, window, cx| {
+        panel.for_each_visible_entry(0..10, window, cx, &mut |_, details, _, _| {
+            if details.filename == "foo.ts" {
+                let git_status = details.git_status;
+                assert!(git_status.index.modified + git_status.worktree.modified > 0);
+            }
+        });
+    });
+
+    // Collapsing from a nested file collapses the file it's nested under.
+    panel.update_in(cx, |panel, window, cx| {
+        panel.collapse_selected_entry(&CollapseSelectedEntry, window, cx);
+    });
+    cx.run_until_parked();
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..6, cx),
+        &[
+            "v tree",
+            "    > .git",
+            "    v src",
+            "          bar.ts",
+            "        > foo.ts  <== selected",
+            "    v .env",
+        ],
+    );
+
+    // Deleting a collapsed file also deletes the files nested under it.
+    select_path(&panel, "tree/Cargo.toml", cx);
+    submit_deletion_skipping_prompt(&panel, cx);
+    assert!(!fs.is_file(path!("/root/tree/Cargo.toml").as_ref()).await);
+    assert!(!fs.is_file(path!("/root/tree/Cargo.lock").as_ref()).await);
+    assert!(fs.is_file(path!("/root/tree/readme.md").as_ref()).await);
+}
+
+#[gpui::test]
+fn test_file_nesting_patterns_replace_defaults(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    let file_names = ["Cargo.toml", "Cargo.lock", "notes.md", "notes.html"];
+    cx.update(|cx| {
+        let rules = &FileNestingSettings::get_global(cx).rules;
+        assert_eq!(rules.nest(&file_names), [None, Some(0), None, None]);
+    });
+
+    // User patterns replace the default ones instead of being merged with them.
+    let set_patterns = |patterns: &[(&str, &str)], cx: &mut gpui::TestAppContext| {
+        let patterns = settings::FileNestingPatterns(
+            patterns
+                .iter()
+                .map(|(parent, children)| (parent.to_string(), children.to_string()))
+                .collect(),
+        );
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings
+                        .project_panel
+                        .get_or_insert_default()
+                        .file_nesting
+                        .get_or_insert_default()
+                        .patterns = Some(patterns);
+                });
+            });
+        });
+    };
+    set_patterns(&[("*.md", "${capture}.html")], cx);
+    cx.update(|cx| {
+        let rules = &FileNestingSettings::get_global(cx).rules;
+        assert_eq!(rules.nest(&file_names), [None, None, None, Some(2)]);
+    });
+
+    set_patterns(&[], cx);
+    cx.update(|cx| assert!(FileNestingSettings::get_global(cx).rules.is_empty()));
+}
+
 #[gpui::test]
 async fn test_select_directory(cx: &mut gpui::TestAppContext) {
     init_test_with_editor(cx);
@@ -9732,7 +9905,7 @@ fn visible_entries_as_strings(
             }
 
             let indent = "    ".repeat(details.depth);
-            let icon = if details.kind.is_dir() {
+            let icon = if details.kind.is_dir() || details.has_nested_files {
                 if details.is_expanded { "v " } else { "> " }
             } else {
                 "  "
@@ -10443,7 +10616,7 @@ pub(crate) fn init_test(cx: &mut TestAppContext) {
     });
 }
 
-fn init_test_with_editor(cx: &mut TestAppContext) {
+pub(crate) fn init_test_with_editor(cx: &mut TestAppContext) {
     cx.update(|cx| {
         let app_state = AppState::test(cx);
         theme::init(theme::LoadThemes::JustBase, cx);
@@ -1,8 +1,9 @@
 use anyhow::anyhow;
 use gpui::{AppContext, SharedString, Task, WeakEntity};
-use project::ProjectPath;
-use std::collections::VecDeque;
+use project::{ProjectPath, WorktreeId};
+use std::{collections::VecDeque, sync::Arc};
 use ui::{App, IntoElement, Label, ParentElement, Styled, v_flex};
+use util::rel_path::RelPath;
 use workspace::{
     Workspace,
     notifications::{NotificationId, simple_message_notification::MessageNotification},
@@ -20,6 +21,19 @@ pub enum ProjectPanelOperation {
         old_path: ProjectPath,
         new_path: ProjectPath,
     },
+    /// Deletes an entry, keeping what's needed to recreate it.
+    Delete {
+        worktree_id: WorktreeId,
+        /// The deleted entry followed by its descendants, parents before their children.
+        entries: Vec<DeletedEntry>,
+    },
+}
+
+#[derive(Clone)]
+pub struct DeletedEntry {
+    pub path: Arc<RelPath>,
+    /// The contents of a deleted file, or `None` for a directory.
+    pub contents: Option<Vec<u8>>,
 }
 
 pub struct UndoManager {
@@ -48,7 +62,7 @@ impl UndoManager {
 
     pub fn undo(&mut self, cx: &mut App) {
         if let Some(operation) = self.stack.pop_back() {
-            let task = self.revert_operation(operation, cx);
+            let task = Self::revert_operation(&self.workspace, operation, cx);
             let workspace = self.workspace.clone();
 
             cx.spawn(async move |cx| {
@@ -93,13 +107,13 @@ impl UndoManager {
     /// For all operations other than [`crate::undo::ProjectPanelOperation::Batch`], a maximum
     /// of one error is returned.
     fn revert_operation(
-        &self,
+        workspace: &WeakEntity<Workspace>,
         operation: ProjectPanelOperation,
         cx: &mut App,
     ) -> Task<Vec<anyhow::Error>> {
         match operation {
             ProjectPanelOperation::Create { project_path } => {
-                let Some(workspace) = self.workspace.upgrade() else {
+                let Some(workspace) = workspace.upgrade() else {
                     return Task::ready(vec![anyhow!("Failed to obtain workspace.")]);
                 };
 
@@ -127,7 +141,7 @@ impl UndoManager {
                 })
             }
             ProjectPanelOperation::Rename { old_path, new_path } => {
-                let Some(workspace) = self.workspace.upgrade() else {
+                let Some(workspace) = workspace.upgrade() else {
                     return Task::ready(vec![anyhow!("Failed to obtain workspace.")]);
                 };
 
@@ -162,22 +176,50 @@ impl UndoManager {
                 //
                 // If we first try to revert the directory creation, it would
                 // fail because there's still files inside the directory.
-                // Operations are also reverted sequentially in order to avoid
-                // this same problem.
-                let tasks: Vec<_> = operations
-                    .into_iter()
-                    .rev()
-                    .map(|operation| self.revert_operation(operation, cx))
-                    .collect();
-
-                cx.spawn(async move |_| {
+                // Operations are also reverted sequentially, each one starting
+                // once the previous one has finished, since reverting an
+                // operation looks up entries by the paths that earlier reverts
+                // move them to.
+                let workspace = workspace.clone();
+                cx.spawn(async move |cx| {
                     let mut errors = Vec::new();
-                    for task in tasks {
+                    for operation in operations.into_iter().rev() {
+                        let task =
+                            cx.update(|cx| Self::revert_operation(&workspace, operation, cx));
                         errors.extend(task.await);
                     }
                     errors
                 })
             }
+            ProjectPanelOperation::Delete {
+                worktree_id,
+                entries,
+            } => {
+                let Some(workspace) = workspace.upgrade() else {
+                    return Task::ready(vec![anyhow!("Failed to obtain workspace.")]);
+                };
+                let Some(worktree) = workspace
+                    .read(cx)
+                    .project()
+                    .read(cx)
+                    .worktree_for_id(worktree_id, cx)
+                else {
+                    return Task::ready(vec![anyhow!("No worktree for path.")]);
+                };
+
+                cx.spawn(async move |cx| {
+                    for entry in entries {
+                        let is_directory = entry.contents.is_none();
+                        let task = worktree.update(cx, |worktree, cx| {
+                            worktree.create_entry(entry.path, is_directory, entry.contents, cx)
+                        });
+                        if let Err(err) = task.await {
+                            return vec![err];
+                        }
+                    }
+                    vec![]
+                })
+            }
         }
     }
 
@@ -117,9 +117,7 @@ impl PickerDelegate for ProjectSymbolsDelegate {
             .get(self.selected_match_index)
             .map(|mat| self.symbols[mat.candidate_id].clone())
         {
-            let buffer = self.project.update(cx, |project, cx| {
-                project.open_buffer_for_symbol(&symbol, cx)
-            });
+            let buffer = symbol_index::open_buffer_for_symbol(&self.project, &symbol, cx);
             let symbol = symbol.clone();
             let workspace = self.workspace.clone();
             cx.spawn_in(window, async move |_, cx| {
@@ -189,9 +187,7 @@ impl PickerDelegate for ProjectSymbolsDelegate {
             .to_owned();
         self.filter(&query_filter, window, cx);
         self.show_worktree_root_name = self.project.read(cx).visible_worktrees(cx).count() > 1;
-        let symbols = self
-            .project
-            .update(cx, |project, cx| project.symbols(&query, cx));
+        let symbols = symbol_index::search_symbols(&self.project, &query, cx);
         cx.spawn_in(window, async move |this, cx| {
             let symbols = symbols.await.log_err();
             if let Some(symbols) = symbols {
@@ -12,6 +12,9 @@ use project::{Project, ProjectPath, Toolchains, WorktreeId};
 use remote::RemoteConnectionOptions;
 pub use remote_kernels::*;
 
+mod query;
+pub use query::*;
+
 mod ssh_kernel;
 pub use ssh_kernel::*;
 
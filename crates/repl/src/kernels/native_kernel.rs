@@ -16,7 +16,7 @@ use std::{
     env,
     fmt::Debug,
     net::{IpAddr, Ipv4Addr, SocketAddr},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
@@ -74,6 +74,86 @@ impl LocalKernelSpecification {
 
         Ok(cmd)
     }
+
+    /// ipykernel from the Python on the PATH, used when no kernel is selected.
+    pub fn default_python() -> Self {
+        LocalKernelSpecification {
+            name: "python3".to_string(),
+            path: PathBuf::from("python3"),
+            kernelspec: JupyterKernelspec {
+                argv: vec![
+                    "python3".to_string(),
+                    "-m".to_string(),
+                    "ipykernel_launcher".to_string(),
+                    "-f".to_string(),
+                    "{connection_file}".to_string(),
+                ],
+                display_name: "Python 3".to_string(),
+                language: "python".to_string(),
+                interrupt_mode: None,
+                metadata: None,
+                env: None,
+            },
+        }
+    }
+
+    /// Writes a connection file named after `connection_id` to the Jupyter
+    /// runtime directory and starts the kernel with it.
+    pub async fn launch(
+        &self,
+        connection_id: &str,
+        working_directory: &Path,
+        fs: &dyn Fs,
+    ) -> Result<LaunchedKernel> {
+        let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+        let ports = peek_ports(ip).await?;
+
+        let connection_info = ConnectionInfo {
+            transport: Transport::TCP,
+            ip: ip.to_string(),
+            stdin_port: ports[0],
+            control_port: ports[1],
+            hb_port: ports[2],
+            shell_port: ports[3],
+            iopub_port: ports[4],
+            signature_scheme: "hmac-sha256".to_string(),
+            key: uuid::Uuid::new_v4().to_string(),
+            kernel_name: Some(format!("zed-{}", self.name)),
+        };
+
+        let runtime_dir = dirs::runtime_dir();
+        fs.create_dir(&runtime_dir)
+            .await
+            .with_context(|| format!("Failed to create jupyter runtime dir {runtime_dir:?}"))?;
+        let connection_path = runtime_dir.join(format!("kernel-zed-{connection_id}.json"));
+        let content = serde_json::to_string(&connection_info)?;
+        fs.atomic_write(connection_path.clone(), content).await?;
+
+        let mut cmd = self.command(&connection_path)?;
+        cmd.current_dir(working_directory);
+
+        let process = util::process::Child::spawn(
+            cmd,
+            std::process::Stdio::piped(),
+            std::process::Stdio::piped(),
+            std::process::Stdio::piped(),
+        )?;
+
+        Ok(LaunchedKernel {
+            process,
+            connection_info,
+            connection_path,
+        })
+    }
+}
+
+/// A kernel process started from a [`LocalKernelSpecification`].
+pub struct LaunchedKernel {
+    pub process: util::process::Child,
+    pub connection_info: ConnectionInfo,
+    /// The connection file passed to the kernel, which should be removed once
+    /// the kernel exits.
+    pub connection_path: PathBuf,
 }
 
 // Find a set of open ports. This creates a listener with port set to 0. The listener will be closed at the end when it goes out of scope.
@@ -121,39 +201,13 @@ impl NativeRunningKernel {
         cx: &mut App,
     ) -> Task<Result<Box<dyn RunningKernel>>> {
         window.spawn(cx, async move |cx| {
-            let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
-            let ports = peek_ports(ip).await?;
-
-            let connection_info = ConnectionInfo {
-                transport: Transport::TCP,
-                ip: ip.to_string(),
-                stdin_port: ports[0],
-                control_port: ports[1],
-                hb_port: ports[2],
-                shell_port: ports[3],
-                iopub_port: ports[4],
-                signature_scheme: "hmac-sha256".to_string(),
-                key: uuid::Uuid::new_v4().to_string(),
-                kernel_name: Some(format!("zed-{}", kernel_specification.name)),
-            };
-
-            let runtime_dir = dirs::runtime_dir();
-            fs.create_dir(&runtime_dir)
-                .await
-                .with_context(|| format!("Failed to create jupyter runtime dir {runtime_dir:?}"))?;
-            let connection_path = runtime_dir.join(format!("kernel-zed-{entity_id}.json"));
-            let content = serde_json::to_string(&connection_info)?;
-            fs.atomic_write(connection_path.clone(), content).await?;
-
-            let mut cmd = kernel_specification.command(&connection_path)?;
-            cmd.current_dir(&working_directory);
-
-            let mut process = util::process::Child::spawn(
-                cmd,
-                std::process::Stdio::piped(),
-                std::process::Stdio::piped(),
-                std::process::Stdio::piped(),
-            )?;
+            let LaunchedKernel {
+                mut process,
+                connection_info,
+                connection_path,
+            } = kernel_specification
+                .launch(&entity_id.to_string(), &working_directory, fs.as_ref())
+                .await?;
 
             let session_id = Uuid::new_v4().to_string();
 
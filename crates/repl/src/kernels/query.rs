@@ -0,0 +1,202 @@
+use anyhow::{Result, anyhow};
+use collections::HashMap;
+use futures::channel::oneshot;
+use runtimelib::{
+    ExecuteRequest, ExecutionState, JupyterMessage, JupyterMessageContent, ReplyStatus, Stdio,
+};
+
+/// Emitted by a kernel session when the kernel goes idle after executing user
+/// code, so that views derived from the kernel's state can refresh.
+pub struct KernelExecutionFinished;
+
+/// Tracks code that Zed runs in a kernel on its own behalf, such as the
+/// variable explorer's introspection snippets.
+///
+/// Queries report back through stdout, which is collected and handed to the
+/// requester once the kernel replies. Every message belonging to a query is
+/// consumed here so it never reaches the outputs of the user's cells.
+#[derive(Default)]
+pub struct KernelQueries {
+    pending: HashMap<String, PendingQuery>,
+}
+
+struct PendingQuery {
+    stdout: String,
+    error: Option<String>,
+    idle: bool,
+    response: Option<oneshot::Sender<Result<String>>>,
+}
+
+impl KernelQueries {
+    /// Builds the execute request for `code`, returning it along with a
+    /// receiver for the stdout the code produces.
+    pub fn request(&mut self, code: String) -> (JupyterMessage, oneshot::Receiver<Result<String>>) {
+        // Not `silent`, as kernels may drop stdout for silent requests.
+        let message: JupyterMessage = ExecuteRequest {
+            code,
+            silent: false,
+            store_history: false,
+            allow_stdin: false,
+            ..Default::default()
+        }
+        .into();
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.insert(
+            message.header.msg_id.clone(),
+            PendingQuery {
+                stdout: String::new(),
+                error: None,
+                idle: false,
+                response: Some(tx),
+            },
+        );
+        (message, rx)
+    }
+
+    /// Handles `message` if it belongs to a query, returning whether it did.
+    pub fn handle_message(&mut self, message: &JupyterMessage) -> bool {
+        let Some(parent) = message.parent_header.as_ref() else {
+            return false;
+        };
+        let Some(query) = self.pending.get_mut(&parent.msg_id) else {
+            return false;
+        };
+
+        match &message.content {
+            JupyterMessageContent::StreamContent(stream)
+                if matches!(stream.name, Stdio::Stdout) =>
+            {
+                query.stdout.push_str(&stream.text);
+            }
+            JupyterMessageContent::ErrorOutput(error) => {
+                query.error = Some(format!("{}: {}", error.ename, error.evalue));
+            }
+            JupyterMessageContent::ExecuteReply(reply) => {
+                let result = match (query.error.take(), &reply.status) {
+                    (Some(error), _) => Err(anyhow!(error)),
+                    (None, ReplyStatus::Ok) => Ok(std::mem::take(&mut query.stdout)),
+                    (None, status) => Err(anyhow!("kernel query failed with status {status:?}")),
+                };
+                if let Some(response) = query.response.take() {
+                    response.send(result).ok();
+                }
+            }
+            JupyterMessageContent::Status(status)
+                if status.execution_state == ExecutionState::Idle =>
+            {
+                query.idle = true;
+            }
+            _ => {}
+        }
+
+        // The reply arrives on the shell channel and the idle status on iopub,
+        // in either order. The query is done once both have been seen.
+        if query.idle && query.response.is_none() {
+            self.pending.remove(&parent.msg_id);
+        }
+
+        true
+    }
+
+    /// Drops all pending queries, failing their receivers. Used when the
+    /// kernel goes away before they can be answered.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use runtimelib::{ErrorOutput, ExecuteReply, Status, StreamContent};
+
+    fn stdout(text: &str) -> StreamContent {
+        StreamContent {
+            name: Stdio::Stdout,
+            text: text.to_string(),
+        }
+    }
+
+    fn idle() -> Status {
+        Status {
+            execution_state: ExecutionState::Idle,
+        }
+    }
+
+    #[test]
+    fn test_reply_before_idle() {
+        let mut queries = KernelQueries::default();
+        let (request, mut response) = queries.request("print(1)".to_string());
+
+        assert!(queries.handle_message(&stdout("1\n").as_child_of(&request)));
+        assert!(queries.handle_message(&ExecuteReply::default().as_child_of(&request)));
+        assert_eq!(response.try_recv().unwrap().unwrap().unwrap(), "1\n");
+
+        // The idle status that follows still belongs to the query, and completes it.
+        assert!(queries.handle_message(&idle().as_child_of(&request)));
+        assert!(queries.pending.is_empty());
+        assert!(!queries.handle_message(&idle().as_child_of(&request)));
+    }
+
+    #[test]
+    fn test_idle_before_reply() {
+        let mut queries = KernelQueries::default();
+        let (request, mut response) = queries.request("print(1)".to_string());
+
+        assert!(queries.handle_message(&stdout("1\n").as_child_of(&request)));
+        assert!(queries.handle_message(&idle().as_child_of(&request)));
+        assert!(response.try_recv().unwrap().is_none());
+
+        assert!(queries.handle_message(&ExecuteReply::default().as_child_of(&request)));
+        assert_eq!(response.try_recv().unwrap().unwrap().unwrap(), "1\n");
+        assert!(queries.pending.is_empty());
+    }
+
+    #[test]
+    fn test_error_output() {
+        let mut queries = KernelQueries::default();
+        let (request, mut response) = queries.request("print(x)".to_string());
+
+        let error = ErrorOutput {
+            ename: "NameError".to_string(),
+            evalue: "name 'x' is not defined".to_string(),
+            traceback: Vec::new(),
+        };
+        assert!(queries.handle_message(&error.as_child_of(&request)));
+        let reply = ExecuteReply {
+            status: ReplyStatus::Error,
+            ..Default::default()
+        };
+        assert!(queries.handle_message(&reply.as_child_of(&request)));
+        assert_eq!(
+            response
+                .try_recv()
+                .unwrap()
+                .unwrap()
+                .unwrap_err()
+                .to_string(),
+            "NameError: name 'x' is not defined"
+        );
+    }
+
+    #[test]
+    fn test_unrelated_messages() {
+        let mut queries = KernelQueries::default();
+        let (request, mut response) = queries.request("print(1)".to_string());
+
+        // Output of the user's own cells is left for them.
+        let cell: JupyterMessage = ExecuteRequest::new("print(2)".to_string()).into();
+        assert!(!queries.handle_message(&stdout("2\n").as_child_of(&cell)));
+        assert!(!queries.handle_message(&ExecuteReply::default().as_child_of(&cell)));
+        assert!(!queries.handle_message(&stdout("3\n").into()));
+
+        assert!(queries.handle_message(&ExecuteReply::default().as_child_of(&request)));
+        assert_eq!(response.try_recv().unwrap().unwrap().unwrap(), "");
+
+        // Clearing fails the queries that are still pending.
+        let (_, mut response) = queries.request("print(1)".to_string());
+        queries.clear();
+        assert!(response.try_recv().is_err());
+    }
+}
@@ -1,4 +1,6 @@
 mod cell;
 mod notebook_ui;
+mod runner;
 pub use cell::*;
 pub use notebook_ui::*;
+pub use runner::*;
@@ -20,6 +20,7 @@ use crate::{
     notebook::{CODE_BLOCK_INSET, GUTTER_WIDTH},
     outputs::{Output, plain, plain::TerminalOutput, user_error::ErrorView},
     repl_settings::ReplSettings,
+    widgets::WidgetStore,
 };
 
 #[derive(Copy, Clone, PartialEq, PartialOrd)]
@@ -100,6 +101,7 @@ pub enum Cell {
 
 fn convert_outputs(
     outputs: &Vec<nbformat::v4::Output>,
+    widgets: &Entity<WidgetStore>,
     window: &mut Window,
     cx: &mut App,
 ) -> Vec<Output> {
@@ -110,10 +112,10 @@ fn convert_outputs(
                 content: cx.new(|cx| TerminalOutput::from(&text.0, window, cx)),
             },
             nbformat::v4::Output::DisplayData(display_data) => {
-                Output::new(&display_data.data, None, window, cx)
+                Output::for_bundle(&display_data.data, None, Some(widgets), window, cx)
             }
             nbformat::v4::Output::ExecuteResult(execute_result) => {
-                Output::new(&execute_result.data, None, window, cx)
+                Output::for_bundle(&execute_result.data, None, Some(widgets), window, cx)
             }
             nbformat::v4::Output::Error(error) => Output::ErrorOutput(ErrorView {
                 ename: error.ename.clone(),
@@ -162,6 +164,7 @@ impl Cell {
         cell: &nbformat::v4::Cell,
         languages: &Arc<LanguageRegistry>,
         notebook_language: Shared<Task<Option<Arc<Language>>>>,
+        widgets: &Entity<WidgetStore>,
         window: &mut Window,
         cx: &mut App,
     ) -> Self {
@@ -195,7 +198,7 @@ impl Cell {
                 outputs,
             } => {
                 let text = source.join("");
-                let outputs = convert_outputs(outputs, window, cx);
+                let outputs = convert_outputs(outputs, widgets, window, cx);
 
                 Cell::Code(cx.new(|cx| {
                     CodeCell::load(
@@ -833,6 +836,7 @@ impl CodeCell {
     pub fn handle_message(
         &mut self,
         message: &JupyterMessage,
+        widgets: &Entity<WidgetStore>,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
@@ -843,12 +847,22 @@ impl CodeCell {
                 });
             }
             JupyterMessageContent::DisplayData(display_data) => {
-                self.outputs
-                    .push(Output::new(&display_data.data, None, window, cx));
+                self.outputs.push(Output::for_bundle(
+                    &display_data.data,
+                    None,
+                    Some(widgets),
+                    window,
+                    cx,
+                ));
             }
             JupyterMessageContent::ExecuteResult(execute_result) => {
-                self.outputs
-                    .push(Output::new(&execute_result.data, None, window, cx));
+                self.outputs.push(Output::for_bundle(
+                    &execute_result.data,
+                    None,
+                    Some(widgets),
+                    window,
+                    cx,
+                ));
             }
             JupyterMessageContent::ExecuteInput(input) => {
                 self.execution_count = serde_json::to_value(&input.execution_count)
@@ -32,13 +32,17 @@ use uuid::Uuid;
 
 use crate::components::{KernelPickerDelegate, KernelSelector};
 use crate::kernels::{
-    Kernel, KernelSession, KernelSpecification, KernelStatus, LocalKernelSpecification,
-    NativeRunningKernel, RemoteRunningKernel, SshRunningKernel, WslRunningKernel,
+    Kernel, KernelExecutionFinished, KernelQueries, KernelSession, KernelSpecification,
+    KernelStatus, LocalKernelSpecification, NativeRunningKernel, RemoteRunningKernel,
+    SshRunningKernel, WslRunningKernel,
 };
 use crate::repl_store::ReplStore;
+use crate::widgets::{WidgetCommMessage, WidgetStore};
 
 use picker::Picker;
-use runtimelib::{ExecuteRequest, JupyterMessage, JupyterMessageContent};
+use runtimelib::{
+    CommId, CommMsg, ExecuteRequest, ExecutionState, JupyterMessage, JupyterMessageContent,
+};
 use ui::PopoverMenuHandle;
 use zed_actions::editor::{MoveDown, MoveUp};
 use zed_actions::notebook::{NotebookMoveDown, NotebookMoveUp};
@@ -114,6 +118,8 @@ pub struct NotebookEditor {
     kernel: Kernel,
     kernel_specification: Option<KernelSpecification>,
     execution_requests: HashMap<String, CellId>,
+    kernel_queries: KernelQueries,
+    widgets: Entity<WidgetStore>,
     kernel_picker_handle: PopoverMenuHandle<Picker<KernelPickerDelegate>>,
 }
 
@@ -135,6 +141,19 @@ impl NotebookEditor {
             .spawn_in(window, async move |_, _| notebook_language.await)
             .shared();
 
+        let widgets = cx.new(|_| WidgetStore::new());
+        cx.subscribe(&widgets, |this, _, event: &WidgetCommMessage, _cx| {
+            if let Kernel::RunningKernel(kernel) = &mut this.kernel {
+                let message: JupyterMessage = CommMsg {
+                    comm_id: CommId(event.comm_id.clone()),
+                    data: event.data.clone(),
+                }
+                .into();
+                kernel.request_tx().try_send(message).ok();
+            }
+        })
+        .detach();
+
         let mut cell_order = vec![]; // Vec<CellId>
         let mut cell_map = HashMap::default(); // HashMap<CellId, Cell>
 
@@ -143,7 +162,14 @@ impl NotebookEditor {
             let cell = notebook_item.read(cx).notebook.cells[index].clone();
             let cell_id = cell.id();
             cell_order.push(cell_id.clone());
-            let cell_entity = Cell::load(&cell, &languages, notebook_language.clone(), window, cx);
+            let cell_entity = Cell::load(
+                &cell,
+                &languages,
+                notebook_language.clone(),
+                &widgets,
+                window,
+                cx,
+            );
 
             match &cell_entity {
                 Cell::Code(code_cell) => {
@@ -246,6 +272,8 @@ impl NotebookEditor {
             kernel: Kernel::Shutdown, // TODO: use recommended kernel after the implementation is done in repl
             kernel_specification: None,
             execution_requests: HashMap::default(),
+            kernel_queries: KernelQueries::default(),
+            widgets,
             kernel_picker_handle: PopoverMenuHandle::default(),
         };
         editor.launch_kernel(window, cx);
@@ -352,24 +380,7 @@ impl NotebookEditor {
     fn launch_kernel(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         // use default Python kernel if no specification is set
         let spec = self.kernel_specification.clone().unwrap_or_else(|| {
-            KernelSpecification::Jupyter(LocalKernelSpecification {
-                name: "python3".to_string(),
-                path: PathBuf::from("python3"),
-                kernelspec: JupyterKernelspec {
-                    argv: vec![
-                        "python3".to_string(),
-                        "-m".to_string(),
-                        "ipykernel_launcher".to_string(),
-                        "-f".to_string(),
-                        "{connection_file}".to_string(),
-                    ],
-                    display_name: "Python 3".to_string(),
-                    language: "python".to_string(),
-                    interrupt_mode: None,
-                    metadata: None,
-                    env: None,
-                },
-            })
+            KernelSpecification::Jupyter(LocalKernelSpecification::default_python())
         });
 
         self.launch_kernel_with_spec(spec, window, cx);
@@ -494,6 +505,8 @@ impl NotebookEditor {
         }
 
         self.execution_requests.clear();
+        self.kernel_queries.clear();
+        self.widgets.update(cx, |widgets, cx| widgets.clear(cx));
 
         self.launch_kernel_with_spec(spec, window, cx);
     }
@@ -505,6 +518,8 @@ impl NotebookEditor {
             }
 
             self.kernel = Kernel::Restarting;
+            self.kernel_queries.clear();
+            self.widgets.update(cx, |widgets, cx| widgets.clear(cx));
             cx.notify();
 
             self.launch_kernel_with_spec(spec, window, cx);
@@ -561,6 +576,25 @@ impl NotebookEditor {
         }
     }
 
+    /// Runs `code` in the kernel without showing any output, resolving to
+    /// whatever it prints to stdout.
+    pub fn query_kernel(&mut self, code: String, cx: &mut Context<Self>) -> Task<Result<String>> {
+        let Kernel::RunningKernel(kernel) = &mut self.kernel else {
+            return Task::ready(Err(anyhow::anyhow!("kernel is not running")));
+        };
+        let (message, response) = self.kernel_queries.request(code);
+        kernel.request_tx().try_send(message).ok();
+        cx.background_spawn(async move { response.await.context("kernel query was cancelled")? })
+    }
+
+    pub fn kernel_specification(&self) -> Option<&KernelSpecification> {
+        self.kernel_specification.as_ref()
+    }
+
+    pub fn kernel(&self) -> &Kernel {
+        &self.kernel
+    }
+
     fn has_outputs(&self, window: &mut Window, cx: &mut Context<Self>) -> bool {
         self.cell_map.values().any(|cell| {
             if let Cell::Code(code_cell) = cell {
@@ -1478,48 +1512,7 @@ impl project::ProjectItem for NotebookItem {
                     .await?;
                 let file_content = buffer.read_with(cx, |buffer, _| buffer.text());
 
-                let notebook = if file_content.trim().is_empty() {
-                    nbformat::v4::Notebook {
-                        nbformat: 4,
-                        nbformat_minor: 5,
-                        cells: vec![],
-                        metadata: serde_json::from_str("{}").unwrap(),
-                    }
-                } else {
-                    let notebook = match nbformat::parse_notebook(&file_content) {
-                        Ok(nb) => nb,
-                        Err(_) => {
-                            // Pre-process to ensure IDs exist
-                            let mut json: serde_json::Value = serde_json::from_str(&file_content)?;
-                            if let Some(cells) =
-                                json.get_mut("cells").and_then(|c| c.as_array_mut())
-                            {
-                                for cell in cells {
-                                    if cell.get("id").is_none() {
-                                        cell["id"] =
-                                            serde_json::Value::String(Uuid::new_v4().to_string());
-                                    }
-                                }
-                            }
-                            let file_content = serde_json::to_string(&json)?;
-                            nbformat::parse_notebook(&file_content)?
-                        }
-                    };
-
-                    match notebook {
-                        nbformat::Notebook::V4(notebook) => notebook,
-                        // 4.1 - 4.4 are converted to 4.5
-                        nbformat::Notebook::Legacy(legacy_notebook) => {
-                            // TODO: Decide if we want to mutate the notebook by including Cell IDs
-                            // and any other conversions
-
-                            nbformat::upgrade_legacy_notebook(legacy_notebook)?
-                        }
-                        nbformat::Notebook::V3(v3_notebook) => {
-                            nbformat::upgrade_v3_notebook(v3_notebook)?
-                        }
-                    }
-                };
+                let notebook = super::parse_notebook(&file_content)?;
 
                 let id = project
                     .update(cx, |project, cx| {
@@ -1586,6 +1579,7 @@ impl NotebookItem {
 impl EventEmitter<()> for NotebookItem {}
 
 impl EventEmitter<()> for NotebookEditor {}
+impl EventEmitter<KernelExecutionFinished> for NotebookEditor {}
 
 // pub struct NotebookControls {
 //     pane_focused: bool,
@@ -1810,8 +1804,14 @@ impl Item for NotebookEditor {
                 for cell in notebook.cells.iter() {
                     let cell_id = cell.id();
                     cell_order.push(cell_id.clone());
-                    let cell_entity =
-                        Cell::load(cell, &languages, notebook_language.clone(), window, cx);
+                    let cell_entity = Cell::load(
+                        cell,
+                        &languages,
+                        notebook_language.clone(),
+                        &this.widgets,
+                        window,
+                        cx,
+                    );
                     cell_map.insert(cell_id.clone(), cell_entity);
                 }
 
@@ -1848,9 +1848,23 @@ impl ProjectItem for NotebookEditor {
 
 impl KernelSession for NotebookEditor {
     fn route(&mut self, message: &JupyterMessage, window: &mut Window, cx: &mut Context<Self>) {
+        // Zed's own queries never produce visible output
+        if self.kernel_queries.handle_message(message) {
+            return;
+        }
+
+        if self.widgets.update(cx, |widgets, cx| {
+            widgets.handle_comm_message(message, window, cx)
+        }) {
+            return;
+        }
+
         // Handle kernel status updates (these are broadcast to all)
         if let JupyterMessageContent::Status(status) = &message.content {
             self.kernel.set_execution_state(&status.execution_state);
+            if status.execution_state == ExecutionState::Idle {
+                cx.emit(KernelExecutionFinished);
+            }
             cx.notify();
         }
 
@@ -1868,12 +1882,20 @@ impl KernelSession for NotebookEditor {
             cx.notify();
         }
 
+        // Output widgets capture the outputs of the execution they are active in
+        if self.widgets.update(cx, |widgets, cx| {
+            widgets.capture_output(message, window, cx)
+        }) {
+            return;
+        }
+
         // Handle cell-specific messages
         if let Some(parent_header) = &message.parent_header {
             if let Some(cell_id) = self.execution_requests.get(&parent_header.msg_id) {
                 if let Some(Cell::Code(cell)) = self.cell_map.get(cell_id) {
+                    let widgets = self.widgets.clone();
                     cell.update(cx, |cell, cx| {
-                        cell.handle_message(message, window, cx);
+                        cell.handle_message(message, &widgets, window, cx);
                     });
                 }
             }
@@ -0,0 +1,662 @@
+//! Runs notebooks without opening them in the editor, for `zed --run-notebook`.
+//!
+//! Code cells are executed in order in a native Jupyter kernel. Their outputs
+//! are written back into the notebook as they would be when running the cells
+//! in the notebook editor, and execution stops at the first cell that fails or
+//! exceeds its timeout.
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::{Context as _, Result, anyhow};
+use futures::{AsyncBufReadExt as _, FutureExt as _, StreamExt as _, io::BufReader};
+use gpui::BackgroundExecutor;
+use project::Fs;
+use runtimelib::{
+    ExecuteRequest, ExecutionState, JupyterMessage, JupyterMessageContent, KernelInfoRequest,
+    ReplyStatus,
+};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::kernels::{LaunchedKernel, LocalKernelSpecification, local_kernel_specifications};
+
+pub const DEFAULT_CELL_TIMEOUT: Duration = Duration::from_secs(600);
+const KERNEL_STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct NotebookRunOptions {
+    /// How long each cell may run before the run is stopped.
+    pub cell_timeout: Duration,
+    pub working_directory: PathBuf,
+}
+
+#[derive(Debug)]
+pub struct CellFailure {
+    pub cell_index: usize,
+    pub message: String,
+}
+
+pub struct NotebookRun {
+    /// The notebook with the outputs of every cell that was executed.
+    pub notebook: nbformat::v4::Notebook,
+    /// The cell that stopped the run, if any.
+    pub failure: Option<CellFailure>,
+}
+
+/// Parses a notebook, upgrading older formats to nbformat v4.5.
+pub fn parse_notebook(contents: &str) -> Result<nbformat::v4::Notebook> {
+    if contents.trim().is_empty() {
+        return Ok(nbformat::v4::Notebook {
+            nbformat: 4,
+            nbformat_minor: 5,
+            cells: vec![],
+            metadata: serde_json::from_str("{}")?,
+        });
+    }
+
+    let notebook = match nbformat::parse_notebook(contents) {
+        Ok(notebook) => notebook,
+        Err(_) => {
+            // v4.5 requires cell IDs, which some tools don't write.
+            let mut json: Value = serde_json::from_str(contents)?;
+            if let Some(cells) = json.get_mut("cells").and_then(|c| c.as_array_mut()) {
+                for cell in cells {
+                    if cell.get("id").is_none() {
+                        cell["id"] = Value::String(Uuid::new_v4().to_string());
+                    }
+                }
+            }
+            nbformat::parse_notebook(&serde_json::to_string(&json)?)?
+        }
+    };
+
+    Ok(match notebook {
+        nbformat::Notebook::V4(notebook) => notebook,
+        // 4.1 - 4.4 are converted to 4.5
+        nbformat::Notebook::Legacy(legacy_notebook) => {
+            nbformat::upgrade_legacy_notebook(legacy_notebook)?
+        }
+        nbformat::Notebook::V3(v3_notebook) => nbformat::upgrade_v3_notebook(v3_notebook)?,
+    })
+}
+
+/// Finds the kernel to run `notebook` in among the locally installed kernels:
+/// the one named `kernel_name`, else the one in the notebook's metadata, else
+/// any kernel for the notebook's language.
+pub async fn find_notebook_kernel(
+    notebook: &nbformat::v4::Notebook,
+    kernel_name: Option<&str>,
+    fs: Arc<dyn Fs>,
+) -> Result<LocalKernelSpecification> {
+    let kernels = local_kernel_specifications(fs).await?;
+
+    if let Some(kernel_name) = kernel_name {
+        return kernels
+            .into_iter()
+            .find(|kernel| kernel.name == kernel_name)
+            .with_context(|| format!("No Jupyter kernel named {kernel_name:?} is installed"));
+    }
+
+    let kernelspec = notebook.metadata.kernelspec.as_ref();
+    if let Some(kernel) =
+        kernelspec.and_then(|spec| kernels.iter().find(|kernel| kernel.name == spec.name))
+    {
+        return Ok(kernel.clone());
+    }
+
+    let language = notebook
+        .metadata
+        .language_info
+        .as_ref()
+        .map(|info| info.name.clone())
+        .or_else(|| kernelspec.and_then(|spec| spec.language.clone()))
+        .unwrap_or_else(|| "python".to_string());
+    if let Some(kernel) = kernels
+        .iter()
+        .find(|kernel| kernel.kernelspec.language.eq_ignore_ascii_case(&language))
+    {
+        return Ok(kernel.clone());
+    }
+
+    // Like the notebook editor, fall back to ipykernel from the Python on the PATH.
+    anyhow::ensure!(
+        language.eq_ignore_ascii_case("python"),
+        "No Jupyter kernel for {language} is installed"
+    );
+    Ok(LocalKernelSpecification::default_python())
+}
+
+/// Executes the notebook's code cells in order, stopping at the first failure.
+pub async fn run_notebook(
+    mut notebook: nbformat::v4::Notebook,
+    kernel: LocalKernelSpecification,
+    options: NotebookRunOptions,
+    fs: Arc<dyn Fs>,
+    executor: BackgroundExecutor,
+) -> Result<NotebookRun> {
+    let mut kernel = RunnerKernel(
+        kernel
+            .launch(
+                &format!("headless-{}", Uuid::new_v4()),
+                &options.working_directory,
+                fs.as_ref(),
+            )
+            .await
+            .with_context(|| format!("Failed to start the {} kernel", kernel.name))?,
+    );
+
+    let stdout = kernel.0.process.stdout.take();
+    let stderr = kernel.0.process.stderr.take();
+    executor
+        .spawn(async move {
+            use futures::future::Either;
+
+            let stderr_lines = match stderr {
+                Some(s) => Either::Left(
+                    BufReader::new(s)
+                        .lines()
+                        .map(|line| (log::Level::Error, line)),
+                ),
+                None => Either::Right(futures::stream::empty()),
+            };
+            let stdout_lines = match stdout {
+                Some(s) => Either::Left(
+                    BufReader::new(s)
+                        .lines()
+                        .map(|line| (log::Level::Info, line)),
+                ),
+                None => Either::Right(futures::stream::empty()),
+            };
+            let mut lines = futures::stream::select(stderr_lines, stdout_lines);
+            while let Some((level, Ok(line))) = lines.next().await {
+                log::log!(level, "kernel: {}", line);
+            }
+        })
+        .detach();
+
+    let connection_info = &kernel.0.connection_info;
+    let session_id = Uuid::new_v4().to_string();
+    let mut iopub =
+        runtimelib::create_client_iopub_connection(connection_info, "", &session_id).await?;
+    let peer_identity = runtimelib::peer_identity_for_session(&session_id)?;
+    let shell = runtimelib::create_client_shell_connection_with_identity(
+        connection_info,
+        &session_id,
+        peer_identity,
+    )
+    .await?;
+    let (mut shell_send, mut shell_recv) = shell.split();
+
+    let kernel_info = async {
+        let request: JupyterMessage = KernelInfoRequest {}.into();
+        let msg_id = request.header.msg_id.clone();
+        shell_send.send(request).await?;
+        loop {
+            let message = shell_recv
+                .read()
+                .await
+                .map_err(|error| anyhow!("shell: {error}"))?;
+            if is_response_to(&message, &msg_id)
+                && matches!(message.content, JupyterMessageContent::KernelInfoReply(_))
+            {
+                return anyhow::Ok(());
+            }
+        }
+    };
+    futures::select_biased! {
+        result = kernel_info.fuse() => result?,
+        _ = executor.timer(KERNEL_STARTUP_TIMEOUT).fuse() => {
+            anyhow::bail!("The kernel didn't respond within {KERNEL_STARTUP_TIMEOUT:?}")
+        }
+    }
+
+    let mut failure = None;
+    for (cell_index, cell) in notebook.cells.iter_mut().enumerate() {
+        let nbformat::v4::Cell::Code {
+            source,
+            outputs,
+            execution_count,
+            ..
+        } = cell
+        else {
+            continue;
+        };
+        let code = source.join("");
+        if code.trim().is_empty() {
+            continue;
+        }
+
+        let request: JupyterMessage = ExecuteRequest {
+            code,
+            silent: false,
+            store_history: true,
+            allow_stdin: false,
+            ..Default::default()
+        }
+        .into();
+        let msg_id = request.header.msg_id.clone();
+        let mut execution = CellExecution::default();
+        let run_cell = async {
+            shell_send.send(request).await?;
+            while !execution.is_done() {
+                let message = futures::select_biased! {
+                    message = iopub.read().fuse() => {
+                        message.map_err(|error| anyhow!("iopub: {error}"))?
+                    }
+                    message = shell_recv.read().fuse() => {
+                        message.map_err(|error| anyhow!("shell: {error}"))?
+                    }
+                };
+                if is_response_to(&message, &msg_id) {
+                    execution.handle_message(&message.content);
+                }
+            }
+            anyhow::Ok(())
+        };
+        let result = futures::select_biased! {
+            result = run_cell.fuse() => result,
+            _ = executor.timer(options.cell_timeout).fuse() => {
+                Err(anyhow!("Timed out after {:?}", options.cell_timeout))
+            }
+        };
+
+        *outputs = execution.nbformat_outputs();
+        *execution_count = execution.execution_count;
+        if let Some(message) = result
+            .err()
+            .map(|error| format!("{error:#}"))
+            .or(execution.error)
+        {
+            failure = Some(CellFailure {
+                cell_index,
+                message,
+            });
+            break;
+        }
+    }
+
+    notebook.nbformat = 4;
+    notebook.nbformat_minor = 5;
+    Ok(NotebookRun { notebook, failure })
+}
+
+/// Kills the kernel and removes its connection file when the run ends.
+struct RunnerKernel(LaunchedKernel);
+
+impl Drop for RunnerKernel {
+    fn drop(&mut self) {
+        self.0.process.kill().ok();
+        std::fs::remove_file(&self.0.connection_path).ok();
+    }
+}
+
+fn is_response_to(message: &JupyterMessage, msg_id: &str) -> bool {
+    message
+        .parent_header
+        .as_ref()
+        .is_some_and(|parent| parent.msg_id == msg_id)
+}
+
+/// The outputs and result of executing a single cell, collected from the
+/// kernel's messages in nbformat's JSON representation.
+#[derive(Default)]
+struct CellExecution {
+    outputs: Vec<Value>,
+    clear_before_next_output: bool,
+    execution_count: Option<i32>,
+    error: Option<String>,
+    replied: bool,
+    idle: bool,
+}
+
+impl CellExecution {
+    fn handle_message(&mut self, content: &JupyterMessageContent) {
+        match content {
+            JupyterMessageContent::StreamContent(stream) => {
+                let name = serde_json::to_value(&stream.name).unwrap_or_default();
+                self.clear_if_waiting();
+                match self.outputs.last_mut() {
+                    Some(last) if last["output_type"] == "stream" && last["name"] == name => {
+                        let text = format!(
+                            "{}{}",
+                            last["text"].as_str().unwrap_or_default(),
+                            stream.text
+                        );
+                        last["text"] = Value::String(text);
+                    }
+                    _ => self.outputs.push(json!({
+                        "output_type": "stream",
+                        "name": name,
+                        "text": stream.text,
+                    })),
+                }
+            }
+            JupyterMessageContent::DisplayData(display_data) => self.push_output(json!({
+                "output_type": "display_data",
+                "data": display_data.data,
+                "metadata": {},
+            })),
+            JupyterMessageContent::ExecuteResult(execute_result) => self.push_output(json!({
+                "output_type": "execute_result",
+                "execution_count": execute_result.execution_count,
+                "data": execute_result.data,
+                "metadata": {},
+            })),
+            JupyterMessageContent::ErrorOutput(error) => {
+                self.error
+                    .get_or_insert_with(|| format!("{}: {}", error.ename, error.evalue));
+                self.push_output(json!({
+                    "output_type": "error",
+                    "ename": error.ename,
+                    "evalue": error.evalue,
+                    "traceback": error.traceback,
+                }));
+            }
+            JupyterMessageContent::ClearOutput(options) => {
+                if options.wait {
+                    self.clear_before_next_output = true;
+                } else {
+                    self.outputs.clear();
+                }
+            }
+            JupyterMessageContent::ExecuteReply(reply) => {
+                self.replied = true;
+                self.execution_count = serde_json::to_value(&reply.execution_count)
+                    .ok()
+                    .and_then(|count| count.as_i64())
+                    .map(|count| count as i32);
+                if !matches!(reply.status, ReplyStatus::Ok) {
+                    self.error.get_or_insert_with(|| {
+                        format!("Cell failed with status {:?}", reply.status)
+                    });
+                }
+            }
+            JupyterMessageContent::Status(status)
+                if status.execution_state == ExecutionState::Idle =>
+            {
+                self.idle = true;
+            }
+            _ => {}
+        }
+    }
+
+    /// The reply arrives on the shell channel and the idle status on iopub,
+    /// in either order. The cell is done once both have been seen.
+    fn is_done(&self) -> bool {
+        self.replied && self.idle
+    }
+
+    fn push_output(&mut self, output: Value) {
+        self.clear_if_waiting();
+        self.outputs.push(output);
+    }
+
+    fn clear_if_waiting(&mut self) {
+        if self.clear_before_next_output {
+            self.clear_before_next_output = false;
+            self.outputs.clear();
+        }
+    }
+
+    fn nbformat_outputs(&self) -> Vec<nbformat::v4::Output> {
+        self.outputs
+            .iter()
+            .filter_map(|output| {
+                serde_json::from_value(output.clone())
+                    .map_err(|error| log::warn!("skipping unsupported cell output: {error}"))
+                    .ok()
+            })
+            .collect()
+    }
+}
+
+/// Renders the notebook as Markdown, with code cells in fenced code blocks
+/// followed by their outputs.
+pub fn notebook_to_markdown(notebook: &nbformat::v4::Notebook) -> Result<String> {
+    let notebook = serde_json::to_value(notebook).context("Failed to serialize notebook")?;
+    Ok(markdown_from_json(&notebook))
+}
+
+fn markdown_from_json(notebook: &Value) -> String {
+    let metadata = &notebook["metadata"];
+    let language = metadata["language_info"]["name"]
+        .as_str()
+        .or(metadata["kernelspec"]["language"].as_str())
+        .unwrap_or_default();
+
+    let mut blocks = Vec::new();
+    for cell in notebook["cells"].as_array().into_iter().flatten() {
+        let source = multiline_string(&cell["source"]);
+        match cell["cell_type"].as_str() {
+            Some("code") => {
+                blocks.push(fenced(language, &source));
+                for output in cell["outputs"].as_array().into_iter().flatten() {
+                    blocks.extend(output_to_markdown(output));
+                }
+            }
+            Some("markdown") | Some("raw") => {
+                if !source.trim().is_empty() {
+                    blocks.push(source.trim_end().to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut markdown = blocks.join("\n\n");
+    markdown.push('\n');
+    markdown
+}
+
+fn output_to_markdown(output: &Value) -> Option<String> {
+    match output["output_type"].as_str()? {
+        "stream" => Some(fenced("", &strip_ansi(&multiline_string(&output["text"])))),
+        "error" => {
+            let traceback = output["traceback"]
+                .as_array()
+                .map(|lines| {
+                    lines
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .filter(|traceback| !traceback.is_empty())
+                .unwrap_or_else(|| {
+                    format!(
+                        "{}: {}",
+                        output["ename"].as_str().unwrap_or_default(),
+                        output["evalue"].as_str().unwrap_or_default()
+                    )
+                });
+            Some(fenced("", &strip_ansi(&traceback)))
+        }
+        "display_data" | "execute_result" => {
+            let data = &output["data"];
+            if let Some(markdown) = data.get("text/markdown") {
+                return Some(multiline_string(markdown).trim_end().to_string());
+            }
+            for mime_type in ["image/png", "image/jpeg", "image/gif"] {
+                if let Some(image) = data.get(mime_type) {
+                    let image: String = multiline_string(image)
+                        .chars()
+                        .filter(|c| !c.is_whitespace())
+                        .collect();
+                    return Some(format!("![output](data:{mime_type};base64,{image})"));
+                }
+            }
+            let text = data.get("text/plain")?;
+            Some(fenced("", &strip_ansi(&multiline_string(text))))
+        }
+        _ => None,
+    }
+}
+
+/// nbformat stores text either as a string or as a list of lines.
+fn multiline_string(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+/// Wraps `text` in a code fence longer than any run of backticks inside it.
+fn fenced(info: &str, text: &str) -> String {
+    let longest_run = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{info}\n{}\n{fence}", text.trim_end_matches('\n'))
+}
+
+/// Removes the terminal color codes that kernels use in tracebacks.
+fn strip_ansi(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            result.push(c);
+        } else if chars.clone().next() == Some('[') {
+            chars.next();
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use runtimelib::{ClearOutput, ErrorOutput, Status, Stdio, StreamContent};
+
+    fn stream(text: &str) -> JupyterMessageContent {
+        JupyterMessageContent::StreamContent(StreamContent {
+            name: Stdio::Stdout,
+            text: text.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_cell_execution_outputs() {
+        let mut execution = CellExecution::default();
+        execution.handle_message(&stream("progress 1\n"));
+        execution.handle_message(&JupyterMessageContent::ClearOutput(ClearOutput {
+            wait: true,
+        }));
+        assert_eq!(execution.outputs.len(), 1);
+
+        execution.handle_message(&stream("hello "));
+        execution.handle_message(&stream("world\n"));
+        execution.handle_message(&JupyterMessageContent::ErrorOutput(ErrorOutput {
+            ename: "ZeroDivisionError".to_string(),
+            evalue: "division by zero".to_string(),
+            traceback: vec!["\x1b[0;31mZeroDivisionError\x1b[0m: division by zero".to_string()],
+        }));
+        execution.handle_message(&JupyterMessageContent::Status(Status {
+            execution_state: ExecutionState::Idle,
+        }));
+
+        assert!(execution.idle);
+        assert!(!execution.is_done());
+        assert_eq!(
+            execution.error.as_deref(),
+            Some("ZeroDivisionError: division by zero")
+        );
+        assert_eq!(
+            execution.outputs,
+            [
+                json!({"output_type": "stream", "name": "stdout", "text": "hello world\n"}),
+                json!({
+                    "output_type": "error",
+                    "ename": "ZeroDivisionError",
+                    "evalue": "division by zero",
+                    "traceback": ["\x1b[0;31mZeroDivisionError\x1b[0m: division by zero"],
+                }),
+            ]
+        );
+        assert_eq!(execution.nbformat_outputs().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_and_export_notebook() {
+        let notebook = parse_notebook(
+            &json!({
+                "nbformat": 4,
+                "nbformat_minor": 4,
+                "metadata": {
+                    "kernelspec": {"name": "python3", "display_name": "Python 3", "language": "python"}
+                },
+                "cells": [
+                    {"cell_type": "markdown", "metadata": {}, "source": ["# Title\n", "Some text"]},
+                    {
+                        "cell_type": "code",
+                        "metadata": {},
+                        "execution_count": 1,
+                        "source": "print('```')\n1 / 0",
+                        "outputs": [
+                            {"output_type": "stream", "name": "stdout", "text": ["```\n"]},
+                            {
+                                "output_type": "error",
+                                "ename": "ZeroDivisionError",
+                                "evalue": "division by zero",
+                                "traceback": ["\x1b[0;31mZeroDivisionError\x1b[0m: division by zero"]
+                            }
+                        ]
+                    },
+                    {
+                        "cell_type": "code",
+                        "metadata": {},
+                        "execution_count": 2,
+                        "source": "2 + 2",
+                        "outputs": [
+                            {
+                                "output_type": "execute_result",
+                                "execution_count": 2,
+                                "metadata": {},
+                                "data": {"text/plain": "4"}
+                            }
+                        ]
+                    }
+                ]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(notebook.cells.len(), 3);
+
+        assert_eq!(
+            notebook_to_markdown(&notebook).unwrap(),
+            indoc::indoc! {"
+                # Title
+                Some text
+
+                ````python
+                print('```')
+                1 / 0
+                ````
+
+                ````
+                ```
+                ````
+
+                ```
+                ZeroDivisionError: division by zero
+                ```
+
+                ```python
+                2 + 2
+                ```
+
+                ```
+                4
+                ```
+            "}
+        );
+    }
+}
@@ -17,6 +17,7 @@
 //! - Images (PNG and JPEG)
 //! - Tables
 //! - Error messages
+//! - Jupyter widgets
 //!
 //! ## Clipboard Support
 //!
@@ -46,7 +47,7 @@ use image::ImageView;
 mod markdown;
 use markdown::MarkdownView;
 
-mod table;
+pub(crate) mod table;
 use table::TableView;
 
 mod json;
@@ -61,7 +62,11 @@ pub(crate) mod user_error;
 use user_error::ErrorView;
 use workspace::Workspace;
 
+pub(crate) mod widget;
+use widget::WidgetView;
+
 use crate::repl_settings::ReplSettings;
+use crate::widgets::{WidgetStore, widget_model_id};
 use settings::Settings;
 
 /// When deciding what to render from a collection of mediatypes, we need to rank them in order of importance
@@ -136,6 +141,10 @@ pub enum Output {
         content: Entity<JsonView>,
         display_id: Option<String>,
     },
+    Widget {
+        content: Entity<WidgetView>,
+        display_id: Option<String>,
+    },
     ClearOutputWaitMarker,
 }
 
@@ -173,7 +182,8 @@ impl Output {
             Output::Image { .. }
             | Output::Markdown { .. }
             | Output::Table { .. }
-            | Output::Json { .. } => None,
+            | Output::Json { .. }
+            | Output::Widget { .. } => None,
             Output::Message(_) => None,
             Output::ClearOutputWaitMarker => None,
         }
@@ -262,6 +272,7 @@ impl Output {
             Self::Message(message) => Some(div().child(message.clone()).into_any_element()),
             Self::Table { content, .. } => Some(content.clone().into_any_element()),
             Self::Json { content, .. } => Some(content.clone().into_any_element()),
+            Self::Widget { content, .. } => Some(content.clone().into_any_element()),
             Self::ErrorOutput(error_view) => error_view.render(window, cx),
             Self::ClearOutputWaitMarker => None,
         }
@@ -370,6 +381,7 @@ impl Output {
                         .into_any_element(),
                 ),
                 Self::Message(_) => None,
+                Self::Widget { .. } => None,
                 Self::Table { content, .. } => {
                     Self::render_output_controls(content.clone(), workspace, window, cx)
                 }
@@ -387,10 +399,34 @@ impl Output {
             Output::Table { display_id, .. } => display_id.clone(),
             Output::Markdown { display_id, .. } => display_id.clone(),
             Output::Json { display_id, .. } => display_id.clone(),
+            Output::Widget { display_id, .. } => display_id.clone(),
             Output::ClearOutputWaitMarker => None,
         }
     }
 
+    /// Like [`Output::new`], but renders widget views natively when their model
+    /// is in the given store.
+    pub fn for_bundle(
+        data: &MimeBundle,
+        display_id: Option<String>,
+        widgets: Option<&Entity<WidgetStore>>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Self {
+        if let Some(widgets) = widgets
+            && let Some(model_id) = widget_model_id(data)
+            && widgets.read(cx).model(&model_id).is_some()
+        {
+            let widgets = widgets.clone();
+            return Output::Widget {
+                content: cx.new(|cx| WidgetView::new(widgets, model_id, window, cx)),
+                display_id,
+            };
+        }
+
+        Output::new(data, display_id, window, cx)
+    }
+
     pub fn new(
         data: &MimeBundle,
         display_id: Option<String>,
@@ -484,6 +520,7 @@ pub struct ExecutionView {
     pub outputs: Vec<Output>,
     pub status: ExecutionStatus,
     pending_input: Option<PendingInput>,
+    widgets: Option<Entity<WidgetStore>>,
 }
 
 impl EventEmitter<ExecutionViewFinishedEmpty> for ExecutionView {}
@@ -501,9 +538,26 @@ impl ExecutionView {
             outputs: Default::default(),
             status,
             pending_input: None,
+            widgets: None,
         }
     }
 
+    /// Renders widget outputs natively, using the models of the given store.
+    pub fn with_widgets(mut self, widgets: Entity<WidgetStore>) -> Self {
+        self.widgets = Some(widgets);
+        self
+    }
+
+    fn output_for_bundle(
+        &self,
+        data: &MimeBundle,
+        display_id: Option<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Output {
+        Output::for_bundle(data, display_id, self.widgets.as_ref(), window, cx)
+    }
+
     fn submit_input(&mut self, _window: &mut Window, cx: &mut Context<Self>) {
         if let Some(pending_input) = self.pending_input.take() {
             let value = pending_input.editor.read(cx).text(cx);
@@ -561,13 +615,13 @@ impl ExecutionView {
         cx: &mut Context<Self>,
     ) {
         let output: Output = match message {
-            JupyterMessageContent::ExecuteResult(result) => Output::new(
+            JupyterMessageContent::ExecuteResult(result) => self.output_for_bundle(
                 &result.data,
                 result.transient.as_ref().and_then(|t| t.display_id.clone()),
                 window,
                 cx,
             ),
-            JupyterMessageContent::DisplayData(result) => Output::new(
+            JupyterMessageContent::DisplayData(result) => self.output_for_bundle(
                 &result.data,
                 result.transient.as_ref().and_then(|t| t.display_id.clone()),
                 window,
@@ -668,14 +722,13 @@ impl ExecutionView {
     ) {
         let mut any = false;
 
-        self.outputs.iter_mut().for_each(|output| {
-            if let Some(other_display_id) = output.display_id().as_ref()
-                && other_display_id == display_id
-            {
-                *output = Output::new(data, Some(display_id.to_owned()), window, cx);
+        for ix in 0..self.outputs.len() {
+            if self.outputs[ix].display_id().as_deref() == Some(display_id) {
+                self.outputs[ix] =
+                    self.output_for_bundle(data, Some(display_id.to_owned()), window, cx);
                 any = true;
             }
-        });
+        }
 
         if any {
             cx.notify();
@@ -75,7 +75,7 @@ pub struct TableView {
     cached_clipboard_content: ClipboardItem,
 }
 
-fn cell_content(row: &Value, field: &str) -> String {
+pub(crate) fn cell_content(row: &Value, field: &str) -> String {
     match row.get(field) {
         Some(Value::String(s)) => s.clone(),
         Some(Value::Number(n)) => n.to_string(),
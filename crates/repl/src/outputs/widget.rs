@@ -0,0 +1,491 @@
+//! Native renderings of the core `ipywidgets` models.
+//!
+//! A [`WidgetView`] renders one model from a [`WidgetStore`] and writes user
+//! interactions back to it, which syncs them to the kernel.
+
+use collections::HashMap;
+use editor::{Editor, EditorEvent};
+use gpui::{
+    Bounds, DragMoveEvent, Entity, EntityId, MouseButton, MouseDownEvent, Pixels, Subscription,
+    canvas,
+};
+use serde_json::{Map, Value, json};
+use ui::{Checkbox, ContextMenu, DropdownMenu, ProgressBar, TintColor, Tooltip, prelude::*};
+
+use crate::widgets::{WidgetState, WidgetStore};
+
+struct DraggedSlider(EntityId);
+
+pub struct WidgetView {
+    store: Entity<WidgetStore>,
+    model_id: String,
+    children: HashMap<String, Entity<WidgetView>>,
+    text_editor: Option<Entity<Editor>>,
+    slider_bounds: Bounds<Pixels>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl WidgetView {
+    pub fn new(
+        store: Entity<WidgetStore>,
+        model_id: String,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let subscription = cx.observe_in(&store, window, |this, _, window, cx| {
+            this.sync_text_editor(window, cx);
+            cx.notify();
+        });
+
+        Self {
+            store,
+            model_id,
+            children: HashMap::default(),
+            text_editor: None,
+            slider_bounds: Bounds::default(),
+            _subscriptions: vec![subscription],
+        }
+    }
+
+    fn set_state(&self, key: &str, value: Value, window: &mut Window, cx: &mut App) {
+        let mut state = Map::new();
+        state.insert(key.to_string(), value);
+        self.store.update(cx, |store, cx| {
+            store.set_state(&self.model_id, state, window, cx);
+        });
+    }
+
+    fn child_view(
+        &mut self,
+        model_id: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<Self> {
+        self.children
+            .entry(model_id.to_string())
+            .or_insert_with(|| {
+                cx.new(|cx| Self::new(self.store.clone(), model_id.to_string(), window, cx))
+            })
+            .clone()
+    }
+
+    fn text_editor(
+        &mut self,
+        model: &WidgetState,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<Editor> {
+        if let Some(editor) = &self.text_editor {
+            return editor.clone();
+        }
+
+        let value = model.get_str("value").unwrap_or_default().to_string();
+        let placeholder = model.get_str("placeholder").unwrap_or_default().to_string();
+        let multi_line = model.model_name() == "TextareaModel";
+        let masked = model.model_name() == "PasswordModel";
+        let continuous_update = model.get_bool("continuous_update");
+
+        let editor = cx.new(|cx| {
+            let mut editor = if multi_line {
+                Editor::auto_height(1, 8, window, cx)
+            } else {
+                Editor::single_line(window, cx)
+            };
+            editor.set_text(value, window, cx);
+            editor.set_placeholder_text(&placeholder, window, cx);
+            if masked {
+                editor.set_masked(true, cx);
+            }
+            editor
+        });
+
+        let subscription = cx.subscribe_in(
+            &editor,
+            window,
+            move |this, editor, event: &EditorEvent, window, cx| match event {
+                EditorEvent::BufferEdited if continuous_update => {
+                    this.submit_text(editor, window, cx)
+                }
+                EditorEvent::Blurred => this.submit_text(editor, window, cx),
+                _ => {}
+            },
+        );
+        self._subscriptions.push(subscription);
+        self.text_editor = Some(editor.clone());
+        editor
+    }
+
+    fn submit_text(&self, editor: &Entity<Editor>, window: &mut Window, cx: &mut App) {
+        let text = editor.read(cx).text(cx);
+        let unchanged = self
+            .store
+            .read(cx)
+            .model(&self.model_id)
+            .is_some_and(|model| model.state().get_str("value") == Some(text.as_str()));
+        if !unchanged {
+            self.set_state("value", Value::String(text), window, cx);
+        }
+    }
+
+    /// Applies `value` updates from the kernel to the text input.
+    fn sync_text_editor(&self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(editor) = &self.text_editor else {
+            return;
+        };
+        let Some(value) = self
+            .store
+            .read(cx)
+            .model(&self.model_id)
+            .and_then(|model| model.state().get_str("value"))
+            .map(ToString::to_string)
+        else {
+            return;
+        };
+        if editor.read(cx).text(cx) != value {
+            editor.update(cx, |editor, cx| editor.set_text(value, window, cx));
+        }
+    }
+
+    fn set_slider_position(
+        &self,
+        x: Pixels,
+        bounds: Bounds<Pixels>,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let Some(model) = self
+            .store
+            .read(cx)
+            .model(&self.model_id)
+            .map(|model| model.state())
+        else {
+            return;
+        };
+        if model.get_bool("disabled") || bounds.size.width <= Pixels::ZERO {
+            return;
+        }
+
+        let min = model.get_f64("min").unwrap_or(0.);
+        let max = model.get_f64("max").unwrap_or(100.);
+        let step = model.get_f64("step").filter(|step| *step > 0.);
+        let fraction = ((x - bounds.left()) / bounds.size.width).clamp(0., 1.) as f64;
+        let mut value = min + fraction * (max - min);
+        if let Some(step) = step {
+            value = min + ((value - min) / step).round() * step;
+        }
+        let value = value.clamp(min, max);
+
+        let value = if model.model_name() == "IntSliderModel" {
+            json!(value.round() as i64)
+        } else {
+            json!(value)
+        };
+        if model.get("value") != Some(&value) {
+            self.set_state("value", value, window, cx);
+        }
+    }
+
+    fn render_description(model: &WidgetState) -> Option<Label> {
+        model
+            .get_str("description")
+            .filter(|description| !description.is_empty())
+            .map(|description| Label::new(description.to_string()))
+    }
+
+    fn render_slider(&self, model: &WidgetState, cx: &mut Context<Self>) -> AnyElement {
+        let min = model.get_f64("min").unwrap_or(0.);
+        let max = model.get_f64("max").unwrap_or(100.);
+        let value = model.get_f64("value").unwrap_or(min);
+        let fraction = if max > min {
+            ((value - min) / (max - min)).clamp(0., 1.) as f32
+        } else {
+            0.
+        };
+        let readout = if model.model_name() == "IntSliderModel" {
+            format!("{}", value.round() as i64)
+        } else {
+            let precision = model
+                .get_str("readout_format")
+                .and_then(|format| format.strip_prefix('.')?.strip_suffix('f')?.parse().ok())
+                .unwrap_or(2);
+            format!("{value:.precision$}")
+        };
+        let disabled = model.get_bool("disabled");
+        let view_id = cx.entity_id();
+        let view = cx.entity();
+
+        let track = div()
+            .id("slider-track")
+            .relative()
+            .w_48()
+            .h_1p5()
+            .rounded_full()
+            .bg(cx.theme().colors().element_background)
+            .when(!disabled, |track| track.cursor_pointer())
+            .child(
+                div()
+                    .h_full()
+                    .rounded_full()
+                    .bg(cx.theme().colors().text_accent)
+                    .when(disabled, |fill| fill.opacity(0.5))
+                    .w(relative(fraction)),
+            )
+            .child(
+                canvas(
+                    move |bounds, _, cx| view.update(cx, |view, _| view.slider_bounds = bounds),
+                    |_, _, _, _| {},
+                )
+                .absolute()
+                .size_full(),
+            )
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|this, event: &MouseDownEvent, window, cx| {
+                    this.set_slider_position(event.position.x, this.slider_bounds, window, cx);
+                }),
+            )
+            .on_drag(DraggedSlider(view_id), |_, _, _, cx| {
+                cx.new(|_| gpui::Empty)
+            })
+            .on_drag_move(cx.listener(
+                move |this, event: &DragMoveEvent<DraggedSlider>, window, cx| {
+                    if event.drag(cx).0 == view_id {
+                        this.set_slider_position(event.event.position.x, event.bounds, window, cx);
+                    }
+                },
+            ));
+
+        h_flex()
+            .gap_2()
+            .children(Self::render_description(model))
+            .child(track)
+            .when(model.get("readout") != Some(&Value::Bool(false)), |row| {
+                row.child(Label::new(readout).color(Color::Muted))
+            })
+            .into_any_element()
+    }
+
+    fn render_progress(&self, model: &WidgetState, cx: &mut Context<Self>) -> AnyElement {
+        let min = model.get_f64("min").unwrap_or(0.);
+        let max = model.get_f64("max").unwrap_or(100.);
+        let value = model.get_f64("value").unwrap_or(min);
+        let color = match model.get_str("bar_style").unwrap_or_default() {
+            "success" => cx.theme().status().success,
+            "warning" => cx.theme().status().warning,
+            "danger" => cx.theme().status().error,
+            _ => cx.theme().status().info,
+        };
+
+        h_flex()
+            .gap_2()
+            .children(Self::render_description(model))
+            .child(
+                div().w_48().child(
+                    ProgressBar::new("progress", (value - min) as f32, (max - min) as f32, cx)
+                        .fg_color(color),
+                ),
+            )
+            .into_any_element()
+    }
+
+    fn render_checkbox(&self, model: &WidgetState, cx: &mut Context<Self>) -> AnyElement {
+        let view = cx.entity().downgrade();
+        Checkbox::new("checkbox", model.get_bool("value").into())
+            .label(model.get_str("description").unwrap_or_default().to_string())
+            .disabled(model.get_bool("disabled"))
+            .on_click(move |state, window, cx| {
+                let selected = state.selected();
+                view.update(cx, |this, cx| {
+                    this.set_state("value", Value::Bool(selected), window, cx)
+                })
+                .ok();
+            })
+            .into_any_element()
+    }
+
+    fn render_dropdown(
+        &self,
+        model: &WidgetState,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let labels: Vec<String> = model
+            .get("_options_labels")
+            .and_then(Value::as_array)
+            .map(|labels| {
+                labels
+                    .iter()
+                    .filter_map(|label| Some(label.as_str()?.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let selected_index = model
+            .get("index")
+            .and_then(Value::as_u64)
+            .map(|index| index as usize);
+        let selected_label = selected_index
+            .and_then(|index| labels.get(index).cloned())
+            .unwrap_or_default();
+
+        let view = cx.entity().downgrade();
+        let menu = ContextMenu::build(window, cx, move |mut menu, _, _| {
+            for (index, label) in labels.into_iter().enumerate() {
+                let view = view.clone();
+                menu = menu.toggleable_entry(
+                    label,
+                    selected_index == Some(index),
+                    IconPosition::Start,
+                    None,
+                    move |window, cx| {
+                        view.update(cx, |this, cx| {
+                            this.set_state("index", json!(index), window, cx)
+                        })
+                        .ok();
+                    },
+                );
+            }
+            menu
+        });
+
+        h_flex()
+            .gap_2()
+            .children(Self::render_description(model))
+            .child(
+                DropdownMenu::new("dropdown", selected_label, menu)
+                    .trigger_size(ButtonSize::Compact)
+                    .disabled(model.get_bool("disabled")),
+            )
+            .into_any_element()
+    }
+
+    fn render_text(
+        &mut self,
+        model: &WidgetState,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let editor = self.text_editor(model, window, cx);
+        editor.update(cx, |editor, _| {
+            editor.set_read_only(model.get_bool("disabled"))
+        });
+
+        h_flex()
+            .gap_2()
+            .items_start()
+            .children(Self::render_description(model))
+            .child(
+                div()
+                    .w_64()
+                    .px_2()
+                    .py_1()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .rounded_md()
+                    .child(editor),
+            )
+            .into_any_element()
+    }
+
+    fn render_button(&self, model: &WidgetState, cx: &mut Context<Self>) -> AnyElement {
+        let style = match model.get_str("button_style").unwrap_or_default() {
+            "primary" | "info" => ButtonStyle::Tinted(TintColor::Accent),
+            "success" => ButtonStyle::Tinted(TintColor::Success),
+            "warning" => ButtonStyle::Tinted(TintColor::Warning),
+            "danger" => ButtonStyle::Tinted(TintColor::Error),
+            _ => ButtonStyle::Filled,
+        };
+        let tooltip = model
+            .get_str("tooltip")
+            .filter(|tooltip| !tooltip.is_empty())
+            .map(ToString::to_string);
+
+        Button::new(
+            "button",
+            model.get_str("description").unwrap_or_default().to_string(),
+        )
+        .style(style)
+        .disabled(model.get_bool("disabled"))
+        .when_some(tooltip, |button, tooltip| {
+            button.tooltip(Tooltip::text(tooltip))
+        })
+        .on_click(cx.listener(|this, _, _, cx| {
+            this.store.update(cx, |store, cx| {
+                store.send_custom(&this.model_id, json!({ "event": "click" }), cx);
+            });
+        }))
+        .into_any_element()
+    }
+
+    fn render_box(
+        &mut self,
+        model: &WidgetState,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let children = model
+            .children()
+            .iter()
+            .map(|model_id| self.child_view(model_id, window, cx))
+            .collect::<Vec<_>>();
+
+        if model.model_name() == "VBoxModel" {
+            v_flex().gap_1().children(children).into_any_element()
+        } else {
+            h_flex()
+                .gap_2()
+                .items_start()
+                .children(children)
+                .into_any_element()
+        }
+    }
+
+    fn render_output(&self, window: &mut Window, cx: &mut Context<Self>) -> AnyElement {
+        let outputs = self.store.update(cx, |store, cx| {
+            store
+                .model(&self.model_id)
+                .map(|model| {
+                    model
+                        .outputs()
+                        .iter()
+                        .filter_map(|output| output.content(window, cx))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        });
+
+        v_flex().w_full().children(outputs).into_any_element()
+    }
+}
+
+impl Render for WidgetView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(model) = self
+            .store
+            .read(cx)
+            .model(&self.model_id)
+            .map(|model| model.state().clone())
+        else {
+            return div().into_any_element();
+        };
+        let model = &model;
+
+        match model.model_name() {
+            "IntSliderModel" | "FloatSliderModel" => self.render_slider(model, cx),
+            "IntProgressModel" | "FloatProgressModel" => self.render_progress(model, cx),
+            "CheckboxModel" => self.render_checkbox(model, cx),
+            "DropdownModel" => self.render_dropdown(model, window, cx),
+            "TextModel" | "TextareaModel" | "PasswordModel" => self.render_text(model, window, cx),
+            "ButtonModel" => self.render_button(model, cx),
+            "BoxModel" | "HBoxModel" | "VBoxModel" => self.render_box(model, window, cx),
+            "OutputModel" => self.render_output(window, cx),
+            "LabelModel" => Label::new(model.get_str("value").unwrap_or_default().to_string())
+                .into_any_element(),
+            model_name => Label::new(format!(
+                "{} widgets are not supported",
+                model_name.trim_end_matches("Model")
+            ))
+            .color(Color::Muted)
+            .into_any_element(),
+        }
+    }
+}
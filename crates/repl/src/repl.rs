@@ -8,6 +8,8 @@ mod repl_sessions_ui;
 mod repl_settings;
 mod repl_store;
 mod session;
+mod variable_explorer;
+mod widgets;
 
 use std::{sync::Arc, time::Duration};
 
@@ -25,15 +27,23 @@ pub use crate::repl_sessions_ui::{
 pub use crate::repl_settings::ReplSettings;
 pub use crate::repl_store::ReplStore;
 pub use crate::session::Session;
+pub use crate::variable_explorer::{DataViewer, VariableExplorerPanel};
 
 pub const KERNEL_DOCS_URL: &str = "https://zed.dev/docs/repl#changing-kernels";
 
 pub fn init(fs: Arc<dyn Fs>, cx: &mut App) {
     set_dispatcher(zed_dispatcher(cx));
     repl_sessions_ui::init(cx);
+    variable_explorer::init(cx);
     ReplStore::init(fs, cx);
 }
 
+/// Sets up kernel connections for [`notebook::run_notebook`] when Zed runs
+/// without a window, in place of [`init`].
+pub fn init_headless(cx: &mut App) {
+    set_dispatcher(zed_dispatcher(cx));
+}
+
 fn zed_dispatcher(cx: &mut App) -> impl Dispatcher {
     struct ZedDispatcher {
         dispatcher: Arc<dyn PlatformDispatcher>,
@@ -58,8 +58,9 @@ pub fn assign_kernelspec(
                 let store = store.clone();
                 move |_this, _session, event, cx| match event {
                     SessionEvent::Shutdown(shutdown_event) => {
-                        store.update(cx, |store, _cx| {
+                        store.update(cx, |store, cx| {
                             store.remove_session(shutdown_event.entity_id());
+                            cx.notify();
                         });
                     }
                 }
@@ -68,8 +69,9 @@ pub fn assign_kernelspec(
         })
         .ok();
 
-    store.update(cx, |store, _cx| {
+    store.update(cx, |store, cx| {
         store.insert_session(weak_editor.entity_id(), session.clone());
+        cx.notify();
     });
 
     Ok(())
@@ -240,8 +242,9 @@ pub fn run(
                     let store = store.clone();
                     move |_this, _session, event, cx| match event {
                         SessionEvent::Shutdown(shutdown_event) => {
-                            store.update(cx, |store, _cx| {
+                            store.update(cx, |store, cx| {
                                 store.remove_session(shutdown_event.entity_id());
+                                cx.notify();
                             });
                         }
                     }
@@ -249,8 +252,9 @@ pub fn run(
                 .detach();
             });
 
-            store.update(cx, |store, _cx| {
+            store.update(cx, |store, cx| {
                 store.insert_session(editor.entity_id(), session.clone());
+                cx.notify();
             });
 
             session
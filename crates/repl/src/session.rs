@@ -3,14 +3,15 @@ use crate::setup_editor_session_actions;
 use crate::{
     KernelStatus,
     kernels::{
-        Kernel, KernelSession, KernelSpecification, NativeRunningKernel, RemoteRunningKernel,
-        SshRunningKernel, WslRunningKernel,
+        Kernel, KernelExecutionFinished, KernelQueries, KernelSession, KernelSpecification,
+        NativeRunningKernel, RemoteRunningKernel, SshRunningKernel, WslRunningKernel,
     },
     outputs::{
         ExecutionStatus, ExecutionView, ExecutionViewFinishedEmpty, ExecutionViewFinishedSmall,
         InputReplyEvent,
     },
     repl_settings::ReplSettings,
+    widgets::{WidgetCommMessage, WidgetStore},
 };
 use anyhow::Context as _;
 use collections::{HashMap, HashSet};
@@ -35,7 +36,7 @@ use gpui::{
 use language::Point;
 use project::Fs;
 use runtimelib::{
-    ExecuteRequest, ExecutionState, InputReply, InterruptRequest, JupyterMessage,
+    CommId, CommMsg, ExecuteRequest, ExecutionState, InputReply, InterruptRequest, JupyterMessage,
     JupyterMessageContent, KernelInfoRequest, ReplyStatus, ShutdownRequest,
 };
 use settings::Settings as _;
@@ -53,6 +54,8 @@ pub struct Session {
     blocks: HashMap<String, EditorBlock>,
     result_inlays: HashMap<String, (InlayId, Range<Anchor>, usize)>,
     next_inlay_id: usize,
+    widgets: Entity<WidgetStore>,
+    kernel_queries: KernelQueries,
 
     _subscriptions: Vec<Subscription>,
 }
@@ -72,13 +75,15 @@ impl EditorBlock {
         editor: WeakEntity<Editor>,
         code_range: Range<Anchor>,
         status: ExecutionStatus,
+        widgets: Entity<WidgetStore>,
         on_close: CloseBlockFn,
         cx: &mut Context<Session>,
     ) -> anyhow::Result<Self> {
         let editor = editor.upgrade().context("editor is not open")?;
         let workspace = editor.read(cx).workspace().context("workspace dropped")?;
 
-        let execution_view = cx.new(|cx| ExecutionView::new(status, workspace.downgrade(), cx));
+        let execution_view = cx
+            .new(|cx| ExecutionView::new(status, workspace.downgrade(), cx).with_widgets(widgets));
 
         let (block_id, invalidation_anchor) = editor.update(cx, |editor, cx| {
             let buffer = editor.buffer().clone();
@@ -250,6 +255,17 @@ impl Session {
             })
             .ok();
 
+        let widgets = cx.new(|_| WidgetStore::new());
+        let widgets_subscription =
+            cx.subscribe(&widgets, |session, _, event: &WidgetCommMessage, cx| {
+                let message: JupyterMessage = CommMsg {
+                    comm_id: CommId(event.comm_id.clone()),
+                    data: event.data.clone(),
+                }
+                .into();
+                session.send(message, cx).ok();
+            });
+
         let mut session = Self {
             fs,
             editor,
@@ -257,8 +273,10 @@ impl Session {
             blocks: HashMap::default(),
             result_inlays: HashMap::default(),
             next_inlay_id: 0,
+            widgets,
+            kernel_queries: KernelQueries::default(),
             kernel_specification,
-            _subscriptions: vec![subscription],
+            _subscriptions: vec![subscription, widgets_subscription],
         };
 
         session.start_kernel(window, cx);
@@ -486,6 +504,21 @@ impl Session {
         anyhow::Ok(())
     }
 
+    /// Runs `code` in the kernel without showing any output, resolving to
+    /// whatever it prints to stdout.
+    pub fn query_kernel(
+        &mut self,
+        code: String,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<String>> {
+        let Kernel::RunningKernel(kernel) = &mut self.kernel else {
+            return Task::ready(Err(anyhow::anyhow!("kernel is not running")));
+        };
+        let (message, response) = self.kernel_queries.request(code);
+        kernel.request_tx().try_send(message).ok();
+        cx.background_spawn(async move { response.await.context("kernel query was cancelled")? })
+    }
+
     fn send_stdin_reply(
         &mut self,
         value: String,
@@ -721,6 +754,7 @@ impl Session {
             self.editor.clone(),
             anchor_range.clone(),
             status,
+            self.widgets.clone(),
             on_close,
             cx,
         ) else {
@@ -856,6 +890,8 @@ impl Session {
 
                     this.update(cx, |session, cx| {
                         session.clear_outputs(cx);
+                        session.widgets.update(cx, |widgets, cx| widgets.clear(cx));
+                        session.kernel_queries.clear();
                         session.kernel(Kernel::Shutdown, cx);
                         cx.notify();
                     })
@@ -898,6 +934,8 @@ impl Session {
                     this.update_in(cx, |session, window, cx| {
                         // TODO: Differentiate between restart and restart+clear-outputs
                         session.clear_outputs(cx);
+                        session.widgets.update(cx, |widgets, cx| widgets.clear(cx));
+                        session.kernel_queries.clear();
                         session.start_kernel(window, cx);
                     })
                     .ok();
@@ -906,6 +944,8 @@ impl Session {
             }
             _ => {
                 self.clear_outputs(cx);
+                self.widgets.update(cx, |widgets, cx| widgets.clear(cx));
+                self.kernel_queries.clear();
                 self.start_kernel(window, cx);
             }
         }
@@ -918,6 +958,7 @@ pub enum SessionEvent {
 }
 
 impl EventEmitter<SessionEvent> for Session {}
+impl EventEmitter<KernelExecutionFinished> for Session {}
 
 impl Render for Session {
     fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
@@ -977,6 +1018,17 @@ impl Render for Session {
 
 impl KernelSession for Session {
     fn route(&mut self, message: &JupyterMessage, window: &mut Window, cx: &mut Context<Self>) {
+        if self.widgets.update(cx, |widgets, cx| {
+            widgets.handle_comm_message(message, window, cx)
+        }) {
+            return;
+        }
+
+        // Zed's own queries never produce visible output
+        if self.kernel_queries.handle_message(message) {
+            return;
+        }
+
         let parent_message_id = match message.parent_header.as_ref() {
             Some(header) => &header.msg_id,
             None => return,
@@ -985,6 +1037,9 @@ impl KernelSession for Session {
         match &message.content {
             JupyterMessageContent::Status(status) => {
                 self.kernel.set_execution_state(&status.execution_state);
+                if status.execution_state == ExecutionState::Idle {
+                    cx.emit(KernelExecutionFinished);
+                }
 
                 telemetry::event!(
                     "Kernel Status Changed",
@@ -1016,6 +1071,13 @@ impl KernelSession for Session {
             _ => {}
         }
 
+        // Output widgets capture the outputs of the execution they are active in
+        if self.widgets.update(cx, |widgets, cx| {
+            widgets.capture_output(message, window, cx)
+        }) {
+            return;
+        }
+
         if let Some(block) = self.blocks.get_mut(parent_message_id) {
             block.handle_message(message, window, cx);
         }
@@ -0,0 +1,516 @@
+//! # Variable Explorer
+//!
+//! A dock panel listing the variables defined in the kernel behind the active
+//! notebook or REPL editor. Variables are collected by running a small
+//! introspection snippet in the kernel, refreshed whenever the kernel finishes
+//! executing, and tabular ones can be opened in a [`DataViewer`].
+
+mod data_viewer;
+mod introspection;
+
+use anyhow::{Result, anyhow};
+use editor::Editor;
+use gpui::{
+    Action, AsyncWindowContext, Entity, EventEmitter, FocusHandle, Focusable, Subscription, Task,
+    WeakEntity, actions,
+};
+use ui::{ListItem, Tooltip, prelude::*};
+use workspace::{
+    ItemHandle, Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+};
+
+pub use data_viewer::DataViewer;
+
+use crate::kernels::{KernelExecutionFinished, KernelSpecification, KernelStatus};
+use crate::notebook::NotebookEditor;
+use crate::{ReplStore, Session};
+
+use introspection::Variable;
+
+/// The number of rows fetched when opening a variable in the data viewer.
+const DATA_VIEWER_ROW_LIMIT: usize = 1000;
+
+actions!(
+    variable_explorer,
+    [
+        /// Toggles focus on the variable explorer panel.
+        ToggleFocus,
+        /// Lists the variables of the active kernel again.
+        Refresh
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+            workspace.toggle_panel_focus::<VariableExplorerPanel>(window, cx);
+        });
+    })
+    .detach();
+}
+
+/// The kernel that the variable explorer inspects.
+#[derive(Clone, PartialEq)]
+enum KernelTarget {
+    Session(WeakEntity<Session>),
+    Notebook(WeakEntity<NotebookEditor>),
+}
+
+impl KernelTarget {
+    fn kernel_specification(&self, cx: &App) -> Option<KernelSpecification> {
+        match self {
+            KernelTarget::Session(session) => session
+                .upgrade()
+                .map(|session| session.read(cx).kernel_specification.clone()),
+            KernelTarget::Notebook(notebook) => notebook
+                .upgrade()
+                .and_then(|notebook| notebook.read(cx).kernel_specification().cloned()),
+        }
+    }
+
+    fn kernel_status(&self, cx: &App) -> Option<KernelStatus> {
+        match self {
+            KernelTarget::Session(session) => session
+                .upgrade()
+                .map(|session| KernelStatus::from(&session.read(cx).kernel)),
+            KernelTarget::Notebook(notebook) => notebook
+                .upgrade()
+                .map(|notebook| KernelStatus::from(notebook.read(cx).kernel())),
+        }
+    }
+
+    fn query(&self, code: String, cx: &mut App) -> Task<Result<String>> {
+        let task = match self {
+            KernelTarget::Session(session) => session
+                .upgrade()
+                .map(|session| session.update(cx, |session, cx| session.query_kernel(code, cx))),
+            KernelTarget::Notebook(notebook) => notebook.upgrade().map(|notebook| {
+                notebook.update(cx, |notebook, cx| notebook.query_kernel(code, cx))
+            }),
+        };
+        task.unwrap_or_else(|| Task::ready(Err(anyhow!("kernel was closed"))))
+    }
+}
+
+pub struct VariableExplorerPanel {
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    position: DockPosition,
+    width: Option<Pixels>,
+    active: bool,
+    target: Option<KernelTarget>,
+    variables: Vec<Variable>,
+    error: Option<SharedString>,
+    /// Set when the kernel changed while the panel was hidden.
+    stale: bool,
+    refresh_task: Option<Task<()>>,
+    _target_subscriptions: Vec<Subscription>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl VariableExplorerPanel {
+    pub fn load(
+        workspace: WeakEntity<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<Entity<Self>>> {
+        cx.spawn(async move |cx| {
+            workspace.update_in(cx, |workspace, window, cx| Self::new(workspace, window, cx))
+        })
+    }
+
+    fn new(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let workspace_handle = cx.entity();
+        cx.new(|cx| {
+            let subscriptions = vec![
+                cx.subscribe_in(
+                    &workspace_handle,
+                    window,
+                    |this, workspace, event: &workspace::Event, window, cx| {
+                        if let workspace::Event::ActiveItemChanged = event {
+                            let active_item = workspace.read(cx).active_item(cx);
+                            this.update_target(active_item, window, cx);
+                        }
+                    },
+                ),
+                // Sessions are created when code is first run in an editor
+                cx.observe_in(&ReplStore::global(cx), window, |this, _, window, cx| {
+                    if let Some(workspace) = this.workspace.upgrade() {
+                        let active_item = workspace.read(cx).active_item(cx);
+                        this.update_target(active_item, window, cx);
+                    }
+                }),
+            ];
+
+            let mut this = Self {
+                workspace: workspace.weak_handle(),
+                focus_handle: cx.focus_handle(),
+                position: DockPosition::Right,
+                width: None,
+                active: false,
+                target: None,
+                variables: Vec::new(),
+                error: None,
+                stale: false,
+                refresh_task: None,
+                _target_subscriptions: Vec::new(),
+                _subscriptions: subscriptions,
+            };
+            this.update_target(workspace.active_item(cx), window, cx);
+            this
+        })
+    }
+
+    /// Follows the kernel of the active item. Items without a kernel leave
+    /// the current target in place, so that e.g. the data viewer can be
+    /// focused without losing the variable list.
+    fn update_target(
+        &mut self,
+        active_item: Option<Box<dyn ItemHandle>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(item) = active_item else {
+            return;
+        };
+
+        let target = if let Some(notebook) = item.downcast::<NotebookEditor>() {
+            KernelTarget::Notebook(notebook.downgrade())
+        } else if let Some(session) = item.act_as::<Editor>(cx).and_then(|editor| {
+            ReplStore::global(cx)
+                .read(cx)
+                .get_session(editor.entity_id())
+                .cloned()
+        }) {
+            KernelTarget::Session(session.downgrade())
+        } else {
+            return;
+        };
+        if self.target.as_ref() == Some(&target) {
+            return;
+        }
+
+        self._target_subscriptions = match &target {
+            KernelTarget::Session(session) => session
+                .upgrade()
+                .map(|session| {
+                    vec![
+                        cx.observe(&session, |_, _, cx| cx.notify()),
+                        cx.subscribe_in(&session, window, Self::on_execution_finished),
+                    ]
+                })
+                .unwrap_or_default(),
+            KernelTarget::Notebook(notebook) => notebook
+                .upgrade()
+                .map(|notebook| {
+                    vec![
+                        cx.observe(&notebook, |_, _, cx| cx.notify()),
+                        cx.subscribe_in(&notebook, window, Self::on_execution_finished),
+                    ]
+                })
+                .unwrap_or_default(),
+        };
+        self.target = Some(target);
+        self.variables.clear();
+        self.error = None;
+        self.refresh_task = None;
+        self.refresh(window, cx);
+    }
+
+    fn on_execution_finished<T: 'static>(
+        &mut self,
+        _: &Entity<T>,
+        _: &KernelExecutionFinished,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.refresh(window, cx);
+    }
+
+    fn refresh(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.active {
+            self.stale = true;
+            return;
+        }
+        self.stale = false;
+
+        let Some(target) = self.target.clone() else {
+            return;
+        };
+        if !target
+            .kernel_status(cx)
+            .is_some_and(|status| status.is_connected())
+        {
+            return;
+        }
+        let Some(language) = target.kernel_specification(cx).map(|spec| spec.language()) else {
+            return;
+        };
+        let Some(snippet) = introspection::variables_snippet(&language) else {
+            return;
+        };
+
+        let query = target.query(snippet, cx);
+        self.refresh_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let result = query
+                .await
+                .and_then(|output| introspection::parse_variables(&output));
+            this.update(cx, |this, cx| {
+                this.refresh_task = None;
+                match result {
+                    Ok(variables) => {
+                        this.variables = variables;
+                        this.error = None;
+                    }
+                    Err(error) => this.error = Some(format!("{error:#}").into()),
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn open_data_viewer(&mut self, name: String, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(target) = self.target.clone() else {
+            return;
+        };
+        let Some(snippet) = target.kernel_specification(cx).and_then(|spec| {
+            introspection::table_snippet(&spec.language(), &name, DATA_VIEWER_ROW_LIMIT)
+        }) else {
+            return;
+        };
+
+        let query = target.query(snippet, cx);
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let result = query
+                .await
+                .and_then(|output| introspection::parse_table(&output));
+            match result {
+                Ok(snapshot) => workspace.update_in(cx, |workspace, window, cx| {
+                    let viewer = cx.new(|cx| DataViewer::new(name, snapshot, cx));
+                    workspace.add_item_to_active_pane(Box::new(viewer), None, true, window, cx);
+                }),
+                Err(error) => this.update(cx, |this, cx| {
+                    this.error = Some(format!("Failed to open {name}: {error:#}").into());
+                    cx.notify();
+                }),
+            }
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_variable(
+        &self,
+        ix: usize,
+        variable: &Variable,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let name = variable.name.clone();
+        ListItem::new(ix)
+            .child(
+                v_flex()
+                    .w_full()
+                    .overflow_hidden()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(variable.name.clone()).buffer_font(cx))
+                            .child(
+                                Label::new(variable.type_name.clone())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .children(variable.shape_label().map(|shape| {
+                                Label::new(shape).size(LabelSize::Small).color(Color::Muted)
+                            })),
+                    )
+                    .child(
+                        Label::new(variable.preview.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .buffer_font(cx)
+                            .single_line()
+                            .truncate(),
+                    ),
+            )
+            .when(variable.viewable, |item| {
+                item.end_slot(
+                    IconButton::new(("open-variable", ix), IconName::ArrowUpRight)
+                        .icon_size(IconSize::Small)
+                        .tooltip(Tooltip::text("Open in Data Viewer"))
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.open_data_viewer(name.clone(), window, cx);
+                        })),
+                )
+            })
+    }
+
+    fn render_message(&self, message: impl Into<SharedString>) -> impl IntoElement {
+        v_flex().p_4().child(
+            Label::new(message)
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+        )
+    }
+}
+
+impl Render for VariableExplorerPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let status = self
+            .target
+            .as_ref()
+            .and_then(|target| target.kernel_status(cx));
+        let language = self
+            .target
+            .as_ref()
+            .and_then(|target| target.kernel_specification(cx))
+            .map(|spec| spec.language());
+
+        let header = h_flex()
+            .px_2()
+            .py_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                Label::new(match &language {
+                    Some(language) => format!("{language} kernel"),
+                    None => "No kernel".to_string(),
+                })
+                .size(LabelSize::Small),
+            )
+            .child(
+                IconButton::new("refresh-variables", IconName::RotateCw)
+                    .icon_size(IconSize::Small)
+                    .disabled(self.refresh_task.is_some())
+                    .tooltip(Tooltip::for_action_title("Refresh Variables", &Refresh))
+                    .on_click(cx.listener(|this, _, window, cx| this.refresh(window, cx))),
+            );
+
+        let body = if status.is_none() {
+            self.render_message(
+                "Run code in a REPL session or open a notebook to see its variables.",
+            )
+            .into_any_element()
+        } else if !status.is_some_and(|status| status.is_connected()) {
+            self.render_message("The kernel is not running.")
+                .into_any_element()
+        } else if language
+            .as_ref()
+            .is_some_and(|language| !introspection::supports_language(language))
+        {
+            self.render_message(format!(
+                "Listing variables is not supported for {} kernels.",
+                language.unwrap_or_default()
+            ))
+            .into_any_element()
+        } else if self.variables.is_empty() {
+            self.render_message(if self.refresh_task.is_some() {
+                "Loading variables…"
+            } else {
+                "No variables defined."
+            })
+            .into_any_element()
+        } else {
+            v_flex()
+                .id("variables")
+                .size_full()
+                .overflow_y_scroll()
+                .children(
+                    self.variables
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, variable)| self.render_variable(ix, variable, cx)),
+                )
+                .into_any_element()
+        };
+
+        v_flex()
+            .key_context("VariableExplorer")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(|this, _: &Refresh, window, cx| this.refresh(window, cx)))
+            .size_full()
+            .bg(cx.theme().colors().panel_background)
+            .child(header)
+            .children(self.error.clone().map(|error| {
+                div()
+                    .px_2()
+                    .py_1()
+                    .child(Label::new(error).size(LabelSize::Small).color(Color::Error))
+            }))
+            .child(body)
+    }
+}
+
+impl Focusable for VariableExplorerPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for VariableExplorerPanel {}
+
+impl Panel for VariableExplorerPanel {
+    fn persistent_name() -> &'static str {
+        "VariableExplorerPanel"
+    }
+
+    fn panel_key() -> &'static str {
+        "VariableExplorerPanel"
+    }
+
+    fn position(&self, _: &Window, _: &App) -> DockPosition {
+        self.position
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        self.position = position;
+        cx.notify();
+    }
+
+    fn size(&self, _: &Window, _: &App) -> Pixels {
+        self.width.unwrap_or(px(320.))
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, _: &mut Window, cx: &mut Context<Self>) {
+        self.width = size;
+        cx.notify();
+    }
+
+    fn set_active(&mut self, active: bool, window: &mut Window, cx: &mut Context<Self>) {
+        self.active = active;
+        if active && self.stale {
+            self.refresh(window, cx);
+        }
+    }
+
+    fn icon(&self, _: &Window, _: &App) -> Option<IconName> {
+        Some(IconName::ReplNeutral)
+    }
+
+    fn icon_tooltip(&self, _: &Window, _: &App) -> Option<&'static str> {
+        Some("Variable Explorer")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn activation_priority(&self) -> u32 {
+        10
+    }
+
+    fn enabled(&self, cx: &App) -> bool {
+        ReplStore::global(cx).read(cx).is_enabled()
+    }
+}
@@ -0,0 +1,158 @@
+use csv_preview::{FontType, VerticalAlignment, render_table_cell};
+use gpui::{AnyElement, Entity, EventEmitter, FocusHandle, Focusable};
+use runtimelib::media::datatable::TabularDataResource;
+use ui::{
+    DefiniteLength, Table, TableColumnWidths, TableInteractionState, TableResizeBehavior,
+    prelude::*,
+};
+use workspace::Item;
+
+use crate::outputs::table::cell_content;
+use crate::variable_explorer::introspection::TableSnapshot;
+
+/// Shows the first rows of a kernel variable, such as a DataFrame, in a
+/// scrollable table.
+pub struct DataViewer {
+    name: SharedString,
+    total_rows: usize,
+    table: TabularDataResource,
+    focus_handle: FocusHandle,
+    table_interaction_state: Entity<TableInteractionState>,
+    column_widths: Entity<TableColumnWidths>,
+}
+
+impl DataViewer {
+    pub fn new(
+        name: impl Into<SharedString>,
+        snapshot: TableSnapshot,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let columns = snapshot.table.schema.fields.len();
+        Self {
+            name: name.into(),
+            total_rows: snapshot.rows,
+            table: snapshot.table,
+            focus_handle: cx.focus_handle(),
+            table_interaction_state: cx.new(|cx| TableInteractionState::new(cx)),
+            column_widths: cx.new(|cx| TableColumnWidths::new(columns, cx)),
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        self.table.data.as_ref().map_or(0, |data| data.len())
+    }
+
+    fn render_table(&self, cx: &mut Context<Self>) -> AnyElement {
+        let columns = self.table.schema.fields.len();
+        let headers = self
+            .table
+            .schema
+            .fields
+            .iter()
+            .map(|field| {
+                Label::new(field.name.clone())
+                    .size(LabelSize::Small)
+                    .buffer_font(cx)
+            })
+            .collect::<Vec<_>>();
+
+        Table::new(columns)
+            .interactable(&self.table_interaction_state)
+            .striped()
+            .column_widths(vec![DefiniteLength::Fraction(1. / columns as f32); columns])
+            .resizable_columns(
+                vec![TableResizeBehavior::Resizable; columns],
+                &self.column_widths,
+                cx,
+            )
+            .header(headers)
+            .uniform_list(
+                "data-viewer-rows",
+                self.row_count(),
+                cx.processor(move |this, range: std::ops::Range<usize>, _window, cx| {
+                    let Some(data) = this.table.data.as_ref() else {
+                        return Vec::new();
+                    };
+                    range
+                        .filter_map(|row_ix| {
+                            let row = data.get(row_ix)?;
+                            let cells = this
+                                .table
+                                .schema
+                                .fields
+                                .iter()
+                                .enumerate()
+                                .map(|(column_ix, field)| {
+                                    render_table_cell(
+                                        ("data-viewer-cell", row_ix * columns + column_ix),
+                                        cell_content(row, &field.name).into(),
+                                        VerticalAlignment::Center,
+                                        FontType::Monospace,
+                                        cx,
+                                    )
+                                    .into_any_element()
+                                })
+                                .collect();
+                            Some(cells)
+                        })
+                        .collect()
+                }),
+            )
+            .into_any_element()
+    }
+}
+
+impl Render for DataViewer {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let row_count = self.row_count();
+        let summary = if row_count < self.total_rows {
+            format!("Showing the first {row_count} of {} rows", self.total_rows)
+        } else {
+            format!("{row_count} rows")
+        };
+
+        v_flex()
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_4()
+            .gap_2()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(self.name.clone()).buffer_font(cx))
+                    .child(
+                        Label::new(summary)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(if self.table.schema.fields.is_empty() {
+                Label::new("No columns to display")
+                    .color(Color::Muted)
+                    .into_any_element()
+            } else {
+                self.render_table(cx)
+            })
+    }
+}
+
+impl Focusable for DataViewer {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<()> for DataViewer {}
+
+impl Item for DataViewer {
+    type Event = ();
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::ReplNeutral))
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        self.name.clone()
+    }
+}
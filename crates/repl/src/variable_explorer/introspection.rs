@@ -0,0 +1,211 @@
+//! Snippets that the variable explorer runs in a kernel to inspect its
+//! namespace. Each snippet prints a single line of JSON to stdout.
+
+use anyhow::{Context as _, Result};
+use runtimelib::media::datatable::TabularDataResource;
+use serde::Deserialize;
+
+const PYTHON_VARIABLES: &str = r#"
+def __zed_variables():
+    import json, reprlib, types
+    hidden = {"In", "Out", "exit", "quit", "get_ipython"}
+    skipped = (types.ModuleType, types.FunctionType, types.BuiltinFunctionType, types.MethodType, type)
+    short_repr = reprlib.Repr()
+    short_repr.maxstring = short_repr.maxother = 120
+    variables = []
+    for name, value in list(globals().items()):
+        if name.startswith("_") or name in hidden or isinstance(value, skipped):
+            continue
+        kind = type(value)
+        module = kind.__module__ or ""
+        shape = getattr(value, "shape", None)
+        if not (isinstance(shape, tuple) and all(isinstance(size, int) for size in shape)):
+            sized = (list, tuple, dict, set, frozenset, str, bytes)
+            shape = (len(value),) if isinstance(value, sized) else None
+        try:
+            preview = " ".join(short_repr.repr(value).split())
+        except Exception:
+            preview = ""
+        is_frame = (module.startswith("pandas") and kind.__name__ in ("DataFrame", "Series")) or (
+            module.startswith("polars") and kind.__name__ == "DataFrame"
+        )
+        is_records = isinstance(value, list) and len(value) > 0 and all(isinstance(item, dict) for item in value[:100])
+        variables.append({
+            "name": name,
+            "type": kind.__name__,
+            "shape": list(shape) if shape is not None else None,
+            "preview": preview,
+            "viewable": is_frame or is_records,
+        })
+    print(json.dumps(variables))
+__zed_variables()
+del __zed_variables
+"#;
+
+const PYTHON_TABLE: &str = r#"
+def __zed_table(value, limit):
+    import json
+    kind = type(value)
+    module = kind.__module__ or ""
+    if module.startswith("pandas"):
+        frame = value.to_frame() if kind.__name__ == "Series" else value
+        if type(frame.index).__name__ != "RangeIndex":
+            frame = frame.reset_index()
+        rows = json.loads(frame.head(limit).to_json(orient="records", date_format="iso", default_handler=str))
+        columns = [str(column) for column in frame.columns]
+    elif module.startswith("polars"):
+        rows = value.head(limit).to_dicts()
+        columns = list(value.columns)
+    else:
+        rows = [{str(key): item for key, item in row.items()} for row in value[:limit]]
+        columns = list(dict.fromkeys(key for row in rows for key in row))
+    rows = json.loads(json.dumps(rows, default=str), parse_constant=lambda _: None)
+    def field_type(column):
+        for row in rows:
+            item = row.get(column)
+            if item is None:
+                continue
+            if isinstance(item, bool):
+                return "boolean"
+            if isinstance(item, int):
+                return "integer"
+            if isinstance(item, float):
+                return "number"
+            return "string"
+        return "any"
+    fields = [{"name": column, "type": field_type(column)} for column in columns]
+    print(json.dumps({"rows": len(value), "table": {"schema": {"fields": fields}, "data": rows}}))
+"#;
+
+/// A variable defined in the kernel's namespace.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Variable {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub shape: Option<Vec<u64>>,
+    pub preview: String,
+    /// Whether the variable can be opened in the data viewer.
+    #[serde(default)]
+    pub viewable: bool,
+}
+
+impl Variable {
+    /// Formats the shape as e.g. `3 × 4`, omitting it for scalars.
+    pub fn shape_label(&self) -> Option<String> {
+        let shape = self.shape.as_ref().filter(|shape| !shape.is_empty())?;
+        Some(
+            shape
+                .iter()
+                .map(|size| size.to_string())
+                .collect::<Vec<_>>()
+                .join(" × "),
+        )
+    }
+}
+
+/// The first rows of a tabular variable.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TableSnapshot {
+    /// The number of rows in the variable, which may exceed those in `table`.
+    pub rows: usize,
+    pub table: TabularDataResource,
+}
+
+pub fn supports_language(language: &str) -> bool {
+    language.eq_ignore_ascii_case("python")
+}
+
+/// The code that lists the variables in the kernel's namespace.
+pub fn variables_snippet(language: &str) -> Option<String> {
+    supports_language(language).then(|| PYTHON_VARIABLES.to_string())
+}
+
+/// The code that prints the first `row_limit` rows of the variable `name`.
+pub fn table_snippet(language: &str, name: &str, row_limit: usize) -> Option<String> {
+    if !supports_language(language) {
+        return None;
+    }
+    // A JSON string is also a valid Python string literal.
+    let name = serde_json::to_string(name).ok()?;
+    Some(format!(
+        "{PYTHON_TABLE}__zed_table(globals()[{name}], {row_limit})\ndel __zed_table\n"
+    ))
+}
+
+pub fn parse_variables(output: &str) -> Result<Vec<Variable>> {
+    let mut variables: Vec<Variable> =
+        serde_json::from_str(last_line(output)).context("failed to parse kernel variables")?;
+    variables.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(variables)
+}
+
+pub fn parse_table(output: &str) -> Result<TableSnapshot> {
+    serde_json::from_str(last_line(output)).context("failed to parse table data")
+}
+
+/// Anything the namespace prints while being inspected, such as a property
+/// with side effects, comes before the snippet's own output.
+fn last_line(output: &str) -> &str {
+    output
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use runtimelib::datatable::FieldType;
+
+    #[test]
+    fn test_parse_variables() {
+        let output = concat!(
+            "some unrelated output\n",
+            r#"[{"name": "df", "type": "DataFrame", "shape": [3, 2], "preview": "a b", "viewable": true}, "#,
+            r#"{"name": "count", "type": "int", "shape": null, "preview": "42", "viewable": false}, "#,
+            r#"{"name": "arr", "type": "ndarray", "shape": [], "preview": "array(1)", "viewable": false}]"#,
+            "\n",
+        );
+
+        let variables = parse_variables(output).unwrap();
+        let names = variables
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["arr", "count", "df"]);
+
+        assert_eq!(variables[2].type_name, "DataFrame");
+        assert!(variables[2].viewable);
+        assert_eq!(variables[2].shape_label().as_deref(), Some("3 × 2"));
+        assert_eq!(variables[1].shape_label(), None);
+        assert_eq!(variables[0].shape_label(), None);
+
+        assert!(parse_variables("").is_err());
+    }
+
+    #[test]
+    fn test_parse_table() {
+        let output = r#"{"rows": 10, "table": {"schema": {"fields": [{"name": "id", "type": "integer"}, {"name": "name", "type": "string"}]}, "data": [{"id": 1, "name": "Alice"}]}}"#;
+
+        let snapshot = parse_table(output).unwrap();
+        assert_eq!(snapshot.rows, 10);
+        assert_eq!(snapshot.table.schema.fields.len(), 2);
+        assert_eq!(
+            snapshot.table.schema.fields[0].field_type,
+            FieldType::Integer
+        );
+        assert_eq!(snapshot.table.data.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_snippets_by_language() {
+        assert!(variables_snippet("Python").is_some());
+        assert!(variables_snippet("typescript").is_none());
+        assert!(table_snippet("r", "df", 10).is_none());
+
+        let snippet = table_snippet("python", r#"we"ird"#, 100).unwrap();
+        assert!(snippet.contains(r#"__zed_table(globals()["we\"ird"], 100)"#));
+    }
+}
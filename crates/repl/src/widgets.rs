@@ -0,0 +1,559 @@
+//! Jupyter widget models, kept in sync with the kernel over the comm protocol.
+//!
+//! Every `ipywidgets` model lives on both sides of a comm opened by the kernel
+//! with the `jupyter.widget` target. The kernel sends the full model state when
+//! it opens the comm and partial `update`s afterwards; the views in
+//! [`crate::outputs::widget`] send `update`s and `custom` events back through
+//! [`WidgetCommMessage`]s, which the owning session forwards to the kernel.
+
+use collections::HashMap;
+use gpui::{AppContext as _, Context, EventEmitter, Window};
+use runtimelib::{JupyterMessage, JupyterMessageContent, MimeBundle, MimeType};
+use serde_json::{Map, Value, json};
+
+use crate::outputs::Output;
+use crate::outputs::plain::TerminalOutput;
+use crate::outputs::user_error::ErrorView;
+use crate::outputs::widget::WidgetView;
+
+const WIDGET_COMM_TARGET: &str = "jupyter.widget";
+const MODEL_REFERENCE_PREFIX: &str = "IPY_MODEL_";
+
+/// The synced state of a widget model.
+#[derive(Clone, Default)]
+pub struct WidgetState(Map<String, Value>);
+
+impl WidgetState {
+    pub fn model_name(&self) -> &str {
+        self.get_str("_model_name").unwrap_or_default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.get(key)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(Value::as_str)
+    }
+
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.0.get(key).and_then(Value::as_f64)
+    }
+
+    pub fn get_bool(&self, key: &str) -> bool {
+        self.0.get(key).and_then(Value::as_bool).unwrap_or_default()
+    }
+
+    /// The ids of the models referenced by the `children` of a box widget.
+    pub fn children(&self) -> Vec<String> {
+        self.0
+            .get("children")
+            .and_then(Value::as_array)
+            .map(|children| {
+                children
+                    .iter()
+                    .filter_map(|child| child.as_str()?.strip_prefix(MODEL_REFERENCE_PREFIX))
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// A widget model, as last synced with the kernel.
+pub struct WidgetModel {
+    state: WidgetState,
+    /// Rendered outputs of an `Output` widget, derived from its `outputs` state.
+    outputs: Vec<Output>,
+}
+
+impl WidgetModel {
+    pub fn state(&self) -> &WidgetState {
+        &self.state
+    }
+
+    pub fn outputs(&self) -> &[Output] {
+        &self.outputs
+    }
+}
+
+/// A comm message for the kernel, emitted when the user interacts with a widget.
+pub struct WidgetCommMessage {
+    pub comm_id: String,
+    pub data: Map<String, Value>,
+}
+
+/// All widget models opened by a kernel.
+#[derive(Default)]
+pub struct WidgetStore {
+    models: HashMap<String, WidgetModel>,
+}
+
+impl EventEmitter<WidgetCommMessage> for WidgetStore {}
+
+impl WidgetStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn model(&self, model_id: &str) -> Option<&WidgetModel> {
+        self.models.get(model_id)
+    }
+
+    /// Drops every model, e.g. once the kernel that owns them is gone.
+    pub fn clear(&mut self, cx: &mut Context<Self>) {
+        self.models.clear();
+        cx.notify();
+    }
+
+    /// Handles `comm_open`, `comm_msg` and `comm_close` messages for widget comms.
+    ///
+    /// Returns whether the message was consumed.
+    pub fn handle_comm_message(
+        &mut self,
+        message: &JupyterMessage,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        match &message.content {
+            JupyterMessageContent::CommOpen(open) if open.target_name == WIDGET_COMM_TARGET => {
+                let model_id = open.comm_id.0.clone();
+                let state = open
+                    .data
+                    .get("state")
+                    .and_then(Value::as_object)
+                    .cloned()
+                    .unwrap_or_default();
+                self.models.insert(
+                    model_id.clone(),
+                    WidgetModel {
+                        state: WidgetState::default(),
+                        outputs: Vec::new(),
+                    },
+                );
+                self.apply_state(&model_id, state, window, cx);
+            }
+            JupyterMessageContent::CommMsg(comm_message)
+                if self.models.contains_key(&comm_message.comm_id.0) =>
+            {
+                // `echo_update`s repeat state this frontend already applied, and
+                // custom messages have no meaning for the built-in widgets.
+                if comm_message.data.get("method").and_then(Value::as_str) == Some("update") {
+                    let state = comm_message
+                        .data
+                        .get("state")
+                        .and_then(Value::as_object)
+                        .cloned()
+                        .unwrap_or_default();
+                    self.apply_state(&comm_message.comm_id.0, state, window, cx);
+                }
+            }
+            JupyterMessageContent::CommClose(close) => {
+                if self.models.remove(&close.comm_id.0).is_none() {
+                    return false;
+                }
+            }
+            _ => return false,
+        }
+
+        cx.notify();
+        true
+    }
+
+    /// Updates the state of a model on both sides of its comm.
+    pub fn set_state(
+        &mut self,
+        model_id: &str,
+        state: Map<String, Value>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.models.contains_key(model_id) {
+            return;
+        }
+
+        cx.emit(WidgetCommMessage {
+            comm_id: model_id.to_string(),
+            data: json_object(json!({
+                "method": "update",
+                "state": state.clone(),
+                "buffer_paths": [],
+            })),
+        });
+        self.apply_state(model_id, state, window, cx);
+        cx.notify();
+    }
+
+    /// Sends a custom event, such as a button click, to the kernel side of a model.
+    pub fn send_custom(&mut self, model_id: &str, content: Value, cx: &mut Context<Self>) {
+        if !self.models.contains_key(model_id) {
+            return;
+        }
+
+        cx.emit(WidgetCommMessage {
+            comm_id: model_id.to_string(),
+            data: json_object(json!({
+                "method": "custom",
+                "content": content,
+            })),
+        });
+    }
+
+    /// Appends an output message to the `Output` widget currently capturing the
+    /// execution it belongs to.
+    ///
+    /// Returns whether the message was captured.
+    pub fn capture_output(
+        &mut self,
+        message: &JupyterMessage,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(parent_header) = message.parent_header.as_ref() else {
+            return false;
+        };
+
+        let Some((model_id, model)) = self.models.iter().find(|(_, model)| {
+            model.state.model_name() == "OutputModel"
+                && model.state.get_str("msg_id") == Some(parent_header.msg_id.as_str())
+        }) else {
+            return false;
+        };
+
+        let mut outputs = model
+            .state
+            .get("outputs")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+
+        match &message.content {
+            JupyterMessageContent::StreamContent(stream) => {
+                let name = serde_json::to_value(&stream.name).unwrap_or_default();
+                match outputs.last_mut() {
+                    Some(last) if last["output_type"] == "stream" && last["name"] == name => {
+                        let text = format!(
+                            "{}{}",
+                            last["text"].as_str().unwrap_or_default(),
+                            stream.text
+                        );
+                        last["text"] = Value::String(text);
+                    }
+                    _ => outputs.push(json!({
+                        "output_type": "stream",
+                        "name": name,
+                        "text": stream.text,
+                    })),
+                }
+            }
+            JupyterMessageContent::DisplayData(display_data) => {
+                outputs.push(display_data_output(&display_data.data));
+            }
+            JupyterMessageContent::ExecuteResult(execute_result) => {
+                outputs.push(display_data_output(&execute_result.data));
+            }
+            JupyterMessageContent::ErrorOutput(error) => outputs.push(json!({
+                "output_type": "error",
+                "ename": error.ename,
+                "evalue": error.evalue,
+                "traceback": error.traceback,
+            })),
+            JupyterMessageContent::ClearOutput(_) => outputs.clear(),
+            _ => return false,
+        }
+
+        let model_id = model_id.clone();
+        let mut state = Map::new();
+        state.insert("outputs".to_string(), Value::Array(outputs));
+        self.set_state(&model_id, state, window, cx);
+        true
+    }
+
+    fn apply_state(
+        &mut self,
+        model_id: &str,
+        state: Map<String, Value>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let outputs = state
+            .get("outputs")
+            .and_then(Value::as_array)
+            .map(|outputs| {
+                outputs
+                    .iter()
+                    .filter_map(|output| {
+                        serde_json::from_value::<nbformat::v4::Output>(output.clone()).ok()
+                    })
+                    .map(|output| self.output_from_nbformat(&output, window, cx))
+                    .collect::<Vec<_>>()
+            });
+
+        let Some(model) = self.models.get_mut(model_id) else {
+            return;
+        };
+        if let Some(outputs) = outputs {
+            model.outputs = outputs;
+        }
+        model.state.0.extend(state);
+    }
+
+    fn output_from_nbformat(
+        &self,
+        output: &nbformat::v4::Output,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Output {
+        match output {
+            nbformat::v4::Output::Stream { text, .. } => Output::Stream {
+                content: cx.new(|cx| TerminalOutput::from(&text.0, window, cx)),
+            },
+            nbformat::v4::Output::DisplayData(display_data) => {
+                self.output_for_bundle(&display_data.data, window, cx)
+            }
+            nbformat::v4::Output::ExecuteResult(execute_result) => {
+                self.output_for_bundle(&execute_result.data, window, cx)
+            }
+            nbformat::v4::Output::Error(error) => Output::ErrorOutput(ErrorView {
+                ename: error.ename.clone(),
+                evalue: error.evalue.clone(),
+                traceback: cx
+                    .new(|cx| TerminalOutput::from(&error.traceback.join("\n"), window, cx)),
+            }),
+        }
+    }
+
+    /// Widgets displayed inside an `Output` widget render natively as well.
+    fn output_for_bundle(
+        &self,
+        data: &MimeBundle,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Output {
+        match widget_model_id(data).filter(|model_id| self.models.contains_key(model_id)) {
+            Some(model_id) => {
+                let store = cx.entity();
+                Output::Widget {
+                    content: cx.new(|cx| WidgetView::new(store, model_id, window, cx)),
+                    display_id: None,
+                }
+            }
+            None => Output::new(data, None, window, cx),
+        }
+    }
+}
+
+/// The id of the widget model referenced by a `application/vnd.jupyter.widget-view+json` output.
+pub fn widget_model_id(data: &MimeBundle) -> Option<String> {
+    data.content.iter().find_map(|media| match media {
+        MimeType::WidgetView(view) => Some(view.get("model_id")?.as_str()?.to_string()),
+        _ => None,
+    })
+}
+
+fn display_data_output(data: &MimeBundle) -> Value {
+    json!({
+        "output_type": "display_data",
+        "data": data,
+        "metadata": {},
+    })
+}
+
+fn json_object(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(object) => object,
+        _ => Map::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, Entity, TestAppContext, VisualTestContext};
+    use runtimelib::{CommClose, CommId, CommMsg, CommOpen, ExecuteRequest, Stdio, StreamContent};
+    use settings::SettingsStore;
+    use std::{cell::RefCell, rc::Rc};
+
+    fn init_test(cx: &mut TestAppContext) -> (&mut VisualTestContext, Entity<WidgetStore>) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+        });
+        let cx = cx.add_empty_window();
+        let store = cx.update(|_, cx| cx.new(|_| WidgetStore::new()));
+        (cx, store)
+    }
+
+    fn comm_open(comm_id: &str, state: Value) -> JupyterMessage {
+        let open: CommOpen = serde_json::from_value(json!({
+            "comm_id": comm_id,
+            "target_name": WIDGET_COMM_TARGET,
+            "data": { "state": state, "buffer_paths": [] },
+        }))
+        .unwrap();
+        JupyterMessage::new(open, None)
+    }
+
+    fn comm_msg(comm_id: &str, data: Value) -> JupyterMessage {
+        JupyterMessage::new(
+            CommMsg {
+                comm_id: CommId(comm_id.to_string()),
+                data: json_object(data),
+            },
+            None,
+        )
+    }
+
+    fn record_comm_messages(
+        cx: &mut VisualTestContext,
+        store: &Entity<WidgetStore>,
+    ) -> Rc<RefCell<Vec<(String, Value)>>> {
+        let sent = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|_, cx| {
+            let sent = sent.clone();
+            cx.subscribe(store, move |_, event: &WidgetCommMessage, _| {
+                sent.borrow_mut()
+                    .push((event.comm_id.clone(), Value::Object(event.data.clone())));
+            })
+            .detach();
+        });
+        sent
+    }
+
+    #[gpui::test]
+    async fn test_widget_state_sync(cx: &mut TestAppContext) {
+        let (cx, store) = init_test(cx);
+        let sent = record_comm_messages(cx, &store);
+
+        cx.update(|window, cx| {
+            store.update(cx, |store, cx| {
+                let open = comm_open(
+                    "slider",
+                    json!({
+                        "_model_name": "IntSliderModel",
+                        "value": 3,
+                        "min": 0,
+                        "max": 10,
+                    }),
+                );
+                assert!(store.handle_comm_message(&open, window, cx));
+
+                let update = comm_msg(
+                    "slider",
+                    json!({ "method": "update", "state": { "max": 20 }, "buffer_paths": [] }),
+                );
+                assert!(store.handle_comm_message(&update, window, cx));
+            });
+        });
+
+        cx.update(|window, cx| {
+            let model = store.read(cx).model("slider").unwrap().state();
+            assert_eq!(model.model_name(), "IntSliderModel");
+            assert_eq!(model.get_f64("value"), Some(3.));
+            assert_eq!(model.get_f64("max"), Some(20.));
+
+            store.update(cx, |store, cx| {
+                let mut state = Map::new();
+                state.insert("value".to_string(), json!(7));
+                store.set_state("slider", state, window, cx);
+                store.send_custom("slider", json!({ "event": "click" }), cx);
+            });
+        });
+
+        cx.update(|_, cx| {
+            let model = store.read(cx).model("slider").unwrap().state();
+            assert_eq!(model.get_f64("value"), Some(7.));
+        });
+        assert_eq!(
+            *sent.borrow(),
+            vec![
+                (
+                    "slider".to_string(),
+                    json!({ "method": "update", "state": { "value": 7 }, "buffer_paths": [] }),
+                ),
+                (
+                    "slider".to_string(),
+                    json!({ "method": "custom", "content": { "event": "click" } }),
+                ),
+            ]
+        );
+
+        cx.update(|window, cx| {
+            store.update(cx, |store, cx| {
+                let close = JupyterMessage::new(
+                    CommClose {
+                        comm_id: CommId("slider".to_string()),
+                        data: Map::new(),
+                    },
+                    None,
+                );
+                assert!(store.handle_comm_message(&close, window, cx));
+                assert!(store.model("slider").is_none());
+
+                let unknown = comm_msg("slider", json!({ "method": "update", "state": {} }));
+                assert!(!store.handle_comm_message(&unknown, window, cx));
+            });
+        });
+    }
+
+    #[gpui::test]
+    async fn test_output_widget_capture(cx: &mut TestAppContext) {
+        let (cx, store) = init_test(cx);
+        let sent = record_comm_messages(cx, &store);
+
+        let execution = JupyterMessage::new(ExecuteRequest::default(), None);
+        let other_execution = JupyterMessage::new(ExecuteRequest::default(), None);
+        let stream = |parent: &JupyterMessage, text: &str| {
+            JupyterMessage::new(
+                StreamContent {
+                    name: Stdio::Stdout,
+                    text: text.to_string(),
+                },
+                Some(parent),
+            )
+        };
+
+        cx.update(|window, cx| {
+            store.update(cx, |store, cx| {
+                let open = comm_open(
+                    "out",
+                    json!({
+                        "_model_name": "OutputModel",
+                        "msg_id": execution.header.msg_id,
+                        "outputs": [],
+                    }),
+                );
+                store.handle_comm_message(&open, window, cx);
+
+                assert!(store.capture_output(&stream(&execution, "hello "), window, cx));
+                assert!(store.capture_output(&stream(&execution, "world"), window, cx));
+                assert!(!store.capture_output(&stream(&other_execution, "elsewhere"), window, cx));
+            });
+        });
+
+        cx.update(|_, cx| {
+            let model = store.read(cx).model("out").unwrap();
+            assert_eq!(
+                model.state().get("outputs"),
+                Some(&json!([
+                    { "output_type": "stream", "name": "stdout", "text": "hello world" }
+                ]))
+            );
+            assert_eq!(model.outputs().len(), 1);
+        });
+        assert_eq!(sent.borrow().len(), 2);
+
+        cx.update(|window, cx| {
+            store.update(cx, |store, cx| {
+                let update = comm_msg(
+                    "out",
+                    json!({ "method": "update", "state": { "msg_id": "", "outputs": [] } }),
+                );
+                store.handle_comm_message(&update, window, cx);
+                assert!(!store.capture_output(&stream(&execution, "after"), window, cx));
+                assert!(store.model("out").unwrap().outputs().is_empty());
+            });
+        });
+    }
+}
@@ -37,6 +37,8 @@ actions!(
         ToggleCaseSensitive,
         /// Toggles regular expression mode.
         ToggleRegex,
+        /// Toggles structural search, matching code by its syntax tree.
+        ToggleStructural,
         /// Toggles the replace interface.
         ToggleReplace,
         /// Toggles searching within selection only.
@@ -71,6 +73,7 @@ bitflags! {
         const ONE_MATCH_PER_LINE = 1 << SearchOption::OneMatchPerLine as u8;
         /// If set, reverse direction when finding the active match
         const BACKWARDS = 1 << SearchOption::Backwards as u8;
+        const STRUCTURAL = 1 << SearchOption::Structural as u8;
     }
 }
 
@@ -83,6 +86,7 @@ pub enum SearchOption {
     Regex,
     OneMatchPerLine,
     Backwards,
+    Structural,
 }
 
 pub(crate) enum SearchSource<'a, 'b> {
@@ -103,6 +107,7 @@ impl SearchOption {
             SearchOption::Regex => "Use Regular Expressions",
             SearchOption::OneMatchPerLine => "One Match Per Line",
             SearchOption::Backwards => "Search Backwards",
+            SearchOption::Structural => "Match Code Structure",
         }
     }
 
@@ -112,6 +117,7 @@ impl SearchOption {
             SearchOption::CaseSensitive => ui::IconName::CaseSensitive,
             SearchOption::IncludeIgnored => ui::IconName::Sliders,
             SearchOption::Regex => ui::IconName::Regex,
+            SearchOption::Structural => ui::IconName::Code,
             _ => panic!("{self:?} is not a named SearchOption"),
         }
     }
@@ -122,6 +128,7 @@ impl SearchOption {
             SearchOption::CaseSensitive => &ToggleCaseSensitive,
             SearchOption::IncludeIgnored => &ToggleIncludeIgnored,
             SearchOption::Regex => &ToggleRegex,
+            SearchOption::Structural => &ToggleStructural,
             _ => panic!("{self:?} is not a toggle action"),
         }
     }
@@ -134,6 +141,8 @@ impl SearchOption {
     ) -> impl IntoElement {
         let action = self.to_toggle_action();
         let label = self.label();
+        let unsupported = active.contains(SearchOptions::STRUCTURAL)
+            && SearchOptions::UNSUPPORTED_BY_STRUCTURAL.contains(self.as_options());
         IconButton::new(
             (label, matches!(search_source, SearchSource::Buffer) as u32),
             self.icon(),
@@ -157,12 +166,17 @@ impl SearchOption {
         })
         .style(ButtonStyle::Subtle)
         .shape(IconButtonShape::Square)
-        .toggle_state(active.contains(self.as_options()))
+        .toggle_state(active.contains(self.as_options()) && !unsupported)
+        .disabled(unsupported)
         .tooltip(move |_window, cx| Tooltip::for_action_in(label, action, &focus_handle, cx))
     }
 }
 
 impl SearchOptions {
+    /// The options that have no effect on structural queries.
+    pub const UNSUPPORTED_BY_STRUCTURAL: SearchOptions =
+        SearchOptions::WHOLE_WORD.union(SearchOptions::CASE_SENSITIVE);
+
     pub fn none() -> SearchOptions {
         SearchOptions::NONE
     }
@@ -173,6 +187,7 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::STRUCTURAL, query.is_structural());
         options
     }
 
@@ -969,6 +969,48 @@ impl KeymapFile {
             None
         }
     }
+
+    /// Appends a section with the given bindings to the keymap, leaving out the bindings that a
+    /// section with the same context already has.
+    pub fn append_bindings(
+        context: Option<&str>,
+        bindings: impl IntoIterator<Item = (String, Value)>,
+        mut keymap_contents: String,
+        tab_size: usize,
+    ) -> Result<String> {
+        let keymap = Self::parse(&keymap_contents).context("Failed to parse keymap")?;
+        let context_parsed = KeyBindingContextPredicate::parse(context.unwrap_or("")).ok();
+        let existing_bindings = keymap
+            .sections()
+            .filter(|section| {
+                KeyBindingContextPredicate::parse(&section.context).ok() == context_parsed
+            })
+            .flat_map(KeymapSection::bindings)
+            .collect::<Vec<_>>();
+        let bindings = bindings
+            .into_iter()
+            .filter(|(keystrokes, action)| {
+                !existing_bindings
+                    .iter()
+                    .any(|(existing_keystrokes, existing_action)| {
+                        *existing_keystrokes == keystrokes && &existing_action.0 == action
+                    })
+            })
+            .collect::<serde_json::Map<_, _>>();
+        if bindings.is_empty() {
+            return Ok(keymap_contents);
+        }
+
+        let mut value = serde_json::Map::with_capacity(2);
+        if let Some(context) = context {
+            value.insert("context".to_string(), context.into());
+        }
+        value.insert("bindings".to_string(), bindings.into());
+        let (replace_range, replace_value) =
+            append_top_level_array_value_in_json_text(&keymap_contents, &value.into(), tab_size);
+        keymap_contents.replace_range(replace_range, &replace_value);
+        Ok(keymap_contents)
+    }
 }
 
 #[derive(Clone, Debug)]
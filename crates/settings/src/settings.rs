@@ -6,6 +6,7 @@ mod keymap_file;
 mod settings_file;
 mod settings_store;
 mod vscode_import;
+mod vscode_keymap_import;
 
 pub use settings_macros::RegisterSetting;
 
@@ -50,6 +51,9 @@ pub use settings_store::{
 };
 
 pub use vscode_import::{VsCodeSettings, VsCodeSettingsSource};
+pub use vscode_keymap_import::{
+    UnmappedReason, UnmappedVsCodeKeybinding, VsCodeKeybindings, VsCodeKeymapImport,
+};
 
 pub use keymap_file::ActionSequence;
 
@@ -553,6 +553,7 @@ impl VsCodeSettings {
             inlay_hints: None,
             jsx_tag_auto_close: None,
             language_servers: None,
+            linters: None,
             semantic_tokens: self
                 .read_bool("editor.semanticHighlighting.enabled")
                 .map(|enabled| {
@@ -807,6 +808,7 @@ impl VsCodeSettings {
             sticky_scroll: None,
             auto_open: None,
             diagnostic_badges: None,
+            file_nesting: self.project_panel_file_nesting_content(),
         };
 
         if let (Some(false), Some(false)) = (
@@ -820,6 +822,26 @@ impl VsCodeSettings {
         skip_default(project_panel_settings)
     }
 
+    fn project_panel_file_nesting_content(&self) -> Option<ProjectPanelFileNestingContent> {
+        let patterns = self
+            .read_value("explorer.fileNesting.patterns")
+            .and_then(Value::as_object)
+            .map(|patterns| {
+                FileNestingPatterns(
+                    patterns
+                        .iter()
+                        .filter_map(|(parent, children)| {
+                            Some((parent.clone(), children.as_str()?.to_owned()))
+                        })
+                        .collect(),
+                )
+            });
+        skip_default(ProjectPanelFileNestingContent {
+            enabled: self.read_bool("explorer.fileNesting.enabled"),
+            patterns,
+        })
+    }
+
     fn telemetry_settings_content(&self) -> Option<TelemetrySettingsContent> {
         self.read_enum("telemetry.telemetryLevel", |level| {
             let (metrics, diagnostics) = match level {
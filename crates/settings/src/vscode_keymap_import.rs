@@ -0,0 +1,910 @@
+use crate::{KeymapFile, VsCodeSettingsSource};
+use anyhow::{Context as _, Result, anyhow};
+use collections::IndexMap;
+use fs::Fs;
+use gpui::KeyBindingContextPredicate;
+use paths::{cursor_keybindings_file_paths, vscode_keybindings_file_paths};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::{fmt, iter::Peekable, path::Path, sync::Arc, vec};
+
+const WORKSPACE: &str = "Workspace";
+const PANE: &str = "Pane";
+const EDITOR: &str = "Editor";
+const FULL_EDITOR: &str = "Editor && mode == full";
+const TERMINAL: &str = "Terminal";
+const PROJECT_PANEL: &str = "ProjectPanel";
+
+/// A key binding in a VS Code `keybindings.json` file.
+#[derive(Clone, Debug, Deserialize)]
+struct VsCodeKeybinding {
+    key: String,
+    command: String,
+    #[serde(default)]
+    when: Option<String>,
+    #[serde(default)]
+    args: Option<Value>,
+}
+
+pub struct VsCodeKeybindings {
+    pub source: VsCodeSettingsSource,
+    pub path: Arc<Path>,
+    bindings: Vec<VsCodeKeybinding>,
+}
+
+impl VsCodeKeybindings {
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn from_str(content: &str, source: VsCodeSettingsSource) -> Result<Self> {
+        Ok(Self {
+            source,
+            path: Path::new("/example-path/Code/User/keybindings.json").into(),
+            bindings: serde_json_lenient::from_str(content)?,
+        })
+    }
+
+    pub async fn load_user_keybindings(
+        source: VsCodeSettingsSource,
+        fs: Arc<dyn Fs>,
+    ) -> Result<Self> {
+        let candidate_paths = match source {
+            VsCodeSettingsSource::VsCode => vscode_keybindings_file_paths(),
+            VsCodeSettingsSource::Cursor => cursor_keybindings_file_paths(),
+        };
+        let mut path = None;
+        for candidate_path in candidate_paths.iter() {
+            if fs.is_file(candidate_path).await {
+                path = Some(candidate_path.clone());
+            }
+        }
+        let Some(path) = path else {
+            return Err(anyhow!(
+                "No keybindings file found, expected to find it in one of the following paths:\n{}",
+                candidate_paths
+                    .into_iter()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ));
+        };
+        let content = fs.load(&path).await.with_context(|| {
+            format!(
+                "Error loading {} keybindings file from {}",
+                source,
+                path.display()
+            )
+        })?;
+        let bindings = serde_json_lenient::from_str(&content).with_context(|| {
+            format!(
+                "Error parsing {} keybindings file from {}",
+                source,
+                path.display()
+            )
+        })?;
+        Ok(Self {
+            source,
+            path: path.into(),
+            bindings,
+        })
+    }
+
+    /// Translates the key bindings into Zed key bindings, collecting the ones that can't be.
+    pub fn import(&self) -> VsCodeKeymapImport {
+        let mut import = VsCodeKeymapImport::default();
+        for binding in &self.bindings {
+            match translate_binding(binding) {
+                Ok((context, keystrokes, action)) => {
+                    let bindings = import.sections.entry(context).or_default();
+                    // A removal only stands for VS Code's own binding, so it shouldn't replace a
+                    // binding that was imported for the same keystrokes.
+                    if action.is_null()
+                        && bindings
+                            .get(&keystrokes)
+                            .is_some_and(|action| !action.is_null())
+                    {
+                        continue;
+                    }
+                    bindings.insert(keystrokes, action);
+                }
+                Err(reason) => import.unmapped.push(UnmappedVsCodeKeybinding {
+                    key: binding.key.clone(),
+                    command: binding.command.clone(),
+                    when: binding.when.clone(),
+                    reason,
+                }),
+            }
+        }
+        import
+    }
+}
+
+/// The Zed key bindings translated from a VS Code `keybindings.json` file.
+#[derive(Debug, Default)]
+pub struct VsCodeKeymapImport {
+    /// The translated bindings, grouped by context in the order the contexts first appear.
+    pub sections: IndexMap<String, IndexMap<String, Value>>,
+    /// The VS Code key bindings that couldn't be translated.
+    pub unmapped: Vec<UnmappedVsCodeKeybinding>,
+}
+
+impl VsCodeKeymapImport {
+    pub fn binding_count(&self) -> usize {
+        self.sections.values().map(IndexMap::len).sum()
+    }
+
+    /// Appends the translated bindings to the contents of a keymap file, skipping the ones it
+    /// already has.
+    pub fn apply_to_keymap(&self, mut keymap_contents: String, tab_size: usize) -> Result<String> {
+        for (context, bindings) in &self.sections {
+            keymap_contents = KeymapFile::append_bindings(
+                Some(context),
+                bindings
+                    .iter()
+                    .map(|(keystrokes, action)| (keystrokes.clone(), action.clone())),
+                keymap_contents,
+                tab_size,
+            )?;
+        }
+        Ok(keymap_contents)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnmappedVsCodeKeybinding {
+    pub key: String,
+    pub command: String,
+    pub when: Option<String>,
+    pub reason: UnmappedReason,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnmappedReason {
+    UnknownCommand,
+    UnsupportedKey,
+    UnsupportedWhenClause,
+    UnsupportedArguments,
+}
+
+impl fmt::Display for UnmappedReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnmappedReason::UnknownCommand => write!(f, "no matching Zed action"),
+            UnmappedReason::UnsupportedKey => write!(f, "unsupported key"),
+            UnmappedReason::UnsupportedWhenClause => write!(f, "unsupported `when` clause"),
+            UnmappedReason::UnsupportedArguments => write!(f, "unsupported arguments"),
+        }
+    }
+}
+
+/// Returns the context, keystrokes and action of the Zed binding for a VS Code binding.
+fn translate_binding(
+    binding: &VsCodeKeybinding,
+) -> Result<(String, String, Value), UnmappedReason> {
+    // A leading `-` removes the binding of the command to the key.
+    let (command, is_removal) = match binding.command.strip_prefix('-') {
+        Some(command) => (command, true),
+        None => (binding.command.as_str(), false),
+    };
+    let (default_context, action) = match (command, &binding.args) {
+        (_, None) => action_for_command(command).ok_or(UnmappedReason::UnknownCommand)?,
+        ("type", Some(args)) => {
+            let text = args
+                .get("text")
+                .and_then(Value::as_str)
+                .ok_or(UnmappedReason::UnsupportedArguments)?;
+            (EDITOR, json!(["editor::HandleInput", text]))
+        }
+        (_, Some(_)) if is_removal => {
+            action_for_command(command).ok_or(UnmappedReason::UnknownCommand)?
+        }
+        (_, Some(_)) => return Err(UnmappedReason::UnsupportedArguments),
+    };
+    let keystrokes = translate_keystrokes(&binding.key).ok_or(UnmappedReason::UnsupportedKey)?;
+    let context = match binding.when.as_deref().map(str::trim) {
+        None | Some("") => default_context.to_string(),
+        Some(when) => {
+            match translate_when_clause(when).ok_or(UnmappedReason::UnsupportedWhenClause)? {
+                None => default_context.to_string(),
+                // The clause only replaces the command's context when it names a context of its
+                // own, like `editorTextFocus` or `terminalFocus`, and narrows it otherwise.
+                Some(predicate) if names_component(&predicate) => predicate.to_string(),
+                Some(predicate) => {
+                    let default_context = KeyBindingContextPredicate::parse(default_context)
+                        .map_err(|_| UnmappedReason::UnsupportedWhenClause)?;
+                    KeyBindingContextPredicate::And(Box::new(default_context), Box::new(predicate))
+                        .to_string()
+                }
+            }
+        }
+    };
+    let action = if is_removal { Value::Null } else { action };
+    Ok((context, keystrokes, action))
+}
+
+/// Returns whether the predicate refers to a context set by a view, like `Editor`, rather than
+/// to a key in one.
+fn names_component(predicate: &KeyBindingContextPredicate) -> bool {
+    match predicate {
+        KeyBindingContextPredicate::Identifier(name) => {
+            name.starts_with(|c: char| c.is_ascii_uppercase())
+        }
+        KeyBindingContextPredicate::Equal(..) | KeyBindingContextPredicate::NotEqual(..) => false,
+        KeyBindingContextPredicate::Not(predicate) => names_component(predicate),
+        KeyBindingContextPredicate::Descendant(left, right)
+        | KeyBindingContextPredicate::And(left, right)
+        | KeyBindingContextPredicate::Or(left, right) => {
+            names_component(left) || names_component(right)
+        }
+    }
+}
+
+/// Translates VS Code keystrokes, like `ctrl+k ctrl+c`, to Zed keystrokes, like `ctrl-k ctrl-c`.
+fn translate_keystrokes(key: &str) -> Option<String> {
+    let mut keystrokes = Vec::new();
+    for chord in key.split_whitespace() {
+        let chord = chord.to_lowercase();
+        let (modifiers, key) = match chord.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None => chord.rsplit_once('+').unwrap_or(("", chord.as_str())),
+        };
+        let mut keystroke = String::new();
+        for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+            keystroke.push_str(match modifier {
+                "ctrl" | "shift" | "alt" | "cmd" => modifier,
+                "meta" | "win" => "super",
+                _ => return None,
+            });
+            keystroke.push('-');
+        }
+        keystroke.push_str(translate_key(key)?);
+        keystrokes.push(keystroke);
+    }
+    (!keystrokes.is_empty()).then(|| keystrokes.join(" "))
+}
+
+fn translate_key(key: &str) -> Option<&str> {
+    match key {
+        "escape" | "enter" | "tab" | "backspace" | "delete" | "insert" | "home" | "end"
+        | "pageup" | "pagedown" | "up" | "down" | "left" | "right" | "space" => Some(key),
+        "contextmenu" => Some("menu"),
+        _ if key.len() == 1 && key.chars().all(|c| c.is_ascii_graphic()) => Some(key),
+        _ if key
+            .strip_prefix('f')
+            .and_then(|number| number.parse::<u8>().ok())
+            .is_some_and(|number| (1..=24).contains(&number)) =>
+        {
+            Some(key)
+        }
+        _ => None,
+    }
+}
+
+/// Translates a VS Code `when` clause to a Zed context predicate, if all of the context keys it
+/// uses have a Zed equivalent. Returns `Some(None)` for clauses that always hold in Zed.
+fn translate_when_clause(when: &str) -> Option<Option<KeyBindingContextPredicate>> {
+    let mut tokens = tokenize_when_clause(when)?;
+    remove_read_only_conditions(&mut tokens);
+    if tokens.is_empty() {
+        return Some(None);
+    }
+    let mut parser = WhenClauseParser {
+        tokens: tokens.into_iter().peekable(),
+    };
+    let predicate = parser.parse_or()?;
+    parser.tokens.peek().is_none().then_some(Some(predicate))
+}
+
+/// Removes `!editorReadonly` conditions from conjunctions, as Zed editors ignore edits to
+/// read-only buffers.
+fn remove_read_only_conditions(tokens: &mut Vec<Token<'_>>) {
+    while let Some(ix) = tokens
+        .windows(2)
+        .position(|window| window == [Token::Not, Token::Word("editorReadonly")])
+    {
+        if tokens.get(ix + 2) == Some(&Token::And) {
+            tokens.drain(ix..ix + 3);
+        } else if ix > 0 && tokens[ix - 1] == Token::And {
+            tokens.drain(ix - 1..ix + 2);
+        } else if tokens.len() == 2 {
+            tokens.clear();
+        } else {
+            break;
+        }
+    }
+}
+
+fn translate_context_key(key: &str) -> Option<KeyBindingContextPredicate> {
+    let identifier = match key {
+        "editorTextFocus" | "editorFocus" | "textInputFocus" => EDITOR,
+        "suggestWidgetVisible" => "showing_completions",
+        "parameterHintsVisible" => "showing_signature_help",
+        "inSnippetMode" => "in_snippet",
+        "hasNextTabstop" => "has_next_tabstop",
+        "hasPrevTabstop" => "has_previous_tabstop",
+        "inlineSuggestionVisible" | "inlineEditIsVisible" => "edit_prediction",
+        "terminalFocus" => TERMINAL,
+        "inQuickOpen" => "Picker",
+        "filesExplorerFocus" | "explorerViewletFocus" => PROJECT_PANEL,
+        "searchViewletFocus" => "ProjectSearchView",
+        "isMac" => return Some(equal("os", "macos")),
+        "isLinux" => return Some(equal("os", "linux")),
+        "isWindows" => return Some(equal("os", "windows")),
+        _ => return None,
+    };
+    Some(KeyBindingContextPredicate::Identifier(identifier.into()))
+}
+
+fn translate_comparison(key: &str, value: &str) -> Option<KeyBindingContextPredicate> {
+    match key {
+        "resourceExtname" => {
+            let extension = value.strip_prefix('.')?;
+            Some(equal("extension", &extension.to_lowercase()))
+        }
+        _ => None,
+    }
+}
+
+fn equal(key: &str, value: &str) -> KeyBindingContextPredicate {
+    KeyBindingContextPredicate::Equal(key.to_string().into(), value.to_string().into())
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Not,
+    And,
+    Or,
+    Equal,
+    NotEqual,
+    OpenParen,
+    CloseParen,
+    Word(&'a str),
+}
+
+fn tokenize_when_clause(when: &str) -> Option<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = when.trim_start();
+    while !rest.is_empty() {
+        let (token, len) = if rest.starts_with("&&") {
+            (Token::And, 2)
+        } else if rest.starts_with("||") {
+            (Token::Or, 2)
+        } else if rest.starts_with("===") {
+            (Token::Equal, 3)
+        } else if rest.starts_with("==") {
+            (Token::Equal, 2)
+        } else if rest.starts_with("!==") {
+            (Token::NotEqual, 3)
+        } else if rest.starts_with("!=") {
+            (Token::NotEqual, 2)
+        } else if rest.starts_with('!') {
+            (Token::Not, 1)
+        } else if rest.starts_with('(') {
+            (Token::OpenParen, 1)
+        } else if rest.starts_with(')') {
+            (Token::CloseParen, 1)
+        } else if let Some(quoted) = rest.strip_prefix('\'') {
+            let end = quoted.find('\'')?;
+            (Token::Word(&quoted[..end]), end + 2)
+        } else {
+            // Regex matches and numeric comparisons have no equivalent in Zed.
+            let len = rest
+                .find(|c: char| c.is_whitespace() || "!&|=()'<>~".contains(c))
+                .unwrap_or(rest.len());
+            if len == 0 {
+                return None;
+            }
+            (Token::Word(&rest[..len]), len)
+        };
+        tokens.push(token);
+        rest = rest[len..].trim_start();
+    }
+    Some(tokens)
+}
+
+/// Parses `when` clauses, where `!` binds tighter than `==` and `!=`, which bind tighter than
+/// `&&`, which binds tighter than `||`.
+struct WhenClauseParser<'a> {
+    tokens: Peekable<vec::IntoIter<Token<'a>>>,
+}
+
+impl WhenClauseParser<'_> {
+    fn parse_or(&mut self) -> Option<KeyBindingContextPredicate> {
+        let mut predicate = self.parse_and()?;
+        while self.tokens.next_if_eq(&Token::Or).is_some() {
+            let right = self.parse_and()?;
+            predicate = KeyBindingContextPredicate::Or(Box::new(predicate), Box::new(right));
+        }
+        Some(predicate)
+    }
+
+    fn parse_and(&mut self) -> Option<KeyBindingContextPredicate> {
+        let mut predicate = self.parse_primary()?;
+        while self.tokens.next_if_eq(&Token::And).is_some() {
+            let right = self.parse_primary()?;
+            predicate = KeyBindingContextPredicate::And(Box::new(predicate), Box::new(right));
+        }
+        Some(predicate)
+    }
+
+    fn parse_primary(&mut self) -> Option<KeyBindingContextPredicate> {
+        match self.tokens.next()? {
+            Token::Not => Some(KeyBindingContextPredicate::Not(Box::new(
+                self.parse_primary()?,
+            ))),
+            Token::OpenParen => {
+                let predicate = self.parse_or()?;
+                self.tokens.next_if_eq(&Token::CloseParen)?;
+                Some(predicate)
+            }
+            Token::Word(key) => match self.tokens.peek() {
+                Some(Token::Equal | Token::NotEqual) => {
+                    let is_equal = self.tokens.next()? == Token::Equal;
+                    let Token::Word(value) = self.tokens.next()? else {
+                        return None;
+                    };
+                    let predicate = translate_comparison(key, value)?;
+                    Some(if is_equal {
+                        predicate
+                    } else {
+                        KeyBindingContextPredicate::Not(Box::new(predicate))
+                    })
+                }
+                Some(Token::Word(_)) => None,
+                _ => translate_context_key(key),
+            },
+            Token::And | Token::Or | Token::Equal | Token::NotEqual | Token::CloseParen => None,
+        }
+    }
+}
+
+/// Returns the Zed action for a VS Code command, along with the context it's bound in by default.
+fn action_for_command(command: &str) -> Option<(&'static str, Value)> {
+    let (context, action) = match command {
+        "workbench.action.showCommands" => (WORKSPACE, json!("command_palette::Toggle")),
+        "workbench.action.quickOpen" => (WORKSPACE, json!("file_finder::Toggle")),
+        "workbench.action.showAllSymbols" => (WORKSPACE, json!("project_symbols::Toggle")),
+        "workbench.action.showAllEditors" => (WORKSPACE, json!("tab_switcher::Toggle")),
+        "workbench.action.gotoLine" => (FULL_EDITOR, json!("go_to_line::Toggle")),
+        "workbench.action.gotoSymbol" => (FULL_EDITOR, json!("outline::Toggle")),
+        "workbench.action.files.save" => (WORKSPACE, json!("workspace::Save")),
+        "workbench.action.files.saveAs" => (WORKSPACE, json!("workspace::SaveAs")),
+        "workbench.action.files.saveAll" => (WORKSPACE, json!("workspace::SaveAll")),
+        "workbench.action.files.saveWithoutFormatting" => {
+            (WORKSPACE, json!("workspace::SaveWithoutFormat"))
+        }
+        "workbench.action.files.newUntitledFile" => (WORKSPACE, json!("workspace::NewFile")),
+        "workbench.action.files.openFile" | "workbench.action.files.openFolder" => {
+            (WORKSPACE, json!("workspace::Open"))
+        }
+        "workbench.action.newWindow" => (WORKSPACE, json!("workspace::NewWindow")),
+        "workbench.action.closeWindow" => (WORKSPACE, json!("workspace::CloseWindow")),
+        "workbench.action.openRecent" => (
+            WORKSPACE,
+            json!(["projects::OpenRecent", { "create_new_window": false }]),
+        ),
+        "workbench.action.quit" => (WORKSPACE, json!("zed::Quit")),
+        "workbench.action.closeActiveEditor" => (
+            PANE,
+            json!(["pane::CloseActiveItem", { "close_pinned": false }]),
+        ),
+        "workbench.action.closeAllEditors" => (
+            PANE,
+            json!(["pane::CloseAllItems", { "close_pinned": false }]),
+        ),
+        "workbench.action.closeOtherEditors" => (
+            PANE,
+            json!(["pane::CloseOtherItems", { "close_pinned": false }]),
+        ),
+        "workbench.action.reopenClosedEditor" => (WORKSPACE, json!("pane::ReopenClosedItem")),
+        "workbench.action.nextEditor" => (PANE, json!("pane::ActivateNextItem")),
+        "workbench.action.previousEditor" => (PANE, json!("pane::ActivatePreviousItem")),
+        "workbench.action.lastEditorInGroup" => (PANE, json!("pane::ActivateLastItem")),
+        "workbench.action.splitEditor" | "workbench.action.splitEditorRight" => {
+            (PANE, json!("pane::SplitRight"))
+        }
+        "workbench.action.splitEditorLeft" => (PANE, json!("pane::SplitLeft")),
+        "workbench.action.splitEditorUp" => (PANE, json!("pane::SplitUp")),
+        "workbench.action.splitEditorDown" => (PANE, json!("pane::SplitDown")),
+        "workbench.action.navigateBack" => (PANE, json!("pane::GoBack")),
+        "workbench.action.navigateForward" => (PANE, json!("pane::GoForward")),
+        "workbench.action.pinEditor" | "workbench.action.unpinEditor" => {
+            (PANE, json!("pane::TogglePinTab"))
+        }
+        "workbench.action.focusLeftGroup" => (WORKSPACE, json!("workspace::ActivatePaneLeft")),
+        "workbench.action.focusRightGroup" => (WORKSPACE, json!("workspace::ActivatePaneRight")),
+        "workbench.action.focusAboveGroup" => (WORKSPACE, json!("workspace::ActivatePaneUp")),
+        "workbench.action.focusBelowGroup" => (WORKSPACE, json!("workspace::ActivatePaneDown")),
+        "workbench.action.toggleSidebarVisibility" => {
+            (WORKSPACE, json!("workspace::ToggleLeftDock"))
+        }
+        "workbench.action.togglePanel" => (WORKSPACE, json!("workspace::ToggleBottomDock")),
+        "workbench.action.toggleAuxiliaryBar" => (WORKSPACE, json!("workspace::ToggleRightDock")),
+        "workbench.action.toggleMaximizedPanel" => (WORKSPACE, json!("workspace::ToggleZoom")),
+        "workbench.action.toggleCenteredLayout" => {
+            (WORKSPACE, json!("workspace::ToggleCenteredLayout"))
+        }
+        "workbench.action.toggleFullScreen" => (WORKSPACE, json!("zed::ToggleFullScreen")),
+        "workbench.action.zoomIn" => (
+            WORKSPACE,
+            json!(["zed::IncreaseBufferFontSize", { "persist": false }]),
+        ),
+        "workbench.action.zoomOut" => (
+            WORKSPACE,
+            json!(["zed::DecreaseBufferFontSize", { "persist": false }]),
+        ),
+        "workbench.action.zoomReset" => (
+            WORKSPACE,
+            json!(["zed::ResetBufferFontSize", { "persist": false }]),
+        ),
+        "workbench.action.openSettings" => (WORKSPACE, json!("zed::OpenSettings")),
+        "workbench.action.openSettingsJson" => (WORKSPACE, json!("zed::OpenSettingsFile")),
+        "workbench.action.openGlobalKeybindings" => (WORKSPACE, json!("zed::OpenKeymap")),
+        "workbench.action.openGlobalKeybindingsFile" => (WORKSPACE, json!("zed::OpenKeymapFile")),
+        "workbench.action.selectTheme" => (WORKSPACE, json!("theme_selector::Toggle")),
+        "workbench.action.editor.changeLanguageMode" => {
+            (WORKSPACE, json!("language_selector::Toggle"))
+        }
+        "workbench.action.findInFiles" | "workbench.view.search" => {
+            (WORKSPACE, json!("pane::DeploySearch"))
+        }
+        "workbench.action.replaceInFiles" => (
+            WORKSPACE,
+            json!(["pane::DeploySearch", { "replace_enabled": true }]),
+        ),
+        "workbench.view.explorer" => (WORKSPACE, json!("project_panel::ToggleFocus")),
+        "workbench.view.scm" => (WORKSPACE, json!("git_panel::ToggleFocus")),
+        "workbench.view.debug" => (WORKSPACE, json!("debug_panel::ToggleFocus")),
+        "workbench.view.extensions" => (WORKSPACE, json!("zed::Extensions")),
+        "workbench.actions.view.problems" => (WORKSPACE, json!("diagnostics::Deploy")),
+        "workbench.action.tasks.runTask" => (WORKSPACE, json!("task::Spawn")),
+        "workbench.action.tasks.reRunTask" => (WORKSPACE, json!("task::Rerun")),
+        "workbench.action.terminal.toggleTerminal" => (WORKSPACE, json!("terminal_panel::Toggle")),
+        "workbench.action.terminal.focus" => (WORKSPACE, json!("terminal_panel::ToggleFocus")),
+        "workbench.action.terminal.new" => (WORKSPACE, json!("workspace::NewTerminal")),
+        "workbench.action.terminal.clear" => (TERMINAL, json!("terminal::Clear")),
+        "workbench.action.terminal.copySelection" => (TERMINAL, json!("terminal::Copy")),
+        "workbench.action.terminal.paste" => (TERMINAL, json!("terminal::Paste")),
+        "workbench.action.terminal.scrollUp" => (TERMINAL, json!("terminal::ScrollLineUp")),
+        "workbench.action.terminal.scrollDown" => (TERMINAL, json!("terminal::ScrollLineDown")),
+        "workbench.action.terminal.scrollUpPage" => (TERMINAL, json!("terminal::ScrollPageUp")),
+        "workbench.action.terminal.scrollDownPage" => (TERMINAL, json!("terminal::ScrollPageDown")),
+        "workbench.action.terminal.scrollToTop" => (TERMINAL, json!("terminal::ScrollToTop")),
+        "workbench.action.terminal.scrollToBottom" => (TERMINAL, json!("terminal::ScrollToBottom")),
+        "workbench.action.debug.start" => (WORKSPACE, json!("debugger::Start")),
+        "workbench.action.debug.stop" => (WORKSPACE, json!("debugger::Stop")),
+        "workbench.action.debug.restart" => (WORKSPACE, json!("debugger::RerunSession")),
+        "workbench.action.debug.continue" => (WORKSPACE, json!("debugger::Continue")),
+        "workbench.action.debug.pause" => (WORKSPACE, json!("debugger::Pause")),
+        "workbench.action.debug.stepOver" => (WORKSPACE, json!("debugger::StepOver")),
+        "workbench.action.debug.stepInto" => (WORKSPACE, json!("debugger::StepInto")),
+        "workbench.action.debug.stepOut" => (WORKSPACE, json!("debugger::StepOut")),
+        "editor.debug.action.toggleBreakpoint" => (EDITOR, json!("editor::ToggleBreakpoint")),
+        "explorer.newFile" => (PROJECT_PANEL, json!("project_panel::NewFile")),
+        "explorer.newFolder" => (PROJECT_PANEL, json!("project_panel::NewDirectory")),
+        "renameFile" => (PROJECT_PANEL, json!("project_panel::Rename")),
+        "deleteFile" => (
+            PROJECT_PANEL,
+            json!(["project_panel::Trash", { "skip_prompt": false }]),
+        ),
+        "filesExplorer.copy" => (PROJECT_PANEL, json!("project_panel::Copy")),
+        "filesExplorer.cut" => (PROJECT_PANEL, json!("project_panel::Cut")),
+        "filesExplorer.paste" => (PROJECT_PANEL, json!("project_panel::Paste")),
+        "copyFilePath" => (PROJECT_PANEL, json!("project_panel::CopyPath")),
+        "copyRelativeFilePath" => (PROJECT_PANEL, json!("workspace::CopyRelativePath")),
+        "revealFileInOS" => (PROJECT_PANEL, json!("project_panel::RevealInFileManager")),
+        "workbench.files.action.collapseExplorerFolders" => {
+            (PROJECT_PANEL, json!("project_panel::CollapseAllEntries"))
+        }
+        "undo" => (EDITOR, json!("editor::Undo")),
+        "redo" => (EDITOR, json!("editor::Redo")),
+        "editor.action.clipboardCutAction" => (EDITOR, json!("editor::Cut")),
+        "editor.action.clipboardCopyAction" => (EDITOR, json!("editor::Copy")),
+        "editor.action.clipboardPasteAction" => (EDITOR, json!("editor::Paste")),
+        "editor.action.selectAll" => (EDITOR, json!("editor::SelectAll")),
+        "expandLineSelection" => (EDITOR, json!("editor::SelectLine")),
+        "cursorUndo" => (EDITOR, json!("editor::UndoSelection")),
+        "cursorRedo" => (EDITOR, json!("editor::RedoSelection")),
+        "cursorUp" => (EDITOR, json!("editor::MoveUp")),
+        "cursorDown" => (EDITOR, json!("editor::MoveDown")),
+        "cursorLeft" => (EDITOR, json!("editor::MoveLeft")),
+        "cursorRight" => (EDITOR, json!("editor::MoveRight")),
+        "cursorWordLeft" => (EDITOR, json!("editor::MoveToPreviousWordStart")),
+        "cursorWordRight" | "cursorWordEndRight" => (EDITOR, json!("editor::MoveToNextWordEnd")),
+        "cursorHome" | "cursorLineStart" => (EDITOR, json!("editor::MoveToBeginningOfLine")),
+        "cursorEnd" | "cursorLineEnd" => (EDITOR, json!("editor::MoveToEndOfLine")),
+        "cursorTop" => (EDITOR, json!("editor::MoveToBeginning")),
+        "cursorBottom" => (EDITOR, json!("editor::MoveToEnd")),
+        "cursorPageUp" => (EDITOR, json!("editor::MovePageUp")),
+        "cursorPageDown" => (EDITOR, json!("editor::MovePageDown")),
+        "cursorUpSelect" => (EDITOR, json!("editor::SelectUp")),
+        "cursorDownSelect" => (EDITOR, json!("editor::SelectDown")),
+        "cursorLeftSelect" => (EDITOR, json!("editor::SelectLeft")),
+        "cursorRightSelect" => (EDITOR, json!("editor::SelectRight")),
+        "cursorWordLeftSelect" => (EDITOR, json!("editor::SelectToPreviousWordStart")),
+        "cursorWordRightSelect" | "cursorWordEndRightSelect" => {
+            (EDITOR, json!("editor::SelectToNextWordEnd"))
+        }
+        "cursorHomeSelect" => (EDITOR, json!("editor::SelectToBeginningOfLine")),
+        "cursorEndSelect" => (EDITOR, json!("editor::SelectToEndOfLine")),
+        "cursorTopSelect" => (EDITOR, json!("editor::SelectToBeginning")),
+        "cursorBottomSelect" => (EDITOR, json!("editor::SelectToEnd")),
+        "scrollLineUp" => (EDITOR, json!("editor::LineUp")),
+        "scrollLineDown" => (EDITOR, json!("editor::LineDown")),
+        "deleteLeft" => (EDITOR, json!("editor::Backspace")),
+        "deleteRight" => (EDITOR, json!("editor::Delete")),
+        "deleteWordLeft" => (EDITOR, json!("editor::DeleteToPreviousWordStart")),
+        "deleteWordRight" => (EDITOR, json!("editor::DeleteToNextWordEnd")),
+        "tab" => (EDITOR, json!("editor::Tab")),
+        "outdent" => (EDITOR, json!("editor::Backtab")),
+        "editor.action.indentLines" => (EDITOR, json!("editor::Indent")),
+        "editor.action.outdentLines" => (EDITOR, json!("editor::Outdent")),
+        "editor.action.insertLineAfter" => (FULL_EDITOR, json!("editor::NewlineBelow")),
+        "editor.action.insertLineBefore" => (FULL_EDITOR, json!("editor::NewlineAbove")),
+        "editor.action.deleteLines" => (EDITOR, json!("editor::DeleteLine")),
+        "editor.action.joinLines" => (EDITOR, json!("editor::JoinLines")),
+        "editor.action.moveLinesUpAction" => (EDITOR, json!("editor::MoveLineUp")),
+        "editor.action.moveLinesDownAction" => (EDITOR, json!("editor::MoveLineDown")),
+        "editor.action.copyLinesUpAction" => (EDITOR, json!("editor::DuplicateLineUp")),
+        "editor.action.copyLinesDownAction" => (EDITOR, json!("editor::DuplicateLineDown")),
+        "editor.action.commentLine" => (
+            EDITOR,
+            json!(["editor::ToggleComments", { "advance_downwards": false }]),
+        ),
+        "editor.action.transformToUppercase" => (EDITOR, json!("editor::ConvertToUpperCase")),
+        "editor.action.transformToLowercase" => (EDITOR, json!("editor::ConvertToLowerCase")),
+        "editor.action.sortLinesAscending" => (EDITOR, json!("editor::SortLinesCaseSensitive")),
+        "editor.action.formatDocument" => (EDITOR, json!("editor::Format")),
+        "editor.action.organizeImports" => (EDITOR, json!("editor::OrganizeImports")),
+        "editor.action.rename" => (EDITOR, json!("editor::Rename")),
+        "editor.action.revealDefinition" | "editor.action.goToDeclaration" => {
+            (EDITOR, json!("editor::GoToDefinition"))
+        }
+        "editor.action.revealDefinitionAside" => (EDITOR, json!("editor::GoToDefinitionSplit")),
+        "editor.action.goToTypeDefinition" => (EDITOR, json!("editor::GoToTypeDefinition")),
+        "editor.action.goToImplementation" => (EDITOR, json!("editor::GoToImplementation")),
+        "editor.action.goToReferences" | "references-view.findReferences" => {
+            (EDITOR, json!("editor::FindAllReferences"))
+        }
+        "editor.action.showHover" => (EDITOR, json!("editor::Hover")),
+        "editor.action.quickFix" => (EDITOR, json!("editor::ToggleCodeActions")),
+        "editor.action.triggerSuggest" => (EDITOR, json!("editor::ShowCompletions")),
+        "editor.action.triggerParameterHints" => (EDITOR, json!("editor::ShowSignatureHelp")),
+        "acceptSelectedSuggestion" => (EDITOR, json!("editor::ConfirmCompletion")),
+        "hideSuggestWidget" => (EDITOR, json!("editor::Cancel")),
+        "editor.action.inlineSuggest.commit" => (EDITOR, json!("editor::AcceptEditPrediction")),
+        "editor.action.showContextMenu" => (EDITOR, json!("editor::OpenContextMenu")),
+        "editor.action.marker.next" | "editor.action.marker.nextInFiles" => {
+            (EDITOR, json!("editor::GoToDiagnostic"))
+        }
+        "editor.action.marker.prev" | "editor.action.marker.prevInFiles" => {
+            (EDITOR, json!("editor::GoToPreviousDiagnostic"))
+        }
+        "editor.action.dirtydiff.next" => (EDITOR, json!("editor::GoToHunk")),
+        "editor.action.dirtydiff.previous" => (EDITOR, json!("editor::GoToPreviousHunk")),
+        "editor.action.addSelectionToNextFindMatch" => (
+            EDITOR,
+            json!(["editor::SelectNext", { "replace_newest": false }]),
+        ),
+        "editor.action.moveSelectionToNextFindMatch" => (
+            EDITOR,
+            json!(["editor::SelectNext", { "replace_newest": true }]),
+        ),
+        "editor.action.addSelectionToPreviousFindMatch" => (
+            EDITOR,
+            json!(["editor::SelectPrevious", { "replace_newest": false }]),
+        ),
+        "editor.action.selectHighlights" | "editor.action.changeAll" => {
+            (EDITOR, json!("editor::SelectAllMatches"))
+        }
+        "editor.action.insertCursorAbove" => (EDITOR, json!("editor::AddSelectionAbove")),
+        "editor.action.insertCursorBelow" => (EDITOR, json!("editor::AddSelectionBelow")),
+        "editor.action.smartSelect.expand" => (EDITOR, json!("editor::SelectLargerSyntaxNode")),
+        "editor.action.smartSelect.shrink" => (EDITOR, json!("editor::SelectSmallerSyntaxNode")),
+        "editor.action.jumpToBracket" => (EDITOR, json!("editor::MoveToEnclosingBracket")),
+        "editor.fold" => (EDITOR, json!("editor::Fold")),
+        "editor.unfold" => (EDITOR, json!("editor::UnfoldLines")),
+        "editor.toggleFold" => (EDITOR, json!("editor::ToggleFold")),
+        "editor.foldRecursively" => (EDITOR, json!("editor::FoldRecursive")),
+        "editor.unfoldRecursively" => (EDITOR, json!("editor::UnfoldRecursive")),
+        "editor.foldAll" => (EDITOR, json!("editor::FoldAll")),
+        "editor.unfoldAll" => (EDITOR, json!("editor::UnfoldAll")),
+        "editor.action.toggleWordWrap" => (FULL_EDITOR, json!("editor::ToggleSoftWrap")),
+        "actions.find" => (FULL_EDITOR, json!("buffer_search::Deploy")),
+        "editor.action.startFindReplaceAction" => {
+            (FULL_EDITOR, json!("buffer_search::DeployReplace"))
+        }
+        "editor.action.nextMatchFindAction" => (PANE, json!("search::SelectNextMatch")),
+        "editor.action.previousMatchFindAction" => (PANE, json!("search::SelectPreviousMatch")),
+        _ => return None,
+    };
+    Some((context, action))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn import(content: &str) -> VsCodeKeymapImport {
+        VsCodeKeybindings::from_str(content, VsCodeSettingsSource::VsCode)
+            .unwrap()
+            .import()
+    }
+
+    #[test]
+    fn test_translating_keystrokes() {
+        assert_eq!(
+            translate_keystrokes("ctrl+shift+p").as_deref(),
+            Some("ctrl-shift-p")
+        );
+        assert_eq!(
+            translate_keystrokes("ctrl+k ctrl+c").as_deref(),
+            Some("ctrl-k ctrl-c")
+        );
+        assert_eq!(
+            translate_keystrokes("Cmd+Alt+Up").as_deref(),
+            Some("cmd-alt-up")
+        );
+        assert_eq!(
+            translate_keystrokes("meta+f12").as_deref(),
+            Some("super-f12")
+        );
+        assert_eq!(translate_keystrokes("ctrl++").as_deref(), Some("ctrl-+"));
+        assert_eq!(translate_keystrokes("ctrl+-").as_deref(), Some("ctrl--"));
+        assert_eq!(
+            translate_keystrokes("shift+contextmenu").as_deref(),
+            Some("shift-menu")
+        );
+        assert_eq!(translate_keystrokes("ctrl+numpad_add"), None);
+        assert_eq!(translate_keystrokes("ctrl+[KeyA]"), None);
+        assert_eq!(translate_keystrokes("hyper+a"), None);
+    }
+
+    #[test]
+    fn test_translating_when_clauses() {
+        let translate = |when| {
+            translate_when_clause(when)
+                .map(|predicate| predicate.map_or(String::new(), |predicate| predicate.to_string()))
+        };
+        assert_eq!(translate("editorTextFocus").as_deref(), Some("Editor"));
+        assert_eq!(
+            translate("editorTextFocus && !suggestWidgetVisible").as_deref(),
+            Some("Editor && !showing_completions")
+        );
+        assert_eq!(
+            translate("(terminalFocus || editorFocus) && isMac").as_deref(),
+            Some("(Terminal || Editor) && os == macos")
+        );
+        assert_eq!(
+            translate("editorTextFocus && resourceExtname == '.RS'").as_deref(),
+            Some("Editor && extension == rs")
+        );
+        assert_eq!(
+            translate("resourceExtname != .md").as_deref(),
+            Some("!(extension == md)")
+        );
+        assert_eq!(
+            translate("editorTextFocus && !editorReadonly").as_deref(),
+            Some("Editor")
+        );
+        assert_eq!(
+            translate("!editorReadonly && editorTextFocus || inQuickOpen").as_deref(),
+            Some("Editor || Picker")
+        );
+        assert_eq!(translate("!editorReadonly").as_deref(), Some(""));
+        assert_eq!(translate("editorTextFocus && editorReadonly"), None);
+        assert_eq!(translate("editorLangId == rust"), None);
+        assert_eq!(translate("resourceFilename =~ /test/"), None);
+        assert_eq!(translate("resourceExtname in someList"), None);
+        assert_eq!(translate("(editorTextFocus"), None);
+        assert_eq!(translate("editorTextFocus &&"), None);
+    }
+
+    #[test]
+    fn test_importing_keybindings() {
+        let import = import(indoc! {r#"
+            // Place your key bindings in this file to override the defaults
+            [
+                { "key": "ctrl+shift+d", "command": "editor.action.copyLinesDownAction", "when": "editorTextFocus && !editorReadonly" },
+                { "key": "ctrl+d", "command": "editor.action.copyLinesDownAction", "when": "editorTextFocus" },
+                { "key": "ctrl+d", "command": "-editor.action.addSelectionToNextFindMatch", "when": "editorFocus" },
+                { "key": "ctrl+alt+f", "command": "editor.action.formatDocument", "when": "resourceExtname == .rs" },
+                { "key": "ctrl+e", "command": "workbench.action.quickOpen" },
+                { "key": "ctrl+'", "command": "workbench.action.terminal.toggleTerminal", "when": "terminal.active" },
+                { "key": "alt+t", "command": "type", "args": { "text": "->" }, "when": "editorTextFocus" },
+                { "key": "alt+r", "command": "workbench.action.tasks.runTask", "args": "build" },
+                { "key": "ctrl+numpad1", "command": "editor.action.commentLine" },
+                { "key": "ctrl+shift+u", "command": "gitlens.showCommitSearch" },
+            ]
+        "#});
+
+        assert_eq!(
+            import.sections,
+            IndexMap::from_iter([
+                (
+                    "Editor".to_string(),
+                    IndexMap::from_iter([
+                        (
+                            "ctrl-shift-d".to_string(),
+                            json!("editor::DuplicateLineDown")
+                        ),
+                        ("ctrl-d".to_string(), json!("editor::DuplicateLineDown")),
+                        ("alt-t".to_string(), json!(["editor::HandleInput", "->"])),
+                    ])
+                ),
+                (
+                    "Editor && extension == rs".to_string(),
+                    IndexMap::from_iter([("ctrl-alt-f".to_string(), json!("editor::Format"))])
+                ),
+                (
+                    "Workspace".to_string(),
+                    IndexMap::from_iter([("ctrl-e".to_string(), json!("file_finder::Toggle"))])
+                ),
+            ])
+        );
+        assert_eq!(import.binding_count(), 5);
+        assert_eq!(
+            import
+                .unmapped
+                .iter()
+                .map(|binding| (binding.key.as_str(), binding.reason))
+                .collect::<Vec<_>>(),
+            [
+                ("ctrl+'", UnmappedReason::UnsupportedWhenClause),
+                ("alt+r", UnmappedReason::UnsupportedArguments),
+                ("ctrl+numpad1", UnmappedReason::UnsupportedKey),
+                ("ctrl+shift+u", UnmappedReason::UnknownCommand),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_applying_import_to_keymap() {
+        let import = import(indoc! {r#"
+            [
+                { "key": "ctrl+e", "command": "workbench.action.quickOpen" },
+                { "key": "ctrl+k ctrl+/", "command": "editor.action.commentLine", "when": "editorTextFocus" },
+                { "key": "ctrl+shift+k", "command": "-editor.action.deleteLines", "when": "editorTextFocus" },
+            ]
+        "#});
+        let keymap = indoc! {r#"
+            [
+              {
+                "context": "Workspace",
+                "bindings": {
+                  "ctrl-e": "file_finder::Toggle"
+                }
+              }
+            ]
+        "#};
+
+        let keymap = import.apply_to_keymap(keymap.to_string(), 2).unwrap();
+        pretty_assertions::assert_eq!(
+            keymap,
+            indoc! {r#"
+                [
+                  {
+                    "context": "Workspace",
+                    "bindings": {
+                      "ctrl-e": "file_finder::Toggle"
+                    }
+                  },
+                  {
+                    "context": "Editor",
+                    "bindings": {
+                      "ctrl-k ctrl-/": [
+                        "editor::ToggleComments",
+                        {
+                          "advance_downwards": false
+                        }
+                      ],
+                      "ctrl-shift-k": null
+                    }
+                  }
+                ]
+            "#}
+        );
+
+        // Importing again leaves the keymap as it is.
+        assert_eq!(import.apply_to_keymap(keymap.clone(), 2).unwrap(), keymap);
+    }
+}
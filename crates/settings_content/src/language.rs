@@ -499,6 +499,15 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: ["..."]
     pub language_servers: Option<Vec<String>>,
+    /// The list of linters provided by extensions to run (or disable) for this language.
+    ///
+    /// This array should consist of linter names, as well as the following
+    /// special tokens:
+    /// - `"!<linter_name>"` - A linter name prefixed with a `!` will be disabled.
+    /// - `"..."` - A placeholder to refer to the **rest** of the registered linters for this language.
+    ///
+    /// Default: ["..."]
+    pub linters: Option<LinterList>,
     /// Controls how semantic tokens from language servers are used for syntax highlighting.
     ///
     /// Options:
@@ -947,6 +956,17 @@ impl AsRef<[Formatter]> for FormatterList {
     }
 }
 
+/// Controls which extension linters should run for a language.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema, MergeFrom)]
+#[serde(transparent)]
+pub struct LinterList(pub Vec<String>);
+
+impl AsRef<[String]> for LinterList {
+    fn as_ref(&self) -> &[String] {
+        &self.0
+    }
+}
+
 /// Controls which formatter should be used when formatting code. If there are multiple formatters, they are executed in the order of declaration.
 #[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema, MergeFrom)]
 #[serde(rename_all = "snake_case")]
@@ -966,6 +986,8 @@ pub enum Formatter {
         /// The arguments to pass to the program.
         arguments: Option<Vec<String>>,
     },
+    /// Format code using a formatter provided by an extension.
+    Extension(String),
     /// Files should be formatted using a code action executed by language servers.
     CodeAction(String),
     /// Format code using a language server.
@@ -1200,6 +1222,16 @@ mod test {
             serde_json::to_string(&LanguageServerFormatterSpecifier::Current).unwrap(),
             "\"language_server\"",
         );
+
+        let raw = "{\"formatter\": [{\"extension\": \"ruff\"}, \"language_server\"]}";
+        let settings: LanguageSettingsContent = serde_json::from_str(raw).unwrap();
+        assert_eq!(
+            settings.formatter,
+            Some(FormatterList::Vec(vec![
+                Formatter::Extension("ruff".to_string()),
+                Formatter::LanguageServer(LanguageServerFormatterSpecifier::Current),
+            ]))
+        );
     }
 
     #[test]
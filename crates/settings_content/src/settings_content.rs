@@ -151,6 +151,10 @@ pub struct SettingsContent {
 
     pub journal: Option<JournalSettingsContent>,
 
+    pub spell_check: Option<SpellCheckSettingsContent>,
+
+    pub markdown_notes: Option<MarkdownNotesSettingsContent>,
+
     /// A map of log scopes to the desired log level.
     /// Useful for filtering out noisy logs or enabling more verbose logging.
     ///
@@ -626,6 +630,11 @@ pub struct GitPanelSettingsContent {
     /// Default: false
     pub collapse_untracked_diff: Option<bool>,
 
+    /// How cell outputs and metadata are shown in diffs of Jupyter notebooks.
+    ///
+    /// Default: collapsed
+    pub notebook_diff_outputs: Option<NotebookDiffOutputs>,
+
     /// Whether to show entries with tree or flat view in the panel
     ///
     /// Default: false
@@ -668,6 +677,32 @@ pub enum StatusStyle {
     LabelColor,
 }
 
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    Debug,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    MergeFrom,
+    PartialEq,
+    Eq,
+    strum::VariantArray,
+    strum::VariantNames,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum NotebookDiffOutputs {
+    /// Summarize each cell's outputs and metadata on a single line that
+    /// changes whenever they do.
+    #[default]
+    Collapsed,
+    /// Show the text of each cell's outputs and its metadata.
+    Expanded,
+    /// Leave outputs and metadata out of the diff.
+    Hidden,
+}
+
 #[with_fallible_options]
 #[derive(
     Copy, Clone, Default, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq, Eq,
@@ -934,6 +969,37 @@ pub enum HourFormat {
     Hour24,
 }
 
+/// Settings specific to spell checking
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
+pub struct SpellCheckSettingsContent {
+    /// Whether to check the spelling of comments, strings and prose files such as Markdown.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// Paths to additional Hunspell dictionaries. Each path names a `.dic` file, whose `.aff`
+    /// file must be alongside it.
+    ///
+    /// Default: []
+    pub dictionaries: Option<Vec<String>>,
+}
+
+/// Settings specific to Markdown notes
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
+pub struct MarkdownNotesSettingsContent {
+    /// Whether to treat the Markdown files of local projects as linked notes, completing and
+    /// following their links and reporting broken links as warnings.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// Whether to update the links to a note, and save the notes containing them, when the note
+    /// is renamed or moved from the project panel.
+    ///
+    /// Default: false
+    pub rewrite_links_on_rename: Option<bool>,
+}
+
 #[with_fallible_options]
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema, MergeFrom, Debug, PartialEq)]
 pub struct OutlinePanelSettingsContent {
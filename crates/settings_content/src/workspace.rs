@@ -1,6 +1,6 @@
 use std::num::NonZeroUsize;
 
-use collections::HashMap;
+use collections::{HashMap, IndexMap};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings_macros::{MergeFrom, with_fallible_options};
@@ -743,6 +743,39 @@ pub struct ProjectPanelSettingsContent {
     ///
     /// Default: true
     pub diagnostic_badges: Option<bool>,
+    /// Settings for grouping related files under a parent file.
+    pub file_nesting: Option<ProjectPanelFileNestingContent>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq, Eq)]
+pub struct ProjectPanelFileNestingContent {
+    /// Whether to nest related files under a parent file in the project panel.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// Maps a parent file name pattern to a comma-separated list of the file name patterns
+    /// nested under it. A `*` in the parent pattern matches any text, which `${capture}`
+    /// stands for in the nested patterns, and a `*` in a nested pattern matches any text.
+    ///
+    /// For example, `"*.ts": "${capture}.js, ${capture}.js.map"` nests `foo.js` and
+    /// `foo.js.map` under `foo.ts`.
+    ///
+    /// Setting this replaces the default patterns rather than adding to them, so that `{}`
+    /// removes every pattern.
+    pub patterns: Option<FileNestingPatterns>,
+}
+
+/// File nesting patterns, which are replaced as a whole rather than merged key by key, so that
+/// the default patterns can be removed.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct FileNestingPatterns(pub IndexMap<String, String>);
+
+impl crate::merge_from::MergeFrom for FileNestingPatterns {
+    fn merge_from(&mut self, other: &Self) {
+        *self = other.clone();
+    }
 }
 
 #[derive(
@@ -1,7 +1,9 @@
 mod dropdown;
 mod font_picker;
+mod formatter_dropdown;
 mod icon_theme_picker;
 mod input_field;
+mod linter_dropdown;
 mod number_field;
 mod ollama_model_picker;
 mod section_items;
@@ -9,8 +11,10 @@ mod theme_picker;
 
 pub use dropdown::*;
 pub use font_picker::font_picker;
+pub use formatter_dropdown::render_formatter_dropdown;
 pub use icon_theme_picker::icon_theme_picker;
 pub use input_field::*;
+pub use linter_dropdown::render_linter_dropdown;
 pub use number_field::*;
 pub use ollama_model_picker::render_ollama_model_picker;
 pub use section_items::*;
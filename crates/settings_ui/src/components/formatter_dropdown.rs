@@ -0,0 +1,158 @@
+use gpui::{AnyElement, App, ReadGlobal, SharedString, Window};
+use itertools::Itertools as _;
+use language::{ExternalToolRegistry, LanguageName};
+use settings::{Formatter, FormatterList, LanguageServerFormatterSpecifier, SettingsStore};
+use ui::{ContextMenu, DropdownMenu, DropdownStyle, IconPosition, IntoElement};
+use util::ResultExt as _;
+
+use crate::{
+    SettingField, SettingsFieldMetadata, SettingsUiFile, active_language, update_settings_file,
+};
+
+fn formatter_label(formatter: &Formatter) -> SharedString {
+    match formatter {
+        Formatter::Auto => "Auto".into(),
+        Formatter::None => "None".into(),
+        Formatter::Prettier => "Prettier".into(),
+        Formatter::LanguageServer(LanguageServerFormatterSpecifier::Current) => {
+            "Language Server".into()
+        }
+        Formatter::LanguageServer(LanguageServerFormatterSpecifier::Specific { name }) => {
+            format!("Language Server ({name})").into()
+        }
+        Formatter::Extension(name) => name.clone().into(),
+        Formatter::External { command, .. } => command.clone().into(),
+        Formatter::CodeAction(action) => action.clone().into(),
+    }
+}
+
+/// Returns the formatter list after picking `formatter` in the dropdown.
+///
+/// Picking a formatter replaces a single formatter, while a list of several
+/// formatters gains or loses the picked one so that the rest of it is kept.
+fn toggle_formatter(current: &FormatterList, formatter: Formatter) -> FormatterList {
+    let FormatterList::Vec(formatters) = current else {
+        return FormatterList::Single(formatter);
+    };
+    let mut formatters = formatters.clone();
+    if let Some(ix) = formatters
+        .iter()
+        .position(|existing| *existing == formatter)
+    {
+        if formatters.len() > 1 {
+            formatters.remove(ix);
+        }
+    } else {
+        formatters.push(formatter);
+    }
+    match formatters.as_slice() {
+        [single] => FormatterList::Single(single.clone()),
+        _ => FormatterList::Vec(formatters),
+    }
+}
+
+/// Renders a dropdown for picking the formatters to use, including the
+/// formatters provided by extensions for the active language.
+pub fn render_formatter_dropdown(
+    field: SettingField<FormatterList>,
+    file: SettingsUiFile,
+    _metadata: Option<&SettingsFieldMetadata>,
+    window: &mut Window,
+    cx: &mut App,
+) -> AnyElement {
+    let (_, current_value) =
+        SettingsStore::global(cx).get_value_from_file(file.to_settings(), field.pick);
+    let current_value = current_value.cloned().unwrap_or_default();
+    let current_label: SharedString = current_value
+        .as_ref()
+        .iter()
+        .map(formatter_label)
+        .join(", ")
+        .into();
+
+    let language = active_language().map(LanguageName::from);
+    let mut formatters = vec![
+        Formatter::Auto,
+        Formatter::None,
+        Formatter::Prettier,
+        Formatter::LanguageServer(LanguageServerFormatterSpecifier::Current),
+    ];
+    if let Some(registry) = ExternalToolRegistry::try_global(cx) {
+        formatters.extend(
+            registry
+                .formatter_names(language.as_ref())
+                .into_iter()
+                .map(|name| Formatter::Extension(name.to_string())),
+        );
+    }
+
+    let menu = ContextMenu::build(window, cx, move |mut menu, _, _| {
+        for formatter in &formatters {
+            let formatter = formatter.clone();
+            let is_current = current_value.as_ref().contains(&formatter);
+            let label = formatter_label(&formatter);
+            let file = file.clone();
+            let current_value = current_value.clone();
+            menu = menu.toggleable_entry(
+                label,
+                is_current,
+                IconPosition::Start,
+                None,
+                move |window, cx| {
+                    let value = toggle_formatter(&current_value, formatter.clone());
+                    update_settings_file(
+                        file.clone(),
+                        field.json_path,
+                        window,
+                        cx,
+                        move |settings, _cx| {
+                            (field.write)(settings, Some(value));
+                        },
+                    )
+                    .log_err(); // todo(settings_ui) don't log err
+                },
+            );
+        }
+        menu
+    });
+
+    DropdownMenu::new("formatter-dropdown", current_label, menu)
+        .style(DropdownStyle::Outlined)
+        .full_width(true)
+        .into_any_element()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_formatter() {
+        let prettier = Formatter::Prettier;
+        let language_server = Formatter::LanguageServer(LanguageServerFormatterSpecifier::Current);
+        let ruff = Formatter::Extension("ruff".to_string());
+
+        // Picking a formatter replaces a single formatter.
+        assert_eq!(
+            toggle_formatter(&FormatterList::Single(prettier.clone()), ruff.clone()),
+            FormatterList::Single(ruff.clone())
+        );
+
+        // Picking a formatter appends it to a list of formatters.
+        let list = FormatterList::Vec(vec![prettier.clone(), language_server.clone()]);
+        assert_eq!(
+            toggle_formatter(&list, ruff.clone()),
+            FormatterList::Vec(vec![
+                prettier.clone(),
+                language_server.clone(),
+                ruff.clone()
+            ])
+        );
+
+        // Picking a formatter from a list removes only that formatter.
+        assert_eq!(
+            toggle_formatter(&list, prettier.clone()),
+            FormatterList::Single(language_server.clone())
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use gpui::{AnyElement, App, ReadGlobal, SharedString, Window};
+use itertools::Itertools as _;
+use language::{ExternalToolRegistry, LanguageName};
+use settings::{LinterList, SettingsStore};
+use ui::{ContextMenu, DropdownMenu, DropdownStyle, IconPosition, IntoElement};
+use util::ResultExt as _;
+
+use crate::{
+    SettingField, SettingsFieldMetadata, SettingsUiFile, active_language, update_settings_file,
+};
+
+const REST_OF_LINTERS: &str = "...";
+
+/// Returns whether the given linter is enabled by the configured list of linters.
+fn is_linter_enabled(linters: &LinterList, name: &str) -> bool {
+    let disabled = linters
+        .0
+        .iter()
+        .any(|linter| linter.strip_prefix('!') == Some(name));
+    !disabled
+        && linters
+            .0
+            .iter()
+            .any(|linter| linter == name || linter == REST_OF_LINTERS)
+}
+
+/// Returns the linter list after toggling the given linter in the dropdown.
+fn toggle_linter(linters: &LinterList, name: &str) -> LinterList {
+    let enabled = is_linter_enabled(linters, name);
+    let disabled_name = format!("!{name}");
+    let mut linters = linters
+        .0
+        .iter()
+        .filter(|linter| *linter != name && **linter != disabled_name)
+        .cloned()
+        .collect::<Vec<_>>();
+    if enabled {
+        linters.insert(0, disabled_name);
+    } else if !linters.iter().any(|linter| linter == REST_OF_LINTERS) {
+        linters.push(name.to_string());
+    }
+    LinterList(linters)
+}
+
+/// Renders a dropdown for enabling or disabling the linters provided by
+/// extensions for the active language.
+pub fn render_linter_dropdown(
+    field: SettingField<LinterList>,
+    file: SettingsUiFile,
+    _metadata: Option<&SettingsFieldMetadata>,
+    window: &mut Window,
+    cx: &mut App,
+) -> AnyElement {
+    let (_, current_value) =
+        SettingsStore::global(cx).get_value_from_file(file.to_settings(), field.pick);
+    let current_value = current_value.cloned().unwrap_or_default();
+
+    let language = active_language().map(LanguageName::from);
+    let linters: Vec<Arc<str>> = ExternalToolRegistry::try_global(cx)
+        .map(|registry| registry.linter_names(language.as_ref()))
+        .unwrap_or_default();
+
+    let enabled_linters = linters
+        .iter()
+        .filter(|name| is_linter_enabled(&current_value, name))
+        .join(", ");
+    let current_label: SharedString = if linters.is_empty() {
+        "No Linters Installed".into()
+    } else if enabled_linters.is_empty() {
+        "None".into()
+    } else {
+        enabled_linters.into()
+    };
+
+    let menu = ContextMenu::build(window, cx, move |mut menu, _, _| {
+        for name in &linters {
+            let name = name.clone();
+            let is_enabled = is_linter_enabled(&current_value, &name);
+            let file = file.clone();
+            let current_value = current_value.clone();
+            menu = menu.toggleable_entry(
+                SharedString::from(name.to_string()),
+                is_enabled,
+                IconPosition::Start,
+                None,
+                move |window, cx| {
+                    let value = toggle_linter(&current_value, &name);
+                    update_settings_file(
+                        file.clone(),
+                        field.json_path,
+                        window,
+                        cx,
+                        move |settings, _cx| {
+                            (field.write)(settings, Some(value));
+                        },
+                    )
+                    .log_err(); // todo(settings_ui) don't log err
+                },
+            );
+        }
+        menu
+    });
+
+    DropdownMenu::new("linter-dropdown", current_label, menu)
+        .style(DropdownStyle::Outlined)
+        .full_width(true)
+        .into_any_element()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linter_list(linters: &[&str]) -> LinterList {
+        LinterList(linters.iter().map(|linter| linter.to_string()).collect())
+    }
+
+    #[test]
+    fn test_toggle_linter() {
+        let default = linter_list(&["..."]);
+        assert!(is_linter_enabled(&default, "ruff"));
+
+        let disabled = toggle_linter(&default, "ruff");
+        assert_eq!(disabled, linter_list(&["!ruff", "..."]));
+        assert!(!is_linter_enabled(&disabled, "ruff"));
+        assert!(is_linter_enabled(&disabled, "sqlfluff"));
+
+        assert_eq!(toggle_linter(&disabled, "ruff"), default);
+
+        let explicit = linter_list(&["sqlfluff"]);
+        assert!(!is_linter_enabled(&explicit, "ruff"));
+        assert_eq!(
+            toggle_linter(&explicit, "ruff"),
+            linter_list(&["sqlfluff", "ruff"])
+        );
+        assert_eq!(
+            toggle_linter(&explicit, "sqlfluff"),
+            linter_list(&["!sqlfluff"])
+        );
+    }
+}
@@ -4239,7 +4239,7 @@ fn window_and_layout_page() -> SettingsPage {
 }
 
 fn panels_page() -> SettingsPage {
-    fn project_panel_section() -> [SettingsPageItem; 23] {
+    fn project_panel_section() -> [SettingsPageItem; 24] {
         [
             SettingsPageItem::SectionHeader("Project Panel"),
             SettingsPageItem::SettingItem(SettingItem {
@@ -4587,6 +4587,32 @@ fn panels_page() -> SettingsPage {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "File Nesting",
+                description: "Group related files, such as generated files and lockfiles, under a parent file.",
+                field: Box::new(SettingField {
+                    json_path: Some("project_panel.file_nesting.enabled"),
+                    pick: |settings_content| {
+                        settings_content
+                            .project_panel
+                            .as_ref()?
+                            .file_nesting
+                            .as_ref()?
+                            .enabled
+                            .as_ref()
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .project_panel
+                            .get_or_insert_default()
+                            .file_nesting
+                            .get_or_insert_default()
+                            .enabled = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
             SettingsPageItem::SettingItem(SettingItem {
                 title: "Sticky Scroll",
                 description: "Whether to stick parent directories at top of the project panel.",
@@ -5070,7 +5096,7 @@ fn panels_page() -> SettingsPage {
         ]
     }
 
-    fn git_panel_section() -> [SettingsPageItem; 14] {
+    fn git_panel_section() -> [SettingsPageItem; 15] {
         [
             SettingsPageItem::SectionHeader("Git Panel"),
             SettingsPageItem::SettingItem(SettingItem {
@@ -5197,6 +5223,28 @@ fn panels_page() -> SettingsPage {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Notebook Diff Outputs",
+                description: "How cell outputs and metadata are shown in diffs of Jupyter notebooks.",
+                field: Box::new(SettingField {
+                    json_path: Some("git_panel.notebook_diff_outputs"),
+                    pick: |settings_content| {
+                        settings_content
+                            .git_panel
+                            .as_ref()?
+                            .notebook_diff_outputs
+                            .as_ref()
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .git_panel
+                            .get_or_insert_default()
+                            .notebook_diff_outputs = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
             SettingsPageItem::SettingItem(SettingItem {
                 title: "Tree View",
                 description: "Enable to show entries in tree view list, disable to show in flat view list.",
@@ -7902,26 +7950,38 @@ fn language_settings_data() -> Box<[SettingsPageItem]> {
             SettingsPageItem::SettingItem(SettingItem {
                 title: "Formatter",
                 description: "How to perform a buffer format.",
-                field: Box::new(
-                    SettingField {
-                        json_path: Some("languages.$(language).formatter"),
-                        pick: |settings_content| {
-                            language_settings_field(settings_content, |language| {
-                                language.formatter.as_ref()
-                            })
-                        },
-                        write: |settings_content, value| {
-                            language_settings_field_mut(
-                                settings_content,
-                                value,
-                                |language, value| {
-                                    language.formatter = value;
-                                },
-                            )
-                        },
-                    }
-                    .unimplemented(),
-                ),
+                field: Box::new(SettingField {
+                    json_path: Some("languages.$(language).formatter"),
+                    pick: |settings_content| {
+                        language_settings_field(settings_content, |language| {
+                            language.formatter.as_ref()
+                        })
+                    },
+                    write: |settings_content, value| {
+                        language_settings_field_mut(settings_content, value, |language, value| {
+                            language.formatter = value;
+                        })
+                    },
+                }),
+                metadata: None,
+                files: USER | PROJECT,
+            }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Linters",
+                description: "The linters provided by extensions to run for this language.",
+                field: Box::new(SettingField {
+                    json_path: Some("languages.$(language).linters"),
+                    pick: |settings_content| {
+                        language_settings_field(settings_content, |language| {
+                            language.linters.as_ref()
+                        })
+                    },
+                    write: |settings_content, value| {
+                        language_settings_field_mut(settings_content, value, |language, value| {
+                            language.linters = value;
+                        })
+                    },
+                }),
                 metadata: None,
                 files: USER | PROJECT,
             }),
@@ -1,6 +1,11 @@
 use anyhow::{Context as _, Result};
+use regex::{Captures, Regex};
 use smallvec::SmallVec;
-use std::{collections::BTreeMap, ops::Range};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Snippet {
@@ -12,14 +17,97 @@ pub struct Snippet {
 pub struct TabStop {
     pub ranges: SmallVec<[Range<isize>; 2]>,
     pub choices: Option<Vec<String>>,
+    /// Ranges that mirror this tabstop's text through a transform, like `${1/(.*)/${1:/upcase}/}`.
+    pub transformed_ranges: Vec<TransformedRange>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransformedRange {
+    pub range: Range<isize>,
+    pub transform: Transform,
+}
+
+/// A regex substitution applied to a tabstop or variable, written as `/regex/format/options`.
+#[derive(Clone, Debug)]
+pub struct Transform {
+    regex: Regex,
+    format: Vec<FormatItem>,
+    global: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum FormatItem {
+    Text(String),
+    /// `$1`, `${1}` or `${1:/upcase}`.
+    Capture {
+        index: usize,
+        case: Option<CaseChange>,
+    },
+    /// `${1:+if}` or `${1:?if:else}`.
+    Conditional {
+        index: usize,
+        if_text: String,
+        else_text: String,
+    },
+    /// `${1:-else}` or `${1:else}`.
+    Default {
+        index: usize,
+        default: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CaseChange {
+    Upcase,
+    Downcase,
+    Capitalize,
+    Camelcase,
+    Pascalcase,
 }
 
 impl Snippet {
     pub fn parse(source: &str) -> Result<Self> {
-        let mut text = String::with_capacity(source.len());
-        let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
-            .context("failed to parse snippet")?;
+        Self::parse_with_variables(source, &|_| None)
+    }
+
+    /// Parses a snippet, resolving variables like `$TM_FILENAME` or `${CLIPBOARD:default}` with
+    /// `variables`.
+    ///
+    /// Each variable is resolved at most once. When `variables` doesn't know a variable, its
+    /// default is used, or its name if it has none.
+    pub fn parse_with_variables(
+        source: &str,
+        variables: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let mut cx = ParseContext {
+            variables,
+            resolved_variables: Default::default(),
+            placeholders: BTreeMap::new(),
+        };
+        let (mut text, mut tabstops) = parse(source, &cx)?;
+
+        // Transforms apply to their tabstop's placeholder, which may come after them. So once all
+        // placeholders are known, parse again to fill in the transformed text.
+        if tabstops
+            .values()
+            .any(|tabstop| !tabstop.transformed_ranges.is_empty())
+        {
+            cx.placeholders = tabstops
+                .iter()
+                .map(|(index, tabstop)| (*index, placeholder_text(tabstop, &text)))
+                .collect();
+            (text, tabstops) = parse(source, &cx)?;
+        }
+
+        // Tabstops that only appear in transforms still need a place for their text.
+        for tabstop in tabstops.values_mut() {
+            if tabstop.ranges.is_empty()
+                && let Some(transformed_range) = tabstop.transformed_ranges.first()
+            {
+                let start = transformed_range.range.start;
+                tabstop.ranges.push(start..start);
+            }
+        }
 
         let len = text.len() as isize;
         let final_tabstop = tabstops.remove(&0);
@@ -31,6 +119,7 @@ impl Snippet {
             let end_tabstop = TabStop {
                 ranges: [len..len].into_iter().collect(),
                 choices: None,
+                transformed_ranges: Vec::new(),
             };
 
             if !tabstops.last().is_some_and(|t| *t == end_tabstop) {
@@ -42,17 +131,176 @@ impl Snippet {
     }
 }
 
+impl Transform {
+    fn new(regex: &str, format: Vec<FormatItem>, options: &str) -> Result<Self> {
+        let flags = options
+            .chars()
+            .filter(|option| matches!(option, 'i' | 'm' | 's'))
+            .collect::<String>();
+        let regex = if flags.is_empty() {
+            Regex::new(regex)
+        } else {
+            Regex::new(&format!("(?{flags}){regex}"))
+        }
+        .with_context(|| format!("invalid transform regex {regex:?}"))?;
+        Ok(Self {
+            regex,
+            format,
+            global: options.contains('g'),
+        })
+    }
+
+    /// Replaces the first match of the transform's regex in `text`, or every match if the
+    /// transform has the `g` option.
+    pub fn apply(&self, text: &str) -> String {
+        let replacement = |captures: &Captures<'_>| self.expand(captures);
+        if self.global {
+            self.regex.replace_all(text, replacement).into_owned()
+        } else {
+            self.regex.replace(text, replacement).into_owned()
+        }
+    }
+
+    fn expand(&self, captures: &Captures<'_>) -> String {
+        let capture = |index: usize| {
+            captures
+                .get(index)
+                .map(|capture| capture.as_str())
+                .filter(|capture| !capture.is_empty())
+        };
+
+        let mut result = String::new();
+        for item in &self.format {
+            match item {
+                FormatItem::Text(text) => result.push_str(text),
+                FormatItem::Capture { index, case } => {
+                    let capture = capture(*index).unwrap_or_default();
+                    match case {
+                        Some(case) => result.push_str(&case.apply(capture)),
+                        None => result.push_str(capture),
+                    }
+                }
+                FormatItem::Conditional {
+                    index,
+                    if_text,
+                    else_text,
+                } => {
+                    if capture(*index).is_some() {
+                        result.push_str(if_text);
+                    } else {
+                        result.push_str(else_text);
+                    }
+                }
+                FormatItem::Default { index, default } => {
+                    result.push_str(capture(*index).unwrap_or(default));
+                }
+            }
+        }
+        result
+    }
+}
+
+impl PartialEq for Transform {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+            && self.format == other.format
+            && self.global == other.global
+    }
+}
+
+impl CaseChange {
+    fn from_name(name: &str) -> Result<Self> {
+        Ok(match name {
+            "upcase" => Self::Upcase,
+            "downcase" => Self::Downcase,
+            "capitalize" => Self::Capitalize,
+            "camelcase" => Self::Camelcase,
+            "pascalcase" => Self::Pascalcase,
+            _ => anyhow::bail!("unknown case change {name:?}"),
+        })
+    }
+
+    fn apply(self, text: &str) -> String {
+        match self {
+            Self::Upcase => text.to_uppercase(),
+            Self::Downcase => text.to_lowercase(),
+            Self::Capitalize => capitalize(text),
+            Self::Camelcase | Self::Pascalcase => {
+                let mut result = String::new();
+                for word in text
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|word| !word.is_empty())
+                {
+                    if result.is_empty() && self == Self::Camelcase {
+                        let mut chars = word.chars();
+                        result.extend(chars.next().into_iter().flat_map(char::to_lowercase));
+                        result.push_str(chars.as_str());
+                    } else {
+                        result.push_str(&capitalize(word));
+                    }
+                }
+                result
+            }
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .into_iter()
+        .flat_map(char::to_uppercase)
+        .chain(chars)
+        .collect()
+}
+
+struct ParseContext<'a> {
+    variables: &'a dyn Fn(&str) -> Option<String>,
+    resolved_variables: RefCell<HashMap<String, Option<String>>>,
+    /// The text of each tabstop's placeholder, which is the input of the tabstop's transforms.
+    placeholders: BTreeMap<usize, String>,
+}
+
+impl ParseContext<'_> {
+    fn resolve_variable(&self, name: &str) -> Option<String> {
+        self.resolved_variables
+            .borrow_mut()
+            .entry(name.to_string())
+            .or_insert_with(|| (self.variables)(name))
+            .clone()
+    }
+}
+
+fn parse(source: &str, cx: &ParseContext) -> Result<(String, BTreeMap<usize, TabStop>)> {
+    let mut text = String::with_capacity(source.len());
+    let mut tabstops = BTreeMap::new();
+    parse_snippet(source, false, &mut text, &mut tabstops, cx)
+        .context("failed to parse snippet")?;
+    Ok((text, tabstops))
+}
+
+fn placeholder_text(tabstop: &TabStop, text: &str) -> String {
+    tabstop
+        .ranges
+        .iter()
+        .find(|range| !range.is_empty())
+        .map(|range| text[range.start as usize..range.end as usize].to_string())
+        .unwrap_or_default()
+}
+
 fn parse_snippet<'a>(
     mut source: &'a str,
     nested: bool,
     text: &mut String,
     tabstops: &mut BTreeMap<usize, TabStop>,
+    cx: &ParseContext,
 ) -> Result<&'a str> {
     loop {
         match source.chars().next() {
             None => return Ok(""),
             Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
+                source = parse_tabstop(&source[1..], text, tabstops, cx)?;
             }
             Some('\\') => {
                 // As specified in the LSP spec (`Grammar` section),
@@ -93,10 +341,20 @@ fn parse_tabstop<'a>(
     mut source: &'a str,
     text: &mut String,
     tabstops: &mut BTreeMap<usize, TabStop>,
+    cx: &ParseContext,
 ) -> Result<&'a str> {
+    if source.starts_with(is_variable_start) {
+        return parse_variable(source, false, text, tabstops, cx);
+    } else if let Some(rest) = source.strip_prefix('{')
+        && rest.starts_with(is_variable_start)
+    {
+        return parse_variable(rest, true, text, tabstops, cx);
+    }
+
     let tabstop_start = text.len();
     let tabstop_index;
     let mut choices = None;
+    let mut transform = None;
 
     if source.starts_with('{') {
         let (index, rest) = parse_int(&source[1..])?;
@@ -108,7 +366,11 @@ fn parse_tabstop<'a>(
         }
 
         if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
+            source = parse_snippet(&source[1..], true, text, tabstops, cx)?;
+        } else if source.starts_with('/') {
+            let (parsed_transform, rest) = parse_transform(&source[1..])?;
+            transform = Some(parsed_transform);
+            source = rest;
         }
 
         if source.starts_with('}') {
@@ -122,17 +384,70 @@ fn parse_tabstop<'a>(
         source = rest;
     }
 
-    tabstops
-        .entry(tabstop_index)
-        .or_insert_with(|| TabStop {
-            ranges: Default::default(),
-            choices,
-        })
-        .ranges
-        .push(tabstop_start as isize..text.len() as isize);
+    let tabstop = tabstops.entry(tabstop_index).or_insert_with(|| TabStop {
+        ranges: Default::default(),
+        choices,
+        transformed_ranges: Vec::new(),
+    });
+    if let Some(transform) = transform {
+        let placeholder = cx
+            .placeholders
+            .get(&tabstop_index)
+            .map_or("", String::as_str);
+        text.push_str(&transform.apply(placeholder));
+        tabstop.transformed_ranges.push(TransformedRange {
+            range: tabstop_start as isize..text.len() as isize,
+            transform,
+        });
+    } else {
+        tabstop
+            .ranges
+            .push(tabstop_start as isize..text.len() as isize);
+    }
     Ok(source)
 }
 
+fn is_variable_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn parse_variable<'a>(
+    source: &'a str,
+    braced: bool,
+    text: &mut String,
+    tabstops: &mut BTreeMap<usize, TabStop>,
+    cx: &ParseContext,
+) -> Result<&'a str> {
+    let name_len = source
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(source.len());
+    let (name, mut source) = source.split_at(name_len);
+    let value = cx.resolve_variable(name);
+    if !braced {
+        text.push_str(value.as_deref().unwrap_or(name));
+        return Ok(source);
+    }
+
+    if let Some(rest) = source.strip_prefix('/') {
+        let (transform, rest) = parse_transform(rest)?;
+        text.push_str(&transform.apply(value.as_deref().unwrap_or_default()));
+        source = rest;
+    } else if let Some(rest) = source.strip_prefix(':') {
+        match value.filter(|value| !value.is_empty()) {
+            Some(value) => {
+                // The default is unused, but still needs parsing to find where it ends.
+                source = parse_snippet(rest, true, &mut String::new(), &mut BTreeMap::new(), cx)?;
+                text.push_str(&value);
+            }
+            None => source = parse_snippet(rest, true, text, tabstops, cx)?,
+        }
+    } else {
+        text.push_str(value.as_deref().unwrap_or(name));
+    }
+
+    source.strip_prefix('}').context("expected a closing brace")
+}
+
 fn parse_int(source: &str) -> Result<(usize, &str)> {
     let len = source
         .find(|c: char| !c.is_ascii_digit())
@@ -142,6 +457,145 @@ fn parse_int(source: &str) -> Result<(usize, &str)> {
     Ok((prefix.parse()?, suffix))
 }
 
+/// Parses the part of a transform after its first slash, up to its closing brace.
+fn parse_transform(source: &str) -> Result<(Transform, &str)> {
+    let mut regex = String::new();
+    let mut chars = source.char_indices();
+    let format_start = loop {
+        match chars.next() {
+            None => anyhow::bail!("expected a '/' after the transform's regex"),
+            Some((ix, '/')) => break ix + 1,
+            Some((_, '\\')) => match chars.next() {
+                Some((_, '/')) => regex.push('/'),
+                Some((_, c)) => {
+                    regex.push('\\');
+                    regex.push(c);
+                }
+                None => regex.push('\\'),
+            },
+            Some((_, c)) => regex.push(c),
+        }
+    };
+
+    let (format, source) = parse_format(&source[format_start..])?;
+    let options_len = source
+        .find('}')
+        .context("expected a closing brace after the transform")?;
+    let (options, source) = source.split_at(options_len);
+    Ok((Transform::new(&regex, format, options)?, source))
+}
+
+/// Parses a transform's format string, up to and including its closing slash.
+fn parse_format(mut source: &str) -> Result<(Vec<FormatItem>, &str)> {
+    let mut items = Vec::new();
+    let mut text = String::new();
+    loop {
+        match source.chars().next() {
+            None => anyhow::bail!("expected a '/' after the transform's format"),
+            Some('/') => {
+                if !text.is_empty() {
+                    items.push(FormatItem::Text(text));
+                }
+                return Ok((items, &source[1..]));
+            }
+            Some('\\') => {
+                source = &source[1..];
+                match source.chars().next() {
+                    Some(c @ ('$' | '\\' | '/' | '}')) => {
+                        text.push(c);
+                        source = &source[1..];
+                    }
+                    _ => text.push('\\'),
+                }
+            }
+            Some('$') => {
+                let rest = &source[1..];
+                let item;
+                if rest.starts_with(|c: char| c.is_ascii_digit()) {
+                    let (index, rest) = parse_int(rest)?;
+                    item = FormatItem::Capture { index, case: None };
+                    source = rest;
+                } else if let Some(rest) = rest.strip_prefix('{') {
+                    (item, source) = parse_format_item(rest)?;
+                } else {
+                    text.push('$');
+                    source = rest;
+                    continue;
+                }
+                if !text.is_empty() {
+                    items.push(FormatItem::Text(std::mem::take(&mut text)));
+                }
+                items.push(item);
+            }
+            Some(_) => {
+                let chunk_end = source.find(['/', '$', '\\']).unwrap_or(source.len());
+                let (chunk, rest) = source.split_at(chunk_end);
+                text.push_str(chunk);
+                source = rest;
+            }
+        }
+    }
+}
+
+/// Parses a braced format item like `${1:/upcase}`, starting after its opening brace.
+fn parse_format_item(source: &str) -> Result<(FormatItem, &str)> {
+    let (index, source) = parse_int(source)?;
+    if let Some(source) = source.strip_prefix('}') {
+        return Ok((FormatItem::Capture { index, case: None }, source));
+    }
+    let source = source
+        .strip_prefix(':')
+        .context("expected a ':' or a closing brace")?;
+
+    let (item, source) = if let Some(source) = source.strip_prefix('/') {
+        let (name, source) = parse_format_text(source, &['}'])?;
+        let case = Some(CaseChange::from_name(&name)?);
+        (FormatItem::Capture { index, case }, source)
+    } else if let Some(source) = source.strip_prefix('+') {
+        let (if_text, source) = parse_format_text(source, &['}'])?;
+        let else_text = String::new();
+        let item = FormatItem::Conditional {
+            index,
+            if_text,
+            else_text,
+        };
+        (item, source)
+    } else if let Some(source) = source.strip_prefix('?') {
+        let (if_text, source) = parse_format_text(source, &[':'])?;
+        let (else_text, source) = parse_format_text(&source[1..], &['}'])?;
+        let item = FormatItem::Conditional {
+            index,
+            if_text,
+            else_text,
+        };
+        (item, source)
+    } else {
+        let source = source.strip_prefix('-').unwrap_or(source);
+        let (default, source) = parse_format_text(source, &['}'])?;
+        (FormatItem::Default { index, default }, source)
+    };
+    Ok((item, &source[1..]))
+}
+
+/// Parses text up to one of the given terminators, which is left at the start of the returned
+/// source. Any character can be escaped with a backslash.
+fn parse_format_text<'a>(source: &'a str, terminators: &[char]) -> Result<(String, &'a str)> {
+    let mut text = String::new();
+    let mut chars = source.char_indices();
+    while let Some((ix, c)) = chars.next() {
+        if terminators.contains(&c) {
+            return Ok((text, &source[ix..]));
+        } else if c == '\\' {
+            if let Some((_, c)) = chars.next() {
+                text.push(c);
+            }
+        } else {
+            text.push(c);
+        }
+    }
+    anyhow::bail!("expected one of {terminators:?} in the transform's format")
+}
+
 fn parse_choices<'a>(
     mut source: &'a str,
     text: &mut String,
@@ -324,6 +778,111 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![4..4], vec![7..7]]);
     }
 
+    #[test]
+    fn test_snippet_with_variables() {
+        let variables = |name: &str| match name {
+            "TM_FILENAME" => Some("main.rs".to_string()),
+            "TM_SELECTED_TEXT" => Some(String::new()),
+            _ => None,
+        };
+
+        let snippet = Snippet::parse_with_variables("// $TM_FILENAME: $1", &variables).unwrap();
+        assert_eq!(snippet.text, "// main.rs: ");
+        assert_eq!(tabstops(&snippet), &[vec![12..12]]);
+
+        // Empty and unknown variables use their default, which can contain tabstops.
+        let snippet =
+            Snippet::parse_with_variables("(${TM_SELECTED_TEXT:${1:x}})", &variables).unwrap();
+        assert_eq!(snippet.text, "(x)");
+        assert_eq!(tabstops(&snippet), &[vec![1..2], vec![3..3]]);
+
+        // Defaults of known variables are skipped, including their tabstops.
+        let snippet =
+            Snippet::parse_with_variables("${TM_FILENAME:${1:x}} $2", &variables).unwrap();
+        assert_eq!(snippet.text, "main.rs ");
+        assert_eq!(tabstops(&snippet), &[vec![8..8]]);
+
+        // Unknown variables without a default are replaced by their name.
+        let snippet = Snippet::parse_with_variables("$UNKNOWN ${OTHER}", &variables).unwrap();
+        assert_eq!(snippet.text, "UNKNOWN OTHER");
+
+        // Without a resolver, every variable is unknown.
+        let snippet = Snippet::parse("${TM_FILENAME:file}").unwrap();
+        assert_eq!(snippet.text, "file");
+    }
+
+    #[test]
+    fn test_snippet_with_variable_transforms() {
+        let variables = |name: &str| match name {
+            "TM_FILENAME" => Some("snippet_parser.test.rs".to_string()),
+            _ => None,
+        };
+
+        let snippet =
+            Snippet::parse_with_variables(r"${TM_FILENAME/(.*?)\..+$/$1/}", &variables).unwrap();
+        assert_eq!(snippet.text, "snippet_parser");
+
+        let snippet = Snippet::parse_with_variables(
+            r"${TM_FILENAME/([a-z]+)_?/${1:/capitalize}/g}",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "SnippetParser.Test.Rs");
+
+        let snippet = Snippet::parse_with_variables(
+            r"${TM_FILENAME/(.*)\.rs$/${1:/pascalcase}/}",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "SnippetParserTest");
+
+        let snippet =
+            Snippet::parse_with_variables(r"${TM_FILENAME/(.*)\.rs$/${1:/camelcase}/}", &variables)
+                .unwrap();
+        assert_eq!(snippet.text, "snippetParserTest");
+
+        // Slashes can be escaped in the regex and the format.
+        let snippet =
+            Snippet::parse_with_variables(r"${TM_FILENAME/\.|_/\//g}", &variables).unwrap();
+        assert_eq!(snippet.text, "snippet/parser/test/rs");
+
+        assert!(Snippet::parse_with_variables("${TM_FILENAME/(/x/}", &variables).is_err());
+        assert!(
+            Snippet::parse_with_variables("${TM_FILENAME/a/${1:/shout}/}", &variables).is_err()
+        );
+    }
+
+    #[test]
+    fn test_snippet_with_tabstop_transforms() {
+        let snippet = Snippet::parse("${1/(.*)/${1:/upcase}/} = ${1:name};").unwrap();
+        assert_eq!(snippet.text, "NAME = name;");
+        assert_eq!(tabstops(&snippet), &[vec![7..11], vec![12..12]]);
+        assert_eq!(
+            transformed_ranges(&snippet, "foo"),
+            &[vec![(0..4, "FOO".to_string())], vec![]]
+        );
+
+        // Conditional insertions.
+        let snippet = Snippet::parse("${1:x}${1/(y)?.*/${1:+ why}${1:?:!}/}").unwrap();
+        assert_eq!(snippet.text, "x!");
+        assert_eq!(
+            transformed_ranges(&snippet, "y"),
+            &[vec![(1..2, " why".to_string())], vec![]]
+        );
+
+        let snippet = Snippet::parse("${1:abc}${1/^(\\w)?.*$/${1:-none}/}").unwrap();
+        assert_eq!(snippet.text, "abca");
+        assert_eq!(
+            transformed_ranges(&snippet, "-"),
+            &[vec![(3..4, "none".to_string())], vec![]]
+        );
+
+        // Tabstops that only appear in transforms get an empty range at the first transform.
+        let snippet = Snippet::parse("a${1/(.*)/<$1>/}b").unwrap();
+        assert_eq!(snippet.text, "a<>b");
+        assert_eq!(tabstops(&snippet), &[vec![1..1], vec![4..4]]);
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
         snippet.tabstops.iter().map(|t| t.ranges.to_vec()).collect()
     }
@@ -331,4 +890,17 @@ mod tests {
     fn tabstop_choices(snippet: &Snippet) -> Vec<&Option<Vec<String>>> {
         snippet.tabstops.iter().map(|t| &t.choices).collect()
     }
+
+    fn transformed_ranges(snippet: &Snippet, input: &str) -> Vec<Vec<(Range<isize>, String)>> {
+        snippet
+            .tabstops
+            .iter()
+            .map(|t| {
+                t.transformed_ranges
+                    .iter()
+                    .map(|r| (r.range.clone(), r.transform.apply(input)))
+                    .collect()
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,201 @@
+//! Finds the parts of a buffer that contain natural language.
+//!
+//! In source code only comments and strings are checked, as identified by the captures of each
+//! language's highlights query. Prose languages such as Markdown are checked in full, except for
+//! code blocks, code spans and link destinations. Comments and strings within code blocks whose
+//! language is known are checked like any other code.
+
+use std::ops::Range;
+
+use language::BufferSnapshot;
+
+/// Languages whose buffers are checked in full rather than only in comments and strings.
+const PROSE_LANGUAGES: &[&str] = &["Markdown", "Markdown-Inline", "Plain Text", "Git Commit"];
+
+/// Syntax nodes within prose that contain code, whether or not the code's language is known.
+const CODE_NODE_KINDS: &[&str] = &[
+    "fenced_code_block",
+    "indented_code_block",
+    "code_span",
+    "html_block",
+];
+
+enum CaptureKind {
+    /// Natural language within code, such as a comment.
+    Text,
+    /// Text within a string or comment that isn't natural language, such as an escape sequence.
+    ExcludedFromText,
+    /// Text within prose that isn't natural language, such as a code span.
+    ExcludedFromProse,
+}
+
+fn capture_kind(name: &str) -> Option<CaptureKind> {
+    let mut components = name.split('.');
+    match (components.next()?, components.next()) {
+        ("comment", _) | ("string", None | Some("doc")) => Some(CaptureKind::Text),
+        ("string", Some(_)) => Some(CaptureKind::ExcludedFromText),
+        ("text", Some("literal")) | ("link_uri", _) => Some(CaptureKind::ExcludedFromProse),
+        ("punctuation", Some("embedded")) => Some(CaptureKind::ExcludedFromProse),
+        _ => None,
+    }
+}
+
+fn is_prose_language(name: &str) -> bool {
+    PROSE_LANGUAGES.contains(&name)
+}
+
+/// Returns the sorted, disjoint byte ranges of `snapshot` that should be spell checked.
+pub fn checkable_ranges(snapshot: &BufferSnapshot) -> Vec<Range<usize>> {
+    let mut prose = Vec::new();
+    let mut excluded_from_prose = Vec::new();
+    let mut text = Vec::new();
+    let mut excluded_from_text = Vec::new();
+
+    if snapshot
+        .language()
+        .is_none_or(|language| is_prose_language(&language.name()))
+    {
+        prose.push(0..snapshot.len());
+        for layer in snapshot.syntax_layers() {
+            if !is_prose_language(&layer.language.name()) {
+                excluded_from_prose.push(layer.node().byte_range());
+                continue;
+            }
+            let mut cursor = layer.node().walk();
+            'nodes: loop {
+                let node = cursor.node();
+                if CODE_NODE_KINDS.contains(&node.kind()) {
+                    excluded_from_prose.push(node.byte_range());
+                } else if cursor.goto_first_child() {
+                    continue;
+                }
+                while !cursor.goto_next_sibling() {
+                    if !cursor.goto_parent() {
+                        break 'nodes;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut captures = snapshot.captures(0..snapshot.len(), |grammar| {
+        grammar
+            .highlights_config
+            .as_ref()
+            .map(|config| &config.query)
+    });
+    let grammars = captures.grammars().to_vec();
+    while let Some(capture) = captures.peek() {
+        if let Some(config) = grammars[capture.grammar_index].highlights_config.as_ref() {
+            let name = config.query.capture_names()[capture.index as usize];
+            let range = capture.node.byte_range();
+            match capture_kind(name) {
+                Some(CaptureKind::Text) => text.push(range),
+                Some(CaptureKind::ExcludedFromText) => excluded_from_text.push(range),
+                Some(CaptureKind::ExcludedFromProse) => excluded_from_prose.push(range),
+                None => {}
+            }
+        }
+        captures.advance();
+    }
+
+    let mut ranges = subtract(union(prose), &union(excluded_from_prose));
+    ranges.extend(text);
+    subtract(union(ranges), &union(excluded_from_text))
+}
+
+/// Sorts `ranges` and merges those that overlap or touch.
+fn union(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Removes `excluded` from `ranges`, both of which must be sorted and disjoint.
+fn subtract(ranges: Vec<Range<usize>>, excluded: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut result = Vec::with_capacity(ranges.len());
+    let mut excluded = excluded.iter().peekable();
+    for mut range in ranges {
+        while let Some(exclusion) = excluded.peek() {
+            if exclusion.end <= range.start {
+                excluded.next();
+                continue;
+            }
+            if exclusion.start >= range.end {
+                break;
+            }
+            if exclusion.start > range.start {
+                result.push(range.start..exclusion.start);
+            }
+            range.start = exclusion.end.min(range.end);
+            if exclusion.end > range.end {
+                break;
+            }
+            excluded.next();
+        }
+        if !range.is_empty() {
+            result.push(range);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{AppContext as _, TestAppContext};
+    use language::{Buffer, markdown_lang, rust_lang};
+
+    fn checked_text(text: &str, snapshot: &BufferSnapshot) -> Vec<String> {
+        checkable_ranges(snapshot)
+            .into_iter()
+            .map(|range| text[range].trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect()
+    }
+
+    #[gpui::test]
+    async fn test_code_checks_comments_and_strings(cx: &mut TestAppContext) {
+        let text = "// A comment.\nfn main() {\n    let greeting = \"hello\\nthere\";\n}\n";
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(rust_lang(), cx));
+        cx.run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        assert_eq!(
+            checked_text(text, &snapshot),
+            ["// A comment.", "\"hello", "there\""]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_prose_checks_everything_but_code(cx: &mut TestAppContext) {
+        let text = "# Title\n\nSome text.\n\n```\nlet code = 1;\n```\n";
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(markdown_lang(), cx));
+        cx.run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        let checked = checked_text(text, &snapshot).join(" ");
+        assert!(checked.contains("Title"));
+        assert!(checked.contains("Some text."));
+        assert!(!checked.contains("code"));
+        assert!(!checked.contains("```"));
+
+        let buffer = cx.new(|cx| Buffer::local("plain words", cx));
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        assert_eq!(checkable_ranges(&snapshot), [0..11]);
+    }
+
+    #[test]
+    fn test_subtract() {
+        assert_eq!(
+            subtract(vec![0..10, 20..30], &[2..4, 8..22, 25..26]),
+            [0..2, 4..8, 22..25, 26..30]
+        );
+        assert_eq!(subtract(vec![0..10], &[0..10]), []);
+        assert_eq!(union(vec![5..7, 0..3, 2..5, 9..10]), [0..7, 9..10]);
+    }
+}
@@ -0,0 +1,157 @@
+use std::{ops::Range, sync::Arc};
+
+use anyhow::{Result, anyhow};
+use editor::{CodeActionProvider, ExcerptId};
+use gpui::{App, Entity, Task, Window};
+use language::{Anchor, Buffer};
+use project::{CodeAction, LspAction, ProjectTransaction};
+use serde::{Deserialize, Serialize};
+
+use crate::{DIAGNOSTIC_SOURCE, SpellCheckStore, WordListScope};
+
+const PROVIDER_ID: &str = "spell_check";
+
+/// The most replacements offered for a misspelled word.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// What a spell checking code action does, stored in the action's `data`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum SpellCheckAction {
+    Replace { replacement: String },
+    AddToWordList { word: String, scope: WordListScope },
+}
+
+/// Offers replacements for misspelled words, and to add them to a word list.
+pub struct SpellCheckCodeActionProvider {
+    spell_check_store: Entity<SpellCheckStore>,
+}
+
+impl SpellCheckCodeActionProvider {
+    pub fn new(spell_check_store: Entity<SpellCheckStore>) -> Self {
+        Self { spell_check_store }
+    }
+}
+
+impl CodeActionProvider for SpellCheckCodeActionProvider {
+    fn id(&self) -> Arc<str> {
+        PROVIDER_ID.into()
+    }
+
+    fn code_actions(
+        &self,
+        buffer: &Entity<Buffer>,
+        range: Range<Anchor>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Vec<CodeAction>>> {
+        let spell_check_store = self.spell_check_store.read(cx);
+        let Some(dictionary) = spell_check_store.dictionary().cloned() else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let server_id = spell_check_store.server_id();
+        let snapshot = buffer.read(cx).snapshot();
+        let misspellings = snapshot
+            .diagnostics_in_range::<_, usize>(range, false)
+            .filter(|entry| entry.diagnostic.source.as_deref() == Some(DIAGNOSTIC_SOURCE))
+            .map(|entry| entry.range)
+            .collect::<Vec<_>>();
+        if misspellings.is_empty() {
+            return Task::ready(Ok(Vec::new()));
+        }
+
+        cx.background_spawn(async move {
+            let mut actions = Vec::new();
+            for range in misspellings {
+                let word = snapshot.text_for_range(range.clone()).collect::<String>();
+                let range = snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end);
+                let mut push_action = |title: String, action: SpellCheckAction| {
+                    actions.push(CodeAction {
+                        server_id,
+                        range: range.clone(),
+                        lsp_action: LspAction::Action(Box::new(lsp::CodeAction {
+                            title,
+                            kind: Some(lsp::CodeActionKind::QUICKFIX),
+                            data: serde_json::to_value(action).ok(),
+                            ..Default::default()
+                        })),
+                        resolved: true,
+                    })
+                };
+
+                for replacement in dictionary.suggestions(&word, MAX_SUGGESTIONS) {
+                    push_action(
+                        format!("Change to \"{replacement}\""),
+                        SpellCheckAction::Replace { replacement },
+                    );
+                }
+                push_action(
+                    format!("Add \"{word}\" to user dictionary"),
+                    SpellCheckAction::AddToWordList {
+                        word: word.clone(),
+                        scope: WordListScope::User,
+                    },
+                );
+                push_action(
+                    format!("Add \"{word}\" to project dictionary"),
+                    SpellCheckAction::AddToWordList {
+                        word,
+                        scope: WordListScope::Project,
+                    },
+                );
+            }
+            Ok(actions)
+        })
+    }
+
+    fn apply_code_action(
+        &self,
+        buffer: Entity<Buffer>,
+        action: CodeAction,
+        _: ExcerptId,
+        push_to_history: bool,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<ProjectTransaction>> {
+        let spell_check_action = match &action.lsp_action {
+            LspAction::Action(lsp_action) => lsp_action
+                .data
+                .clone()
+                .and_then(|data| serde_json::from_value::<SpellCheckAction>(data).ok()),
+            LspAction::Command(_) | LspAction::CodeLens(_) => None,
+        };
+        let Some(spell_check_action) = spell_check_action else {
+            return Task::ready(Err(anyhow!("not a spell checking code action")));
+        };
+
+        match spell_check_action {
+            SpellCheckAction::Replace { replacement } => {
+                let transaction = buffer.update(cx, |buffer, cx| {
+                    buffer.finalize_last_transaction();
+                    buffer.start_transaction();
+                    buffer.edit([(action.range, replacement)], None, cx);
+                    buffer.end_transaction(cx)?;
+                    let transaction = buffer.finalize_last_transaction()?.clone();
+                    if !push_to_history {
+                        buffer.forget_transaction(transaction.id);
+                    }
+                    Some(transaction)
+                });
+                let mut project_transaction = ProjectTransaction::default();
+                if let Some(transaction) = transaction {
+                    project_transaction.0.insert(buffer, transaction);
+                }
+                Task::ready(Ok(project_transaction))
+            }
+            SpellCheckAction::AddToWordList { word, scope } => {
+                let add_word = self.spell_check_store.update(cx, |store, cx| {
+                    store.add_to_word_list(&word, scope, &buffer, cx)
+                });
+                cx.background_spawn(async move {
+                    add_word.await?;
+                    Ok(ProjectTransaction::default())
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,540 @@
+//! A reader for Hunspell dictionaries.
+//!
+//! A Hunspell dictionary is a pair of files: a `.dic` file listing stems along with the affix
+//! flags that apply to them, and an `.aff` file defining those affixes. Every word the dictionary
+//! accepts is expanded up front, which keeps lookups cheap at the cost of some memory.
+//!
+//! Only the parts of the format that affect which words are accepted are supported: `FLAG`,
+//! `TRY`, `REP`, `PFX`, `SFX`, `NEEDAFFIX` and `FORBIDDENWORD`. Compounding and morphological
+//! fields are ignored.
+
+use anyhow::{Context as _, Result, anyhow};
+use collections::{HashMap, HashSet};
+
+#[derive(Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+    /// The characters to try when generating suggestions, most common first.
+    try_chars: Vec<char>,
+    /// Common misspellings, as pairs of the misspelled text and its replacement.
+    replacements: Vec<(String, String)>,
+}
+
+#[derive(Clone, Copy, Default)]
+enum FlagFormat {
+    /// Each flag is a single character.
+    #[default]
+    Char,
+    /// Each flag is two characters.
+    Long,
+    /// Flags are decimal numbers separated by commas.
+    Numeric,
+}
+
+impl FlagFormat {
+    fn parse(self, flags: &str) -> Vec<String> {
+        match self {
+            FlagFormat::Char => flags.chars().map(String::from).collect(),
+            FlagFormat::Long => flags
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|chunk| chunk.iter().collect())
+                .collect(),
+            FlagFormat::Numeric => flags
+                .split(',')
+                .map(|flag| flag.trim().to_string())
+                .filter(|flag| !flag.is_empty())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AffixKind {
+    Prefix,
+    Suffix,
+}
+
+struct Affix {
+    kind: AffixKind,
+    cross_product: bool,
+    rules: Vec<AffixRule>,
+}
+
+struct AffixRule {
+    strip: String,
+    add: String,
+    condition: Vec<ConditionPart>,
+}
+
+/// One character of an affix condition, which is a restricted regular expression.
+enum ConditionPart {
+    Any,
+    Char(char),
+    OneOf(Vec<char>),
+    NoneOf(Vec<char>),
+}
+
+impl ConditionPart {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            ConditionPart::Any => true,
+            ConditionPart::Char(expected) => c == *expected,
+            ConditionPart::OneOf(chars) => chars.contains(&c),
+            ConditionPart::NoneOf(chars) => !chars.contains(&c),
+        }
+    }
+}
+
+fn parse_condition(condition: &str) -> Result<Vec<ConditionPart>> {
+    let mut parts = Vec::new();
+    let mut chars = condition.chars();
+    while let Some(c) = chars.next() {
+        parts.push(match c {
+            '.' => ConditionPart::Any,
+            '[' => {
+                let mut class = Vec::new();
+                let mut negated = false;
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some('^') if class.is_empty() && !negated => negated = true,
+                        Some(c) => class.push(c),
+                        None => return Err(anyhow!("unterminated condition {condition:?}")),
+                    }
+                }
+                if negated {
+                    ConditionPart::NoneOf(class)
+                } else {
+                    ConditionPart::OneOf(class)
+                }
+            }
+            c => ConditionPart::Char(c),
+        });
+    }
+    Ok(parts)
+}
+
+impl AffixRule {
+    fn apply(&self, word: &str, kind: AffixKind) -> Option<String> {
+        let chars = word.chars().collect::<Vec<_>>();
+        if chars.len() < self.condition.len() {
+            return None;
+        }
+        let (checked, stem) = match kind {
+            AffixKind::Prefix => (
+                &chars[..self.condition.len()],
+                word.strip_prefix(self.strip.as_str())?,
+            ),
+            AffixKind::Suffix => (
+                &chars[chars.len() - self.condition.len()..],
+                word.strip_suffix(self.strip.as_str())?,
+            ),
+        };
+        if !self
+            .condition
+            .iter()
+            .zip(checked)
+            .all(|(part, c)| part.matches(*c))
+        {
+            return None;
+        }
+        if stem.is_empty() {
+            return None;
+        }
+        Some(match kind {
+            AffixKind::Prefix => format!("{}{stem}", self.add),
+            AffixKind::Suffix => format!("{stem}{}", self.add),
+        })
+    }
+}
+
+/// Returns the affix text of a rule, ignoring any continuation flags after a `/`.
+fn affix_text(field: &str) -> &str {
+    let text = field.split('/').next().unwrap_or_default();
+    if text == "0" { "" } else { text }
+}
+
+impl Dictionary {
+    /// Parses a dictionary from the contents of its `.aff` and `.dic` files.
+    pub fn parse(aff: &str, dic: &str) -> Result<Self> {
+        let mut dictionary = Self::default();
+        let mut flag_format = FlagFormat::default();
+        let mut affixes = HashMap::<String, Affix>::default();
+        let mut need_affix = None;
+        let mut forbidden = None;
+
+        for (line_ix, line) in aff.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let context = || format!("invalid affix file line {}: {line:?}", line_ix + 1);
+            match fields.as_slice() {
+                ["FLAG", "long", ..] => flag_format = FlagFormat::Long,
+                ["FLAG", "num", ..] => flag_format = FlagFormat::Numeric,
+                ["FLAG", ..] => flag_format = FlagFormat::Char,
+                ["TRY", chars, ..] => dictionary.try_chars = chars.chars().collect(),
+                ["NEEDAFFIX", flag, ..] => need_affix = Some(flag.to_string()),
+                ["FORBIDDENWORD", flag, ..] => forbidden = Some(flag.to_string()),
+                ["REP", from, to, ..] => dictionary
+                    .replacements
+                    .push((from.replace('_', " "), to.replace('_', " "))),
+                [kind @ ("PFX" | "SFX"), flag, cross_product, count]
+                    if !affixes.contains_key(*flag) =>
+                {
+                    count.parse::<usize>().with_context(context)?;
+                    affixes.insert(
+                        flag.to_string(),
+                        Affix {
+                            kind: if *kind == "PFX" {
+                                AffixKind::Prefix
+                            } else {
+                                AffixKind::Suffix
+                            },
+                            cross_product: *cross_product == "Y",
+                            rules: Vec::new(),
+                        },
+                    );
+                }
+                ["PFX" | "SFX", flag, strip, add, rest @ ..] => {
+                    let affix = affixes.get_mut(*flag).with_context(context)?;
+                    let condition = rest.first().copied().unwrap_or(".");
+                    affix.rules.push(AffixRule {
+                        strip: affix_text(strip).to_string(),
+                        add: affix_text(add).to_string(),
+                        condition: parse_condition(condition).with_context(context)?,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let mut forbidden_words = HashSet::default();
+        for (line_ix, line) in dic.lines().enumerate() {
+            let entry = line.split('\t').next().unwrap_or_default().trim();
+            if entry.is_empty() || (line_ix == 0 && entry.parse::<usize>().is_ok()) {
+                continue;
+            }
+            let entry = entry.split_whitespace().next().unwrap_or_default();
+            let (word, flags) = match entry.split_once('/') {
+                Some((word, flags)) => (word, flag_format.parse(flags)),
+                None => (entry, Vec::new()),
+            };
+            if forbidden.as_ref().is_some_and(|flag| flags.contains(flag)) {
+                forbidden_words.insert(word.to_string());
+                continue;
+            }
+            if !need_affix.as_ref().is_some_and(|flag| flags.contains(flag)) {
+                dictionary.words.insert(word.to_string());
+            }
+
+            let affixes_for_word = flags
+                .iter()
+                .filter_map(|flag| affixes.get(flag))
+                .collect::<Vec<_>>();
+            for affix in &affixes_for_word {
+                for rule in &affix.rules {
+                    let Some(form) = rule.apply(word, affix.kind) else {
+                        continue;
+                    };
+                    if affix.kind == AffixKind::Suffix && affix.cross_product {
+                        for prefix in affixes_for_word.iter().filter(|prefix| {
+                            prefix.kind == AffixKind::Prefix && prefix.cross_product
+                        }) {
+                            dictionary.words.extend(
+                                prefix
+                                    .rules
+                                    .iter()
+                                    .filter_map(|rule| rule.apply(&form, AffixKind::Prefix)),
+                            );
+                        }
+                    }
+                    dictionary.words.insert(form);
+                }
+            }
+        }
+        for word in forbidden_words {
+            dictionary.words.remove(&word);
+        }
+
+        Ok(dictionary)
+    }
+
+    /// Adds the words and suggestion tables of `other` to this dictionary.
+    pub fn extend(&mut self, other: Dictionary) {
+        self.words.extend(other.words);
+        for c in other.try_chars {
+            if !self.try_chars.contains(&c) {
+                self.try_chars.push(c);
+            }
+        }
+        self.replacements.extend(other.replacements);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Returns whether the dictionary accepts `word`.
+    ///
+    /// A word that is capitalized or written in all caps is also accepted if its lowercase form
+    /// is, but a word that the dictionary lists with capitals (such as a name) must keep them.
+    pub fn contains(&self, word: &str) -> bool {
+        if self.words.contains(word) {
+            return true;
+        }
+        match Casing::of(word) {
+            Casing::Lower | Casing::Mixed => false,
+            Casing::Capitalized => self.words.contains(&word.to_lowercase()),
+            Casing::Upper => {
+                let lowercase = word.to_lowercase();
+                self.words.contains(&lowercase) || self.words.contains(&capitalize(&lowercase))
+            }
+        }
+    }
+
+    /// Returns up to `limit` words from the dictionary that `word` was likely meant to be.
+    pub fn suggestions(&self, word: &str, limit: usize) -> Vec<String> {
+        let casing = Casing::of(word);
+        let word = match casing {
+            Casing::Capitalized | Casing::Upper => word.to_lowercase(),
+            Casing::Lower | Casing::Mixed => word.to_string(),
+        };
+
+        let mut suggestions = Vec::new();
+        let push = |candidate: String, suggestions: &mut Vec<String>| {
+            if candidate != word && !suggestions.contains(&candidate) && self.contains(&candidate) {
+                suggestions.push(candidate);
+            }
+        };
+
+        for (from, to) in &self.replacements {
+            for (ix, _) in word.match_indices(from.as_str()) {
+                push(
+                    format!("{}{to}{}", &word[..ix], &word[ix + from.len()..]),
+                    &mut suggestions,
+                );
+            }
+        }
+        let single_edits = self.edits(&word);
+        for candidate in &single_edits {
+            push(candidate.clone(), &mut suggestions);
+        }
+        // Two edits away is only worth trying when one edit finds too little, as there are
+        // many more candidates.
+        if suggestions.len() < limit && word.chars().count() <= MAX_DOUBLE_EDIT_WORD_LEN {
+            for edit in &single_edits {
+                for candidate in self.edits(edit) {
+                    push(candidate, &mut suggestions);
+                }
+            }
+        }
+
+        suggestions.truncate(limit);
+        for suggestion in &mut suggestions {
+            match casing {
+                Casing::Capitalized => *suggestion = capitalize(suggestion),
+                Casing::Upper => *suggestion = suggestion.to_uppercase(),
+                Casing::Lower | Casing::Mixed => {}
+            }
+        }
+        suggestions
+    }
+
+    /// Returns every string one deletion, transposition, substitution or insertion away from
+    /// `word`.
+    fn edits(&self, word: &str) -> Vec<String> {
+        let chars = word.chars().collect::<Vec<_>>();
+        let alphabet = if self.try_chars.is_empty() {
+            ('a'..='z').collect()
+        } else {
+            self.try_chars.clone()
+        };
+        let to_string = |chars: &[char]| chars.iter().collect::<String>();
+
+        let mut edits = Vec::new();
+        for ix in 0..chars.len() {
+            let mut edit = chars.clone();
+            edit.remove(ix);
+            edits.push(to_string(&edit));
+        }
+        for ix in 1..chars.len() {
+            let mut edit = chars.clone();
+            edit.swap(ix - 1, ix);
+            edits.push(to_string(&edit));
+        }
+        for ix in 0..chars.len() {
+            for &c in &alphabet {
+                if chars[ix] != c {
+                    let mut edit = chars.clone();
+                    edit[ix] = c;
+                    edits.push(to_string(&edit));
+                }
+            }
+        }
+        for ix in 0..=chars.len() {
+            for &c in &alphabet {
+                let mut edit = chars.clone();
+                edit.insert(ix, c);
+                edits.push(to_string(&edit));
+            }
+        }
+        edits
+    }
+}
+
+const MAX_DOUBLE_EDIT_WORD_LEN: usize = 12;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Casing {
+    Lower,
+    Capitalized,
+    Upper,
+    Mixed,
+}
+
+impl Casing {
+    fn of(word: &str) -> Self {
+        let mut letters = word.chars().filter(|c| c.is_alphabetic());
+        let Some(first) = letters.next() else {
+            return Casing::Lower;
+        };
+        let (mut has_lower, mut has_upper) = (false, false);
+        for c in letters {
+            has_lower |= c.is_lowercase();
+            has_upper |= c.is_uppercase();
+        }
+        match (first.is_uppercase(), has_lower, has_upper) {
+            (false, _, false) => Casing::Lower,
+            (true, _, false) => Casing::Capitalized,
+            (true, false, true) => Casing::Upper,
+            _ => Casing::Mixed,
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "
+        TRY esianrtolcdugmphbyfvkwz
+        REP 1
+        REP ph f
+
+        SFX S Y 2
+        SFX S y ies [^aeiou]y
+        SFX S 0 s [^y]
+
+        SFX D Y 2
+        SFX D 0 d e
+        SFX D 0 ed [^e]
+
+        PFX U Y 1
+        PFX U 0 un .
+
+        NEEDAFFIX N
+        FORBIDDENWORD X
+    ";
+
+    const DIC: &str = "6
+        spell/DS
+        library/S
+        lock/DSU
+        Paris
+        wrapp/NS
+        teh/X
+    ";
+
+    #[test]
+    fn test_affix_expansion() {
+        let dictionary = Dictionary::parse(AFF, DIC).unwrap();
+        for word in [
+            "spell",
+            "spells",
+            "spelled",
+            "library",
+            "libraries",
+            "lock",
+            "unlock",
+            "unlocks",
+            "unlocked",
+            "wrapps",
+        ] {
+            assert!(dictionary.contains(word), "{word} should be accepted");
+        }
+        for word in ["librarys", "unspell", "wrapp", "teh", "spel"] {
+            assert!(!dictionary.contains(word), "{word} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_casing() {
+        let dictionary = Dictionary::parse(AFF, DIC).unwrap();
+        assert!(dictionary.contains("Spell"));
+        assert!(dictionary.contains("SPELLS"));
+        assert!(dictionary.contains("PARIS"));
+        assert!(!dictionary.contains("paris"));
+        assert!(!dictionary.contains("sPell"));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let dictionary = Dictionary::parse(AFF, DIC).unwrap();
+        assert_eq!(dictionary.suggestions("spel", 1), vec!["spell"]);
+        assert_eq!(dictionary.suggestions("Lcok", 1), vec!["Lock"]);
+        assert_eq!(dictionary.suggestions("librarise", 1), vec!["libraries"]);
+        assert!(dictionary.suggestions("xyzzy", 3).is_empty());
+    }
+
+    #[test]
+    fn test_flag_formats() {
+        let dictionary =
+            Dictionary::parse("FLAG long\nSFX Aa Y 1\nSFX Aa 0 s .\n", "1\nword/Aa\n").unwrap();
+        assert!(dictionary.contains("words"));
+
+        let dictionary =
+            Dictionary::parse("FLAG num\nSFX 12 Y 1\nSFX 12 0 s .\n", "word/3,12\n").unwrap();
+        assert!(dictionary.contains("words"));
+    }
+
+    #[test]
+    fn test_bundled_dictionary() {
+        let dictionary = Dictionary::parse(
+            include_str!("../../../assets/dictionaries/en_US.aff"),
+            include_str!("../../../assets/dictionaries/en_US.dic"),
+        )
+        .unwrap();
+        for word in [
+            "the",
+            "receive",
+            "configuration",
+            "configurations",
+            "iterator",
+            "returns",
+            "handled",
+            "parsing",
+        ] {
+            assert!(dictionary.contains(word), "{word} should be accepted");
+        }
+        for word in ["teh", "recieve", "seperate", "occured"] {
+            assert!(!dictionary.contains(word), "{word} should be rejected");
+        }
+        assert!(
+            dictionary
+                .suggestions("recieve", 5)
+                .contains(&"receive".to_string())
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Offline spell checking for comments, strings and prose.
+//!
+//! Misspelled words are reported as diagnostics through the project's [`LspStore`], the same
+//! pipeline language servers use, and come with code actions that replace the word with a
+//! suggestion or add it to the user's or the project's word list.
+//!
+//! [`LspStore`]: project::LspStore
+
+mod checkable_ranges;
+mod code_actions;
+pub mod dictionary;
+mod spell_check_store;
+pub mod tokenizer;
+
+use std::{ops::Range, path::PathBuf, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use collections::HashSet;
+use fs::Fs;
+use gpui::{App, AppContext as _, AsyncApp, Context, Window};
+use language::BufferSnapshot;
+use settings::{RegisterSetting, Settings};
+use workspace::Workspace;
+
+pub use checkable_ranges::checkable_ranges;
+pub use code_actions::SpellCheckCodeActionProvider;
+pub use dictionary::Dictionary;
+pub use spell_check_store::{SpellCheckStore, WordListScope};
+
+/// The source of the diagnostics reported for misspelled words.
+pub const DIAGNOSTIC_SOURCE: &str = "spell-check";
+
+const BUNDLED_DICTIONARY: &str = "dictionaries/en_US";
+
+#[derive(Clone, Debug, PartialEq, RegisterSetting)]
+pub struct SpellCheckSettings {
+    /// Whether to check the spelling of comments, strings and prose files.
+    pub enabled: bool,
+    /// Paths to additional Hunspell dictionaries, each a `.dic` file with a matching `.aff` file
+    /// alongside it.
+    pub dictionaries: Vec<PathBuf>,
+}
+
+impl Settings for SpellCheckSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let spell_check = content.spell_check.clone().unwrap();
+        Self {
+            enabled: spell_check.enabled.unwrap(),
+            dictionaries: spell_check
+                .dictionaries
+                .unwrap_or_default()
+                .into_iter()
+                .map(PathBuf::from)
+                .collect(),
+        }
+    }
+}
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(
+        |workspace: &mut Workspace, window: Option<&mut Window>, cx: &mut Context<Workspace>| {
+            let Some(window) = window else {
+                return;
+            };
+            let project = workspace.project().clone();
+            if !project.read(cx).is_local() {
+                return;
+            }
+            let spell_check_store = cx.new(|cx| SpellCheckStore::new(project, cx));
+            cx.subscribe_in(
+                &cx.entity(),
+                window,
+                move |_, _, event: &workspace::Event, window, cx| {
+                    if let workspace::Event::ItemAdded { item } = event
+                        && let Some(editor) = item.act_as::<editor::Editor>(cx)
+                    {
+                        let provider = SpellCheckCodeActionProvider::new(spell_check_store.clone());
+                        editor.update(cx, |editor, cx| {
+                            editor.add_code_action_provider(std::rc::Rc::new(provider), window, cx);
+                        });
+                    }
+                },
+            )
+            .detach();
+        },
+    )
+    .detach();
+}
+
+/// Loads the bundled English dictionary along with the dictionaries listed in the settings.
+///
+/// Dictionaries that fail to load are logged and skipped, so that one bad path doesn't disable
+/// spell checking altogether.
+pub(crate) async fn load_dictionary(
+    fs: Arc<dyn Fs>,
+    paths: Vec<PathBuf>,
+    cx: &mut AsyncApp,
+) -> Arc<Dictionary> {
+    let asset_source = cx.update(|cx| cx.asset_source().clone());
+    let bundled = cx
+        .background_spawn(async move {
+            let load = |extension: &str| -> Result<String> {
+                let path = format!("{BUNDLED_DICTIONARY}.{extension}");
+                let contents = asset_source
+                    .load(&path)?
+                    .with_context(|| format!("missing asset {path}"))?;
+                Ok(String::from_utf8(contents.into_owned())?)
+            };
+            Dictionary::parse(&load("aff")?, &load("dic")?)
+        })
+        .await;
+
+    let mut dictionary = match bundled {
+        Ok(dictionary) => dictionary,
+        Err(error) => {
+            log::debug!("failed to load the bundled dictionary: {error:#}");
+            Dictionary::default()
+        }
+    };
+    for dic_path in paths {
+        let aff_path = dic_path.with_extension("aff");
+        let result = async {
+            let dic = fs.load(&dic_path).await?;
+            let aff = fs.load(&aff_path).await.unwrap_or_default();
+            cx.background_spawn(async move { Dictionary::parse(&aff, &dic) })
+                .await
+        }
+        .await;
+        match result {
+            Ok(other) => dictionary.extend(other),
+            Err(error) => log::error!("failed to load dictionary {dic_path:?}: {error:#}"),
+        }
+    }
+    Arc::new(dictionary)
+}
+
+/// Parses a word list, which holds one word per line. Blank lines and lines starting with `#`
+/// are ignored.
+pub(crate) fn parse_word_list(contents: &str) -> HashSet<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Returns the byte ranges of the words in `snapshot` that are neither in `dictionary` nor in
+/// `accepted_words`, which must be lowercase.
+pub fn misspellings(
+    snapshot: &BufferSnapshot,
+    dictionary: &Dictionary,
+    accepted_words: &HashSet<String>,
+) -> Vec<Range<usize>> {
+    let mut misspellings = Vec::new();
+    for range in checkable_ranges(snapshot) {
+        let text = snapshot.text_for_range(range.clone()).collect::<String>();
+        for word_range in tokenizer::words(&text) {
+            let word = &text[word_range.clone()];
+            if !dictionary.contains(word) && !accepted_words.contains(&word.to_lowercase()) {
+                misspellings.push(range.start + word_range.start..range.start + word_range.end);
+            }
+        }
+    }
+    misspellings
+}
@@ -0,0 +1,549 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use futures::StreamExt as _;
+use gpui::{App, AppContext as _, Context, Entity, Subscription, Task, WeakEntity};
+use language::{Buffer, BufferEvent, BufferId, File as _, ToPointUtf16 as _, point_to_lsp};
+use project::{
+    Fs, Project, WorktreeId,
+    buffer_store::{BufferStore, BufferStoreEvent},
+};
+use settings::{Settings as _, SettingsStore};
+use util::ResultExt as _;
+
+use crate::{
+    DIAGNOSTIC_SOURCE, Dictionary, SpellCheckSettings, load_dictionary, misspellings,
+    parse_word_list,
+};
+
+/// How long to wait after a buffer changes before checking it again.
+const CHECK_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Which word list a word is added to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WordListScope {
+    /// The user's word list, which applies to every project.
+    User,
+    /// The word list of the worktree containing the buffer.
+    Project,
+}
+
+struct CheckedBuffer {
+    buffer: WeakEntity<Buffer>,
+    pending_check: Task<()>,
+    _subscriptions: [Subscription; 2],
+}
+
+/// Checks the spelling of a project's open buffers and reports misspellings as diagnostics.
+pub struct SpellCheckStore {
+    project: WeakEntity<Project>,
+    fs: Arc<dyn Fs>,
+    /// The ID that diagnostics are reported under, as if they came from a language server.
+    server_id: lsp::LanguageServerId,
+    settings: SpellCheckSettings,
+    dictionary: Option<Arc<Dictionary>>,
+    user_words: HashSet<String>,
+    project_words: HashMap<WorktreeId, HashSet<String>>,
+    buffers: HashMap<BufferId, CheckedBuffer>,
+    _load_dictionary: Task<()>,
+    _watch_user_words: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SpellCheckStore {
+    pub fn new(project: Entity<Project>, cx: &mut Context<Self>) -> Self {
+        let server_id = project
+            .read(cx)
+            .lsp_store()
+            .update(cx, |lsp_store, _| lsp_store.register_diagnostic_source());
+        let (fs, buffer_store, worktree_ids) = {
+            let project = project.read(cx);
+            (
+                project.fs().clone(),
+                project.buffer_store().clone(),
+                project
+                    .worktrees(cx)
+                    .map(|worktree| worktree.read(cx).id())
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        let (mut user_words_rx, user_words_watcher) = settings::watch_config_file(
+            cx.background_executor(),
+            fs.clone(),
+            paths::user_dictionary_file().clone(),
+        );
+        let watch_user_words = cx.spawn(async move |this, cx| {
+            let _watcher = user_words_watcher;
+            while let Some(contents) = user_words_rx.next().await {
+                let words = parse_word_list(&contents);
+                if this
+                    .update(cx, |this, cx| {
+                        this.user_words = words;
+                        this.check_all_buffers(cx);
+                    })
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        let subscriptions = vec![
+            cx.subscribe(&buffer_store, Self::on_buffer_store_event),
+            cx.subscribe(&project, Self::on_project_event),
+            cx.observe_global::<SettingsStore>(Self::on_settings_changed),
+        ];
+
+        let settings = SpellCheckSettings::get_global(cx).clone();
+        let mut this = Self {
+            project: project.downgrade(),
+            fs,
+            server_id,
+            settings: settings.clone(),
+            dictionary: None,
+            user_words: HashSet::default(),
+            project_words: HashMap::default(),
+            buffers: HashMap::default(),
+            _load_dictionary: Task::ready(()),
+            _watch_user_words: watch_user_words,
+            _subscriptions: subscriptions,
+        };
+        if settings.enabled {
+            this.reload_dictionary(cx);
+        }
+        for worktree_id in worktree_ids {
+            this.reload_project_words(worktree_id, cx);
+        }
+        for buffer in buffer_store.read(cx).buffers().collect::<Vec<_>>() {
+            this.register_buffer(&buffer, cx);
+        }
+        this
+    }
+
+    /// The ID that misspellings are reported under.
+    pub fn server_id(&self) -> lsp::LanguageServerId {
+        self.server_id
+    }
+
+    pub fn dictionary(&self) -> Option<&Arc<Dictionary>> {
+        self.dictionary.as_ref()
+    }
+
+    /// Adds `word` to a word list, both on disk and in memory, so that it's no longer reported.
+    pub fn add_to_word_list(
+        &mut self,
+        word: &str,
+        scope: WordListScope,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let path = match scope {
+            WordListScope::User => {
+                self.user_words.insert(word.to_lowercase());
+                paths::user_dictionary_file().clone()
+            }
+            WordListScope::Project => {
+                let Some((worktree_id, worktree_path)) = self.worktree_for_buffer(buffer, cx)
+                else {
+                    return Task::ready(Err(anyhow::anyhow!(
+                        "cannot add a word to the project dictionary for a file outside of the project"
+                    )));
+                };
+                self.project_words
+                    .entry(worktree_id)
+                    .or_default()
+                    .insert(word.to_lowercase());
+                worktree_path.join(paths::local_dictionary_file_relative_path().as_std_path())
+            }
+        };
+        self.check_all_buffers(cx);
+
+        let fs = self.fs.clone();
+        let word = word.to_string();
+        cx.background_spawn(async move {
+            let mut contents = fs.load(&path).await.unwrap_or_default();
+            if parse_word_list(&contents).contains(&word.to_lowercase()) {
+                return Ok(());
+            }
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                contents.push('\n');
+            }
+            contents.push_str(&word);
+            contents.push('\n');
+            if let Some(parent) = path.parent() {
+                fs.create_dir(parent).await?;
+            }
+            fs.atomic_write(path.clone(), contents)
+                .await
+                .with_context(|| format!("writing word list {path:?}"))
+        })
+    }
+
+    fn worktree_for_buffer(
+        &self,
+        buffer: &Entity<Buffer>,
+        cx: &App,
+    ) -> Option<(WorktreeId, Arc<std::path::Path>)> {
+        let worktree_id = buffer.read(cx).file()?.worktree_id(cx);
+        let worktree = self
+            .project
+            .upgrade()?
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)?;
+        let worktree = worktree.read(cx);
+        worktree
+            .is_local()
+            .then(|| (worktree_id, worktree.abs_path()))
+    }
+
+    fn on_buffer_store_event(
+        &mut self,
+        _: Entity<BufferStore>,
+        event: &BufferStoreEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let BufferStoreEvent::BufferAdded(buffer) = event {
+            self.register_buffer(buffer, cx);
+        }
+    }
+
+    fn on_project_event(
+        &mut self,
+        _: Entity<Project>,
+        event: &project::Event,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            project::Event::WorktreeAdded(worktree_id) => {
+                self.reload_project_words(*worktree_id, cx);
+            }
+            project::Event::WorktreeRemoved(worktree_id) => {
+                if self.project_words.remove(worktree_id).is_some() {
+                    self.check_all_buffers(cx);
+                }
+            }
+            project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                let word_list_path = paths::local_dictionary_file_relative_path();
+                if changes
+                    .iter()
+                    .any(|(path, _, _)| path.as_ref() == word_list_path)
+                {
+                    self.reload_project_words(*worktree_id, cx);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_settings_changed(&mut self, cx: &mut Context<Self>) {
+        let settings = SpellCheckSettings::get_global(cx).clone();
+        if settings == self.settings {
+            return;
+        }
+        let dictionaries_changed = settings.dictionaries != self.settings.dictionaries;
+        self.settings = settings;
+        if !self.settings.enabled {
+            self.clear_all_diagnostics(cx);
+        } else if dictionaries_changed || self.dictionary.is_none() {
+            self.reload_dictionary(cx);
+        } else {
+            self.check_all_buffers(cx);
+        }
+    }
+
+    fn reload_dictionary(&mut self, cx: &mut Context<Self>) {
+        let fs = self.fs.clone();
+        let paths = self.settings.dictionaries.clone();
+        self._load_dictionary = cx.spawn(async move |this, cx| {
+            let dictionary = load_dictionary(fs, paths, cx).await;
+            this.update(cx, |this, cx| {
+                this.dictionary = Some(dictionary);
+                this.check_all_buffers(cx);
+            })
+            .ok();
+        });
+    }
+
+    fn reload_project_words(&mut self, worktree_id: WorktreeId, cx: &mut Context<Self>) {
+        let Some(worktree) = self
+            .project
+            .upgrade()
+            .and_then(|project| project.read(cx).worktree_for_id(worktree_id, cx))
+        else {
+            return;
+        };
+        let worktree = worktree.read(cx);
+        if !worktree.is_local() {
+            return;
+        }
+        let path = worktree
+            .abs_path()
+            .join(paths::local_dictionary_file_relative_path().as_std_path());
+        let fs = self.fs.clone();
+        cx.spawn(async move |this, cx| {
+            let words = fs
+                .load(&path)
+                .await
+                .map(|contents| parse_word_list(&contents))
+                .unwrap_or_default();
+            this.update(cx, |this, cx| {
+                let previous = if words.is_empty() {
+                    this.project_words.remove(&worktree_id)
+                } else {
+                    this.project_words.insert(worktree_id, words.clone())
+                };
+                if previous.unwrap_or_default() != words {
+                    this.check_all_buffers(cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn register_buffer(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let buffer_id = buffer.read(cx).remote_id();
+        if self.buffers.contains_key(&buffer_id) {
+            return;
+        }
+        let subscriptions = [
+            cx.subscribe(buffer, |this, buffer, event, cx| match event {
+                BufferEvent::Edited { .. }
+                | BufferEvent::Reparsed
+                | BufferEvent::LanguageChanged(_)
+                | BufferEvent::Reloaded
+                | BufferEvent::FileHandleChanged => this.check_buffer(&buffer, cx),
+                _ => {}
+            }),
+            // The project clears the buffer's diagnostics when it's released.
+            cx.observe_release(buffer, move |this, _, _| {
+                this.buffers.remove(&buffer_id);
+            }),
+        ];
+        self.buffers.insert(
+            buffer_id,
+            CheckedBuffer {
+                buffer: buffer.downgrade(),
+                pending_check: Task::ready(()),
+                _subscriptions: subscriptions,
+            },
+        );
+        self.check_buffer(buffer, cx);
+    }
+
+    fn check_all_buffers(&mut self, cx: &mut Context<Self>) {
+        let buffers = self
+            .buffers
+            .values()
+            .filter_map(|checked| checked.buffer.upgrade())
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.check_buffer(&buffer, cx);
+        }
+    }
+
+    /// Checks the buffer once it has stopped changing, replacing its previous diagnostics.
+    fn check_buffer(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        if !self.settings.enabled {
+            return;
+        }
+        let Some(dictionary) = self.dictionary.clone().filter(|d| !d.is_empty()) else {
+            return;
+        };
+        let buffer_id = buffer.read(cx).remote_id();
+        let Some(checked) = self.buffers.get_mut(&buffer_id) else {
+            return;
+        };
+
+        let mut accepted_words = self.user_words.clone();
+        if let Some(file) = buffer.read(cx).file()
+            && let Some(words) = self.project_words.get(&file.worktree_id(cx))
+        {
+            accepted_words.extend(words.iter().cloned());
+        }
+        let buffer = buffer.downgrade();
+        checked.pending_check = cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(CHECK_DEBOUNCE).await;
+            let Ok(snapshot) = buffer.read_with(cx, |buffer, _| buffer.snapshot()) else {
+                return;
+            };
+            let diagnostics = cx
+                .background_spawn({
+                    let snapshot = snapshot.clone();
+                    async move {
+                        misspellings(&snapshot, &dictionary, &accepted_words)
+                            .into_iter()
+                            .map(|range| {
+                                let word =
+                                    snapshot.text_for_range(range.clone()).collect::<String>();
+                                lsp::Diagnostic {
+                                    range: lsp::Range::new(
+                                        point_to_lsp(range.start.to_point_utf16(&snapshot)),
+                                        point_to_lsp(range.end.to_point_utf16(&snapshot)),
+                                    ),
+                                    severity: Some(lsp::DiagnosticSeverity::INFORMATION),
+                                    source: Some(DIAGNOSTIC_SOURCE.to_string()),
+                                    message: format!("Unknown word \"{word}\""),
+                                    ..Default::default()
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    }
+                })
+                .await;
+
+            this.update(cx, |this, cx| {
+                let Some(buffer) = buffer.upgrade() else {
+                    return;
+                };
+                // The buffer changed while it was being checked, and another check is pending.
+                if buffer.read(cx).version() != snapshot.version() {
+                    return;
+                }
+                this.publish_diagnostics(&buffer, diagnostics, cx);
+            })
+            .ok();
+        });
+    }
+
+    fn clear_all_diagnostics(&mut self, cx: &mut Context<Self>) {
+        for checked in self.buffers.values_mut() {
+            checked.pending_check = Task::ready(());
+        }
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let server_id = self.server_id;
+        project.read(cx).lsp_store().update(cx, |lsp_store, cx| {
+            lsp_store.clear_diagnostic_source(server_id, cx);
+        });
+    }
+
+    fn publish_diagnostics(
+        &self,
+        buffer: &Entity<Buffer>,
+        diagnostics: Vec<lsp::Diagnostic>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let server_id = self.server_id;
+        project.read(cx).lsp_store().update(cx, |lsp_store, cx| {
+            lsp_store
+                .publish_buffer_diagnostics(server_id, buffer, diagnostics, cx)
+                .log_err();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use language::DiagnosticEntry;
+    use project::FakeFs;
+    use serde_json::json;
+    use util::path;
+    use workspace::AppState;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    let spell_check = settings.spell_check.get_or_insert_default();
+                    spell_check.enabled = Some(true);
+                    spell_check.dictionaries = Some(vec![path!("/dictionaries/en.dic").into()]);
+                });
+            });
+        });
+    }
+
+    fn diagnostics(buffer: &Entity<Buffer>, cx: &mut TestAppContext) -> Vec<String> {
+        buffer.read_with(cx, |buffer, _| {
+            let snapshot = buffer.snapshot();
+            snapshot
+                .diagnostics_in_range::<_, usize>(0..snapshot.len(), false)
+                .map(|entry| {
+                    let entry: DiagnosticEntry<usize> = entry.to_owned();
+                    assert_eq!(entry.diagnostic.source.as_deref(), Some(DIAGNOSTIC_SOURCE));
+                    snapshot.text_for_range(entry.range).collect()
+                })
+                .collect()
+        })
+    }
+
+    #[gpui::test]
+    async fn test_reporting_and_accepting_misspellings(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dictionaries"),
+            json!({
+                "en.aff": "SFX S Y 1\nSFX S 0 s .\n",
+                "en.dic": "4\nsome/S\nword/S\nhere\nthere\n",
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "notes.txt": "Some wrods here and thier words there.",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let store = cx.new(|cx| SpellCheckStore::new(project.clone(), cx));
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/dir/notes.txt"), cx)
+            })
+            .await
+            .unwrap();
+        cx.executor().advance_clock(CHECK_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(diagnostics(&buffer, cx), ["wrods", "and", "thier"]);
+
+        store
+            .update(cx, |store, cx| {
+                store.add_to_word_list("and", WordListScope::User, &buffer, cx)
+            })
+            .await
+            .unwrap();
+        store
+            .update(cx, |store, cx| {
+                store.add_to_word_list("Thier", WordListScope::Project, &buffer, cx)
+            })
+            .await
+            .unwrap();
+        cx.executor().advance_clock(CHECK_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(diagnostics(&buffer, cx), ["wrods"]);
+        assert_eq!(
+            fs.load(path!("/dir/.zed/dictionary.txt").as_ref())
+                .await
+                .unwrap(),
+            "Thier\n"
+        );
+
+        buffer.update(cx, |buffer, cx| buffer.edit([(5..10, "words")], None, cx));
+        cx.executor().advance_clock(CHECK_DEBOUNCE);
+        cx.run_until_parked();
+        assert!(diagnostics(&buffer, cx).is_empty());
+
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings.spell_check.get_or_insert_default().enabled = Some(false);
+                });
+            });
+        });
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "Nwe ")], None, cx));
+        cx.executor().advance_clock(CHECK_DEBOUNCE);
+        cx.run_until_parked();
+        assert!(diagnostics(&buffer, cx).is_empty());
+    }
+}
@@ -0,0 +1,151 @@
+//! Splits text into the words that should be spell checked.
+//!
+//! Identifiers are split into their parts, so that `parseHttpResponse`, `parse_http_response`
+//! and `ParseHTTPResponse` are each checked as `parse`, `Http` (or `HTTP`) and `Response`.
+//! Tokens that look like URLs, email addresses, paths or qualified names are skipped entirely, as
+//! are words containing digits, words in all caps (usually acronyms) and very short words.
+
+use std::ops::Range;
+
+/// Words shorter than this are not checked.
+const MIN_WORD_LEN: usize = 3;
+
+/// Returns the byte ranges of the words in `text` that should be spell checked.
+pub fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    for (token_start, token) in split_with_offsets(text, char::is_whitespace) {
+        for (segment_start, segment) in split_escapes(token) {
+            let trimmed = segment.trim_matches(|c: char| !c.is_alphanumeric());
+            if trimmed.is_empty() || is_skipped_token(trimmed) {
+                continue;
+            }
+            let trimmed_start = token_start + segment_start + substring_offset(segment, trimmed);
+            for (piece_start, piece) in
+                split_with_offsets(trimmed, |c| !(c.is_alphanumeric() || c == '\''))
+            {
+                let part = piece.trim_matches('\'');
+                if part.chars().any(|c| c.is_numeric()) {
+                    continue;
+                }
+                let part_start = trimmed_start + piece_start + substring_offset(piece, part);
+                for word in split_camel_case(part) {
+                    let range = part_start + word.start..part_start + word.end;
+                    let word = &text[range.clone()];
+                    if word.chars().count() >= MIN_WORD_LEN && !is_acronym(word) {
+                        words.push(range);
+                    }
+                }
+            }
+        }
+    }
+    words
+}
+
+/// Returns whether a token refers to something other than prose, such as a URL or a path.
+fn is_skipped_token(token: &str) -> bool {
+    token.contains("://")
+        || token.starts_with("www.")
+        || token.contains(['@', '/', '\\', ':', '.', '=', '$', '#', '<', '>', '%'])
+}
+
+fn is_acronym(word: &str) -> bool {
+    word.chars().all(|c| !c.is_lowercase())
+}
+
+/// Splits `text` on characters matching `separator`, returning the non-empty pieces along with
+/// their byte offsets.
+fn split_with_offsets(
+    text: &str,
+    separator: impl Fn(char) -> bool,
+) -> impl Iterator<Item = (usize, &str)> {
+    text.split(separator)
+        .filter(|piece| !piece.is_empty())
+        .map(move |piece| (substring_offset(text, piece), piece))
+}
+
+/// Splits a token on backslash escape sequences such as `\n`, which would otherwise be read as
+/// part of the following word.
+fn split_escapes(token: &str) -> Vec<(usize, &str)> {
+    let mut segments = Vec::new();
+    let mut segment_start = 0;
+    let mut chars = token.char_indices();
+    while let Some((ix, c)) = chars.next() {
+        if c == '\\' {
+            segments.push((segment_start, &token[segment_start..ix]));
+            segment_start = match chars.next() {
+                Some((ix, c)) => ix + c.len_utf8(),
+                None => token.len(),
+            };
+        }
+    }
+    segments.push((segment_start, &token[segment_start..]));
+    segments.retain(|(_, segment)| !segment.is_empty());
+    segments
+}
+
+/// Splits an identifier at case changes, keeping runs of capitals such as `HTTP` together.
+fn split_camel_case(identifier: &str) -> Vec<Range<usize>> {
+    let chars = identifier.char_indices().collect::<Vec<_>>();
+    let mut words = Vec::new();
+    let mut word_start = 0;
+    for (ix, &(offset, c)) in chars.iter().enumerate().skip(1) {
+        let previous = chars[ix - 1].1;
+        let next = chars.get(ix + 1).map(|(_, c)| *c);
+        let is_boundary = c.is_uppercase()
+            && (previous.is_lowercase()
+                || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase())));
+        if is_boundary {
+            words.push(word_start..offset);
+            word_start = offset;
+        }
+    }
+    words.push(word_start..identifier.len());
+    words
+}
+
+/// Returns the byte offset of `substring` within `text`, which it must be a slice of.
+fn substring_offset(text: &str, substring: &str) -> usize {
+    substring.as_ptr() as usize - text.as_ptr() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(text: &str, expected: &[&str]) {
+        let actual = words(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect::<Vec<_>>();
+        assert_eq!(actual, expected, "words in {text:?}");
+    }
+
+    #[test]
+    fn test_prose() {
+        check(
+            "// Hello, wrold! It's (mostly) fine.",
+            &["Hello", "wrold", "It's", "mostly", "fine"],
+        );
+        check("well-known and so on", &["well", "known", "and"]);
+    }
+
+    #[test]
+    fn test_identifiers() {
+        check("parseHttpResponse", &["parse", "Http", "Response"]);
+        check("ParseHTTPResponse", &["Parse", "Response"]);
+        check("parse_http_response", &["parse", "http", "response"]);
+        check("MAX_BUFFER_SIZE utf8 base64Encode value", &["value"]);
+    }
+
+    #[test]
+    fn test_skipped_tokens() {
+        check(
+            "see https://example.com/docs or me@example.com in src/main.rs via std::fs",
+            &["see", "via"],
+        );
+        check(
+            "\"line\\nbreak\\tand tab\"",
+            &["line", "break", "and", "tab"],
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Extracts a file's symbols from its outline, as defined by its language's `outline.scm` query.
+
+use std::ops::Range;
+
+use language::BufferSnapshot;
+use serde::{Deserialize, Serialize};
+
+/// A symbol found in a file's outline.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct OutlineSymbol {
+    pub name: String,
+    pub kind: lsp::SymbolKind,
+    pub container_name: Option<String>,
+    /// The outline item's text, such as `pub fn foo`, which is shown as the symbol's label.
+    pub text: String,
+    /// The range of the symbol's name within `text`.
+    pub name_range: Range<usize>,
+    /// The start of the symbol's item, as a UTF-16 row and column.
+    pub start: (u32, u32),
+    /// The end of the symbol's item, as a UTF-16 row and column.
+    pub end: (u32, u32),
+}
+
+/// Returns the symbols in the outline of the given snapshot, in the order they appear.
+///
+/// Trait implementations and other items without a kind of their own aren't returned, but still
+/// serve as the container of the items nested inside them.
+pub(crate) fn outline_symbols(snapshot: &BufferSnapshot) -> Vec<OutlineSymbol> {
+    let mut containers: Vec<(usize, String, Option<lsp::SymbolKind>)> = Vec::new();
+    let mut symbols = Vec::new();
+    for item in snapshot.outline_items_as_points_containing(0..snapshot.len(), false, None) {
+        while containers
+            .last()
+            .is_some_and(|(depth, _, _)| *depth >= item.depth)
+        {
+            containers.pop();
+        }
+        let (Some(first_name), Some(last_name)) =
+            (item.name_ranges.first(), item.name_ranges.last())
+        else {
+            continue;
+        };
+        let name_range = first_name.start..last_name.end;
+        let Some(name) = item.text.get(name_range.clone()) else {
+            continue;
+        };
+        let container = containers.last();
+        let kind = symbol_kind(
+            &item.text[..name_range.start],
+            container.map(|(_, _, kind)| *kind),
+        );
+        let container_name = container.map(|(_, name, _)| name.clone());
+        // Name implementations after the type they implement, so that their items are shown as
+        // belonging to it.
+        let container_name_for_children = match kind {
+            Some(_) => name.to_string(),
+            None => item.text[last_name.clone()].to_string(),
+        };
+        containers.push((item.depth, container_name_for_children, kind));
+
+        let Some(kind) = kind else {
+            continue;
+        };
+        let start = snapshot.point_to_point_utf16(item.range.start);
+        let end = snapshot.point_to_point_utf16(item.range.end);
+        symbols.push(OutlineSymbol {
+            name: name.to_string(),
+            kind,
+            container_name,
+            text: item.text.clone(),
+            name_range,
+            start: (start.row, start.column),
+            end: (end.row, end.column),
+        });
+    }
+    symbols
+}
+
+/// Infers the kind of an outline item from the keywords before its name, such as `fn` or
+/// `class`, and from the kind of the item containing it.
+///
+/// Returns `None` for implementation blocks, which aren't symbols in their own right.
+fn symbol_kind(
+    text_before_name: &str,
+    container_kind: Option<Option<lsp::SymbolKind>>,
+) -> Option<lsp::SymbolKind> {
+    let has_container = container_kind.is_some();
+    for keyword in text_before_name.split_whitespace().rev() {
+        let kind = match keyword {
+            "fn" | "func" | "function" | "def" | "fun" => {
+                if has_container {
+                    lsp::SymbolKind::METHOD
+                } else {
+                    lsp::SymbolKind::FUNCTION
+                }
+            }
+            "impl" | "extension" => return None,
+            "class" => lsp::SymbolKind::CLASS,
+            "struct" | "record" | "union" => lsp::SymbolKind::STRUCT,
+            "enum" => lsp::SymbolKind::ENUM,
+            "trait" | "interface" | "protocol" => lsp::SymbolKind::INTERFACE,
+            "mod" | "module" => lsp::SymbolKind::MODULE,
+            "namespace" | "package" => lsp::SymbolKind::NAMESPACE,
+            "const" => lsp::SymbolKind::CONSTANT,
+            "static" | "let" | "var" | "val" => lsp::SymbolKind::VARIABLE,
+            "type" | "typealias" => lsp::SymbolKind::TYPE_PARAMETER,
+            "macro_rules!" | "macro" => lsp::SymbolKind::FUNCTION,
+            _ => continue,
+        };
+        return Some(kind);
+    }
+    Some(match container_kind {
+        Some(Some(lsp::SymbolKind::ENUM)) => lsp::SymbolKind::ENUM_MEMBER,
+        Some(_) => lsp::SymbolKind::FIELD,
+        None => lsp::SymbolKind::VARIABLE,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use language::{Buffer, rust_lang};
+
+    #[gpui::test]
+    fn test_outline_symbols(cx: &mut TestAppContext) {
+        let text = indoc! {"
+            pub struct Point {
+                x: i32,
+            }
+
+            enum Shape {
+                Circle,
+            }
+
+            impl Display for Point {
+                fn fmt(&self) {}
+            }
+
+            fn main() {}
+        "};
+        let snapshot =
+            cx.update(|cx| Buffer::build_snapshot_sync(text.into(), Some(rust_lang()), None, cx));
+        let symbols = outline_symbols(&snapshot)
+            .into_iter()
+            .map(|symbol| {
+                (
+                    symbol.name,
+                    symbol.kind,
+                    symbol.container_name,
+                    symbol.start.0,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            symbols,
+            [
+                ("Point".into(), lsp::SymbolKind::STRUCT, None, 0),
+                ("x".into(), lsp::SymbolKind::FIELD, Some("Point".into()), 1),
+                ("Shape".into(), lsp::SymbolKind::ENUM, None, 4),
+                (
+                    "Circle".into(),
+                    lsp::SymbolKind::ENUM_MEMBER,
+                    Some("Shape".into()),
+                    5
+                ),
+                (
+                    "fmt".into(),
+                    lsp::SymbolKind::METHOD,
+                    Some("Point".into()),
+                    9
+                ),
+                ("main".into(), lsp::SymbolKind::FUNCTION, None, 12),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_symbol_kind() {
+        assert_eq!(
+            symbol_kind("pub async fn ", None),
+            Some(lsp::SymbolKind::FUNCTION)
+        );
+        assert_eq!(
+            symbol_kind("def ", Some(Some(lsp::SymbolKind::CLASS))),
+            Some(lsp::SymbolKind::METHOD)
+        );
+        assert_eq!(symbol_kind("impl ", None), None);
+        assert_eq!(
+            symbol_kind("export default class ", None),
+            Some(lsp::SymbolKind::CLASS)
+        );
+        assert_eq!(symbol_kind("", None), Some(lsp::SymbolKind::VARIABLE));
+    }
+}
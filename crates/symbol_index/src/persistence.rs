@@ -0,0 +1,94 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::Result;
+use db::{
+    query,
+    sqlez::{domain::Domain, thread_safe_connection::ThreadSafeConnection},
+    sqlez_macros::sql,
+};
+use fs::MTime;
+
+pub struct SymbolIndexDb(ThreadSafeConnection);
+
+impl Domain for SymbolIndexDb {
+    const NAME: &str = stringify!(SymbolIndexDb);
+
+    // Indexed files are keyed by the absolute path of their worktree, as worktree IDs aren't
+    // stable across restarts. `symbols` is a JSON array of the file's symbols.
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE symbol_index_files(
+            worktree_path BLOB NOT NULL,
+            path TEXT NOT NULL,
+            mtime_seconds INTEGER NOT NULL,
+            mtime_nanos INTEGER NOT NULL,
+            symbols TEXT NOT NULL,
+            PRIMARY KEY(worktree_path, path)
+        ) STRICT;
+    )];
+}
+
+db::static_connection!(SymbolIndexDb, []);
+
+/// A file as it was last indexed.
+pub(crate) struct PersistedFile {
+    pub path: String,
+    pub mtime: MTime,
+    pub symbols: String,
+}
+
+impl SymbolIndexDb {
+    query! {
+        fn get_files(worktree_path: Arc<Path>) -> Result<Vec<(String, i64, i32, String)>> {
+            SELECT path, mtime_seconds, mtime_nanos, symbols
+            FROM symbol_index_files
+            WHERE worktree_path = ?
+        }
+    }
+
+    pub(crate) fn indexed_files(&self, worktree_path: Arc<Path>) -> Result<Vec<PersistedFile>> {
+        Ok(self
+            .get_files(worktree_path)?
+            .into_iter()
+            .map(|(path, seconds, nanos, symbols)| PersistedFile {
+                path,
+                mtime: MTime::from_seconds_and_nanos(seconds as u64, nanos as u32),
+                symbols,
+            })
+            .collect())
+    }
+
+    /// Replaces the given files' symbols, and forgets the files in `removed_paths`.
+    pub(crate) async fn save_files(
+        &self,
+        worktree_path: Arc<Path>,
+        files: Vec<PersistedFile>,
+        removed_paths: Vec<String>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            for path in removed_paths {
+                conn.exec_bound(sql!(
+                    DELETE FROM symbol_index_files WHERE worktree_path = ?1 AND path = ?2;
+                ))?((worktree_path.clone(), path))?;
+            }
+            for file in files {
+                let Some((seconds, nanos)) = file.mtime.to_seconds_and_nanos_for_persistence()
+                else {
+                    continue;
+                };
+                conn.exec_bound(sql!(
+                    INSERT OR REPLACE INTO symbol_index_files
+                        (worktree_path, path, mtime_seconds, mtime_nanos, symbols)
+                    VALUES (?1, ?2, ?3, ?4, ?5);
+                ))?((
+                    worktree_path.clone(),
+                    file.path,
+                    seconds as i64,
+                    nanos as i32,
+                    file.symbols,
+                ))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+}
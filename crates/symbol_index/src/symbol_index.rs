@@ -0,0 +1,854 @@
+//! An index of the symbols in a project's files, built from the outline queries of their
+//! languages, for searching workspace symbols without relying on language servers.
+//!
+//! The index is kept per local project, updated as files change on disk, and persisted in the
+//! database so that it doesn't need to be rebuilt from scratch on every launch.
+
+mod outline_symbols;
+mod persistence;
+
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use collections::HashMap;
+use fs::MTime;
+use gpui::{
+    App, AppContext as _, Context, Entity, EntityId, Global, Subscription, Task, WeakEntity,
+};
+use language::{
+    Buffer, CodeLabel, LanguageRegistry, LanguageServerId, LanguageServerName, PointUtf16, Rope,
+    Unclipped,
+};
+use project::{
+    Entry, Fs, Project, ProjectPath, Symbol, Worktree, WorktreeId, lsp_store::SymbolLocation,
+};
+use util::{ResultExt as _, rel_path::RelPath};
+use workspace::Workspace;
+
+use crate::{
+    outline_symbols::{OutlineSymbol, outline_symbols},
+    persistence::{PersistedFile, SymbolIndexDb},
+};
+
+/// The language server name that symbols from the index are reported under.
+pub const SYMBOL_INDEX_SERVER_NAME: LanguageServerName =
+    LanguageServerName::new_static("symbol-index");
+
+/// Files larger than this aren't indexed, as they're unlikely to be hand-written source code.
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// How many files are indexed between writes to the database.
+const BATCH_SIZE: usize = 64;
+
+/// The most symbols a search of the index returns. The best matches are kept when a search
+/// matches more.
+const MAX_SEARCH_RESULTS: usize = 10_000;
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(
+        |workspace: &mut Workspace, _, cx: &mut Context<Workspace>| {
+            let project = workspace.project().clone();
+            if !project.read(cx).is_local() || SymbolIndex::for_project(&project, cx).is_some() {
+                return;
+            }
+            let project_id = project.entity_id();
+            let symbol_index = cx.new(|cx| SymbolIndex::new(project.clone(), cx));
+            cx.default_global::<SymbolIndexes>()
+                .0
+                .insert(project_id, symbol_index);
+            project.update(cx, |_, cx| {
+                cx.on_release(move |_, cx| {
+                    cx.default_global::<SymbolIndexes>().0.remove(&project_id);
+                })
+                .detach();
+            });
+        },
+    )
+    .detach();
+}
+
+/// The symbol indexes of the open projects, keyed by the ID of their project.
+#[derive(Default)]
+struct SymbolIndexes(HashMap<EntityId, Entity<SymbolIndex>>);
+
+impl Global for SymbolIndexes {}
+
+/// Searches a project's symbols, merging the results of its language servers with those of its
+/// symbol index.
+///
+/// Symbols that both report are only returned once, as reported by the language server.
+pub fn search_symbols(
+    project: &Entity<Project>,
+    query: &str,
+    cx: &mut App,
+) -> Task<anyhow::Result<Vec<Symbol>>> {
+    let lsp_symbols = project.update(cx, |project, cx| project.symbols(query, cx));
+    let Some(symbol_index) = SymbolIndex::for_project(project, cx) else {
+        return lsp_symbols;
+    };
+    let index_symbols = symbol_index.read(cx).symbols(query, cx);
+    cx.background_spawn(async move {
+        // Fall back to the index alone if the language servers can't be queried.
+        let mut symbols = lsp_symbols.await.log_err().unwrap_or_default();
+        let mut lsp_symbols_by_path = HashMap::<&ProjectPath, Vec<&Symbol>>::default();
+        for symbol in &symbols {
+            if let SymbolLocation::InProject(project_path) = &symbol.path {
+                lsp_symbols_by_path
+                    .entry(project_path)
+                    .or_default()
+                    .push(symbol);
+            }
+        }
+        let index_symbols = index_symbols
+            .await
+            .into_iter()
+            .filter(|index_symbol| {
+                let SymbolLocation::InProject(project_path) = &index_symbol.path else {
+                    return true;
+                };
+                let rows = index_symbol.range.start.0.row..=index_symbol.range.end.0.row;
+                !lsp_symbols_by_path
+                    .get(project_path)
+                    .is_some_and(|lsp_symbols| {
+                        lsp_symbols.iter().any(|lsp_symbol| {
+                            lsp_symbol.name == index_symbol.name
+                                && rows.contains(&lsp_symbol.range.start.0.row)
+                        })
+                    })
+            })
+            .collect::<Vec<_>>();
+        symbols.extend(index_symbols);
+        Ok(symbols)
+    })
+}
+
+/// Opens the buffer containing a symbol returned by [`search_symbols`].
+pub fn open_buffer_for_symbol(
+    project: &Entity<Project>,
+    symbol: &Symbol,
+    cx: &mut App,
+) -> Task<anyhow::Result<Entity<Buffer>>> {
+    project.update(cx, |project, cx| {
+        // Symbols from the index don't have a language server to resolve them.
+        if symbol.language_server_name == SYMBOL_INDEX_SERVER_NAME
+            && let SymbolLocation::InProject(project_path) = &symbol.path
+        {
+            project.open_buffer(project_path.clone(), cx)
+        } else {
+            project.open_buffer_for_symbol(symbol, cx)
+        }
+    })
+}
+
+struct IndexedWorktree {
+    abs_path: Arc<Path>,
+    /// Whether the persisted index has been loaded and reconciled with the worktree's files.
+    /// Until then, changes to the worktree's files are ignored.
+    is_loaded: bool,
+    files: HashMap<Arc<RelPath>, IndexedFile>,
+    _load: Task<()>,
+}
+
+struct IndexedFile {
+    mtime: MTime,
+    symbols: Arc<[OutlineSymbol]>,
+}
+
+/// A file waiting to be indexed.
+struct IndexJob {
+    worktree_id: WorktreeId,
+    path: Arc<RelPath>,
+    abs_path: PathBuf,
+    mtime: MTime,
+}
+
+/// Indexes the symbols in the files of a local project's visible worktrees.
+pub struct SymbolIndex {
+    project: WeakEntity<Project>,
+    fs: Arc<dyn Fs>,
+    languages: Arc<LanguageRegistry>,
+    db: SymbolIndexDb,
+    /// The ID that symbols from the index are reported under, as if they came from a language
+    /// server.
+    server_id: LanguageServerId,
+    worktrees: HashMap<WorktreeId, IndexedWorktree>,
+    pending_files: VecDeque<(WorktreeId, Arc<RelPath>)>,
+    is_indexing: bool,
+    _index_files: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SymbolIndex {
+    pub fn new(project: Entity<Project>, cx: &mut Context<Self>) -> Self {
+        let (fs, languages, worktrees) = {
+            let project = project.read(cx);
+            (
+                project.fs().clone(),
+                project.languages().clone(),
+                project.worktrees(cx).collect::<Vec<_>>(),
+            )
+        };
+        let mut this = Self {
+            project: project.downgrade(),
+            fs,
+            server_id: languages.next_language_server_id(),
+            languages,
+            db: SymbolIndexDb::global(cx),
+            worktrees: HashMap::default(),
+            pending_files: VecDeque::new(),
+            is_indexing: false,
+            _index_files: Task::ready(()),
+            _subscriptions: vec![cx.subscribe(&project, Self::on_project_event)],
+        };
+        for worktree in worktrees {
+            this.add_worktree(&worktree, cx);
+        }
+        this
+    }
+
+    /// Returns the symbol index of the given project, if it's a local project in a workspace.
+    pub fn for_project(project: &Entity<Project>, cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<SymbolIndexes>()?
+            .0
+            .get(&project.entity_id())
+            .cloned()
+    }
+
+    /// Returns the indexed symbols whose names contain the characters of the query in order,
+    /// ignoring case, to be fuzzy matched by the caller like those of a language server.
+    ///
+    /// As with rust-analyzer, only the last segment of a `::`-separated query is matched. When
+    /// more than [`MAX_SEARCH_RESULTS`] symbols match, the best matches according to
+    /// [`match_rank`] are returned.
+    pub fn symbols(&self, query: &str, cx: &App) -> Task<Vec<Symbol>> {
+        let query = query
+            .rsplit_once("::")
+            .map_or(query, |(_, suffix)| suffix)
+            .to_lowercase();
+        let server_id = self.server_id;
+        let files = self
+            .worktrees
+            .iter()
+            .flat_map(|(worktree_id, worktree)| {
+                worktree
+                    .files
+                    .iter()
+                    .map(|(path, file)| (*worktree_id, path.clone(), file.symbols.clone()))
+            })
+            .collect::<Vec<_>>();
+        cx.background_spawn(async move {
+            let mut matches = Vec::new();
+            for (file_ix, (_, _, file_symbols)) in files.iter().enumerate() {
+                for (symbol_ix, symbol) in file_symbols.iter().enumerate() {
+                    if let Some(rank) = match_rank(&query, &symbol.name.to_lowercase()) {
+                        matches.push((rank, file_ix, symbol_ix));
+                    }
+                }
+            }
+            // Files are listed in no particular order, so ties are broken by path to keep the
+            // results stable.
+            matches.sort_unstable_by(|(a_rank, a_file_ix, a_ix), (b_rank, b_file_ix, b_ix)| {
+                let (a_worktree_id, a_path, _) = &files[*a_file_ix];
+                let (b_worktree_id, b_path, _) = &files[*b_file_ix];
+                a_rank
+                    .cmp(b_rank)
+                    .then_with(|| a_worktree_id.cmp(b_worktree_id))
+                    .then_with(|| a_path.cmp(b_path))
+                    .then_with(|| a_ix.cmp(b_ix))
+            });
+            matches.truncate(MAX_SEARCH_RESULTS);
+            matches
+                .into_iter()
+                .map(|(_, file_ix, symbol_ix)| {
+                    let (worktree_id, path, file_symbols) = &files[file_ix];
+                    project_symbol(&file_symbols[symbol_ix], *worktree_id, path, server_id)
+                })
+                .collect()
+        })
+    }
+
+    fn on_project_event(
+        &mut self,
+        project: Entity<Project>,
+        event: &project::Event,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            project::Event::WorktreeAdded(worktree_id) => {
+                if let Some(worktree) = project.read(cx).worktree_for_id(*worktree_id, cx) {
+                    self.add_worktree(&worktree, cx);
+                }
+            }
+            project::Event::WorktreeRemoved(worktree_id) => {
+                self.worktrees.remove(worktree_id);
+                self.pending_files
+                    .retain(|(pending_worktree_id, _)| pending_worktree_id != worktree_id);
+            }
+            project::Event::WorktreeUpdatedEntries(worktree_id, changes) => {
+                let Some(indexed_worktree) = self.worktrees.get_mut(worktree_id) else {
+                    return;
+                };
+                if !indexed_worktree.is_loaded {
+                    return;
+                }
+                let Some(worktree) = project.read(cx).worktree_for_id(*worktree_id, cx) else {
+                    return;
+                };
+                let worktree = worktree.read(cx);
+                let mut removed_paths = Vec::new();
+                for (path, _, _) in changes.iter() {
+                    match worktree.entry_for_path(path) {
+                        Some(entry) if is_indexable(entry, &self.languages) => {
+                            self.pending_files.push_back((*worktree_id, path.clone()));
+                        }
+                        _ => {
+                            if indexed_worktree.files.remove(path).is_some() {
+                                removed_paths.push(path.as_unix_str().to_string());
+                            }
+                        }
+                    }
+                }
+                if !removed_paths.is_empty() {
+                    let db = self.db.clone();
+                    let abs_path = indexed_worktree.abs_path.clone();
+                    cx.background_spawn(async move {
+                        db.save_files(abs_path, Vec::new(), removed_paths).await
+                    })
+                    .detach_and_log_err(cx);
+                }
+                self.index_pending_files(cx);
+            }
+            _ => {}
+        }
+    }
+
+    fn add_worktree(&mut self, worktree: &Entity<Worktree>, cx: &mut Context<Self>) {
+        let worktree = worktree.read(cx);
+        let Some(local_worktree) = worktree.as_local() else {
+            return;
+        };
+        if !worktree.is_visible() {
+            return;
+        }
+        let worktree_id = worktree.id();
+        let abs_path = worktree.abs_path();
+        let scan_complete = local_worktree.scan_complete();
+        let db = self.db.clone();
+        let load = cx.spawn({
+            let abs_path = abs_path.clone();
+            async move |this, cx| {
+                let persisted_files = cx
+                    .background_spawn(async move { db.indexed_files(abs_path) })
+                    .await
+                    .log_err()
+                    .unwrap_or_default();
+                scan_complete.await;
+                this.update(cx, |this, cx| {
+                    this.reconcile_worktree(worktree_id, persisted_files, cx);
+                })
+                .ok();
+            }
+        });
+        self.worktrees.insert(
+            worktree_id,
+            IndexedWorktree {
+                abs_path,
+                is_loaded: false,
+                files: HashMap::default(),
+                _load: load,
+            },
+        );
+    }
+
+    /// Keeps the persisted files that haven't changed since they were indexed, and queues the
+    /// worktree's other files for indexing.
+    fn reconcile_worktree(
+        &mut self,
+        worktree_id: WorktreeId,
+        persisted_files: Vec<PersistedFile>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let Some(worktree) = project.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let Some(indexed_worktree) = self.worktrees.get_mut(&worktree_id) else {
+            return;
+        };
+        let worktree = worktree.read(cx);
+
+        let mut removed_paths = Vec::new();
+        for persisted_file in persisted_files {
+            let entry = RelPath::unix(&persisted_file.path)
+                .ok()
+                .and_then(|path| worktree.entry_for_path(path))
+                .filter(|entry| {
+                    entry.mtime == Some(persisted_file.mtime)
+                        && is_indexable(entry, &self.languages)
+                });
+            let symbols = entry.and_then(|_| {
+                serde_json::from_str::<Vec<OutlineSymbol>>(&persisted_file.symbols).log_err()
+            });
+            match entry.zip(symbols) {
+                Some((entry, symbols)) => {
+                    indexed_worktree.files.insert(
+                        entry.path.clone(),
+                        IndexedFile {
+                            mtime: persisted_file.mtime,
+                            symbols: symbols.into(),
+                        },
+                    );
+                }
+                None => removed_paths.push(persisted_file.path),
+            }
+        }
+        for entry in worktree.files(false, 0) {
+            if !indexed_worktree.files.contains_key(&entry.path)
+                && is_indexable(entry, &self.languages)
+            {
+                self.pending_files
+                    .push_back((worktree_id, entry.path.clone()));
+            }
+        }
+        indexed_worktree.is_loaded = true;
+
+        if !removed_paths.is_empty() {
+            let db = self.db.clone();
+            let abs_path = indexed_worktree.abs_path.clone();
+            cx.background_spawn(
+                async move { db.save_files(abs_path, Vec::new(), removed_paths).await },
+            )
+            .detach_and_log_err(cx);
+        }
+        self.index_pending_files(cx);
+    }
+
+    fn index_pending_files(&mut self, cx: &mut Context<Self>) {
+        if self.is_indexing || self.pending_files.is_empty() {
+            return;
+        }
+        self.is_indexing = true;
+        let fs = self.fs.clone();
+        let languages = self.languages.clone();
+        let db = self.db.clone();
+        self._index_files = cx.spawn(async move |this, cx| {
+            while let Ok(Some(jobs)) = this.update(cx, |this, cx| this.next_jobs(cx)) {
+                let mut indexed_files = Vec::new();
+                for job in jobs {
+                    let Some(text) = fs.load(&job.abs_path).await.log_err() else {
+                        continue;
+                    };
+                    let Some(language) = languages
+                        .load_language_for_file_path(&job.abs_path)
+                        .await
+                        .ok()
+                    else {
+                        continue;
+                    };
+                    let symbols = if language
+                        .grammar()
+                        .is_some_and(|grammar| grammar.outline_config.is_some())
+                    {
+                        let snapshot = cx.update(|cx| {
+                            Buffer::build_snapshot(
+                                Rope::from(text.as_str()),
+                                Some(language),
+                                Some(languages.clone()),
+                                cx,
+                            )
+                        });
+                        cx.background_spawn(async move { outline_symbols(&snapshot.await) })
+                            .await
+                    } else {
+                        Vec::new()
+                    };
+                    indexed_files.push((job, symbols));
+                }
+
+                let Ok(saved_files) =
+                    this.update(cx, |this, _| this.insert_indexed_files(indexed_files))
+                else {
+                    break;
+                };
+                for (abs_path, files) in saved_files {
+                    db.save_files(abs_path, files, Vec::new()).await.log_err();
+                }
+            }
+        });
+    }
+
+    /// Takes the next batch of pending files, skipping those that have been removed or haven't
+    /// changed since they were last indexed.
+    fn next_jobs(&mut self, cx: &mut Context<Self>) -> Option<Vec<IndexJob>> {
+        let project = self.project.upgrade()?;
+        let project = project.read(cx);
+        let mut jobs = Vec::new();
+        while jobs.len() < BATCH_SIZE
+            && let Some((worktree_id, path)) = self.pending_files.pop_front()
+        {
+            let Some(worktree) = project.worktree_for_id(worktree_id, cx) else {
+                continue;
+            };
+            let worktree = worktree.read(cx);
+            let Some(mtime) = worktree.entry_for_path(&path).and_then(|entry| entry.mtime) else {
+                continue;
+            };
+            let is_up_to_date = self
+                .worktrees
+                .get(&worktree_id)
+                .and_then(|indexed_worktree| indexed_worktree.files.get(&path))
+                .is_some_and(|file| file.mtime == mtime);
+            if is_up_to_date {
+                continue;
+            }
+            jobs.push(IndexJob {
+                worktree_id,
+                abs_path: worktree.absolutize(&path),
+                path,
+                mtime,
+            });
+        }
+        if jobs.is_empty() {
+            self.is_indexing = false;
+            return None;
+        }
+        Some(jobs)
+    }
+
+    /// Adds the symbols of newly indexed files to the index, returning them grouped by the
+    /// absolute path of their worktree to be persisted.
+    fn insert_indexed_files(
+        &mut self,
+        indexed_files: Vec<(IndexJob, Vec<OutlineSymbol>)>,
+    ) -> HashMap<Arc<Path>, Vec<PersistedFile>> {
+        let mut files_to_save = HashMap::<_, Vec<_>>::default();
+        for (job, symbols) in indexed_files {
+            let Some(indexed_worktree) = self.worktrees.get_mut(&job.worktree_id) else {
+                continue;
+            };
+            let Some(serialized_symbols) = serde_json::to_string(&symbols).log_err() else {
+                continue;
+            };
+            files_to_save
+                .entry(indexed_worktree.abs_path.clone())
+                .or_default()
+                .push(PersistedFile {
+                    path: job.path.as_unix_str().to_string(),
+                    mtime: job.mtime,
+                    symbols: serialized_symbols,
+                });
+            indexed_worktree.files.insert(
+                job.path,
+                IndexedFile {
+                    mtime: job.mtime,
+                    symbols: symbols.into(),
+                },
+            );
+        }
+        files_to_save
+    }
+}
+
+/// Returns whether the given entry should be indexed: a file of a known language that is
+/// neither ignored nor outside of its worktree.
+fn is_indexable(entry: &Entry, languages: &Arc<LanguageRegistry>) -> bool {
+    entry.is_file()
+        && !entry.is_ignored
+        && !entry.is_external
+        && !entry.is_private
+        && entry.mtime.is_some()
+        && entry.size <= MAX_FILE_SIZE
+        && languages
+            .language_for_file_path(entry.path.as_std_path())
+            .is_some()
+}
+
+fn project_symbol(
+    symbol: &OutlineSymbol,
+    worktree_id: WorktreeId,
+    path: &Arc<RelPath>,
+    server_id: LanguageServerId,
+) -> Symbol {
+    let label = if symbol.text.get(symbol.name_range.clone()).is_some() {
+        CodeLabel::new(symbol.text.clone(), symbol.name_range.clone(), Vec::new())
+    } else {
+        CodeLabel::plain(symbol.name.clone(), None)
+    };
+    let (start_row, start_column) = symbol.start;
+    let (end_row, end_column) = symbol.end;
+    Symbol {
+        language_server_name: SYMBOL_INDEX_SERVER_NAME,
+        source_worktree_id: worktree_id,
+        source_language_server_id: server_id,
+        path: SymbolLocation::InProject(ProjectPath {
+            worktree_id,
+            path: path.clone(),
+        }),
+        label,
+        name: symbol.name.clone(),
+        kind: symbol.kind,
+        range: Unclipped(PointUtf16::new(start_row, start_column))
+            ..Unclipped(PointUtf16::new(end_row, end_column)),
+        container_name: symbol.container_name.clone(),
+    }
+}
+
+/// Ranks how well a lowercase symbol name matches a lowercase query, lower being better, or
+/// returns `None` if the characters of the query don't appear in the name in order.
+///
+/// Exact matches come first, then prefixes, then other substrings, then other subsequences,
+/// with shorter names first within each.
+fn match_rank(query: &str, name: &str) -> Option<(u8, usize)> {
+    let kind = if name == query {
+        0
+    } else if name.starts_with(query) {
+        1
+    } else if name.contains(query) {
+        2
+    } else if is_subsequence(query, name) {
+        3
+    } else {
+        return None;
+    };
+    Some((kind, name.len()))
+}
+
+/// Returns whether the characters of `query` appear in `text` in the same order.
+fn is_subsequence(query: &str, text: &str) -> bool {
+    let mut text = text.chars();
+    query
+        .chars()
+        .all(|query_char| text.any(|text_char| text_char == query_char))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt as _;
+    use gpui::TestAppContext;
+    use language::{FakeLspAdapter, rust_lang};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            cx.set_global(db::AppDatabase::test_new());
+            release_channel::init(semver::Version::new(0, 0, 0), cx);
+        });
+    }
+
+    async fn test_project(fs: &Arc<FakeFs>, cx: &mut TestAppContext) -> Entity<Project> {
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+        project
+    }
+
+    async fn symbol_names(index: &Entity<SymbolIndex>, cx: &mut TestAppContext) -> Vec<String> {
+        let symbols = index.read_with(cx, |index, cx| index.symbols("", cx));
+        let mut names = symbols
+            .await
+            .into_iter()
+            .map(|symbol| symbol.name)
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[gpui::test]
+    async fn test_indexing_and_updating_files(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "main.rs": "fn main() {}\n",
+                "lib.rs": "pub struct Point {}\n",
+                "notes.txt": "fn not_indexed() {}\n",
+            }),
+        )
+        .await;
+        let project = test_project(&fs, cx).await;
+        let index = cx.new(|cx| SymbolIndex::new(project.clone(), cx));
+        cx.run_until_parked();
+        assert_eq!(symbol_names(&index, cx).await, ["Point", "main"]);
+
+        fs.insert_file(path!("/dir/lib.rs"), b"pub struct Vector {}\n".to_vec())
+            .await;
+        cx.run_until_parked();
+        assert_eq!(symbol_names(&index, cx).await, ["Vector", "main"]);
+
+        fs.remove_file(path!("/dir/main.rs").as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(symbol_names(&index, cx).await, ["Vector"]);
+    }
+
+    #[gpui::test]
+    async fn test_reloading_from_database(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "main.rs": "fn main() {}\n",
+                "lib.rs": "pub struct Point {}\n",
+            }),
+        )
+        .await;
+        let project = test_project(&fs, cx).await;
+        let index = cx.new(|cx| SymbolIndex::new(project.clone(), cx));
+        cx.run_until_parked();
+        assert_eq!(symbol_names(&index, cx).await, ["Point", "main"]);
+        drop(index);
+        drop(project);
+        cx.run_until_parked();
+
+        // Rewrite the persisted symbols of main.rs, keeping its mtime, so that they can be told
+        // apart from the file's actual symbols.
+        let db = cx.update(|cx| SymbolIndexDb::global(cx));
+        let worktree_path: Arc<Path> = Path::new(path!("/dir")).into();
+        let persisted_main = db
+            .indexed_files(worktree_path.clone())
+            .unwrap()
+            .into_iter()
+            .find(|file| file.path == "main.rs")
+            .unwrap();
+        let mut symbols =
+            serde_json::from_str::<Vec<OutlineSymbol>>(&persisted_main.symbols).unwrap();
+        symbols[0].name = "persisted_main".into();
+        db.save_files(
+            worktree_path,
+            vec![PersistedFile {
+                symbols: serde_json::to_string(&symbols).unwrap(),
+                ..persisted_main
+            }],
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+        fs.insert_file(path!("/dir/lib.rs"), b"pub struct Vector {}\n".to_vec())
+            .await;
+
+        // Files whose mtime hasn't changed are loaded from the database, while the others are
+        // indexed again.
+        let project = test_project(&fs, cx).await;
+        let index = cx.new(|cx| SymbolIndex::new(project.clone(), cx));
+        cx.run_until_parked();
+        assert_eq!(symbol_names(&index, cx).await, ["Vector", "persisted_main"]);
+    }
+
+    #[gpui::test]
+    async fn test_searching_with_language_servers(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "main.rs": "fn main() {}\nfn helper() {}\n",
+            }),
+        )
+        .await;
+        let project = test_project(&fs, cx).await;
+        let mut fake_servers = project.read_with(cx, |project, _| {
+            project.languages().register_fake_lsp(
+                "Rust",
+                FakeLspAdapter {
+                    capabilities: lsp::ServerCapabilities {
+                        workspace_symbol_provider: Some(lsp::OneOf::Left(true)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+        });
+        let index = cx.new(|cx| SymbolIndex::new(project.clone(), cx));
+        cx.update(|cx| {
+            cx.default_global::<SymbolIndexes>()
+                .0
+                .insert(project.entity_id(), index.clone());
+        });
+        let _buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer_with_lsp(path!("/dir/main.rs"), cx)
+            })
+            .await
+            .unwrap();
+        let fake_server = fake_servers.next().await.unwrap();
+        fake_server.set_request_handler::<lsp::WorkspaceSymbolRequest, _, _>(|_, _| async {
+            #[allow(deprecated)]
+            let main = lsp::SymbolInformation {
+                name: "main".into(),
+                kind: lsp::SymbolKind::FUNCTION,
+                tags: None,
+                deprecated: None,
+                container_name: None,
+                location: lsp::Location::new(
+                    lsp::Uri::from_file_path(path!("/dir/main.rs")).unwrap(),
+                    lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 7)),
+                ),
+            };
+            Ok(Some(lsp::WorkspaceSymbolResponse::Flat(vec![main])))
+        });
+        cx.run_until_parked();
+
+        // Symbols reported by both the language server and the index are only returned once,
+        // as reported by the language server.
+        let mut symbols = cx
+            .update(|cx| search_symbols(&project, "", cx))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|symbol| (symbol.name, symbol.language_server_name))
+            .collect::<Vec<_>>();
+        symbols.sort();
+        assert_eq!(
+            symbols,
+            [
+                ("helper".to_string(), SYMBOL_INDEX_SERVER_NAME),
+                (
+                    "main".to_string(),
+                    LanguageServerName::new_static("the-fake-language-server")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_rank() {
+        let mut names = [
+            "symbol_index",
+            "index",
+            "indexes",
+            "reindex",
+            "i_n_d_e_x",
+            "other",
+        ]
+        .into_iter()
+        .filter_map(|name| Some((match_rank("index", name)?, name)))
+        .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names.into_iter().map(|(_, name)| name).collect::<Vec<_>>(),
+            ["index", "indexes", "reindex", "symbol_index", "i_n_d_e_x"]
+        );
+    }
+
+    #[test]
+    fn test_is_subsequence() {
+        assert!(is_subsequence("", "anything"));
+        assert!(is_subsequence("ssi", "symbol_index"));
+        assert!(is_subsequence("symbolindex", "symbol_index"));
+        assert!(!is_subsequence("xs", "symbol_index"));
+        assert!(!is_subsequence("indexes", "index"));
+    }
+}
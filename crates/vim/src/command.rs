@@ -1,4 +1,4 @@
-use anyhow::{Result, anyhow};
+use anyhow::{Context as _, Result, anyhow};
 use collections::{HashMap, HashSet};
 use command_palette_hooks::{CommandInterceptItem, CommandInterceptResult};
 use editor::{
@@ -6,9 +6,10 @@ use editor::{
     actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
     display_map::ToDisplayPoint,
 };
-use futures::AsyncWriteExt as _;
+use futures::{AsyncWriteExt as _, FutureExt as _};
 use gpui::{
-    Action, App, AppContext as _, Context, Global, Keystroke, Task, WeakEntity, Window, actions,
+    Action, App, AppContext as _, ClipboardItem, Context, Global, Keystroke, Task, WeakEntity,
+    Window, actions,
 };
 use itertools::Itertools;
 use language::Point;
@@ -41,6 +42,7 @@ use zed_actions::{OpenDocs, RevealTarget};
 
 use crate::{
     ToggleMarksView, ToggleRegistersView, Vim, VimSettings,
+    ex_script::{ExScriptHost, is_script_command},
     motion::{EndOfDocument, Motion, MotionKind, StartOfDocument},
     normal::{
         JoinLines,
@@ -260,6 +262,20 @@ struct VimNorm {
     pub override_rows: Option<Vec<u32>>,
 }
 
+/// Runs ex script commands such as `:let`, `:if` and user-defined commands.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+struct ExScript {
+    pub source: String,
+}
+
+/// Runs the ex script in the specified file.
+#[derive(Clone, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+struct VimSource {
+    pub filename: String,
+}
+
 #[derive(Debug)]
 struct WrappedAction(Box<dyn Action>);
 
@@ -645,6 +661,39 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
         });
     });
 
+    Vim::action(editor, cx, |vim, action: &ExScript, window, cx| {
+        vim.run_ex_script(&action.source, window, cx);
+    });
+
+    Vim::action(editor, cx, |vim, action: &VimSource, window, cx| {
+        let mut path = match action.filename.strip_prefix("~/") {
+            Some(path) => util::paths::home_dir().join(path),
+            None => PathBuf::from(&action.filename),
+        };
+        let Some(workspace) = vim.workspace(window, cx) else {
+            return;
+        };
+        let workspace = workspace.read(cx);
+        if path.is_relative()
+            && let Some(worktree) = workspace.visible_worktrees(cx).next()
+        {
+            path = worktree.read(cx).abs_path().join(path);
+        }
+        let fs = workspace.project().read(cx).fs().clone();
+        cx.spawn_in(window, async move |vim, cx| {
+            let source = fs
+                .load(&path)
+                .await
+                .with_context(|| format!("E484: Can't open file {}", path.display()));
+            vim.update_in(cx, |vim, window, cx| match source {
+                Ok(source) => vim.run_ex_script(&source, window, cx),
+                Err(error) => vim.show_ex_script_error(error, window, cx),
+            })
+            .ok();
+        })
+        .detach();
+    });
+
     Vim::action(editor, cx, |vim, action: &VimEdit, window, cx| {
         vim.update_editor(cx, |vim, editor, cx| {
             let Some(workspace) = vim.workspace(window, cx) else {
@@ -1479,6 +1528,8 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
             action.range.replace(range.clone());
             Some(Box::new(action))
         }),
+        VimCommand::new(("so", "urce"), ArgumentRequired)
+            .filename(|_, filename| Some(VimSource { filename }.boxed_clone())),
         VimCommand::new(("e", "dit"), editor::actions::ReloadFile)
             .bang(editor::actions::ReloadFile)
             .filename(|_, filename| Some(VimEdit { filename }.boxed_clone())),
@@ -1839,6 +1890,35 @@ pub fn command_interceptor(
     let has_trailing_space = query.ends_with(" ");
     let mut query = query.as_str().trim_start();
 
+    let script_state = &Vim::globals(cx).ex_script;
+    let script_commands = if is_script_command(query, script_state) {
+        vec![query.to_string()]
+    } else if query.starts_with(|c: char| c.is_ascii_uppercase()) {
+        script_state
+            .commands_with_prefix(query)
+            .map(|(name, _)| name.to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    if !script_commands.is_empty() {
+        let results = script_commands
+            .into_iter()
+            .map(|source| {
+                let string = format!(":{source}");
+                CommandInterceptItem {
+                    positions: generate_positions(&string, query),
+                    action: ExScript { source }.boxed_clone(),
+                    string,
+                }
+            })
+            .collect();
+        return Task::ready(CommandInterceptResult {
+            results,
+            exclusive: false,
+        });
+    }
+
     let on_matching_lines = (query.starts_with('g') || query.starts_with('v'))
         .then(|| {
             let (pattern, range, search, invert) = OnMatchingLines::parse(query, &range)?;
@@ -1971,7 +2051,9 @@ pub fn command_interceptor(
         return Task::ready(CommandInterceptResult::default());
     };
 
-    if let Some((cmd_idx, parsed_query, display_string, no_args_positions)) = filenames {
+    if let Some((cmd_idx, parsed_query, display_string, no_args_positions)) = filenames
+        && workspace.upgrade().is_some()
+    {
         let filenames = VimCommand::generate_filename_completions(&parsed_query, workspace, cx);
         cx.spawn(async move |cx| {
             let filenames = filenames.await;
@@ -2627,6 +2709,91 @@ impl ShellExec {
     }
 }
 
+impl Vim {
+    pub(crate) fn run_ex_script(
+        &mut self,
+        source: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // The state is taken out of the globals while the script runs so that
+        // the host can access the globals (for example, to read registers).
+        let mut state = std::mem::take(&mut Vim::globals(cx).ex_script);
+        let result = state.run(source, &mut VimScriptHost { window, cx });
+        Vim::globals(cx).ex_script = state;
+        if let Err(error) = result {
+            self.show_ex_script_error(error, window, cx);
+        }
+    }
+
+    fn show_ex_script_error(
+        &mut self,
+        error: anyhow::Error,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        log::error!("failed to run ex script: {error:?}");
+        let Some(workspace) = self.workspace(window, cx) else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| workspace.show_error(&error, cx));
+    }
+}
+
+struct VimScriptHost<'a> {
+    window: &'a mut Window,
+    cx: &'a mut App,
+}
+
+impl ExScriptHost for VimScriptHost<'_> {
+    fn read_register(&mut self, register: char) -> Option<String> {
+        Vim::update_globals(self.cx, |globals, cx| {
+            globals.read_register(Some(register), None, cx)
+        })
+        .map(|register| register.text.to_string())
+    }
+
+    fn write_register(&mut self, register: char, text: String) {
+        match register {
+            '+' | '*' => self.cx.write_to_clipboard(ClipboardItem::new_string(text)),
+            _ => Vim::update_globals(self.cx, |globals, _| {
+                let lower = register.to_ascii_lowercase();
+                if register.is_ascii_uppercase()
+                    && let Some(existing) = globals.registers.get(&lower)
+                {
+                    let text = existing.text.to_string() + &text;
+                    globals.registers.insert(lower, text.into());
+                } else {
+                    globals.registers.insert(lower, text.into());
+                }
+            }),
+        }
+    }
+
+    fn run_ex_command(&mut self, command: &str) -> Result<()> {
+        let (name, data) = command
+            .split_once(char::is_whitespace)
+            .map_or((command, None), |(name, data)| (name, Some(data)));
+        let action = if name.contains("::") {
+            let data = data.map(serde_json::from_str).transpose()?;
+            self.cx.build_action(name, data)?
+        } else {
+            parse_ex_command(command, self.cx)
+                .ok_or_else(|| anyhow!("E492: Not an editor command: {command}"))?
+        };
+        self.window.dispatch_action(action, self.cx);
+        Ok(())
+    }
+}
+
+/// Returns the action that the command palette would run for the given ex command.
+fn parse_ex_command(command: &str, cx: &mut App) -> Option<Box<dyn Action>> {
+    // Without a workspace, the interceptor doesn't look up filename completions
+    // and so its task is always ready.
+    let result = command_interceptor(command, WeakEntity::new_invalid(), cx).now_or_never()?;
+    result.results.into_iter().next().map(|item| item.action)
+}
+
 #[cfg(test)]
 mod test {
     use std::path::{Path, PathBuf};
@@ -2761,6 +2928,38 @@ mod test {
         assert_eq!(fs.load(path).await.unwrap().replace("\r\n", "\n"), "@@\n");
     }
 
+    #[gpui::test]
+    async fn test_ex_script(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let fs = cx.workspace(|workspace, _, cx| workspace.project().read(cx).fs().clone());
+        fs.as_fake()
+            .insert_file(
+                path!("/root/dir/init.vim"),
+                indoc! {"
+                    \" Delete one line more than the loop runs.
+                    let g:count = 1
+                    for i in range(2)
+                      let g:count += 1
+                    endfor
+                    command! -nargs=1 Del execute 'normal' <args> . 'dd'
+                    Del g:count
+                "}
+                .into(),
+            )
+            .await;
+
+        cx.set_state("ˇone\ntwo\nthree\nfour\nfive", Mode::Normal);
+        cx.simulate_keystrokes(": s o u r c e space i n i t . v i m");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.assert_state("ˇfour\nfive", Mode::Normal);
+
+        cx.simulate_keystrokes(": D e l space 1");
+        cx.simulate_keystrokes("enter");
+        cx.run_until_parked();
+        cx.assert_state("ˇfive", Mode::Normal);
+    }
+
     #[gpui::test]
     async fn test_command_read(cx: &mut TestAppContext) {
         let mut cx = VimTestContext::new(cx, true).await;
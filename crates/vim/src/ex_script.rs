@@ -0,0 +1,1316 @@
+//! A small interpreter for the subset of Vim script that is commonly found in
+//! `init.vim`-style configuration files.
+//!
+//! Scripts are made up of ex commands. The interpreter evaluates the scripting
+//! commands itself (`:let`, `:if`, `:for`, `:execute`, `:command`, ...) and hands
+//! every other command to an [`ExScriptHost`], which is responsible for turning it
+//! into an editor action.
+use anyhow::{Context as _, Result, anyhow, bail};
+use collections::{BTreeMap, HashMap};
+use regex::RegexBuilder;
+use std::fmt;
+
+/// How deeply `:execute` and user commands may nest before evaluation is aborted.
+const MAX_DEPTH: usize = 100;
+/// The largest list `range()` may produce.
+const MAX_RANGE_LEN: usize = 1_000_000;
+
+/// The capabilities the interpreter needs from the editor.
+pub(crate) trait ExScriptHost {
+    fn read_register(&mut self, register: char) -> Option<String>;
+    fn write_register(&mut self, register: char, text: String);
+    /// Runs an ex command (such as `:w` or `:normal dd`) or a Zed action (such as
+    /// `editor::Format`) that the interpreter does not handle itself.
+    fn run_ex_command(&mut self, command: &str) -> Result<()>;
+}
+
+/// The variables and user commands defined by the scripts that have run so far.
+#[derive(Default)]
+pub(crate) struct ExScriptState {
+    variables: HashMap<String, Value>,
+    commands: BTreeMap<String, String>,
+}
+
+impl ExScriptState {
+    pub(crate) fn run(&mut self, source: &str, host: &mut dyn ExScriptHost) -> Result<()> {
+        Interpreter {
+            state: self,
+            host,
+            depth: 0,
+        }
+        .run_source(source)
+    }
+
+    pub(crate) fn has_command(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
+    /// Returns the user commands whose names start with the given prefix.
+    pub(crate) fn commands_with_prefix<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        self.commands
+            .range(prefix.to_string()..)
+            .take_while(move |(name, _)| name.starts_with(prefix))
+            .map(|(name, replacement)| (name.as_str(), replacement.as_str()))
+    }
+}
+
+/// Returns whether the given command line should be run by the interpreter
+/// rather than being parsed as a regular ex command.
+pub(crate) fn is_script_command(command: &str, state: &ExScriptState) -> bool {
+    let (name, _, _) = split_command(command);
+    Keyword::parse(name).is_some() || user_command_name(name, state).is_some()
+}
+
+fn user_command_name<'a>(name: &'a str, state: &ExScriptState) -> Option<&'a str> {
+    (name.starts_with(|c: char| c.is_ascii_uppercase()) && state.has_command(name)).then_some(name)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Number(i64),
+    String(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    fn to_number(&self) -> Result<i64> {
+        match self {
+            Value::Number(number) => Ok(*number),
+            Value::String(string) => Ok(parse_leading_number(string)),
+            Value::List(_) => bail!("E745: Using a List as a Number"),
+        }
+    }
+
+    fn to_text(&self) -> Result<String> {
+        match self {
+            Value::Number(number) => Ok(number.to_string()),
+            Value::String(string) => Ok(string.clone()),
+            Value::List(_) => bail!("E730: Using a List as a String"),
+        }
+    }
+
+    fn is_truthy(&self) -> Result<bool> {
+        Ok(self.to_number()? != 0)
+    }
+
+    fn from_bool(value: bool) -> Self {
+        Value::Number(value as i64)
+    }
+}
+
+/// Formats values the way Vim's `string()` does.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{number}"),
+            Value::String(string) => write!(f, "'{}'", string.replace('\'', "''")),
+            Value::List(items) => {
+                write!(f, "[")?;
+                for (ix, item) in items.iter().enumerate() {
+                    if ix > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+fn parse_leading_number(text: &str) -> i64 {
+    let text = text.trim_start();
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let end = digits
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(digits.len());
+    let number = digits[..end].parse::<i64>().unwrap_or(0);
+    if negative { -number } else { number }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Keyword {
+    Let,
+    Unlet,
+    If,
+    ElseIf,
+    Else,
+    EndIf,
+    For,
+    EndFor,
+    Break,
+    Continue,
+    Execute,
+    Command,
+    DelCommand,
+}
+
+impl Keyword {
+    /// Matches a command name against the keyword's full name and the shortest
+    /// abbreviation Vim accepts for it.
+    fn parse(name: &str) -> Option<Self> {
+        const KEYWORDS: &[(&str, &str, Keyword)] = &[
+            ("let", "", Keyword::Let),
+            ("unl", "et", Keyword::Unlet),
+            ("if", "", Keyword::If),
+            ("elsei", "f", Keyword::ElseIf),
+            ("el", "se", Keyword::Else),
+            ("en", "dif", Keyword::EndIf),
+            ("for", "", Keyword::For),
+            ("endfo", "r", Keyword::EndFor),
+            ("brea", "k", Keyword::Break),
+            ("con", "tinue", Keyword::Continue),
+            ("exe", "cute", Keyword::Execute),
+            ("com", "mand", Keyword::Command),
+            ("delc", "ommand", Keyword::DelCommand),
+        ];
+        KEYWORDS
+            .iter()
+            .find(|(prefix, suffix, _)| is_abbreviation(name, prefix, suffix))
+            .map(|(_, _, keyword)| *keyword)
+    }
+}
+
+/// Whether `name` is `prefix` followed by the start of `suffix`, such as `exe`
+/// or `execu` for `exe[cute]`.
+fn is_abbreviation(name: &str, prefix: &str, suffix: &str) -> bool {
+    name.strip_prefix(prefix)
+        .is_some_and(|rest| suffix.starts_with(rest))
+}
+
+/// Splits a command line into its name, whether it has a bang, and its arguments.
+fn split_command(line: &str) -> (&str, bool, &str) {
+    let line = line.trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+    let name_len = line
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(line.len());
+    let (name, rest) = line.split_at(name_len);
+    match rest.strip_prefix('!') {
+        Some(rest) => (name, true, rest.trim()),
+        None => (name, false, rest.trim()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LetTarget {
+    Variable(String),
+    Register(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Statement {
+    Let {
+        target: LetTarget,
+        operator: Option<char>,
+        expression: String,
+    },
+    Unlet {
+        names: Vec<String>,
+        force: bool,
+    },
+    If {
+        branches: Vec<(String, Vec<Statement>)>,
+        otherwise: Vec<Statement>,
+    },
+    For {
+        variable: String,
+        iterable: String,
+        body: Vec<Statement>,
+    },
+    Break,
+    Continue,
+    Execute(String),
+    Command {
+        name: String,
+        replacement: String,
+        force: bool,
+    },
+    DelCommand(String),
+    Ex(String),
+}
+
+/// Joins continuation lines, drops comments and blank lines, and splits lines
+/// at `|` where the command allows it.
+fn script_lines(source: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in source.lines() {
+        let line = line.trim();
+        if let Some(continuation) = line.strip_prefix('\\')
+            && let Some(previous) = lines.last_mut()
+        {
+            previous.push_str(continuation);
+            continue;
+        }
+        if line.is_empty() || line.starts_with('"') {
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+
+    let mut commands = Vec::new();
+    for line in lines {
+        let mut rest = line.as_str();
+        loop {
+            match find_bar(rest) {
+                Some(ix) => {
+                    commands.push(rest[..ix].trim().to_string());
+                    rest = rest[ix + 1..].trim_start();
+                }
+                None => {
+                    commands.push(rest.trim().to_string());
+                    break;
+                }
+            }
+        }
+    }
+    commands.retain(|command| !command.trim_start_matches(':').is_empty());
+    commands
+}
+
+/// Finds the `|` that separates the first command on the line from the next.
+///
+/// As in Vim, `:normal`, `:global`, `:command` and shell commands treat the rest
+/// of the line as their argument. Inside script commands, a `|` that is part of
+/// a string literal or of `||` does not separate commands.
+fn find_bar(line: &str) -> Option<usize> {
+    let (name, _, args) = split_command(line);
+    let keyword = Keyword::parse(name);
+    let takes_bar = match keyword {
+        Some(keyword) => keyword == Keyword::Command,
+        None => {
+            args.starts_with('!')
+                || (name.is_empty() && line.trim_start_matches(':').starts_with('!'))
+                || is_abbreviation(name, "norm", "al")
+                || is_abbreviation(name, "g", "lobal")
+                || is_abbreviation(name, "v", "global")
+        }
+    };
+    if takes_bar {
+        return None;
+    }
+
+    let mut chars = line.char_indices().peekable();
+    let mut quote = None;
+    while let Some((ix, c)) = chars.next() {
+        match (quote, c) {
+            (Some('"'), '\\') | (None, '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') if keyword.is_some() => quote = Some(c),
+            (None, '|') => {
+                if chars.peek().is_some_and(|(_, next)| *next == '|') {
+                    chars.next();
+                } else {
+                    return Some(ix);
+                }
+            }
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+fn parse_script(source: &str) -> Result<Vec<Statement>> {
+    let lines = script_lines(source);
+    let mut ix = 0;
+    let (statements, terminator) = parse_block(&lines, &mut ix, &[])?;
+    debug_assert!(terminator.is_none());
+    Ok(statements)
+}
+
+/// Parses statements until one of the terminators is reached, returning the
+/// terminator along with its arguments.
+fn parse_block(
+    lines: &[String],
+    ix: &mut usize,
+    terminators: &[Keyword],
+) -> Result<(Vec<Statement>, Option<(Keyword, String)>)> {
+    let mut statements = Vec::new();
+    while let Some(line) = lines.get(*ix) {
+        *ix += 1;
+        let (name, bang, args) = split_command(line);
+        let Some(keyword) = Keyword::parse(name) else {
+            statements.push(Statement::Ex(line.clone()));
+            continue;
+        };
+        if terminators.contains(&keyword) {
+            return Ok((statements, Some((keyword, args.to_string()))));
+        }
+        let statement = match keyword {
+            Keyword::Let => parse_let(args)?,
+            Keyword::Unlet => Statement::Unlet {
+                names: args.split_whitespace().map(variable_name).collect(),
+                force: bang,
+            },
+            Keyword::If => {
+                let mut branches = Vec::new();
+                let mut otherwise = Vec::new();
+                let mut condition = args.to_string();
+                loop {
+                    let (body, terminator) =
+                        parse_block(lines, ix, &[Keyword::ElseIf, Keyword::Else, Keyword::EndIf])?;
+                    branches.push((condition, body));
+                    match terminator {
+                        Some((Keyword::ElseIf, args)) => condition = args,
+                        Some((Keyword::Else, _)) => {
+                            let (body, terminator) = parse_block(lines, ix, &[Keyword::EndIf])?;
+                            if terminator.is_none() {
+                                bail!("E171: Missing :endif");
+                            }
+                            otherwise = body;
+                            break;
+                        }
+                        Some(_) => break,
+                        None => bail!("E171: Missing :endif"),
+                    }
+                }
+                Statement::If {
+                    branches,
+                    otherwise,
+                }
+            }
+            Keyword::For => {
+                let (variable, iterable) = args
+                    .split_once(" in ")
+                    .ok_or_else(|| anyhow!("E690: Missing \"in\" after :for"))?;
+                let (body, terminator) = parse_block(lines, ix, &[Keyword::EndFor])?;
+                if terminator.is_none() {
+                    bail!("E170: Missing :endfor");
+                }
+                Statement::For {
+                    variable: variable_name(variable.trim()),
+                    iterable: iterable.to_string(),
+                    body,
+                }
+            }
+            Keyword::Break => Statement::Break,
+            Keyword::Continue => Statement::Continue,
+            Keyword::Execute => Statement::Execute(args.to_string()),
+            Keyword::Command => parse_command_definition(args, bang)?,
+            Keyword::DelCommand => Statement::DelCommand(args.to_string()),
+            Keyword::ElseIf | Keyword::Else => bail!("E582: :{name} without :if"),
+            Keyword::EndIf => bail!("E580: :endif without :if"),
+            Keyword::EndFor => bail!("E588: :endfor without :for"),
+        };
+        statements.push(statement);
+    }
+    Ok((statements, None))
+}
+
+fn parse_let(args: &str) -> Result<Statement> {
+    let (target, rest) = if let Some(rest) = args.strip_prefix('@') {
+        let mut chars = rest.chars();
+        let register = chars.next().context("E15: Invalid expression")?;
+        (LetTarget::Register(register), chars.as_str())
+    } else {
+        let name_len = args
+            .find(|c: char| !c.is_ascii_alphanumeric() && !matches!(c, '_' | ':' | '#'))
+            .unwrap_or(args.len());
+        if name_len == 0 {
+            bail!("E15: Invalid expression: \"{args}\"");
+        }
+        (
+            LetTarget::Variable(variable_name(&args[..name_len])),
+            &args[name_len..],
+        )
+    };
+    let rest = rest.trim_start();
+    let (operator, expression) = if let Some(expression) = rest.strip_prefix("..=") {
+        (Some('.'), expression)
+    } else if let Some(expression) = rest.strip_prefix('=') {
+        (None, expression)
+    } else {
+        let mut chars = rest.chars();
+        match (chars.next(), chars.next()) {
+            (Some(operator @ ('+' | '-' | '*' | '/' | '%' | '.')), Some('=')) => {
+                (Some(operator), chars.as_str())
+            }
+            _ => bail!("E15: Invalid expression: \"{args}\""),
+        }
+    };
+    Ok(Statement::Let {
+        target,
+        operator,
+        expression: expression.trim().to_string(),
+    })
+}
+
+fn parse_command_definition(args: &str, force: bool) -> Result<Statement> {
+    let mut rest = args;
+    // Attributes such as `-nargs=*` and `-bang` only affect how Vim validates
+    // arguments, so they are accepted and ignored.
+    while let Some(attribute) = rest.strip_prefix('-') {
+        rest = attribute
+            .split_once(char::is_whitespace)
+            .map_or("", |(_, rest)| rest)
+            .trim_start();
+    }
+    let (name, replacement) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        bail!("E183: User defined commands must start with an uppercase letter");
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        bail!("E182: Invalid command name");
+    }
+    let replacement = replacement.trim();
+    if replacement.is_empty() {
+        bail!("E471: Argument required");
+    }
+    Ok(Statement::Command {
+        name: name.to_string(),
+        replacement: replacement.to_string(),
+        force,
+    })
+}
+
+/// Outside of functions, `foo` and `g:foo` refer to the same variable.
+fn variable_name(name: &str) -> String {
+    name.strip_prefix("g:").unwrap_or(name).to_string()
+}
+
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+}
+
+struct Interpreter<'a> {
+    state: &'a mut ExScriptState,
+    host: &'a mut dyn ExScriptHost,
+    depth: usize,
+}
+
+impl Interpreter<'_> {
+    fn run_source(&mut self, source: &str) -> Result<()> {
+        if self.depth >= MAX_DEPTH {
+            bail!("E169: Command too recursive");
+        }
+        let statements = parse_script(source)?;
+        self.depth += 1;
+        let result = self.run(&statements);
+        self.depth -= 1;
+        match result? {
+            Flow::Normal => Ok(()),
+            Flow::Break => bail!("E587: :break without :while or :for"),
+            Flow::Continue => bail!("E586: :continue without :while or :for"),
+        }
+    }
+
+    fn run(&mut self, statements: &[Statement]) -> Result<Flow> {
+        for statement in statements {
+            match statement {
+                Statement::Let {
+                    target,
+                    operator,
+                    expression,
+                } => {
+                    let mut value = self.evaluate(expression)?;
+                    if let Some(operator) = operator {
+                        let current = match target {
+                            LetTarget::Variable(name) => self.variable(name)?,
+                            LetTarget::Register(register) => Value::String(
+                                self.host.read_register(*register).unwrap_or_default(),
+                            ),
+                        };
+                        value = apply_operator(*operator, current, value)?;
+                    }
+                    match target {
+                        LetTarget::Variable(name) => {
+                            self.state.variables.insert(name.clone(), value);
+                        }
+                        LetTarget::Register(register) => {
+                            self.host.write_register(*register, value.to_text()?);
+                        }
+                    }
+                }
+                Statement::Unlet { names, force } => {
+                    for name in names {
+                        if self.state.variables.remove(name).is_none() && !force {
+                            bail!("E108: No such variable: \"{name}\"");
+                        }
+                    }
+                }
+                Statement::If {
+                    branches,
+                    otherwise,
+                } => {
+                    let mut body = otherwise;
+                    for (condition, branch) in branches {
+                        if self.evaluate(condition)?.is_truthy()? {
+                            body = branch;
+                            break;
+                        }
+                    }
+                    match self.run(body)? {
+                        Flow::Normal => {}
+                        flow => return Ok(flow),
+                    }
+                }
+                Statement::For {
+                    variable,
+                    iterable,
+                    body,
+                } => {
+                    let Value::List(items) = self.evaluate(iterable)? else {
+                        bail!("E714: List required");
+                    };
+                    for item in items {
+                        self.state.variables.insert(variable.clone(), item);
+                        if let Flow::Break = self.run(body)? {
+                            break;
+                        }
+                    }
+                }
+                Statement::Break => return Ok(Flow::Break),
+                Statement::Continue => return Ok(Flow::Continue),
+                Statement::Execute(expressions) => {
+                    let command = self.evaluate_all(expressions)?.join(" ");
+                    self.run_source(&command)?;
+                }
+                Statement::Command {
+                    name,
+                    replacement,
+                    force,
+                } => {
+                    if !force && self.state.commands.contains_key(name) {
+                        bail!("E174: Command already exists: add ! to replace it: {name}");
+                    }
+                    self.state
+                        .commands
+                        .insert(name.clone(), replacement.clone());
+                }
+                Statement::DelCommand(name) => {
+                    if self.state.commands.remove(name).is_none() {
+                        bail!("E184: No such user-defined command: {name}");
+                    }
+                }
+                Statement::Ex(line) => self.run_ex_command(line)?,
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn run_ex_command(&mut self, line: &str) -> Result<()> {
+        let (name, bang, args) = split_command(line);
+        if let Some(name) = user_command_name(name, self.state) {
+            let replacement = self.state.commands[name].clone();
+            let expanded = replacement
+                .replace("<args>", args)
+                .replace("<q-args>", &Value::String(args.to_string()).to_string())
+                .replace("<bang>", if bang { "!" } else { "" });
+            return self.run_source(&expanded);
+        }
+        self.host
+            .run_ex_command(line.trim_start_matches(|c: char| c == ':' || c.is_whitespace()))
+    }
+
+    fn variable(&self, name: &str) -> Result<Value> {
+        lookup_variable(&self.state.variables, name)
+    }
+
+    fn evaluate(&mut self, source: &str) -> Result<Value> {
+        let mut evaluator = Evaluator::new(source, &self.state.variables, self.host);
+        let value = evaluator.expression(true)?;
+        evaluator.expect_end()?;
+        Ok(value)
+    }
+
+    /// Evaluates whitespace-separated expressions, as used by `:execute`.
+    fn evaluate_all(&mut self, source: &str) -> Result<Vec<String>> {
+        let mut evaluator = Evaluator::new(source, &self.state.variables, self.host);
+        let mut values = Vec::new();
+        while !evaluator.at_end() {
+            values.push(evaluator.expression(true)?.to_text()?);
+        }
+        Ok(values)
+    }
+}
+
+fn lookup_variable(variables: &HashMap<String, Value>, name: &str) -> Result<Value> {
+    match name {
+        "v:true" => Ok(Value::Number(1)),
+        "v:false" => Ok(Value::Number(0)),
+        _ => variables
+            .get(&variable_name(name))
+            .cloned()
+            .ok_or_else(|| anyhow!("E121: Undefined variable: {name}")),
+    }
+}
+
+fn apply_operator(operator: char, left: Value, right: Value) -> Result<Value> {
+    Ok(match operator {
+        '.' => Value::String(left.to_text()? + &right.to_text()?),
+        '+' => match (left, right) {
+            (Value::List(mut left), Value::List(right)) => {
+                left.extend(right);
+                Value::List(left)
+            }
+            (left, right) => Value::Number(left.to_number()?.wrapping_add(right.to_number()?)),
+        },
+        '-' => Value::Number(left.to_number()?.wrapping_sub(right.to_number()?)),
+        '*' => Value::Number(left.to_number()?.wrapping_mul(right.to_number()?)),
+        '/' | '%' => {
+            let (left, right) = (left.to_number()?, right.to_number()?);
+            if right == 0 {
+                bail!("E1154: Divide by zero");
+            }
+            Value::Number(if operator == '/' {
+                left.wrapping_div(right)
+            } else {
+                left.wrapping_rem(right)
+            })
+        }
+        _ => bail!("E15: Invalid operator: {operator}"),
+    })
+}
+
+/// A recursive-descent evaluator for Vim script expressions.
+///
+/// Like Vim's own evaluator, it takes an `evaluate` flag so that the skipped
+/// side of `&&`, `||` and `?:` is parsed without being evaluated.
+struct Evaluator<'a> {
+    source: &'a str,
+    position: usize,
+    variables: &'a HashMap<String, Value>,
+    host: &'a mut dyn ExScriptHost,
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(
+        source: &'a str,
+        variables: &'a HashMap<String, Value>,
+        host: &'a mut dyn ExScriptHost,
+    ) -> Self {
+        Self {
+            source,
+            position: 0,
+            variables,
+            host,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.rest().is_empty()
+    }
+
+    fn expect_end(&mut self) -> Result<()> {
+        if self.at_end() {
+            Ok(())
+        } else {
+            bail!("E488: Trailing characters: {}", self.rest())
+        }
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            bail!("E15: Invalid expression: missing '{token}'")
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.rest().chars().next()
+    }
+
+    /// `expr1 ? expr1 : expr1`
+    fn expression(&mut self, evaluate: bool) -> Result<Value> {
+        let condition = self.or(evaluate)?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+        let condition = evaluate && condition.is_truthy()?;
+        let then = self.expression(condition)?;
+        self.expect(":")?;
+        let otherwise = self.expression(evaluate && !condition)?;
+        Ok(if condition { then } else { otherwise })
+    }
+
+    fn or(&mut self, evaluate: bool) -> Result<Value> {
+        let mut value = self.and(evaluate)?;
+        while self.eat("||") {
+            let left = evaluate && value.is_truthy()?;
+            let right = self.and(evaluate && !left)?;
+            value = Value::from_bool(left || (evaluate && right.is_truthy()?));
+        }
+        Ok(value)
+    }
+
+    fn and(&mut self, evaluate: bool) -> Result<Value> {
+        let mut value = self.comparison(evaluate)?;
+        while self.eat("&&") {
+            let left = evaluate && value.is_truthy()?;
+            let right = self.comparison(left)?;
+            value = Value::from_bool(left && right.is_truthy()?);
+        }
+        Ok(value)
+    }
+
+    fn comparison(&mut self, evaluate: bool) -> Result<Value> {
+        let left = self.additive(evaluate)?;
+        const OPERATORS: &[&str] = &["==", "!=", ">=", "<=", "=~", "!~", ">", "<"];
+        self.skip_whitespace();
+        let Some(operator) = OPERATORS
+            .iter()
+            .find(|operator| self.rest().starts_with(**operator))
+        else {
+            return Ok(left);
+        };
+        self.position += operator.len();
+        let ignore_case = match self.rest().chars().next() {
+            Some('?') => {
+                self.position += 1;
+                true
+            }
+            Some('#') => {
+                self.position += 1;
+                false
+            }
+            _ => false,
+        };
+        let right = self.additive(evaluate)?;
+        if !evaluate {
+            return Ok(Value::Number(0));
+        }
+        compare(operator, &left, &right, ignore_case).map(Value::from_bool)
+    }
+
+    fn additive(&mut self, evaluate: bool) -> Result<Value> {
+        let mut value = self.multiplicative(evaluate)?;
+        loop {
+            let operator = if self.eat("..") || self.eat(".") {
+                '.'
+            } else if self.eat("+") {
+                '+'
+            } else if self.eat("-") {
+                '-'
+            } else {
+                return Ok(value);
+            };
+            let right = self.multiplicative(evaluate)?;
+            if evaluate {
+                value = apply_operator(operator, value, right)?;
+            }
+        }
+    }
+
+    fn multiplicative(&mut self, evaluate: bool) -> Result<Value> {
+        let mut value = self.unary(evaluate)?;
+        loop {
+            let operator = match self.peek() {
+                Some(operator @ ('*' | '/' | '%')) => operator,
+                _ => return Ok(value),
+            };
+            self.position += 1;
+            let right = self.unary(evaluate)?;
+            if evaluate {
+                value = apply_operator(operator, value, right)?;
+            }
+        }
+    }
+
+    fn unary(&mut self, evaluate: bool) -> Result<Value> {
+        if self.eat("!") {
+            let value = self.unary(evaluate)?;
+            return Ok(Value::from_bool(evaluate && !value.is_truthy()?));
+        }
+        if self.eat("-") {
+            let value = self.unary(evaluate)?;
+            return Ok(Value::Number(if evaluate {
+                value.to_number()?.wrapping_neg()
+            } else {
+                0
+            }));
+        }
+        if self.eat("+") {
+            let value = self.unary(evaluate)?;
+            return Ok(Value::Number(if evaluate {
+                value.to_number()?
+            } else {
+                0
+            }));
+        }
+        self.postfix(evaluate)
+    }
+
+    fn postfix(&mut self, evaluate: bool) -> Result<Value> {
+        let mut value = self.primary(evaluate)?;
+        while self.rest().starts_with('[') {
+            self.position += 1;
+            let index = self.expression(evaluate)?;
+            self.expect("]")?;
+            if evaluate {
+                value = index_value(value, index.to_number()?)?;
+            }
+        }
+        Ok(value)
+    }
+
+    fn primary(&mut self, evaluate: bool) -> Result<Value> {
+        let Some(c) = self.peek() else {
+            bail!("E15: Invalid expression: \"{}\"", self.source);
+        };
+        match c {
+            '0'..='9' => self.number(),
+            '"' => self.double_quoted_string(),
+            '\'' => self.single_quoted_string(),
+            '[' => {
+                self.position += 1;
+                let mut items = Vec::new();
+                while !self.eat("]") {
+                    items.push(self.expression(evaluate)?);
+                    if !self.eat(",") {
+                        self.expect("]")?;
+                        break;
+                    }
+                }
+                Ok(Value::List(items))
+            }
+            '(' => {
+                self.position += 1;
+                let value = self.expression(evaluate)?;
+                self.expect(")")?;
+                Ok(value)
+            }
+            '@' => {
+                self.position += 1;
+                let register = self
+                    .rest()
+                    .chars()
+                    .next()
+                    .context("E15: Missing register")?;
+                self.position += register.len_utf8();
+                Ok(Value::String(if evaluate {
+                    self.host.read_register(register).unwrap_or_default()
+                } else {
+                    String::new()
+                }))
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let rest = self.rest();
+                let name_len = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric() && !matches!(c, '_' | ':' | '#'))
+                    .unwrap_or(rest.len());
+                let name = &rest[..name_len];
+                self.position += name_len;
+                if self.rest().starts_with('(') {
+                    self.position += 1;
+                    let mut arguments = Vec::new();
+                    while !self.eat(")") {
+                        arguments.push(self.expression(evaluate)?);
+                        if !self.eat(",") {
+                            self.expect(")")?;
+                            break;
+                        }
+                    }
+                    if !evaluate {
+                        return Ok(Value::Number(0));
+                    }
+                    call_function(name, arguments, self.variables)
+                } else if evaluate {
+                    lookup_variable(self.variables, name)
+                } else {
+                    Ok(Value::Number(0))
+                }
+            }
+            _ => bail!("E15: Invalid expression: \"{}\"", self.rest()),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value> {
+        let rest = self.rest();
+        let (radix, digits) = match rest.get(..2) {
+            Some("0x" | "0X") => (16, &rest[2..]),
+            _ => (10, rest),
+        };
+        let len = digits
+            .find(|c: char| !c.is_digit(radix))
+            .unwrap_or(digits.len());
+        let number = i64::from_str_radix(&digits[..len], radix)
+            .with_context(|| format!("E15: Invalid number: \"{rest}\""))?;
+        self.position += rest.len() - digits.len() + len;
+        Ok(Value::Number(number))
+    }
+
+    fn double_quoted_string(&mut self) -> Result<Value> {
+        let mut chars = self.rest().char_indices().skip(1);
+        let mut string = String::new();
+        while let Some((ix, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += ix + 1;
+                    return Ok(Value::String(string));
+                }
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => string.push('\n'),
+                    Some('t') => string.push('\t'),
+                    Some('r') => string.push('\r'),
+                    Some('e') => string.push('\x1b'),
+                    Some(c) => string.push(c),
+                    None => break,
+                },
+                c => string.push(c),
+            }
+        }
+        bail!("E114: Missing double quote: {}", self.rest())
+    }
+
+    fn single_quoted_string(&mut self) -> Result<Value> {
+        let rest = self.rest();
+        let mut chars = rest.char_indices().skip(1).peekable();
+        let mut string = String::new();
+        while let Some((ix, c)) = chars.next() {
+            if c == '\'' {
+                if chars.peek().is_some_and(|(_, next)| *next == '\'') {
+                    chars.next();
+                } else {
+                    self.position += ix + 1;
+                    return Ok(Value::String(string));
+                }
+            }
+            string.push(c);
+        }
+        bail!("E115: Missing single quote: {rest}")
+    }
+}
+
+fn compare(operator: &str, left: &Value, right: &Value, ignore_case: bool) -> Result<bool> {
+    if matches!(operator, "=~" | "!~") {
+        let pattern = RegexBuilder::new(&right.to_text()?)
+            .case_insensitive(ignore_case)
+            .build()?;
+        let is_match = pattern.is_match(&left.to_text()?);
+        return Ok(is_match == (operator == "=~"));
+    }
+
+    let ordering = match (left, right) {
+        (Value::List(_), _) | (_, Value::List(_)) => {
+            return match operator {
+                "==" => Ok(left == right),
+                "!=" => Ok(left != right),
+                _ => bail!("E692: Invalid operation for List"),
+            };
+        }
+        (Value::String(left), Value::String(right)) => {
+            if ignore_case {
+                left.to_lowercase().cmp(&right.to_lowercase())
+            } else {
+                left.cmp(right)
+            }
+        }
+        (left, right) => left.to_number()?.cmp(&right.to_number()?),
+    };
+    Ok(match operator {
+        "==" => ordering.is_eq(),
+        "!=" => ordering.is_ne(),
+        ">" => ordering.is_gt(),
+        ">=" => ordering.is_ge(),
+        "<" => ordering.is_lt(),
+        "<=" => ordering.is_le(),
+        _ => unreachable!("unknown comparison operator {operator}"),
+    })
+}
+
+fn index_value(value: Value, index: i64) -> Result<Value> {
+    match value {
+        Value::List(items) => {
+            let ix = if index < 0 {
+                items.len() as i64 + index
+            } else {
+                index
+            };
+            usize::try_from(ix)
+                .ok()
+                .and_then(|ix| items.into_iter().nth(ix))
+                .ok_or_else(|| anyhow!("E684: List index out of range: {index}"))
+        }
+        value => {
+            let text = value.to_text()?;
+            let byte = usize::try_from(index)
+                .ok()
+                .and_then(|ix| text.get(ix..ix + 1))
+                .unwrap_or_default();
+            Ok(Value::String(byte.to_string()))
+        }
+    }
+}
+
+fn call_function(
+    name: &str,
+    arguments: Vec<Value>,
+    variables: &HashMap<String, Value>,
+) -> Result<Value> {
+    let argument = |ix: usize| -> Result<&Value> {
+        arguments
+            .get(ix)
+            .ok_or_else(|| anyhow!("E119: Not enough arguments for function: {name}"))
+    };
+    Ok(match name {
+        "len" => Value::Number(match argument(0)? {
+            Value::List(items) => items.len() as i64,
+            value => value.to_text()?.len() as i64,
+        }),
+        "empty" => Value::from_bool(match argument(0)? {
+            Value::List(items) => items.is_empty(),
+            Value::String(string) => string.is_empty(),
+            Value::Number(number) => *number == 0,
+        }),
+        "range" => {
+            let numbers = arguments
+                .iter()
+                .map(Value::to_number)
+                .collect::<Result<Vec<_>>>()?;
+            let (start, end, stride) = match numbers.as_slice() {
+                [count] => (0, count.saturating_sub(1), 1),
+                [start, end] => (*start, *end, 1),
+                [start, end, stride] => (*start, *end, *stride),
+                _ => bail!("E118: Too many arguments for function: {name}"),
+            };
+            if stride == 0 {
+                bail!("E726: Stride is zero");
+            }
+            let len = (i128::from(end) - i128::from(start)) / i128::from(stride) + 1;
+            if len > MAX_RANGE_LEN as i128 {
+                bail!("E1240: Resulting List is too long: {len} items");
+            }
+            let mut items = Vec::with_capacity(len.max(0) as usize);
+            let mut number = Some(start);
+            while let Some(current) = number
+                && ((stride > 0 && current <= end) || (stride < 0 && current >= end))
+            {
+                items.push(Value::Number(current));
+                number = current.checked_add(stride);
+            }
+            Value::List(items)
+        }
+        "join" => {
+            let Value::List(items) = argument(0)? else {
+                bail!("E714: List required");
+            };
+            let separator = match arguments.get(1) {
+                Some(separator) => separator.to_text()?,
+                None => " ".to_string(),
+            };
+            Value::String(
+                items
+                    .iter()
+                    .map(Value::to_text)
+                    .collect::<Result<Vec<_>>>()?
+                    .join(&separator),
+            )
+        }
+        "split" => {
+            let text = argument(0)?.to_text()?;
+            let items = match arguments.get(1) {
+                Some(pattern) => RegexBuilder::new(&pattern.to_text()?)
+                    .build()?
+                    .split(&text)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| Value::String(item.to_string()))
+                    .collect(),
+                None => text
+                    .split_whitespace()
+                    .map(|item| Value::String(item.to_string()))
+                    .collect(),
+            };
+            Value::List(items)
+        }
+        "toupper" => Value::String(argument(0)?.to_text()?.to_uppercase()),
+        "tolower" => Value::String(argument(0)?.to_text()?.to_lowercase()),
+        "string" => Value::String(match argument(0)? {
+            Value::String(string) => string.clone(),
+            value => value.to_string(),
+        }),
+        "str2nr" => Value::Number(parse_leading_number(&argument(0)?.to_text()?)),
+        "exists" => {
+            let name = argument(0)?.to_text()?;
+            Value::from_bool(lookup_variable(variables, &name).is_ok())
+        }
+        _ => bail!("E117: Unknown function: {name}"),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeHost {
+        registers: HashMap<char, String>,
+        commands: Vec<String>,
+    }
+
+    impl ExScriptHost for FakeHost {
+        fn read_register(&mut self, register: char) -> Option<String> {
+            self.registers.get(&register).cloned()
+        }
+
+        fn write_register(&mut self, register: char, text: String) {
+            self.registers.insert(register, text);
+        }
+
+        fn run_ex_command(&mut self, command: &str) -> Result<()> {
+            self.commands.push(command.to_string());
+            Ok(())
+        }
+    }
+
+    fn run(source: &str) -> (ExScriptState, FakeHost) {
+        let mut state = ExScriptState::default();
+        let mut host = FakeHost::default();
+        state.run(source, &mut host).unwrap();
+        (state, host)
+    }
+
+    #[test]
+    fn test_let_and_expressions() {
+        let (state, host) = run(indoc::indoc! {r#"
+            " comments and blank lines are ignored
+
+            let g:count = 2 + 3 * 4
+            let name = 'it''s' . " here"
+            let count += 1
+            let list = [1, 'two', [3]]
+            let @a = toupper(name)
+            let @a .= '!'
+            let flag = count > 10 && len(list) == 3 ? 'yes' : 'no'
+            let last = list[-1][0]
+        "#});
+        assert_eq!(state.variables["count"], Value::Number(15));
+        assert_eq!(state.variables["name"], Value::String("it's here".into()));
+        assert_eq!(state.variables["flag"], Value::String("yes".into()));
+        assert_eq!(state.variables["last"], Value::Number(3));
+        assert_eq!(host.registers[&'a'], "IT'S HERE!");
+        assert!(host.commands.is_empty());
+    }
+
+    #[test]
+    fn test_control_flow() {
+        let (_, host) = run(indoc::indoc! {r#"
+            let mode = 'light'
+            if mode ==# 'dark'
+              set nonumber
+            elseif mode =~ '^li'
+              set number
+            else
+              set relativenumber
+            endif
+            for i in range(1, 5)
+              if i % 2 == 0 | continue | endif
+              if i > 3 | break | endif
+              execute 'normal' i . 'j'
+            endfor
+            for word in split('a b')
+              exe "echo '" . word . "'"
+            endfor
+        "#});
+        assert_eq!(
+            host.commands,
+            [
+                "set number",
+                "normal 1j",
+                "normal 3j",
+                "echo 'a'",
+                "echo 'b'"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_short_circuit() {
+        let (state, _) = run(indoc::indoc! {r#"
+            let a = exists('g:undefined') && g:undefined
+            let b = 1 || undefined
+            let c = 0 ? undefined : 'ok'
+        "#});
+        assert_eq!(state.variables["a"], Value::Number(0));
+        assert_eq!(state.variables["b"], Value::Number(1));
+        assert_eq!(state.variables["c"], Value::String("ok".into()));
+    }
+
+    #[test]
+    fn test_user_commands() {
+        let (mut state, mut host) = run(indoc::indoc! {r#"
+            command! -nargs=* Grep execute 'grep' <q-args>
+            command -bang Fmt editor::Format | w<bang>
+            command -nargs=1 Twice exe <q-args> | exe <q-args>
+        "#});
+        state
+            .run("Grep foo bar\nFmt!\n:Twice normal dd", &mut host)
+            .unwrap();
+        assert_eq!(
+            host.commands,
+            [
+                "grep foo bar",
+                "editor::Format",
+                "w!",
+                "normal dd",
+                "normal dd"
+            ]
+        );
+
+        let error = state.run("command Fmt w", &mut host).unwrap_err();
+        assert!(error.to_string().starts_with("E174"), "{error}");
+        state.run("delcommand Fmt", &mut host).unwrap();
+        assert!(!state.has_command("Fmt"));
+        assert!(state.run("command lower w", &mut host).is_err());
+
+        state.run("command! Loop Loop", &mut host).unwrap();
+        let error = state.run("Loop", &mut host).unwrap_err();
+        assert!(error.to_string().starts_with("E169"), "{error}");
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut state = ExScriptState::default();
+        let mut host = FakeHost::default();
+        for (source, code) in [
+            ("if 1\nset number", "E171"),
+            ("for i in [1]", "E170"),
+            ("endif", "E580"),
+            ("let x = undefined", "E121"),
+            ("let x = 1 +", "E15"),
+            ("let x = 1 2", "E488"),
+            ("unlet x", "E108"),
+            ("for i in 3\nendfor", "E714"),
+            ("let x = range(1, 2, 0)", "E726"),
+            ("let x = range(100000000)", "E1240"),
+        ] {
+            let error = state.run(source, &mut host).unwrap_err();
+            assert!(
+                error.to_string().starts_with(code),
+                "{source:?} failed with {error}, expected {code}"
+            );
+        }
+        assert!(host.commands.is_empty());
+    }
+}
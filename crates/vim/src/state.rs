@@ -1,4 +1,5 @@
 use crate::command::command_interceptor;
+use crate::ex_script::ExScriptState;
 use crate::motion::MotionKind;
 use crate::normal::repeat::Replayer;
 use crate::surrounds::SurroundsType;
@@ -253,6 +254,12 @@ pub struct VimGlobals {
     pub focused_vim: Option<WeakEntity<Vim>>,
 
     pub marks: HashMap<EntityId, Entity<MarksState>>,
+
+    /// Variables and user commands defined by ex scripts.
+    pub(crate) ex_script: ExScriptState,
+    /// The contents of the user's `init.vim`, taken and run by the first vim
+    /// editor to be focused.
+    pub(crate) startup_script: Option<Arc<str>>,
 }
 
 pub struct MarksState {
@@ -746,6 +753,7 @@ impl VimGlobals {
                     filter.show_namespace(Vim::NAMESPACE);
                 });
                 GlobalCommandPaletteInterceptor::set(cx, command_interceptor);
+                VimGlobals::load_startup_script(cx);
                 for window in cx.windows() {
                     if let Some(multi_workspace) = window.downcast::<MultiWorkspace>() {
                         multi_workspace
@@ -777,6 +785,28 @@ impl VimGlobals {
         .detach()
     }
 
+    fn load_startup_script(cx: &mut App) {
+        cx.spawn(async move |cx| {
+            let source = cx
+                .background_spawn(async { std::fs::read_to_string(paths::vim_script_file()) })
+                .await;
+            let source = match source {
+                Ok(source) => source,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => return,
+                Err(error) => {
+                    log::error!("failed to read {:?}: {error}", paths::vim_script_file());
+                    return;
+                }
+            };
+            cx.update(|cx| {
+                if Vim::enabled(cx) {
+                    Vim::globals(cx).startup_script = Some(source.into());
+                }
+            });
+        })
+        .detach();
+    }
+
     fn register_workspace(&mut self, workspace: &Workspace, cx: &mut Context<Workspace>) {
         let entity_id = cx.entity_id();
         self.marks.insert(entity_id, MarksState::new(workspace, cx));
@@ -6,6 +6,7 @@ mod test;
 mod change_list;
 mod command;
 mod digraph;
+mod ex_script;
 mod helix;
 mod indent;
 mod insert;
@@ -1518,6 +1519,10 @@ impl Vim {
             }
         }
         Vim::globals(cx).focused_vim = Some(cx.entity().downgrade());
+
+        if let Some(source) = Vim::globals(cx).startup_script.take() {
+            self.run_ex_script(&source, window, cx);
+        }
     }
 
     fn blurred(&mut self, window: &mut Window, cx: &mut Context<Self>) {
@@ -1,12 +1,12 @@
 use std::{path::Path, sync::Arc};
 
-use gpui::{EventEmitter, FocusHandle, Focusable};
+use gpui::{Action, EventEmitter, FocusHandle, Focusable};
 use ui::{
     App, Button, ButtonCommon, ButtonStyle, Clickable, Context, FluentBuilder, InteractiveElement,
     KeyBinding, Label, LabelCommon, LabelSize, ParentElement, Render, SharedString, Styled as _,
     Window, h_flex, v_flex,
 };
-use zed_actions::workspace::OpenWithSystem;
+use zed_actions::{hex_editor::OpenInHexEditor, workspace::OpenWithSystem};
 
 use crate::Item;
 
@@ -97,15 +97,34 @@ impl Render for InvalidItemView {
                                 .child(Label::new(self.error.clone()).size(LabelSize::Small)),
                         )
                         .when(self.is_local, |contents| {
+                            let open_in_hex_editor = OpenInHexEditor {
+                                abs_path: Some(abs_path.to_path_buf()),
+                            };
                             contents.child(
-                                h_flex().justify_center().child(
-                                    Button::new("open-with-system", "Open in Default App")
-                                        .on_click(move |_, _, cx| {
-                                            cx.open_with_system(&abs_path);
-                                        })
-                                        .style(ButtonStyle::Outlined)
-                                        .key_binding(KeyBinding::for_action(&OpenWithSystem, cx)),
-                                ),
+                                h_flex()
+                                    .justify_center()
+                                    .gap_2()
+                                    .child(
+                                        Button::new("open-with-system", "Open in Default App")
+                                            .on_click(move |_, _, cx| {
+                                                cx.open_with_system(&abs_path);
+                                            })
+                                            .style(ButtonStyle::Outlined)
+                                            .key_binding(KeyBinding::for_action(
+                                                &OpenWithSystem,
+                                                cx,
+                                            )),
+                                    )
+                                    .child(
+                                        Button::new("open-in-hex-editor", "Open in Hex Editor")
+                                            .on_click(move |_, window, cx| {
+                                                window.dispatch_action(
+                                                    open_in_hex_editor.boxed_clone(),
+                                                    cx,
+                                                );
+                                            })
+                                            .style(ButtonStyle::Outlined),
+                                    ),
                             )
                         }),
                 ),
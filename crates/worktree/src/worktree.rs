@@ -76,6 +76,14 @@ use crate::ignore::IgnoreKind;
 
 pub const FS_WATCH_LATENCY: Duration = Duration::from_millis(100);
 
+/// Files at least this large are not loaded into buffers.
+///
+/// We are not efficient with our memory usage per file, and use in excess of 64GB for a 10GB file.
+/// Therefore, as a temporary workaround to prevent system freezes (#27283), we just bail before
+/// opening a file if it is too large. 5GB seems to be more reasonable, peaking at ~16GB, while 6GB
+/// jumps up to >24GB which seems like a reasonable limit.
+pub const MAX_LOADABLE_FILE_SIZE: u64 = 6 * 1024 * 1024 * 1024; // 6GB
+
 /// A set of local or remote files that are being opened as part of a project.
 /// Responsible for tracking related FS (for local)/collab (for remote) events and corresponding updates.
 /// Stores git repositories data and the diagnostics for the file(s).
@@ -1351,15 +1359,10 @@ impl LocalWorktree {
         let this = cx.weak_entity();
         cx.background_spawn(async move {
             // WARN: Temporary workaround for #27283.
-            //       We are not efficient with our memory usage per file, and use in excess of 64GB for a 10GB file
-            //       Therefore, as a temporary workaround to prevent system freezes, we just bail before opening a file
-            //       if it is too large
-            //       5GB seems to be more reasonable, peaking at ~16GB, while 6GB jumps up to >24GB which seems like a
-            //       reasonable limit
+            //       See `MAX_LOADABLE_FILE_SIZE` for details.
             {
-                const FILE_SIZE_MAX: u64 = 6 * 1024 * 1024 * 1024; // 6GB
                 if let Ok(Some(metadata)) = fs.metadata(&abs_path).await
-                    && metadata.len >= FILE_SIZE_MAX
+                    && metadata.len >= MAX_LOADABLE_FILE_SIZE
                 {
                     anyhow::bail!("File is too large to load");
                 }
@@ -2,6 +2,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod reliability;
+mod run_notebook;
 mod zed;
 
 use agent::{SharedThread, ThreadStore};
@@ -302,6 +303,17 @@ fn main() {
         return;
     }
 
+    // `zed --run-notebook` Executes a notebook without opening a window
+    if let Some(path) = args.run_notebook.clone() {
+        run_notebook::main(run_notebook::RunNotebookArgs {
+            path,
+            kernel_name: args.notebook_kernel.clone(),
+            cell_timeout: args.notebook_timeout.map(std::time::Duration::from_secs),
+            markdown_path: args.notebook_markdown.clone(),
+        });
+        return;
+    }
+
     rayon::ThreadPoolBuilder::new()
         .num_threads(std::thread::available_parallelism().map_or(1, |n| n.get().div_ceil(2)))
         .stack_size(10 * 1024 * 1024)
@@ -666,6 +678,7 @@ fn main() {
         language_models::init(app_state.user_store.clone(), app_state.client.clone(), cx);
         acp_tools::init(cx);
         zed::telemetry_log::init(cx);
+        zed::log_view::init(cx);
         zed::remote_debug::init(cx);
         edit_prediction_ui::init(cx);
         web_search::init(cx);
@@ -695,6 +708,9 @@ fn main() {
 
         editor::init(cx);
         image_viewer::init(cx);
+        large_file_viewer::init(cx);
+        hex_editor::init(cx);
+        bookmarks::init(cx);
         repl::notebook::init(cx);
         diagnostics::init(cx);
 
@@ -707,6 +723,7 @@ fn main() {
         tab_switcher::init(cx);
         outline::init(cx);
         project_symbols::init(cx);
+        symbol_index::init(cx);
         project_panel::init(cx);
         outline_panel::init(cx);
         tasks_ui::init(cx);
@@ -725,6 +742,8 @@ fn main() {
         vim::init(cx);
         terminal_view::init(cx);
         journal::init(app_state.clone(), cx);
+        spell_check::init(cx);
+        markdown_notes::init(cx);
         encoding_selector::init(cx);
         language_selector::init(cx);
         line_ending_selector::init(cx);
@@ -1677,6 +1696,23 @@ struct Args {
     #[arg(long, hide = true)]
     dump_all_actions: bool,
 
+    /// Runs all code cells of a Jupyter notebook and saves their outputs to it,
+    /// without opening a window. Exits with a non-zero status if a cell fails.
+    #[arg(long, value_name = "NOTEBOOK")]
+    run_notebook: Option<PathBuf>,
+
+    /// The Jupyter kernel to run the notebook in. Defaults to the notebook's kernel.
+    #[arg(long, value_name = "NAME", requires = "run_notebook")]
+    notebook_kernel: Option<String>,
+
+    /// How long each notebook cell may run, in seconds. Defaults to 600.
+    #[arg(long, value_name = "SECONDS", requires = "run_notebook")]
+    notebook_timeout: Option<u64>,
+
+    /// Also exports the executed notebook to Markdown at this path.
+    #[arg(long, value_name = "PATH", requires = "run_notebook")]
+    notebook_markdown: Option<PathBuf>,
+
     /// Output current environment variables as JSON to stdout
     #[arg(long, hide = true)]
     printenv: bool,
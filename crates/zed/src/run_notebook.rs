@@ -0,0 +1,94 @@
+//! `zed --run-notebook`, which executes a Jupyter notebook without opening a
+//! window, e.g. to check in CI that a notebook still runs.
+
+use std::{path::PathBuf, process, sync::Arc, time::Duration};
+
+use anyhow::{Context as _, Result};
+use fs::{Fs, RealFs};
+use gpui::BackgroundExecutor;
+use repl::notebook::{
+    DEFAULT_CELL_TIMEOUT, NotebookRunOptions, find_notebook_kernel, notebook_to_markdown,
+    parse_notebook, run_notebook,
+};
+
+pub struct RunNotebookArgs {
+    pub path: PathBuf,
+    pub kernel_name: Option<String>,
+    pub cell_timeout: Option<Duration>,
+    pub markdown_path: Option<PathBuf>,
+}
+
+pub fn main(args: RunNotebookArgs) {
+    gpui_platform::headless().run(move |cx| {
+        repl::init_headless(cx);
+        let executor = cx.background_executor().clone();
+        let fs: Arc<dyn Fs> = Arc::new(RealFs::new(None, executor.clone()));
+        cx.spawn(async move |_| {
+            let exit_code = match run(args, fs, executor).await {
+                Ok(()) => 0,
+                Err(error) => {
+                    eprintln!("Error: {error:#}");
+                    1
+                }
+            };
+            process::exit(exit_code);
+        })
+        .detach();
+    });
+}
+
+async fn run(args: RunNotebookArgs, fs: Arc<dyn Fs>, executor: BackgroundExecutor) -> Result<()> {
+    let path = std::path::absolute(&args.path)
+        .with_context(|| format!("Invalid notebook path {:?}", args.path))?;
+    let contents = fs
+        .load(&path)
+        .await
+        .with_context(|| format!("Failed to read {path:?}"))?;
+    let notebook =
+        parse_notebook(&contents).with_context(|| format!("Failed to parse {path:?}"))?;
+
+    let kernel = find_notebook_kernel(&notebook, args.kernel_name.as_deref(), fs.clone()).await?;
+    eprintln!(
+        "Running {} with the {} kernel",
+        path.display(),
+        kernel.kernelspec.display_name
+    );
+
+    let working_directory = path
+        .parent()
+        .map(|parent| parent.to_path_buf())
+        .unwrap_or_else(std::env::temp_dir);
+    let run = run_notebook(
+        notebook,
+        kernel,
+        NotebookRunOptions {
+            cell_timeout: args.cell_timeout.unwrap_or(DEFAULT_CELL_TIMEOUT),
+            working_directory,
+        },
+        fs.clone(),
+        executor,
+    )
+    .await?;
+
+    // Outputs are saved even when a cell fails, so that its error can be inspected.
+    let json =
+        serde_json::to_string_pretty(&run.notebook).context("Failed to serialize notebook")?;
+    fs.atomic_write(path.clone(), json).await?;
+    if let Some(markdown_path) = args.markdown_path {
+        let markdown = notebook_to_markdown(&run.notebook)?;
+        fs.atomic_write(markdown_path.clone(), markdown)
+            .await
+            .with_context(|| format!("Failed to write {markdown_path:?}"))?;
+    }
+
+    if let Some(failure) = run.failure {
+        anyhow::bail!(
+            "Cell {} of {} failed: {}",
+            failure.cell_index + 1,
+            path.display(),
+            failure.message
+        );
+    }
+    eprintln!("Ran all cells of {}", path.display());
+    Ok(())
+}
@@ -1,5 +1,6 @@
 mod app_menus;
 pub mod edit_prediction_registry;
+pub mod log_view;
 #[cfg(target_os = "macos")]
 pub(crate) mod mac_only_instance;
 mod migrate;
@@ -640,6 +641,8 @@ fn initialize_panels(
             workspace_handle.clone(),
             cx.clone(),
         );
+        let variable_explorer =
+            repl::VariableExplorerPanel::load(workspace_handle.clone(), cx.clone());
         let debug_panel = DebugPanel::load(workspace_handle.clone(), cx);
 
         async fn add_panel_when_ready(
@@ -665,6 +668,7 @@ fn initialize_panels(
             add_panel_when_ready(channels_panel, workspace_handle.clone(), cx.clone()),
             add_panel_when_ready(notification_panel, workspace_handle.clone(), cx.clone()),
             add_panel_when_ready(debug_panel, workspace_handle.clone(), cx.clone()),
+            add_panel_when_ready(variable_explorer, workspace_handle.clone(), cx.clone()),
             initialize_agent_panel(workspace_handle, prompt_builder, cx.clone()).map(|r| r.log_err()),
         );
 
@@ -1226,6 +1230,8 @@ fn initialize_pane(
             let telemetry_log_item =
                 cx.new(|cx| telemetry_log::TelemetryLogToolbarItemView::new(window, cx));
             toolbar.add_item(telemetry_log_item, window, cx);
+            let log_view_item = cx.new(|cx| log_view::LogViewToolbarItemView::new(window, cx));
+            toolbar.add_item(log_view_item, window, cx);
             let syntax_tree_item = cx.new(|_| language_tools::SyntaxTreeToolbarItemView::new());
             toolbar.add_item(syntax_tree_item, window, cx);
             let migration_banner =
@@ -4845,6 +4851,7 @@ mod tests {
                 "auto_update",
                 "branch_picker",
                 "bedrock",
+                "bookmarks",
                 "branches",
                 "buffer_search",
                 "channel_modal",
@@ -4872,6 +4879,7 @@ mod tests {
                 "git_panel",
                 "git_picker",
                 "go_to_line",
+                "hex_editor",
                 "highlights_tree_view",
                 "icon_theme_selector",
                 "image_viewer",
@@ -4880,10 +4888,12 @@ mod tests {
                 "keymap_editor",
                 "keystroke_input",
                 "language_selector",
+                "large_file_viewer",
                 "welcome",
                 "line_ending_selector",
                 "lsp_tool",
                 "markdown",
+                "markdown_notes",
                 "menu",
                 "multi_workspace",
                 "new_process_modal",
@@ -5113,6 +5123,10 @@ mod tests {
                 cx,
             );
             image_viewer::init(cx);
+            large_file_viewer::init(cx);
+            hex_editor::init(cx);
+            bookmarks::init(cx);
+            markdown_notes::init(cx);
             language_model::init(app_state.user_store.clone(), app_state.client.clone(), cx);
             language_models::init(app_state.user_store.clone(), app_state.client.clone(), cx);
             web_search::init(cx);
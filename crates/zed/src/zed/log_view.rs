@@ -0,0 +1,882 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use fs::Fs;
+use futures::StreamExt;
+use gpui::{
+    App, Corner, Empty, Entity, EventEmitter, FocusHandle, Focusable, ListAlignment, ListState,
+    Task, Window, list, prelude::*,
+};
+use log::{Level, LevelFilter};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use ui::{
+    ContextMenu, ContextMenuEntry, Icon, IconButton, IconName, IconSize, Label, PopoverMenu,
+    Tooltip, WithScrollbar, prelude::*,
+};
+use workspace::{
+    Item, ItemHandle, OpenLog, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView, Workspace,
+};
+
+const MAX_ENTRIES: usize = 10_000;
+const LEVELS: [Level; 5] = [
+    Level::Error,
+    Level::Warn,
+    Level::Info,
+    Level::Debug,
+    Level::Trace,
+];
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(
+        |workspace: &mut Workspace, _window, _cx: &mut Context<Workspace>| {
+            workspace.register_action(|workspace, _: &zed_actions::OpenLogViewer, window, cx| {
+                if let Some(log_view) = workspace.item_of_type::<LogView>(cx) {
+                    workspace.activate_item(&log_view, true, true, window, cx);
+                    return;
+                }
+                let fs = workspace.app_state().fs.clone();
+                let log_view = cx.new(|cx| LogView::new(fs, paths::log_file().clone(), cx));
+                workspace.add_item_to_active_pane(Box::new(log_view), None, true, window, cx);
+            });
+        },
+    )
+    .detach();
+}
+
+/// A live view of the Zed log, which understands both the text and JSON
+/// formats written by `zlog`.
+pub struct LogView {
+    focus_handle: FocusHandle,
+    entries: VecDeque<LogEntry>,
+    filtered_indices: Vec<usize>,
+    list_state: ListState,
+    filter: LogFilter,
+    /// Crate names that appear in the log, offered in the scope levels menu.
+    known_scopes: BTreeSet<SharedString>,
+    read_error: Option<SharedString>,
+    _tail_task: Task<()>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct LogEntry {
+    timestamp: SharedString,
+    /// `None` for lines that don't belong to any record, e.g. output written
+    /// before the logger was initialized.
+    level: Option<Level>,
+    /// The scope or module path the record was logged from.
+    scope: SharedString,
+    line: Option<u32>,
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+struct LogFilter {
+    max_level: LevelFilter,
+    scope: String,
+    search: Option<Regex>,
+    search_error: Option<SharedString>,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            max_level: LevelFilter::Trace,
+            scope: String::new(),
+            search: None,
+            search_error: None,
+        }
+    }
+}
+
+impl LogFilter {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        if let Some(level) = entry.level
+            && level > self.max_level
+        {
+            return false;
+        }
+        if !self.scope.is_empty() && !scope_matches(&entry.scope, &self.scope) {
+            return false;
+        }
+        if let Some(search) = &self.search {
+            return search.is_match(&entry.message)
+                || search.is_match(&entry.scope)
+                || entry
+                    .fields
+                    .iter()
+                    .any(|(key, value)| search.is_match(key) || search.is_match(value));
+        }
+        true
+    }
+}
+
+/// Whether `scope` is `filter` or nested inside of it, so that `project`
+/// matches `project::lsp_store` and `project.lsp`, but not `project_panel`.
+fn scope_matches(scope: &str, filter: &str) -> bool {
+    scope
+        .strip_prefix(filter)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(':') || rest.starts_with('.'))
+}
+
+/// The name of the crate a scope or module path belongs to.
+fn root_scope(scope: &str) -> &str {
+    let end = scope.find([':', '.']).unwrap_or(scope.len());
+    &scope[..end]
+}
+
+impl LogView {
+    pub fn new(fs: Arc<dyn Fs>, path: PathBuf, cx: &mut Context<Self>) -> Self {
+        let tail_task = cx.spawn(async move |this, cx| {
+            let (mut events, _watcher) = fs.watch(&path, Duration::from_millis(100)).await;
+            let mut offset = 0;
+            loop {
+                let contents = fs.load_bytes(&path).await;
+                let result = this.update(cx, |this, cx| match &contents {
+                    Ok(_) => this.read_error = None,
+                    Err(error) => {
+                        this.read_error =
+                            Some(format!("Failed to read {}: {error:#}", path.display()).into());
+                        cx.notify();
+                    }
+                });
+                if result.is_err() {
+                    break;
+                }
+
+                if let Ok(contents) = contents {
+                    // The log is truncated when it gets rotated.
+                    let rotated = contents.len() < offset;
+                    if rotated {
+                        offset = 0;
+                    }
+                    let start = offset;
+                    let (entries, consumed) = cx
+                        .background_spawn(async move { parse_log(&contents[start..]) })
+                        .await;
+                    offset += consumed;
+
+                    let result = this.update(cx, |this, cx| {
+                        if rotated {
+                            this.clear_entries(cx);
+                        }
+                        this.push_entries(entries, cx);
+                    });
+                    if result.is_err() {
+                        break;
+                    }
+                }
+
+                if events.next().await.is_none() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            focus_handle: cx.focus_handle(),
+            entries: VecDeque::with_capacity(MAX_ENTRIES),
+            filtered_indices: Vec::new(),
+            list_state: ListState::new(0, ListAlignment::Bottom, px(2048.)),
+            filter: LogFilter::default(),
+            known_scopes: BTreeSet::new(),
+            read_error: None,
+            _tail_task: tail_task,
+        }
+    }
+
+    fn push_entries(&mut self, entries: Vec<LogEntry>, cx: &mut Context<Self>) {
+        if entries.is_empty() {
+            return;
+        }
+
+        for entry in entries {
+            // A line without a header continues the previous record.
+            if entry.level.is_none()
+                && let Some(last) = self.entries.back_mut()
+            {
+                last.message.push('\n');
+                last.message.push_str(&entry.message);
+                continue;
+            }
+            if entry.level.is_some() && !self.known_scopes.contains(root_scope(&entry.scope)) {
+                self.known_scopes
+                    .insert(root_scope(&entry.scope).to_string().into());
+            }
+            self.entries.push_back(entry);
+        }
+
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+
+        self.recompute_filtered_indices();
+        cx.notify();
+    }
+
+    fn clear_entries(&mut self, cx: &mut Context<Self>) {
+        self.entries.clear();
+        self.filtered_indices.clear();
+        self.list_state.reset(0);
+        cx.notify();
+    }
+
+    fn recompute_filtered_indices(&mut self) {
+        self.filtered_indices.clear();
+        for (index, entry) in self.entries.iter().enumerate() {
+            if self.filter.matches(entry) {
+                self.filtered_indices.push(index);
+            }
+        }
+        self.list_state.reset(self.filtered_indices.len());
+    }
+
+    pub fn set_max_level(&mut self, max_level: LevelFilter, cx: &mut Context<Self>) {
+        self.filter.max_level = max_level;
+        self.recompute_filtered_indices();
+        cx.notify();
+    }
+
+    pub fn set_scope_filter(&mut self, scope: String, cx: &mut Context<Self>) {
+        self.filter.scope = scope.trim().to_string();
+        self.recompute_filtered_indices();
+        cx.notify();
+    }
+
+    pub fn set_search_query(&mut self, query: String, cx: &mut Context<Self>) {
+        if query.is_empty() {
+            self.filter.search = None;
+            self.filter.search_error = None;
+        } else {
+            match RegexBuilder::new(&query).case_insensitive(true).build() {
+                Ok(search) => {
+                    self.filter.search = Some(search);
+                    self.filter.search_error = None;
+                }
+                Err(error) => {
+                    // Keep showing the results of the last valid query.
+                    self.filter.search_error = Some(error.to_string().into());
+                    cx.notify();
+                    return;
+                }
+            }
+        }
+        self.recompute_filtered_indices();
+        cx.notify();
+    }
+
+    fn render_entry(
+        &mut self,
+        filtered_index: usize,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let Some(entry) = self
+            .filtered_indices
+            .get(filtered_index)
+            .and_then(|&index| self.entries.get(index))
+        else {
+            return Empty.into_any();
+        };
+
+        let (level_label, level_color) = match entry.level {
+            Some(Level::Error) => ("ERROR", Color::Error),
+            Some(Level::Warn) => ("WARN", Color::Warning),
+            Some(Level::Info) => ("INFO", Color::Info),
+            Some(Level::Debug) => ("DEBUG", Color::Muted),
+            Some(Level::Trace) => ("TRACE", Color::Muted),
+            None => ("", Color::Muted),
+        };
+        let source = match entry.line {
+            Some(line) => format!("{}:{line}", entry.scope),
+            None => entry.scope.to_string(),
+        };
+        let fields = entry
+            .fields
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        v_flex()
+            .id(filtered_index)
+            .w_full()
+            .py_1()
+            .px_4()
+            .gap_0p5()
+            .font_buffer(cx)
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Label::new(entry.timestamp.clone())
+                            .buffer_font(cx)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        div().w(rems(3.)).child(
+                            Label::new(level_label)
+                                .buffer_font(cx)
+                                .size(LabelSize::Small)
+                                .color(level_color),
+                        ),
+                    )
+                    .when(!source.is_empty(), |this| {
+                        this.child(
+                            Label::new(source)
+                                .buffer_font(cx)
+                                .size(LabelSize::Small)
+                                .color(Color::Accent),
+                        )
+                    }),
+            )
+            .child(
+                div()
+                    .text_sm()
+                    .whitespace_normal()
+                    .child(entry.message.clone()),
+            )
+            .when(!fields.is_empty(), |this| {
+                this.child(
+                    Label::new(fields)
+                        .buffer_font(cx)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .into_any()
+    }
+}
+
+/// Parses the complete lines at the start of `contents`, returning the parsed
+/// entries and the number of bytes consumed.
+fn parse_log(contents: &[u8]) -> (Vec<LogEntry>, usize) {
+    let Some(consumed) = contents.iter().rposition(|&byte| byte == b'\n') else {
+        return (Vec::new(), 0);
+    };
+    let consumed = consumed + 1;
+    let text = String::from_utf8_lossy(&contents[..consumed]);
+
+    let mut entries = Vec::<LogEntry>::new();
+    for line in text.lines() {
+        match parse_line(line) {
+            Some(entry) => entries.push(entry),
+            None => match entries.last_mut() {
+                Some(last) if last.level.is_some() => {
+                    last.message.push('\n');
+                    last.message.push_str(line);
+                }
+                _ => entries.push(LogEntry {
+                    timestamp: SharedString::default(),
+                    level: None,
+                    scope: SharedString::default(),
+                    line: None,
+                    message: line.to_string(),
+                    fields: Vec::new(),
+                }),
+            },
+        }
+    }
+    (entries, consumed)
+}
+
+fn parse_line(line: &str) -> Option<LogEntry> {
+    if line.starts_with('{') {
+        parse_json_line(line)
+    } else {
+        parse_text_line(line)
+    }
+}
+
+/// Parses `<timestamp> <LEVEL> [<scope>:<line>] <message>`.
+fn parse_text_line(line: &str) -> Option<LogEntry> {
+    let (timestamp, rest) = line.split_once(' ')?;
+    if timestamp.len() < 19 || timestamp.as_bytes().get(10) != Some(&b'T') {
+        return None;
+    }
+    let level = rest.get(..5)?.trim_end().parse::<Level>().ok()?;
+    let rest = rest.get(5..)?.trim_start().strip_prefix('[')?;
+    let (source, message) = rest
+        .split_once("] ")
+        .or_else(|| rest.strip_suffix(']').map(|source| (source, "")))?;
+
+    let (scope, line_number) = match source.rsplit_once(':') {
+        Some((scope, line_number)) if !scope.ends_with(':') => match line_number.parse::<u32>() {
+            Ok(line_number) => (scope, Some(line_number)),
+            Err(_) => (source, None),
+        },
+        _ => (source, None),
+    };
+
+    Some(LogEntry {
+        timestamp: timestamp.to_string().into(),
+        level: Some(level),
+        scope: scope.to_string().into(),
+        line: line_number,
+        message: message.to_string(),
+        fields: Vec::new(),
+    })
+}
+
+/// A line written by `zlog` with the JSON sink format.
+#[derive(Deserialize)]
+struct JsonLogLine {
+    timestamp: String,
+    level: String,
+    scope: String,
+    #[serde(default)]
+    module: Option<String>,
+    #[serde(default)]
+    line: Option<u32>,
+    message: String,
+    #[serde(default)]
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+fn parse_json_line(line: &str) -> Option<LogEntry> {
+    let json_line = serde_json::from_str::<JsonLogLine>(line).ok()?;
+    let level = json_line.level.parse::<Level>().ok()?;
+    // Like the text format, show the module path for records that are only
+    // scoped to their crate.
+    let scope = match json_line.module {
+        Some(module) if !json_line.scope.contains('.') => module,
+        _ => json_line.scope,
+    };
+    let fields = json_line
+        .fields
+        .into_iter()
+        .map(|(key, value)| match value {
+            serde_json::Value::String(value) => (key, value),
+            value => (key, value.to_string()),
+        })
+        .collect();
+
+    Some(LogEntry {
+        timestamp: json_line.timestamp.into(),
+        level: Some(level),
+        scope: scope.into(),
+        line: json_line.line,
+        message: json_line.message,
+        fields,
+    })
+}
+
+impl EventEmitter<()> for LogView {}
+
+impl Item for LogView {
+    type Event = ();
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Zed Log".into()
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::FileTextOutlined))
+    }
+}
+
+impl Focusable for LogView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for LogView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(if self.filtered_indices.is_empty() {
+                let message = if let Some(error) = &self.read_error {
+                    error.clone()
+                } else if self.entries.is_empty() {
+                    "The log is empty".into()
+                } else {
+                    "No log entries match the current filter".into()
+                };
+                h_flex()
+                    .size_full()
+                    .justify_center()
+                    .items_center()
+                    .child(Label::new(message).color(Color::Muted))
+                    .into_any()
+            } else {
+                div()
+                    .size_full()
+                    .flex_grow()
+                    .child(
+                        list(self.list_state.clone(), cx.processor(Self::render_entry))
+                            .with_sizing_behavior(gpui::ListSizingBehavior::Auto)
+                            .size_full(),
+                    )
+                    .vertical_scrollbar_for(&self.list_state, window, cx)
+                    .into_any()
+            })
+    }
+}
+
+pub struct LogViewToolbarItemView {
+    log_view: Option<Entity<LogView>>,
+    search_editor: Entity<editor::Editor>,
+    scope_editor: Entity<editor::Editor>,
+}
+
+impl LogViewToolbarItemView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let search_editor = cx.new(|cx| {
+            let mut editor = editor::Editor::single_line(window, cx);
+            editor.set_placeholder_text("Search with regex…", window, cx);
+            editor
+        });
+        let scope_editor = cx.new(|cx| {
+            let mut editor = editor::Editor::single_line(window, cx);
+            editor.set_placeholder_text("Filter by scope…", window, cx);
+            editor
+        });
+
+        cx.subscribe(
+            &search_editor,
+            |this, editor, event: &editor::EditorEvent, cx| {
+                if let editor::EditorEvent::BufferEdited { .. } = event {
+                    let query = editor.read(cx).text(cx);
+                    if let Some(log_view) = &this.log_view {
+                        log_view.update(cx, |log_view, cx| log_view.set_search_query(query, cx));
+                    }
+                    cx.notify();
+                }
+            },
+        )
+        .detach();
+        cx.subscribe(
+            &scope_editor,
+            |this, editor, event: &editor::EditorEvent, cx| {
+                if let editor::EditorEvent::BufferEdited { .. } = event {
+                    let scope = editor.read(cx).text(cx);
+                    if let Some(log_view) = &this.log_view {
+                        log_view.update(cx, |log_view, cx| log_view.set_scope_filter(scope, cx));
+                    }
+                }
+            },
+        )
+        .detach();
+
+        Self {
+            log_view: None,
+            search_editor,
+            scope_editor,
+        }
+    }
+
+    fn render_level_menu(&self, log_view: &Entity<LogView>, cx: &App) -> impl IntoElement {
+        let max_level = log_view.read(cx).filter.max_level;
+        let log_view = log_view.downgrade();
+
+        PopoverMenu::new("log-view-level-menu")
+            .anchor(Corner::TopRight)
+            .trigger(
+                Button::new("log_view_level_selector", level_label(max_level)).end_icon(
+                    Icon::new(IconName::ChevronDown)
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                ),
+            )
+            .menu(move |window, cx| {
+                let log_view = log_view.clone();
+                Some(ContextMenu::build(window, cx, move |mut menu, _, _| {
+                    menu = menu.header("Show Levels Up To");
+                    for level in LEVELS {
+                        let level = level.to_level_filter();
+                        let log_view = log_view.clone();
+                        menu = menu.toggleable_entry(
+                            level_label(level),
+                            level == max_level,
+                            IconPosition::Start,
+                            None,
+                            move |_, cx| {
+                                log_view
+                                    .update(cx, |log_view, cx| log_view.set_max_level(level, cx))
+                                    .ok();
+                            },
+                        );
+                    }
+                    menu
+                }))
+            })
+    }
+
+    fn render_scope_levels_menu(&self, log_view: &Entity<LogView>, cx: &App) -> impl IntoElement {
+        let log_view = log_view.read(cx);
+        let mut scopes = Vec::<SharedString>::new();
+        if !log_view.filter.scope.is_empty() {
+            scopes.push(log_view.filter.scope.clone().into());
+        }
+        for (scope, _) in zlog::filter::scope_overrides() {
+            if !scopes.iter().any(|existing| existing.as_ref() == scope) {
+                scopes.push(scope.into());
+            }
+        }
+        for scope in &log_view.known_scopes {
+            if !scopes.contains(scope) {
+                scopes.push(scope.clone());
+            }
+        }
+
+        PopoverMenu::new("log-view-scope-levels-menu")
+            .anchor(Corner::TopRight)
+            .trigger_with_tooltip(
+                IconButton::new("log_view_scope_levels", IconName::Sliders)
+                    .icon_size(IconSize::Small),
+                Tooltip::text("Change Scope Log Levels"),
+            )
+            .menu(move |window, cx| {
+                let scopes = scopes.clone();
+                Some(ContextMenu::build(window, cx, move |mut menu, _, _| {
+                    menu = menu.header("Scope Log Levels");
+                    if scopes.is_empty() {
+                        return menu.label("No scopes have logged yet");
+                    }
+                    for scope in scopes {
+                        let label = match scope_override(&scope) {
+                            Some(level) => format!("{scope}: {}", level_label(level)),
+                            None => scope.to_string(),
+                        };
+                        menu = menu.submenu(label, move |mut menu, _, _| {
+                            let current = scope_override(&scope);
+                            for level in [LevelFilter::Off]
+                                .into_iter()
+                                .chain(LEVELS.map(|level| level.to_level_filter()))
+                            {
+                                let scope = scope.clone();
+                                menu = menu.toggleable_entry(
+                                    level_label(level),
+                                    current == Some(level),
+                                    IconPosition::Start,
+                                    None,
+                                    move |_, _| {
+                                        zlog::filter::set_scope_override(&scope, Some(level));
+                                    },
+                                );
+                            }
+                            let scope = scope.clone();
+                            menu.separator().item(
+                                ContextMenuEntry::new("Use Settings")
+                                    .disabled(current.is_none())
+                                    .handler(move |_, _| {
+                                        zlog::filter::set_scope_override(&scope, None);
+                                    }),
+                            )
+                        });
+                    }
+                    menu
+                }))
+            })
+    }
+}
+
+fn scope_override(scope: &str) -> Option<LevelFilter> {
+    zlog::filter::scope_overrides()
+        .into_iter()
+        .find_map(|(existing, level)| (existing == scope).then_some(level))
+}
+
+fn level_label(level: LevelFilter) -> &'static str {
+    match level {
+        LevelFilter::Off => "Off",
+        LevelFilter::Error => "Error",
+        LevelFilter::Warn => "Warn",
+        LevelFilter::Info => "Info",
+        LevelFilter::Debug => "Debug",
+        LevelFilter::Trace => "Trace",
+    }
+}
+
+impl Render for LogViewToolbarItemView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(log_view) = self.log_view.clone() else {
+            return Empty.into_any_element();
+        };
+
+        let search_error = log_view.read(cx).filter.search_error.clone();
+        let has_entries = !log_view.read(cx).entries.is_empty();
+        let border_color = if search_error.is_some() {
+            Color::Error.color(cx)
+        } else {
+            cx.theme().colors().border
+        };
+
+        h_flex()
+            .gap_2()
+            .child(
+                div()
+                    .id("log_view_search")
+                    .w(px(200.))
+                    .px_1()
+                    .border_1()
+                    .rounded_sm()
+                    .border_color(border_color)
+                    .child(self.search_editor.clone())
+                    .when_some(search_error, |this, error| {
+                        this.tooltip(Tooltip::text(error))
+                    }),
+            )
+            .child(
+                div()
+                    .w(px(160.))
+                    .px_1()
+                    .border_1()
+                    .rounded_sm()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.scope_editor.clone()),
+            )
+            .child(self.render_level_menu(&log_view, cx))
+            .child(self.render_scope_levels_menu(&log_view, cx))
+            .child(
+                IconButton::new("clear_log_entries", IconName::Trash)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::text("Clear Entries"))
+                    .disabled(!has_entries)
+                    .on_click(move |_, _window, cx| {
+                        log_view.update(cx, |log_view, cx| log_view.clear_entries(cx));
+                    }),
+            )
+            .child(
+                IconButton::new("open_log_file", IconName::File)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::text("Open Log File"))
+                    .on_click(|_, window, cx| {
+                        window.dispatch_action(Box::new(OpenLog), cx);
+                    }),
+            )
+            .into_any()
+    }
+}
+
+impl EventEmitter<ToolbarItemEvent> for LogViewToolbarItemView {}
+
+impl ToolbarItemView for LogViewToolbarItemView {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> ToolbarItemLocation {
+        if let Some(item) = active_pane_item
+            && let Some(log_view) = item.downcast::<LogView>()
+        {
+            self.log_view = Some(log_view);
+            cx.notify();
+            return ToolbarItemLocation::PrimaryRight;
+        }
+        if self.log_view.take().is_some() {
+            cx.notify();
+        }
+        ToolbarItemLocation::Hidden
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_text_log() {
+        let log = "\
+not a record
+2025-01-02T03:04:05+00:00 INFO  [zed] Zed started
+2025-01-02T03:04:06+00:00 ERROR [project::lsp_store:120] server exited
+stack backtrace:
+  0: main
+2025-01-02T03:04:07+00:00 DEBUG [editor.blame] no blame [1, 2]
+2025-01-02T03:04:08+00:00 WARN  [gpui";
+
+        let (entries, consumed) = parse_log(log.as_bytes());
+        assert_eq!(consumed, log.rfind('\n').unwrap() + 1);
+        assert_eq!(entries.len(), 4);
+
+        assert_eq!(entries[0].level, None);
+        assert_eq!(entries[0].message, "not a record");
+
+        assert_eq!(entries[1].level, Some(Level::Info));
+        assert_eq!(entries[1].timestamp.as_ref(), "2025-01-02T03:04:05+00:00");
+        assert_eq!(entries[1].scope.as_ref(), "zed");
+        assert_eq!(entries[1].message, "Zed started");
+
+        assert_eq!(entries[2].level, Some(Level::Error));
+        assert_eq!(entries[2].scope.as_ref(), "project::lsp_store");
+        assert_eq!(entries[2].line, Some(120));
+        assert_eq!(
+            entries[2].message,
+            "server exited\nstack backtrace:\n  0: main"
+        );
+
+        assert_eq!(entries[3].level, Some(Level::Debug));
+        assert_eq!(entries[3].scope.as_ref(), "editor.blame");
+        assert_eq!(entries[3].line, None);
+        assert_eq!(entries[3].message, "no blame [1, 2]");
+    }
+
+    #[test]
+    fn test_parse_json_log() {
+        let log = concat!(
+            r#"{"timestamp":"2025-01-02T03:04:05+00:00","level":"WARN","scope":"project","module":"project::lsp_store","line":7,"message":"slow","fields":{"server":"rust-analyzer","ms":250}}"#,
+            "\n",
+            r#"{"timestamp":"2025-01-02T03:04:06+00:00","level":"TRACE","scope":"editor.blame","module":"editor::git::blame","message":"done"}"#,
+            "\n",
+        );
+
+        let (entries, consumed) = parse_log(log.as_bytes());
+        assert_eq!(consumed, log.len());
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].level, Some(Level::Warn));
+        assert_eq!(entries[0].scope.as_ref(), "project::lsp_store");
+        assert_eq!(entries[0].line, Some(7));
+        assert_eq!(
+            entries[0].fields,
+            [
+                ("server".to_string(), "rust-analyzer".to_string()),
+                ("ms".to_string(), "250".to_string())
+            ]
+        );
+
+        assert_eq!(entries[1].level, Some(Level::Trace));
+        assert_eq!(entries[1].scope.as_ref(), "editor.blame");
+        assert!(entries[1].fields.is_empty());
+    }
+
+    #[test]
+    fn test_log_filter() {
+        let (entries, _) = parse_log(
+            b"2025-01-02T03:04:05+00:00 INFO  [project::lsp_store] started rust-analyzer
+2025-01-02T03:04:06+00:00 DEBUG [project.lsp] sent request
+2025-01-02T03:04:07+00:00 ERROR [project_panel] failed to reveal entry
+",
+        );
+        let matching = |filter: &LogFilter| {
+            entries
+                .iter()
+                .filter(|entry| filter.matches(entry))
+                .map(|entry| entry.scope.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let mut filter = LogFilter::default();
+        assert_eq!(matching(&filter).len(), 3);
+
+        filter.scope = "project".to_string();
+        assert_eq!(matching(&filter), ["project::lsp_store", "project.lsp"]);
+
+        filter.max_level = LevelFilter::Info;
+        assert_eq!(matching(&filter), ["project::lsp_store"]);
+
+        filter.scope.clear();
+        filter.search = Some(Regex::new("rust-\\w+|reveal").unwrap());
+        assert_eq!(matching(&filter), ["project::lsp_store", "project_panel"]);
+    }
+}
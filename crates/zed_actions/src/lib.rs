@@ -68,6 +68,8 @@ actions!(
         OpenLicenses,
         /// Opens the telemetry log.
         OpenTelemetryLog,
+        /// Opens a live view of the Zed log.
+        OpenLogViewer,
         /// Opens the performance profiler.
         OpenPerformanceProfiler,
         /// Opens the onboarding view.
@@ -803,3 +805,21 @@ pub mod notebook {
         ]
     );
 }
+
+pub mod hex_editor {
+    use std::path::PathBuf;
+
+    use gpui::Action;
+    use schemars::JsonSchema;
+    use serde::Deserialize;
+
+    /// Opens a file in the hex editor.
+    #[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
+    #[action(namespace = hex_editor)]
+    #[serde(deny_unknown_fields)]
+    pub struct OpenInHexEditor {
+        /// The file to open. Defaults to the file of the active item.
+        #[serde(default)]
+        pub abs_path: Option<PathBuf>,
+    }
+}
@@ -11,6 +11,12 @@ use log;
 
 static ENV_FILTER: OnceLock<env_config::EnvFilter> = OnceLock::new();
 static SCOPE_MAP: RwLock<ScopeMap> = RwLock::new(ScopeMap::empty());
+/// The scopes most recently passed to [`refresh_from_settings`], kept so that
+/// the scope map can be rebuilt when an override changes.
+static SETTINGS_SCOPES: RwLock<Option<HashMap<String, String>>> = RwLock::new(None);
+/// Scope levels set at runtime with [`set_scope_override`]. These take
+/// precedence over both the environment and the user's settings.
+static SCOPE_OVERRIDES: RwLock<Vec<(String, log::LevelFilter)>> = RwLock::new(Vec::new());
 
 pub const LEVEL_ENABLED_MAX_DEFAULT: log::LevelFilter = log::LevelFilter::Info;
 /// The maximum log level of verbosity that is enabled by default.
@@ -89,14 +95,66 @@ pub fn is_scope_enabled(
 }
 
 pub fn refresh_from_settings(settings: &HashMap<String, String>) {
+    {
+        let mut settings_scopes = SETTINGS_SCOPES.write().unwrap_or_else(|err| {
+            SETTINGS_SCOPES.clear_poison();
+            err.into_inner()
+        });
+        *settings_scopes = Some(settings.clone());
+    }
+    refresh();
+}
+
+/// Sets the level of a scope (e.g. `project.lsp` or `project::lsp_store`) until
+/// the process exits, or removes the override when `level` is `None`.
+pub fn set_scope_override(scope: &str, level: Option<log::LevelFilter>) {
+    {
+        let mut overrides = SCOPE_OVERRIDES.write().unwrap_or_else(|err| {
+            SCOPE_OVERRIDES.clear_poison();
+            err.into_inner()
+        });
+        overrides.retain(|(existing, _)| existing != scope);
+        if let Some(level) = level {
+            overrides.push((scope.to_string(), level));
+        }
+    }
+    refresh();
+}
+
+pub fn scope_overrides() -> Vec<(String, log::LevelFilter)> {
+    SCOPE_OVERRIDES
+        .read()
+        .unwrap_or_else(|err| {
+            SCOPE_OVERRIDES.clear_poison();
+            err.into_inner()
+        })
+        .clone()
+}
+
+fn refresh() {
+    let mut scopes = SETTINGS_SCOPES
+        .read()
+        .unwrap_or_else(|err| {
+            SETTINGS_SCOPES.clear_poison();
+            err.into_inner()
+        })
+        .clone()
+        .unwrap_or_default();
+    for (scope, level) in scope_overrides() {
+        scopes.insert(scope, level.as_str().to_string());
+    }
+
     let env_config = ENV_FILTER.get();
-    let map_new = ScopeMap::new_from_settings_and_env(settings, env_config, DEFAULT_FILTERS);
+    let map_new = ScopeMap::new_from_settings_and_env(&scopes, env_config, DEFAULT_FILTERS);
     let mut level_enabled_max = LEVEL_ENABLED_MAX_STATIC.load(Ordering::Acquire);
     for entry in &map_new.entries {
         if let Some(level) = entry.enabled {
             level_enabled_max = level_enabled_max.max(level as u8);
         }
     }
+    for (_, level) in &map_new.modules {
+        level_enabled_max = level_enabled_max.max(*level as u8);
+    }
     LEVEL_ENABLED_MAX_CONFIG.store(level_enabled_max, Ordering::Release);
 
     {
@@ -838,4 +896,29 @@ mod tests {
             "crate::submodule should be disabled by disabling `crate` filter"
         );
     }
+
+    #[test]
+    fn test_scope_override() {
+        let scope = scope_new(&["zlog_override_test", "child"]);
+        let mut settings = HashMap::default();
+        settings.insert("zlog_override_test".to_string(), "warn".to_string());
+        refresh_from_settings(&settings);
+        assert!(!is_scope_enabled(&scope, None, log::Level::Debug));
+
+        set_scope_override("zlog_override_test.child", Some(LevelFilter::Debug));
+        assert!(is_scope_enabled(&scope, None, log::Level::Debug));
+        assert!(!is_scope_enabled(&scope, None, log::Level::Trace));
+
+        // overrides survive settings changes
+        refresh_from_settings(&settings);
+        assert!(is_scope_enabled(&scope, None, log::Level::Debug));
+        assert!(
+            scope_overrides()
+                .contains(&("zlog_override_test.child".to_string(), LevelFilter::Debug))
+        );
+
+        set_scope_override("zlog_override_test.child", None);
+        assert!(!is_scope_enabled(&scope, None, log::Level::Debug));
+        assert!(is_scope_enabled(&scope, None, log::Level::Warn));
+    }
 }
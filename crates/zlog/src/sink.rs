@@ -4,11 +4,13 @@ use std::{
     path::PathBuf,
     sync::{
         Mutex, OnceLock,
-        atomic::{AtomicBool, AtomicU64, Ordering},
+        atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering},
     },
 };
 
-use crate::{SCOPE_STRING_SEP_CHAR, ScopeRef};
+use serde::Serialize;
+
+use crate::{SCOPE_STRING_SEP_CHAR, SCOPE_STRING_SEP_STR, ScopeRef};
 
 // ANSI color escape codes for log levels
 const ANSI_RESET: &str = "\x1b[0m";
@@ -33,6 +35,18 @@ static ENABLED_SINKS_STDERR: AtomicBool = AtomicBool::new(false);
 static SINK_FILE_SIZE_BYTES: AtomicU64 = AtomicU64::new(0);
 /// Maximum size of the log file before it will be rotated, in bytes.
 const SINK_FILE_SIZE_BYTES_MAX: u64 = 1024 * 1024; // 1 MB
+/// The [`SinkFormat`] used by all sinks, stored as its discriminant.
+static SINK_FORMAT: AtomicU8 = AtomicU8::new(SinkFormat::Text as u8);
+
+/// How records are written to the enabled sinks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SinkFormat {
+    /// `<timestamp> <LEVEL> [<scope>] <message>`, colored when writing to a terminal.
+    Text = 0,
+    /// One JSON object per line, including the record's key-value fields.
+    Json = 1,
+}
 
 pub struct Record<'a> {
     pub scope: ScopeRef<'a>,
@@ -40,6 +54,7 @@ pub struct Record<'a> {
     pub message: &'a std::fmt::Arguments<'a>,
     pub module_path: Option<&'a str>,
     pub line: Option<u32>,
+    pub key_values: Option<&'a dyn log::kv::Source>,
 }
 
 pub fn init_output_stdout() {
@@ -52,6 +67,17 @@ pub fn init_output_stderr() {
     ENABLED_SINKS_STDERR.store(true, Ordering::Release);
 }
 
+pub fn set_format(format: SinkFormat) {
+    SINK_FORMAT.store(format as u8, Ordering::Release);
+}
+
+pub fn format() -> SinkFormat {
+    match SINK_FORMAT.load(Ordering::Acquire) {
+        1 => SinkFormat::Json,
+        _ => SinkFormat::Text,
+    }
+}
+
 pub fn init_output_file(
     path: &'static PathBuf,
     path_rotate: Option<&'static PathBuf>,
@@ -118,38 +144,13 @@ pub fn submit(mut record: Record) {
         // Only render line numbers for actual rust files emitted by `log_err` and friends
         record.line.take();
     }
+    let format = format();
     if ENABLED_SINKS_STDOUT.load(Ordering::Acquire) {
         let mut stdout = std::io::stdout().lock();
-        _ = writeln!(
-            &mut stdout,
-            "{} {ANSI_BOLD}{}{}{ANSI_RESET} {} {}",
-            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%:z"),
-            LEVEL_ANSI_COLORS[record.level as usize],
-            LEVEL_OUTPUT_STRINGS[record.level as usize],
-            SourceFmt {
-                scope: record.scope,
-                module_path: record.module_path,
-                line: record.line,
-                ansi: true,
-            },
-            record.message
-        );
+        _ = write_record(&mut stdout, &record, format, true);
     } else if ENABLED_SINKS_STDERR.load(Ordering::Acquire) {
-        let mut stdout = std::io::stderr().lock();
-        _ = writeln!(
-            &mut stdout,
-            "{} {ANSI_BOLD}{}{}{ANSI_RESET} {} {}",
-            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%:z"),
-            LEVEL_ANSI_COLORS[record.level as usize],
-            LEVEL_OUTPUT_STRINGS[record.level as usize],
-            SourceFmt {
-                scope: record.scope,
-                module_path: record.module_path,
-                line: record.line,
-                ansi: true,
-            },
-            record.message
-        );
+        let mut stderr = std::io::stderr().lock();
+        _ = write_record(&mut stderr, &record, format, true);
     }
     let mut file_guard = ENABLED_SINKS_FILE.lock().unwrap_or_else(|handle| {
         ENABLED_SINKS_FILE.clear_poison();
@@ -173,19 +174,7 @@ pub fn submit(mut record: Record) {
         }
         let file_size_bytes = {
             let mut writer = SizedWriter { file, written: 0 };
-            _ = writeln!(
-                &mut writer,
-                "{} {} {} {}",
-                chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%:z"),
-                LEVEL_OUTPUT_STRINGS[record.level as usize],
-                SourceFmt {
-                    scope: record.scope,
-                    module_path: record.module_path,
-                    line: record.line,
-                    ansi: false,
-                },
-                record.message
-            );
+            _ = write_record(&mut writer, &record, format, false);
             SINK_FILE_SIZE_BYTES.fetch_add(writer.written, Ordering::AcqRel) + writer.written
         };
         if file_size_bytes > SINK_FILE_SIZE_BYTES_MAX {
@@ -218,6 +207,92 @@ pub fn flush() {
     }
 }
 
+fn write_record(
+    writer: &mut impl Write,
+    record: &Record,
+    format: SinkFormat,
+    ansi: bool,
+) -> io::Result<()> {
+    let timestamp = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%:z");
+    let source = SourceFmt {
+        scope: record.scope,
+        module_path: record.module_path,
+        line: record.line,
+        ansi,
+    };
+    match format {
+        SinkFormat::Text if ansi => writeln!(
+            writer,
+            "{timestamp} {ANSI_BOLD}{}{}{ANSI_RESET} {source} {}",
+            LEVEL_ANSI_COLORS[record.level as usize],
+            LEVEL_OUTPUT_STRINGS[record.level as usize],
+            record.message
+        ),
+        SinkFormat::Text => writeln!(
+            writer,
+            "{timestamp} {} {source} {}",
+            LEVEL_OUTPUT_STRINGS[record.level as usize], record.message
+        ),
+        SinkFormat::Json => {
+            let mut fields = serde_json::Map::new();
+            if let Some(key_values) = record.key_values {
+                _ = key_values.visit(&mut FieldCollector(&mut fields));
+            }
+            let json_record = JsonRecord {
+                timestamp: timestamp.to_string(),
+                level: record.level.as_str(),
+                scope: scope_path(record.scope),
+                module: record.module_path,
+                line: record.line,
+                message: record.message.to_string(),
+                fields,
+            };
+            serde_json::to_writer(&mut *writer, &json_record)?;
+            writer.write_all(b"\n")
+        }
+    }
+}
+
+/// A single line of [`SinkFormat::Json`] output.
+#[derive(Serialize)]
+struct JsonRecord<'a> {
+    timestamp: String,
+    level: &'static str,
+    /// The scope path, in the same dotted form used by the `log` setting.
+    scope: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    module: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+    message: String,
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+fn scope_path(scope: ScopeRef<'_>) -> String {
+    scope
+        .iter()
+        .take_while(|subscope| !subscope.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(SCOPE_STRING_SEP_STR)
+}
+
+struct FieldCollector<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+impl<'kvs> log::kv::VisitSource<'kvs> for FieldCollector<'_> {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        let value = serde_json::to_value(&value)
+            .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
 struct SourceFmt<'a> {
     scope: ScopeRef<'a>,
     module_path: Option<&'a str>,
@@ -324,6 +399,45 @@ mod tests {
         assert_eq!(std::fs::read_to_string(&log_file_path).unwrap(), contents);
     }
 
+    #[test]
+    fn test_json_record() {
+        let key_values = [("path", "src/main.rs"), ("server", "rust-analyzer")];
+        let record = Record {
+            scope: crate::private::scope_new(&["project", "lsp"]),
+            level: log::Level::Warn,
+            message: &format_args!("server {} exited", 1),
+            module_path: Some("project::lsp_store"),
+            line: Some(42),
+            key_values: Some(&key_values),
+        };
+        let mut output = Vec::new();
+        write_record(&mut output, &record, SinkFormat::Json, false).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with('\n'));
+        assert_eq!(output.lines().count(), 1);
+
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert!(json["timestamp"].is_string());
+        assert_eq!(json["level"], "WARN");
+        assert_eq!(json["scope"], "project.lsp");
+        assert_eq!(json["module"], "project::lsp_store");
+        assert_eq!(json["line"], 42);
+        assert_eq!(json["message"], "server 1 exited");
+        assert_eq!(json["fields"]["path"], "src/main.rs");
+        assert_eq!(json["fields"]["server"], "rust-analyzer");
+
+        let record = Record {
+            key_values: None,
+            line: None,
+            ..record
+        };
+        let mut output = Vec::new();
+        write_record(&mut output, &record, SinkFormat::Json, false).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert!(json.get("fields").is_none());
+        assert!(json.get("line").is_none());
+    }
+
     /// Regression test, ensuring that if log level values change we are made aware
     #[test]
     fn test_log_level_names() {
@@ -5,7 +5,9 @@ mod env_config;
 pub mod filter;
 pub mod sink;
 
-pub use sink::{flush, init_output_file, init_output_stderr, init_output_stdout};
+pub use sink::{
+    SinkFormat, flush, init_output_file, init_output_stderr, init_output_stdout, set_format,
+};
 
 pub const SCOPE_DEPTH_MAX: usize = 4;
 
@@ -20,6 +22,7 @@ pub fn try_init(filter: Option<String>) -> anyhow::Result<()> {
     log::set_logger(&ZLOG)?;
     log::set_max_level(log::LevelFilter::max());
     process_env(filter);
+    process_format_env();
     filter::refresh_from_settings(&std::collections::HashMap::default());
     Ok(())
 }
@@ -57,6 +60,18 @@ pub fn process_env(filter: Option<String>) {
     }
 }
 
+/// Selects the sink format from `ZED_LOG_FORMAT`, which may be `text` (the default) or `json`.
+fn process_format_env() {
+    let Ok(format) = std::env::var("ZED_LOG_FORMAT") else {
+        return;
+    };
+    match format.to_ascii_lowercase().as_str() {
+        "json" => sink::set_format(SinkFormat::Json),
+        "text" | "" => sink::set_format(SinkFormat::Text),
+        _ => eprintln!("Unknown log format \"{format}\", expected \"text\" or \"json\""),
+    }
+}
+
 static ZLOG: Zlog = Zlog {};
 
 pub struct Zlog {}
@@ -94,6 +109,7 @@ impl log::Log for Zlog {
             // PERF(batching): store non-static paths in a cache + leak them and pass static str here
             module_path,
             line: record.line(),
+            key_values: Some(record.key_values()),
         });
     }
 
@@ -115,6 +131,7 @@ macro_rules! log {
                 message: &format_args!($($arg)+),
                 module_path: Some(module_path!()),
                 line: Some(line!()),
+                key_values: None,
             });
         }
     }
@@ -311,6 +328,7 @@ impl log::Log for Logger {
             message: record.args(),
             module_path: record.module_path(),
             line: record.line(),
+            key_values: Some(record.key_values()),
         });
     }
 